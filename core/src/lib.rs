@@ -0,0 +1,220 @@
+//! Verification half of the hash-based algorithms (SD-JWT and MERKLE), building without std so that constrained
+//! verifiers, e.g. smartcard readers or kiosks, can check disclosed claims against the digests signed by the issuer
+//! with nothing more than an allocator.
+//!
+//! The crate only covers the checks on the claims: decoding the VP, verifying the signatures of the issuer and of the
+//! holder and applying the policies of the verifier are left to the caller, which on std targets is `csd_jwt`.
+#![no_std]
+
+extern crate alloc;
+
+use alloc::collections::BTreeSet;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use rs_merkle::{Hasher, MerkleProof};
+use sha2::{Digest, Sha256};
+
+/// Length of the SHA-256 digests in bytes.
+pub const DIGEST_LEN: usize = 32;
+
+
+/// Computes the SHA-256 digest of some bytes.
+///
+/// # Arguments
+/// * `data` - Bytes to be hashed.
+///
+/// # Returns
+/// The digest.
+pub fn sha256(data: &[u8]) -> [u8; DIGEST_LEN] {
+    Sha256::digest(data).into()
+}
+
+
+/// Encodes a claim as the input of the element committed to by the issuer, prefixing the name, the value and the
+/// opening, if any, with their length in bytes. The encoding is injective, so that the element of a claim binds its
+/// name: a digest, leaf, witness or message issued for a claim cannot be presented under another name, nor can
+/// characters be moved between the name, the value and the opening, as a plain concatenation would allow (e.g.
+/// `ab` + `c` and `a` + `bc`).
+///
+/// # Arguments
+/// * `name` - Name of the claim.
+/// * `value` - Serialized value of the claim.
+/// * `opening` - Opening of the claim, if any.
+///
+/// # Returns
+/// The encoded claim.
+///
+/// # Examples
+/// ```
+/// use csd_jwt_core::bind_claim;
+///
+/// assert_eq!(bind_claim("name", "Alice", None), b"4:name5:Alice".to_vec());
+/// assert_ne!(bind_claim("ab", "c", None), bind_claim("a", "bc", None));
+/// assert_ne!(bind_claim("a", "b:c", None), bind_claim("a:b", "c", None));
+/// ```
+pub fn bind_claim(name: &str, value: &str, opening: Option<&str>) -> Vec<u8> {
+    let mut encoded_claim: Vec<u8> = Vec::new();
+    for part in [Some(name), Some(value), opening].into_iter().flatten() {
+        encoded_claim.extend_from_slice(format!("{}:", part.len()).as_bytes());
+        encoded_claim.extend_from_slice(part.as_bytes());
+    }
+    encoded_claim
+}
+
+
+/// Runs every check of a verification before reporting the first failure, so that the time taken by a rejected VC or
+/// VP does not reveal which claim failed. The checks are consumed lazily, so they must not be short-circuited by the
+/// caller, e.g. through `collect::<Result<_, _>>()`.
+///
+/// # Arguments
+/// * `checks` - Checks of the individual claims.
+///
+/// # Returns
+/// A result containing the error of the first failed check.
+///
+/// # Examples
+/// ```
+/// use csd_jwt_core::check_all;
+///
+/// let mut checked = 0;
+/// let result = check_all((0..4).map(|claim| {
+///     checked += 1;
+///     if claim == 1 { Err(format!("Claim {claim} is invalid")) } else { Ok(()) }
+/// }));
+/// assert_eq!(result, Err("Claim 1 is invalid".to_string()));
+/// assert_eq!(checked, 4);
+/// ```
+pub fn check_all<I: IntoIterator<Item = Result<(), String>>>(checks: I) -> Result<(), String> {
+    let mut outcome: Result<(), String> = Ok(());
+    for check in checks {
+        if let Err(err) = check {
+            if outcome.is_ok() {
+                outcome = Err(err);
+            }
+        }
+    }
+    outcome
+}
+
+
+/// Claim disclosed in a SD-JWT VP along with the salt of its digest.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SaltedClaim<'a> {
+    /// Name of the claim.
+    pub name: &'a str,
+    /// Value of the claim.
+    pub value: &'a str,
+    /// Salt of the claim, as encoded in the Salt-Value Container.
+    pub salt: &'a str,
+}
+
+impl SaltedClaim<'_> {
+
+    /// Computes the digest the issuer signed for the claim.
+    pub fn digest(&self) -> [u8; DIGEST_LEN] {
+        sha256(&bind_claim(self.name, self.value, Some(self.salt)))
+    }
+}
+
+
+/// Checks that the digest of every disclosed claim of a SD-JWT VP is among the digests signed by the issuer. Every
+/// claim is checked before reporting a failure.
+///
+/// # Arguments
+/// * `claims` - Disclosed claims with their salts.
+/// * `digests` - Digests signed by the issuer, including the ones of the undisclosed claims.
+///
+/// # Returns
+/// A result containing a string containing an error in case a claim does not match any digest.
+///
+/// # Examples
+/// ```
+/// use csd_jwt_core::{verify_salted_claims, SaltedClaim};
+///
+/// let claim = SaltedClaim { name: "name", value: "Alice", salt: "c2FsdA" };
+/// let digests = vec![claim.digest()];
+///
+/// assert!(verify_salted_claims(&[claim], &digests).is_ok());
+/// assert!(verify_salted_claims(&[SaltedClaim { value: "Mallory", ..claim }], &digests).is_err());
+/// ```
+pub fn verify_salted_claims(claims: &[SaltedClaim], digests: &[[u8; DIGEST_LEN]]) -> Result<(), String> {
+    let digests: BTreeSet<&[u8; DIGEST_LEN]> = digests.iter().collect();
+
+    check_all(claims.iter().map(|claim| {
+        if digests.contains(&claim.digest()) {
+            Ok(())
+        } else {
+            Err(format!("Hashes array does not contain the hash of claim {}", claim.name))
+        }
+    }))
+}
+
+
+/// SHA-256 hasher of the leaves and nodes of the Merkle trees.
+#[derive(Clone)]
+pub struct Sha256Hasher;
+
+impl Hasher for Sha256Hasher {
+    type Hash = [u8; DIGEST_LEN];
+
+    fn hash(data: &[u8]) -> Self::Hash {
+        sha256(data)
+    }
+}
+
+
+/// Computes the Merkle leaf of a claim, salted so that the undisclosed leaves of a proof do not reveal their claims.
+///
+/// # Arguments
+/// * `name` - Name of the claim.
+/// * `value` - Value of the claim.
+/// * `salt` - Salt of the claim.
+///
+/// # Returns
+/// The leaf.
+pub fn merkle_leaf<H: Hasher<Hash = [u8; DIGEST_LEN]>>(name: &str, value: &str, salt: &str) -> [u8; DIGEST_LEN] {
+    H::hash(&bind_claim(name, value, Some(salt)))
+}
+
+
+/// Checks a Merkle multi-proof of the disclosed leaves against the root signed by the issuer.
+///
+/// # Arguments
+/// * `proof` - Serialized Merkle proof.
+/// * `root` - Root of the Merkle tree.
+/// * `indices` - Indices of the disclosed leaves in the tree.
+/// * `leaves` - Disclosed leaves, in the order of their indices.
+/// * `leaves_len` - Amount of leaves in the tree.
+///
+/// # Returns
+/// A result containing a string containing an error in case the proof is malformed or does not prove the leaves.
+///
+/// # Examples
+/// ```
+/// use rs_merkle::MerkleTree;
+/// use csd_jwt_core::{merkle_leaf, verify_merkle_proof, Sha256Hasher};
+///
+/// let leaves = vec![merkle_leaf::<Sha256Hasher>("name", "Alice", "c2FsdA"), merkle_leaf::<Sha256Hasher>("age", "30", "cGVwcGVy")];
+/// let tree: MerkleTree<Sha256Hasher> = MerkleTree::from_leaves(&leaves);
+/// let proof = tree.proof(&[1]).to_bytes();
+///
+/// assert!(verify_merkle_proof::<Sha256Hasher>(&proof, tree.root().unwrap(), &[1], &leaves[1..], 2).is_ok());
+/// assert!(verify_merkle_proof::<Sha256Hasher>(&proof, tree.root().unwrap(), &[1], &leaves[..1], 2).is_err());
+/// ```
+pub fn verify_merkle_proof<H: Hasher<Hash = [u8; DIGEST_LEN]>>(proof: &[u8], root: [u8; DIGEST_LEN], indices: &[usize], leaves: &[[u8; DIGEST_LEN]], leaves_len: usize) -> Result<(), String> {
+    let proof: MerkleProof<H> = match MerkleProof::from_bytes(proof) {
+        Ok(proof) => { proof }
+        Err(err) => { return Err(format!("Could not decode proof from bytes: [{err}]")) }
+    };
+
+    if indices.len() != leaves.len() {
+        return Err(format!("Proof discloses {} leaves but {} indices.", leaves.len(), indices.len()));
+    }
+
+    if proof.verify(root, indices, leaves, leaves_len) {
+        Ok(())
+    } else {
+        Err(String::from("Proof verification failed."))
+    }
+}
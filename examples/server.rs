@@ -0,0 +1,30 @@
+use std::env;
+use csd_jwt::adapters::registry::AdapterRegistry;
+use csd_jwt::server::serve;
+
+/// Starts the issuer/verifier service for credentials of up to `CSD_JWT_SERVER_CLAIMS` claims (32 by default) on the
+/// address in `CSD_JWT_SERVER_ADDRESS` (`127.0.0.1:8080` by default). Setting `CSD_JWT_SERVER_UNIFORM_ERRORS` hides the
+/// reasons of rejected VPs from the callers.
+#[tokio::main]
+async fn main() -> Result<(), String> {
+    env_logger::Builder::new()
+        .filter_level(log::LevelFilter::Info)
+        .parse_default_env()
+        .init();
+
+    let claims_len: usize = match env::var("CSD_JWT_SERVER_CLAIMS") {
+        Ok(claims_len) => match claims_len.parse::<usize>() {
+            Ok(claims_len) => { claims_len }
+            Err(err) => { return Err(format!("CSD_JWT_SERVER_CLAIMS cannot be parsed to usize: [{err}]")) }
+        },
+        Err(_) => { 32 }
+    };
+    let address: String = env::var("CSD_JWT_SERVER_ADDRESS").unwrap_or("127.0.0.1:8080".to_string());
+
+    let mut registry = AdapterRegistry::with_all_adapters(claims_len)?;
+    if env::var("CSD_JWT_SERVER_UNIFORM_ERRORS").is_ok() {
+        registry = registry.with_uniform_errors();
+    }
+
+    serve(registry, &address).await
+}
@@ -1,55 +1,243 @@
 use ark_bn254::{Bn254, Fr};
 use ark_std::rand::rngs::StdRng;
-use ark_std::rand::SeedableRng;
+use std::sync::Arc;
 use serde_json::{Map, Value};
 use vb_accumulator::setup::{Keypair, PublicKey, SecretKey, SetupParams};
 use crate::common_data::CommonData;
-use crate::adapters::adapter::Adapter;
-use crate::sd_algorithms::accumulators::csd_jwt::CsdJwtInstance;
-use crate::sd_algorithms::sd_algorithm::SdAlgorithm;
+use crate::deterministic::ark_rng;
+use crate::adapters::adapter::{Adapter, VerifierAdapter};
+use crate::adapters::accumulators::csd_jwt_verifier_adapter::CsdJwtVerifierAdapter;
+use crate::ephemeral_binding::EphemeralKey;
+use crate::holder_binding::{Es256Binding, HolderBinding};
+use crate::presentation_validity::PresentationValidity;
+use crate::keys::jwks::accumulator_jwk;
+use crate::sd_algorithms::accumulators::csd_jwt::{CsdJwtInstance, PreparedVerificationKey};
+use crate::sd_algorithms::sd_algorithm::{PresentationBatch, SdAlgorithm};
+use crate::sd_algorithms::verification_cache::VerificationCache;
+use crate::size_estimate::{SizeEstimate, SizeModel};
+use crate::verifier_bundle::VerifierBundle;
+use crate::verifier_config::VerifierConfig;
 
 pub struct CsdJwtAdapter {
     holder_public_key: Vec<u8>,
     holder_private_key: Vec<u8>,
+    holder_binding: Arc<dyn HolderBinding>,
     issuer_public_key: PublicKey<Bn254>,
     issuer_private_key: SecretKey<Fr>,
     params: SetupParams<Bn254>,
+    verification_cache: Option<Arc<VerificationCache>>,
+    prepared_key: Option<Arc<PreparedVerificationKey>>,
+    element_blinding: bool,
+    max_claims: Option<usize>,
+    verifier_config: VerifierConfig,
 }
 
-impl Adapter for CsdJwtAdapter {
+impl CsdJwtAdapter {
+
+    /// Replaces the default ES256 holder binding of the VCs and VPs, e.g. to measure the presentations without any proof
+    /// of possession. Ephemeral keys are still certified by the ES256 key of the holder, so VCs bound otherwise cannot be
+    /// presented with them.
+    ///
+    /// # Arguments
+    /// * `holder_binding` - Holder binding of the issued VCs and VPs.
+    ///
+    /// # Returns
+    /// Returns the adapter using the holder binding.
+    pub fn with_holder_binding(mut self, holder_binding: Arc<dyn HolderBinding>) -> Self {
+        self.holder_binding = holder_binding;
+        self
+    }
+
+    /// Blinds the accumulated elements of every issued VC with a random factor chosen for that VC only, so that the
+    /// same claim is accumulated as unrelated elements in different VCs.
+    ///
+    /// # Returns
+    /// Returns the adapter issuing blinded VCs.
+    pub fn with_element_blinding(mut self) -> Self {
+        self.element_blinding = true;
+        self
+    }
+
+    /// Declares the maximum amount of claims of the VCs issued with this setup, so that a VC with more claims is
+    /// rejected at issuance. The accumulator itself does not bound the amount of claims.
+    ///
+    /// # Arguments
+    /// * `max_claims` - Maximum amount of claims of an issued VC.
+    ///
+    /// # Returns
+    /// Returns the adapter rejecting larger VCs.
+    pub fn with_max_claims(mut self, max_claims: usize) -> Self {
+        self.max_claims = Some(max_claims);
+        self
+    }
+
+    /// Enables the verifier-side cache so that repeated presentations of the same credential skip redundant work.
+    ///
+    /// # Returns
+    /// Returns the adapter with an empty verification cache.
+    pub fn with_verification_cache(mut self) -> Self {
+        self.verification_cache = Some(Arc::new(VerificationCache::new()));
+        self
+    }
+
+    /// Precomputes the G2 pairing inputs of the issuer's accumulator public key and of the setup parameters, so that
+    /// every subsequent VP verification skips their preparation.
+    ///
+    /// # Returns
+    /// Returns the adapter with the prepared verification key.
+    pub fn prepare(mut self) -> Self {
+        self.prepared_key = Some(Arc::new(PreparedVerificationKey::new(&self.issuer_public_key, &self.params)));
+        self
+    }
+
+    /// Derives a VP signed with a freshly generated ephemeral key, certified by the key the VC is bound to.
+    ///
+    /// # Arguments
+    /// * `vc` - Verifiable Credential.
+    /// * `disclosures` - List of strings containing the names of the claims that are to be disclosed.
+    ///
+    /// # Returns
+    /// Returns the VP both in form of a Map and in form of a signed JWT, or a string containing an error.
+    pub fn issue_vp_with_ephemeral_key(&self, vc: &Map<String, Value>, disclosures: &[String]) -> Result<(Map<String, Value>, String), String> {
+        self.log_timed("VP issuance with ephemeral key", || {
+            let ephemeral_key = EphemeralKey::generate()?;
+            let certificate = ephemeral_key.certify(&self.holder_private_key)?;
+            CsdJwtInstance::issue_vp_with_ephemeral_key(vc, disclosures, None, None, &ephemeral_key, &certificate)
+        })
+    }
+
+    /// Revokes some claims of a VC, returning the signed witness update message for its holder.
+    ///
+    /// # Arguments
+    /// * `vc` - Verifiable Credential, as issued.
+    /// * `revoked` - Names of the claims to be revoked.
+    /// * `signing_key` - Private key of the issuer signing the message.
+    ///
+    /// # Returns
+    /// Returns the JWT of the message, or a string containing an error.
+    pub fn revoke_claims(&self, vc: &Map<String, Value>, revoked: &[String], signing_key: &[u8]) -> Result<String, String> {
+        self.log_timed("Claim revocation", || CsdJwtInstance::revoke_claims(vc, revoked, &self.issuer_private_key, &signing_key))
+    }
+
+    /// Applies a witness update message to a VC, dropping the revoked claims and updating the remaining witnesses.
+    ///
+    /// # Arguments
+    /// * `vc` - Verifiable Credential.
+    /// * `update_jwt` - Witness update message signed by the issuer.
+    /// * `signing_public_key` - Public key of the issuer signing the message.
+    ///
+    /// # Returns
+    /// Returns the updated VC both in form of a Map and in form of a JWT, or a string containing an error.
+    pub fn apply_witness_update(&self, vc: &Map<String, Value>, update_jwt: &str, signing_public_key: &[u8]) -> Result<(Map<String, Value>, String), String> {
+        self.log_timed("Witness update", || CsdJwtInstance::apply_witness_update(vc, update_jwt, &signing_public_key))
+    }
+
+    /// Verifies a VP signed with a certified ephemeral key.
+    ///
+    /// # Arguments
+    /// * `vp_jwt` - Verifiable Presentation encoded as a jwt.
+    ///
+    /// # Returns
+    /// Returns a string containing an error in case of failure.
+    pub fn verify_vp_with_ephemeral_key(&self, vp_jwt: &str) -> Result<(), String> {
+        self.log_timed("VP verification with ephemeral key", || CsdJwtInstance::verify_vp_with_ephemeral_key(vp_jwt, &self.issuer_public_key, &self.holder_public_key, &self.params, &self.verifier_config))
+    }
+
+    /// Sets the limits enforced on the VCs and VPs verified by the adapter, e.g. a lower maximum amount of claims.
+    ///
+    /// # Arguments
+    /// * `verifier_config` - Limits of the verifier.
+    ///
+    /// # Returns
+    /// Returns the adapter enforcing the limits.
+    pub fn with_verifier_config(mut self, verifier_config: VerifierConfig) -> Self {
+        self.verifier_config = verifier_config;
+        self
+    }
+}
+
+impl VerifierAdapter for CsdJwtAdapter {
 
     fn sd_algorithm(&self) -> String {
         CsdJwtInstance::ALGORITHM.to_string()
     }
 
+    fn verify_vc(&self, vc: &Map<String, Value>) -> Result<(), String> {
+        self.log_timed("VC verification", || CsdJwtInstance::verify_vc_with_config(vc, &self.issuer_public_key, &self.params, &self.verifier_config))
+    }
+
+    fn verify_vp(&self, vp_jwt: &str) -> Result<(), String> {
+        self.log_timed("VP verification", || CsdJwtInstance::verify_vp_with_cache(vp_jwt, &self.issuer_public_key, self.holder_binding.as_ref(), &self.params, self.verification_cache.as_ref(), self.prepared_key.as_ref(), &self.verifier_config))
+    }
+
+    fn proof_artifacts(&self, vp: &Map<String, Value>) -> Result<Map<String, Value>, String> {
+        CsdJwtInstance::proof_artifacts(vp)
+    }
+
+    fn claim_proofs(&self, vp: &Map<String, Value>) -> Result<Map<String, Value>, String> {
+        CsdJwtInstance::claim_proofs(vp)
+    }
+
+    fn verifier_state_length(&self) -> Result<usize, String> {
+        Ok(VerifierBundle::new(self.params.clone(), self.issuer_public_key.clone()).to_json()?.len())
+    }
+
+    fn verification_cache_statistics(&self) -> Option<(usize, usize)> {
+        self.verification_cache.as_ref().map(|cache| (cache.hits(), cache.misses()))
+    }
+}
+
+impl Adapter for CsdJwtAdapter {
+
     fn new(_claims_len: usize) -> Result<Self, String> {
         let (holder_public_key, holder_private_key) = CommonData::holder_keys()?;
-        let mut rng: StdRng = StdRng::from_entropy();
+        let mut rng: StdRng = ark_rng();
         let (params, Keypair { secret_key: ref issuer_private_key, public_key: ref issuer_public_key}) = CsdJwtInstance::initialize_params(&mut rng);
 
+        let holder_binding = Arc::new(Es256Binding::new(holder_public_key.clone(), holder_private_key.clone()));
+
         Ok(CsdJwtAdapter {
             holder_public_key,
+            holder_binding,
             holder_private_key,
             issuer_public_key: issuer_public_key.clone(),
             issuer_private_key: issuer_private_key.clone(),
-            params
+            params,
+            verification_cache: None,
+            prepared_key: None,
+            element_blinding: false,
+            max_claims: None,
+            verifier_config: VerifierConfig::DEFAULT,
         })
     }
 
     fn issue_vc(&self, raw_vc: &Map<String, Value>) -> Result<(Map<String, Value>, String), String> {
-        CsdJwtInstance::issue_vc(raw_vc, &self.issuer_private_key, &self.params)
+        self.log_timed("VC issuance", || {
+            if let Some(max_claims) = self.max_claims {
+                CsdJwtInstance::check_capacity(raw_vc, max_claims)?;
+            }
+            CsdJwtInstance::issue_vc_with_blinding(&self.holder_binding.bind_credential(raw_vc)?, &self.issuer_private_key, &self.params, self.element_blinding)
+        })
     }
 
-    fn verify_vc(&self, vc: &Map<String, Value>) -> Result<(), String> {
-        CsdJwtInstance::verify_vc(vc, &self.issuer_public_key, &self.params)
+    fn issue_vp(&self, vc: &Map<String, Value>, disclosures: &[String]) -> Result<(Map<String, Value>, String), String> {
+        self.log_timed("VP issuance", || CsdJwtInstance::issue_vp(vc, disclosures, self.holder_binding.as_ref()))
+    }
+
+    fn issue_vp_for_audience(&self, vc: &Map<String, Value>, disclosures: &[String], audience: Option<String>, validity: Option<PresentationValidity>) -> Result<(Map<String, Value>, String), String> {
+        self.log_timed("VP issuance", || CsdJwtInstance::issue_vp_for_audience(vc, disclosures, audience, validity, self.holder_binding.as_ref()))
+    }
+
+    fn issue_vp_batch(&self, vc: &Map<String, Value>, disclosure_sets: &[Vec<String>]) -> Result<PresentationBatch, String> {
+        self.log_timed("VP batch issuance", || CsdJwtInstance::issue_vp_batch(vc, disclosure_sets, self.holder_binding.as_ref()))
     }
 
-    fn issue_vp(&self, vc: &Map<String, Value>, disclosures: &Vec<String>) -> Result<(Map<String, Value>, String), String> {
-        CsdJwtInstance::issue_vp(vc, disclosures, &self.holder_private_key)
+    fn holder_storage_length(&self, vc_jwt: &str) -> Result<usize, String> {
+        Ok(vc_jwt.len() + self.holder_binding.secret_length())
     }
 
-    fn verify_vp(&self, vp_jwt: &String) -> Result<(), String> {
-        CsdJwtInstance::verify_vp(vp_jwt, &self.issuer_public_key, &self.holder_public_key, &self.params)
+    fn estimate_sizes(&self, raw_vc: &Map<String, Value>, disclosures: &[String]) -> Result<SizeEstimate, String> {
+        SizeModel::CsdJwt { blinded: self.element_blinding }.estimate(&self.holder_binding.bind_credential(raw_vc)?, disclosures)
     }
 
     fn issuer_keypair(&self) -> Result<(String, String), String> {
@@ -64,4 +252,16 @@ impl Adapter for CsdJwtAdapter {
 
         Ok((issuer_public_key, issuer_private_key))
     }
+
+    fn issuer_jwk(&self) -> Result<Map<String, Value>, String> {
+        accumulator_jwk(&self.issuer_public_key, &self.params, &self.sd_algorithm())
+    }
+
+    fn verifier_bundle(&self) -> Result<VerifierBundle, String> {
+        Ok(VerifierBundle::new(self.params.clone(), self.issuer_public_key.clone()))
+    }
+
+    fn verifier_from_bundle(bundle: VerifierBundle, holder_public_key: Vec<u8>) -> Result<Box<dyn VerifierAdapter>, String> {
+        Ok(Box::new(CsdJwtVerifierAdapter::from_bundle(bundle, holder_public_key)))
+    }
 }
\ No newline at end of file
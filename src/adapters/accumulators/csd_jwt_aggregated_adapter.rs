@@ -0,0 +1,166 @@
+use std::sync::Arc;
+use ark_bn254::{Bn254, Fr, G2Affine};
+use ark_std::rand::rngs::StdRng;
+use serde_json::{Map, Value};
+use vb_accumulator::setup::{Keypair, PublicKey, SecretKey, SetupParams};
+use crate::deterministic::ark_rng;
+use crate::adapters::adapter::{Adapter, VerifierAdapter};
+use crate::holder_binding::{Es256Binding, HolderBinding};
+use crate::presentation_validity::PresentationValidity;
+use crate::keys::jwks::accumulator_jwk;
+use crate::sd_algorithms::accumulators::csd_jwt::{CsdJwtInstance, AGGREGATED_ALGORITHM};
+use crate::size_estimate::{SizeEstimate, SizeModel};
+use crate::verifier_bundle::VerifierBundle;
+use crate::verifier_config::VerifierConfig;
+
+/// Adapter for CSD-JWT where every presentation carries a single aggregated witness for all the disclosed claims.
+pub struct CsdJwtAggregatedAdapter {
+    holder_binding: Arc<dyn HolderBinding>,
+    issuer_public_key: PublicKey<Bn254>,
+    issuer_private_key: SecretKey<Fr>,
+    params: SetupParams<Bn254>,
+    aggregation_key: Vec<G2Affine>,
+    element_blinding: bool,
+    verifier_config: VerifierConfig,
+}
+
+impl CsdJwtAggregatedAdapter {
+
+    /// Replaces the default ES256 holder binding of the VCs and VPs, e.g. to measure the presentations without any proof
+    /// of possession.
+    ///
+    /// # Arguments
+    /// * `holder_binding` - Holder binding of the issued VCs and VPs.
+    ///
+    /// # Returns
+    /// Returns the adapter using the holder binding.
+    pub fn with_holder_binding(mut self, holder_binding: Arc<dyn HolderBinding>) -> Self {
+        self.holder_binding = holder_binding;
+        self
+    }
+
+    /// Blinds the accumulated elements of every issued VC with a random factor chosen for that VC only, so that the
+    /// same claim is accumulated as unrelated elements in different VCs.
+    ///
+    /// # Returns
+    /// Returns the adapter issuing blinded VCs.
+    pub fn with_element_blinding(mut self) -> Self {
+        self.element_blinding = true;
+        self
+    }
+
+    /// Provisions the aggregation key for a declared maximum amount of claims, replacing the one generated for the
+    /// amount passed to `new`. VCs with more claims are rejected at issuance, since their presentations could not be
+    /// verified.
+    ///
+    /// # Arguments
+    /// * `max_claims` - Maximum amount of claims of an issued VC.
+    ///
+    /// # Returns
+    /// Returns the adapter with the provisioned aggregation key.
+    pub fn with_max_claims(mut self, max_claims: usize) -> Self {
+        self.aggregation_key = CsdJwtInstance::initialize_aggregation_key(&self.issuer_private_key, &self.params, max_claims);
+        self
+    }
+
+    /// Sets the limits enforced on the VCs and VPs verified by the adapter, e.g. a lower maximum amount of claims.
+    ///
+    /// # Arguments
+    /// * `verifier_config` - Limits of the verifier.
+    ///
+    /// # Returns
+    /// Returns the adapter enforcing the limits.
+    pub fn with_verifier_config(mut self, verifier_config: VerifierConfig) -> Self {
+        self.verifier_config = verifier_config;
+        self
+    }
+}
+
+impl VerifierAdapter for CsdJwtAggregatedAdapter {
+
+    fn sd_algorithm(&self) -> String {
+        AGGREGATED_ALGORITHM.to_string()
+    }
+
+    fn verify_vc(&self, vc: &Map<String, Value>) -> Result<(), String> {
+        self.log_timed("VC verification", || CsdJwtInstance::verify_vc_with_config(vc, &self.issuer_public_key, &self.params, &self.verifier_config))
+    }
+
+    fn verify_vp(&self, vp_jwt: &str) -> Result<(), String> {
+        self.log_timed("VP verification", || CsdJwtInstance::verify_aggregated_vp(vp_jwt, &self.issuer_public_key, self.holder_binding.as_ref(), &self.params, &self.aggregation_key, &self.verifier_config))
+    }
+
+    fn proof_artifacts(&self, vp: &Map<String, Value>) -> Result<Map<String, Value>, String> {
+        CsdJwtInstance::proof_artifacts(vp)
+    }
+
+    fn claim_proofs(&self, vp: &Map<String, Value>) -> Result<Map<String, Value>, String> {
+        CsdJwtInstance::claim_proofs(vp)
+    }
+
+    fn verifier_state_length(&self) -> Result<usize, String> {
+        Ok(VerifierBundle::new(self.params.clone(), self.issuer_public_key.clone()).to_json()?.len())
+    }
+}
+
+impl Adapter for CsdJwtAggregatedAdapter {
+
+    fn new(claims_len: usize) -> Result<Self, String> {
+        let mut rng: StdRng = ark_rng();
+        let (params, Keypair { secret_key: ref issuer_private_key, public_key: ref issuer_public_key}) = CsdJwtInstance::initialize_params(&mut rng);
+        let aggregation_key = CsdJwtInstance::initialize_aggregation_key(issuer_private_key, &params, claims_len);
+
+        Ok(CsdJwtAggregatedAdapter {
+            holder_binding: Arc::new(Es256Binding::from_holder_keys()?),
+            issuer_public_key: issuer_public_key.clone(),
+            issuer_private_key: issuer_private_key.clone(),
+            params,
+            aggregation_key,
+            element_blinding: false,
+            verifier_config: VerifierConfig::DEFAULT,
+        })
+    }
+
+    fn issue_vc(&self, raw_vc: &Map<String, Value>) -> Result<(Map<String, Value>, String), String> {
+        self.log_timed("VC issuance", || {
+            CsdJwtInstance::check_capacity(raw_vc, CsdJwtInstance::aggregation_capacity(&self.aggregation_key))?;
+            CsdJwtInstance::issue_vc_with_blinding(&self.holder_binding.bind_credential(raw_vc)?, &self.issuer_private_key, &self.params, self.element_blinding)
+        })
+    }
+
+    fn issue_vp(&self, vc: &Map<String, Value>, disclosures: &[String]) -> Result<(Map<String, Value>, String), String> {
+        self.issue_vp_for_audience(vc, disclosures, None, None)
+    }
+
+    fn issue_vp_for_audience(&self, vc: &Map<String, Value>, disclosures: &[String], audience: Option<String>, validity: Option<PresentationValidity>) -> Result<(Map<String, Value>, String), String> {
+        if audience.is_some() {
+            return Err(format!("{} does not support audience-bound presentations.", self.sd_algorithm()));
+        }
+        self.log_timed("VP issuance", || CsdJwtInstance::issue_aggregated_vp(vc, disclosures, validity, self.holder_binding.as_ref()))
+    }
+
+    fn holder_storage_length(&self, vc_jwt: &str) -> Result<usize, String> {
+        Ok(vc_jwt.len() + self.holder_binding.secret_length())
+    }
+
+    fn estimate_sizes(&self, raw_vc: &Map<String, Value>, disclosures: &[String]) -> Result<SizeEstimate, String> {
+        SizeModel::CsdJwtAggregated { blinded: self.element_blinding }.estimate(&self.holder_binding.bind_credential(raw_vc)?, disclosures)
+    }
+
+    fn issuer_keypair(&self) -> Result<(String, String), String> {
+        let issuer_public_key = match serde_json::to_string(&self.issuer_public_key) {
+            Ok(ipk) => {ipk}
+            Err(err) => { return Err(format!("Error in serializing issuer public key: [{err}]")) }
+        };
+        let issuer_private_key = match serde_json::to_string(&self.issuer_private_key) {
+            Ok(ipk) => {ipk}
+            Err(err) => { return Err(format!("Error in serializing issuer private key: [{err}]")) }
+        };
+
+        Ok((issuer_public_key, issuer_private_key))
+    }
+
+    fn issuer_jwk(&self) -> Result<Map<String, Value>, String> {
+        accumulator_jwk(&self.issuer_public_key, &self.params, &self.sd_algorithm())
+    }
+}
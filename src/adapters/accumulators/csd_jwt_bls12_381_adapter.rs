@@ -0,0 +1,131 @@
+use ark_bls12_381::{Bls12_381, Fr};
+use ark_std::rand::rngs::StdRng;
+use std::sync::Arc;
+use serde_json::{Map, Value};
+use vb_accumulator::setup::{Keypair, PublicKey, SecretKey, SetupParams};
+use crate::deterministic::ark_rng;
+use crate::adapters::adapter::{Adapter, VerifierAdapter};
+use crate::holder_binding::{Es256Binding, HolderBinding};
+use crate::presentation_validity::PresentationValidity;
+use crate::keys::jwks::bls12_381_accumulator_jwk;
+use crate::sd_algorithms::accumulators::csd_jwt::CsdJwtInstance;
+use crate::sd_algorithms::accumulators::csd_jwt_bls12_381::CsdJwtBls12381Instance;
+use crate::sd_algorithms::sd_algorithm::SdAlgorithm;
+use crate::size_estimate::{SizeEstimate, SizeModel};
+use crate::verifier_config::VerifierConfig;
+
+pub struct CsdJwtBls12381Adapter {
+    holder_binding: Arc<dyn HolderBinding>,
+    issuer_public_key: PublicKey<Bls12_381>,
+    issuer_private_key: SecretKey<Fr>,
+    params: SetupParams<Bls12_381>,
+    verifier_config: VerifierConfig,
+}
+
+impl CsdJwtBls12381Adapter {
+
+    /// Replaces the default ES256 holder binding of the VCs and VPs, e.g. to measure the presentations without any proof
+    /// of possession.
+    ///
+    /// # Arguments
+    /// * `holder_binding` - Holder binding of the issued VCs and VPs.
+    ///
+    /// # Returns
+    /// Returns the adapter using the holder binding.
+    pub fn with_holder_binding(mut self, holder_binding: Arc<dyn HolderBinding>) -> Self {
+        self.holder_binding = holder_binding;
+        self
+    }
+
+    /// Sets the limits enforced on the VCs and VPs verified by the adapter, e.g. a lower maximum amount of claims.
+    ///
+    /// # Arguments
+    /// * `verifier_config` - Limits of the verifier.
+    ///
+    /// # Returns
+    /// Returns the adapter enforcing the limits.
+    pub fn with_verifier_config(mut self, verifier_config: VerifierConfig) -> Self {
+        self.verifier_config = verifier_config;
+        self
+    }
+}
+
+impl VerifierAdapter for CsdJwtBls12381Adapter {
+
+    fn sd_algorithm(&self) -> String {
+        CsdJwtBls12381Instance::ALGORITHM.to_string()
+    }
+
+    fn verify_vc(&self, vc: &Map<String, Value>) -> Result<(), String> {
+        self.log_timed("VC verification", || CsdJwtBls12381Instance::verify_vc_with_config(vc, &self.issuer_public_key, &self.params, &self.verifier_config))
+    }
+
+    fn verify_vp(&self, vp_jwt: &str) -> Result<(), String> {
+        self.log_timed("VP verification", || CsdJwtBls12381Instance::verify_vp_with_config(vp_jwt, &self.issuer_public_key, self.holder_binding.as_ref(), &self.params, &self.verifier_config))
+    }
+
+    fn proof_artifacts(&self, vp: &Map<String, Value>) -> Result<Map<String, Value>, String> {
+        CsdJwtBls12381Instance::proof_artifacts(vp)
+    }
+
+    fn claim_proofs(&self, vp: &Map<String, Value>) -> Result<Map<String, Value>, String> {
+        CsdJwtBls12381Instance::claim_proofs(vp)
+    }
+
+    fn verifier_state_length(&self) -> Result<usize, String> {
+        Ok(CsdJwtInstance::serialize(&self.issuer_public_key)?.len() + CsdJwtInstance::serialize(&self.params)?.len())
+    }
+}
+
+impl Adapter for CsdJwtBls12381Adapter {
+
+    fn new(_claims_len: usize) -> Result<Self, String> {
+        let mut rng: StdRng = ark_rng();
+        let (params, Keypair { secret_key: ref issuer_private_key, public_key: ref issuer_public_key}) = CsdJwtBls12381Instance::initialize_params(&mut rng);
+
+        Ok(CsdJwtBls12381Adapter {
+            holder_binding: Arc::new(Es256Binding::from_holder_keys()?),
+            issuer_public_key: issuer_public_key.clone(),
+            issuer_private_key: issuer_private_key.clone(),
+            params,
+            verifier_config: VerifierConfig::DEFAULT,
+        })
+    }
+
+    fn issue_vc(&self, raw_vc: &Map<String, Value>) -> Result<(Map<String, Value>, String), String> {
+        self.log_timed("VC issuance", || CsdJwtBls12381Instance::issue_vc(&self.holder_binding.bind_credential(raw_vc)?, &self.issuer_private_key, &self.params))
+    }
+
+    fn issue_vp(&self, vc: &Map<String, Value>, disclosures: &[String]) -> Result<(Map<String, Value>, String), String> {
+        self.log_timed("VP issuance", || CsdJwtBls12381Instance::issue_vp(vc, disclosures, None, None, self.holder_binding.as_ref()))
+    }
+
+    fn issue_vp_for_audience(&self, vc: &Map<String, Value>, disclosures: &[String], audience: Option<String>, validity: Option<PresentationValidity>) -> Result<(Map<String, Value>, String), String> {
+        self.log_timed("VP issuance", || CsdJwtBls12381Instance::issue_vp(vc, disclosures, audience, validity, self.holder_binding.as_ref()))
+    }
+
+    fn holder_storage_length(&self, vc_jwt: &str) -> Result<usize, String> {
+        Ok(vc_jwt.len() + self.holder_binding.secret_length())
+    }
+
+    fn estimate_sizes(&self, raw_vc: &Map<String, Value>, disclosures: &[String]) -> Result<SizeEstimate, String> {
+        SizeModel::CsdJwtBls12381.estimate(&self.holder_binding.bind_credential(raw_vc)?, disclosures)
+    }
+
+    fn issuer_keypair(&self) -> Result<(String, String), String> {
+        let issuer_public_key = match serde_json::to_string(&self.issuer_public_key) {
+            Ok(ipk) => {ipk}
+            Err(err) => { return Err(format!("Error in serializing issuer public key: [{err}]")) }
+        };
+        let issuer_private_key = match serde_json::to_string(&self.issuer_private_key) {
+            Ok(ipk) => {ipk}
+            Err(err) => { return Err(format!("Error in serializing issuer private key: [{err}]")) }
+        };
+
+        Ok((issuer_public_key, issuer_private_key))
+    }
+
+    fn issuer_jwk(&self) -> Result<Map<String, Value>, String> {
+        bls12_381_accumulator_jwk(&self.issuer_public_key, &self.params, &self.sd_algorithm())
+    }
+}
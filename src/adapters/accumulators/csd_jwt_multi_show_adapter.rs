@@ -0,0 +1,175 @@
+use std::sync::Arc;
+use ark_bn254::{Bn254, Fr, G1Affine};
+use ark_std::rand::rngs::StdRng;
+use serde_json::{Map, Value};
+use vb_accumulator::setup::{Keypair, MembershipProvingKey, PublicKey, SecretKey, SetupParams};
+use crate::deterministic::ark_rng;
+use crate::adapters::adapter::{Adapter, VerifierAdapter};
+use crate::holder_binding::{Es256Binding, HolderBinding};
+use crate::presentation_validity::PresentationValidity;
+use crate::keys::jwks::accumulator_jwk;
+use crate::sd_algorithms::accumulators::csd_jwt::{CsdJwtInstance, MULTI_SHOW_ALGORITHM};
+use crate::size_estimate::{SizeEstimate, SizeModel};
+use crate::verifier_bundle::VerifierBundle;
+use crate::verifier_config::VerifierConfig;
+
+/// Adapter for CSD-JWT where every presentation carries fresh zero-knowledge membership proofs instead of the witnesses.
+pub struct CsdJwtMultiShowAdapter {
+    holder_binding: Arc<dyn HolderBinding>,
+    issuer_public_key: PublicKey<Bn254>,
+    issuer_private_key: SecretKey<Fr>,
+    params: SetupParams<Bn254>,
+    proving_key: MembershipProvingKey<G1Affine>,
+    element_blinding: bool,
+    max_claims: Option<usize>,
+    verifier_config: VerifierConfig,
+}
+
+impl CsdJwtMultiShowAdapter {
+
+    /// Replaces the default ES256 holder binding of the VCs and VPs, e.g. to measure the presentations without any proof
+    /// of possession.
+    ///
+    /// # Arguments
+    /// * `holder_binding` - Holder binding of the issued VCs and VPs.
+    ///
+    /// # Returns
+    /// Returns the adapter using the holder binding.
+    pub fn with_holder_binding(mut self, holder_binding: Arc<dyn HolderBinding>) -> Self {
+        self.holder_binding = holder_binding;
+        self
+    }
+
+    /// Blinds the accumulated elements of every issued VC with a random factor chosen for that VC only, so that the
+    /// same claim is accumulated as unrelated elements in different VCs.
+    ///
+    /// # Returns
+    /// Returns the adapter issuing blinded VCs.
+    pub fn with_element_blinding(mut self) -> Self {
+        self.element_blinding = true;
+        self
+    }
+
+    /// Declares the maximum amount of claims of the VCs issued with this setup, so that a VC with more claims is
+    /// rejected at issuance. The accumulator itself does not bound the amount of claims.
+    ///
+    /// # Arguments
+    /// * `max_claims` - Maximum amount of claims of an issued VC.
+    ///
+    /// # Returns
+    /// Returns the adapter rejecting larger VCs.
+    pub fn with_max_claims(mut self, max_claims: usize) -> Self {
+        self.max_claims = Some(max_claims);
+        self
+    }
+
+    /// Sets the limits enforced on the VCs and VPs verified by the adapter, e.g. a lower maximum amount of claims.
+    ///
+    /// # Arguments
+    /// * `verifier_config` - Limits of the verifier.
+    ///
+    /// # Returns
+    /// Returns the adapter enforcing the limits.
+    pub fn with_verifier_config(mut self, verifier_config: VerifierConfig) -> Self {
+        self.verifier_config = verifier_config;
+        self
+    }
+}
+
+impl VerifierAdapter for CsdJwtMultiShowAdapter {
+
+    fn sd_algorithm(&self) -> String {
+        MULTI_SHOW_ALGORITHM.to_string()
+    }
+
+    fn verify_vc(&self, vc: &Map<String, Value>) -> Result<(), String> {
+        self.log_timed("VC verification", || CsdJwtInstance::verify_vc_with_config(vc, &self.issuer_public_key, &self.params, &self.verifier_config))
+    }
+
+    fn verify_vp(&self, vp_jwt: &str) -> Result<(), String> {
+        self.log_timed("VP verification", || CsdJwtInstance::verify_multi_show_vp(vp_jwt, &self.issuer_public_key, self.holder_binding.as_ref(), &self.params, &self.proving_key, &self.verifier_config))
+    }
+
+    fn proof_artifacts(&self, vp: &Map<String, Value>) -> Result<Map<String, Value>, String> {
+        CsdJwtInstance::proof_artifacts(vp)
+    }
+
+    fn claim_proofs(&self, vp: &Map<String, Value>) -> Result<Map<String, Value>, String> {
+        CsdJwtInstance::claim_proofs(vp)
+    }
+
+    fn verifier_state_length(&self) -> Result<usize, String> {
+        // Multi-show presentations are also verified against the proving key of the membership proofs.
+        let bundle_length: usize = VerifierBundle::new(self.params.clone(), self.issuer_public_key.clone()).to_json()?.len();
+        Ok(bundle_length + CsdJwtInstance::serialize(&self.proving_key)?.len())
+    }
+}
+
+impl Adapter for CsdJwtMultiShowAdapter {
+
+    fn new(_claims_len: usize) -> Result<Self, String> {
+        let mut rng: StdRng = ark_rng();
+        let (params, Keypair { secret_key: ref issuer_private_key, public_key: ref issuer_public_key}) = CsdJwtInstance::initialize_params(&mut rng);
+        let proving_key = CsdJwtInstance::initialize_proving_key(&mut rng);
+
+        Ok(CsdJwtMultiShowAdapter {
+            holder_binding: Arc::new(Es256Binding::from_holder_keys()?),
+            issuer_public_key: issuer_public_key.clone(),
+            issuer_private_key: issuer_private_key.clone(),
+            params,
+            proving_key,
+            element_blinding: false,
+            max_claims: None,
+            verifier_config: VerifierConfig::DEFAULT,
+        })
+    }
+
+    fn issue_vc(&self, raw_vc: &Map<String, Value>) -> Result<(Map<String, Value>, String), String> {
+        self.log_timed("VC issuance", || {
+            if let Some(max_claims) = self.max_claims {
+                CsdJwtInstance::check_capacity(raw_vc, max_claims)?;
+            }
+            CsdJwtInstance::issue_vc_with_blinding(&self.holder_binding.bind_credential(raw_vc)?, &self.issuer_private_key, &self.params, self.element_blinding)
+        })
+    }
+
+    fn issue_vp(&self, vc: &Map<String, Value>, disclosures: &[String]) -> Result<(Map<String, Value>, String), String> {
+        self.issue_vp_for_audience(vc, disclosures, None, None)
+    }
+
+    fn issue_vp_for_audience(&self, vc: &Map<String, Value>, disclosures: &[String], audience: Option<String>, validity: Option<PresentationValidity>) -> Result<(Map<String, Value>, String), String> {
+        if audience.is_some() {
+            return Err(format!("{} does not support audience-bound presentations.", self.sd_algorithm()));
+        }
+        self.log_timed("VP issuance", || CsdJwtInstance::issue_multi_show_vp(vc, disclosures, validity, self.holder_binding.as_ref(), &self.issuer_public_key, &self.params, &self.proving_key))
+    }
+
+    fn holder_storage_length(&self, vc_jwt: &str) -> Result<usize, String> {
+        // Membership proofs are generated against the issuer's public key, the setup parameters and the proving key.
+        let proof_material: usize = CsdJwtInstance::serialize(&self.issuer_public_key)?.len()
+            + CsdJwtInstance::serialize(&self.params)?.len()
+            + CsdJwtInstance::serialize(&self.proving_key)?.len();
+        Ok(vc_jwt.len() + self.holder_binding.secret_length() + proof_material)
+    }
+
+    fn estimate_sizes(&self, raw_vc: &Map<String, Value>, disclosures: &[String]) -> Result<SizeEstimate, String> {
+        SizeModel::CsdJwtMultiShow { blinded: self.element_blinding }.estimate(&self.holder_binding.bind_credential(raw_vc)?, disclosures)
+    }
+
+    fn issuer_keypair(&self) -> Result<(String, String), String> {
+        let issuer_public_key = match serde_json::to_string(&self.issuer_public_key) {
+            Ok(ipk) => {ipk}
+            Err(err) => { return Err(format!("Error in serializing issuer public key: [{err}]")) }
+        };
+        let issuer_private_key = match serde_json::to_string(&self.issuer_private_key) {
+            Ok(ipk) => {ipk}
+            Err(err) => { return Err(format!("Error in serializing issuer private key: [{err}]")) }
+        };
+
+        Ok((issuer_public_key, issuer_private_key))
+    }
+
+    fn issuer_jwk(&self) -> Result<Map<String, Value>, String> {
+        accumulator_jwk(&self.issuer_public_key, &self.params, &self.sd_algorithm())
+    }
+}
@@ -0,0 +1,104 @@
+use std::sync::Arc;
+use ark_bn254::Bn254;
+use serde_json::{Map, Value};
+use vb_accumulator::setup::{PublicKey, SetupParams};
+use crate::adapters::adapter::VerifierAdapter;
+use crate::sd_algorithms::accumulators::csd_jwt::{CsdJwtInstance, PreparedVerificationKey};
+use crate::sd_algorithms::sd_algorithm::SdAlgorithm;
+use crate::sd_algorithms::verification_cache::VerificationCache;
+use crate::verifier_bundle::VerifierBundle;
+use crate::verifier_config::VerifierConfig;
+
+/// Verification-only adapter for CSD-JWT, built from a verifier bundle and therefore without any secret key. It only
+/// implements `VerifierAdapter`, so that issuance operations cannot even be called on it.
+pub struct CsdJwtVerifierAdapter {
+    holder_public_key: Vec<u8>,
+    issuer_public_key: PublicKey<Bn254>,
+    params: SetupParams<Bn254>,
+    verification_cache: Option<Arc<VerificationCache>>,
+    prepared_key: Option<Arc<PreparedVerificationKey>>,
+    verifier_config: VerifierConfig,
+}
+
+impl CsdJwtVerifierAdapter {
+
+    /// Constructor for a verification-only adapter.
+    ///
+    /// # Arguments
+    /// * `bundle` - Verifier bundle exported by the issuer.
+    /// * `holder_public_key` - Public key of the holder presenting the VPs.
+    ///
+    /// # Returns
+    /// The adapter.
+    pub fn from_bundle(bundle: VerifierBundle, holder_public_key: Vec<u8>) -> Self {
+        CsdJwtVerifierAdapter {
+            holder_public_key,
+            issuer_public_key: bundle.issuer_public_key,
+            params: bundle.params,
+            verification_cache: None,
+            prepared_key: None,
+            verifier_config: VerifierConfig::DEFAULT,
+        }
+    }
+
+    /// Enables the verifier-side cache so that repeated presentations of the same credential skip redundant work.
+    ///
+    /// # Returns
+    /// Returns the adapter with an empty verification cache.
+    pub fn with_verification_cache(mut self) -> Self {
+        self.verification_cache = Some(Arc::new(VerificationCache::new()));
+        self
+    }
+
+    /// Precomputes the G2 pairing inputs of the issuer's accumulator public key and of the setup parameters.
+    ///
+    /// # Returns
+    /// Returns the adapter with the prepared verification key.
+    pub fn prepare(mut self) -> Self {
+        self.prepared_key = Some(Arc::new(PreparedVerificationKey::new(&self.issuer_public_key, &self.params)));
+        self
+    }
+
+    /// Sets the limits enforced on the VCs and VPs verified by the adapter, e.g. a lower maximum amount of claims.
+    ///
+    /// # Arguments
+    /// * `verifier_config` - Limits of the verifier.
+    ///
+    /// # Returns
+    /// Returns the adapter enforcing the limits.
+    pub fn with_verifier_config(mut self, verifier_config: VerifierConfig) -> Self {
+        self.verifier_config = verifier_config;
+        self
+    }
+}
+
+impl VerifierAdapter for CsdJwtVerifierAdapter {
+
+    fn sd_algorithm(&self) -> String {
+        CsdJwtInstance::ALGORITHM.to_string()
+    }
+
+    fn verify_vc(&self, vc: &Map<String, Value>) -> Result<(), String> {
+        self.log_timed("VC verification", || CsdJwtInstance::verify_vc_with_config(vc, &self.issuer_public_key, &self.params, &self.verifier_config))
+    }
+
+    fn verify_vp(&self, vp_jwt: &str) -> Result<(), String> {
+        self.log_timed("VP verification", || CsdJwtInstance::verify_vp_with_cache(vp_jwt, &self.issuer_public_key, &self.holder_public_key, &self.params, self.verification_cache.as_ref(), self.prepared_key.as_ref(), &self.verifier_config))
+    }
+
+    fn proof_artifacts(&self, vp: &Map<String, Value>) -> Result<Map<String, Value>, String> {
+        CsdJwtInstance::proof_artifacts(vp)
+    }
+
+    fn claim_proofs(&self, vp: &Map<String, Value>) -> Result<Map<String, Value>, String> {
+        CsdJwtInstance::claim_proofs(vp)
+    }
+
+    fn verifier_state_length(&self) -> Result<usize, String> {
+        Ok(VerifierBundle::new(self.params.clone(), self.issuer_public_key.clone()).to_json()?.len())
+    }
+
+    fn verification_cache_statistics(&self) -> Option<(usize, usize)> {
+        self.verification_cache.as_ref().map(|cache| (cache.hits(), cache.misses()))
+    }
+}
@@ -1 +1,5 @@
-pub mod csd_jwt_adapter;
\ No newline at end of file
+pub mod csd_jwt_adapter;
+pub mod csd_jwt_multi_show_adapter;
+pub mod csd_jwt_aggregated_adapter;
+pub mod csd_jwt_verifier_adapter;
+pub mod csd_jwt_bls12_381_adapter;
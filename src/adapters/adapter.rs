@@ -1,7 +1,24 @@
+use std::time::Instant;
+use log::{debug, log_enabled, Level};
 use serde_json::{Map, Value};
+use crate::convert::lower_vc;
+use crate::credential_ir::{credential_id, CredentialIr};
+use crate::disclosure::{DisclosureRequest, DisclosureResponse};
+use crate::entropy;
+use crate::issuance_log::{IssuanceLog, IssuanceRecord};
+use crate::presentation_validity::PresentationValidity;
+use crate::sd_algorithms::sd_algorithm::PresentationBatch;
+use crate::size_estimate::SizeEstimate;
+use crate::transformations::{AuthorizedTransformations, Transformation};
+use crate::verifier_bundle::VerifierBundle;
 
-/// Adapter trait to level heterogeneous algorithm instances to execute different instances using the same notation.
-pub trait Adapter {
+/// Verification side of an adapter, needing only public key material (issuer public key, setup parameters and holder
+/// public key). Verifiers built from public material implement only this trait, so that the type system rules out
+/// issuing credentials or presentations with them.
+///
+/// Verifier adapters are shared by concurrent verifier threads and by the tasks of the HTTP service, hence they must
+/// be `Send` and `Sync`.
+pub trait VerifierAdapter: Send + Sync {
 
     /// Retrieve the name of the algorithm.
     ///
@@ -10,6 +27,109 @@ pub trait Adapter {
     fn sd_algorithm(&self) -> String;
 
 
+    /// Verifies the VC.
+    ///
+    /// # Arguments
+    /// * `vc` - Verifiable Credential to be verified.
+    ///
+    /// # Returns
+    /// Returns a result with a string illustrating an error, if this happens.
+    fn verify_vc(&self, vc: &Map<String, Value>) -> Result<(), String>;
+
+
+    /// Given a VP, verify it.
+    ///
+    /// # Arguments
+    /// * `vp_jwt` - jwt of the Verifiable Presentation to be verified.
+    ///
+    /// # Returns
+    /// Returns a result containing a string illustrating an error, if it occurs.
+    fn verify_vp(&self, vp_jwt: &str) -> Result<(), String>;
+
+
+    /// Retrieve the algorithm-specific proof artifacts of a VC or VP, separated from the JWT envelope and encoded in base64url,
+    /// to be used with other verification stacks or to debug interoperability failures.
+    ///
+    /// # Arguments
+    /// * `vp` - Verifiable Credential or Verifiable Presentation from which the artifacts must be extracted.
+    ///
+    /// # Returns
+    /// Returns a result containing a map of the artifacts or a string illustrating an error, if it occurs.
+    fn proof_artifacts(&self, vp: &Map<String, Value>) -> Result<Map<String, Value>, String>;
+
+
+    /// Retrieve the minimal proof of every disclosed claim of a VP together with the material shared by all of them, so
+    /// that auditors can re-verify individual disclosures. See `crate::audit::audit_document` for the structure.
+    ///
+    /// # Arguments
+    /// * `vp` - Verifiable Presentation or Verifiable Credential from which the proofs must be extracted.
+    ///
+    /// # Returns
+    /// Returns a result containing the `shared` and `claims` objects of the audit document or a string illustrating an
+    /// error, if it occurs.
+    fn claim_proofs(&self, vp: &Map<String, Value>) -> Result<Map<String, Value>, String>;
+
+
+    /// Length of the state a verifier must store or fetch to verify the presentations of an issuer, i.e. the issuer's
+    /// public key and, for the accumulator-based algorithms, the setup parameters and proving key, as distributed.
+    ///
+    /// # Returns
+    /// Returns a result containing the length in bytes or a string illustrating an error, if it occurs.
+    fn verifier_state_length(&self) -> Result<usize, String>;
+
+
+    /// Retrieve the statistics of the verifier-side verification cache.
+    ///
+    /// # Returns
+    /// Returns the amount of cache hits and misses respectively, or None if the adapter does not use a verification cache.
+    fn verification_cache_statistics(&self) -> Option<(usize, usize)> {
+        None
+    }
+
+
+    /// Runs an operation of the adapter, logging its outcome and duration at debug level and attributing the
+    /// randomness it draws to the algorithm and operation when the entropy source of the thread records them.
+    ///
+    /// # Arguments
+    /// * `operation` - Name of the operation, as it appears in the logs.
+    /// * `function` - Operation to be executed.
+    ///
+    /// # Returns
+    /// Returns the result of the operation.
+    fn log_timed<T, F>(&self, operation: &str, function: F) -> Result<T, String>
+    where
+        F: FnOnce() -> Result<T, String>,
+        Self: Sized,
+    {
+        let function = || {
+            if entropy::records_scopes() {
+                entropy::with_scope(&self.sd_algorithm(), operation, function)
+            } else {
+                function()
+            }
+        };
+        if !log_enabled!(Level::Debug) {
+            return function();
+        }
+
+        let start = Instant::now();
+        let result = function();
+        match &result {
+            Ok(_) => { debug!("[{}] {} succeeded in {:?}", self.sd_algorithm(), operation, start.elapsed()) }
+            Err(err) => { debug!("[{}] {} failed in {:?}: {}", self.sd_algorithm(), operation, start.elapsed(), err) }
+        }
+
+        result
+    }
+}
+
+
+/// Adapter trait to level heterogeneous algorithm instances to execute different instances using the same notation.
+///
+/// Besides the verification operations of `VerifierAdapter`, adapters hold the issuer and holder private keys to issue
+/// VCs and VPs.
+pub trait Adapter: VerifierAdapter {
+
     /// Create a new instance of the algorithm.
     ///
     /// # Arguments
@@ -29,14 +149,47 @@ pub trait Adapter {
     fn issue_vc(&self, raw_vc: &Map<String, Value>) -> Result<(Map<String, Value>, String), String>;
 
 
-    /// Verifies the VC.
+    /// Issues a new VC and records it in the issuance log.
     ///
     /// # Arguments
-    /// * `vc` - Verifiable Credential to be verified.
+    /// * `raw_vc` - Skeleton of a VC to be decorated with all the methods to create Verifiable Credentials.
+    /// * `log` - Issuance log where the credential ID, claim digests, algorithm and issuer key ID are recorded.
     ///
     /// # Returns
-    /// Returns a result with a string illustrating an error, if this happens.
-    fn verify_vc(&self, vc: &Map<String, Value>) -> Result<(), String>;
+    /// Returns a result containing a map of the VC and the encoded jwt or a string highlighting an error, if it occurs.
+    fn issue_vc_logged(&self, raw_vc: &Map<String, Value>, log: &dyn IssuanceLog) -> Result<(Map<String, Value>, String), String> {
+        let (vc, jwt) = self.issue_vc(raw_vc)?;
+        let (issuer_public_key, _) = self.issuer_keypair()?;
+
+        log.record(&IssuanceRecord::new(raw_vc, &jwt, self.sd_algorithm(), &issuer_public_key))?;
+
+        Ok((vc, jwt))
+    }
+
+
+    /// Re-issues a VC with updated claims, e.g. after a change of address, keeping the holder binding and the metadata
+    /// of the VC and linking the new VC to it through its `previousCredential` field.
+    ///
+    /// # Arguments
+    /// * `vc` - Verifiable Credential to be replaced, issued by this adapter.
+    /// * `updated_claims` - Claims to be added or replaced, a `null` value removing the claim.
+    ///
+    /// # Returns
+    /// Returns a result containing a map of the new VC and the encoded jwt or a string highlighting an error, if the VC
+    /// is invalid or the new VC would be bound to another holder key.
+    fn reissue_vc(&self, vc: &Map<String, Value>, updated_claims: &Map<String, Value>) -> Result<(Map<String, Value>, String), String> {
+        if let Err(err) = self.verify_vc(vc) {
+            return Err(format!("Failed to verify the {} VC to be re-issued: [{err}]", self.sd_algorithm()));
+        }
+
+        let credential: CredentialIr = lower_vc(vc, &self.sd_algorithm())?;
+        let (reissued_vc, jwt) = self.issue_vc(&credential.reissued_raw_vc(updated_claims, &credential_id(vc))?)?;
+        if lower_vc(&reissued_vc, &self.sd_algorithm())?.holder_binding() != credential.holder_binding() {
+            return Err("Re-issued VC is not bound to the holder key of the replaced VC".to_string());
+        }
+
+        Ok((reissued_vc, jwt))
+    }
 
 
     /// Given a VC and a list of disclosures, generate a Verifiable Presentation.
@@ -47,17 +200,104 @@ pub trait Adapter {
     ///
     /// # Returns
     /// Returns a result containing a map of the VP and the encoded jwt or a string highlighting an error, if it occurs.
-    fn issue_vp(&self, vc: &Map<String, Value>, disclosures: &Vec<String>) -> Result<(Map<String, Value>, String), String>;
+    fn issue_vp(&self, vc: &Map<String, Value>, disclosures: &[String]) -> Result<(Map<String, Value>, String), String>;
 
 
-    /// Given a VP, verify it.
+    /// Given a VC and a list of disclosures, generate a Verifiable Presentation bound to an optional audience, as
+    /// required to disclose claims tagged as sensitive, and valid within an optional window.
     ///
     /// # Arguments
-    /// * `vp_jwt` - jwt of the Verifiable Presentation to be verified.
+    /// * `vc` - Verifiable Credential from which the VP must be generated.
+    /// * `disclosures` - Array containing the identifiers of the claims to disclose.
+    /// * `audience` - Optional intended audience of the VP.
+    /// * `validity` - Optional validity window of the VP.
     ///
     /// # Returns
-    /// Returns a result containing a string illustrating an error, if it occurs.
-    fn verify_vp(&self, vp_jwt: &String) -> Result<(), String>;
+    /// Returns a result containing a map of the VP and the encoded jwt or a string highlighting an error, if it occurs.
+    fn issue_vp_for_audience(&self, vc: &Map<String, Value>, disclosures: &[String], audience: Option<String>, validity: Option<PresentationValidity>) -> Result<(Map<String, Value>, String), String> {
+        match (audience, validity) {
+            (None, None) => { self.issue_vp(vc, disclosures) }
+            (Some(_), _) => { Err(format!("{} does not support audience-bound presentations.", self.sd_algorithm())) }
+            (None, Some(_)) => { Err(format!("{} does not support expiring presentations.", self.sd_algorithm())) }
+        }
+    }
+
+
+    /// Given a VC and the request of a verifier, resolve the request against the claims of the VC and generate the
+    /// Verifiable Presentation answering it.
+    ///
+    /// # Arguments
+    /// * `vc` - Verifiable Credential from which the VP must be generated.
+    /// * `request` - Claims and predicates requested by the verifier.
+    /// * `audience` - Optional intended audience of the VP.
+    ///
+    /// # Returns
+    /// Returns a result containing a map of the VP, the encoded jwt and the record of the disclosed claims, or a string highlighting an error, if it occurs.
+    fn issue_vp_for_request(&self, vc: &Map<String, Value>, request: &DisclosureRequest, audience: Option<String>) -> Result<(Map<String, Value>, String, DisclosureResponse), String> {
+        let credential: CredentialIr = lower_vc(vc, &self.sd_algorithm())?;
+        let response: DisclosureResponse = request.resolve(credential.claims())?;
+        let (vp, vp_jwt) = self.issue_vp_for_audience(vc, &response.disclosed, audience, None)?;
+
+        Ok((vp, vp_jwt, response))
+    }
+
+
+    /// Given a VC and a list of disclosures, generate a Verifiable Presentation disclosing, for the claims the holder
+    /// chose to coarsen, the transformed values the issuer pre-authorized instead of the exact ones. The names of the
+    /// disclosed transformed claims record the transformation for the verifier.
+    ///
+    /// # Arguments
+    /// * `vc` - Verifiable Credential from which the VP must be generated.
+    /// * `disclosures` - Array containing the identifiers of the claims to disclose.
+    /// * `hooks` - Transformations applied by the holder, by claim.
+    ///
+    /// # Returns
+    /// Returns a result containing a map of the VP and the encoded jwt or a string highlighting an error, if it occurs.
+    fn issue_transformed_vp(&self, vc: &Map<String, Value>, disclosures: &[String], hooks: &[(String, Transformation)]) -> Result<(Map<String, Value>, String), String> {
+        let credential: CredentialIr = lower_vc(vc, &self.sd_algorithm())?;
+        let disclosures: Vec<String> = AuthorizedTransformations::transform_disclosures(credential.claims(), disclosures, hooks)?;
+
+        self.issue_vp(vc, &disclosures)
+    }
+
+
+    /// Given a VC and several lists of disclosures, generate one Verifiable Presentation per list, sharing the decoding
+    /// of the VC and any proof precomputation across all of them.
+    ///
+    /// # Arguments
+    /// * `vc` - Verifiable Credential from which the VPs must be generated.
+    /// * `disclosure_sets` - Arrays containing the identifiers of the claims to disclose in each VP.
+    ///
+    /// # Returns
+    /// Returns a result containing the maps of the VPs and the encoded jwts, in the same order of the disclosure sets, or a string highlighting an error, if it occurs.
+    fn issue_vp_batch(&self, vc: &Map<String, Value>, disclosure_sets: &[Vec<String>]) -> Result<PresentationBatch, String> {
+        disclosure_sets.iter().map(|disclosures| self.issue_vp(vc, disclosures)).collect()
+    }
+
+
+    /// Length of what the holder must persist to derive presentations from a VC, as opposed to the VC JWT sent over
+    /// the wire: the VC JWT itself, whose disclosure material (e.g. salts or witnesses) the holder keeps, along with
+    /// the auxiliary material not included in it, e.g. the holder's private key or the public parameters of the proofs.
+    ///
+    /// # Arguments
+    /// * `vc_jwt` - Encoded VC stored by the holder.
+    ///
+    /// # Returns
+    /// Returns a result containing the length in bytes or a string highlighting an error, if it occurs.
+    fn holder_storage_length(&self, vc_jwt: &str) -> Result<usize, String>;
+
+
+    /// Dry run of an issuance and presentation: estimates the lengths of the VC issued from a raw VC and of a VP
+    /// disclosing some of its claims from the size model of the algorithm, without any cryptographic operation, so that
+    /// an issuer can interactively choose an algorithm for a schema. See `crate::size_estimate::SizeModel`.
+    ///
+    /// # Arguments
+    /// * `raw_vc` - Skeleton of a VC, as given to `issue_vc`.
+    /// * `disclosures` - Names of the claims disclosed by the VP.
+    ///
+    /// # Returns
+    /// Returns a result containing the estimate or a string highlighting an error, if it occurs.
+    fn estimate_sizes(&self, raw_vc: &Map<String, Value>, disclosures: &[String]) -> Result<SizeEstimate, String>;
 
 
     /// Retrieve the issuer's cryptographic key material.
@@ -65,4 +305,292 @@ pub trait Adapter {
     /// # Returns
     /// Returns a result containing the encodings of the issuer's public key and secret key respectively, or a string highlighting an error, if it occurs.
     fn issuer_keypair(&self,) -> Result<(String, String), String>;
+
+
+    /// Retrieve the issuer's verification key as a JWK, with its thumbprint as `kid`, to be published in a JWKS.
+    ///
+    /// # Returns
+    /// Returns a result containing the JWK, or a string highlighting an error if the key cannot be represented as a JWK.
+    fn issuer_jwk(&self) -> Result<Map<String, Value>, String> {
+        Err(format!("{} does not publish its issuer key as a JWK.", self.sd_algorithm()))
+    }
+
+
+    /// Export the public key material verifiers need, without any private key.
+    ///
+    /// # Returns
+    /// Returns a result containing the verifier bundle of the issuer, or a string highlighting an error if the algorithm does not support verifier bundles.
+    fn verifier_bundle(&self) -> Result<VerifierBundle, String> {
+        Err(format!("{} does not support verifier bundles.", self.sd_algorithm()))
+    }
+
+
+    /// Create a verifier of the algorithm from public key material only.
+    ///
+    /// # Arguments
+    /// * `bundle` - Verifier bundle exported by the issuer.
+    /// * `holder_public_key` - Public key of the holder presenting the VPs.
+    ///
+    /// # Returns
+    /// Returns a result containing the verifier, or a string highlighting an error if the algorithm does not support verifier bundles.
+    fn verifier_from_bundle(_bundle: VerifierBundle, _holder_public_key: Vec<u8>) -> Result<Box<dyn VerifierAdapter>, String> where Self: Sized {
+        Err("The algorithm does not support verifier bundles.".to_string())
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{Map, Value};
+    use crate::adapters::accumulators::csd_jwt_adapter::CsdJwtAdapter;
+    use crate::adapters::accumulators::csd_jwt_aggregated_adapter::CsdJwtAggregatedAdapter;
+    use crate::adapters::accumulators::csd_jwt_bls12_381_adapter::CsdJwtBls12381Adapter;
+    use crate::adapters::accumulators::csd_jwt_multi_show_adapter::CsdJwtMultiShowAdapter;
+    use crate::adapters::adapter::{Adapter, VerifierAdapter};
+    use crate::adapters::hashes::merkle_tree_adapter::MerkleTreeAdapter;
+    use crate::adapters::hashes::sd_jwt_adapter::SdJwtAdapter;
+    use crate::adapters::signatures::bbs_plus_adapter::BBSPlusAdapter;
+    use crate::claims_diff::diff_claims;
+    use crate::audit::SIGNED_FIELDS;
+    use crate::common_data::{CommonData, EXPIRATION, VC};
+    use crate::encoding::Encoding;
+    use crate::presentation_validity::PresentationValidity;
+    use crate::verifier_bundle::VerifierBundle;
+    use crate::verifier_config::VerifierConfig;
+
+    /// Issues a VC and a VP with the default limits, then checks that stricter limits set on the same adapter reject them.
+    fn check_runtime_limits<A: Adapter>(adapter: A, with_verifier_config: fn(A, VerifierConfig) -> A) -> Result<(), String> {
+        let raw_vc: Map<String, Value> = match serde_json::from_str::<Map<String, Value>>(VC) {
+            Ok(vc) => { vc }
+            Err(err) => { return Err(format!("Failed to parse Raw Verifiable Credential from string. [{err}]")); }
+        };
+        let (vc, _vc_jwt) = adapter.issue_vc(&raw_vc)?;
+        let (_vp, vp_jwt) = adapter.issue_vp(&vc, &vec!["name".to_string()])?;
+        adapter.verify_vc(&vc)?;
+        adapter.verify_vp(&vp_jwt)?;
+
+        let oversized = VerifierConfig { max_jwt_length: vp_jwt.len() - 1, ..VerifierConfig::DEFAULT };
+        let adapter: A = with_verifier_config(adapter, oversized);
+        assert!(adapter.verify_vp(&vp_jwt).is_err(), "[{}] Oversized VP was verified.", adapter.sd_algorithm());
+
+        let too_many_claims = VerifierConfig { max_claims: 1, ..VerifierConfig::DEFAULT };
+        let adapter: A = with_verifier_config(adapter, too_many_claims);
+        assert!(adapter.verify_vc(&vc).is_err(), "[{}] VC with too many claims was verified.", adapter.sd_algorithm());
+
+        let adapter: A = with_verifier_config(adapter, VerifierConfig::DEFAULT);
+        adapter.verify_vp(&vp_jwt)
+    }
+
+    /// Issues a VP valid for a minute long ago, then checks that the adapter rejects it unless it verifies at a time
+    /// within the window.
+    fn check_expired_presentation<A: Adapter>(adapter: A, with_verifier_config: fn(A, VerifierConfig) -> A) -> Result<(), String> {
+        let raw_vc: Map<String, Value> = match serde_json::from_str::<Map<String, Value>>(VC) {
+            Ok(vc) => { vc }
+            Err(err) => { return Err(format!("Failed to parse Raw Verifiable Credential from string. [{err}]")); }
+        };
+        let (vc, _vc_jwt) = adapter.issue_vc(&raw_vc)?;
+        let validity = PresentationValidity::new(60).with_not_before(1_000_000);
+        let (_vp, vp_jwt) = adapter.issue_vp_for_audience(&vc, &vec!["name".to_string()], None, Some(validity))?;
+        assert!(adapter.verify_vp(&vp_jwt).is_err(), "[{}] Expired VP was verified.", adapter.sd_algorithm());
+
+        let within_window = VerifierConfig { verification_time: Some(1_000_030), ..VerifierConfig::DEFAULT };
+        let adapter: A = with_verifier_config(adapter, within_window);
+        adapter.verify_vp(&vp_jwt)?;
+
+        let before_window = VerifierConfig { verification_time: Some(900_000), ..VerifierConfig::DEFAULT };
+        let adapter: A = with_verifier_config(adapter, before_window);
+        assert!(adapter.verify_vp(&vp_jwt).is_err(), "[{}] VP was verified before its window.", adapter.sd_algorithm());
+
+        Ok(())
+    }
+
+    /// Issues a VC expiring at a given time, then checks that the adapter rejects it, and its VPs, once the holder
+    /// extends its expiration.
+    fn check_extended_expiration<A: Adapter>(adapter: A) -> Result<(), String> {
+        let mut raw_vc: Map<String, Value> = match serde_json::from_str::<Map<String, Value>>(VC) {
+            Ok(vc) => { vc }
+            Err(err) => { return Err(format!("Failed to parse Raw Verifiable Credential from string. [{err}]")); }
+        };
+        raw_vc.insert(EXPIRATION.to_string(), Value::from(2_000_000_000u64));
+        let (vc, _vc_jwt) = adapter.issue_vc(&raw_vc)?;
+        adapter.verify_vc(&vc)?;
+
+        let mut extended_vc: Map<String, Value> = vc.clone();
+        extended_vc.insert(EXPIRATION.to_string(), Value::from(3_000_000_000u64));
+        assert!(adapter.verify_vc(&extended_vc).is_err(), "[{}] VC with an extended expiration was verified.", adapter.sd_algorithm());
+        let (_vp, vp_jwt) = adapter.issue_vp(&extended_vc, &vec!["name".to_string()])?;
+        assert!(adapter.verify_vp(&vp_jwt).is_err(), "[{}] VP of a VC with an extended expiration was verified.", adapter.sd_algorithm());
+
+        Ok(())
+    }
+
+    /// Derives several VPs at once, then checks that each one verifies and discloses exactly its set of claims, and that
+    /// a batch with a set naming an unknown claim is refused.
+    fn check_batch_presentations<A: Adapter>(adapter: A) -> Result<(), String> {
+        let raw_vc: Map<String, Value> = match serde_json::from_str::<Map<String, Value>>(VC) {
+            Ok(vc) => { vc }
+            Err(err) => { return Err(format!("Failed to parse Raw Verifiable Credential from string. [{err}]")); }
+        };
+        let (vc, _vc_jwt) = adapter.issue_vc(&raw_vc)?;
+        let disclosure_sets: Vec<Vec<String>> = vec![
+            vec!["name".to_string()],
+            vec!["birthdate".to_string(), "field".to_string()],
+            vec!["name".to_string(), "birthdate".to_string(), "field".to_string()],
+        ];
+
+        let vps = adapter.issue_vp_batch(&vc, &disclosure_sets)?;
+        assert_eq!(vps.len(), disclosure_sets.len(), "[{}] Batch derived {} VPs instead of {}.", adapter.sd_algorithm(), vps.len(), disclosure_sets.len());
+        for ((vp, vp_jwt), disclosures) in vps.iter().zip(&disclosure_sets) {
+            adapter.verify_vp(vp_jwt)?;
+            diff_claims(&adapter, &vc, vp)?.check_disclosures(disclosures)?;
+        }
+
+        assert!(adapter.issue_vp_batch(&vc, &[]).is_ok_and(|vps| vps.is_empty()), "[{}] Empty batch derived VPs.", adapter.sd_algorithm());
+        let unknown_claim: Vec<Vec<String>> = vec![vec!["name".to_string()], vec!["unknown claim".to_string()]];
+        assert!(adapter.issue_vp_batch(&vc, &unknown_claim).is_err(), "[{}] Batch disclosing an unknown claim was derived.", adapter.sd_algorithm());
+
+        Ok(())
+    }
+
+    /// Checks that every string found in proof artifacts, outside of the signed fields, is encoded in base64url.
+    fn check_base64url_artifacts(algorithm: &str, path: &str, value: &Value) -> Result<(), String> {
+        match value {
+            Value::String(encoded) => match Encoding::Base64UrlNoPad.decode(encoded) {
+                Ok(_) => { Ok(()) }
+                Err(err) => { Err(format!("[{algorithm}] Artifact {path} is not encoded in base64url: [{err}]")) }
+            },
+            Value::Array(array) => { array.iter().try_for_each(|element| check_base64url_artifacts(algorithm, &format!("{path}[]"), element)) }
+            Value::Object(map) => { map.iter().try_for_each(|(name, member)| check_base64url_artifacts(algorithm, &format!("{path}.{name}"), member)) }
+            _ => { Ok(()) }
+        }
+    }
+
+    /// Extracts the proof artifacts of a VC and of a VP, then checks that they are encoded in base64url, carry the
+    /// signed fields of the credential and cannot be extracted from a credential without its components.
+    fn check_proof_artifacts<A: Adapter>(adapter: A) -> Result<(), String> {
+        let mut raw_vc: Map<String, Value> = match serde_json::from_str::<Map<String, Value>>(VC) {
+            Ok(vc) => { vc }
+            Err(err) => { return Err(format!("Failed to parse Raw Verifiable Credential from string. [{err}]")); }
+        };
+        raw_vc.insert(EXPIRATION.to_string(), Value::from(2_000_000_000u64));
+        let (vc, _vc_jwt) = adapter.issue_vc(&raw_vc)?;
+        let (vp, _vp_jwt) = adapter.issue_vp(&vc, &vec!["name".to_string()])?;
+
+        for credential in [&vc, &vp] {
+            let mut artifacts: Map<String, Value> = adapter.proof_artifacts(credential)?;
+            let signed_fields: Option<Value> = artifacts.remove(SIGNED_FIELDS);
+            assert_eq!(signed_fields.as_ref().and_then(|fields| fields.get(EXPIRATION)), Some(&Value::from(2_000_000_000u64)), "[{}] Artifacts do not carry the signed fields.", adapter.sd_algorithm());
+            assert!(!artifacts.is_empty(), "[{}] Artifacts are empty.", adapter.sd_algorithm());
+            check_base64url_artifacts(&adapter.sd_algorithm(), "artifacts", &Value::Object(artifacts))?;
+        }
+
+        assert!(adapter.proof_artifacts(&raw_vc).is_err(), "[{}] Artifacts were extracted from a raw VC.", adapter.sd_algorithm());
+
+        Ok(())
+    }
+
+    #[test]
+    fn runtime_limits_apply_to_every_adapter() -> Result<(), String> {
+        check_runtime_limits(SdJwtAdapter::new(16)?, SdJwtAdapter::with_verifier_config)?;
+        check_runtime_limits(MerkleTreeAdapter::new(16)?, MerkleTreeAdapter::with_verifier_config)?;
+        check_runtime_limits(BBSPlusAdapter::new(16)?, BBSPlusAdapter::with_verifier_config)?;
+        check_runtime_limits(CsdJwtAdapter::new(16)?, CsdJwtAdapter::with_verifier_config)?;
+        check_runtime_limits(CsdJwtMultiShowAdapter::new(16)?, CsdJwtMultiShowAdapter::with_verifier_config)?;
+        check_runtime_limits(CsdJwtAggregatedAdapter::new(16)?, CsdJwtAggregatedAdapter::with_verifier_config)?;
+        check_runtime_limits(CsdJwtBls12381Adapter::new(16)?, CsdJwtBls12381Adapter::with_verifier_config)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn expired_presentations_are_rejected_by_every_adapter() -> Result<(), String> {
+        check_expired_presentation(SdJwtAdapter::new(16)?, SdJwtAdapter::with_verifier_config)?;
+        check_expired_presentation(MerkleTreeAdapter::new(16)?, MerkleTreeAdapter::with_verifier_config)?;
+        check_expired_presentation(BBSPlusAdapter::new(16)?, BBSPlusAdapter::with_verifier_config)?;
+        check_expired_presentation(CsdJwtAdapter::new(16)?, CsdJwtAdapter::with_verifier_config)?;
+        check_expired_presentation(CsdJwtMultiShowAdapter::new(16)?, CsdJwtMultiShowAdapter::with_verifier_config)?;
+        check_expired_presentation(CsdJwtAggregatedAdapter::new(16)?, CsdJwtAggregatedAdapter::with_verifier_config)?;
+        check_expired_presentation(CsdJwtBls12381Adapter::new(16)?, CsdJwtBls12381Adapter::with_verifier_config)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn extended_expiration_is_rejected_by_every_adapter() -> Result<(), String> {
+        check_extended_expiration(SdJwtAdapter::new(16)?)?;
+        check_extended_expiration(MerkleTreeAdapter::new(16)?)?;
+        check_extended_expiration(BBSPlusAdapter::new(16)?)?;
+        check_extended_expiration(CsdJwtAdapter::new(16)?)?;
+        check_extended_expiration(CsdJwtMultiShowAdapter::new(16)?)?;
+        check_extended_expiration(CsdJwtAggregatedAdapter::new(16)?)?;
+        check_extended_expiration(CsdJwtBls12381Adapter::new(16)?)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn batch_presentations_of_every_adapter() -> Result<(), String> {
+        check_batch_presentations(SdJwtAdapter::new(16)?)?;
+        check_batch_presentations(MerkleTreeAdapter::new(16)?)?;
+        check_batch_presentations(BBSPlusAdapter::new(16)?)?;
+        check_batch_presentations(CsdJwtAdapter::new(16)?)?;
+        check_batch_presentations(CsdJwtMultiShowAdapter::new(16)?)?;
+        check_batch_presentations(CsdJwtAggregatedAdapter::new(16)?)?;
+        check_batch_presentations(CsdJwtBls12381Adapter::new(16)?)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn proof_artifacts_of_every_adapter() -> Result<(), String> {
+        check_proof_artifacts(SdJwtAdapter::new(16)?)?;
+        check_proof_artifacts(MerkleTreeAdapter::new(16)?)?;
+        check_proof_artifacts(BBSPlusAdapter::new(16)?)?;
+        check_proof_artifacts(CsdJwtAdapter::new(16)?)?;
+        check_proof_artifacts(CsdJwtMultiShowAdapter::new(16)?)?;
+        check_proof_artifacts(CsdJwtAggregatedAdapter::new(16)?)?;
+        check_proof_artifacts(CsdJwtBls12381Adapter::new(16)?)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn verifiers_from_bundles() -> Result<(), String> {
+        let raw_vc: Map<String, Value> = match serde_json::from_str::<Map<String, Value>>(VC) {
+            Ok(vc) => { vc }
+            Err(err) => { return Err(format!("Failed to parse Raw Verifiable Credential from string. [{err}]")); }
+        };
+        let issuer = CsdJwtAdapter::new(16)?;
+        let (vc, _vc_jwt) = issuer.issue_vc(&raw_vc)?;
+        let (_vp, vp_jwt) = issuer.issue_vp(&vc, &vec!["name".to_string()])?;
+
+        let (holder_public_key, _) = CommonData::holder_keys()?;
+        let bundle: VerifierBundle = VerifierBundle::from_cbor(&issuer.verifier_bundle()?.to_cbor()?)?;
+        let verifier: Box<dyn VerifierAdapter> = CsdJwtAdapter::verifier_from_bundle(bundle, holder_public_key.clone())?;
+        assert_eq!(verifier.sd_algorithm(), issuer.sd_algorithm());
+        assert_eq!(verifier.verifier_state_length()?, issuer.verifier_state_length()?);
+        verifier.verify_vc(&vc)?;
+        verifier.verify_vp(&vp_jwt)?;
+
+        // The verifier only accepts presentations of the issuer of the bundle, by the holder it was built for.
+        let other_issuer = CsdJwtAdapter::new(16)?;
+        let (other_vc, _) = other_issuer.issue_vc(&raw_vc)?;
+        let (_, other_vp_jwt) = other_issuer.issue_vp(&other_vc, &vec!["name".to_string()])?;
+        assert!(verifier.verify_vc(&other_vc).is_err());
+        assert!(verifier.verify_vp(&other_vp_jwt).is_err());
+        let (other_holder_public_key, _) = CommonData::issuer_keys()?;
+        let verifier: Box<dyn VerifierAdapter> = CsdJwtAdapter::verifier_from_bundle(issuer.verifier_bundle()?, other_holder_public_key)?;
+        assert!(verifier.verify_vp(&vp_jwt).is_err());
+
+        // Algorithms without verifier bundles say so instead of building a verifier.
+        let bundle: VerifierBundle = issuer.verifier_bundle()?;
+        assert!(SdJwtAdapter::new(16)?.verifier_bundle().is_err());
+        assert!(MerkleTreeAdapter::new(16)?.verifier_bundle().is_err());
+        assert!(BBSPlusAdapter::new(16)?.verifier_bundle().is_err());
+        assert!(SdJwtAdapter::verifier_from_bundle(bundle.clone(), holder_public_key.clone()).is_err());
+        assert!(MerkleTreeAdapter::verifier_from_bundle(bundle.clone(), holder_public_key.clone()).is_err());
+        assert!(BBSPlusAdapter::verifier_from_bundle(bundle, holder_public_key).is_err());
+
+        Ok(())
+    }
 }
@@ -1,48 +1,140 @@
+use std::sync::Arc;
 use serde_json::{Map, Value};
 use crate::common_data::CommonData;
-use crate::adapters::adapter::Adapter;
+use crate::adapters::adapter::{Adapter, VerifierAdapter};
+use crate::derived_claims::DerivedClaims;
+use crate::holder_binding::{Es256Binding, HolderBinding};
+use crate::presentation_validity::PresentationValidity;
+use crate::keys::jwks::es256_jwk;
 use crate::sd_algorithms::hashes::sd_jwt::SdJwtInstance;
-use crate::sd_algorithms::sd_algorithm::SdAlgorithm;
+use crate::sd_algorithms::sd_algorithm::{PresentationBatch, SdAlgorithm};
+use crate::size_estimate::{SizeEstimate, SizeModel};
+use crate::verifier_config::VerifierConfig;
 
 pub struct SdJwtAdapter {
-    holder_public_key: Vec<u8>,
-    holder_private_key: Vec<u8>,
+    holder_binding: Arc<dyn HolderBinding>,
     issuer_public_key: Vec<u8>,
     issuer_private_key: Vec<u8>,
+    derived_claims: Option<DerivedClaims>,
+    verifier_config: VerifierConfig,
 }
 
-impl Adapter for SdJwtAdapter {
+impl SdJwtAdapter {
+
+    /// Replaces the default ES256 holder binding of the VCs and VPs, e.g. to measure the presentations without any proof
+    /// of possession.
+    ///
+    /// # Arguments
+    /// * `holder_binding` - Holder binding of the issued VCs and VPs.
+    ///
+    /// # Returns
+    /// Returns the adapter using the holder binding.
+    pub fn with_holder_binding(mut self, holder_binding: Arc<dyn HolderBinding>) -> Self {
+        self.holder_binding = holder_binding;
+        self
+    }
+
+    /// Adds claims derived from the source claims (e.g. `age_over_18` from `birthdate`) to every issued VC, each of them
+    /// individually disclosable.
+    ///
+    /// # Arguments
+    /// * `derived_claims` - Derivations applied at issuance.
+    ///
+    /// # Returns
+    /// Returns the adapter deriving the claims.
+    pub fn with_derived_claims(mut self, derived_claims: DerivedClaims) -> Self {
+        self.derived_claims = Some(derived_claims);
+        self
+    }
+
+    /// Sets the limits enforced on the VCs and VPs verified by the adapter, e.g. a lower maximum amount of claims.
+    ///
+    /// # Arguments
+    /// * `verifier_config` - Limits of the verifier.
+    ///
+    /// # Returns
+    /// Returns the adapter enforcing the limits.
+    pub fn with_verifier_config(mut self, verifier_config: VerifierConfig) -> Self {
+        self.verifier_config = verifier_config;
+        self
+    }
+
+    /// Binds a raw VC to the holder and adds the derived claims, if any.
+    fn prepare_raw_vc(&self, raw_vc: &Map<String, Value>) -> Result<Map<String, Value>, String> {
+        let mut raw_vc = self.holder_binding.bind_credential(raw_vc)?;
+        if let Some(derived_claims) = &self.derived_claims {
+            derived_claims.insert_into(&mut raw_vc)?;
+        }
+        Ok(raw_vc)
+    }
+}
+
+impl VerifierAdapter for SdJwtAdapter {
 
     fn sd_algorithm(&self) -> String {
         SdJwtInstance::ALGORITHM.to_string()
     }
 
+    fn verify_vc(&self, vc: &Map<String, Value>) -> Result<(), String> {
+        self.log_timed("VC verification", || SdJwtInstance::verify_vc_with_config(vc, &self.issuer_public_key, &self.verifier_config))
+    }
+
+    fn verify_vp(&self, vp_jwt: &str) -> Result<(), String> {
+        self.log_timed("VP verification", || SdJwtInstance::verify_vp_with_config(vp_jwt, &self.issuer_public_key, self.holder_binding.as_ref(), &self.verifier_config))
+    }
+
+    fn proof_artifacts(&self, vp: &Map<String, Value>) -> Result<Map<String, Value>, String> {
+        SdJwtInstance::proof_artifacts(vp)
+    }
+
+    fn claim_proofs(&self, vp: &Map<String, Value>) -> Result<Map<String, Value>, String> {
+        SdJwtInstance::claim_proofs(vp)
+    }
+
+    fn verifier_state_length(&self) -> Result<usize, String> {
+        match serde_json::to_string(&self.issuer_public_key) {
+            Ok(ipk) => { Ok(ipk.len()) }
+            Err(err) => { Err(format!("Error in serializing issuer public key: [{err}]")) }
+        }
+    }
+}
+
+impl Adapter for SdJwtAdapter {
+
     fn new(_claims_len: usize) -> Result<Self, String> {
-        let (holder_public_key, holder_private_key) = CommonData::holder_keys()?;
         let (issuer_public_key, issuer_private_key) = CommonData::issuer_keys()?;
 
         Ok(SdJwtAdapter {
-            holder_public_key,
-            holder_private_key,
+            holder_binding: Arc::new(Es256Binding::from_holder_keys()?),
             issuer_public_key,
             issuer_private_key,
+            derived_claims: None,
+            verifier_config: VerifierConfig::DEFAULT,
         })
     }
 
     fn issue_vc(&self, raw_vc: &Map<String, Value>) -> Result<(Map<String, Value>, String), String> {
-        SdJwtInstance::issue_vc(raw_vc, &self.issuer_private_key)
+        self.log_timed("VC issuance", || SdJwtInstance::issue_vc(&self.prepare_raw_vc(raw_vc)?, &self.issuer_private_key))
     }
 
-    fn verify_vc(&self, vc: &Map<String, Value>) -> Result<(), String> {
-        SdJwtInstance::verify_vc(vc, &self.issuer_public_key)
+    fn issue_vp(&self, vc: &Map<String, Value>, disclosures: &[String]) -> Result<(Map<String, Value>, String), String> {
+        self.log_timed("VP issuance", || SdJwtInstance::issue_vp(vc, disclosures, self.holder_binding.as_ref()))
+    }
+
+    fn issue_vp_for_audience(&self, vc: &Map<String, Value>, disclosures: &[String], audience: Option<String>, validity: Option<PresentationValidity>) -> Result<(Map<String, Value>, String), String> {
+        self.log_timed("VP issuance", || SdJwtInstance::issue_vp_for_audience(vc, disclosures, audience, validity, self.holder_binding.as_ref()))
     }
 
-    fn issue_vp(&self, vc: &Map<String, Value>, disclosures: &Vec<String>) -> Result<(Map<String, Value>, String), String> {
-        SdJwtInstance::issue_vp(vc, disclosures, &self.holder_private_key)
+    fn issue_vp_batch(&self, vc: &Map<String, Value>, disclosure_sets: &[Vec<String>]) -> Result<PresentationBatch, String> {
+        self.log_timed("VP batch issuance", || SdJwtInstance::issue_vp_batch(vc, disclosure_sets, self.holder_binding.as_ref()))
     }
 
-    fn verify_vp(&self, vp_jwt: &String) -> Result<(), String> {
-        SdJwtInstance::verify_vp(vp_jwt, &self.issuer_public_key, &self.holder_public_key)
+    fn holder_storage_length(&self, vc_jwt: &str) -> Result<usize, String> {
+        Ok(vc_jwt.len() + self.holder_binding.secret_length())
+    }
+
+    fn estimate_sizes(&self, raw_vc: &Map<String, Value>, disclosures: &[String]) -> Result<SizeEstimate, String> {
+        SizeModel::SdJwt.estimate(&self.prepare_raw_vc(raw_vc)?, disclosures)
     }
 
     fn issuer_keypair(&self) -> Result<(String, String), String> {
@@ -57,4 +149,8 @@ impl Adapter for SdJwtAdapter {
 
         Ok((issuer_public_key, issuer_private_key))
     }
+
+    fn issuer_jwk(&self) -> Result<Map<String, Value>, String> {
+        es256_jwk(&self.issuer_public_key, &self.sd_algorithm())
+    }
 }
\ No newline at end of file
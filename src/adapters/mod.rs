@@ -1,4 +1,4 @@
 pub mod adapter;
 pub mod hashes;
 pub mod accumulators;
-pub mod signatures;
\ No newline at end of file
+pub mod signatures;pub mod registry;
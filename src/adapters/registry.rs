@@ -0,0 +1,115 @@
+use std::collections::BTreeMap;
+use crate::adapters::accumulators::csd_jwt_adapter::CsdJwtAdapter;
+use crate::adapters::accumulators::csd_jwt_aggregated_adapter::CsdJwtAggregatedAdapter;
+use crate::adapters::accumulators::csd_jwt_bls12_381_adapter::CsdJwtBls12381Adapter;
+use crate::adapters::accumulators::csd_jwt_multi_show_adapter::CsdJwtMultiShowAdapter;
+use crate::adapters::adapter::Adapter;
+use crate::adapters::hashes::merkle_tree_adapter::MerkleTreeAdapter;
+use crate::adapters::hashes::sd_jwt_adapter::SdJwtAdapter;
+use crate::adapters::signatures::bbs_plus_adapter::BBSPlusAdapter;
+
+/// Collection of adapters addressed by the name of their algorithm, so that callers (e.g. the HTTP service) can select
+/// the algorithm at runtime.
+///
+/// # Examples
+/// ```
+/// use csd_jwt::adapters::adapter::Adapter;
+/// use csd_jwt::adapters::hashes::sd_jwt_adapter::SdJwtAdapter;
+/// use csd_jwt::adapters::registry::AdapterRegistry;
+///
+/// let mut registry = AdapterRegistry::with_all_adapters(16).unwrap();
+/// assert_eq!(registry.algorithms().len(), 7);
+/// assert!(registry.algorithms().windows(2).all(|pair| pair[0] < pair[1]));
+/// assert_eq!(registry.get("SD-JWT").map(|adapter| adapter.sd_algorithm()), Some("SD-JWT".to_string()));
+/// assert!(registry.get("unknown").is_none());
+///
+/// // Every algorithm is served by a single adapter.
+/// assert!(registry.register(Box::new(SdJwtAdapter::new(16).unwrap())).is_err());
+/// ```
+pub struct AdapterRegistry {
+    /// Adapters, keyed by the name of their algorithm.
+    adapters: BTreeMap<String, Box<dyn Adapter>>,
+    /// Whether the reasons of failed verifications are hidden from the callers behind a uniform error.
+    uniform_errors: bool,
+}
+
+impl AdapterRegistry {
+
+    /// Constructor for an empty registry.
+    ///
+    /// # Returns
+    /// A registry without any adapter.
+    pub fn new() -> Self {
+        AdapterRegistry { adapters: BTreeMap::new(), uniform_errors: false }
+    }
+
+    /// Creates a registry holding an adapter for every algorithm of the crate.
+    ///
+    /// # Arguments
+    /// * `claims_len` - Maximum amount of claims of the credentials handled by the adapters.
+    ///
+    /// # Returns
+    /// A result containing the registry or a string containing an error if an adapter cannot be created.
+    pub fn with_all_adapters(claims_len: usize) -> Result<Self, String> {
+        let mut registry = Self::new();
+        registry.register(Box::new(SdJwtAdapter::new(claims_len)?))?;
+        registry.register(Box::new(CsdJwtAdapter::new(claims_len)?))?;
+        registry.register(Box::new(MerkleTreeAdapter::new(claims_len)?))?;
+        registry.register(Box::new(BBSPlusAdapter::new(claims_len)?))?;
+        registry.register(Box::new(CsdJwtMultiShowAdapter::new(claims_len)?))?;
+        registry.register(Box::new(CsdJwtAggregatedAdapter::new(claims_len)?))?;
+        registry.register(Box::new(CsdJwtBls12381Adapter::new(claims_len)?))?;
+
+        Ok(registry)
+    }
+
+    /// Adds an adapter to the registry.
+    ///
+    /// # Arguments
+    /// * `adapter` - Adapter to be added.
+    ///
+    /// # Returns
+    /// A result containing a string in case an adapter for the same algorithm is already registered.
+    pub fn register(&mut self, adapter: Box<dyn Adapter>) -> Result<(), String> {
+        let algorithm = adapter.sd_algorithm();
+        if self.adapters.contains_key(&algorithm) {
+            return Err(format!("An adapter for {algorithm} is already registered"));
+        }
+
+        self.adapters.insert(algorithm, adapter);
+        Ok(())
+    }
+
+    /// Retrieves the adapter of an algorithm.
+    ///
+    /// # Arguments
+    /// * `algorithm` - Name of the algorithm.
+    ///
+    /// # Returns
+    /// The adapter, or None if no adapter is registered for the algorithm.
+    pub fn get(&self, algorithm: &str) -> Option<&dyn Adapter> {
+        self.adapters.get(algorithm).map(|adapter| adapter.as_ref())
+    }
+
+    /// Hides the reasons of failed verifications from the callers behind a uniform error, logging them internally.
+    pub fn with_uniform_errors(mut self) -> Self {
+        self.uniform_errors = true;
+        self
+    }
+
+    /// Whether the reasons of failed verifications are hidden from the callers.
+    pub fn uniform_errors(&self) -> bool {
+        self.uniform_errors
+    }
+
+    /// Names of the registered algorithms, in alphabetical order.
+    pub fn algorithms(&self) -> Vec<String> {
+        self.adapters.keys().cloned().collect()
+    }
+}
+
+impl Default for AdapterRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
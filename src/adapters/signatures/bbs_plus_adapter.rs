@@ -1,31 +1,100 @@
-use rand::rngs::StdRng;
-use rand::{Rng, SeedableRng};
+use std::sync::Arc;
+use rand::Rng;
 use serde_json::{Map, Value};
 use zkryptium::bbsplus::ciphersuites::{BbsCiphersuite, Bls12381Sha256};
 use zkryptium::bbsplus::keys::{BBSplusPublicKey, BBSplusSecretKey};
 use zkryptium::keys::pair::KeyPair;
 use zkryptium::schemes::algorithms::BBSplus;
-use crate::common_data::CommonData;
-use crate::adapters::adapter::Adapter;
-use crate::sd_algorithms::sd_algorithm::SdAlgorithm;
-use crate::sd_algorithms::signatures::bbs_plus::BBSPlusInstance;
+use crate::deterministic;
+use crate::adapters::adapter::{Adapter, VerifierAdapter};
+use crate::holder_binding::{Es256Binding, HolderBinding};
+use crate::presentation_validity::PresentationValidity;
+use crate::keys::jwks::bbs_jwk;
+use crate::sd_algorithms::sd_algorithm::{PresentationBatch, SdAlgorithm};
+use crate::sd_algorithms::signatures::bbs_plus::{BBSPlusInstance, PreparedPresentation};
+use crate::size_estimate::{SizeEstimate, SizeModel};
+use crate::verifier_config::VerifierConfig;
 
 pub struct BBSPlusAdapter {
-    holder_public_key: Vec<u8>,
-    holder_private_key: Vec<u8>,
+    holder_binding: Arc<dyn HolderBinding>,
     issuer_public_key: BBSplusPublicKey,
     issuer_private_key: BBSplusSecretKey,
+    verifier_config: VerifierConfig,
 }
 
-impl Adapter for BBSPlusAdapter {
+impl BBSPlusAdapter {
+
+    /// Replaces the default ES256 holder binding of the VCs and VPs, e.g. to measure the presentations without any proof
+    /// of possession.
+    ///
+    /// # Arguments
+    /// * `holder_binding` - Holder binding of the issued VCs and VPs.
+    ///
+    /// # Returns
+    /// Returns the adapter using the holder binding.
+    pub fn with_holder_binding(mut self, holder_binding: Arc<dyn HolderBinding>) -> Self {
+        self.holder_binding = holder_binding;
+        self
+    }
+
+    /// Prepares the presentations of a VC ahead of the verifier's request, see `BBSPlusInstance::prepare_presentation`.
+    pub fn prepare_presentation(&self, vc: &Map<String, Value>) -> Result<PreparedPresentation, String> {
+        self.log_timed("VP preparation", || BBSPlusInstance::prepare_presentation(vc))
+    }
+
+    /// Issues a VP from a prepared presentation, bound to the verifier's nonce, see `BBSPlusInstance::issue_prepared_vp`.
+    pub fn issue_prepared_vp(&self, prepared: &PreparedPresentation, disclosures: &[String], nonce: &[u8]) -> Result<(Map<String, Value>, String), String> {
+        self.log_timed("Prepared VP issuance", || BBSPlusInstance::issue_prepared_vp(prepared, disclosures, nonce, None, None, &self.issuer_public_key, self.holder_binding.as_ref()))
+    }
+
+    /// Sets the limits enforced on the VCs and VPs verified by the adapter, e.g. a lower maximum amount of claims.
+    ///
+    /// # Arguments
+    /// * `verifier_config` - Limits of the verifier.
+    ///
+    /// # Returns
+    /// Returns the adapter enforcing the limits.
+    pub fn with_verifier_config(mut self, verifier_config: VerifierConfig) -> Self {
+        self.verifier_config = verifier_config;
+        self
+    }
+}
+
+impl VerifierAdapter for BBSPlusAdapter {
 
     fn sd_algorithm(&self) -> String {
         BBSPlusInstance::ALGORITHM.to_string()
     }
 
+    fn verify_vc(&self, vc: &Map<String, Value>) -> Result<(), String> {
+        self.log_timed("VC verification", || BBSPlusInstance::verify_vc_with_config(vc, &self.issuer_public_key, &self.verifier_config))
+    }
+
+    fn verify_vp(&self, vp_jwt: &str) -> Result<(), String> {
+        self.log_timed("VP verification", || BBSPlusInstance::verify_vp_with_config(vp_jwt, &self.issuer_public_key, self.holder_binding.as_ref(), &self.verifier_config))
+    }
+
+    fn proof_artifacts(&self, vp: &Map<String, Value>) -> Result<Map<String, Value>, String> {
+        BBSPlusInstance::proof_artifacts(vp)
+    }
+
+    fn claim_proofs(&self, vp: &Map<String, Value>) -> Result<Map<String, Value>, String> {
+        BBSPlusInstance::claim_proofs(vp)
+    }
+
+    fn verifier_state_length(&self) -> Result<usize, String> {
+        match serde_json::to_string(&self.issuer_public_key) {
+            Ok(ipk) => { Ok(ipk.len()) }
+            Err(err) => { Err(format!("Error in serializing issuer public key: [{err}]")) }
+        }
+    }
+}
+
+impl Adapter for BBSPlusAdapter {
+
     fn new(_claims_len: usize) -> Result<Self, String> {
 
-        let mut rng = StdRng::from_os_rng();
+        let mut rng = deterministic::rng();
         let key_material: Vec<u8> = (0..Bls12381Sha256::IKM_LEN).map(|_| rng.random()).collect();
 
         let issuer_keypair = match KeyPair::<BBSplus<Bls12381Sha256>>::generate(&key_material, None, None) {
@@ -33,34 +102,46 @@ impl Adapter for BBSPlusAdapter {
             Err(err) => { return Err(format!("Error in issuing BBS+ keypair [{err}]")) }
         };
 
-        let (holder_public_key, holder_private_key) = CommonData::holder_keys()?;
         let (issuer_public_key, issuer_private_key) = (
             issuer_keypair.public_key().clone(),
             issuer_keypair.private_key().clone()
         );
 
         Ok(BBSPlusAdapter {
-            holder_public_key,
-            holder_private_key,
+            holder_binding: Arc::new(Es256Binding::from_holder_keys()?),
             issuer_public_key,
             issuer_private_key,
+            verifier_config: VerifierConfig::DEFAULT,
         })
     }
 
     fn issue_vc(&self, raw_vc: &Map<String, Value>) -> Result<(Map<String, Value>, String), String> {
-        BBSPlusInstance::issue_vc(raw_vc, &self.issuer_public_key, &self.issuer_private_key)
+        self.log_timed("VC issuance", || BBSPlusInstance::issue_vc(&self.holder_binding.bind_credential(raw_vc)?, &self.issuer_public_key, &self.issuer_private_key))
     }
 
-    fn verify_vc(&self, vc: &Map<String, Value>) -> Result<(), String> {
-        BBSPlusInstance::verify_vc(vc, &self.issuer_public_key)
+    fn issue_vp(&self, vc: &Map<String, Value>, disclosures: &[String]) -> Result<(Map<String, Value>, String), String> {
+        self.log_timed("VP issuance", || BBSPlusInstance::issue_vp(vc, disclosures, &self.issuer_public_key, self.holder_binding.as_ref()))
     }
 
-    fn issue_vp(&self, vc: &Map<String, Value>, disclosures: &Vec<String>) -> Result<(Map<String, Value>, String), String> {
-        BBSPlusInstance::issue_vp(vc, disclosures, &self.issuer_public_key, &self.holder_private_key)
+    fn issue_vp_for_audience(&self, vc: &Map<String, Value>, disclosures: &[String], audience: Option<String>, validity: Option<PresentationValidity>) -> Result<(Map<String, Value>, String), String> {
+        self.log_timed("VP issuance", || BBSPlusInstance::issue_vp_for_audience(vc, disclosures, audience, validity, &self.issuer_public_key, self.holder_binding.as_ref()))
     }
 
-    fn verify_vp(&self, vp_jwt: &String) -> Result<(), String> {
-        BBSPlusInstance::verify_vp(vp_jwt, &self.issuer_public_key, &self.holder_public_key)
+    fn issue_vp_batch(&self, vc: &Map<String, Value>, disclosure_sets: &[Vec<String>]) -> Result<PresentationBatch, String> {
+        self.log_timed("VP batch issuance", || BBSPlusInstance::issue_vp_batch(vc, disclosure_sets, &self.issuer_public_key, self.holder_binding.as_ref()))
+    }
+
+    fn holder_storage_length(&self, vc_jwt: &str) -> Result<usize, String> {
+        // Proofs are generated against the issuer's public key, which the holder keeps along with the VC.
+        let issuer_public_key = match serde_json::to_string(&self.issuer_public_key) {
+            Ok(ipk) => { ipk }
+            Err(err) => { return Err(format!("Error in serializing issuer public key: [{err}]")) }
+        };
+        Ok(vc_jwt.len() + self.holder_binding.secret_length() + issuer_public_key.len())
+    }
+
+    fn estimate_sizes(&self, raw_vc: &Map<String, Value>, disclosures: &[String]) -> Result<SizeEstimate, String> {
+        SizeModel::BbsPlus.estimate(&self.holder_binding.bind_credential(raw_vc)?, disclosures)
     }
 
     fn issuer_keypair(&self) -> Result<(String, String), String> {
@@ -75,4 +156,8 @@ impl Adapter for BBSPlusAdapter {
 
         Ok((issuer_public_key, issuer_private_key))
     }
+
+    fn issuer_jwk(&self) -> Result<Map<String, Value>, String> {
+        bbs_jwk(&self.issuer_public_key, &self.sd_algorithm())
+    }
 }
\ No newline at end of file
@@ -0,0 +1,229 @@
+use serde_json::{Map, Value};
+use crate::adapters::adapter::VerifierAdapter;
+
+/// Field of an audit document naming the algorithm of the presentation.
+pub const ALGORITHM: &str = "algorithm";
+/// Field of an audit document holding the material shared by every claim, e.g. the issuer signature.
+pub const SHARED: &str = "shared";
+/// Field of an audit document holding the proof of every disclosed claim, by claim name.
+pub const CLAIM_PROOFS: &str = "claims";
+/// Field of a claim proof holding the disclosed value.
+pub const VALUE: &str = "value";
+/// Field of the shared material holding the signed fields of the credential, e.g. its `cnf` claim.
+pub const SIGNED_FIELDS: &str = "signed_fields";
+
+/// Shared material and claim proofs of an audit document.
+pub type ClaimProofs<'a> = (&'a Map<String, Value>, &'a Map<String, Value>);
+
+
+/// Builds the audit document of a VP: the minimal proof of every disclosed claim, separated from the presentation
+/// envelope, the holder binding and the proofs of the other claims, so that an auditor can re-verify a single
+/// disclosure without the machinery verifying whole presentations. Binary values are encoded in base64url.
+///
+/// The document has the following structure:
+/// ```json
+/// {
+///   "algorithm": "SD-JWT",
+///   "shared": { "issuer_signature": "...", "hashes": ["..."] },
+///   "claims": { "name": { "value": "Albert Einstein", "salt": "...", "digest": "..." } }
+/// }
+/// ```
+///
+/// The material shared by all the claims and the fields of each claim proof depend on the algorithm:
+/// * `SD-JWT` - shared `issuer_signature` and `hashes`, signed by the issuer; per claim the `salt` and the `digest`
+///   found in `hashes`.
+/// * `Merkle Tree` - shared `root`, `root_signature`, `leaves_len`, and the multi-proof `proof` with its
+///   `disclosed_indices` and `leaves`; per claim the `salt`, the leaf `index` and the `leaf`. The multi-proof covers all
+///   the disclosed leaves at once, so it is checked with all of them.
+/// * `BBS+` - shared `proof`, `nonce` and `disclosed_indices` for presentations, or `signature` for credentials; per
+///   claim the signed message `index` and the `message`. The proof of knowledge covers all the disclosed messages.
+/// * `CSD-JWT` and its variants - shared `accumulator`, and `blinding` for blinded credentials; per claim the accumulated
+///   `element` and the membership `witness`, or the zero-knowledge `membership_proof` and the `element_blinding` of
+///   multi-show presentations. The shared `aggregated_witness` of aggregated presentations covers all the disclosed
+///   claims.
+///
+/// Every algorithm also shares the `signed_fields` of the credential, e.g. its `cnf` claim, which the issuer commitment
+/// is bound to, so that they are checked together with the claims.
+///
+/// # Arguments
+/// * `verifier` - Verifier of the algorithm of the VP.
+/// * `vp` - Verifiable Presentation, or Verifiable Credential.
+///
+/// # Returns
+/// A result containing the audit document or a string containing an error.
+///
+/// # Examples
+/// ```
+/// use csd_jwt::adapters::adapter::{Adapter, VerifierAdapter};
+/// use csd_jwt::adapters::hashes::sd_jwt_adapter::SdJwtAdapter;
+/// use csd_jwt::audit::{audit_document, claim_proof};
+/// use csd_jwt::common_data::VC;
+/// use csd_jwt::sd_algorithms::hashes::sd_jwt::SdJwtInstance;
+/// use csd_jwt::verifier_config::VerifierConfig;
+///
+/// let adapter = SdJwtAdapter::new(13).unwrap();
+/// let (vc, _) = adapter.issue_vc(&serde_json::from_str(VC).unwrap()).unwrap();
+/// let (vp, _) = adapter.issue_vp(&vc, &vec!["name".to_string(), "field".to_string()]).unwrap();
+///
+/// let document = audit_document(&adapter, &vp).unwrap();
+/// let proof = claim_proof(&document, "name").unwrap();
+/// let issuer_public_key: Vec<u8> = serde_json::from_str(&adapter.issuer_keypair().unwrap().0).unwrap();
+/// SdJwtInstance::verify_claim_proofs(&proof, &issuer_public_key, &VerifierConfig::DEFAULT).unwrap();
+/// assert!(claim_proof(&document, "birthdate").is_err());
+/// ```
+pub fn audit_document(verifier: &dyn VerifierAdapter, vp: &Map<String, Value>) -> Result<Map<String, Value>, String> {
+    let mut document: Map<String, Value> = Map::new();
+    document.insert(ALGORITHM.to_string(), Value::String(verifier.sd_algorithm()));
+    document.extend(verifier.claim_proofs(vp)?);
+    Ok(document)
+}
+
+/// Restricts an audit document to the proof of a single claim, keeping the shared material it is checked against.
+///
+/// # Arguments
+/// * `document` - Audit document, as built by `audit_document`.
+/// * `name` - Name of the claim.
+///
+/// # Returns
+/// A result containing the audit document of the claim or a string containing an error if the claim is not disclosed.
+pub fn claim_proof(document: &Map<String, Value>, name: &str) -> Result<Map<String, Value>, String> {
+    let proof: &Value = match document.get(CLAIM_PROOFS).and_then(|claims| claims.get(name)) {
+        Some(proof) => { proof }
+        None => { return Err(format!("Claim {name} is not disclosed in the audit document")) }
+    };
+
+    let mut restricted: Map<String, Value> = document.clone();
+    restricted.insert(CLAIM_PROOFS.to_string(), Value::Object(Map::from_iter([(name.to_string(), proof.clone())])));
+    Ok(restricted)
+}
+
+/// Assembles the material shared by every claim and the proofs of the claims into the body of an audit document.
+///
+/// # Arguments
+/// * `shared` - Material shared by every claim.
+/// * `claims` - Proof of every disclosed claim, by claim name.
+///
+/// # Returns
+/// The body of the audit document, without the algorithm.
+pub fn claim_proofs_document(shared: Map<String, Value>, claims: Map<String, Value>) -> Map<String, Value> {
+    let mut document: Map<String, Value> = Map::new();
+    document.insert(SHARED.to_string(), Value::Object(shared));
+    document.insert(CLAIM_PROOFS.to_string(), Value::Object(claims));
+    document
+}
+
+/// Splits the body of an audit document into the material shared by every claim and the proofs of the claims.
+///
+/// # Arguments
+/// * `document` - Audit document.
+///
+/// # Returns
+/// A result containing the shared material and the claim proofs or a string containing an error if they are missing.
+pub fn split_claim_proofs(document: &Map<String, Value>) -> Result<ClaimProofs<'_>, String> {
+    match (document.get(SHARED), document.get(CLAIM_PROOFS)) {
+        (Some(Value::Object(shared)), Some(Value::Object(claims))) => { Ok((shared, claims)) }
+        _ => { Err(format!("Audit document is missing the {SHARED} or {CLAIM_PROOFS} object")) }
+    }
+}
+
+/// Retrieves the signed fields of the credential from the material shared by every claim of an audit document.
+///
+/// # Arguments
+/// * `shared` - Material shared by every claim.
+///
+/// # Returns
+/// A result containing the signed fields, empty if the credential has none, or a string containing an error if they
+/// are malformed.
+pub fn shared_signed_fields(shared: &Map<String, Value>) -> Result<Map<String, Value>, String> {
+    match shared.get(SIGNED_FIELDS) {
+        None => { Ok(Map::new()) }
+        Some(Value::Object(signed_fields)) => { Ok(signed_fields.clone()) }
+        Some(_) => { Err(format!("Field {SIGNED_FIELDS} of the audit document is not an object")) }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Map, Value};
+    use crate::adapters::adapter::{Adapter, VerifierAdapter};
+    use crate::adapters::hashes::sd_jwt_adapter::SdJwtAdapter;
+    use crate::audit::{audit_document, claim_proof, claim_proofs_document, shared_signed_fields, split_claim_proofs, ALGORITHM, CLAIM_PROOFS, SHARED, SIGNED_FIELDS, VALUE};
+    use crate::common_data::{EXPIRATION, VC};
+    use crate::sd_algorithms::hashes::sd_jwt::SdJwtInstance;
+    use crate::verifier_config::VerifierConfig;
+
+    #[test]
+    fn claim_proof_keeps_shared_material() -> Result<(), String> {
+        let shared: Map<String, Value> = Map::from_iter([("issuer_signature".to_string(), Value::String("signature".to_string()))]);
+        let claims: Map<String, Value> = Map::from_iter([
+            ("name".to_string(), Value::Object(Map::new())),
+            ("field".to_string(), Value::Object(Map::new())),
+        ]);
+        let document: Map<String, Value> = claim_proofs_document(shared.clone(), claims);
+
+        let proof: Map<String, Value> = claim_proof(&document, "name")?;
+        let (proof_shared, proof_claims) = split_claim_proofs(&proof)?;
+        assert_eq!(proof_shared, &shared);
+        assert_eq!(proof_claims.keys().collect::<Vec<_>>(), vec!["name"]);
+        assert!(claim_proof(&document, "birthdate").is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn audit_sd_jwt_presentation() -> Result<(), String> {
+        let adapter = SdJwtAdapter::new(13)?;
+        let mut raw_vc: Map<String, Value> = match serde_json::from_str(VC) {
+            Ok(raw_vc) => { raw_vc }
+            Err(err) => { return Err(format!("Failed to parse the mock credential: [{err}]")) }
+        };
+        raw_vc.insert(EXPIRATION.to_string(), json!(2_000_000_000u64));
+        let (vc, _vc_jwt) = adapter.issue_vc(&raw_vc)?;
+        let disclosures: Vec<String> = vec!["name".to_string(), "field".to_string()];
+        let (vp, _vp_jwt) = adapter.issue_vp(&vc, &disclosures)?;
+        let issuer_public_key: Vec<u8> = match serde_json::from_str(&adapter.issuer_keypair()?.0) {
+            Ok(issuer_public_key) => { issuer_public_key }
+            Err(err) => { return Err(format!("Failed to parse the issuer public key: [{err}]")) }
+        };
+
+        let document: Map<String, Value> = audit_document(&adapter, &vp)?;
+        assert_eq!(document.get(ALGORITHM), Some(&Value::String(adapter.sd_algorithm())));
+        let (shared, claims) = split_claim_proofs(&document)?;
+        assert_eq!(claims.len(), disclosures.len());
+        assert_eq!(shared_signed_fields(shared)?.get(EXPIRATION), Some(&json!(2_000_000_000u64)));
+        SdJwtInstance::verify_claim_proofs(&document, &issuer_public_key, &VerifierConfig::DEFAULT)?;
+
+        // Every disclosed claim is re-verified on its own.
+        for name in &disclosures {
+            SdJwtInstance::verify_claim_proofs(&claim_proof(&document, name)?, &issuer_public_key, &VerifierConfig::DEFAULT)?;
+        }
+
+        // Altered values and signed fields are rejected.
+        let mut proof: Map<String, Value> = claim_proof(&document, "name")?;
+        proof[CLAIM_PROOFS]["name"][VALUE] = json!("Isaac Newton");
+        assert!(SdJwtInstance::verify_claim_proofs(&proof, &issuer_public_key, &VerifierConfig::DEFAULT).is_err());
+
+        let mut proof: Map<String, Value> = claim_proof(&document, "name")?;
+        proof[SHARED][SIGNED_FIELDS][EXPIRATION] = json!(3_000_000_000u64);
+        assert!(SdJwtInstance::verify_claim_proofs(&proof, &issuer_public_key, &VerifierConfig::DEFAULT).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn malformed_audit_documents() -> Result<(), String> {
+        assert!(split_claim_proofs(&Map::new()).is_err());
+        let document: Map<String, Value> = match json!({ SHARED: {}, CLAIM_PROOFS: [] }) {
+            Value::Object(document) => { document }
+            _ => { return Err("Document is not an object".to_string()) }
+        };
+        assert!(split_claim_proofs(&document).is_err());
+
+        assert_eq!(shared_signed_fields(&Map::new())?, Map::new());
+        let shared: Map<String, Value> = Map::from_iter([(SIGNED_FIELDS.to_string(), json!("cnf"))]);
+        assert!(shared_signed_fields(&shared).is_err());
+
+        Ok(())
+    }
+}
@@ -1,6 +1,16 @@
 use std::time::{Duration, Instant};
+use log::{debug, warn};
 use crate::adapters::adapter::Adapter;
 
+/// How many times each benchmarked function is executed before averaging its duration.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Measurement {
+    /// Fixed amount of executions.
+    Iterations(i8),
+    /// As many executions as fit in the wall-clock budget, and at least one.
+    TimeBudget(Duration),
+}
+
 /// An empty struct whose methods permit to retrieve the duration execution of a given function.
 pub struct Benchmark {}
 
@@ -27,22 +37,95 @@ impl Benchmark {
     {
         let mut start: Instant;
         let mut result = None;
+        let mut last_error: Option<String> = None;
         let mut total: f64 = 0f64;
 
         for _ in 0..iterations {
             start = Instant::now();
             match func() {
                 Ok(inner) => { result = Some(inner) }
-                Err(err) => { println!("Benchmarked function returned error [{err}]") }
+                Err(err) => {
+                    warn!("Benchmarked function returned error [{err}]");
+                    last_error = Some(err);
+                }
             }
 
             total = total + start.elapsed().as_secs_f64();
         }
 
-        let average_duration: Duration = Duration::from_secs_f64(total / (iterations as f64));
+        match result {
+            Some(result) => { Ok((Duration::from_secs_f64(total / (iterations as f64)), result)) },
+            None => { Err(last_error.unwrap_or("Function did not return a result".to_string())) }
+        }
+    }
+
+    /// Benchmarks a function by executing it until the wall-clock budget is consumed and averaging the results, so that
+    /// the duration of a benchmark is predictable regardless of the speed of the function.
+    ///
+    /// # Arguments
+    /// * `func` - Function to be executed.
+    /// * `budget` - Wall-clock time after which no new execution is started. The function is executed at least once.
+    ///
+    /// # Returns
+    /// A result containing either the averaged duration or a string containing an error.
+    ///
+    /// # Example
+    /// ```
+    /// use std::time::Duration;
+    /// use csd_jwt::benchmark::Benchmark;
+    /// fn example() -> Result<(), String> { Ok(()) }
+    /// let result = Benchmark::benchmark_function_for(example, Duration::from_millis(10));
+    /// ```
+    pub fn benchmark_function_for<F, T>(func: F, budget: Duration) -> Result<(Duration, T), String>
+    where
+        F: Fn() -> Result<T, String>
+    {
+        let budget_start = Instant::now();
+        let mut start: Instant;
+        let mut result = None;
+        let mut last_error: Option<String> = None;
+        let mut total: f64 = 0f64;
+        let mut samples: u32 = 0;
+
+        while samples == 0 || budget_start.elapsed() < budget {
+            start = Instant::now();
+            match func() {
+                Ok(inner) => { result = Some(inner) }
+                Err(err) => {
+                    warn!("Benchmarked function returned error [{err}]");
+                    last_error = Some(err);
+                }
+            }
+
+            total += start.elapsed().as_secs_f64();
+            samples += 1;
+        }
+
+        debug!("Collected {samples} samples in {:?}", budget_start.elapsed());
+
+        let average_duration: Duration = Duration::from_secs_f64(total / (samples as f64));
         match result {
             Some(result) => { Ok((average_duration, result)) },
-            None => { Err("Function did not return a result".to_string()) }
+            None => { Err(last_error.unwrap_or("Function did not return a result".to_string())) }
+        }
+    }
+
+
+    /// Benchmarks a function either for a fixed amount of iterations or for a time budget.
+    ///
+    /// # Arguments
+    /// * `func` - Function to be executed.
+    /// * `measurement` - How many times the function needs to be executed for average.
+    ///
+    /// # Returns
+    /// A result containing either the averaged duration or a string containing an error.
+    pub fn measure<F, T>(func: F, measurement: Measurement) -> Result<(Duration, T), String>
+    where
+        F: Fn() -> Result<T, String>
+    {
+        match measurement {
+            Measurement::Iterations(iterations) => { Benchmark::benchmark_function(func, iterations) }
+            Measurement::TimeBudget(budget) => { Benchmark::benchmark_function_for(func, budget) }
         }
     }
 
@@ -51,16 +134,55 @@ impl Benchmark {
     ///
     /// # Arguments
     /// * `func` - Function to be executed.
-    /// * `iterations` - Amount of times that the function needs to be executed for average.
+    /// * `measurement` - How many times the function needs to be executed for average.
     ///
     /// # Returns
     /// A result containing either the averaged duration or a string containing an error.
-    pub fn benchmark_initialization<F, T>(func: F, iterations: i8) -> Result<(Duration, Box<T>), String>
+    pub fn benchmark_initialization<F, T>(func: F, measurement: Measurement) -> Result<(Duration, Box<T>), String>
     where
         F: Fn() -> Result<T, String>,
         T: Adapter,
     {
-        let (duration, result) = Benchmark::benchmark_function(func, iterations)?;
+        let (duration, result) = Benchmark::measure(func, measurement)?;
         Ok((duration, Box::new(result)))
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+    use std::time::Duration;
+    use crate::benchmark::{Benchmark, Measurement};
+
+    #[test]
+    fn errors_are_returned_when_no_execution_succeeds() {
+        for measurement in [Measurement::Iterations(3), Measurement::TimeBudget(Duration::from_millis(1))] {
+            let calls: Cell<u32> = Cell::new(0);
+            let result = Benchmark::measure(|| -> Result<(), String> {
+                calls.set(calls.get() + 1);
+                Err(format!("failure {}", calls.get()))
+            }, measurement);
+            assert_eq!(result, Err(format!("failure {}", calls.get())));
+        }
+
+        let result = Benchmark::measure(|| Ok(()), Measurement::Iterations(0));
+        assert_eq!(result.map(|(_, result)| result), Err("Function did not return a result".to_string()));
+    }
+
+    #[test]
+    fn a_single_success_is_enough() -> Result<(), String> {
+        for measurement in [Measurement::Iterations(3), Measurement::TimeBudget(Duration::from_millis(1))] {
+            let calls: Cell<u32> = Cell::new(0);
+            let (_, result) = Benchmark::measure(|| {
+                calls.set(calls.get() + 1);
+                match calls.get() {
+                    1 => { Ok(calls.get()) }
+                    _ => { Err("later failure".to_string()) }
+                }
+            }, measurement)?;
+            assert_eq!(result, 1);
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,460 @@
+use ciborium::value::{Integer, Value as CborValue};
+use josekit::jws::{JwsSigner, JwsVerifier, ES256};
+use serde_json::{Map, Number, Value};
+use crate::common_data::{AUDIENCE, CLAIMS, CLAIM_GROUPS, CLAIM_METADATA, CNF, JWK_THUMBPRINT, PRESENTATION_NONCE, SIGNATURE, VERIFIABLE_CREDENTIAL, VP};
+use crate::compression::decode_payload;
+use crate::encoding::Encoding;
+use crate::ephemeral_binding::EPHEMERAL_KEY_CERTIFICATE;
+use crate::sd_algorithms::sd_algorithm::ENCODING_HEADER;
+use crate::verifier_config::VerifierConfig;
+
+/// CBOR tag of a COSE_Sign1 structure, as defined by RFC 9052.
+const COSE_SIGN1_TAG: u64 = 18;
+/// Label of the `alg` parameter of a COSE header.
+const COSE_ALGORITHM: i64 = 1;
+/// COSE identifier of ES256.
+const COSE_ES256: i64 = -7;
+/// Context string of the structure signed by a COSE_Sign1.
+const SIGNATURE1_CONTEXT: &str = "Signature1";
+/// Non-registered CBOR tag marking an algorithm-specific component that the JSON form embeds as an encoded JSON string.
+const EMBEDDED_COMPONENT_TAG: u64 = 0x6373;
+/// Non-registered CBOR tag marking a byte string that the JSON form holds as an array of numbers.
+const BYTE_ARRAY_TAG: u64 = 0x6374;
+
+/// Decoded header, payload and signature of a JWT.
+type JwtParts = (Map<String, Value>, Map<String, Value>, Vec<u8>);
+
+/// Integer labels of the fields of VCs and VPs. Registered JWT claims keep their CWT label (RFC 8392, RFC 8747), the
+/// fields of the crate get negative labels, encoded in a single byte down to -24.
+const LABELS: [(&str, i64); 34] = [
+    ("iss", 1),
+    ("sub", 2),
+    (AUDIENCE, 3),
+    ("exp", 4),
+    ("nbf", 5),
+    ("iat", 6),
+    ("jti", 7),
+    (CNF, 8),
+    ("@context", -1),
+    ("type", -2),
+    ("issuer", -3),
+    (CLAIMS, -4),
+    (CLAIM_METADATA, -5),
+    (JWK_THUMBPRINT, -6),
+    (VP, -7),
+    (VERIFIABLE_CREDENTIAL, -8),
+    (PRESENTATION_NONCE, -9),
+    (EPHEMERAL_KEY_CERTIFICATE, -10),
+    (SIGNATURE, -11),
+    ("hashes", -12),
+    ("svc", -13),
+    ("root", -14),
+    ("salts", -15),
+    ("merkle_proof", -16),
+    ("leaves_len", -17),
+    ("root_sig", -18),
+    ("disclosed_indices", -19),
+    ("accumulator", -20),
+    ("wvc", -21),
+    ("pvc", -22),
+    ("dvc", -23),
+    ("aggregated_witness", -24),
+    ("indices", -25),
+    (CLAIM_GROUPS, -26),
+];
+
+
+/// Converts the JSON payload of a VC or VP JWT into its binary form: a CBOR map with integer keys, in which the
+/// algorithm-specific components (`wvc`, `svc`, `hashes`, Merkle proofs, ...) are native CBOR values instead of
+/// encoded JSON strings, and the encoded elements they contain are byte strings. The conversion is lossless:
+/// `payload_from_cbor` returns the same JSON payload.
+///
+/// # Arguments
+/// * `payload` - JSON payload of the VC or VP.
+/// * `encoding` - Encoding of the elements embedded in the VC or VP.
+///
+/// # Returns
+/// A result containing the CBOR bytes or a string containing an error.
+///
+/// # Examples
+/// ```
+/// use csd_jwt::binary_envelope::{payload_from_cbor, payload_to_cbor};
+/// use csd_jwt::encoding::Encoding;
+/// use csd_jwt::verifier_config::VerifierConfig;
+/// use serde_json::{json, Map, Value};
+///
+/// let container = Encoding::Base64UrlNoPad.encode(json!({"name": ["c2FsdHNhbHRzYWx0c2FsdA", "Alice"]}).to_string());
+/// let payload: Map<String, Value> = json!({"iss": "issuer", "credentialSubject": {}, "svc": container}).as_object().unwrap().clone();
+///
+/// let cbor = payload_to_cbor(&payload, Encoding::Base64UrlNoPad).unwrap();
+/// assert!(cbor.len() < Value::Object(payload.clone()).to_string().len());
+/// assert_eq!(payload_from_cbor(&cbor, Encoding::Base64UrlNoPad, &VerifierConfig::DEFAULT).unwrap(), payload);
+/// ```
+pub fn payload_to_cbor(payload: &Map<String, Value>, encoding: Encoding) -> Result<Vec<u8>, String> {
+    serialize_cbor(&map_to_cbor(payload, encoding, false), "payload")
+}
+
+/// Converts the binary form of a VC or VP payload back into its JSON form.
+///
+/// # Arguments
+/// * `cbor` - CBOR bytes produced by `payload_to_cbor`.
+/// * `encoding` - Encoding of the elements embedded in the VC or VP.
+/// * `config` - Limits on the size and depth of the payload.
+///
+/// # Returns
+/// A result containing the JSON payload or a string containing an error.
+pub fn payload_from_cbor(cbor: &[u8], encoding: Encoding, config: &VerifierConfig) -> Result<Map<String, Value>, String> {
+    if cbor.len() > config.max_jwt_length {
+        return Err(format!("Binary payload length {} exceeds the limit of {} bytes", cbor.len(), config.max_jwt_length));
+    }
+    let payload: Map<String, Value> = match deserialize_cbor(cbor, "payload")? {
+        CborValue::Map(entries) => { map_from_cbor(entries, encoding)? }
+        _ => { return Err("Binary payload is not a CBOR map".to_string()) }
+    };
+
+    config.check_map_depth(&payload)?;
+    Ok(payload)
+}
+
+/// Wraps the payload of a VC or VP in a COSE_Sign1 envelope (RFC 9052) signed with ES256, or in an unsecured envelope
+/// without algorithm and with an empty signature, the binary counterpart of an unsecured JWT.
+///
+/// # Arguments
+/// * `payload` - JSON payload of the VC or VP.
+/// * `encoding` - Encoding of the elements embedded in the VC or VP, declared in the protected header.
+/// * `private_key` - Optional PEM private key signing the envelope.
+///
+/// # Returns
+/// A result containing the envelope or a string containing an error.
+///
+/// # Examples
+/// ```
+/// use csd_jwt::binary_envelope::{sign_envelope, verify_envelope};
+/// use csd_jwt::common_data::CommonData;
+/// use csd_jwt::encoding::Encoding;
+/// use csd_jwt::verifier_config::VerifierConfig;
+/// use serde_json::{json, Map, Value};
+///
+/// let (holder_public_key, holder_private_key) = CommonData::holder_keys().unwrap();
+/// let payload: Map<String, Value> = json!({"nonce": "n-0S6_WzA2Mj", "aud": "verifier"}).as_object().unwrap().clone();
+///
+/// let envelope = sign_envelope(&payload, Encoding::Base64UrlNoPad, Some(holder_private_key.as_slice())).unwrap();
+/// assert_eq!(verify_envelope(&envelope, Some(holder_public_key.as_slice()), &VerifierConfig::DEFAULT).unwrap(), payload);
+/// assert!(verify_envelope(&envelope, None, &VerifierConfig::DEFAULT).is_err());
+/// ```
+pub fn sign_envelope(payload: &Map<String, Value>, encoding: Encoding, private_key: Option<&[u8]>) -> Result<Vec<u8>, String> {
+    let protected: Vec<u8> = protected_header(encoding, private_key.is_some())?;
+    let payload: Vec<u8> = payload_to_cbor(payload, encoding)?;
+
+    let signature: Vec<u8> = match private_key {
+        Some(private_key) => {
+            let signer = match ES256.signer_from_pem(private_key) {
+                Ok(signer) => { signer }
+                Err(err) => { return Err(format!("Failed to create signer: [{err}]")) }
+            };
+            match signer.sign(&signature_structure(&protected, &payload)?) {
+                Ok(signature) => { signature }
+                Err(err) => { return Err(format!("Failed to sign envelope: [{err}]")) }
+            }
+        }
+        None => { vec![] }
+    };
+
+    assemble_envelope(protected, payload, signature)
+}
+
+/// Verifies a COSE_Sign1 envelope and returns the JSON form of its payload. Without a public key, only unsecured
+/// envelopes are accepted.
+///
+/// # Arguments
+/// * `envelope` - Envelope produced by `sign_envelope`.
+/// * `public_key` - Optional PEM public key of the signer.
+/// * `config` - Limits on the size and depth of the payload.
+///
+/// # Returns
+/// A result containing the JSON payload or a string containing an error.
+pub fn verify_envelope(envelope: &[u8], public_key: Option<&[u8]>, config: &VerifierConfig) -> Result<Map<String, Value>, String> {
+    if envelope.len() > config.max_jwt_length {
+        return Err(format!("Envelope length {} exceeds the limit of {} bytes", envelope.len(), config.max_jwt_length));
+    }
+    let (protected, payload, signature): (Vec<u8>, Vec<u8>, Vec<u8>) = match deserialize_cbor(envelope, "envelope")? {
+        CborValue::Tag(COSE_SIGN1_TAG, structure) => match *structure {
+            CborValue::Array(fields) => match <[CborValue; 4]>::try_from(fields) {
+                Ok([CborValue::Bytes(protected), CborValue::Map(_), CborValue::Bytes(payload), CborValue::Bytes(signature)]) => { (protected, payload, signature) }
+                _ => { return Err("Envelope fields are not those of a COSE_Sign1".to_string()) }
+            },
+            _ => { return Err("Envelope is not a CBOR array".to_string()) }
+        },
+        _ => { return Err("Envelope is not a tagged COSE_Sign1".to_string()) }
+    };
+
+    let header: Vec<(CborValue, CborValue)> = match deserialize_cbor(&protected, "protected header")? {
+        CborValue::Map(header) => { header }
+        _ => { return Err("Protected header is not a CBOR map".to_string()) }
+    };
+    let algorithm: Option<&CborValue> = header.iter().find(|(label, _)| *label == CborValue::from(COSE_ALGORITHM)).map(|(_, algorithm)| algorithm);
+    let encoding: Encoding = match header.iter().find(|(label, _)| *label == CborValue::from(ENCODING_HEADER)) {
+        Some((_, CborValue::Text(name))) => { Encoding::from_name(name)? }
+        Some(_) => { return Err(format!("Protected header parameter {ENCODING_HEADER} is not a string")) }
+        None => { Encoding::default() }
+    };
+
+    match (public_key, algorithm) {
+        (Some(public_key), Some(algorithm)) if *algorithm == CborValue::from(COSE_ES256) => {
+            let verifier = match ES256.verifier_from_pem(public_key) {
+                Ok(verifier) => { verifier }
+                Err(err) => { return Err(format!("Failed to create verifier: [{err}]")) }
+            };
+            if let Err(err) = verifier.verify(&signature_structure(&protected, &payload)?, &signature) {
+                return Err(format!("Failed to verify envelope: [{err}]"));
+            }
+        }
+        (Some(_), _) => { return Err("Envelope is not signed with ES256".to_string()) }
+        (None, None) if signature.is_empty() => {}
+        (None, _) => { return Err("Signed envelope cannot be accepted without a public key".to_string()) }
+    }
+
+    payload_from_cbor(&payload, encoding, config)
+}
+
+/// Converts a VC or VP JWT into a COSE_Sign1 envelope with the same payload, signed again with the given key.
+///
+/// # Arguments
+/// * `jwt` - Encoded VC or VP, possibly with a compressed payload.
+/// * `private_key` - Optional PEM private key signing the envelope, the one that signed the JWT.
+///
+/// # Returns
+/// A result containing the envelope or a string containing an error.
+pub fn envelope_from_jwt(jwt: &str, private_key: Option<&[u8]>) -> Result<Vec<u8>, String> {
+    let (header, payload, _) = split_jwt(jwt)?;
+    sign_envelope(&payload, encoding_of(&header)?, private_key)
+}
+
+/// Length the JWT would have as a binary envelope. The signature is kept as is, as the length of an ES256 signature is
+/// the same in JWS and COSE, so the envelope does not need to be signed.
+///
+/// # Arguments
+/// * `jwt` - Encoded VC or VP.
+///
+/// # Returns
+/// A result containing the length in bytes or a string containing an error.
+///
+/// # Examples
+/// ```
+/// use csd_jwt::adapters::adapter::Adapter;
+/// use csd_jwt::adapters::accumulators::csd_jwt_adapter::CsdJwtAdapter;
+/// use csd_jwt::binary_envelope::envelope_length;
+/// use csd_jwt::common_data::VC;
+///
+/// let adapter = CsdJwtAdapter::new(4).unwrap();
+/// let (_, vc_jwt) = adapter.issue_vc(&serde_json::from_str(VC).unwrap()).unwrap();
+/// assert!(envelope_length(&vc_jwt).unwrap() < vc_jwt.len());
+/// assert!(envelope_length("not a JWT").is_err());
+/// ```
+pub fn envelope_length(jwt: &str) -> Result<usize, String> {
+    let (header, payload, signature) = split_jwt(jwt)?;
+    let encoding: Encoding = encoding_of(&header)?;
+
+    let envelope: Vec<u8> = assemble_envelope(protected_header(encoding, !signature.is_empty())?, payload_to_cbor(&payload, encoding)?, signature)?;
+    Ok(envelope.len())
+}
+
+
+/// Decodes the header, the payload and the signature of a JWT without verifying it.
+fn split_jwt(jwt: &str) -> Result<JwtParts, String> {
+    let (encoded_header, encoded_payload, encoded_signature) = match jwt.trim().split('.').collect::<Vec<&str>>().as_slice() {
+        [encoded_header, encoded_payload, encoded_signature] => { (*encoded_header, *encoded_payload, *encoded_signature) }
+        _ => { return Err("JWT is not of the form header.payload.signature".to_string()) }
+    };
+
+    let header: Map<String, Value> = match serde_json::from_slice::<Map<String, Value>>(&Encoding::Base64UrlNoPad.decode(encoded_header)?) {
+        Ok(header) => { header }
+        Err(err) => { return Err(format!("Failed to parse JWT header: [{err}]")) }
+    };
+    let payload: Map<String, Value> = decode_payload(&header, encoded_payload, VerifierConfig::DEFAULT.max_jwt_length)?;
+
+    Ok((header, payload, Encoding::Base64UrlNoPad.decode(encoded_signature)?))
+}
+
+/// Reads the encoding declared in a JWT header.
+fn encoding_of(header: &Map<String, Value>) -> Result<Encoding, String> {
+    match header.get(ENCODING_HEADER) {
+        Some(Value::String(name)) => { Encoding::from_name(name) }
+        _ => { Ok(Encoding::default()) }
+    }
+}
+
+/// Serializes the protected header of an envelope, declaring ES256 only if the envelope is signed.
+fn protected_header(encoding: Encoding, signed: bool) -> Result<Vec<u8>, String> {
+    let mut header: Vec<(CborValue, CborValue)> = vec![];
+    if signed {
+        header.push((CborValue::from(COSE_ALGORITHM), CborValue::from(COSE_ES256)));
+    }
+    if encoding != Encoding::default() {
+        header.push((CborValue::from(ENCODING_HEADER), CborValue::from(encoding.name())));
+    }
+
+    serialize_cbor(&CborValue::Map(header), "protected header")
+}
+
+/// Serializes the Sig_structure signed by a COSE_Sign1, without external data.
+fn signature_structure(protected: &[u8], payload: &[u8]) -> Result<Vec<u8>, String> {
+    serialize_cbor(&CborValue::Array(vec![
+        CborValue::from(SIGNATURE1_CONTEXT),
+        CborValue::Bytes(protected.to_vec()),
+        CborValue::Bytes(vec![]),
+        CborValue::Bytes(payload.to_vec()),
+    ]), "signature structure")
+}
+
+/// Serializes a tagged COSE_Sign1 with an empty unprotected header.
+fn assemble_envelope(protected: Vec<u8>, payload: Vec<u8>, signature: Vec<u8>) -> Result<Vec<u8>, String> {
+    serialize_cbor(&CborValue::Tag(COSE_SIGN1_TAG, Box::new(CborValue::Array(vec![
+        CborValue::Bytes(protected),
+        CborValue::Map(vec![]),
+        CborValue::Bytes(payload),
+        CborValue::Bytes(signature),
+    ]))), "envelope")
+}
+
+fn serialize_cbor(value: &CborValue, name: &str) -> Result<Vec<u8>, String> {
+    let mut cbor: Vec<u8> = Vec::new();
+    match ciborium::ser::into_writer(value, &mut cbor) {
+        Ok(()) => { Ok(cbor) }
+        Err(err) => { Err(format!("Failed to serialize {name} to CBOR: [{err}]")) }
+    }
+}
+
+fn deserialize_cbor(cbor: &[u8], name: &str) -> Result<CborValue, String> {
+    match ciborium::de::from_reader::<CborValue, _>(cbor) {
+        Ok(value) => { Ok(value) }
+        Err(err) => { Err(format!("Failed to parse {name} from CBOR: [{err}]")) }
+    }
+}
+
+/// Integer label of a field, if it has one.
+fn label_of(name: &str) -> Option<i64> {
+    LABELS.iter().find(|(field, _)| *field == name).map(|(_, label)| *label)
+}
+
+/// Field with the given integer label.
+fn name_of(label: i64) -> Result<&'static str, String> {
+    match LABELS.iter().find(|(_, field_label)| *field_label == label) {
+        Some((name, _)) => { Ok(*name) }
+        None => { Err(format!("Unknown label {label} in binary payload")) }
+    }
+}
+
+/// Converts a JSON object into a CBOR map, replacing the known field names with their label.
+fn map_to_cbor(map: &Map<String, Value>, encoding: Encoding, in_component: bool) -> CborValue {
+    CborValue::Map(map
+        .iter()
+        .map(|(name, value)| {
+            let key: CborValue = match label_of(name) {
+                Some(label) => { CborValue::from(label) }
+                None => { CborValue::from(name.as_str()) }
+            };
+            (key, value_to_cbor(value, encoding, in_component))
+        })
+        .collect())
+}
+
+/// Converts a JSON value into CBOR. Strings holding encoded JSON components, and, within components, strings holding
+/// encoded bytes and arrays of bytes are converted into native values only if the conversion can be exactly reversed.
+fn value_to_cbor(value: &Value, encoding: Encoding, in_component: bool) -> CborValue {
+    match value {
+        Value::Null => { CborValue::Null }
+        Value::Bool(boolean) => { CborValue::Bool(*boolean) }
+        Value::Number(number) => match (number.as_u64(), number.as_i64(), number.as_f64()) {
+            (Some(unsigned), _, _) => { CborValue::from(unsigned) }
+            (None, Some(signed), _) => { CborValue::from(signed) }
+            (None, None, Some(float)) => { CborValue::Float(float) }
+            (None, None, None) => { CborValue::Text(number.to_string()) }
+        },
+        Value::String(string) => {
+            let bytes: Option<Vec<u8>> = encoding.decode(string).ok().filter(|bytes| encoding.encode(bytes) == *string);
+            let component: Option<Value> = bytes.as_ref().and_then(|bytes| {
+                serde_json::from_slice::<Value>(bytes)
+                    .ok()
+                    .filter(|component| component.is_object() || component.is_array())
+                    .filter(|component| serde_json::to_vec(component).is_ok_and(|serialized| serialized == *bytes))
+            });
+
+            match (component, bytes) {
+                (Some(component), _) => { CborValue::Tag(EMBEDDED_COMPONENT_TAG, Box::new(value_to_cbor(&component, encoding, true))) }
+                (None, Some(bytes)) if in_component => { CborValue::Bytes(bytes) }
+                _ => { CborValue::Text(string.clone()) }
+            }
+        }
+        Value::Array(array) => {
+            let bytes: Option<Vec<u8>> = array.iter().map(|element| element.as_u64().and_then(|byte| u8::try_from(byte).ok())).collect();
+            match bytes {
+                Some(bytes) if in_component && !bytes.is_empty() => { CborValue::Tag(BYTE_ARRAY_TAG, Box::new(CborValue::Bytes(bytes))) }
+                _ => { CborValue::Array(array.iter().map(|element| value_to_cbor(element, encoding, in_component)).collect()) }
+            }
+        }
+        Value::Object(map) => { map_to_cbor(map, encoding, in_component) }
+    }
+}
+
+/// Converts a CBOR map back into a JSON object.
+fn map_from_cbor(entries: Vec<(CborValue, CborValue)>, encoding: Encoding) -> Result<Map<String, Value>, String> {
+    let mut map: Map<String, Value> = Map::new();
+
+    for (key, value) in entries {
+        let name: String = match key {
+            CborValue::Text(name) if label_of(&name).is_none() => { name }
+            CborValue::Integer(label) => match i64::try_from(label) {
+                Ok(label) => { name_of(label)?.to_string() }
+                Err(_) => { return Err("Label out of range in binary payload".to_string()) }
+            },
+            _ => { return Err("Binary payload contains a key that is neither a label nor an unlabelled name".to_string()) }
+        };
+        if map.insert(name.clone(), value_from_cbor(value, encoding)?).is_some() {
+            return Err(format!("Duplicate field {name} in binary payload"));
+        }
+    }
+
+    Ok(map)
+}
+
+/// Converts a CBOR value back into JSON, encoding the components and byte strings with the given encoding.
+fn value_from_cbor(value: CborValue, encoding: Encoding) -> Result<Value, String> {
+    match value {
+        CborValue::Null => { Ok(Value::Null) }
+        CborValue::Bool(boolean) => { Ok(Value::Bool(boolean)) }
+        CborValue::Integer(integer) => { integer_from_cbor(integer) }
+        CborValue::Float(float) => match Number::from_f64(float) {
+            Some(number) => { Ok(Value::Number(number)) }
+            None => { Err("Binary payload contains a non-finite number".to_string()) }
+        },
+        CborValue::Text(string) => { Ok(Value::String(string)) }
+        CborValue::Bytes(bytes) => { Ok(Value::String(encoding.encode(bytes))) }
+        CborValue::Array(array) => {
+            Ok(Value::Array(array.into_iter().map(|element| value_from_cbor(element, encoding)).collect::<Result<Vec<Value>, String>>()?))
+        }
+        CborValue::Map(entries) => { Ok(Value::Object(map_from_cbor(entries, encoding)?)) }
+        CborValue::Tag(EMBEDDED_COMPONENT_TAG, component) => {
+            let component: Value = value_from_cbor(*component, encoding)?;
+            match serde_json::to_vec(&component) {
+                Ok(serialized) => { Ok(Value::String(encoding.encode(serialized))) }
+                Err(err) => { Err(format!("Failed to serialize component: [{err}]")) }
+            }
+        }
+        CborValue::Tag(BYTE_ARRAY_TAG, bytes) => match *bytes {
+            CborValue::Bytes(bytes) => { Ok(Value::Array(bytes.into_iter().map(Value::from).collect())) }
+            _ => { Err("Tagged byte array does not hold a byte string".to_string()) }
+        },
+        _ => { Err("Binary payload contains an unsupported CBOR value".to_string()) }
+    }
+}
+
+/// Converts a CBOR integer back into a JSON number.
+fn integer_from_cbor(integer: Integer) -> Result<Value, String> {
+    let integer: i128 = i128::from(integer);
+    match (u64::try_from(integer), i64::try_from(integer)) {
+        (Ok(unsigned), _) => { Ok(Value::from(unsigned)) }
+        (Err(_), Ok(signed)) => { Ok(Value::from(signed)) }
+        _ => { Err(format!("Integer {integer} is out of the range of JSON numbers")) }
+    }
+}
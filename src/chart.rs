@@ -0,0 +1,126 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+/// Width in pixels of the generated charts.
+const WIDTH: f64 = 720.0;
+/// Height in pixels of the generated charts.
+const HEIGHT: f64 = 420.0;
+/// Margin in pixels around the plot area, holding the axes labels and the legend.
+const MARGIN: f64 = 60.0;
+/// Width in pixels of the legend, on the right of the plot area.
+const LEGEND_WIDTH: f64 = 160.0;
+/// Amount of ticks on each axis.
+const TICKS: usize = 5;
+/// Colors of the series, assigned in order and reused when exhausted.
+const COLORS: [&str; 8] = ["#1f77b4", "#ff7f0e", "#2ca02c", "#d62728", "#9467bd", "#8c564b", "#e377c2", "#7f7f7f"];
+
+/// Line chart with one series per algorithm, rendered as a standalone SVG document without external dependencies.
+///
+/// # Examples
+/// ```
+/// use csd_jwt::chart::LineChart;
+///
+/// let mut chart = LineChart::new("VP verification", "claims", "µs");
+/// chart.add_point("SD-JWT", 10.0, 120.0);
+/// chart.add_point("SD-JWT", 20.0, 180.0);
+/// assert!(chart.to_svg().starts_with("<svg"));
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct LineChart {
+    /// Title, drawn above the plot area.
+    title: String,
+    /// Label of the horizontal axis.
+    x_label: String,
+    /// Label of the vertical axis.
+    y_label: String,
+    /// Points of every series, indexed by the name of the series.
+    series: BTreeMap<String, Vec<(f64, f64)>>,
+}
+
+impl LineChart {
+
+    /// Constructor for a chart without any point.
+    ///
+    /// # Arguments
+    /// * `title` - Title of the chart.
+    /// * `x_label` - Label of the horizontal axis.
+    /// * `y_label` - Label of the vertical axis.
+    pub fn new(title: &str, x_label: &str, y_label: &str) -> Self {
+        LineChart { title: title.to_string(), x_label: x_label.to_string(), y_label: y_label.to_string(), series: BTreeMap::new() }
+    }
+
+    /// Adds a point to a series, creating the series if needed. Points are drawn in order of abscissa.
+    ///
+    /// # Arguments
+    /// * `series` - Name of the series.
+    /// * `x` - Abscissa of the point.
+    /// * `y` - Ordinate of the point.
+    pub fn add_point(&mut self, series: &str, x: f64, y: f64) {
+        let points = self.series.entry(series.to_string()).or_default();
+        points.push((x, y));
+        points.sort_by(|first, second| first.0.total_cmp(&second.0));
+    }
+
+    /// Renders the chart. Both axes start from zero, and an empty chart is rendered as empty axes.
+    ///
+    /// # Returns
+    /// The SVG document.
+    pub fn to_svg(&self) -> String {
+        let points = self.series.values().flatten();
+        let max_x: f64 = points.clone().map(|(x, _)| *x).fold(0.0, f64::max).max(1.0);
+        let max_y: f64 = points.map(|(_, y)| *y).fold(0.0, f64::max).max(1.0);
+        let plot_width: f64 = WIDTH - 2.0 * MARGIN - LEGEND_WIDTH;
+        let plot_height: f64 = HEIGHT - 2.0 * MARGIN;
+        let scale = |x: f64, y: f64| -> (f64, f64) {
+            (MARGIN + x / max_x * plot_width, HEIGHT - MARGIN - y / max_y * plot_height)
+        };
+
+        let mut svg: String = String::new();
+        let _ = writeln!(svg, r#"<svg xmlns="http://www.w3.org/2000/svg" width="{WIDTH}" height="{HEIGHT}" font-family="sans-serif" font-size="12">"#);
+        let _ = writeln!(svg, r#"<rect width="{WIDTH}" height="{HEIGHT}" fill="white"/>"#);
+        let _ = writeln!(svg, r#"<text x="{}" y="{}" text-anchor="middle" font-size="16">{}</text>"#, MARGIN + plot_width / 2.0, MARGIN / 2.0, escape(&self.title));
+
+        let (origin_x, origin_y) = scale(0.0, 0.0);
+        let (end_x, end_y) = scale(max_x, max_y);
+        let _ = writeln!(svg, r#"<line x1="{origin_x}" y1="{origin_y}" x2="{end_x}" y2="{origin_y}" stroke="black"/>"#);
+        let _ = writeln!(svg, r#"<line x1="{origin_x}" y1="{origin_y}" x2="{origin_x}" y2="{end_y}" stroke="black"/>"#);
+        for tick in 0..=TICKS {
+            let fraction: f64 = tick as f64 / TICKS as f64;
+            let (x, _) = scale(fraction * max_x, 0.0);
+            let (_, y) = scale(0.0, fraction * max_y);
+            let _ = writeln!(svg, r#"<text x="{x}" y="{}" text-anchor="middle">{}</text>"#, origin_y + 16.0, format_tick(fraction * max_x));
+            let _ = writeln!(svg, r#"<text x="{}" y="{}" text-anchor="end">{}</text>"#, origin_x - 6.0, y + 4.0, format_tick(fraction * max_y));
+            let _ = writeln!(svg, r##"<line x1="{origin_x}" y1="{y}" x2="{end_x}" y2="{y}" stroke="#e0e0e0"/>"##);
+        }
+        let _ = writeln!(svg, r#"<text x="{}" y="{}" text-anchor="middle">{}</text>"#, MARGIN + plot_width / 2.0, HEIGHT - MARGIN / 3.0, escape(&self.x_label));
+        let _ = writeln!(svg, r#"<text x="{}" y="{}" text-anchor="middle" transform="rotate(-90 {} {})">{}</text>"#, MARGIN / 3.0, HEIGHT / 2.0, MARGIN / 3.0, HEIGHT / 2.0, escape(&self.y_label));
+
+        for (index, (name, points)) in self.series.iter().enumerate() {
+            let color: &str = COLORS[index % COLORS.len()];
+            let scaled_points: Vec<(f64, f64)> = points.iter().map(|(x, y)| scale(*x, *y)).collect();
+            let path: Vec<String> = scaled_points.iter().map(|(x, y)| format!("{x:.1},{y:.1}")).collect();
+            let _ = writeln!(svg, r#"<polyline points="{}" fill="none" stroke="{color}" stroke-width="2"/>"#, path.join(" "));
+            for (x, y) in &scaled_points {
+                let _ = writeln!(svg, r#"<circle cx="{x:.1}" cy="{y:.1}" r="3" fill="{color}"/>"#);
+            }
+
+            let legend_y: f64 = MARGIN + 20.0 * index as f64;
+            let legend_x: f64 = WIDTH - MARGIN - LEGEND_WIDTH + 20.0;
+            let _ = writeln!(svg, r#"<rect x="{legend_x}" y="{}" width="12" height="12" fill="{color}"/>"#, legend_y - 10.0);
+            let _ = writeln!(svg, r#"<text x="{}" y="{legend_y}">{}</text>"#, legend_x + 18.0, escape(name));
+        }
+
+        svg.push_str("</svg>\n");
+        svg
+    }
+}
+
+/// Formats the value of a tick with at most one decimal digit.
+fn format_tick(value: f64) -> String {
+    if value.fract() == 0.0 { format!("{value:.0}") } else { format!("{value:.1}") }
+}
+
+/// Escapes the characters of a text that are reserved in XML.
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
@@ -0,0 +1,433 @@
+use std::fs;
+use std::path::Path;
+use fake::Fake;
+use fake::faker::address::en::{BuildingNumber, CityName, CountryName, StreetName, ZipCode};
+use fake::faker::company::en::CompanyName;
+use fake::faker::internet::en::SafeEmail;
+use fake::faker::job::en::Title;
+use fake::faker::name::en::{FirstName, LastName};
+use fake::faker::phone_number::en::PhoneNumber;
+use rand::Rng;
+use serde_json::{Map, Value};
+use crate::common_data::{CLAIMS, VC};
+
+/// Source of the claims placed in the credentials of the benchmark.
+///
+/// Claims are identified by an index starting from 1, so that the same disclosures (e.g. the first `n` claims) can be
+/// requested regardless of the names the generator gives to its claims.
+///
+/// # Examples
+/// ```
+/// use csd_jwt::claim_generator::{ClaimGenerator, FlatClaimGenerator};
+///
+/// let generator = FlatClaimGenerator;
+/// let claims = generator.claims(3).unwrap();
+/// assert_eq!(claims.len(), 3);
+/// assert_eq!(generator.disclosures(&[1, 3]), vec!["Claim Key 1".to_string(), "Claim Key 3".to_string()]);
+/// ```
+pub trait ClaimGenerator {
+
+    /// Name of the generator, as selected in the benchmark configuration.
+    ///
+    /// # Returns
+    /// A static string identifying the generator.
+    fn name(&self) -> &'static str;
+
+
+    /// Name of the claim at a given index.
+    ///
+    /// # Arguments
+    /// * `index` - Index of the claim, starting from 1.
+    ///
+    /// # Returns
+    /// The name of the claim.
+    fn claim_name(&self, index: usize) -> String;
+
+
+    /// Value of the claim at a given index.
+    ///
+    /// # Arguments
+    /// * `index` - Index of the claim, starting from 1.
+    ///
+    /// # Returns
+    /// The value of the claim.
+    fn claim_value(&self, index: usize) -> Value;
+
+
+    /// Generates a set of claims.
+    ///
+    /// # Arguments
+    /// * `n_claims` - Amount of claims to generate.
+    ///
+    /// # Returns
+    /// A result containing the map of the claims or a string containing an error.
+    fn claims(&self, n_claims: usize) -> Result<Map<String, Value>, String> {
+        let mut claims: Map<String, Value> = Map::new();
+        for index in 1..=n_claims {
+            let name = self.claim_name(index);
+            if claims.insert(name.clone(), self.claim_value(index)).is_some() {
+                return Err(format!("Generator {} produced the claim name {name} twice", self.name()));
+            }
+        }
+
+        Ok(claims)
+    }
+
+
+    /// Replaces the claims of a raw VC with generated ones.
+    ///
+    /// # Arguments
+    /// * `raw_vc` - Template VC whose claims must be replaced.
+    /// * `n_claims` - Amount of claims to generate.
+    ///
+    /// # Returns
+    /// A result containing a string in case of error.
+    fn substitute_claims(&self, raw_vc: &mut Map<String, Value>, n_claims: usize) -> Result<(), String> {
+        let claims = self.claims(n_claims)?;
+        raw_vc.insert(CLAIMS.to_string(), Value::Object(claims));       // We simply ignore if previous claims were present
+
+        Ok(())
+    }
+
+
+    /// Names of the claims at the given indices, to be used as disclosures.
+    ///
+    /// # Arguments
+    /// * `indices` - Indices of the claims to disclose, starting from 1.
+    ///
+    /// # Returns
+    /// The names of the claims to disclose.
+    fn disclosures(&self, indices: &[usize]) -> Vec<String> {
+        indices.iter().map(|index| self.claim_name(*index)).collect()
+    }
+}
+
+
+/// Generator of flat string claims of the form `Claim Key i: Claim Value i`, as used by the original benchmark.
+pub struct FlatClaimGenerator;
+
+impl ClaimGenerator for FlatClaimGenerator {
+
+    fn name(&self) -> &'static str {
+        "flat"
+    }
+
+    fn claim_name(&self, index: usize) -> String {
+        format!("Claim Key {index}")
+    }
+
+    fn claim_value(&self, index: usize) -> Value {
+        Value::String(format!("Claim Value {index}"))
+    }
+}
+
+
+/// Appends a round suffix to a base name when a generator cycles over a finite list of names, so that claim names stay unique.
+fn cycled_name(names: &[String], index: usize) -> String {
+    let base = &names[(index - 1) % names.len()];
+    match (index - 1) / names.len() {
+        0 => { base.clone() }
+        round => { format!("{base}_{}", round + 1) }
+    }
+}
+
+
+/// Fields of a generated persona, in the order they are assigned to claim indices.
+const PERSONA_FIELDS: [&str; 14] = [
+    "given_name",
+    "family_name",
+    "birthdate",
+    "age",
+    "email",
+    "phone_number",
+    "street_address",
+    "locality",
+    "postal_code",
+    "country",
+    "nationality",
+    "employer",
+    "job_title",
+    "is_over_18",
+];
+
+/// Generator of realistic persona data (names, dates, addresses, ...), with claim values of different types and lengths.
+pub struct PersonaClaimGenerator {
+    /// Names of the persona fields.
+    names: Vec<String>,
+}
+
+impl PersonaClaimGenerator {
+
+    /// Constructor for a persona generator.
+    ///
+    /// # Returns
+    /// A new persona generator.
+    pub fn new() -> Self {
+        PersonaClaimGenerator { names: PERSONA_FIELDS.iter().map(|field| field.to_string()).collect() }
+    }
+}
+
+impl Default for PersonaClaimGenerator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ClaimGenerator for PersonaClaimGenerator {
+
+    fn name(&self) -> &'static str {
+        "persona"
+    }
+
+    fn claim_name(&self, index: usize) -> String {
+        cycled_name(&self.names, index)
+    }
+
+    fn claim_value(&self, index: usize) -> Value {
+        let mut rng = rand::rng();
+        match PERSONA_FIELDS[(index - 1) % PERSONA_FIELDS.len()] {
+            "given_name" => { Value::String(FirstName().fake()) }
+            "family_name" => { Value::String(LastName().fake()) }
+            "birthdate" => {
+                Value::String(format!("{:04}-{:02}-{:02}", rng.random_range(1940..2006), rng.random_range(1..=12), rng.random_range(1..=28)))
+            }
+            "age" => { Value::from(rng.random_range(18..85)) }
+            "email" => { Value::String(SafeEmail().fake()) }
+            "phone_number" => { Value::String(PhoneNumber().fake()) }
+            "street_address" => {
+                let number: String = BuildingNumber().fake();
+                let street: String = StreetName().fake();
+                Value::String(format!("{number} {street}"))
+            }
+            "locality" => { Value::String(CityName().fake()) }
+            "postal_code" => { Value::String(ZipCode().fake()) }
+            "country" | "nationality" => { Value::String(CountryName().fake()) }
+            "employer" => { Value::String(CompanyName().fake()) }
+            "job_title" => { Value::String(Title().fake()) }
+            _ => { Value::Bool(rng.random_bool(0.5)) }
+        }
+    }
+}
+
+
+/// Generator replaying the claims of a real credential template, cycling over them when more claims are needed than
+/// the template holds.
+pub struct TemplateClaimGenerator {
+    /// Names of the claims of the template.
+    names: Vec<String>,
+    /// Values of the claims of the template, in the same order of the names.
+    values: Vec<Value>,
+}
+
+impl TemplateClaimGenerator {
+
+    /// Builds a generator from the `credentialSubject` of a credential.
+    ///
+    /// # Arguments
+    /// * `credential` - Credential used as template.
+    ///
+    /// # Returns
+    /// A result containing the generator or a string containing an error if the template has no claims.
+    pub fn from_credential(credential: &Map<String, Value>) -> Result<Self, String> {
+        let claims: &Map<String, Value> = match credential.get(CLAIMS) {
+            Some(Value::Object(claims)) if !claims.is_empty() => { claims }
+            _ => { return Err(format!("Credential template does not contain any {CLAIMS}")) }
+        };
+
+        Ok(TemplateClaimGenerator {
+            names: claims.keys().cloned().collect(),
+            values: claims.values().cloned().collect(),
+        })
+    }
+
+    /// Builds a generator from a credential template stored as a JSON file.
+    ///
+    /// # Arguments
+    /// * `path` - Path of the JSON file.
+    ///
+    /// # Returns
+    /// A result containing the generator or a string containing an error.
+    pub fn from_file(path: &Path) -> Result<Self, String> {
+        let content = match fs::read_to_string(path) {
+            Ok(content) => { content }
+            Err(err) => { return Err(format!("Failed to read credential template {}: [{err}]", path.display())) }
+        };
+
+        match serde_json::from_str::<Map<String, Value>>(&content) {
+            Ok(credential) => { Self::from_credential(&credential) }
+            Err(err) => { Err(format!("Failed to parse credential template {}: [{err}]", path.display())) }
+        }
+    }
+}
+
+impl ClaimGenerator for TemplateClaimGenerator {
+
+    fn name(&self) -> &'static str {
+        "template"
+    }
+
+    fn claim_name(&self, index: usize) -> String {
+        cycled_name(&self.names, index)
+    }
+
+    fn claim_value(&self, index: usize) -> Value {
+        self.values[(index - 1) % self.values.len()].clone()
+    }
+}
+
+
+/// Writing systems of the claims generated by `MultilingualClaimGenerator`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Script {
+    /// Chinese, Japanese and Korean characters, three bytes each in UTF-8.
+    Cjk,
+    /// Arabic characters, written right to left, two bytes each in UTF-8.
+    Arabic,
+    /// Emoji, including flags and ZWJ sequences made of several code points of four bytes each.
+    Emoji,
+    /// All of the above, cycled claim by claim, with Latin text using combining accents.
+    Mixed,
+}
+
+impl Script {
+
+    /// Parses the name of a script, as used in the configuration of the generator.
+    ///
+    /// # Arguments
+    /// * `name` - Name of the script.
+    ///
+    /// # Returns
+    /// A result containing the script or a string containing an error if the name is unknown.
+    pub fn from_name(name: &str) -> Result<Self, String> {
+        match name {
+            "cjk" => { Ok(Script::Cjk) }
+            "arabic" => { Ok(Script::Arabic) }
+            "emoji" => { Ok(Script::Emoji) }
+            "mixed" => { Ok(Script::Mixed) }
+            _ => { Err(format!("Unknown script {name}, expected cjk, arabic, emoji or mixed")) }
+        }
+    }
+
+    /// Words the names of the claims are built from.
+    fn names(&self) -> &'static [&'static str] {
+        match self {
+            Script::Cjk => { &["氏名", "生年月日", "住所", "国籍", "学校", "職業", "전화번호", "电子邮件"] }
+            Script::Arabic => { &["الاسم", "تاريخ الميلاد", "العنوان", "الجنسية", "المدرسة", "المهنة"] }
+            Script::Emoji => { &["🪪", "🎂", "🏠", "🌍", "🎓", "💼", "👩‍💻", "🇪🇺"] }
+            Script::Mixed => { &[] }
+        }
+    }
+
+    /// Texts the values of the claims are built from.
+    fn values(&self) -> &'static [&'static str] {
+        match self {
+            Script::Cjk => { &["山田太郎", "東京都千代田区千代田1-1", "北京市海淀区中关村大街", "서울특별시 강남구 테헤란로", "日本国", "東京大学大学院情報理工学系研究科"] }
+            Script::Arabic => { &["محمد بن عبد الله", "القاهرة، جمهورية مصر العربية", "١٩٨٥-٠٣-١٤", "مهندس برمجيات", "جامعة الملك سعود"] }
+            Script::Emoji => { &["👨‍👩‍👧‍👦", "🇮🇹🇯🇵🇸🇦", "🧑🏽‍🔬 ⚛️ 🏆", "👍🏿👍🏻", "😀😃😄😁😆😅"] }
+            Script::Mixed => { &[] }
+        }
+    }
+}
+
+
+/// Generator of claims whose names and values are written in non-Latin scripts, to measure how multi-byte UTF-8
+/// text affects hashing, canonicalization and the size of the encoded credentials. Names and values are derived from
+/// the index only, so that the same claims are generated at every run.
+///
+/// The algorithms protect the UTF-8 bytes of the claims as they are, without Unicode normalization: the mixed script
+/// includes text with combining accents (`e` followed by U+0301), which a holder must present byte for byte and not
+/// in its precomposed form.
+///
+/// # Examples
+/// ```
+/// use csd_jwt::claim_generator::{ClaimGenerator, MultilingualClaimGenerator, Script};
+///
+/// let generator = MultilingualClaimGenerator::new(Script::Cjk);
+/// assert_eq!(generator.claim_name(1), "氏名 1");
+/// assert!(!generator.claims(20).unwrap().keys().any(|name| name.is_ascii()));
+/// ```
+pub struct MultilingualClaimGenerator {
+    /// Script of the claims.
+    script: Script,
+}
+
+impl MultilingualClaimGenerator {
+
+    /// Constructor for a multilingual generator.
+    ///
+    /// # Arguments
+    /// * `script` - Script of the claims.
+    ///
+    /// # Returns
+    /// A new multilingual generator.
+    pub fn new(script: Script) -> Self {
+        MultilingualClaimGenerator { script }
+    }
+
+    /// Script of the claim at a given index, cycling over the scripts for the mixed generator.
+    fn script_of(&self, index: usize) -> Script {
+        match self.script {
+            Script::Mixed => { [Script::Cjk, Script::Arabic, Script::Emoji, Script::Mixed][(index - 1) % 4] }
+            script => { script }
+        }
+    }
+}
+
+impl ClaimGenerator for MultilingualClaimGenerator {
+
+    fn name(&self) -> &'static str {
+        match self.script {
+            Script::Cjk => { "multilingual:cjk" }
+            Script::Arabic => { "multilingual:arabic" }
+            Script::Emoji => { "multilingual:emoji" }
+            Script::Mixed => { "multilingual" }
+        }
+    }
+
+    fn claim_name(&self, index: usize) -> String {
+        match self.script_of(index) {
+            Script::Mixed => { format!("Prénom de l'élève {index}") }
+            script => { format!("{} {index}", script.names()[(index - 1) % script.names().len()]) }
+        }
+    }
+
+    fn claim_value(&self, index: usize) -> Value {
+        match self.script_of(index) {
+            Script::Mixed => { Value::String(format!("Rene\u{301}e Lefe\u{300}vre, cafe\u{301} n\u{b0}{index}")) }
+            script => { Value::String(format!("{} {index}", script.values()[(index - 1) % script.values().len()])) }
+        }
+    }
+}
+
+
+/// Selects a claim generator from its configuration name: `flat`, `persona`, `template` (replaying the crate's mock
+/// credential), `template:<path>` (replaying the credential stored in the given JSON file), `multilingual` (cycling
+/// over non-Latin scripts) or `multilingual:<script>` with `cjk`, `arabic` or `emoji`.
+///
+/// # Arguments
+/// * `name` - Configuration name of the generator.
+///
+/// # Returns
+/// A result containing the generator or a string containing an error if the name is unknown or the template cannot be loaded.
+pub fn claim_generator_from_name(name: &str) -> Result<Box<dyn ClaimGenerator>, String> {
+    match name.split_once(':') {
+        Some(("template", path)) => { return Ok(Box::new(TemplateClaimGenerator::from_file(Path::new(path))?)) }
+        Some(("multilingual", script)) => { return Ok(Box::new(MultilingualClaimGenerator::new(Script::from_name(script)?))) }
+        Some(_) => { return Err(format!("Unknown claim generator {name}")) }
+        None => {}
+    }
+
+    match name {
+        "flat" => { Ok(Box::new(FlatClaimGenerator)) }
+        "persona" => { Ok(Box::new(PersonaClaimGenerator::new())) }
+        "multilingual" => { Ok(Box::new(MultilingualClaimGenerator::new(Script::Mixed))) }
+        "template" => {
+            let credential: Map<String, Value> = match serde_json::from_str(VC) {
+                Ok(credential) => { credential }
+                Err(err) => { return Err(format!("Failed to parse the mock credential: [{err}]")) }
+            };
+            Ok(Box::new(TemplateClaimGenerator::from_credential(&credential)?))
+        }
+        _ => { Err(format!("Unknown claim generator {name}")) }
+    }
+}
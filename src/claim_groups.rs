@@ -0,0 +1,268 @@
+use std::collections::BTreeMap;
+use serde_json::{Map, Value};
+use crate::claim_metadata::ClaimMetadata;
+use crate::common_data::{CLAIMS, CLAIM_GROUPS, CLAIM_METADATA};
+
+/// Groups of claims forming a single disclosable unit, e.g. `street`, `city` and `zip` grouped as `address`. At issuance
+/// the claims of a group are merged into a single claim named after the group, whose value is the canonical
+/// serialization of the object holding them, so that the group costs one digest, leaf, witness or message. The groups
+/// are carried by the `claimGroups` sidecar of the VC, next to `credentialSubject`, so that the holder can disclose a
+/// group by naming any of its claims and the verifier can expand it.
+///
+/// # Examples
+/// ```
+/// use serde_json::{json, Map, Value};
+/// use csd_jwt::claim_groups::ClaimGroups;
+///
+/// let mut raw_vc: Map<String, Value> = json!({
+///     "credentialSubject": {"name": "Alice", "street": "Via Roma 1", "city": "Torino", "zip": "10121"}
+/// }).as_object().unwrap().clone();
+/// ClaimGroups::new().with_group("address", &["street", "city", "zip"]).insert_into(&mut raw_vc).unwrap();
+///
+/// let grouped_vc = ClaimGroups::group_claims(&raw_vc).unwrap();
+/// let claims = grouped_vc["credentialSubject"].as_object().unwrap();
+/// let mut names: Vec<&String> = claims.keys().collect();
+/// names.sort();
+/// assert_eq!(names, vec!["address", "name"]);
+///
+/// let groups = ClaimGroups::extract(&grouped_vc).unwrap();
+/// assert_eq!(groups.group_of("city"), Some("address"));
+/// assert_eq!(groups.expand(claims).unwrap()["zip"], "10121");
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ClaimGroups {
+    /// Claims of every group, indexed by the name of the group.
+    groups: BTreeMap<String, Vec<String>>,
+}
+
+impl ClaimGroups {
+
+    /// Constructor for an empty set of groups.
+    pub fn new() -> Self {
+        ClaimGroups::default()
+    }
+
+    /// Adds a group to the set.
+    ///
+    /// # Arguments
+    /// * `name` - Name of the claim the group is merged into.
+    /// * `claims` - Names of the claims of the group.
+    ///
+    /// # Returns
+    /// The set of groups including the new group.
+    pub fn with_group(mut self, name: &str, claims: &[&str]) -> Self {
+        self.groups.insert(name.to_string(), claims.iter().map(|claim| claim.to_string()).collect());
+        self
+    }
+
+    /// Whether the set contains no group.
+    pub fn is_empty(&self) -> bool {
+        self.groups.is_empty()
+    }
+
+    /// Name of the group a claim belongs to, if any.
+    ///
+    /// # Arguments
+    /// * `claim` - Name of the claim.
+    ///
+    /// # Returns
+    /// The name of the group, or none if the claim is not grouped.
+    pub fn group_of(&self, claim: &str) -> Option<&str> {
+        self.groups.iter().find(|(_, claims)| claims.iter().any(|grouped| grouped == claim)).map(|(name, _)| name.as_str())
+    }
+
+    /// Claims of a group, in the order they were configured.
+    ///
+    /// # Arguments
+    /// * `name` - Name of the group.
+    ///
+    /// # Returns
+    /// The names of the claims of the group, or none if there is no such group.
+    pub fn claims_of(&self, name: &str) -> Option<&[String]> {
+        self.groups.get(name).map(Vec::as_slice)
+    }
+
+    /// Writes the groups in the `claimGroups` sidecar of a raw VC, replacing any previous one.
+    ///
+    /// # Arguments
+    /// * `raw_vc` - Raw VC the groups are configured for.
+    ///
+    /// # Returns
+    /// A result containing a string in case the groups do not match the claims of the VC.
+    pub fn insert_into(&self, raw_vc: &mut Map<String, Value>) -> Result<(), String> {
+        match raw_vc.get(CLAIMS) {
+            Some(Value::Object(claims)) => { self.check_claims(claims)? }
+            _ => { return Err(format!("Raw VC does not contain {CLAIMS}")) }
+        };
+
+        let sidecar: Map<String, Value> = self.groups
+            .iter()
+            .map(|(name, claims)| (name.clone(), Value::Array(claims.iter().cloned().map(Value::String).collect())))
+            .collect();
+        raw_vc.insert(CLAIM_GROUPS.to_string(), Value::Object(sidecar));
+
+        Ok(())
+    }
+
+    /// Retrieves the groups from the `claimGroups` sidecar of a VC or a derived credential.
+    ///
+    /// # Arguments
+    /// * `map` - VC or derived credential, possibly containing the sidecar.
+    ///
+    /// # Returns
+    /// A result containing the groups, empty if the sidecar is absent, or a string containing an error.
+    pub fn extract(map: &Map<String, Value>) -> Result<Self, String> {
+        let sidecar: &Map<String, Value> = match map.get(CLAIM_GROUPS) {
+            None => { return Ok(ClaimGroups::new()) }
+            Some(Value::Object(sidecar)) => { sidecar }
+            Some(_) => { return Err(format!("{CLAIM_GROUPS} field is not an object")) }
+        };
+
+        let mut groups: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        for (name, claims) in sidecar {
+            let claims: Vec<String> = match claims {
+                Value::Array(claims) => match claims.iter().map(|claim| claim.as_str().map(str::to_string)).collect::<Option<Vec<String>>>() {
+                    Some(claims) => { claims }
+                    None => { return Err(format!("Claims of group {name} are not strings")) }
+                },
+                _ => { return Err(format!("Claims of group {name} are not an array")) }
+            };
+            groups.insert(name.clone(), claims);
+        }
+
+        Ok(ClaimGroups { groups })
+    }
+
+    /// Checks that every group is non-empty, that groups are disjoint, and that the name of a group is neither one of
+    /// the claims nor a member of another group.
+    fn check_claims(&self, claims: &Map<String, Value>) -> Result<(), String> {
+        let mut grouped_claims: Vec<&String> = vec![];
+
+        for (name, members) in &self.groups {
+            if members.is_empty() {
+                return Err(format!("Group {name} does not contain any claim"));
+            }
+            if claims.contains_key(name) || self.group_of(name).is_some() {
+                return Err(format!("Group name {name} is already the name of a claim"));
+            }
+            for claim in members {
+                if !claims.contains_key(claim) {
+                    return Err(format!("Claim {claim} of group {name} is not in the credential"));
+                }
+                if grouped_claims.contains(&claim) {
+                    return Err(format!("Claim {claim} belongs to more than one group"));
+                }
+                grouped_claims.push(claim);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Merges the claims of every group configured in a raw VC into a single claim, before issuance. The metadata of
+    /// a group is mandatory or sensitive if the metadata of any of its claims is. Raw VCs without groups are returned
+    /// as they are.
+    ///
+    /// # Arguments
+    /// * `raw_vc` - Raw VC, possibly containing the `claimGroups` sidecar.
+    ///
+    /// # Returns
+    /// A result containing the raw VC with the grouped claims or a string containing an error.
+    pub fn group_claims(raw_vc: &Map<String, Value>) -> Result<Map<String, Value>, String> {
+        let groups: ClaimGroups = Self::extract(raw_vc)?;
+        if groups.is_empty() {
+            return Ok(raw_vc.clone());
+        }
+
+        let mut grouped_vc: Map<String, Value> = raw_vc.clone();
+        let mut claims: Map<String, Value> = match grouped_vc.remove(CLAIMS) {
+            Some(Value::Object(claims)) => { claims }
+            _ => { return Err(format!("Raw VC does not contain {CLAIMS}")) }
+        };
+        groups.check_claims(&claims)?;
+        let mut metadata: BTreeMap<String, ClaimMetadata> = ClaimMetadata::extract(raw_vc)?;
+
+        for (name, members) in &groups.groups {
+            let mut parts: Map<String, Value> = Map::new();
+            let mut members_metadata: Vec<ClaimMetadata> = vec![];
+            for claim in members {
+                if let Some(value) = claims.remove(claim) {
+                    parts.insert(claim.clone(), value);
+                }
+                members_metadata.extend(metadata.remove(claim));
+            }
+
+            claims.insert(name.clone(), Value::String(Value::Object(parts).to_string()));
+            if let Some(group_metadata) = ClaimMetadata::bundle(&members_metadata) {
+                metadata.insert(name.clone(), group_metadata);
+            }
+        }
+
+        grouped_vc.insert(CLAIMS.to_string(), Value::Object(claims));
+        if grouped_vc.contains_key(CLAIM_METADATA) {
+            let sidecar: Map<String, Value> = metadata.into_iter().map(|(claim, claim_metadata)| (claim, claim_metadata.to_json())).collect();
+            grouped_vc.insert(CLAIM_METADATA.to_string(), Value::Object(sidecar));
+        }
+
+        Ok(grouped_vc)
+    }
+
+    /// Resolves the disclosures chosen by a holder to the groups of their claims, so that naming any claim of a group
+    /// discloses the whole group. Every group is disclosed once.
+    ///
+    /// # Arguments
+    /// * `disclosures` - Names of the claims or groups to be disclosed.
+    ///
+    /// # Returns
+    /// The disclosures with the grouped claims replaced by their group.
+    pub fn resolve(&self, disclosures: &[String]) -> Vec<String> {
+        let mut resolved_disclosures: Vec<String> = vec![];
+
+        for disclosure in disclosures {
+            let resolved: String = match self.group_of(disclosure) {
+                Some(name) => { name.to_string() }
+                None => { disclosure.clone() }
+            };
+            if !resolved_disclosures.contains(&resolved) {
+                resolved_disclosures.push(resolved);
+            }
+        }
+
+        resolved_disclosures
+    }
+
+    /// Expands the disclosed groups back into the claims they merge, on the verifier side.
+    ///
+    /// # Arguments
+    /// * `claims` - Disclosed claims.
+    ///
+    /// # Returns
+    /// A result containing the expanded claims or a string containing an error if a group does not hold its claims.
+    pub fn expand(&self, claims: &Map<String, Value>) -> Result<Map<String, Value>, String> {
+        let mut expanded_claims: Map<String, Value> = Map::new();
+
+        for (name, value) in claims {
+            let members: &Vec<String> = match self.groups.get(name) {
+                Some(members) => { members }
+                None => {
+                    expanded_claims.insert(name.clone(), value.clone());
+                    continue
+                }
+            };
+
+            let parts: Map<String, Value> = match value {
+                Value::String(serialized) => match serde_json::from_str::<Map<String, Value>>(serialized) {
+                    Ok(parts) => { parts }
+                    Err(err) => { return Err(format!("Failed to parse group {name}: [{err}]")) }
+                },
+                _ => { return Err(format!("Group {name} is not a string")) }
+            };
+            if parts.len() != members.len() || members.iter().any(|claim| !parts.contains_key(claim)) {
+                return Err(format!("Group {name} does not hold exactly its claims"));
+            }
+            expanded_claims.extend(parts);
+        }
+
+        Ok(expanded_claims)
+    }
+}
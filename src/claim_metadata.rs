@@ -0,0 +1,211 @@
+use std::collections::BTreeMap;
+use serde_json::{Map, Value};
+use crate::common_data::CLAIM_METADATA;
+use crate::typed_claims::{canonical_date, canonical_number};
+
+/// Data types a claim value can be tagged with. Numbers may be JSON numbers or decimal strings, and dates are RFC 3339
+/// strings; both are issued in their canonical encoding, see `typed_claims`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ClaimDataType {
+    String,
+    Number,
+    Date,
+    Boolean,
+    Object,
+    Array,
+}
+
+impl ClaimDataType {
+
+    /// Name of the data type, as written in the claim metadata.
+    ///
+    /// # Returns
+    /// A static string identifying the data type.
+    pub fn name(&self) -> &'static str {
+        match self {
+            ClaimDataType::String => { "string" }
+            ClaimDataType::Number => { "number" }
+            ClaimDataType::Date => { "date" }
+            ClaimDataType::Boolean => { "boolean" }
+            ClaimDataType::Object => { "object" }
+            ClaimDataType::Array => { "array" }
+        }
+    }
+
+    /// Retrieves the data type corresponding to a name previously produced by `name`.
+    ///
+    /// # Arguments
+    /// * `name` - Name of the data type.
+    ///
+    /// # Returns
+    /// A result containing the data type or a string containing an error if the name is unknown.
+    pub fn from_name(name: &str) -> Result<Self, String> {
+        match name {
+            "string" => { Ok(ClaimDataType::String) }
+            "number" => { Ok(ClaimDataType::Number) }
+            "date" => { Ok(ClaimDataType::Date) }
+            "boolean" => { Ok(ClaimDataType::Boolean) }
+            "object" => { Ok(ClaimDataType::Object) }
+            "array" => { Ok(ClaimDataType::Array) }
+            _ => { Err(format!("Unknown claim data type {name}")) }
+        }
+    }
+
+    /// Checks whether a claim value is of this data type.
+    ///
+    /// # Arguments
+    /// * `value` - Claim value.
+    ///
+    /// # Returns
+    /// True if the value matches the data type, false otherwise.
+    pub fn matches(&self, value: &Value) -> bool {
+        match self {
+            ClaimDataType::String => { value.is_string() }
+            ClaimDataType::Number => { value.is_number() || value.as_str().is_some_and(|number| canonical_number(number).is_ok()) }
+            ClaimDataType::Date => { value.as_str().is_some_and(|date| canonical_date(date).is_ok()) }
+            ClaimDataType::Boolean => { value.is_boolean() }
+            ClaimDataType::Object => { value.is_object() }
+            ClaimDataType::Array => { value.is_array() }
+        }
+    }
+}
+
+/// Metadata attached to a single claim by the issuer, carried in the `claimMetadata` sidecar object of the VC next to
+/// `credentialSubject`.
+///
+/// # Examples
+/// ```
+/// use serde_json::json;
+/// use csd_jwt::claim_metadata::ClaimMetadata;
+///
+/// let metadata = ClaimMetadata::from_json(&json!({"mandatory": true, "type": "string"})).unwrap();
+/// assert!(metadata.mandatory);
+/// assert!(!metadata.sensitive);
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ClaimMetadata {
+    /// Mandatory claims are always disclosed, regardless of the disclosures chosen by the holder.
+    pub mandatory: bool,
+    /// Sensitive claims can only be disclosed in presentations bound to an audience.
+    pub sensitive: bool,
+    /// Expected data type of the claim value, if any.
+    pub data_type: Option<ClaimDataType>,
+}
+
+impl ClaimMetadata {
+
+    /// Parses the metadata of a claim from its JSON representation.
+    ///
+    /// # Arguments
+    /// * `value` - JSON object with the optional `mandatory`, `sensitive` and `type` fields.
+    ///
+    /// # Returns
+    /// A result containing the metadata or a string containing an error.
+    pub fn from_json(value: &Value) -> Result<Self, String> {
+        let map = match value {
+            Value::Object(map) => { map }
+            _ => { return Err("Claim metadata is not an object".to_string()) }
+        };
+
+        let flag = |name: &str| -> Result<bool, String> {
+            match map.get(name) {
+                None => { Ok(false) }
+                Some(Value::Bool(flag)) => { Ok(*flag) }
+                Some(_) => { Err(format!("Claim metadata field {name} is not a boolean")) }
+            }
+        };
+
+        let data_type = match map.get("type") {
+            None => { None }
+            Some(Value::String(name)) => { Some(ClaimDataType::from_name(name)?) }
+            Some(_) => { return Err("Claim metadata field type is not a string".to_string()) }
+        };
+
+        Ok(ClaimMetadata { mandatory: flag("mandatory")?, sensitive: flag("sensitive")?, data_type })
+    }
+
+    /// Converts the metadata to its JSON representation.
+    ///
+    /// # Returns
+    /// The JSON object containing the fields of the metadata.
+    pub fn to_json(&self) -> Value {
+        let mut map: Map<String, Value> = Map::new();
+        map.insert("mandatory".to_string(), Value::Bool(self.mandatory));
+        map.insert("sensitive".to_string(), Value::Bool(self.sensitive));
+        if let Some(data_type) = self.data_type {
+            map.insert("type".to_string(), Value::String(data_type.name().to_string()));
+        }
+
+        Value::Object(map)
+    }
+
+    /// Retrieves the metadata of every tagged claim from a VC or a derived credential.
+    ///
+    /// # Arguments
+    /// * `map` - VC or derived credential, possibly containing the `claimMetadata` sidecar.
+    ///
+    /// # Returns
+    /// A result containing the metadata indexed by claim name, empty if the sidecar is absent, or a string containing an error.
+    pub fn extract(map: &Map<String, Value>) -> Result<BTreeMap<String, ClaimMetadata>, String> {
+        let sidecar = match map.get(CLAIM_METADATA) {
+            None => { return Ok(BTreeMap::new()) }
+            Some(Value::Object(sidecar)) => { sidecar }
+            Some(_) => { return Err(format!("{CLAIM_METADATA} field is not an object")) }
+        };
+
+        let mut metadata: BTreeMap<String, ClaimMetadata> = BTreeMap::new();
+        for (claim, value) in sidecar {
+            match Self::from_json(value) {
+                Ok(claim_metadata) => { metadata.insert(claim.clone(), claim_metadata); }
+                Err(err) => { return Err(format!("Invalid metadata for claim {claim}: [{err}]")) }
+            };
+        }
+
+        Ok(metadata)
+    }
+
+    /// Merges the metadata of claims serialized together into a single string claim: the merged claim is mandatory or
+    /// sensitive if any of the claims is.
+    ///
+    /// # Arguments
+    /// * `metadata` - Metadata of the tagged claims among those merged.
+    ///
+    /// # Returns
+    /// The metadata of the merged claim, or none if none of the claims is tagged.
+    pub fn bundle(metadata: &[ClaimMetadata]) -> Option<ClaimMetadata> {
+        if metadata.is_empty() {
+            return None;
+        }
+
+        Some(ClaimMetadata {
+            mandatory: metadata.iter().any(|claim_metadata| claim_metadata.mandatory),
+            sensitive: metadata.iter().any(|claim_metadata| claim_metadata.sensitive),
+            data_type: Some(ClaimDataType::String),
+        })
+    }
+
+    /// Checks that every tagged claim is present among the claims and that its value matches the tagged data type.
+    ///
+    /// # Arguments
+    /// * `metadata` - Metadata indexed by claim name.
+    /// * `claims` - Claims of the VC.
+    ///
+    /// # Returns
+    /// Returns a string containing an error if a tag does not match the claims.
+    pub fn check_claims(metadata: &BTreeMap<String, ClaimMetadata>, claims: &Map<String, Value>) -> Result<(), String> {
+        for (claim, claim_metadata) in metadata {
+            let value = match claims.get(claim) {
+                Some(value) => { value }
+                None => { return Err(format!("Metadata refers to the missing claim {claim}")) }
+            };
+
+            if let Some(data_type) = claim_metadata.data_type {
+                if !data_type.matches(value) {
+                    return Err(format!("Claim {claim} is not of type {}", data_type.name()));
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,288 @@
+use std::collections::BTreeMap;
+use serde_json::{Map, Value};
+use crate::adapters::adapter::Adapter;
+use crate::claim_metadata::ClaimMetadata;
+use crate::common_data::{CLAIMS, CLAIM_METADATA};
+use crate::disclosure::DisclosureRequest;
+
+/// Separator of the names of the claims merged into a bundle, e.g. `birthdate+name`.
+pub const BUNDLE_SEPARATOR: &str = "+";
+
+
+/// Presentations a verifier requests.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct VerifierPolicy {
+    /// Claims and predicates requested by each presentation.
+    pub requests: Vec<DisclosureRequest>,
+}
+
+
+/// Advice on the claims of a credential given the policy of its verifiers: claims that are never requested can be
+/// omitted at issuance, and claims that are always requested together can be merged into a single bundle claim, so that
+/// they cost a single salt, leaf, witness or message instead of one each.
+///
+/// # Examples
+/// ```
+/// use serde_json::{json, Map, Value};
+/// use csd_jwt::claim_minimization::{expand_bundles, MinimizationAdvice, VerifierPolicy};
+/// use csd_jwt::disclosure::DisclosureRequest;
+///
+/// let raw_vc: Map<String, Value> = json!({
+///     "credentialSubject": {"name": "Alice", "surname": "Smith", "birthdate": "2000-01-01", "nationality": "IT"}
+/// }).as_object().unwrap().clone();
+/// let policy = VerifierPolicy { requests: vec![
+///     DisclosureRequest::new(vec!["name".to_string(), "surname".to_string()]),
+///     DisclosureRequest::new(vec!["name".to_string(), "surname".to_string()]).with_optional(vec!["birthdate".to_string()]),
+/// ] };
+///
+/// let advice = MinimizationAdvice::analyze(&raw_vc, &policy).unwrap();
+/// assert_eq!(advice.omitted, vec!["nationality".to_string()]);
+/// assert_eq!(advice.bundles, vec![vec!["name".to_string(), "surname".to_string()]]);
+///
+/// let minimized_vc = advice.apply(&raw_vc).unwrap();
+/// let claims = minimized_vc["credentialSubject"].as_object().unwrap();
+/// assert_eq!(claims.keys().collect::<Vec<&String>>(), vec!["birthdate", "name+surname"]);
+/// let response = policy.requests[1].resolve(raw_vc["credentialSubject"].as_object().unwrap()).unwrap();
+/// assert_eq!(advice.disclosures(&response.disclosed).unwrap(), vec!["name+surname".to_string(), "birthdate".to_string()]);
+/// assert_eq!(expand_bundles(claims).unwrap()["surname"], "Smith");
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MinimizationAdvice {
+    /// Claims never requested by the verifiers.
+    pub omitted: Vec<String>,
+    /// Groups of at least two claims requested by exactly the same presentations, each sorted by name.
+    pub bundles: Vec<Vec<String>>,
+}
+
+impl MinimizationAdvice {
+
+    /// Analyzes the claims of a raw VC against the policy of its verifiers. Claims tagged as mandatory count as
+    /// requested by every presentation, optional claims count as requested, and claims whose name contains the bundle
+    /// separator are never bundled.
+    ///
+    /// # Arguments
+    /// * `raw_vc` - Template VC to be issued.
+    /// * `policy` - Presentations requested by the verifiers.
+    ///
+    /// # Returns
+    /// A result containing the advice or a string containing an error if the policy requires an unknown claim.
+    pub fn analyze(raw_vc: &Map<String, Value>, policy: &VerifierPolicy) -> Result<Self, String> {
+        let claims: &Map<String, Value> = match raw_vc.get(CLAIMS) {
+            Some(Value::Object(claims)) => { claims }
+            _ => { return Err(format!("Raw VC does not contain the {CLAIMS} object")) }
+        };
+        if policy.requests.is_empty() {
+            return Err("Policy does not request any presentation".to_string());
+        }
+        for claim in policy.requests.iter().flat_map(DisclosureRequest::mandatory_claims) {
+            if !claims.contains_key(&claim) {
+                return Err(format!("Policy requests the claim {claim}, which is not in the credential"));
+            }
+        }
+
+        let metadata: BTreeMap<String, ClaimMetadata> = ClaimMetadata::extract(raw_vc)?;
+        let mut omitted: Vec<String> = vec![];
+        let mut groups: BTreeMap<Vec<bool>, Vec<String>> = BTreeMap::new();
+
+        for claim in claims.keys() {
+            let mandatory: bool = metadata.get(claim).is_some_and(|claim_metadata| claim_metadata.mandatory);
+            let requested_by: Vec<bool> = policy.requests.iter().map(|request| mandatory || request.requests(claim)).collect();
+
+            if !requested_by.contains(&true) {
+                omitted.push(claim.clone());
+            } else if !claim.contains(BUNDLE_SEPARATOR) {
+                groups.entry(requested_by).or_default().push(claim.clone());
+            }
+        }
+
+        let mut bundles: Vec<Vec<String>> = groups.into_values().filter(|group| group.len() > 1).collect();
+        bundles.sort();
+
+        Ok(MinimizationAdvice { omitted, bundles })
+    }
+
+    /// Name of the claim a bundle is merged into.
+    ///
+    /// # Arguments
+    /// * `bundle` - Names of the claims of the bundle.
+    ///
+    /// # Returns
+    /// The names joined by the bundle separator.
+    pub fn bundle_name(bundle: &[String]) -> String {
+        bundle.join(BUNDLE_SEPARATOR)
+    }
+
+    /// Applies the advice to a raw VC before issuance: omitted claims are removed, and the claims of every bundle are
+    /// replaced by a single claim whose value is the canonical serialization of the object holding them, so that every
+    /// algorithm can sign it as a string. The claim metadata of a bundle is mandatory or sensitive if the metadata of
+    /// any of its claims is.
+    ///
+    /// # Arguments
+    /// * `raw_vc` - Template VC to be issued.
+    ///
+    /// # Returns
+    /// A result containing the minimized raw VC or a string containing an error.
+    pub fn apply(&self, raw_vc: &Map<String, Value>) -> Result<Map<String, Value>, String> {
+        let mut minimized_vc: Map<String, Value> = raw_vc.clone();
+        let mut claims: Map<String, Value> = match minimized_vc.remove(CLAIMS) {
+            Some(Value::Object(claims)) => { claims }
+            _ => { return Err(format!("Raw VC does not contain the {CLAIMS} object")) }
+        };
+        let mut metadata: BTreeMap<String, ClaimMetadata> = ClaimMetadata::extract(raw_vc)?;
+
+        for claim in &self.omitted {
+            claims.remove(claim);
+            metadata.remove(claim);
+        }
+
+        for bundle in &self.bundles {
+            let mut parts: Map<String, Value> = Map::new();
+            let mut bundled_metadata: Vec<ClaimMetadata> = vec![];
+            for claim in bundle {
+                match claims.remove(claim) {
+                    Some(value) => { parts.insert(claim.clone(), value); }
+                    None => { return Err(format!("Bundled claim {claim} is not in the credential")) }
+                }
+                bundled_metadata.extend(metadata.remove(claim));
+            }
+
+            let name: String = Self::bundle_name(bundle);
+            claims.insert(name.clone(), Value::String(Value::Object(parts).to_string()));
+            if let Some(bundle_metadata) = ClaimMetadata::bundle(&bundled_metadata) {
+                metadata.insert(name, bundle_metadata);
+            }
+        }
+
+        minimized_vc.insert(CLAIMS.to_string(), Value::Object(claims));
+        if minimized_vc.contains_key(CLAIM_METADATA) {
+            let sidecar: Map<String, Value> = metadata.into_iter().map(|(claim, claim_metadata)| (claim, claim_metadata.to_json())).collect();
+            minimized_vc.insert(CLAIM_METADATA.to_string(), Value::Object(sidecar));
+        }
+
+        Ok(minimized_vc)
+    }
+
+    /// Translates the claims requested by a presentation into the disclosures of a VC the advice was applied to.
+    ///
+    /// # Arguments
+    /// * `request` - Claims requested by the presentation.
+    ///
+    /// # Returns
+    /// A result containing the disclosures, with every bundle disclosed once, or a string containing an error if an
+    /// omitted claim is requested.
+    pub fn disclosures(&self, request: &[String]) -> Result<Vec<String>, String> {
+        let mut disclosures: Vec<String> = vec![];
+
+        for claim in request {
+            if self.omitted.contains(claim) {
+                return Err(format!("Claim {claim} was omitted at issuance"));
+            }
+            let disclosure: String = match self.bundles.iter().find(|bundle| bundle.contains(claim)) {
+                Some(bundle) => { Self::bundle_name(bundle) }
+                None => { claim.clone() }
+            };
+            if !disclosures.contains(&disclosure) {
+                disclosures.push(disclosure);
+            }
+        }
+
+        Ok(disclosures)
+    }
+
+    /// Measures the effect of the advice with an algorithm, issuing the VC with and without it and presenting it to
+    /// every request of the policy.
+    ///
+    /// # Arguments
+    /// * `adapter` - Adapter of the algorithm.
+    /// * `raw_vc` - Template VC to be issued.
+    /// * `policy` - Presentations requested by the verifiers.
+    ///
+    /// # Returns
+    /// A result containing the report or a string containing an error.
+    ///
+    /// # Examples
+    /// ```
+    /// use serde_json::{Map, Value};
+    /// use csd_jwt::adapters::adapter::Adapter;
+    /// use csd_jwt::adapters::hashes::merkle_tree_adapter::MerkleTreeAdapter;
+    /// use csd_jwt::claim_minimization::{MinimizationAdvice, VerifierPolicy};
+    /// use csd_jwt::common_data::VC;
+    /// use csd_jwt::disclosure::DisclosureRequest;
+    ///
+    /// let raw_vc: Map<String, Value> = serde_json::from_str(VC).unwrap();
+    /// let policy = VerifierPolicy { requests: vec![DisclosureRequest::new(vec!["name".to_string(), "birthdate".to_string()])] };
+    /// let advice = MinimizationAdvice::analyze(&raw_vc, &policy).unwrap();
+    ///
+    /// let report = advice.evaluate(&MerkleTreeAdapter::new(13).unwrap(), &raw_vc, &policy).unwrap();
+    /// assert_eq!(report.algorithm, "MERKLE");
+    /// assert!(report.minimized_vc_jwt_length < report.vc_jwt_length);
+    /// assert!(report.minimized_vp_jwt_length < report.vp_jwt_length);
+    /// ```
+    pub fn evaluate(&self, adapter: &dyn Adapter, raw_vc: &Map<String, Value>, policy: &VerifierPolicy) -> Result<MinimizationReport, String> {
+        let (vc, vc_jwt) = adapter.issue_vc(raw_vc)?;
+        let (minimized_vc, minimized_vc_jwt) = adapter.issue_vc(&self.apply(raw_vc)?)?;
+
+        let mut report: MinimizationReport = MinimizationReport {
+            algorithm: adapter.sd_algorithm(),
+            vc_jwt_length: vc_jwt.len(),
+            minimized_vc_jwt_length: minimized_vc_jwt.len(),
+            ..MinimizationReport::default()
+        };
+        for request in &policy.requests {
+            let (_, vp_jwt, response) = adapter.issue_vp_for_request(&vc, request, None)?;
+            let (_, minimized_vp_jwt) = adapter.issue_vp(&minimized_vc, &self.disclosures(&response.disclosed)?)?;
+            report.vp_jwt_length += vp_jwt.len();
+            report.minimized_vp_jwt_length += minimized_vp_jwt.len();
+        }
+
+        Ok(report)
+    }
+}
+
+
+/// Sizes of the credentials and presentations of an algorithm with and without the minimization advice.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MinimizationReport {
+    /// Name of the algorithm.
+    pub algorithm: String,
+    /// Length in bytes of the VC JWT issued from the raw VC.
+    pub vc_jwt_length: usize,
+    /// Length in bytes of the VC JWT issued from the minimized raw VC.
+    pub minimized_vc_jwt_length: usize,
+    /// Total length in bytes of the VP JWTs answering every request of the policy.
+    pub vp_jwt_length: usize,
+    /// Total length in bytes of the VP JWTs answering every request of the policy with the minimized VC.
+    pub minimized_vp_jwt_length: usize,
+}
+
+
+/// Expands the bundle claims of a presentation back into the claims they merge, on the verifier side. Claims that are
+/// not bundles are kept as they are.
+///
+/// # Arguments
+/// * `claims` - Disclosed claims.
+///
+/// # Returns
+/// A result containing the expanded claims or a string containing an error if a bundle does not hold its claims.
+pub fn expand_bundles(claims: &Map<String, Value>) -> Result<Map<String, Value>, String> {
+    let mut expanded_claims: Map<String, Value> = Map::new();
+
+    for (name, value) in claims {
+        let bundled_claims: Option<Map<String, Value>> = match value {
+            Value::String(serialized) if name.contains(BUNDLE_SEPARATOR) => { serde_json::from_str::<Map<String, Value>>(serialized).ok() }
+            _ => { None }
+        };
+
+        match bundled_claims {
+            Some(bundled_claims) => {
+                if !bundled_claims.keys().eq(name.split(BUNDLE_SEPARATOR)) {
+                    return Err(format!("Bundle {name} does not hold exactly its claims"));
+                }
+                expanded_claims.extend(bundled_claims);
+            }
+            None => { expanded_claims.insert(name.clone(), value.clone()); }
+        }
+    }
+
+    Ok(expanded_claims)
+}
@@ -0,0 +1,143 @@
+use serde_json::{Map, Value};
+use crate::adapters::adapter::VerifierAdapter;
+use crate::audit::{split_claim_proofs, VALUE};
+use crate::convert::lower_vc;
+
+/// Comparison of the claims of a VC with the claims revealed by a VP derived from it, e.g. to show a holder what a
+/// presentation is about to share before sending it.
+///
+/// # Examples
+/// ```
+/// use serde_json::json;
+/// use csd_jwt::claims_diff::ClaimsDiff;
+///
+/// let vc_claims = json!({"name": "Ada", "birthdate": "1815-12-10", "field": "Mathematics"});
+/// let vp_claims = json!({"name": "Ada", "field": "Poetry", "nationality": "British"});
+/// let diff = ClaimsDiff::new(vc_claims.as_object().unwrap(), vp_claims.as_object().unwrap());
+///
+/// assert_eq!(diff.disclosed, vec!["field", "name"]);
+/// assert_eq!(diff.withheld, vec!["birthdate"]);
+/// assert_eq!(diff.unexpected, vec!["nationality"]);
+/// assert_eq!(diff.altered, vec!["field"]);
+/// assert!(!diff.is_faithful());
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ClaimsDiff {
+    /// Claims of the VC revealed by the VP, in alphabetical order.
+    pub disclosed: Vec<String>,
+    /// Claims of the VC not revealed by the VP, in alphabetical order.
+    pub withheld: Vec<String>,
+    /// Claims revealed by the VP that the VC does not contain, in alphabetical order.
+    pub unexpected: Vec<String>,
+    /// Disclosed claims whose value in the VP differs from the one in the VC, in alphabetical order.
+    pub altered: Vec<String>,
+}
+
+impl ClaimsDiff {
+
+    /// Compares the claims of a VC with the claims revealed by a VP.
+    ///
+    /// # Arguments
+    /// * `vc_claims` - Claims of the VC.
+    /// * `vp_claims` - Claims revealed by the VP.
+    ///
+    /// # Returns
+    /// The comparison.
+    pub fn new(vc_claims: &Map<String, Value>, vp_claims: &Map<String, Value>) -> Self {
+        let mut diff: ClaimsDiff = ClaimsDiff::default();
+
+        for (name, value) in vc_claims {
+            match vp_claims.get(name) {
+                Some(revealed) => {
+                    diff.disclosed.push(name.clone());
+                    if revealed != value {
+                        diff.altered.push(name.clone());
+                    }
+                }
+                None => { diff.withheld.push(name.clone()) }
+            }
+        }
+        diff.unexpected.extend(vp_claims.keys().filter(|name| !vc_claims.contains_key(*name)).cloned());
+
+        for names in [&mut diff.disclosed, &mut diff.withheld, &mut diff.unexpected, &mut diff.altered] {
+            names.sort();
+        }
+        diff
+    }
+
+    /// Whether the VP reveals only claims of the VC, with their issued values.
+    pub fn is_faithful(&self) -> bool {
+        self.unexpected.is_empty() && self.altered.is_empty()
+    }
+
+    /// Checks that the VP faithfully reveals exactly the requested claims of the VC.
+    ///
+    /// # Arguments
+    /// * `disclosures` - Claims requested to be disclosed, in any order.
+    ///
+    /// # Returns
+    /// Returns a string describing the first mismatch, if any.
+    pub fn check_disclosures(&self, disclosures: &[String]) -> Result<(), String> {
+        if !self.unexpected.is_empty() {
+            return Err(format!("VP reveals claims not contained in the VC: {:?}", self.unexpected));
+        }
+        if !self.altered.is_empty() {
+            return Err(format!("VP reveals values differing from the VC for claims {:?}", self.altered));
+        }
+
+        let mut requested: Vec<String> = disclosures.to_vec();
+        requested.sort();
+        requested.dedup();
+        if requested != self.disclosed {
+            return Err(format!("VP reveals claims {:?} instead of the requested {:?}", self.disclosed, requested));
+        }
+
+        Ok(())
+    }
+}
+
+
+/// Compares the claims of a VC with the claims revealed by a VP derived from it, reading the revealed values from the
+/// claim proofs of the VP, with the same algorithm-specific logic as `audit::audit_document`.
+///
+/// # Arguments
+/// * `verifier` - Verifier of the algorithm of the VC and VP, or the adapter itself.
+/// * `vc` - Verifiable Credential.
+/// * `vp` - Verifiable Presentation derived from the VC.
+///
+/// # Returns
+/// A result containing the comparison or a string containing an error if the claims cannot be read.
+///
+/// # Examples
+/// ```
+/// use csd_jwt::adapters::adapter::Adapter;
+/// use csd_jwt::adapters::signatures::bbs_plus_adapter::BBSPlusAdapter;
+/// use csd_jwt::claims_diff::diff_claims;
+/// use csd_jwt::common_data::VC;
+///
+/// let adapter = BBSPlusAdapter::new(13).unwrap();
+/// let (vc, _) = adapter.issue_vc(&serde_json::from_str(VC).unwrap()).unwrap();
+/// let (vp, _) = adapter.issue_vp(&vc, &vec!["name".to_string()]).unwrap();
+///
+/// let diff = diff_claims(&adapter, &vc, &vp).unwrap();
+/// assert_eq!(diff.disclosed, vec!["name"]);
+/// assert_eq!(diff.withheld.len(), 12);
+/// assert!(diff.is_faithful());
+/// assert!(diff.check_disclosures(&["name".to_string()]).is_ok());
+/// assert!(diff.check_disclosures(&["name".to_string(), "field".to_string()]).is_err());
+/// ```
+pub fn diff_claims<V: VerifierAdapter + ?Sized>(verifier: &V, vc: &Map<String, Value>, vp: &Map<String, Value>) -> Result<ClaimsDiff, String> {
+    let vc_claims: Map<String, Value> = lower_vc(vc, &verifier.sd_algorithm())?.claims().clone();
+
+    let claim_proofs: Map<String, Value> = verifier.claim_proofs(vp)?;
+    let (_shared, proofs) = split_claim_proofs(&claim_proofs)?;
+    let mut vp_claims: Map<String, Value> = Map::new();
+    for (name, proof) in proofs {
+        match proof.get(VALUE) {
+            Some(value) => { vp_claims.insert(name.clone(), value.clone()); }
+            None => { return Err(format!("Proof of claim {name} does not contain its {VALUE}")) }
+        }
+    }
+
+    Ok(ClaimsDiff::new(&vc_claims, &vp_claims))
+}
@@ -0,0 +1,73 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+use crate::deterministic::unix_time;
+
+/// Source of the current time of issuers and verifiers, injected so that the validity period of credentials and the
+/// freshness of presentations can be tested deterministically and long-lived credentials simulated without sleeping.
+pub trait Clock: Send + Sync {
+
+    /// Current time.
+    ///
+    /// # Returns
+    /// A result containing the time in seconds since the UNIX epoch or a string containing an error.
+    fn now(&self) -> Result<u64, String>;
+}
+
+
+/// Clock reading the system time, or the timestamp injected by the deterministic mode if enabled.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Result<u64, String> {
+        unix_time()
+    }
+}
+
+
+/// Clock standing still at a simulated time until it is explicitly set or advanced, shared by the issuer and verifiers
+/// of a simulation through an `Arc`.
+///
+/// # Examples
+/// ```
+/// use std::time::Duration;
+/// use csd_jwt::clock::{Clock, SimulatedClock};
+///
+/// let clock = SimulatedClock::new(1_700_000_000);
+/// clock.advance(Duration::from_secs(86_400));
+/// assert_eq!(clock.now().unwrap(), 1_700_086_400);
+/// clock.set(1_600_000_000);
+/// assert_eq!(clock.now().unwrap(), 1_600_000_000);
+/// ```
+#[derive(Debug, Default)]
+pub struct SimulatedClock {
+    /// Simulated time in seconds since the UNIX epoch.
+    now: AtomicU64,
+}
+
+impl SimulatedClock {
+
+    /// Constructor for a clock standing at the given time.
+    ///
+    /// # Arguments
+    /// * `now` - Simulated time in seconds since the UNIX epoch.
+    pub fn new(now: u64) -> Self {
+        SimulatedClock { now: AtomicU64::new(now) }
+    }
+
+    /// Moves the clock to the given time, possibly backwards.
+    pub fn set(&self, now: u64) {
+        self.now.store(now, Ordering::Relaxed);
+    }
+
+    /// Moves the clock forward by the given duration, in whole seconds.
+    pub fn advance(&self, duration: Duration) {
+        self.now.fetch_add(duration.as_secs(), Ordering::Relaxed);
+    }
+}
+
+impl Clock for SimulatedClock {
+    fn now(&self) -> Result<u64, String> {
+        Ok(self.now.load(Ordering::Relaxed))
+    }
+}
@@ -15,6 +15,38 @@ pub const HEADER: &str = "header";
 pub const SIGNATURE: &str = "signature";
 /// Key for claims in the VC.
 pub const CLAIMS: &str = "credentialSubject";
+/// Key for the presentation in the VP JWT payload.
+pub const VP: &str = "vp";
+/// Key for the credentials held in a presentation.
+pub const VERIFIABLE_CREDENTIAL: &str = "verifiableCredential";
+/// Context of Verifiable Presentations.
+pub const VP_CONTEXT: &str = "https://www.w3.org/ns/credentials/v2";
+/// Type of Verifiable Presentations.
+pub const VP_TYPE: &str = "VerifiablePresentation";
+/// Key for the nonce in the VP JWT payload.
+pub const PRESENTATION_NONCE: &str = "nonce";
+/// Key for the audience in the VP JWT payload.
+pub const AUDIENCE: &str = "aud";
+/// Key for the issuance time of a VC or VP, in seconds since the UNIX epoch.
+pub const ISSUED_AT: &str = "iat";
+/// Key for the expiration time of a VC or VP, in seconds since the UNIX epoch.
+pub const EXPIRATION: &str = "exp";
+/// Key for the start of the validity window of a VP, in seconds since the UNIX epoch.
+pub const NOT_BEFORE: &str = "nbf";
+/// Key for the per-claim metadata sidecar in the VC.
+pub const CLAIM_METADATA: &str = "claimMetadata";
+/// Key for the claim groups sidecar in the VC, listing the claims merged into every group.
+pub const CLAIM_GROUPS: &str = "claimGroups";
+/// Key for the confirmation claim binding the VC to the holder key.
+pub const CNF: &str = "cnf";
+/// Key for the JWK thumbprint of the holder key inside the confirmation claim.
+pub const JWK_THUMBPRINT: &str = "jkt";
+/// Key for the identifier of the issuer in the VC.
+pub const ISSUER: &str = "issuer";
+/// Key for the identifier of the VC.
+pub const CREDENTIAL_ID: &str = "id";
+/// Key for the identifier of the VC a re-issued VC replaces.
+pub const PREVIOUS_CREDENTIAL: &str = "previousCredential";
 
 /// Mock VC.
 pub const VC: &str = r#"{
@@ -0,0 +1,256 @@
+use std::io::{Read, Write};
+use flate2::read::DeflateDecoder;
+use flate2::write::DeflateEncoder;
+use flate2::Compression as DeflateLevel;
+use josekit::jws;
+use josekit::jws::{JwsHeader, JwsSigner, JwsVerifier};
+use josekit::jwt::JwtPayload;
+use serde_json::{Map, Value};
+use crate::encoding::Encoding;
+use crate::json;
+
+/// Name of the JWT header parameter declaring the compression of the payload, as defined for JWE by RFC 7516.
+pub const COMPRESSION_HEADER: &str = "zip";
+/// Algorithm of the JWT header parameter for a raw DEFLATE (RFC 1951) compression.
+const DEFLATE: &str = "DEF";
+
+/// Compressions of the JWT payloads, applied before the base64url encoding and reversed on decoding. The `zip` header
+/// is standard for JWE only: applying it to JWS, and to unsecured VC JWTs, is a non-standard extension that other
+/// implementations are not expected to understand.
+///
+/// # Examples
+/// ```
+/// use csd_jwt::compression::Compression;
+///
+/// let payload = br#"{"claims":["aaaaaaaa","aaaaaaaa","aaaaaaaa"]}"#;
+/// let compressed = Compression::Deflate.compress(payload).unwrap();
+/// assert!(compressed.len() < payload.len());
+/// assert_eq!(Compression::Deflate.decompress(&compressed, 1024).unwrap(), payload);
+/// assert!(Compression::Deflate.decompress(&compressed, 8).is_err());
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Compression {
+    /// Payload encoded as is, without the `zip` header.
+    #[default]
+    None,
+    /// Payload compressed with raw DEFLATE, declared as `zip: DEF`.
+    Deflate,
+}
+
+impl Compression {
+
+    /// Value of the `zip` header parameter, if any.
+    ///
+    /// # Returns
+    /// The name of the compression algorithm, or none for uncompressed payloads.
+    pub fn name(&self) -> Option<&'static str> {
+        match self {
+            Compression::None => { None }
+            Compression::Deflate => { Some(DEFLATE) }
+        }
+    }
+
+    /// Retrieves the compression declared in a JWT header.
+    ///
+    /// # Arguments
+    /// * `header` - Claims of the JWT header.
+    ///
+    /// # Returns
+    /// A result containing the compression or a string containing an error if it is unknown.
+    pub fn from_header(header: &Map<String, Value>) -> Result<Self, String> {
+        match header.get(COMPRESSION_HEADER) {
+            None => { Ok(Compression::None) }
+            Some(Value::String(name)) if name == DEFLATE => { Ok(Compression::Deflate) }
+            Some(value) => { Err(format!("Unsupported compression {value} in JWT header")) }
+        }
+    }
+
+    /// Compresses a payload.
+    ///
+    /// # Arguments
+    /// * `bytes` - Serialized payload.
+    ///
+    /// # Returns
+    /// A result containing the compressed payload or a string containing an error.
+    pub fn compress(&self, bytes: &[u8]) -> Result<Vec<u8>, String> {
+        match self {
+            Compression::None => { Ok(bytes.to_vec()) }
+            Compression::Deflate => {
+                let mut encoder = DeflateEncoder::new(Vec::new(), DeflateLevel::best());
+                if let Err(err) = encoder.write_all(bytes) {
+                    return Err(format!("Failed to compress payload: [{err}]"));
+                }
+                match encoder.finish() {
+                    Ok(compressed) => { Ok(compressed) }
+                    Err(err) => { Err(format!("Failed to compress payload: [{err}]")) }
+                }
+            }
+        }
+    }
+
+    /// Decompresses a payload, refusing to inflate it beyond a limit so that a small hostile JWT cannot exhaust the
+    /// memory of the verifier.
+    ///
+    /// # Arguments
+    /// * `bytes` - Compressed payload.
+    /// * `max_len` - Maximum length in bytes of the decompressed payload.
+    ///
+    /// # Returns
+    /// A result containing the serialized payload or a string containing an error.
+    pub fn decompress(&self, bytes: &[u8], max_len: usize) -> Result<Vec<u8>, String> {
+        let decompressed: Vec<u8> = match self {
+            Compression::None => { bytes.to_vec() }
+            Compression::Deflate => {
+                let mut decompressed: Vec<u8> = vec![];
+                if let Err(err) = DeflateDecoder::new(bytes).take(max_len as u64 + 1).read_to_end(&mut decompressed) {
+                    return Err(format!("Failed to decompress payload: [{err}]"));
+                }
+                decompressed
+            }
+        };
+
+        if decompressed.len() > max_len {
+            return Err(format!("Decompressed payload exceeds the limit of {max_len} bytes"));
+        }
+        Ok(decompressed)
+    }
+}
+
+
+/// Encodes a JWT in compact serialization, compressing its payload and declaring the compression in its header. The
+/// JWT is signed if a signer is given, and unsecured otherwise.
+///
+/// # Arguments
+/// * `header` - Header of the JWT.
+/// * `payload` - Payload of the JWT.
+/// * `compression` - Compression of the payload.
+/// * `signer` - Optional signer of the JWT.
+///
+/// # Returns
+/// A result containing the encoded JWT or a string containing an error.
+pub fn encode_compact(header: &JwsHeader, payload: &JwtPayload, compression: Compression, signer: Option<&dyn JwsSigner>) -> Result<String, String> {
+    let mut header: JwsHeader = header.clone();
+    if let Some(name) = compression.name() {
+        if let Err(err) = header.set_claim(COMPRESSION_HEADER, Some(Value::String(name.to_string()))) {
+            return Err(format!("Failed to set compression in header: [{err}]"));
+        }
+    }
+    let compressed_payload: Vec<u8> = compression.compress(payload.to_string().as_bytes())?;
+
+    match signer {
+        Some(signer) => match jws::serialize_compact(&compressed_payload, &header, signer) {
+            Ok(jwt) => { Ok(jwt) }
+            Err(err) => { Err(format!("Failed to encode and sign jwt: [{err}]")) }
+        },
+        None => {
+            header.set_algorithm("none");
+            Ok(format!("{}.{}.", Encoding::Base64UrlNoPad.encode(header.to_string()), Encoding::Base64UrlNoPad.encode(compressed_payload)))
+        }
+    }
+}
+
+/// Decodes a JWT in compact serialization, verifying it if a verifier is given and decompressing its payload according
+/// to its header. Unsecured JWTs must declare the `none` algorithm and carry an empty signature.
+///
+/// # Arguments
+/// * `jwt` - Encoded JWT.
+/// * `verifier` - Optional verifier of the JWT.
+/// * `max_len` - Maximum length in bytes of the decompressed payload.
+///
+/// # Returns
+/// A result containing the claims and the header of the JWT or a string containing an error.
+pub fn decode_compact(jwt: &str, verifier: Option<&dyn JwsVerifier>, max_len: usize) -> Result<(Map<String, Value>, JwsHeader), String> {
+    let (header, encoded_payload): (JwsHeader, Vec<u8>) = match verifier {
+        Some(verifier) => match jws::deserialize_compact(jwt, verifier) {
+            Ok((payload, header)) => { (header, payload) }
+            Err(err) => { return Err(format!("Failed to decode and verify jwt: [{err}]")) }
+        },
+        None => {
+            let (encoded_header, encoded_payload) = match jwt.split('.').collect::<Vec<&str>>().as_slice() {
+                [encoded_header, encoded_payload, ""] => { (*encoded_header, *encoded_payload) }
+                _ => { return Err("Unsecured jwt is not of the form header.payload.".to_string()) }
+            };
+            let header: JwsHeader = match JwsHeader::from_map(decode_json(encoded_header, "header")?) {
+                Ok(header) => { header }
+                Err(err) => { return Err(format!("Failed to parse header: [{err}]")) }
+            };
+            if header.algorithm() != Some("none") {
+                return Err("Unsecured jwt does not declare the none algorithm".to_string());
+            }
+            (header, Encoding::Base64UrlNoPad.decode(encoded_payload)?)
+        }
+    };
+
+    let payload: Vec<u8> = Compression::from_header(header.claims_set())?.decompress(&encoded_payload, max_len)?;
+    match json::from_slice::<Map<String, Value>>(&payload) {
+        Ok(claims) => { Ok((claims, header)) }
+        Err(err) => { Err(format!("Failed to parse payload: [{err}]")) }
+    }
+}
+
+/// Decodes the payload of a JWT without verifying it, decompressing it according to the header.
+///
+/// # Arguments
+/// * `header` - Claims of the JWT header.
+/// * `encoded_payload` - Base64url encoded payload.
+/// * `max_len` - Maximum length in bytes of the decompressed payload.
+///
+/// # Returns
+/// A result containing the claims or a string containing an error.
+pub fn decode_payload(header: &Map<String, Value>, encoded_payload: &str, max_len: usize) -> Result<Map<String, Value>, String> {
+    let payload: Vec<u8> = Compression::from_header(header)?.decompress(&Encoding::Base64UrlNoPad.decode(encoded_payload)?, max_len)?;
+    match json::from_slice::<Map<String, Value>>(&payload) {
+        Ok(claims) => { Ok(claims) }
+        Err(err) => { Err(format!("Failed to parse payload: [{err}]")) }
+    }
+}
+
+/// Length the JWT would have with its payload compressed with DEFLATE. The signature is kept as is, as the length of
+/// an ES256 signature does not depend on the payload, so the JWT does not need to be signed again.
+///
+/// # Arguments
+/// * `jwt` - Uncompressed JWT.
+///
+/// # Returns
+/// A result containing the length in bytes or a string containing an error.
+///
+/// # Examples
+/// ```
+/// use csd_jwt::adapters::adapter::Adapter;
+/// use csd_jwt::adapters::hashes::sd_jwt_adapter::SdJwtAdapter;
+/// use csd_jwt::common_data::VC;
+/// use csd_jwt::compression::compressed_jwt_length;
+///
+/// let adapter = SdJwtAdapter::new(4).unwrap();
+/// let (_, vc_jwt) = adapter.issue_vc(&serde_json::from_str(VC).unwrap()).unwrap();
+/// assert!(compressed_jwt_length(&vc_jwt).unwrap() < vc_jwt.len());
+/// assert!(compressed_jwt_length("not a jwt").is_err());
+/// ```
+pub fn compressed_jwt_length(jwt: &str) -> Result<usize, String> {
+    let (encoded_header, encoded_payload, signature) = match jwt.split('.').collect::<Vec<&str>>().as_slice() {
+        [encoded_header, encoded_payload, signature] => { (*encoded_header, *encoded_payload, *signature) }
+        _ => { return Err("JWT is not of the form header.payload.signature".to_string()) }
+    };
+
+    let mut header: Map<String, Value> = decode_json(encoded_header, "header")?;
+    if Compression::from_header(&header)? != Compression::None {
+        return Ok(jwt.len());
+    }
+    header.insert(COMPRESSION_HEADER.to_string(), Value::String(DEFLATE.to_string()));
+    let compressed_payload: Vec<u8> = Compression::Deflate.compress(&Encoding::Base64UrlNoPad.decode(encoded_payload)?)?;
+
+    Ok(Encoding::Base64UrlNoPad.encode(Value::Object(header).to_string()).len() + 1 + Encoding::Base64UrlNoPad.encode(compressed_payload).len() + 1 + signature.len())
+}
+
+/// Decodes a base64url JWT part into a JSON object.
+fn decode_json(part: &str, name: &str) -> Result<Map<String, Value>, String> {
+    let bytes: Vec<u8> = match Encoding::Base64UrlNoPad.decode(part) {
+        Ok(bytes) => { bytes }
+        Err(err) => { return Err(format!("Failed to decode {name}: [{err}]")) }
+    };
+
+    match serde_json::from_slice::<Map<String, Value>>(&bytes) {
+        Ok(map) => { Ok(map) }
+        Err(err) => { Err(format!("Failed to parse {name}: [{err}]")) }
+    }
+}
@@ -0,0 +1,530 @@
+use josekit::jws::{JwsHeader, ES256};
+use josekit::jwt;
+use josekit::jwt::JwtPayload;
+use serde_json::{Map, Value};
+use crate::adapters::adapter::Adapter;
+use crate::claims_diff::diff_claims;
+use crate::claim_generator::{ClaimGenerator, MultilingualClaimGenerator, Script};
+use crate::common_data::{CommonData, CLAIMS, VC, VERIFIABLE_CREDENTIAL, VP};
+use crate::encoding::Encoding;
+use crate::sd_algorithms::sd_algorithm::ENCODING_HEADER;
+
+/// Amount of claims in the credentials issued by the conformance suite.
+pub const CONFORMANCE_CLAIMS: usize = 8;
+
+/// Check of the conformance suite, by name.
+type Check = (&'static str, fn(&dyn Adapter) -> Result<(), String>);
+/// Decoded header and payload of a JWT.
+type HeaderAndPayload = (Map<String, Value>, Map<String, Value>);
+
+/// Outcome of a single check of the conformance suite.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ConformanceCheck {
+    /// Name of the check.
+    pub name: &'static str,
+    /// Error describing why the adapter did not meet the expectation, if any.
+    pub error: Option<String>,
+}
+
+/// Runs the whole conformance suite against a freshly created adapter.
+///
+/// The suite checks that an adapter:
+/// * issues VCs that verify and from which VPs disclosing every subset of claims verify (issuance round-trip);
+/// * reveals the values of exactly the disclosed claims, for several disclosure subsets;
+/// * proves exactly the disclosed claims, with their issued values and without additions, as reported by `diff_claims`;
+/// * either refuses to issue a VP without disclosures or issues one that verifies and reveals nothing;
+/// * reveals every claim when every claim is disclosed;
+/// * rejects VCs whose claim values are altered, or whose fields are swapped with the ones of another credential of the same issuer;
+/// * rejects VPs whose signature is altered, or whose components are swapped with the ones of another credential of the same issuer;
+/// * rejects VPs presenting the digest, leaf, witness or message of a disclosed claim under the name of another one;
+/// * encodes VCs whose JWT payload matches the returned map and that still verify after a JSON round-trip;
+/// * issues and presents claims whose names and values are non-ASCII UTF-8 text (CJK, Arabic, emoji, combining accents).
+///
+/// Claim values are revealed if they can be found in clear in the VP or in a component decoded with the encoding
+/// declared in the JWT header. Tamper checks re-sign the VP with the holder keys of `CommonData`, so the adapter must
+/// use them to sign its presentations.
+///
+/// # Returns
+/// A result containing a string listing all the failed checks, if any.
+///
+/// # Examples
+/// ```
+/// use csd_jwt::adapters::hashes::sd_jwt_adapter::SdJwtAdapter;
+/// use csd_jwt::conformance::run_conformance;
+///
+/// run_conformance::<SdJwtAdapter>().unwrap();
+/// ```
+pub fn run_conformance<A: Adapter>() -> Result<(), String> {
+    let adapter: A = A::new(CONFORMANCE_CLAIMS)?;
+    let failures: Vec<String> = run_conformance_checks(&adapter)
+        .into_iter()
+        .filter_map(|check| check.error.map(|error| format!("{}: {error}", check.name)))
+        .collect();
+
+    if failures.is_empty() {
+        Ok(())
+    } else {
+        Err(format!("[{}] {} conformance checks failed:\n{}", adapter.sd_algorithm(), failures.len(), failures.join("\n")))
+    }
+}
+
+/// Runs every check of the conformance suite against an adapter created with `CONFORMANCE_CLAIMS` claims.
+///
+/// # Arguments
+/// * `adapter` - Adapter under test.
+///
+/// # Returns
+/// The outcome of each check, in the order they were run.
+pub fn run_conformance_checks(adapter: &dyn Adapter) -> Vec<ConformanceCheck> {
+    let checks: [Check; 10] = [
+        ("issuance round-trip", check_round_trip),
+        ("disclosure subsets", check_disclosure_subsets),
+        ("claims diff", check_claims_diff),
+        ("empty disclosure", check_empty_disclosure),
+        ("full disclosure", check_full_disclosure),
+        ("VC tamper rejection", check_vc_tamper_rejection),
+        ("tamper rejection", check_tamper_rejection),
+        ("claim swap rejection", check_claim_swap_rejection),
+        ("serialization stability", check_serialization_stability),
+        ("non-ASCII claims", check_non_ascii_claims),
+    ];
+
+    checks.into_iter().map(|(name, check)| ConformanceCheck { name, error: check(adapter).err() }).collect()
+}
+
+
+/// Name of the claim at a given index, starting from 0.
+fn claim_name(index: usize) -> String {
+    format!("conformance claim {index:04}")
+}
+
+/// Value of the claim at a given index, starting from 0. The fixed width ensures no value contains another one.
+fn claim_value(index: usize, variant: &str) -> String {
+    format!("value of claim {index:04} {variant}")
+}
+
+/// Builds a raw VC holding `CONFORMANCE_CLAIMS` claims, whose values depend on the variant.
+fn raw_vc(variant: &str) -> Result<Map<String, Value>, String> {
+    let mut raw_vc: Map<String, Value> = match serde_json::from_str(VC) {
+        Ok(raw_vc) => { raw_vc }
+        Err(err) => { return Err(format!("Failed to parse the mock credential: [{err}]")) }
+    };
+
+    let claims: Map<String, Value> = (0..CONFORMANCE_CLAIMS).map(|index| (claim_name(index), Value::String(claim_value(index, variant)))).collect();
+    raw_vc.insert(CLAIMS.to_string(), Value::Object(claims));
+
+    Ok(raw_vc)
+}
+
+/// Issues and verifies a VC from the raw VC of the given variant.
+fn issue_verified_vc(adapter: &dyn Adapter, variant: &str) -> Result<(Map<String, Value>, String), String> {
+    let (vc, vc_jwt) = adapter.issue_vc(&raw_vc(variant)?)?;
+    adapter.verify_vc(&vc)?;
+    Ok((vc, vc_jwt))
+}
+
+/// Decodes a base64url JWT part into a JSON object.
+fn decode_part(part: &str) -> Result<Map<String, Value>, String> {
+    let bytes = match Encoding::Base64UrlNoPad.decode(part) {
+        Ok(bytes) => { bytes }
+        Err(err) => { return Err(format!("Failed to decode JWT part: [{err}]")) }
+    };
+
+    match serde_json::from_slice(&bytes) {
+        Ok(map) => { Ok(map) }
+        Err(err) => { Err(format!("Failed to parse JWT part: [{err}]")) }
+    }
+}
+
+/// Splits a JWT into its decoded header and payload.
+fn decode_header_and_payload(jwt: &str) -> Result<HeaderAndPayload, String> {
+    let parts: Vec<&str> = jwt.split('.').collect();
+    if parts.len() != 3 {
+        return Err(format!("JWT has {} parts instead of 3", parts.len()));
+    }
+
+    Ok((decode_part(parts[0])?, decode_part(parts[1])?))
+}
+
+/// Collects everything a verifier can read from a VP: the clear fields of the derived credential and its components
+/// decoded with the encoding declared in the header.
+fn revealed_text(vp_jwt: &str) -> Result<String, String> {
+    let (header, payload) = decode_header_and_payload(vp_jwt)?;
+    let encoding: Encoding = match header.get(ENCODING_HEADER) {
+        Some(Value::String(name)) => { Encoding::from_name(name)? }
+        _ => { Encoding::default() }
+    };
+
+    let credential: &Map<String, Value> = match payload.get(VP).and_then(|vp| vp.get(VERIFIABLE_CREDENTIAL)).and_then(|credentials| credentials.get(0)) {
+        Some(Value::Object(credential)) => { credential }
+        _ => { return Err("VP does not hold a credential".to_string()) }
+    };
+
+    let mut text: String = String::new();
+    for value in credential.values() {
+        match value {
+            Value::String(encoded) => match encoding.decode(encoded) {
+                Ok(decoded) => { text.push_str(&String::from_utf8_lossy(&decoded)) }
+                Err(_) => { text.push_str(encoded) }
+            },
+            _ => { text.push_str(&value.to_string()) }
+        }
+        text.push('\n');
+    }
+
+    Ok(text)
+}
+
+/// Issues and verifies a VP disclosing the claims at the given indices, then checks that exactly their values are revealed.
+fn check_disclosure(adapter: &dyn Adapter, vc: &Map<String, Value>, variant: &str, indices: &[usize]) -> Result<(), String> {
+    let disclosures: Vec<String> = indices.iter().map(|index| claim_name(*index)).collect();
+    let (_vp, vp_jwt) = adapter.issue_vp(vc, &disclosures)?;
+    adapter.verify_vp(&vp_jwt)?;
+
+    let text: String = revealed_text(&vp_jwt)?;
+    for index in 0..CONFORMANCE_CLAIMS {
+        let revealed = text.contains(&claim_value(index, variant));
+        match (indices.contains(&index), revealed) {
+            (true, false) => { return Err(format!("Disclosed claim {} is not revealed when disclosing {:?}", claim_name(index), indices)) }
+            (false, true) => { return Err(format!("Withheld claim {} is revealed when disclosing {:?}", claim_name(index), indices)) }
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
+
+/// Checks that a VC verifies and that VPs disclosing every claim verify, twice from the same VC.
+fn check_round_trip(adapter: &dyn Adapter) -> Result<(), String> {
+    let (vc, _vc_jwt) = issue_verified_vc(adapter, "a")?;
+    let disclosures: Vec<String> = (0..CONFORMANCE_CLAIMS).map(claim_name).collect();
+
+    let (_vp, vp_jwt) = adapter.issue_vp(&vc, &disclosures)?;
+    adapter.verify_vp(&vp_jwt)?;
+
+    // Presentations must not consume the credential.
+    let (_vp, vp_jwt) = adapter.issue_vp(&vc, &disclosures)?;
+    adapter.verify_vp(&vp_jwt)
+}
+
+/// Checks that VPs disclosing several subsets of claims verify and reveal exactly the disclosed values.
+fn check_disclosure_subsets(adapter: &dyn Adapter) -> Result<(), String> {
+    let (vc, _vc_jwt) = issue_verified_vc(adapter, "a")?;
+    let subsets: [Vec<usize>; 4] = [
+        vec![0],
+        vec![CONFORMANCE_CLAIMS - 1],
+        (0..CONFORMANCE_CLAIMS).step_by(2).collect(),
+        (1..CONFORMANCE_CLAIMS).rev().step_by(3).collect(),
+    ];
+
+    for subset in subsets {
+        check_disclosure(adapter, &vc, "a", &subset)?;
+    }
+
+    Ok(())
+}
+
+/// Checks that VPs disclosing several subsets of claims prove exactly the disclosed claims of the VC, with their values.
+fn check_claims_diff(adapter: &dyn Adapter) -> Result<(), String> {
+    let (vc, _vc_jwt) = issue_verified_vc(adapter, "a")?;
+    let subsets: [Vec<usize>; 3] = [
+        vec![0],
+        (0..CONFORMANCE_CLAIMS).step_by(2).collect(),
+        (0..CONFORMANCE_CLAIMS).collect(),
+    ];
+
+    for subset in subsets {
+        let disclosures: Vec<String> = subset.iter().map(|index| claim_name(*index)).collect();
+        let (vp, _vp_jwt) = adapter.issue_vp(&vc, &disclosures)?;
+        if let Err(err) = diff_claims(adapter, &vc, &vp)?.check_disclosures(&disclosures) {
+            return Err(format!("{err} when disclosing {subset:?}"));
+        }
+    }
+
+    Ok(())
+}
+
+/// Checks that a VP without disclosures is either refused or verifies without revealing any value.
+fn check_empty_disclosure(adapter: &dyn Adapter) -> Result<(), String> {
+    let (vc, _vc_jwt) = issue_verified_vc(adapter, "a")?;
+
+    // Refusing to present nothing is acceptable, presenting something that does not verify or leaks claims is not.
+    match adapter.issue_vp(&vc, &[]) {
+        Err(_) => { Ok(()) }
+        Ok(_) => { check_disclosure(adapter, &vc, "a", &[]) }
+    }
+}
+
+/// Checks that a VP disclosing every claim verifies and reveals every value.
+fn check_full_disclosure(adapter: &dyn Adapter) -> Result<(), String> {
+    let (vc, _vc_jwt) = issue_verified_vc(adapter, "a")?;
+    check_disclosure(adapter, &vc, "a", &(0..CONFORMANCE_CLAIMS).collect::<Vec<usize>>())
+}
+
+/// Checks that VCs whose claim value is altered, wherever it appears in the VC, or whose fields are swapped with the
+/// ones of another credential of the same issuer are rejected.
+fn check_vc_tamper_rejection(adapter: &dyn Adapter) -> Result<(), String> {
+    let (vc, vc_jwt) = issue_verified_vc(adapter, "a")?;
+    let (other_vc, _other_vc_jwt) = issue_verified_vc(adapter, "b")?;
+    let (header, _payload) = decode_header_and_payload(&vc_jwt)?;
+    let encoding: Encoding = match header.get(ENCODING_HEADER) {
+        Some(Value::String(name)) => { Encoding::from_name(name)? }
+        _ => { Encoding::default() }
+    };
+
+    // Value of a claim replaced by another one.
+    let (value, altered_value) = (claim_value(0, "a"), claim_value(0, "b"));
+    let mut altered_vc: Map<String, Value> = Map::new();
+    for (field, field_value) in &vc {
+        altered_vc.insert(field.clone(), swap_claim_names(field_value, encoding, &value, &altered_value)?);
+    }
+    if altered_vc == vc {
+        return Err(format!("VC does not hold the value of {}", claim_name(0)));
+    }
+    if adapter.verify_vc(&altered_vc).is_ok() {
+        return Err(format!("VC whose {} was altered was verified", claim_name(0)));
+    }
+
+    // Fields swapped with the ones of another credential of the same issuer.
+    for (field, other_value) in &other_vc {
+        if vc.get(field) == Some(other_value) {
+            continue;
+        }
+
+        let mut tampered_vc: Map<String, Value> = vc.clone();
+        tampered_vc.insert(field.clone(), other_value.clone());
+        if adapter.verify_vc(&tampered_vc).is_ok() {
+            return Err(format!("VC whose {field} was swapped with the one of another credential was verified"));
+        }
+    }
+
+    Ok(())
+}
+
+/// Checks that VPs with an altered signature or with components of another credential are rejected.
+fn check_tamper_rejection(adapter: &dyn Adapter) -> Result<(), String> {
+    let (_, holder_private_key) = CommonData::holder_keys()?;
+    let disclosures: Vec<String> = vec![claim_name(0), claim_name(1)];
+
+    let (vc, _vc_jwt) = issue_verified_vc(adapter, "a")?;
+    let (other_vc, _other_vc_jwt) = issue_verified_vc(adapter, "b")?;
+    let (vp, vp_jwt) = adapter.issue_vp(&vc, &disclosures)?;
+    let (other_vp, _other_vp_jwt) = adapter.issue_vp(&other_vc, &disclosures)?;
+
+    // Altered holder signature.
+    let mut tampered_jwt: String = vp_jwt.clone();
+    let last = match tampered_jwt.pop() {
+        Some(last) => { last }
+        None => { return Err("VP JWT is empty".to_string()) }
+    };
+    tampered_jwt.push(if last == 'A' { 'B' } else { 'A' });
+    if adapter.verify_vp(&tampered_jwt).is_ok() {
+        return Err("VP with an altered signature was verified".to_string());
+    }
+
+    // Components swapped with the ones of another credential of the same issuer, re-signed by the holder.
+    let (header, _payload) = decode_header_and_payload(&vp_jwt)?;
+    let credential = presented_credential(&vp)?;
+    let other_credential = presented_credential(&other_vp)?;
+
+    for (field, other_value) in other_credential {
+        if credential.get(field) == Some(other_value) {
+            continue;
+        }
+
+        let mut tampered_credential: Map<String, Value> = credential.clone();
+        tampered_credential.insert(field.clone(), other_value.clone());
+        let mut tampered_vp: Map<String, Value> = vp.clone();
+        if let Some(Value::Object(presentation)) = tampered_vp.get_mut(VP) {
+            presentation.insert(VERIFIABLE_CREDENTIAL.to_string(), Value::Array(vec![Value::Object(tampered_credential)]));
+        }
+
+        let tampered_jwt: String = sign(&header, &tampered_vp, &holder_private_key)?;
+        if adapter.verify_vp(&tampered_jwt).is_ok() {
+            return Err(format!("VP whose {field} was swapped with the one of another credential was verified"));
+        }
+    }
+
+    Ok(())
+}
+
+/// Checks that a VP disclosing two claims is rejected once the names of the claims are swapped wherever they appear in
+/// the presented credential and the VP is re-signed by the holder, i.e. that the proof of a claim cannot be presented
+/// under the name of another claim.
+fn check_claim_swap_rejection(adapter: &dyn Adapter) -> Result<(), String> {
+    let (_, holder_private_key) = CommonData::holder_keys()?;
+    let (first, second) = (claim_name(0), claim_name(1));
+
+    let (vc, _vc_jwt) = issue_verified_vc(adapter, "a")?;
+    let (vp, vp_jwt) = adapter.issue_vp(&vc, &[first.clone(), second.clone()])?;
+    let (header, _payload) = decode_header_and_payload(&vp_jwt)?;
+    let encoding: Encoding = match header.get(ENCODING_HEADER) {
+        Some(Value::String(name)) => { Encoding::from_name(name)? }
+        _ => { Encoding::default() }
+    };
+
+    let credential: &Map<String, Value> = presented_credential(&vp)?;
+    let mut swapped_credential: Map<String, Value> = Map::new();
+    for (field, value) in credential {
+        swapped_credential.insert(field.clone(), swap_claim_names(value, encoding, &first, &second)?);
+    }
+    if &swapped_credential == credential {
+        return Err("VP does not name the disclosed claims".to_string());
+    }
+
+    let mut swapped_vp: Map<String, Value> = vp.clone();
+    if let Some(Value::Object(presentation)) = swapped_vp.get_mut(VP) {
+        presentation.insert(VERIFIABLE_CREDENTIAL.to_string(), Value::Array(vec![Value::Object(swapped_credential)]));
+    }
+
+    let swapped_jwt: String = sign(&header, &swapped_vp, &holder_private_key)?;
+    if adapter.verify_vp(&swapped_jwt).is_ok() {
+        return Err(format!("VP presenting {first} under the name {second} and vice versa was verified"));
+    }
+
+    Ok(())
+}
+
+/// Checks that the VC JWT payload matches the returned VC and that the VC survives a JSON round-trip.
+fn check_serialization_stability(adapter: &dyn Adapter) -> Result<(), String> {
+    let (vc, vc_jwt) = issue_verified_vc(adapter, "a")?;
+
+    let (_header, payload) = decode_header_and_payload(&vc_jwt)?;
+    if payload != vc {
+        return Err("Payload of the VC JWT differs from the returned VC".to_string());
+    }
+
+    let serialized_vc: String = match serde_json::to_string(&vc) {
+        Ok(serialized_vc) => { serialized_vc }
+        Err(err) => { return Err(format!("Failed to serialize VC: [{err}]")) }
+    };
+    let parsed_vc: Map<String, Value> = match serde_json::from_str(&serialized_vc) {
+        Ok(parsed_vc) => { parsed_vc }
+        Err(err) => { return Err(format!("Failed to parse serialized VC: [{err}]")) }
+    };
+
+    adapter.verify_vc(&parsed_vc)?;
+    check_disclosure(adapter, &parsed_vc, "a", &[0, CONFORMANCE_CLAIMS - 1])
+}
+
+/// Checks that claims in non-Latin scripts are issued, disclosed and verified like ASCII ones.
+fn check_non_ascii_claims(adapter: &dyn Adapter) -> Result<(), String> {
+    let generator = MultilingualClaimGenerator::new(Script::Mixed);
+    let mut raw_vc: Map<String, Value> = raw_vc("a")?;
+    generator.substitute_claims(&mut raw_vc, CONFORMANCE_CLAIMS)?;
+    let (vc, _vc_jwt) = adapter.issue_vc(&raw_vc)?;
+    adapter.verify_vc(&vc)?;
+
+    let indices: Vec<usize> = (1..=CONFORMANCE_CLAIMS).step_by(2).collect();
+    let (_vp, vp_jwt) = adapter.issue_vp(&vc, &generator.disclosures(&indices))?;
+    adapter.verify_vp(&vp_jwt)?;
+
+    let text: String = revealed_text(&vp_jwt)?;
+    for index in 1..=CONFORMANCE_CLAIMS {
+        let value: String = match generator.claim_value(index) {
+            Value::String(value) => { value }
+            value => { value.to_string() }
+        };
+        match (indices.contains(&index), text.contains(&value)) {
+            (true, false) => { return Err(format!("Disclosed claim {} is not revealed", generator.claim_name(index))) }
+            (false, true) => { return Err(format!("Withheld claim {} is revealed", generator.claim_name(index))) }
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
+
+/// Swaps two claim names wherever they appear in a field of a presented credential, decoding the field first if it is a
+/// component encoded with the encoding declared in the header.
+fn swap_claim_names(value: &Value, encoding: Encoding, first: &str, second: &str) -> Result<Value, String> {
+    let swap = |text: &str| text.replace(first, "\u{0}").replace(second, first).replace('\u{0}', second);
+
+    match value {
+        Value::String(encoded) => {
+            let decoded: Option<String> = encoding.decode(encoded).ok()
+                .and_then(|bytes| String::from_utf8(bytes).ok())
+                .filter(|text| serde_json::from_str::<Value>(text).is_ok());
+            match decoded {
+                Some(text) => { Ok(Value::String(encoding.encode(swap(&text)))) }
+                None => { Ok(Value::String(swap(encoded))) }
+            }
+        }
+        _ => match serde_json::from_str::<Value>(&swap(&value.to_string())) {
+            Ok(swapped) => { Ok(swapped) }
+            Err(err) => { Err(format!("Failed to swap claim names: [{err}]")) }
+        }
+    }
+}
+
+/// Retrieves the derived credential of a VP envelope.
+fn presented_credential(vp: &Map<String, Value>) -> Result<&Map<String, Value>, String> {
+    match vp.get(VP).and_then(|presentation| presentation.get(VERIFIABLE_CREDENTIAL)).and_then(|credentials| credentials.get(0)) {
+        Some(Value::Object(credential)) => { Ok(credential) }
+        _ => { Err("VP does not hold a credential".to_string()) }
+    }
+}
+
+/// Signs a VP envelope with the given header, as a holder would.
+fn sign(header: &Map<String, Value>, envelope: &Map<String, Value>, private_key: &impl AsRef<[u8]>) -> Result<String, String> {
+    let header = match JwsHeader::from_map(header.clone()) {
+        Ok(header) => { header }
+        Err(err) => { return Err(format!("Failed to build header: [{err}]")) }
+    };
+    let payload = match JwtPayload::from_map(envelope.clone()) {
+        Ok(payload) => { payload }
+        Err(err) => { return Err(format!("Failed to build payload: [{err}]")) }
+    };
+    let signer = match ES256.signer_from_pem(private_key) {
+        Ok(signer) => { signer }
+        Err(err) => { return Err(format!("Failed to create signer: [{err}]")) }
+    };
+
+    match jwt::encode_with_signer(&payload, &header, &signer) {
+        Ok(jwt) => { Ok(jwt) }
+        Err(err) => { Err(format!("Failed to encode and sign jwt: [{err}]")) }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::adapters::accumulators::csd_jwt_adapter::CsdJwtAdapter;
+    use crate::adapters::accumulators::csd_jwt_aggregated_adapter::CsdJwtAggregatedAdapter;
+    use crate::adapters::accumulators::csd_jwt_multi_show_adapter::CsdJwtMultiShowAdapter;
+    use crate::adapters::hashes::merkle_tree_adapter::MerkleTreeAdapter;
+    use crate::adapters::hashes::sd_jwt_adapter::SdJwtAdapter;
+    use crate::adapters::signatures::bbs_plus_adapter::BBSPlusAdapter;
+
+    use super::*;
+
+    #[test]
+    fn sd_jwt() -> Result<(), String> {
+        run_conformance::<SdJwtAdapter>()
+    }
+
+    #[test]
+    fn csd_jwt() -> Result<(), String> {
+        run_conformance::<CsdJwtAdapter>()
+    }
+
+    #[test]
+    fn csd_jwt_multi_show() -> Result<(), String> {
+        run_conformance::<CsdJwtMultiShowAdapter>()
+    }
+
+    #[test]
+    fn csd_jwt_aggregated() -> Result<(), String> {
+        run_conformance::<CsdJwtAggregatedAdapter>()
+    }
+
+    #[test]
+    fn merkle_tree() -> Result<(), String> {
+        run_conformance::<MerkleTreeAdapter>()
+    }
+
+    #[test]
+    fn bbs_plus() -> Result<(), String> {
+        run_conformance::<BBSPlusAdapter>()
+    }
+}
@@ -0,0 +1,198 @@
+use std::collections::BTreeMap;
+use crate::csv_writer::CSVWriter;
+use crate::transport::qr::QR_CAPACITY;
+
+/// Maximum size of a payload imposed by a transport, e.g. an NDEF message or a single QR code.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PayloadConstraint {
+    /// Name of the constraint, as written in the result file.
+    pub name: String,
+    /// Maximum size of the payload in bytes.
+    pub max_bytes: usize,
+}
+
+impl PayloadConstraint {
+
+    /// Constructor for a payload constraint.
+    ///
+    /// # Arguments
+    /// * `name` - Name of the constraint.
+    /// * `max_bytes` - Maximum size of the payload in bytes.
+    ///
+    /// # Returns
+    /// The payload constraint.
+    pub fn new(name: &str, max_bytes: usize) -> Self {
+        PayloadConstraint { name: name.to_string(), max_bytes }
+    }
+
+    /// Default constraints: an NDEF message of 64 KB, as read from an NFC Forum type 4 tag, and a single QR code.
+    ///
+    /// # Returns
+    /// The default constraints.
+    pub fn defaults() -> Vec<Self> {
+        vec![
+            PayloadConstraint::new("NDEF", 64 * 1024),
+            PayloadConstraint::new("QR", QR_CAPACITY),
+        ]
+    }
+
+    /// Parses a comma separated list of constraints of the form `name:max_bytes`.
+    ///
+    /// # Arguments
+    /// * `configuration` - List of constraints.
+    ///
+    /// # Returns
+    /// A result containing the constraints or a string containing an error.
+    ///
+    /// # Examples
+    /// ```
+    /// use csd_jwt::constraints::PayloadConstraint;
+    ///
+    /// let constraints = PayloadConstraint::parse_list("NDEF:65536, BLE-ATT:512").unwrap();
+    /// assert_eq!(constraints[1], PayloadConstraint::new("BLE-ATT", 512));
+    /// assert!(constraints[1].fits(512) && !constraints[1].fits(513));
+    /// assert!(PayloadConstraint::parse_list("NDEF").is_err());
+    /// ```
+    pub fn parse_list(configuration: &str) -> Result<Vec<Self>, String> {
+        configuration.split(',').map(|constraint| {
+            match constraint.trim().split_once(':') {
+                Some((name, max_bytes)) => match max_bytes.parse::<usize>() {
+                    Ok(max_bytes) => { Ok(PayloadConstraint::new(name, max_bytes)) }
+                    Err(err) => { Err(format!("Invalid size in payload constraint {constraint}: [{err}]")) }
+                },
+                None => { Err(format!("Payload constraint {constraint} is not of the form name:max_bytes")) }
+            }
+        }).collect()
+    }
+
+    /// Checks whether a payload satisfies the constraint.
+    ///
+    /// # Arguments
+    /// * `bytes` - Size of the payload.
+    ///
+    /// # Returns
+    /// True if the payload is not larger than the maximum size.
+    pub fn fits(&self, bytes: usize) -> bool {
+        bytes <= self.max_bytes
+    }
+}
+
+
+/// Records the sizes of the VCs and VPs of the benchmark and reports, for every constraint, algorithm and amount of
+/// claims, whether they fit.
+///
+/// # Examples
+/// ```
+/// use csd_jwt::constraints::{FeasibilityMatrix, PayloadConstraint};
+///
+/// let mut matrix = FeasibilityMatrix::new(vec![PayloadConstraint::new("QR", 2953)]);
+/// matrix.record("vc", 10, 10, "SD-JWT", 2000);
+/// matrix.record("vc", 20, 20, "SD-JWT", 3500);
+/// assert_eq!(matrix.max_feasible_claims("QR", "vc", "SD-JWT"), Some(10));
+/// ```
+pub struct FeasibilityMatrix {
+    /// Constraints checked against every payload.
+    constraints: Vec<PayloadConstraint>,
+    /// Sizes of the payloads, keyed by credential type, amount of claims and disclosed claims, and by algorithm.
+    sizes: BTreeMap<(&'static str, usize, usize), BTreeMap<String, usize>>,
+}
+
+impl FeasibilityMatrix {
+
+    /// Constructor for an empty feasibility matrix.
+    ///
+    /// # Arguments
+    /// * `constraints` - Constraints checked against every payload.
+    ///
+    /// # Returns
+    /// A matrix without any recorded size.
+    pub fn new(constraints: Vec<PayloadConstraint>) -> Self {
+        FeasibilityMatrix { constraints, sizes: BTreeMap::new() }
+    }
+
+    /// Getter of the constraints.
+    pub fn constraints(&self) -> &Vec<PayloadConstraint> {
+        &self.constraints
+    }
+
+    /// Records the size of a payload.
+    ///
+    /// # Arguments
+    /// * `credential` - Type of the payload, i.e. `vc` or `vp`.
+    /// * `n_claims` - Amount of claims in the credential.
+    /// * `n_disclosures` - Amount of disclosed claims, equal to the amount of claims for VCs.
+    /// * `algorithm` - Name of the algorithm.
+    /// * `bytes` - Size of the payload.
+    pub fn record(&mut self, credential: &'static str, n_claims: usize, n_disclosures: usize, algorithm: &str, bytes: usize) {
+        self.sizes
+            .entry((credential, n_claims, n_disclosures))
+            .or_default()
+            .insert(algorithm.to_string(), bytes);
+    }
+
+    /// Largest amount of claims for which every recorded payload of an algorithm satisfies a constraint, assuming sizes
+    /// grow with the amount of claims.
+    ///
+    /// # Arguments
+    /// * `constraint` - Name of the constraint.
+    /// * `credential` - Type of the payload, i.e. `vc` or `vp`.
+    /// * `algorithm` - Name of the algorithm.
+    ///
+    /// # Returns
+    /// The largest amount of claims, or None if the constraint is unknown or no recorded payload fits.
+    pub fn max_feasible_claims(&self, constraint: &str, credential: &str, algorithm: &str) -> Option<usize> {
+        let constraint = self.constraints.iter().find(|candidate| candidate.name == constraint)?;
+
+        let recorded: Vec<(usize, bool)> = self.sizes
+            .iter()
+            .filter(|((recorded_credential, _, _), _)| *recorded_credential == credential)
+            .filter_map(|((_, n_claims, _), sizes)| sizes.get(algorithm).map(|bytes| (*n_claims, constraint.fits(*bytes))))
+            .collect();
+        let first_infeasible_claims: Option<usize> = recorded.iter().filter(|(_, fits)| !fits).map(|(n_claims, _)| *n_claims).min();
+
+        recorded
+            .iter()
+            .map(|(n_claims, _)| *n_claims)
+            .filter(|n_claims| first_infeasible_claims.is_none_or(|first_infeasible_claims| *n_claims < first_infeasible_claims))
+            .max()
+    }
+
+    /// Writes the feasibility matrix to a CSV file, with one row per constraint and payload and one column per algorithm,
+    /// whose cells are `true` if the payload fits, `false` if it does not and empty if the payload was not produced.
+    ///
+    /// # Arguments
+    /// * `filename` - Name of the CSV file, without extension.
+    /// * `algorithms` - Names of the algorithms, in the order of the columns.
+    ///
+    /// # Returns
+    /// The result of the operation or a string containing an error.
+    pub fn write_matrix(&self, filename: &String, algorithms: &[String]) -> Result<(), String> {
+
+        let mut columns: Vec<String> = ["constraint", "max_bytes", "credential", "claims", "disclosed"]
+            .iter()
+            .map(|column| column.to_string())
+            .collect();
+        columns.extend(algorithms.iter().cloned());
+
+        let mut writer = CSVWriter::new(columns)?;
+        writer.add_file(filename)?;
+
+        for constraint in &self.constraints {
+            for ((credential, n_claims, n_disclosures), sizes) in &self.sizes {
+                let mut record: Vec<String> = vec![
+                    constraint.name.clone(),
+                    constraint.max_bytes.to_string(),
+                    credential.to_string(),
+                    n_claims.to_string(),
+                    n_disclosures.to_string(),
+                ];
+                record.extend(algorithms.iter().map(|algorithm| {
+                    sizes.get(algorithm).map(|bytes| constraint.fits(*bytes).to_string()).unwrap_or_default()
+                }));
+                writer.write_record_to_file(filename, record)?;
+            }
+        }
+
+        Ok(())
+    }
+}
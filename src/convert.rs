@@ -0,0 +1,97 @@
+use serde_json::{Map, Value};
+use crate::adapters::adapter::{Adapter, VerifierAdapter};
+use crate::credential_ir::CredentialIr;
+use crate::sd_algorithms::accumulators::csd_jwt::{CsdJwtInstance, AGGREGATED_ALGORITHM, MULTI_SHOW_ALGORITHM};
+use crate::sd_algorithms::accumulators::csd_jwt_bls12_381::CsdJwtBls12381Instance;
+use crate::sd_algorithms::hashes::merkle_trees::MerkleTreeInstance;
+use crate::sd_algorithms::hashes::sd_jwt::SdJwtInstance;
+use crate::sd_algorithms::sd_algorithm::SdAlgorithm;
+use crate::sd_algorithms::signatures::bbs_plus::BBSPlusInstance;
+
+/// Lowers a VC to the intermediate representation shared by all the algorithms, dropping the proof material of the
+/// algorithm that issued it.
+///
+/// # Arguments
+/// * `vc` - Verifiable Credential to be converted.
+/// * `algorithm` - Name of the algorithm that issued the VC, as returned by `VerifierAdapter::sd_algorithm`.
+///
+/// # Returns
+/// A result containing the intermediate representation or a string containing an error.
+///
+/// # Examples
+/// ```
+/// use serde_json::{Map, Value};
+/// use csd_jwt::adapters::adapter::Adapter;
+/// use csd_jwt::adapters::hashes::sd_jwt_adapter::SdJwtAdapter;
+/// use csd_jwt::common_data::{CLAIMS, VC};
+/// use csd_jwt::convert::lower_vc;
+///
+/// let adapter = SdJwtAdapter::new(4).unwrap();
+/// let raw_vc: Map<String, Value> = serde_json::from_str(VC).unwrap();
+/// let (vc, _) = adapter.issue_vc(&raw_vc).unwrap();
+///
+/// let credential = lower_vc(&vc, "SD-JWT").unwrap();
+/// assert!(!credential.metadata().contains_key("svc"));
+/// assert_eq!(credential.to_raw_vc().unwrap().get(CLAIMS), raw_vc.get(CLAIMS));
+/// assert!(lower_vc(&vc, "unknown").is_err());
+/// ```
+pub fn lower_vc(vc: &Map<String, Value>, algorithm: &str) -> Result<CredentialIr, String> {
+    let credential = match algorithm {
+        SdJwtInstance::ALGORITHM => { SdJwtInstance::lower_vc(vc) }
+        MerkleTreeInstance::ALGORITHM => { MerkleTreeInstance::lower_vc(vc) }
+        BBSPlusInstance::ALGORITHM => { BBSPlusInstance::lower_vc(vc) }
+        CsdJwtInstance::ALGORITHM | AGGREGATED_ALGORITHM | MULTI_SHOW_ALGORITHM => { CsdJwtInstance::lower_vc(vc) }
+        CsdJwtBls12381Instance::ALGORITHM => { CsdJwtBls12381Instance::lower_vc(vc) }
+        _ => { return Err(format!("Conversion from algorithm {algorithm} is not supported")) }
+    };
+
+    credential.map_err(|err| format!("Failed to recover the credential of the {algorithm} VC: [{err}]"))
+}
+
+
+/// Re-issues a VC under a different algorithm, preserving its claims and metadata, to experiment with the migration
+/// of a population of credentials. The VC is verified with the source algorithm first, and the new VC is bound to the
+/// holder key of the target adapter.
+///
+/// # Arguments
+/// * `vc` - Verifiable Credential to be converted.
+/// * `source` - Adapter of the algorithm that issued the VC.
+/// * `target` - Adapter issuing the new VC.
+///
+/// # Returns
+/// A result containing the new VC and its JWT encoding or a string containing an error.
+///
+/// # Examples
+/// ```
+/// use serde_json::{Map, Value};
+/// use csd_jwt::adapters::adapter::{Adapter, VerifierAdapter};
+/// use csd_jwt::adapters::accumulators::csd_jwt_adapter::CsdJwtAdapter;
+/// use csd_jwt::adapters::hashes::sd_jwt_adapter::SdJwtAdapter;
+/// use csd_jwt::common_data::VC;
+/// use csd_jwt::convert::convert_vc;
+///
+/// let source = SdJwtAdapter::new(4).unwrap();
+/// let target = CsdJwtAdapter::new(4).unwrap();
+/// let raw_vc: Map<String, Value> = serde_json::from_str(VC).unwrap();
+/// let (vc, _) = source.issue_vc(&raw_vc).unwrap();
+///
+/// let (converted_vc, _) = convert_vc(&vc, &source, &target).unwrap();
+/// target.verify_vc(&converted_vc).unwrap();
+/// let (_, vp_jwt) = target.issue_vp(&converted_vc, &vec!["name".to_string()]).unwrap();
+/// target.verify_vp(&vp_jwt).unwrap();
+///
+/// // Only VCs verified by the source adapter are converted.
+/// let (other_vc, _) = SdJwtAdapter::new(4).unwrap().issue_vc(&raw_vc).unwrap();
+/// assert!(convert_vc(&other_vc, &source, &target).is_err());
+/// ```
+pub fn convert_vc(vc: &Map<String, Value>, source: &dyn VerifierAdapter, target: &dyn Adapter) -> Result<(Map<String, Value>, String), String> {
+    if let Err(err) = source.verify_vc(vc) {
+        return Err(format!("Failed to verify the {} VC to be converted: [{err}]", source.sd_algorithm()));
+    }
+
+    let credential: CredentialIr = lower_vc(vc, &source.sd_algorithm())?;
+    match target.issue_vc(&credential.to_raw_vc()?) {
+        Ok(converted) => { Ok(converted) }
+        Err(err) => { Err(format!("Failed to re-issue the VC under {}: [{err}]", target.sd_algorithm())) }
+    }
+}
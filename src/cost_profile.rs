@@ -0,0 +1,112 @@
+use std::ops::Sub;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Amount of pairings computed, a multi-pairing of `n` terms counting as `n` pairings.
+static PAIRINGS: AtomicU64 = AtomicU64::new(0);
+/// Amount of scalar multiplications in G1 and G2.
+static EXPONENTIATIONS: AtomicU64 = AtomicU64::new(0);
+/// Amount of invocations of the hash function on claims, Merkle nodes and challenges.
+static HASHES: AtomicU64 = AtomicU64::new(0);
+
+/// Cryptographic operations counted on the paths of the algorithm instances.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Operation {
+    /// Pairing, or term of a multi-pairing.
+    Pairing,
+    /// Group exponentiation, i.e. scalar multiplication of a curve point.
+    Exponentiation,
+    /// Invocation of the hash function.
+    Hash,
+}
+
+/// Records that an operation was performed a given amount of times.
+///
+/// # Arguments
+/// * `operation` - Operation performed.
+/// * `count` - Amount of times it was performed.
+pub fn record(operation: Operation, count: u64) {
+    let counter: &AtomicU64 = match operation {
+        Operation::Pairing => { &PAIRINGS }
+        Operation::Exponentiation => { &EXPONENTIATIONS }
+        Operation::Hash => { &HASHES }
+    };
+    counter.fetch_add(count, Ordering::Relaxed);
+}
+
+
+/// Amount of cryptographic operations performed by an operation of an algorithm, which, unlike durations, does not
+/// depend on the hardware the benchmark runs on.
+///
+/// Operations are counted where the instances perform them. Those performed inside third-party libraries are counted
+/// at the call site from the known cost of the call: BBS+ verifications count their two pairings but neither the
+/// exponentiations nor the hashes of zkryptium, and the zero-knowledge membership proofs of vb_accumulator in
+/// multi-show CSD-JWT presentations are not counted. The counters are shared by the whole process, so concurrent
+/// operations are counted together: profile operations one at a time.
+///
+/// # Examples
+/// ```
+/// use csd_jwt::cost_profile::{record, CostProfile, Operation};
+///
+/// let ((), profile) = CostProfile::measure(|| {
+///     record(Operation::Pairing, 2);
+///     record(Operation::Hash, 3);
+/// });
+/// assert_eq!(profile, CostProfile { pairings: 2, exponentiations: 0, hashes: 3 });
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CostProfile {
+    /// Amount of pairings.
+    pub pairings: u64,
+    /// Amount of group exponentiations.
+    pub exponentiations: u64,
+    /// Amount of hash invocations.
+    pub hashes: u64,
+}
+
+impl CostProfile {
+
+    /// Names of the counters, in the order of `values`.
+    pub const COUNTERS: [&'static str; 3] = ["pairings", "exponentiations", "hashes"];
+
+    /// Reads the counters of the process.
+    ///
+    /// # Returns
+    /// The amount of operations counted since the start of the process.
+    pub fn snapshot() -> Self {
+        CostProfile {
+            pairings: PAIRINGS.load(Ordering::Relaxed),
+            exponentiations: EXPONENTIATIONS.load(Ordering::Relaxed),
+            hashes: HASHES.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Runs an operation once and counts the cryptographic operations it performs.
+    ///
+    /// # Arguments
+    /// * `operation` - Operation to be profiled.
+    ///
+    /// # Returns
+    /// The output of the operation and its cost profile.
+    pub fn measure<T, F: FnOnce() -> T>(operation: F) -> (T, Self) {
+        let before: CostProfile = Self::snapshot();
+        let output: T = operation();
+        (output, Self::snapshot() - before)
+    }
+
+    /// Values of the counters, in the order of `COUNTERS`.
+    pub fn values(&self) -> [u64; 3] {
+        [self.pairings, self.exponentiations, self.hashes]
+    }
+}
+
+impl Sub for CostProfile {
+    type Output = CostProfile;
+
+    fn sub(self, other: CostProfile) -> CostProfile {
+        CostProfile {
+            pairings: self.pairings.saturating_sub(other.pairings),
+            exponentiations: self.exponentiations.saturating_sub(other.exponentiations),
+            hashes: self.hashes.saturating_sub(other.hashes),
+        }
+    }
+}
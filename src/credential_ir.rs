@@ -0,0 +1,194 @@
+use serde_json::{Map, Value};
+use crate::claim_groups::ClaimGroups;
+use crate::common_data::{CLAIMS, CNF, CREDENTIAL_ID, EXPIRATION, ISSUED_AT, JWK_THUMBPRINT, PREVIOUS_CREDENTIAL};
+use crate::deterministic::random_bytes;
+use crate::encoding::Encoding;
+use crate::hashing::sha256;
+use crate::holder_binding::bound_thumbprint;
+
+/// Algorithm-agnostic representation of a credential, which every algorithm lowers a raw VC to before protecting its
+/// claims, and raises back to the envelope of the VC it issues. It holds the claims as committed to, i.e. with the claim
+/// groups already merged, the metadata of the credential, and the key the credential is bound to.
+///
+/// # Examples
+/// ```
+/// use serde_json::{json, Map, Value};
+/// use csd_jwt::credential_ir::CredentialIr;
+///
+/// let raw_vc: Map<String, Value> = serde_json::from_value(json!({
+///     "issuer": "https://issuer.example",
+///     "cnf": {"jkt": "thumbprint"},
+///     "credentialSubject": {"name": "Ada", "age": 36}
+/// })).unwrap();
+///
+/// let credential = CredentialIr::from_raw_vc(&raw_vc).unwrap();
+/// assert_eq!(credential.holder_binding(), Some("thumbprint"));
+/// assert_eq!(credential.claims().len(), 2);
+/// assert!(!credential.envelope().contains_key("credentialSubject"));
+/// assert_eq!(credential.to_raw_vc().unwrap(), raw_vc);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CredentialIr {
+    /// Fields of the credential other than its claims, its holder binding and any proof material.
+    metadata: Map<String, Value>,
+    /// Claims of the credential, with the claim groups merged.
+    claims: Map<String, Value>,
+    /// Thumbprint of the holder key the credential is bound to, if any.
+    holder_binding: Option<String>,
+}
+
+impl CredentialIr {
+
+    /// Lowers a raw VC, whose claims are in the `credentialSubject` field, to the intermediate representation.
+    ///
+    /// # Arguments
+    /// * `raw_vc` - Template VC, with its claim groups already merged.
+    ///
+    /// # Returns
+    /// A result containing the intermediate representation or a string containing an error.
+    pub fn from_raw_vc(raw_vc: &Map<String, Value>) -> Result<Self, String> {
+        let claims: Map<String, Value> = match raw_vc.get(CLAIMS) {
+            Some(Value::Object(claims)) => { claims.clone() }
+            Some(_) => { return Err("CredentialSubject field is not an object".to_string()) }
+            None => { return Err("Map does not contain the credentialSubject field. No claims can be disclosed.".to_string()) }
+        };
+
+        let mut metadata: Map<String, Value> = raw_vc.clone();
+        metadata.remove(CLAIMS);
+        Self::from_parts(metadata, claims)
+    }
+
+    /// Builds the intermediate representation from the fields of a credential other than its claims, extracting its
+    /// holder binding from the `cnf` field, and from its claims.
+    ///
+    /// # Arguments
+    /// * `metadata` - Fields of the credential other than its claims and its proof material.
+    /// * `claims` - Claims of the credential, with the claim groups merged.
+    ///
+    /// # Returns
+    /// A result containing the intermediate representation or a string containing an error.
+    pub fn from_parts(mut metadata: Map<String, Value>, claims: Map<String, Value>) -> Result<Self, String> {
+        let holder_binding: Option<String> = bound_thumbprint(&metadata)?;
+        metadata.remove(CNF);
+
+        Ok(CredentialIr { metadata, claims, holder_binding })
+    }
+
+    /// Fields of the credential other than its claims, its holder binding and any proof material.
+    pub fn metadata(&self) -> &Map<String, Value> {
+        &self.metadata
+    }
+
+    /// Claims of the credential, with the claim groups merged.
+    pub fn claims(&self) -> &Map<String, Value> {
+        &self.claims
+    }
+
+    /// Thumbprint of the holder key the credential is bound to, if any.
+    pub fn holder_binding(&self) -> Option<&str> {
+        self.holder_binding.as_deref()
+    }
+
+    /// Raises the intermediate representation to the envelope of a VC, i.e. its metadata and holder binding, to which
+    /// an algorithm adds its proof material and, if it keeps them in clear, the claims.
+    pub fn envelope(&self) -> Map<String, Value> {
+        let mut envelope: Map<String, Value> = self.metadata.clone();
+        if let Some(thumbprint) = &self.holder_binding {
+            let mut confirmation: Map<String, Value> = Map::new();
+            confirmation.insert(JWK_THUMBPRINT.to_string(), Value::String(thumbprint.clone()));
+            envelope.insert(CNF.to_string(), Value::Object(confirmation));
+        }
+        envelope
+    }
+
+    /// Raises the intermediate representation to the envelope of a VC with the claims in clear in its
+    /// `credentialSubject` field.
+    pub fn to_vc(&self) -> Map<String, Value> {
+        let mut vc: Map<String, Value> = self.envelope();
+        vc.insert(CLAIMS.to_string(), Value::Object(self.claims.clone()));
+        vc
+    }
+
+    /// Raises the intermediate representation back to a raw VC, expanding the claim groups, ready to be issued by any
+    /// algorithm.
+    ///
+    /// # Returns
+    /// A result containing the raw VC or a string containing an error if a group does not hold its claims.
+    pub fn to_raw_vc(&self) -> Result<Map<String, Value>, String> {
+        let claims: Map<String, Value> = ClaimGroups::extract(&self.metadata)?.expand(&self.claims)?;
+
+        let mut raw_vc: Map<String, Value> = self.envelope();
+        raw_vc.insert(CLAIMS.to_string(), Value::Object(claims));
+        Ok(raw_vc)
+    }
+
+    /// Raises the intermediate representation to the raw VC re-issuing the credential with updated claims. The raw VC
+    /// keeps the metadata and the holder binding of the credential, drops its issuance and expiration times so that the
+    /// issuer sets them afresh, receives a new `id` and links to the credential it replaces in `previousCredential`.
+    ///
+    /// # Arguments
+    /// * `updated_claims` - Claims to be added or replaced, a `null` value removing the claim.
+    /// * `previous_id` - Identifier of the credential being replaced, see `credential_id`.
+    ///
+    /// # Returns
+    /// A result containing the raw VC or a string containing an error if a group does not hold its claims.
+    ///
+    /// # Examples
+    /// ```
+    /// use serde_json::{json, Map, Value};
+    /// use csd_jwt::credential_ir::CredentialIr;
+    ///
+    /// let raw_vc: Map<String, Value> = serde_json::from_value(json!({
+    ///     "id": "urn:example:1",
+    ///     "cnf": {"jkt": "thumbprint"},
+    ///     "credentialSubject": {"name": "Ada", "age": 36}
+    /// })).unwrap();
+    ///
+    /// let credential = CredentialIr::from_raw_vc(&raw_vc).unwrap();
+    /// let updates: Map<String, Value> = serde_json::from_value(json!({"age": 37})).unwrap();
+    /// let reissued = credential.reissued_raw_vc(&updates, "urn:example:1").unwrap();
+    /// assert_eq!(reissued["credentialSubject"]["age"], json!(37));
+    /// assert_eq!(reissued["cnf"], raw_vc["cnf"]);
+    /// assert_eq!(reissued["previousCredential"], json!("urn:example:1"));
+    /// assert_ne!(reissued["id"], raw_vc["id"]);
+    /// ```
+    pub fn reissued_raw_vc(&self, updated_claims: &Map<String, Value>, previous_id: &str) -> Result<Map<String, Value>, String> {
+        let mut credential: CredentialIr = self.clone();
+        for (name, value) in updated_claims {
+            match value {
+                Value::Null => { credential.claims.remove(name); }
+                value => { credential.claims.insert(name.clone(), value.clone()); }
+            }
+        }
+        credential.metadata.remove(ISSUED_AT);
+        credential.metadata.remove(EXPIRATION);
+        credential.metadata.insert(CREDENTIAL_ID.to_string(), Value::String(new_credential_id()));
+        credential.metadata.insert(PREVIOUS_CREDENTIAL.to_string(), Value::String(previous_id.to_string()));
+
+        credential.to_raw_vc()
+    }
+}
+
+
+/// Identifier of a VC: its `id` field if present, otherwise a URN holding the SHA-256 digest of the VC.
+///
+/// # Arguments
+/// * `vc` - Verifiable Credential.
+///
+/// # Returns
+/// The identifier of the VC.
+pub fn credential_id(vc: &Map<String, Value>) -> String {
+    match vc.get(CREDENTIAL_ID) {
+        Some(Value::String(id)) => { id.clone() }
+        _ => { format!("urn:sha256:{}", Encoding::Base64UrlNoPad.encode(sha256(Value::Object(vc.clone()).to_string()))) }
+    }
+}
+
+/// Generates a random version 4 UUID URN identifying a new VC.
+fn new_credential_id() -> String {
+    let mut bytes: Vec<u8> = random_bytes(16);
+    bytes[6] = (bytes[6] & 0x0f) | 0x40;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+    let hex: String = bytes.iter().map(|byte| format!("{byte:02x}")).collect();
+    format!("urn:uuid:{}-{}-{}-{}-{}", &hex[0..8], &hex[8..12], &hex[12..16], &hex[16..20], &hex[20..32])
+}
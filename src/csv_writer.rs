@@ -1,21 +1,19 @@
-use std::collections::HashMap;
-use std::fs::{File, metadata};
-use std::fs::create_dir;
-use std::path::Path;
-use csv::Writer;
+use std::collections::HashSet;
 use serde::Serialize;
+use crate::result_sink::{record_fields, CsvFileSink, ResultSink};
 
 /// An object used to easily write CSV files as it's necessary to keep track of several indicators (one for each csv file) for many different sd_algorithm instances (one for each column).
+/// Records are forwarded to one or more `ResultSink`s, by default the CSV files in the csv directory.
 pub struct CSVWriter {
     /// Names of the columns. For instance, the benchmarked algorithm names.
     columns: Vec<String>,
-    /// A Map containing the writers for all the possible files to be written.
-    writers: HashMap<String, Writer<File>>,
+    /// Names of the files added so far.
+    files: HashSet<String>,
+    /// Sinks every file and record is forwarded to.
+    sinks: Vec<Box<dyn ResultSink>>,
 }
 /// Relative path of the directory where the csv files will be saved in.
-const CSV_DIR: &str = "./csv_dir";
-/// Extension of csv files.
-const CSV_EXT: &str = ".csv";
+pub const CSV_DIR: &str = "./csv_dir";
 
 impl CSVWriter {
 
@@ -34,22 +32,58 @@ impl CSVWriter {
     /// let csv_writer: CSVWriter = CSVWriter::new(vec!["first name".to_string(), "last name".to_string()]).unwrap();
     /// ```
     pub fn new(columns: Vec<String>) -> Result<Self, String> {
+        // Mobile apps cannot write to the csv directory, relative to a working directory they do not control.
+        if cfg!(feature = "mobile") {
+            return Ok(Self::discarding(columns));
+        }
+        Ok(Self::with_sinks(columns, vec![Box::new(CsvFileSink::new()?)]))
+    }
 
-        let csv_dir: &Path = Path::new(CSV_DIR);
-        Self::check_dir_existence_or_create(csv_dir)?;
-
-        Ok(CSVWriter { columns, writers: HashMap::new() })
+    /// Constructor for a CSVWriter forwarding its files and records to the given sinks, e.g. the CSV files along with a
+    /// live stream.
+    ///
+    /// # Arguments
+    /// * `columns` - Vector of strings containing the column names.
+    /// * `sinks` - Sinks the files and records are forwarded to.
+    ///
+    /// # Returns
+    /// An instance of CSVWriter writing to the sinks.
+    ///
+    /// # Examples
+    /// ```
+    /// use csd_jwt::csv_writer::CSVWriter;
+    /// use csd_jwt::result_sink::{CsvFileSink, NdjsonSink};
+    ///
+    /// let mut csv_writer: CSVWriter = CSVWriter::with_sinks(
+    ///     vec!["first name".to_string()],
+    ///     vec![Box::new(CsvFileSink::new().unwrap()), Box::new(NdjsonSink::stdout())],
+    /// );
+    /// csv_writer.add_file(&String::from("Streamed")).unwrap();
+    /// csv_writer.write_record_to_file(&String::from("Streamed"), vec!["Ada"]).unwrap();
+    /// ```
+    pub fn with_sinks(columns: Vec<String>, sinks: Vec<Box<dyn ResultSink>>) -> Self {
+        CSVWriter { columns, files: HashSet::new(), sinks }
     }
 
-    /// A utility function to check whether the csv directory exists or not
-    fn check_dir_existence_or_create(csv_dir: &Path) -> Result<(), String> {
-        if !metadata(csv_dir).is_ok() {            // directory does not exist
-            match create_dir(csv_dir) {
-                Ok(_) => {}
-                Err(err) => { return Err(format!("Error in creating CSV folder: [{err}]")) }
-            };
-        }
-        Ok(())
+    /// Constructor for a CSVWriter that accepts files and records without writing them, so that callers can disable
+    /// the CSV output without branching on every write.
+    ///
+    /// # Arguments
+    /// * `columns` - Vector of strings containing the column names.
+    ///
+    /// # Returns
+    /// An instance of CSVWriter that does not create any file.
+    ///
+    /// # Examples
+    /// ```
+    /// use csd_jwt::csv_writer::CSVWriter;
+    ///
+    /// let mut csv_writer: CSVWriter = CSVWriter::discarding(vec!["first name".to_string()]);
+    /// csv_writer.add_file(&String::from("Discarded")).unwrap();
+    /// csv_writer.write_record_to_file(&String::from("Discarded"), vec!["Ada"]).unwrap();
+    /// ```
+    pub fn discarding(columns: Vec<String>) -> Self {
+        Self::with_sinks(columns, vec![])
     }
 
     /// Adds a new file writer to the CSVWriter object to keep track of yet another key indicator.
@@ -68,26 +102,13 @@ impl CSVWriter {
     /// csv_writer.add_file(&String::from("Office")).unwrap();
     /// ```
     pub fn add_file(&mut self, filename: &String) -> Result<(), String> {
+        if !self.files.insert(filename.clone()) {
+            return Err(format!("CSVWriter already has a file for {filename} key"));
+        }
 
-        let mut filename_with_extension: String = filename.clone();
-        filename_with_extension.push_str(CSV_EXT);
-
-        let csv_dir: &Path = Path::new(CSV_DIR);
-        Self::check_dir_existence_or_create(csv_dir)?;
-        let full_path = csv_dir.join(filename_with_extension);
-
-        let file = match File::create(full_path) {
-            Ok(file) => { file }
-            Err(err) => { return Err(format!("Error in creating file for CSV Writer: [{err}]")) }
-        };
-
-        let writer = Writer::from_writer(file);
-        match self.writers.insert(filename.clone(), writer) {
-            None => { }
-            Some(_) => { return Err(format!("HashMap already has a writer for {filename} key"))}
-        };
-
-        self.write_record_to_file(filename, self.columns.clone())?;
+        for sink in self.sinks.iter_mut() {
+            sink.add_file(filename, &self.columns)?;
+        }
 
         Ok(())
     }
@@ -113,26 +134,29 @@ impl CSVWriter {
     /// ```
     pub fn write_record_to_file<S: Serialize + std::fmt::Debug>(&mut self, filename: &String, record: S) -> Result<(), String>
     {
-        let writer: &mut Writer<File> = match self.writers.get_mut(filename) {
-            None => { return Err(format!("Filename {filename} was not found in map"))}
-            Some(writer) => { writer }
-        };
+        if !self.files.contains(filename) {
+            return Err(format!("Filename {filename} was not found in map"));
+        }
+        if self.sinks.is_empty() {
+            return Ok(());
+        }
 
-        match writer.serialize(record) {
-            Ok(_) => { Ok(()) }
-            Err(err) => { Err(format!("Error in writing record: [{err}]")) }
+        let fields: Vec<String> = record_fields(record)?;
+        for sink in self.sinks.iter_mut() {
+            sink.write_record(filename, &fields)?;
         }
 
+        Ok(())
     }
 
 }
 
 
 impl Drop for CSVWriter {
-    /// Function that is called whenever a CSVWriter file is dropped so to correctly flush the writers.
+    /// Function that is called whenever a CSVWriter file is dropped so to correctly flush the sinks.
     fn drop(&mut self) {
-        for (_, writer) in self.writers.iter_mut() {
-            writer.flush().unwrap();
+        for sink in self.sinks.iter_mut() {
+            sink.flush().unwrap();
         }
     }
-}
\ No newline at end of file
+}
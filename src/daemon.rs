@@ -0,0 +1,626 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread;
+use std::time::{SystemTime, UNIX_EPOCH};
+use axum::body::Bytes;
+use axum::extract::{Path, State};
+use axum::http::header;
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::{Json, Router};
+use log::{error, info, warn};
+use serde_json::{json, Map, Value};
+use crate::adapters::adapter::Adapter;
+use crate::adapters::registry::AdapterRegistry;
+use crate::benchmark::{Benchmark, Measurement};
+use crate::chart::LineChart;
+use crate::claim_generator::{ClaimGenerator, FlatClaimGenerator};
+use crate::common_data::VC;
+use crate::result_format::{FORMAT_VERSION, FORMAT_VERSION_FIELD};
+use crate::server::{parse_request, ApiError};
+
+/// Field of the scenario naming the run, e.g. the commit the daemon was built from.
+pub const LABEL_FIELD: &str = "label";
+/// Optional field of the scenario listing the algorithms to benchmark, all of them by default.
+pub const ALGORITHMS_FIELD: &str = "algorithms";
+/// Field of the scenario listing the amounts of claims of the credentials.
+pub const CLAIMS_FIELD: &str = "claims";
+/// Optional field of the scenario holding the amount of executions averaged by every measurement, 1 by default.
+pub const ITERATIONS_FIELD: &str = "iterations";
+/// Metrics measured for every algorithm and amount of claims, in the order of `ScenarioRow::values`.
+pub const METRICS: [&str; 5] = ["vc_issuance_us", "vp_issuance_us", "vp_verification_us", "vc_jwt_length", "vp_jwt_length"];
+/// Largest amount of claims a scenario can request, bounding the setup of the accumulator.
+const MAX_CLAIMS: usize = 1000;
+
+/// Benchmark submitted to the daemon: every algorithm issues a VC with each amount of claims, presents half of them,
+/// and verifies the presentation.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Scenario {
+    /// Name of the run, used to compare runs over time.
+    pub label: String,
+    /// Algorithms to benchmark, or all the algorithms of the crate if empty.
+    pub algorithms: Vec<String>,
+    /// Amounts of claims of the credentials.
+    pub claims: Vec<usize>,
+    /// Amount of executions averaged by every measurement.
+    pub iterations: i8,
+}
+
+impl Scenario {
+
+    /// Parses a scenario from the body of a submission, e.g. `{"label": "4557bb2", "claims": [10, 20], "iterations": 5}`.
+    ///
+    /// # Arguments
+    /// * `request` - JSON object of the submission.
+    ///
+    /// # Returns
+    /// A result containing the scenario or a string containing an error.
+    pub fn from_json(request: &Map<String, Value>) -> Result<Self, String> {
+        let label: String = match request.get(LABEL_FIELD) {
+            Some(Value::String(label)) if !label.is_empty() => { label.clone() }
+            _ => { return Err(format!("Field {LABEL_FIELD} must be a non-empty string")) }
+        };
+        let algorithms: Vec<String> = match request.get(ALGORITHMS_FIELD) {
+            None => { vec![] }
+            Some(Value::Array(algorithms)) => match algorithms.iter().map(|algorithm| algorithm.as_str().map(str::to_string)).collect::<Option<Vec<String>>>() {
+                Some(algorithms) => { algorithms }
+                None => { return Err(format!("Field {ALGORITHMS_FIELD} must be an array of strings")) }
+            },
+            Some(_) => { return Err(format!("Field {ALGORITHMS_FIELD} must be an array of strings")) }
+        };
+        let claims: Vec<usize> = match request.get(CLAIMS_FIELD) {
+            Some(Value::Array(claims)) if !claims.is_empty() => match claims.iter().map(|claims| claims.as_u64().map(|claims| claims as usize)).collect::<Option<Vec<usize>>>() {
+                Some(claims) if claims.iter().all(|claims| (1..=MAX_CLAIMS).contains(claims)) => { claims }
+                _ => { return Err(format!("Field {CLAIMS_FIELD} must hold amounts of claims between 1 and {MAX_CLAIMS}")) }
+            },
+            _ => { return Err(format!("Field {CLAIMS_FIELD} must be a non-empty array")) }
+        };
+        let iterations: i8 = match request.get(ITERATIONS_FIELD) {
+            None => { 1 }
+            Some(iterations) => match iterations.as_u64().and_then(|iterations| i8::try_from(iterations).ok()) {
+                Some(iterations) if iterations > 0 => { iterations }
+                _ => { return Err(format!("Field {ITERATIONS_FIELD} must be between 1 and {}", i8::MAX)) }
+            },
+        };
+
+        Ok(Scenario { label, algorithms, claims, iterations })
+    }
+
+    /// Converts the scenario to the JSON representation parsed by `from_json`.
+    pub fn to_json(&self) -> Value {
+        let mut scenario: Map<String, Value> = Map::new();
+        scenario.insert(LABEL_FIELD.to_string(), Value::String(self.label.clone()));
+        scenario.insert(ALGORITHMS_FIELD.to_string(), Value::from(self.algorithms.clone()));
+        scenario.insert(CLAIMS_FIELD.to_string(), Value::from(self.claims.clone()));
+        scenario.insert(ITERATIONS_FIELD.to_string(), Value::from(self.iterations));
+
+        Value::Object(scenario)
+    }
+
+    /// Runs the scenario on the calling thread. A failing algorithm only fails its own rows.
+    ///
+    /// # Returns
+    /// A result containing a row for every amount of claims and algorithm, or a string containing an error if the
+    /// algorithms cannot be set up.
+    pub fn run(&self) -> Result<Vec<ScenarioRow>, String> {
+        let max_claims: usize = self.claims.iter().copied().max().unwrap_or(1);
+        let registry: AdapterRegistry = AdapterRegistry::with_all_adapters(max_claims)?;
+        let algorithms: Vec<String> = if self.algorithms.is_empty() { registry.algorithms() } else { self.algorithms.clone() };
+        let measurement: Measurement = Measurement::Iterations(self.iterations);
+        let claim_generator = FlatClaimGenerator;
+        let mut raw_vc: Map<String, Value> = match serde_json::from_str::<Map<String, Value>>(VC) {
+            Ok(raw_vc) => { raw_vc }
+            Err(err) => { return Err(format!("Failed to parse Raw Verifiable Credential from string. [{err}]")) }
+        };
+
+        let mut rows: Vec<ScenarioRow> = vec![];
+        for n_claims in &self.claims {
+            claim_generator.substitute_claims(&mut raw_vc, *n_claims)?;
+            let disclosures: Vec<String> = claim_generator.disclosures(&(1..=(n_claims / 2).max(1)).collect::<Vec<usize>>());
+
+            for algorithm in &algorithms {
+                let values = match registry.get(algorithm) {
+                    Some(adapter) => { measure_adapter(adapter, &raw_vc, &disclosures, measurement) }
+                    None => { Err(format!("No adapter is registered for {algorithm}")) }
+                };
+                if let Err(err) = &values {
+                    warn!("[{}] Scenario {} failed with {} claims: {}", algorithm, self.label, n_claims, err);
+                }
+                rows.push(ScenarioRow { algorithm: algorithm.clone(), claims: *n_claims, values });
+            }
+        }
+
+        Ok(rows)
+    }
+}
+
+/// Measures the metrics of an algorithm, in the order of `METRICS`.
+fn measure_adapter(adapter: &dyn Adapter, raw_vc: &Map<String, Value>, disclosures: &Vec<String>, measurement: Measurement) -> Result<[u128; 5], String> {
+    let (vc_issuance, (vc, vc_jwt)) = Benchmark::measure(|| adapter.issue_vc(raw_vc), measurement)?;
+    let (vp_issuance, (_, vp_jwt)) = Benchmark::measure(|| adapter.issue_vp(&vc, disclosures), measurement)?;
+    let (vp_verification, _) = Benchmark::measure(|| adapter.verify_vp(&vp_jwt), measurement)?;
+
+    Ok([vc_issuance.as_micros(), vp_issuance.as_micros(), vp_verification.as_micros(), vc_jwt.len() as u128, vp_jwt.len() as u128])
+}
+
+
+/// Measurements of an algorithm for an amount of claims.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ScenarioRow {
+    /// Name of the algorithm.
+    pub algorithm: String,
+    /// Amount of claims of the credential.
+    pub claims: usize,
+    /// Values of the metrics, in the order of `METRICS`, or the error that interrupted the measurements.
+    pub values: Result<[u128; 5], String>,
+}
+
+impl ScenarioRow {
+
+    /// Value of a metric, if it was measured.
+    ///
+    /// # Arguments
+    /// * `metric` - Name of the metric, among `METRICS`.
+    pub fn value(&self, metric: &str) -> Option<u128> {
+        let index: usize = METRICS.iter().position(|name| *name == metric)?;
+        self.values.as_ref().ok().map(|values| values[index])
+    }
+
+    /// Converts the row to JSON, with one field per metric, or an `error` field.
+    pub fn to_json(&self) -> Value {
+        let mut row: Map<String, Value> = Map::new();
+        row.insert("algorithm".to_string(), Value::String(self.algorithm.clone()));
+        row.insert("claims".to_string(), Value::from(self.claims));
+        match &self.values {
+            Ok(values) => for (metric, value) in METRICS.iter().zip(values) {
+                row.insert(metric.to_string(), Value::from(*value as u64));
+            },
+            Err(err) => { row.insert("error".to_string(), Value::String(err.clone())); }
+        }
+
+        Value::Object(row)
+    }
+}
+
+
+/// Progress of a submitted scenario.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ScenarioStatus {
+    /// Waiting for the scenarios submitted before it.
+    Queued,
+    /// Being executed.
+    Running,
+    /// Executed, possibly with failing rows.
+    Completed,
+    /// Interrupted before any measurement.
+    Failed(String),
+}
+
+/// Scenario submitted to the daemon, along with its results.
+#[derive(Clone, Debug)]
+pub struct ScenarioRecord {
+    /// Identifier assigned at submission, in order of submission.
+    pub id: usize,
+    /// Submission time, in seconds since the Unix epoch.
+    pub submitted_at: u64,
+    /// Submitted scenario.
+    pub scenario: Scenario,
+    /// Progress of the scenario.
+    pub status: ScenarioStatus,
+    /// Results, once the scenario is completed.
+    pub rows: Vec<ScenarioRow>,
+}
+
+impl ScenarioRecord {
+
+    /// Converts the record to JSON, including the rows if requested.
+    ///
+    /// # Arguments
+    /// * `with_rows` - Whether the rows are included.
+    pub fn to_json(&self, with_rows: bool) -> Value {
+        let (status, error) = match &self.status {
+            ScenarioStatus::Queued => { ("queued", None) }
+            ScenarioStatus::Running => { ("running", None) }
+            ScenarioStatus::Completed => { ("completed", None) }
+            ScenarioStatus::Failed(err) => { ("failed", Some(err.clone())) }
+        };
+
+        let mut record: Map<String, Value> = Map::new();
+        record.insert(FORMAT_VERSION_FIELD.to_string(), Value::from(FORMAT_VERSION));
+        record.insert("id".to_string(), Value::from(self.id));
+        record.insert("submitted_at".to_string(), Value::from(self.submitted_at));
+        record.insert("scenario".to_string(), self.scenario.to_json());
+        record.insert("status".to_string(), Value::String(status.to_string()));
+        if let Some(error) = error {
+            record.insert("error".to_string(), Value::String(error));
+        }
+        if with_rows {
+            record.insert("rows".to_string(), Value::Array(self.rows.iter().map(ScenarioRow::to_json).collect()));
+        }
+
+        Value::Object(record)
+    }
+
+    /// Chart of a metric as a function of the amount of claims, with one series per algorithm.
+    ///
+    /// # Arguments
+    /// * `metric` - Name of the metric, among `METRICS`.
+    pub fn chart(&self, metric: &str) -> LineChart {
+        let mut chart: LineChart = LineChart::new(&format!("{}: {}", self.scenario.label, metric), "claims", metric);
+        for row in &self.rows {
+            if let Some(value) = row.value(metric) {
+                chart.add_point(&row.algorithm, row.claims as f64, value as f64);
+            }
+        }
+
+        chart
+    }
+}
+
+
+/// Values of a metric of every algorithm over a sequence of scenarios.
+pub type History = BTreeMap<String, Vec<Option<u128>>>;
+
+/// Benchmark daemon: scenarios are queued by the HTTP handlers and executed one at a time by a dedicated thread, so
+/// that concurrent submissions do not disturb each other's measurements.
+pub struct Daemon {
+    /// Every scenario submitted since the start of the daemon, indexed by identifier.
+    records: Mutex<Vec<ScenarioRecord>>,
+    /// Queue of the identifiers of the scenarios to execute.
+    queue: Mutex<Sender<usize>>,
+    /// Directory the results and charts of the completed scenarios are written to, if any.
+    results_dir: Option<PathBuf>,
+}
+
+impl Daemon {
+
+    /// Starts the thread executing the scenarios.
+    ///
+    /// # Arguments
+    /// * `results_dir` - Directory the results and charts of the completed scenarios are written to, if any.
+    ///
+    /// # Returns
+    /// A result containing the daemon or a string containing an error if the results directory cannot be created.
+    pub fn start(results_dir: Option<PathBuf>) -> Result<Arc<Self>, String> {
+        if let Some(results_dir) = &results_dir {
+            if let Err(err) = fs::create_dir_all(results_dir) {
+                return Err(format!("Failed to create {}: [{err}]", results_dir.display()));
+            }
+        }
+
+        let (sender, receiver) = channel::<usize>();
+        let daemon: Arc<Daemon> = Arc::new(Daemon { records: Mutex::new(vec![]), queue: Mutex::new(sender), results_dir });
+        let worker: Arc<Daemon> = Arc::clone(&daemon);
+        thread::spawn(move || worker.execute(receiver));
+
+        Ok(daemon)
+    }
+
+    /// Queues a scenario.
+    ///
+    /// # Arguments
+    /// * `scenario` - Scenario to execute.
+    ///
+    /// # Returns
+    /// A result containing the identifier of the scenario or a string containing an error if the executing thread stopped.
+    pub fn submit(&self, scenario: Scenario) -> Result<usize, String> {
+        let submitted_at: u64 = SystemTime::now().duration_since(UNIX_EPOCH).map(|elapsed| elapsed.as_secs()).unwrap_or(0);
+        let mut records = self.records();
+        let id: usize = records.len();
+        info!("Scenario {} ({}) queued", id, scenario.label);
+        records.push(ScenarioRecord { id, submitted_at, scenario, status: ScenarioStatus::Queued, rows: vec![] });
+
+        match self.queue.lock().map(|queue| queue.send(id)) {
+            Ok(Ok(())) => { Ok(id) }
+            _ => {
+                records[id].status = ScenarioStatus::Failed("The daemon is not executing scenarios".to_string());
+                Err("The daemon is not executing scenarios".to_string())
+            }
+        }
+    }
+
+    /// Retrieves a scenario with its results.
+    ///
+    /// # Arguments
+    /// * `id` - Identifier of the scenario.
+    pub fn record(&self, id: usize) -> Option<ScenarioRecord> {
+        self.records().get(id).cloned()
+    }
+
+    /// Retrieves every scenario, in order of submission.
+    pub fn all_records(&self) -> Vec<ScenarioRecord> {
+        self.records().clone()
+    }
+
+    /// Values of a metric over the completed scenarios, in order of submission, for a given amount of claims, so that
+    /// the runs of successive commits can be compared.
+    ///
+    /// # Arguments
+    /// * `metric` - Name of the metric, among `METRICS`.
+    /// * `claims` - Amount of claims of the compared credentials.
+    ///
+    /// # Returns
+    /// The labels of the compared scenarios and, for every algorithm, its value in each of them, if measured.
+    pub fn history(&self, metric: &str, claims: usize) -> (Vec<String>, History) {
+        let records: Vec<ScenarioRecord> = self.records().iter().filter(|record| record.status == ScenarioStatus::Completed).cloned().collect();
+        let labels: Vec<String> = records.iter().map(|record| record.scenario.label.clone()).collect();
+        let mut history: History = BTreeMap::new();
+
+        for (index, record) in records.iter().enumerate() {
+            for row in record.rows.iter().filter(|row| row.claims == claims) {
+                history.entry(row.algorithm.clone()).or_insert_with(|| vec![None; records.len()])[index] = row.value(metric);
+            }
+        }
+
+        (labels, history)
+    }
+
+    /// Locks the records, recovering them if a handler panicked while holding the lock.
+    fn records(&self) -> MutexGuard<'_, Vec<ScenarioRecord>> {
+        self.records.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Executes the queued scenarios, for as long as the process runs.
+    fn execute(&self, receiver: Receiver<usize>) {
+        for id in receiver {
+            let scenario: Scenario = match self.records().get_mut(id) {
+                Some(record) => {
+                    record.status = ScenarioStatus::Running;
+                    record.scenario.clone()
+                }
+                None => { continue }
+            };
+
+            info!("Scenario {} ({}) started", id, scenario.label);
+            let outcome = scenario.run();
+            let record: ScenarioRecord = {
+                let mut records = self.records();
+                match outcome {
+                    Ok(rows) => {
+                        records[id].rows = rows;
+                        records[id].status = ScenarioStatus::Completed;
+                    }
+                    Err(err) => { records[id].status = ScenarioStatus::Failed(err) }
+                }
+                records[id].clone()
+            };
+            info!("Scenario {} ({}) finished: {:?}", id, scenario.label, record.status);
+
+            if let Err(err) = self.write_results(&record) {
+                error!("Failed to write the results of scenario {}: {}", id, err);
+            }
+        }
+    }
+
+    /// Writes the results of a scenario as `scenario_<id>.json`, along with a `scenario_<id>_<metric>.svg` chart per metric.
+    fn write_results(&self, record: &ScenarioRecord) -> Result<(), String> {
+        let results_dir: &PathBuf = match &self.results_dir {
+            Some(results_dir) => { results_dir }
+            None => { return Ok(()) }
+        };
+
+        let mut files: Vec<(PathBuf, String)> = vec![(results_dir.join(format!("scenario_{}.json", record.id)), record.to_json(true).to_string())];
+        if record.status == ScenarioStatus::Completed {
+            for metric in METRICS {
+                files.push((results_dir.join(format!("scenario_{}_{}.svg", record.id, metric)), record.chart(metric).to_svg()));
+            }
+        }
+
+        for (path, content) in files {
+            if let Err(err) = fs::write(&path, content) {
+                return Err(format!("Failed to write {}: [{err}]", path.display()));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+
+/// Checks that a metric is among `METRICS`.
+fn check_metric(metric: &str) -> Result<(), ApiError> {
+    if METRICS.contains(&metric) {
+        Ok(())
+    } else {
+        Err(ApiError::not_found(format!("Unknown metric {metric}, expected one of {METRICS:?}")))
+    }
+}
+
+/// Response holding an SVG document.
+fn svg_response(svg: String) -> Response {
+    ([(header::CONTENT_TYPE, "image/svg+xml")], svg).into_response()
+}
+
+/// Queues a scenario: `{"label": ..., "claims": [...], "algorithms": [...], "iterations": ...}` returns `{"id": ...}`.
+async fn submit(State(daemon): State<Arc<Daemon>>, body: Bytes) -> Result<Json<Value>, ApiError> {
+    let request = parse_request(&body)?;
+    let scenario: Scenario = Scenario::from_json(&request).map_err(ApiError::invalid_request)?;
+
+    match daemon.submit(scenario) {
+        Ok(id) => { Ok(Json(json!({ "id": id }))) }
+        Err(err) => { Err(ApiError::internal_error(err)) }
+    }
+}
+
+/// Lists the scenarios, without their rows.
+async fn scenarios(State(daemon): State<Arc<Daemon>>) -> Json<Value> {
+    Json(Value::Array(daemon.all_records().iter().map(|record| record.to_json(false)).collect()))
+}
+
+/// Returns a scenario with its rows.
+async fn scenario(State(daemon): State<Arc<Daemon>>, Path(id): Path<usize>) -> Result<Json<Value>, ApiError> {
+    match daemon.record(id) {
+        Some(record) => { Ok(Json(record.to_json(true))) }
+        None => { Err(ApiError::not_found(format!("No scenario has identifier {id}"))) }
+    }
+}
+
+/// Returns the chart of a metric of a scenario.
+async fn scenario_chart(State(daemon): State<Arc<Daemon>>, Path((id, metric)): Path<(usize, String)>) -> Result<Response, ApiError> {
+    check_metric(&metric)?;
+    match daemon.record(id) {
+        Some(record) => { Ok(svg_response(record.chart(&metric).to_svg())) }
+        None => { Err(ApiError::not_found(format!("No scenario has identifier {id}"))) }
+    }
+}
+
+/// Returns the values of a metric over the completed scenarios: `{"labels": [...], "series": {"<algorithm>": [...]}}`.
+async fn history(State(daemon): State<Arc<Daemon>>, Path((metric, claims)): Path<(String, usize)>) -> Result<Json<Value>, ApiError> {
+    check_metric(&metric)?;
+    let (labels, history) = daemon.history(&metric, claims);
+    let series: Map<String, Value> = history
+        .into_iter()
+        .map(|(algorithm, values)| (algorithm, Value::Array(values.into_iter().map(|value| value.map_or(Value::Null, |value| Value::from(value as u64))).collect())))
+        .collect();
+
+    Ok(Json(json!({ "metric": metric, "claims": claims, "labels": labels, "series": series })))
+}
+
+/// Returns the chart of the values of a metric over the completed scenarios, the abscissa being the position of the
+/// scenario in the history.
+async fn history_chart(State(daemon): State<Arc<Daemon>>, Path((metric, claims)): Path<(String, usize)>) -> Result<Response, ApiError> {
+    check_metric(&metric)?;
+    let (_, history) = daemon.history(&metric, claims);
+    let mut chart: LineChart = LineChart::new(&format!("{metric} with {claims} claims"), "run", &metric);
+    for (algorithm, values) in &history {
+        for (index, value) in values.iter().enumerate() {
+            if let Some(value) = value {
+                chart.add_point(algorithm, (index + 1) as f64, *value as f64);
+            }
+        }
+    }
+
+    Ok(svg_response(chart.to_svg()))
+}
+
+
+/// Builds the router of the daemon, exposing `POST /scenarios`, `GET /scenarios`, `GET /scenarios/{id}`,
+/// `GET /scenarios/{id}/charts/{metric}`, `GET /history/{metric}/{claims}` and `GET /history/{metric}/{claims}/chart`.
+///
+/// # Arguments
+/// * `daemon` - Daemon executing the scenarios.
+///
+/// # Returns
+/// The router of the daemon.
+pub fn router(daemon: Arc<Daemon>) -> Router {
+    Router::new()
+        .route("/scenarios", get(scenarios).post(submit))
+        .route("/scenarios/{id}", get(scenario))
+        .route("/scenarios/{id}/charts/{metric}", get(scenario_chart))
+        .route("/history/{metric}/{claims}", get(history))
+        .route("/history/{metric}/{claims}/chart", get(history_chart))
+        .with_state(daemon)
+}
+
+/// Serves the benchmark daemon until the process is terminated.
+///
+/// # Arguments
+/// * `address` - Address to listen on, e.g. `127.0.0.1:8081`. The API is unauthenticated, so it should be local.
+/// * `results_dir` - Directory the results and charts of the completed scenarios are written to, if any.
+///
+/// # Returns
+/// A result containing a string in case the daemon cannot be started.
+///
+/// # Examples
+/// ```no_run
+/// use std::path::PathBuf;
+/// use csd_jwt::daemon::serve;
+///
+/// # async fn run() -> Result<(), String> {
+/// serve("127.0.0.1:8081", Some(PathBuf::from("daemon_results"))).await
+/// # }
+/// ```
+pub async fn serve(address: &str, results_dir: Option<PathBuf>) -> Result<(), String> {
+    let daemon: Arc<Daemon> = Daemon::start(results_dir)?;
+    let listener = match tokio::net::TcpListener::bind(address).await {
+        Ok(listener) => { listener }
+        Err(err) => { return Err(format!("Failed to bind {address}: [{err}]")) }
+    };
+
+    info!("Benchmark daemon listening on {}", address);
+    match axum::serve(listener, router(daemon)).await {
+        Ok(_) => { Ok(()) }
+        Err(err) => { Err(format!("Daemon terminated: [{err}]")) }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs;
+    use std::path::PathBuf;
+    use std::thread;
+    use std::time::{Duration, Instant};
+    use serde_json::{json, Map, Value};
+    use crate::daemon::{Daemon, Scenario, ScenarioRecord, ScenarioStatus, METRICS};
+
+    fn object(value: Value) -> Map<String, Value> {
+        value.as_object().cloned().unwrap_or_default()
+    }
+
+    #[test]
+    fn scenarios_are_parsed() -> Result<(), String> {
+        let scenario: Scenario = Scenario::from_json(&object(json!({"label": "4557bb2", "claims": [10, 20]})))?;
+        assert_eq!(scenario, Scenario { label: "4557bb2".to_string(), algorithms: vec![], claims: vec![10, 20], iterations: 1 });
+        assert_eq!(Scenario::from_json(&object(scenario.to_json()))?, scenario);
+
+        let malformed: [Value; 9] = [
+            json!({"claims": [10]}),
+            json!({"label": "", "claims": [10]}),
+            json!({"label": "run", "claims": []}),
+            json!({"label": "run", "claims": [0]}),
+            json!({"label": "run", "claims": [1001]}),
+            json!({"label": "run", "claims": ["10"]}),
+            json!({"label": "run", "claims": [10], "algorithms": ["SD-JWT", 1]}),
+            json!({"label": "run", "claims": [10], "iterations": 0}),
+            json!({"label": "run", "claims": [10], "iterations": 128}),
+        ];
+        for request in malformed {
+            assert!(Scenario::from_json(&object(request.clone())).is_err(), "{request} was parsed");
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn scenarios_are_executed_in_order() -> Result<(), String> {
+        let results_dir: PathBuf = env::temp_dir().join(format!("csd_jwt_daemon_{}", std::process::id()));
+        let daemon = Daemon::start(Some(results_dir.clone()))?;
+        let scenario = |label: &str| Scenario { label: label.to_string(), algorithms: vec!["SD-JWT".to_string(), "unknown".to_string()], claims: vec![2], iterations: 1 };
+        assert_eq!(daemon.submit(scenario("first"))?, 0);
+        assert_eq!(daemon.submit(scenario("second"))?, 1);
+
+        let start = Instant::now();
+        let completed = |record: &ScenarioRecord| record.status == ScenarioStatus::Completed;
+        let last_chart: PathBuf = results_dir.join(format!("scenario_1_{}.svg", METRICS[METRICS.len() - 1]));
+        while !daemon.all_records().iter().all(completed) || !last_chart.exists() {
+            if start.elapsed() > Duration::from_secs(60) {
+                return Err(format!("Scenarios did not complete: {:?}", daemon.all_records()));
+            }
+            thread::sleep(Duration::from_millis(20));
+        }
+
+        // A failing algorithm only fails its own rows.
+        let record: ScenarioRecord = daemon.record(0).ok_or("Scenario 0 is missing")?;
+        assert_eq!(record.rows.len(), 2);
+        assert!(record.rows[0].values.is_ok() && record.rows[1].values.is_err());
+        assert!(record.to_json(true)["rows"][1].get("error").is_some());
+        assert!(daemon.record(2).is_none());
+
+        let (labels, history) = daemon.history("vc_jwt_length", 2);
+        assert_eq!(labels, vec!["first".to_string(), "second".to_string()]);
+        assert!(history["SD-JWT"].iter().all(Option::is_some));
+        assert_eq!(history["unknown"], vec![None, None]);
+        assert!(daemon.history("vc_jwt_length", 3).1.is_empty());
+
+        assert!(results_dir.join("scenario_1.json").exists());
+        assert!(METRICS.iter().all(|metric| results_dir.join(format!("scenario_1_{metric}.svg")).exists()));
+        let _ = fs::remove_dir_all(&results_dir);
+
+        Ok(())
+    }
+}
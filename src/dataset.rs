@@ -0,0 +1,245 @@
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, Instant};
+use csv::ReaderBuilder;
+use serde_json::{Map, Value};
+use crate::adapters::adapter::Adapter;
+use crate::common_data::CLAIMS;
+
+/// Subject data of a population, one row of claims per credential to be issued, as an issuer provisioning real holders
+/// would read it from its records.
+///
+/// # Examples
+/// ```
+/// use serde_json::{json, Map, Value};
+/// use csd_jwt::dataset::SubjectDataset;
+///
+/// let dataset = SubjectDataset::from_csv_str("name,age,member\nAda,36,true\nAlan,,false\n").unwrap();
+/// assert_eq!(dataset.len(), 2);
+/// assert_eq!(dataset.rows()[0].get("age"), Some(&json!(36)));
+/// assert_eq!(dataset.rows()[1].get("age"), None);
+///
+/// let template: Map<String, Value> = serde_json::from_str(r#"{"iss": "issuer", "credentialSubject": {}}"#).unwrap();
+/// let credential = dataset.personalize(&template, 1).unwrap();
+/// assert_eq!(credential["credentialSubject"], json!({"name": "Alan", "member": false}));
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SubjectDataset {
+    /// Claims of every subject, in order of the dataset.
+    rows: Vec<Map<String, Value>>,
+}
+
+impl SubjectDataset {
+
+    /// Parses a CSV dataset, whose header holds the names of the claims. Numbers and booleans are converted to JSON
+    /// numbers and booleans, any other field is a string, and empty fields are left out of the claims of the row.
+    ///
+    /// # Arguments
+    /// * `content` - Content of the CSV dataset.
+    ///
+    /// # Returns
+    /// A result containing the dataset or a string containing an error.
+    pub fn from_csv_str(content: &str) -> Result<Self, String> {
+        let mut reader = ReaderBuilder::new().from_reader(content.as_bytes());
+        let names: Vec<String> = match reader.headers() {
+            Ok(headers) => { headers.iter().map(String::from).collect() }
+            Err(err) => { return Err(format!("Failed to read the header of the dataset: [{err}]")) }
+        };
+
+        let mut rows: Vec<Map<String, Value>> = vec![];
+        for (index, record) in reader.records().enumerate() {
+            let record = match record {
+                Ok(record) => { record }
+                Err(err) => { return Err(format!("Failed to read row {} of the dataset: [{err}]", index + 1)) }
+            };
+            rows.push(names.iter().zip(record.iter())
+                .filter(|(_, field)| !field.is_empty())
+                .map(|(name, field)| (name.clone(), field_to_claim(field)))
+                .collect());
+        }
+
+        Self::from_rows(rows)
+    }
+
+    /// Parses a JSON dataset, an array holding the claims of every subject as an object.
+    ///
+    /// # Arguments
+    /// * `content` - Content of the JSON dataset.
+    ///
+    /// # Returns
+    /// A result containing the dataset or a string containing an error.
+    pub fn from_json_str(content: &str) -> Result<Self, String> {
+        let entries: Vec<Value> = match serde_json::from_str::<Vec<Value>>(content) {
+            Ok(entries) => { entries }
+            Err(err) => { return Err(format!("Failed to parse the dataset as an array: [{err}]")) }
+        };
+
+        let mut rows: Vec<Map<String, Value>> = vec![];
+        for (index, entry) in entries.into_iter().enumerate() {
+            match entry {
+                Value::Object(claims) => { rows.push(claims) }
+                _ => { return Err(format!("Row {} of the dataset is not an object", index + 1)) }
+            }
+        }
+
+        Self::from_rows(rows)
+    }
+
+    /// Reads a dataset from a file, parsed as JSON if its extension is `.json` and as CSV otherwise.
+    ///
+    /// # Arguments
+    /// * `path` - Path of the dataset.
+    ///
+    /// # Returns
+    /// A result containing the dataset or a string containing an error.
+    pub fn read(path: &Path) -> Result<Self, String> {
+        let content: String = match fs::read_to_string(path) {
+            Ok(content) => { content }
+            Err(err) => { return Err(format!("Failed to read dataset {}: [{err}]", path.display())) }
+        };
+
+        let dataset = match path.extension().and_then(|extension| extension.to_str()) {
+            Some("json") => { Self::from_json_str(&content) }
+            _ => { Self::from_csv_str(&content) }
+        };
+        dataset.map_err(|err| format!("Invalid dataset {}: [{err}]", path.display()))
+    }
+
+    /// Builds a dataset from the claims of every subject, rejecting empty datasets and subjects without claims.
+    fn from_rows(rows: Vec<Map<String, Value>>) -> Result<Self, String> {
+        if rows.is_empty() {
+            return Err("The dataset does not contain any row".to_string());
+        }
+        if let Some(index) = rows.iter().position(|claims| claims.is_empty()) {
+            return Err(format!("Row {} of the dataset does not contain any claim", index + 1));
+        }
+
+        Ok(SubjectDataset { rows })
+    }
+
+    /// Claims of every subject, in order of the dataset.
+    pub fn rows(&self) -> &[Map<String, Value>] {
+        &self.rows
+    }
+
+    /// Amount of subjects in the dataset.
+    pub fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    /// Whether the dataset has no subject.
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    /// Largest amount of claims of a subject, to size the algorithms for the dataset.
+    pub fn max_claims(&self) -> usize {
+        self.rows.iter().map(|claims| claims.len()).max().unwrap_or(0)
+    }
+
+    /// Personalizes a raw VC for a subject, replacing its claims with the ones of the row.
+    ///
+    /// # Arguments
+    /// * `template` - Raw VC whose claims are replaced.
+    /// * `index` - Index of the row of the subject, starting from 0.
+    ///
+    /// # Returns
+    /// A result containing the raw VC of the subject or a string containing an error.
+    pub fn personalize(&self, template: &Map<String, Value>, index: usize) -> Result<Map<String, Value>, String> {
+        let claims: &Map<String, Value> = match self.rows.get(index) {
+            Some(claims) => { claims }
+            None => { return Err(format!("Row {index} is out of the {} rows of the dataset", self.rows.len())) }
+        };
+
+        let mut raw_vc: Map<String, Value> = template.clone();
+        raw_vc.insert(CLAIMS.to_string(), Value::Object(claims.clone()));
+        Ok(raw_vc)
+    }
+}
+
+
+/// Outcome of the issuance of a credential to every subject of a dataset.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BulkIssuance {
+    /// Amount of credentials issued.
+    pub credentials: usize,
+    /// Wall-clock duration of the issuance of every credential, including their personalization.
+    pub elapsed: Duration,
+    /// Total length of the issued VC JWTs, i.e. the storage the population needs.
+    pub total_length: usize,
+}
+
+impl BulkIssuance {
+
+    /// Credentials issued per second.
+    pub fn per_second(&self) -> f64 {
+        self.credentials as f64 / self.elapsed.as_secs_f64()
+    }
+
+    /// Mean length of the issued VC JWTs.
+    pub fn mean_length(&self) -> f64 {
+        self.total_length as f64 / self.credentials as f64
+    }
+}
+
+
+/// Issues a credential to every subject of a dataset with the same adapter, as an issuer provisioning a population
+/// would, and measures the aggregate throughput and storage of the issuance.
+///
+/// # Arguments
+/// * `adapter` - Adapter issuing the credentials.
+/// * `template` - Raw VC personalized for every subject.
+/// * `dataset` - Subjects the credentials are issued to.
+///
+/// # Returns
+/// A result containing the outcome of the issuance or a string containing an error if an issuance fails.
+///
+/// # Examples
+/// ```
+/// use serde_json::{Map, Value};
+/// use csd_jwt::adapters::adapter::Adapter;
+/// use csd_jwt::adapters::hashes::sd_jwt_adapter::SdJwtAdapter;
+/// use csd_jwt::common_data::VC;
+/// use csd_jwt::dataset::{issue_population, SubjectDataset};
+///
+/// let dataset = SubjectDataset::from_csv_str("name,age\nAda,36\nAlan,41\n").unwrap();
+/// let adapter = SdJwtAdapter::new(dataset.max_claims()).unwrap();
+/// let template: Map<String, Value> = serde_json::from_str(VC).unwrap();
+///
+/// let issuance = issue_population(&adapter, &template, &dataset).unwrap();
+/// assert_eq!(issuance.credentials, 2);
+/// assert!(issuance.per_second() > 0.0);
+///
+/// // Every issued credential counts towards the total length.
+/// let (_, vc_jwt) = adapter.issue_vc(&dataset.personalize(&template, 0).unwrap()).unwrap();
+/// assert!(issuance.total_length > vc_jwt.len());
+/// ```
+pub fn issue_population(adapter: &dyn Adapter, template: &Map<String, Value>, dataset: &SubjectDataset) -> Result<BulkIssuance, String> {
+    let mut total_length: usize = 0;
+    let start = Instant::now();
+
+    for index in 0..dataset.len() {
+        let raw_vc: Map<String, Value> = dataset.personalize(template, index)?;
+        match adapter.issue_vc(&raw_vc) {
+            Ok((_, vc_jwt)) => { total_length += vc_jwt.len() }
+            Err(err) => { return Err(format!("Failed to issue the credential of row {}: [{err}]", index + 1)) }
+        }
+    }
+
+    Ok(BulkIssuance { credentials: dataset.len(), elapsed: start.elapsed(), total_length })
+}
+
+
+/// Converts a CSV field to a claim: numbers and booleans are converted to JSON, any other field is a string.
+fn field_to_claim(field: &str) -> Value {
+    if let Ok(integer) = field.parse::<i64>() {
+        return Value::from(integer);
+    }
+    if let Ok(boolean) = field.parse::<bool>() {
+        return Value::Bool(boolean);
+    }
+    match field.parse::<f64>().ok().and_then(serde_json::Number::from_f64) {
+        Some(number) => { Value::Number(number) }
+        None => { Value::String(field.to_string()) }
+    }
+}
@@ -0,0 +1,259 @@
+use serde_json::{Map, Value};
+use crate::common_data::CLAIMS;
+use crate::deterministic::unix_time;
+
+/// Claim holding the birthdate from which the age predicates are derived, unless another source is configured.
+pub const DEFAULT_BIRTHDATE_CLAIM: &str = "birthdate";
+/// Prefix of the names of the age predicates, followed by the threshold, e.g. `age_over_18`.
+pub const AGE_OVER_PREFIX: &str = "age_over_";
+
+/// Calendar date, ordered chronologically.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Date {
+    pub year: i64,
+    pub month: u32,
+    pub day: u32,
+}
+
+impl Date {
+
+    /// Parses an ISO 8601 calendar date of the form `YYYY-MM-DD`.
+    ///
+    /// # Arguments
+    /// * `date` - Date to be parsed.
+    ///
+    /// # Returns
+    /// A result containing the date or a string containing an error.
+    pub fn parse(date: &str) -> Result<Self, String> {
+        let parts: Vec<&str> = date.split('-').collect();
+        let (year, month, day) = match parts.as_slice() {
+            [year, month, day] if year.len() == 4 && month.len() == 2 && day.len() == 2 => {
+                match (year.parse::<i64>(), month.parse::<u32>(), day.parse::<u32>()) {
+                    (Ok(year), Ok(month), Ok(day)) => { (year, month, day) }
+                    _ => { return Err(format!("Date {date} is not of the form YYYY-MM-DD")) }
+                }
+            }
+            _ => { return Err(format!("Date {date} is not of the form YYYY-MM-DD")) }
+        };
+
+        if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+            return Err(format!("Date {date} does not exist"));
+        }
+
+        Ok(Date { year, month, day })
+    }
+
+    /// Current date in UTC.
+    pub fn today() -> Self {
+        match unix_time() {
+            Ok(seconds) => { Date::from_days((seconds / 86_400) as i64) }
+            Err(_) => { Date::from_days(0) }
+        }
+    }
+
+    /// Date a given amount of days after 1970-01-01.
+    ///
+    /// # Arguments
+    /// * `days` - Days since the UNIX epoch.
+    ///
+    /// # Returns
+    /// The date in the proleptic Gregorian calendar.
+    pub fn from_days(days: i64) -> Self {
+        // Conversion of the days since 1970-01-01 to the proleptic Gregorian calendar, in eras of 400 years starting
+        // from March 1st so that leap days fall at the end of the year.
+        let z = days + 719_468;
+        let era = z.div_euclid(146_097);
+        let day_of_era = z.rem_euclid(146_097);
+        let year_of_era = (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let shifted_month = (5 * day_of_year + 2) / 153;
+        let day = (day_of_year - (153 * shifted_month + 2) / 5 + 1) as u32;
+        let month = (if shifted_month < 10 { shifted_month + 3 } else { shifted_month - 9 }) as u32;
+        let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+        Date { year, month, day }
+    }
+
+    /// Days since 1970-01-01, the inverse of `from_days`.
+    pub fn days_since_epoch(&self) -> i64 {
+        let year = self.year - if self.month <= 2 { 1 } else { 0 };
+        let era = year.div_euclid(400);
+        let year_of_era = year.rem_euclid(400);
+        let shifted_month = (self.month as i64 + 9) % 12;
+        let day_of_year = (153 * shifted_month + 2) / 5 + self.day as i64 - 1;
+        let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+
+        era * 146_097 + day_of_era - 719_468
+    }
+
+    /// Checks whether somebody born on this date is at least a given age on another date.
+    ///
+    /// # Arguments
+    /// * `years` - Age in years.
+    /// * `on` - Date on which the age is checked.
+    ///
+    /// # Returns
+    /// True if the anniversary of the given age falls on or before `on`.
+    pub fn is_at_least(&self, years: u32, on: &Date) -> bool {
+        Date { year: on.year - years as i64, month: on.month, day: on.day } >= *self
+    }
+}
+
+
+/// Predicate computed by the issuer from a source claim and added to the credential as a claim of its own, which the
+/// holder can disclose instead of the source claim.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Derivation {
+    /// Whether the holder is at least `threshold` years old, according to the date in the `source` claim.
+    AgeOver { source: String, threshold: u32 },
+}
+
+impl Derivation {
+
+    /// Parses a derivation of the form `age_over_<N>`, optionally followed by `@<source claim>`.
+    ///
+    /// # Arguments
+    /// * `derivation` - Derivation to be parsed.
+    ///
+    /// # Returns
+    /// A result containing the derivation or a string containing an error.
+    pub fn parse(derivation: &str) -> Result<Self, String> {
+        let (name, source) = match derivation.trim().split_once('@') {
+            Some((name, source)) => { (name, source.to_string()) }
+            None => { (derivation.trim(), DEFAULT_BIRTHDATE_CLAIM.to_string()) }
+        };
+
+        match name.strip_prefix(AGE_OVER_PREFIX).map(|threshold| threshold.parse::<u32>()) {
+            Some(Ok(threshold)) => { Ok(Derivation::AgeOver { source, threshold }) }
+            Some(Err(err)) => { Err(format!("Invalid threshold in derivation {derivation}: [{err}]")) }
+            None => { Err(format!("Unknown derivation {derivation}, expected {AGE_OVER_PREFIX}<N>[@<claim>]")) }
+        }
+    }
+
+    /// Name of the derived claim.
+    pub fn claim_name(&self) -> String {
+        match self {
+            Derivation::AgeOver { threshold, .. } => { format!("{AGE_OVER_PREFIX}{threshold}") }
+        }
+    }
+
+    /// Computes the value of the derived claim.
+    ///
+    /// # Arguments
+    /// * `claims` - Claims of the credential.
+    /// * `reference_date` - Date on which the predicate is evaluated.
+    ///
+    /// # Returns
+    /// A result containing the value or a string containing an error if the source claim is missing or malformed.
+    pub fn derive(&self, claims: &Map<String, Value>, reference_date: &Date) -> Result<Value, String> {
+        match self {
+            Derivation::AgeOver { source, threshold } => {
+                let birthdate = match claims.get(source) {
+                    Some(Value::String(birthdate)) => { Date::parse(birthdate)? }
+                    Some(_) => { return Err(format!("Claim {source} must be a date to derive {}", self.claim_name())) }
+                    None => { return Err(format!("Claim {source} is missing, {} cannot be derived", self.claim_name())) }
+                };
+                Ok(Value::Bool(birthdate.is_at_least(*threshold, reference_date)))
+            }
+        }
+    }
+}
+
+
+/// Derivations the issuer applies to every credential at issuance, adding one individually disclosable claim per
+/// derivation to the claims of the credential.
+///
+/// # Examples
+/// ```
+/// use serde_json::{json, Map, Value};
+/// use csd_jwt::derived_claims::{Date, DerivedClaims};
+///
+/// let derived_claims = DerivedClaims::parse("age_over_18, age_over_65@dob").unwrap()
+///     .with_reference_date(Date::parse("2025-01-01").unwrap());
+/// let mut raw_vc: Map<String, Value> = serde_json::from_value(json!({
+///     "credentialSubject": { "name": "Alice", "dob": "1990-06-30" }
+/// })).unwrap();
+///
+/// assert!(derived_claims.insert_into(&mut raw_vc).is_err());
+/// let derived_claims = DerivedClaims::parse("age_over_18@dob, age_over_65@dob").unwrap()
+///     .with_reference_date(Date::parse("2025-01-01").unwrap());
+/// derived_claims.insert_into(&mut raw_vc).unwrap();
+/// assert_eq!(raw_vc["credentialSubject"]["age_over_18"], json!(true));
+/// assert_eq!(raw_vc["credentialSubject"]["age_over_65"], json!(false));
+/// ```
+#[derive(Clone, Debug)]
+pub struct DerivedClaims {
+    /// Derivations to apply.
+    derivations: Vec<Derivation>,
+    /// Date on which the predicates are evaluated.
+    reference_date: Date,
+}
+
+impl DerivedClaims {
+
+    /// Constructor for a set of derivations evaluated on the current date.
+    ///
+    /// # Arguments
+    /// * `derivations` - Derivations to apply.
+    ///
+    /// # Returns
+    /// The set of derivations.
+    pub fn new(derivations: Vec<Derivation>) -> Self {
+        DerivedClaims { derivations, reference_date: Date::today() }
+    }
+
+    /// Parses a comma separated list of derivations, e.g. `age_over_18,age_over_21@dob`.
+    ///
+    /// # Arguments
+    /// * `configuration` - List of derivations.
+    ///
+    /// # Returns
+    /// A result containing the set of derivations or a string containing an error.
+    pub fn parse(configuration: &str) -> Result<Self, String> {
+        let derivations = configuration.split(',').map(Derivation::parse).collect::<Result<Vec<Derivation>, String>>()?;
+        Ok(Self::new(derivations))
+    }
+
+    /// Evaluates the predicates on a fixed date instead of the current one.
+    ///
+    /// # Arguments
+    /// * `reference_date` - Date on which the predicates are evaluated.
+    ///
+    /// # Returns
+    /// The set of derivations with the new reference date.
+    pub fn with_reference_date(mut self, reference_date: Date) -> Self {
+        self.reference_date = reference_date;
+        self
+    }
+
+    /// Names of the derived claims.
+    pub fn claim_names(&self) -> Vec<String> {
+        self.derivations.iter().map(|derivation| derivation.claim_name()).collect()
+    }
+
+    /// Adds the derived claims to the claims of a raw VC.
+    ///
+    /// # Arguments
+    /// * `raw_vc` - Raw VC to which the derived claims are added.
+    ///
+    /// # Returns
+    /// A result containing a string in case a source claim is missing or a derived claim already exists.
+    pub fn insert_into(&self, raw_vc: &mut Map<String, Value>) -> Result<(), String> {
+        let claims = match raw_vc.get_mut(CLAIMS) {
+            Some(Value::Object(claims)) => { claims }
+            _ => { return Err(format!("Raw VC does not contain {CLAIMS}")) }
+        };
+
+        let mut derived: Vec<(String, Value)> = vec![];
+        for derivation in &self.derivations {
+            let name = derivation.claim_name();
+            if claims.contains_key(&name) {
+                return Err(format!("Derived claim {name} is already a claim of the credential"));
+            }
+            derived.push((name, derivation.derive(claims, &self.reference_date)?));
+        }
+        claims.extend(derived);
+
+        Ok(())
+    }
+}
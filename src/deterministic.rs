@@ -0,0 +1,153 @@
+use std::cell::RefCell;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+use ark_std::rand::SeedableRng as _;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use crate::entropy::{self, EntropyGuard, SeededEntropy};
+
+thread_local! {
+    /// Injected timestamp of the deterministic mode enabled on the current thread, if any.
+    static DETERMINISTIC_STATE: RefCell<Option<u64>> = const { RefCell::new(None) };
+}
+
+
+/// Mode in which all the randomness drawn by the crate (salts, presentation nonces, issuer keys and setup parameters)
+/// comes from a DRBG seeded with a fixed seed, and the timestamps (e.g. the issuance time of the certificates of
+/// ephemeral keys) are injected, so that repeated runs produce the same outputs and downstream projects can snapshot
+/// test against them.
+///
+/// The mode applies to the adapters and algorithm instances created and used on the thread that enabled it, until the
+/// returned guard is dropped, by installing a `SeededEntropy` as the entropy source of the thread. Randomness internal to the dependencies is not covered: ES256 signatures (the issuer
+/// signature of SD-JWT and Merkle Tree VCs, and the holder signature of every VP) come from OpenSSL, and the BBS+
+/// presentation proofs from zkryptium, so those bytes still differ across runs.
+///
+/// # Examples
+/// ```
+/// use serde_json::{Map, Value};
+/// use csd_jwt::adapters::adapter::Adapter;
+/// use csd_jwt::adapters::accumulators::csd_jwt_adapter::CsdJwtAdapter;
+/// use csd_jwt::common_data::VC;
+/// use csd_jwt::deterministic::DeterministicMode;
+///
+/// let raw_vc: Map<String, Value> = serde_json::from_str(VC).unwrap();
+/// let issue = || {
+///     let _guard = DeterministicMode::new(42).with_timestamp(1_700_000_000).enable();
+///     CsdJwtAdapter::new(16).unwrap().issue_vc(&raw_vc).unwrap().1
+/// };
+/// assert_eq!(issue(), issue());
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DeterministicMode {
+    /// Seed of the DRBG.
+    pub seed: u64,
+    /// Timestamp injected in place of the current time, in seconds since the UNIX epoch.
+    pub timestamp: u64,
+}
+
+impl DeterministicMode {
+
+    /// Creates a deterministic mode with the given seed and a timestamp of 0.
+    ///
+    /// # Arguments
+    /// * `seed` - Seed of the DRBG.
+    pub fn new(seed: u64) -> Self {
+        DeterministicMode { seed, timestamp: 0 }
+    }
+
+    /// Sets the timestamp injected in place of the current time.
+    pub fn with_timestamp(mut self, timestamp: u64) -> Self {
+        self.timestamp = timestamp;
+        self
+    }
+
+    /// Enables the mode on the current thread, restarting the DRBG from the seed.
+    ///
+    /// # Returns
+    /// A guard restoring the previous mode and entropy source of the thread when dropped.
+    pub fn enable(&self) -> DeterministicGuard {
+        let entropy: EntropyGuard = entropy::install_source(Arc::new(SeededEntropy::new(self.seed)));
+        DeterministicGuard {
+            previous: DETERMINISTIC_STATE.with(|current| current.replace(Some(self.timestamp))),
+            _entropy: entropy,
+        }
+    }
+
+    /// Whether a deterministic mode is enabled on the current thread.
+    pub fn is_enabled() -> bool {
+        DETERMINISTIC_STATE.with(|current| current.borrow().is_some())
+    }
+}
+
+
+/// Guard of an enabled deterministic mode, restoring the previous mode of the thread when dropped.
+pub struct DeterministicGuard {
+    /// State of the thread before the mode was enabled.
+    previous: Option<u64>,
+    /// Guard of the seeded entropy source, restoring the previous source when dropped.
+    _entropy: EntropyGuard,
+}
+
+impl Drop for DeterministicGuard {
+    fn drop(&mut self) {
+        let previous = self.previous.take();
+        DETERMINISTIC_STATE.with(|current| current.replace(previous));
+    }
+}
+
+
+/// Fills a buffer with random bytes, drawn from the entropy source of the thread: the DRBG of the deterministic mode
+/// if enabled, and the operating system by default.
+///
+/// # Arguments
+/// * `bytes` - Buffer to be filled.
+pub fn fill_random(bytes: &mut [u8]) {
+    entropy::fill(bytes)
+}
+
+
+/// Random bytes, drawn from the entropy source of the thread.
+///
+/// # Arguments
+/// * `length` - Amount of bytes.
+pub fn random_bytes(length: usize) -> Vec<u8> {
+    let mut bytes: Vec<u8> = vec![0; length];
+    fill_random(&mut bytes);
+    bytes
+}
+
+
+/// Generator for the key material drawn through `rand`, seeded from the entropy source of the thread.
+pub fn rng() -> StdRng {
+    StdRng::from_seed(seed())
+}
+
+
+/// Generator for the arkworks setup parameters and proofs, seeded from the entropy source of the thread.
+pub fn ark_rng() -> ark_std::rand::rngs::StdRng {
+    ark_std::rand::rngs::StdRng::from_seed(seed())
+}
+
+
+/// Current time in seconds since the UNIX epoch, or the timestamp injected by the deterministic mode if enabled.
+///
+/// # Returns
+/// A result containing the time or a string containing an error if the clock is before the UNIX epoch.
+pub fn unix_time() -> Result<u64, String> {
+    if let Some(timestamp) = DETERMINISTIC_STATE.with(|current| *current.borrow()) {
+        return Ok(timestamp);
+    }
+
+    match SystemTime::now().duration_since(UNIX_EPOCH) {
+        Ok(elapsed) => { Ok(elapsed.as_secs()) }
+        Err(err) => { Err(format!("Failed to read the current time: [{err}]")) }
+    }
+}
+
+
+/// Seed of a child generator, drawn from the entropy source of the thread.
+fn seed() -> [u8; 32] {
+    let mut seed = [0u8; 32];
+    fill_random(&mut seed);
+    seed
+}
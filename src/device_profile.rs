@@ -0,0 +1,98 @@
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Rough single-core slowdown of a mid-range phone with respect to a desktop-class core, used when no explicit factor
+/// is configured. It is an order of magnitude for comparisons, not a measurement of a specific device.
+pub const PHONE_SLOWDOWN_FACTOR: f64 = 3.0;
+
+/// Execution profile of a constrained holder device, simulating a slower core by stretching every operation with a
+/// sleep proportional to its duration.
+///
+/// The holder operations of every algorithm are executed sequentially on the calling thread, so that the profile only
+/// needs to stretch them to approximate their duration on a single slower core.
+///
+/// # Examples
+/// ```
+/// use std::time::{Duration, Instant};
+/// use csd_jwt::device_profile::DeviceProfile;
+///
+/// let profile = DeviceProfile::new(2.0).unwrap();
+/// let start = Instant::now();
+/// let result = profile.run(|| { std::thread::sleep(Duration::from_millis(10)); Ok(42) }).unwrap();
+/// assert_eq!(result, 42);
+/// assert!(start.elapsed() >= Duration::from_millis(20));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DeviceProfile {
+    /// Ratio between the duration of an operation on the simulated device and on the host.
+    slowdown_factor: f64,
+}
+
+impl DeviceProfile {
+
+    /// Constructor for a device profile.
+    ///
+    /// # Arguments
+    /// * `slowdown_factor` - Ratio between the duration of an operation on the simulated device and on the host, at least 1.
+    ///
+    /// # Returns
+    /// A result containing the profile or a string containing an error if the factor is not a finite number of at least 1.
+    pub fn new(slowdown_factor: f64) -> Result<Self, String> {
+        if !slowdown_factor.is_finite() || slowdown_factor < 1f64 {
+            return Err(format!("Slowdown factor must be a finite number of at least 1, got {slowdown_factor}"));
+        }
+
+        Ok(DeviceProfile { slowdown_factor })
+    }
+
+    /// Profile of a phone-class core, using [`PHONE_SLOWDOWN_FACTOR`].
+    ///
+    /// # Returns
+    /// The phone profile.
+    pub fn phone() -> Self {
+        DeviceProfile { slowdown_factor: PHONE_SLOWDOWN_FACTOR }
+    }
+
+    /// Builds a profile from its configuration: an empty string or `phone` for the phone profile, or a slowdown factor.
+    ///
+    /// # Arguments
+    /// * `configuration` - Configuration string.
+    ///
+    /// # Returns
+    /// A result containing the profile or a string containing an error.
+    pub fn from_configuration(configuration: &str) -> Result<Self, String> {
+        match configuration.trim() {
+            "" | "phone" => { Ok(Self::phone()) }
+            factor => match factor.parse::<f64>() {
+                Ok(factor) => { Self::new(factor) }
+                Err(err) => { Err(format!("Invalid device profile {configuration}: [{err}]")) }
+            }
+        }
+    }
+
+    /// Getter of the slowdown factor.
+    pub fn slowdown_factor(&self) -> f64 {
+        self.slowdown_factor
+    }
+
+    /// Executes an operation under the profile, sleeping after it for as long as needed for its total duration to be
+    /// the slowdown factor times the duration on the host.
+    ///
+    /// # Arguments
+    /// * `func` - Operation to be executed.
+    ///
+    /// # Returns
+    /// The result of the operation.
+    pub fn run<F, T>(&self, func: F) -> Result<T, String>
+    where
+        F: FnOnce() -> Result<T, String>
+    {
+        let start = Instant::now();
+        let result = func();
+        let elapsed: Duration = start.elapsed();
+
+        thread::sleep(elapsed.mul_f64(self.slowdown_factor - 1f64));
+
+        result
+    }
+}
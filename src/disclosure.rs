@@ -0,0 +1,168 @@
+use serde_json::{Map, Value};
+use crate::derived_claims::AGE_OVER_PREFIX;
+
+/// Predicate a verifier requests instead of the claim it is evaluated on, answered by disclosing the derived claim
+/// computed at issuance.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Predicate {
+    /// The subject is at least the given age, answered by the `age_over_<N>` derived claim.
+    AgeOver(u32),
+}
+
+impl Predicate {
+
+    /// Name of the derived claim answering the predicate.
+    pub fn claim_name(&self) -> String {
+        match self {
+            Predicate::AgeOver(threshold) => { format!("{AGE_OVER_PREFIX}{threshold}") }
+        }
+    }
+}
+
+
+/// Presentation requested by a verifier: claims the holder must disclose, claims the holder may disclose if the
+/// credential contains them, predicates over the claims and the purpose of the request.
+///
+/// # Examples
+/// ```
+/// use serde_json::json;
+/// use csd_jwt::disclosure::{DisclosureRequest, Predicate};
+///
+/// let request = DisclosureRequest::new(vec!["name".to_string()])
+///     .with_optional(vec!["email".to_string(), "phone".to_string()])
+///     .with_predicate(Predicate::AgeOver(18))
+///     .with_purpose("age verification");
+///
+/// let claims = json!({"name": "Ada", "email": "ada@example.com", "age_over_18": true, "birthdate": "1990-01-01"});
+/// let response = request.resolve(claims.as_object().unwrap()).unwrap();
+/// assert_eq!(response.disclosed, vec!["name", "age_over_18", "email"]);
+/// assert_eq!(response.withheld, vec!["phone"]);
+/// assert_eq!(response.purpose.as_deref(), Some("age verification"));
+/// assert!(request.check(&response.disclosed).is_ok());
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DisclosureRequest {
+    /// Claims that must be disclosed.
+    pub required: Vec<String>,
+    /// Claims disclosed only if the credential contains them.
+    pub optional: Vec<String>,
+    /// Predicates that must be answered.
+    pub predicates: Vec<Predicate>,
+    /// Purpose of the request, as shown to the holder.
+    pub purpose: Option<String>,
+}
+
+impl DisclosureRequest {
+
+    /// Creates a request for the given claims, all of them required.
+    ///
+    /// # Arguments
+    /// * `required` - Claims that must be disclosed.
+    pub fn new(required: Vec<String>) -> Self {
+        DisclosureRequest { required, ..DisclosureRequest::default() }
+    }
+
+    /// Adds claims disclosed only if the credential contains them.
+    pub fn with_optional(mut self, optional: Vec<String>) -> Self {
+        self.optional.extend(optional);
+        self
+    }
+
+    /// Adds a predicate that must be answered.
+    pub fn with_predicate(mut self, predicate: Predicate) -> Self {
+        self.predicates.push(predicate);
+        self
+    }
+
+    /// Sets the purpose of the request.
+    pub fn with_purpose(mut self, purpose: &str) -> Self {
+        self.purpose = Some(purpose.to_string());
+        self
+    }
+
+    /// Claims that must be disclosed to satisfy the request: the required claims and the claims answering the
+    /// predicates, without repetitions.
+    pub fn mandatory_claims(&self) -> Vec<String> {
+        let mut claims: Vec<String> = vec![];
+        for claim in self.required.iter().cloned().chain(self.predicates.iter().map(Predicate::claim_name)) {
+            if !claims.contains(&claim) {
+                claims.push(claim);
+            }
+        }
+        claims
+    }
+
+    /// Whether the request asks for a claim, either as required, optional or answering a predicate.
+    pub fn requests(&self, claim: &str) -> bool {
+        self.optional.iter().chain(self.mandatory_claims().iter()).any(|requested| requested == claim)
+    }
+
+    /// Resolves the request against the claims of a credential, on the holder side.
+    ///
+    /// # Arguments
+    /// * `claims` - Claims the credential can disclose.
+    ///
+    /// # Returns
+    /// A result containing the response or a string containing an error if the credential does not contain a
+    /// mandatory claim.
+    pub fn resolve(&self, claims: &Map<String, Value>) -> Result<DisclosureResponse, String> {
+        let mut disclosed: Vec<String> = vec![];
+        for claim in self.mandatory_claims() {
+            if !claims.contains_key(&claim) {
+                return Err(format!("Credential does not contain the requested claim {claim}"));
+            }
+            disclosed.push(claim);
+        }
+
+        let mut withheld: Vec<String> = vec![];
+        for claim in &self.optional {
+            if disclosed.contains(claim) {
+                continue
+            }
+            if claims.contains_key(claim) {
+                disclosed.push(claim.clone());
+            } else {
+                withheld.push(claim.clone());
+            }
+        }
+
+        Ok(DisclosureResponse { disclosed, withheld, purpose: self.purpose.clone() })
+    }
+
+    /// Checks the claims disclosed by a presentation against the request, on the verifier side.
+    ///
+    /// # Arguments
+    /// * `disclosed` - Names of the claims disclosed by the presentation.
+    ///
+    /// # Returns
+    /// Returns a string containing an error if a mandatory claim is missing or a claim that was not requested is disclosed.
+    pub fn check(&self, disclosed: &[String]) -> Result<(), String> {
+        if let Some(claim) = self.mandatory_claims().iter().find(|claim| !disclosed.contains(claim)) {
+            return Err(format!("Presentation does not disclose the requested claim {claim}"));
+        }
+        if let Some(claim) = disclosed.iter().find(|claim| !self.requests(claim)) {
+            return Err(format!("Presentation discloses the claim {claim}, which was not requested"));
+        }
+
+        Ok(())
+    }
+}
+
+impl From<Vec<String>> for DisclosureRequest {
+    fn from(required: Vec<String>) -> Self {
+        DisclosureRequest::new(required)
+    }
+}
+
+
+/// Outcome of a disclosure request: the claims actually disclosed, in the order they are passed to the algorithm, and
+/// the optional claims the credential did not contain.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DisclosureResponse {
+    /// Claims disclosed by the presentation.
+    pub disclosed: Vec<String>,
+    /// Optional claims that were not disclosed.
+    pub withheld: Vec<String>,
+    /// Purpose of the request the presentation answers.
+    pub purpose: Option<String>,
+}
@@ -0,0 +1,357 @@
+use std::collections::BTreeMap;
+use serde_json::{Map, Value};
+use crate::claim_groups::ClaimGroups;
+use crate::claim_metadata::ClaimMetadata;
+use crate::common_data::CLAIMS;
+use crate::disclosure::DisclosureRequest;
+use crate::sd_algorithms::sd_algorithm::SdAlgorithm;
+
+/// How sensitive a claim is for the holder, as declared by the schema of the credential, so that wallets can warn
+/// before disclosing it. Ordered from the least to the most sensitive.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Sensitivity {
+    /// Claims that do not identify the holder, e.g. the field of a degree.
+    Public,
+    /// Claims identifying the holder, e.g. the name; the default for claims the schema does not tag.
+    Personal,
+    /// Claims whose disclosure may harm the holder, e.g. health data.
+    Sensitive,
+}
+
+impl Sensitivity {
+
+    /// Name of the sensitivity, as written in the schema and in the menu.
+    ///
+    /// # Returns
+    /// A static string identifying the sensitivity.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Sensitivity::Public => { "public" }
+            Sensitivity::Personal => { "personal" }
+            Sensitivity::Sensitive => { "sensitive" }
+        }
+    }
+
+    /// Retrieves the sensitivity corresponding to a name previously produced by `name`.
+    ///
+    /// # Arguments
+    /// * `name` - Name of the sensitivity.
+    ///
+    /// # Returns
+    /// A result containing the sensitivity or a string containing an error if the name is unknown.
+    pub fn from_name(name: &str) -> Result<Self, String> {
+        match name {
+            "public" => { Ok(Sensitivity::Public) }
+            "personal" => { Ok(Sensitivity::Personal) }
+            "sensitive" => { Ok(Sensitivity::Sensitive) }
+            _ => { Err(format!("Unknown claim sensitivity {name}")) }
+        }
+    }
+}
+
+
+/// Presentation of a single claim described by the schema.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ClaimSchema {
+    /// Human-readable label of the claim.
+    pub label: String,
+    /// Longer description of the claim, if any.
+    pub description: Option<String>,
+    /// Sensitivity of the claim.
+    pub sensitivity: Sensitivity,
+}
+
+
+/// Schema of a credential, read from a JSON Schema describing either the whole VC or its `credentialSubject`: every
+/// property may carry a `title`, shown as the label of the claim, a `description` and a `sensitivity` among `public`,
+/// `personal` and `sensitive`. Claims missing from the schema are labelled with their name and considered personal.
+///
+/// # Examples
+/// ```
+/// use serde_json::json;
+/// use csd_jwt::disclosure_menu::{CredentialSchema, Sensitivity};
+///
+/// let schema = CredentialSchema::from_json(&json!({"properties": {"credentialSubject": {"properties": {
+///     "birthdate": {"title": "Date of birth", "sensitivity": "sensitive"},
+///     "field": {"title": "Field of study", "sensitivity": "public"}
+/// }}}})).unwrap();
+///
+/// assert_eq!(schema.claim("birthdate").label, "Date of birth");
+/// assert_eq!(schema.claim("field").sensitivity, Sensitivity::Public);
+/// assert_eq!(schema.claim("image").label, "image");
+/// assert_eq!(schema.claim("image").sensitivity, Sensitivity::Personal);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CredentialSchema {
+    /// Presentation of the claims described by the schema, indexed by claim name.
+    claims: BTreeMap<String, ClaimSchema>,
+}
+
+impl CredentialSchema {
+
+    /// Parses the schema of a credential.
+    ///
+    /// # Arguments
+    /// * `schema` - JSON Schema of the VC, or of its `credentialSubject`.
+    ///
+    /// # Returns
+    /// A result containing the schema or a string containing an error if a property is malformed.
+    pub fn from_json(schema: &Value) -> Result<Self, String> {
+        let properties: &Map<String, Value> = match schema.get("properties") {
+            Some(Value::Object(properties)) => { properties }
+            _ => { return Err("Schema does not contain the properties object".to_string()) }
+        };
+        let properties: &Map<String, Value> = match properties.get(CLAIMS).and_then(|subject| subject.get("properties")) {
+            Some(Value::Object(claims)) => { claims }
+            Some(_) => { return Err(format!("Properties of {CLAIMS} are not an object")) }
+            None => { properties }
+        };
+
+        let mut claims: BTreeMap<String, ClaimSchema> = BTreeMap::new();
+        for (claim, property) in properties {
+            let text = |field: &str| -> Result<Option<String>, String> {
+                match property.get(field) {
+                    None => { Ok(None) }
+                    Some(Value::String(text)) => { Ok(Some(text.clone())) }
+                    Some(_) => { Err(format!("Schema field {field} of claim {claim} is not a string")) }
+                }
+            };
+
+            let sensitivity: Sensitivity = match text("sensitivity")? {
+                None => { Sensitivity::Personal }
+                Some(name) => { Sensitivity::from_name(&name)? }
+            };
+            let label: String = text("title")?.unwrap_or_else(|| claim.clone());
+            claims.insert(claim.clone(), ClaimSchema { label, description: text("description")?, sensitivity });
+        }
+
+        Ok(CredentialSchema { claims })
+    }
+
+    /// Presentation of a claim, falling back to its name and to personal sensitivity if the schema does not describe it.
+    ///
+    /// # Arguments
+    /// * `claim` - Name of the claim.
+    ///
+    /// # Returns
+    /// The presentation of the claim.
+    pub fn claim(&self, claim: &str) -> ClaimSchema {
+        match self.claims.get(claim) {
+            Some(claim_schema) => { claim_schema.clone() }
+            None => { ClaimSchema { label: claim.to_string(), description: None, sensitivity: Sensitivity::Personal } }
+        }
+    }
+}
+
+
+/// Entry of a disclosure menu: a claim of the credential, or a claim inside a group.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MenuEntry {
+    /// JSON pointer to the claim in the raw VC, returned by the wallet to select the entry.
+    pub path: String,
+    /// Name of the claim passed to the algorithm when the entry is selected; for the claims of a group, the group.
+    pub claim: String,
+    /// Presentation of the claim from the schema. The sensitivity of a group is the highest among its claims.
+    pub schema: ClaimSchema,
+    /// Whether the issuer discloses the claim in every VP, so that the wallet shows it as always selected.
+    pub mandatory: bool,
+    /// Whether the claim can only be disclosed in VPs bound to an audience.
+    pub audience_bound: bool,
+    /// Claims of the group, disclosed together, or empty if the claim is not a group.
+    pub members: Vec<MenuEntry>,
+}
+
+impl MenuEntry {
+
+    /// Converts the entry to its JSON representation.
+    ///
+    /// # Returns
+    /// The JSON object describing the entry.
+    pub fn to_json(&self) -> Value {
+        let mut map: Map<String, Value> = Map::new();
+        map.insert("path".to_string(), Value::String(self.path.clone()));
+        map.insert("claim".to_string(), Value::String(self.claim.clone()));
+        map.insert("label".to_string(), Value::String(self.schema.label.clone()));
+        if let Some(description) = &self.schema.description {
+            map.insert("description".to_string(), Value::String(description.clone()));
+        }
+        map.insert("sensitivity".to_string(), Value::String(self.schema.sensitivity.name().to_string()));
+        map.insert("mandatory".to_string(), Value::Bool(self.mandatory));
+        map.insert("audienceBound".to_string(), Value::Bool(self.audience_bound));
+        if !self.members.is_empty() {
+            map.insert("members".to_string(), Value::Array(self.members.iter().map(MenuEntry::to_json).collect()));
+        }
+
+        Value::Object(map)
+    }
+}
+
+
+/// Machine-readable disclosure menu of a VC for wallet UIs: one entry per disclosable claim of the credential, as
+/// issued, with the label, description and sensitivity from the schema of the credential, the mandatory and audience
+/// tags from its claim metadata and the claims of every group nested in the entry of the group. The wallet shows the
+/// entries, lets the user pick some of them and hands the picked paths back to `select`, which turns them into the
+/// `DisclosureRequest` of the presentation.
+///
+/// # Examples
+/// ```
+/// use serde_json::{json, Map, Value};
+/// use csd_jwt::common_data::{CommonData, VC};
+/// use csd_jwt::disclosure_menu::{CredentialSchema, DisclosureMenu};
+/// use csd_jwt::sd_algorithms::hashes::sd_jwt::SdJwtInstance;
+///
+/// let raw_vc: Map<String, Value> = serde_json::from_str(VC).unwrap();
+/// let (_, issuer_private_key) = CommonData::issuer_keys().unwrap();
+/// let (vc, _) = SdJwtInstance::issue_vc(&raw_vc, &issuer_private_key).unwrap();
+///
+/// let schema = CredentialSchema::from_json(&json!({"properties": {"name": {"title": "Full name"}}})).unwrap();
+/// let menu = DisclosureMenu::build::<SdJwtInstance>(&vc, &schema).unwrap();
+/// let entries = menu.to_json()["entries"].as_array().unwrap().clone();
+/// assert!(entries.iter().any(|entry| entry["label"] == json!("Full name")));
+///
+/// let request = menu.select(&["/credentialSubject/name".to_string()]).unwrap();
+/// assert_eq!(request.required, vec!["name"]);
+/// assert!(menu.select(&["/credentialSubject/unknown".to_string()]).is_err());
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DisclosureMenu {
+    /// Entries of the menu, in the order of the claims of the credential.
+    pub entries: Vec<MenuEntry>,
+}
+
+impl DisclosureMenu {
+
+    /// Builds the disclosure menu of a VC, on the holder side.
+    ///
+    /// # Arguments
+    /// * `vc` - Verifiable Credential issued by the algorithm.
+    /// * `schema` - Schema of the credential.
+    ///
+    /// # Returns
+    /// A result containing the menu or a string containing an error if the claims or sidecars of the VC are malformed.
+    pub fn build<A: SdAlgorithm>(vc: &Map<String, Value>, schema: &CredentialSchema) -> Result<Self, String> {
+        let claims: Map<String, Value> = A::issued_claims(vc)?;
+        let groups: ClaimGroups = ClaimGroups::extract(vc)?;
+        let metadata: BTreeMap<String, ClaimMetadata> = ClaimMetadata::extract(vc)?;
+
+        let entries: Vec<MenuEntry> = claims.keys().map(|claim| {
+            let claim_metadata: ClaimMetadata = metadata.get(claim).copied().unwrap_or_default();
+            let entry = |path_claim: &String, members: Vec<MenuEntry>| MenuEntry {
+                path: claim_path(path_claim),
+                claim: claim.clone(),
+                schema: schema.claim(path_claim),
+                mandatory: claim_metadata.mandatory,
+                audience_bound: claim_metadata.sensitive,
+                members,
+            };
+
+            let members: Vec<MenuEntry> = groups.claims_of(claim).unwrap_or_default().iter()
+                .map(|member| entry(member, vec![]))
+                .collect();
+            let mut group_entry: MenuEntry = entry(claim, members);
+            if let Some(sensitivity) = group_entry.members.iter().map(|member| member.schema.sensitivity).max() {
+                group_entry.schema.sensitivity = group_entry.schema.sensitivity.max(sensitivity);
+            }
+            group_entry
+        }).collect();
+
+        Ok(DisclosureMenu { entries })
+    }
+
+    /// Turns the entries picked by the user into the request of the presentation. Picking a claim of a group selects
+    /// the whole group, and the mandatory entries are always requested.
+    ///
+    /// # Arguments
+    /// * `selection` - Paths of the picked entries or claims of groups.
+    ///
+    /// # Returns
+    /// A result containing the request, with every selected claim required, or a string containing an error if a path
+    /// is not in the menu.
+    pub fn select(&self, selection: &[String]) -> Result<DisclosureRequest, String> {
+        let mut required: Vec<String> = self.entries.iter()
+            .filter(|entry| entry.mandatory)
+            .map(|entry| entry.claim.clone())
+            .collect();
+
+        for path in selection {
+            let entry: &MenuEntry = match self.entries.iter().find(|entry| entry.path == *path || entry.members.iter().any(|member| member.path == *path)) {
+                Some(entry) => { entry }
+                None => { return Err(format!("Path {path} is not in the disclosure menu")) }
+            };
+            if !required.contains(&entry.claim) {
+                required.push(entry.claim.clone());
+            }
+        }
+
+        Ok(DisclosureRequest::new(required))
+    }
+
+    /// Converts the menu to its JSON representation, consumed by wallet UIs.
+    ///
+    /// # Returns
+    /// The JSON object containing the entries of the menu.
+    pub fn to_json(&self) -> Value {
+        let mut map: Map<String, Value> = Map::new();
+        map.insert("entries".to_string(), Value::Array(self.entries.iter().map(MenuEntry::to_json).collect()));
+        Value::Object(map)
+    }
+}
+
+
+/// JSON pointer to a claim of the `credentialSubject` object, escaping `~` and `/` as in RFC 6901.
+fn claim_path(claim: &str) -> String {
+    format!("/{CLAIMS}/{}", claim.replace('~', "~0").replace('/', "~1"))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Map, Value};
+    use crate::claim_groups::ClaimGroups;
+    use crate::common_data::{CommonData, CLAIM_METADATA, VC};
+    use crate::disclosure_menu::{CredentialSchema, DisclosureMenu, MenuEntry, Sensitivity};
+    use crate::sd_algorithms::hashes::sd_jwt::SdJwtInstance;
+    use crate::sd_algorithms::sd_algorithm::SdAlgorithm;
+
+    #[test]
+    fn selection_becomes_request() -> Result<(), String> {
+        let mut raw_vc: Map<String, Value> = match serde_json::from_str::<Map<String, Value>>(VC) {
+            Ok(vc) => { vc }
+            Err(err) => { return Err(format!("Failed to parse Raw Verifiable Credential. [{err}]")); }
+        };
+        ClaimGroups::new().with_group("identity", &["name", "birthdate"]).insert_into(&mut raw_vc)?;
+        raw_vc.insert(CLAIM_METADATA.to_string(), json!({"nationality": {"mandatory": true}}));
+
+        let schema: CredentialSchema = CredentialSchema::from_json(&json!({"properties": {
+            "identity": {"title": "Identity"},
+            "name": {"title": "Full name"},
+            "birthdate": {"title": "Date of birth", "sensitivity": "sensitive"},
+            "field": {"title": "Field of study", "description": "Main field of research", "sensitivity": "public"}
+        }}))?;
+
+        let (holder_public_key, holder_private_key) = CommonData::holder_keys()?;
+        let (issuer_public_key, issuer_private_key) = CommonData::issuer_keys()?;
+        let (vc, _) = SdJwtInstance::issue_vc(&raw_vc, &issuer_private_key)?;
+        let menu: DisclosureMenu = DisclosureMenu::build::<SdJwtInstance>(&vc, &schema)?;
+
+        let identity: &MenuEntry = match menu.entries.iter().find(|entry| entry.claim == "identity") {
+            Some(identity) => { identity }
+            None => { return Err("Group is not in the disclosure menu.".to_string()) }
+        };
+        assert_eq!(identity.schema.sensitivity, Sensitivity::Sensitive, "Group is less sensitive than its claims.");
+        assert_eq!(identity.members.iter().map(|member| member.schema.label.as_str()).collect::<Vec<&str>>(), vec!["Full name", "Date of birth"]);
+        assert!(menu.entries.iter().all(|entry| entry.mandatory == (entry.claim == "nationality")));
+        assert_eq!(menu.to_json()["entries"].as_array().map(Vec::len), Some(menu.entries.len()));
+
+        let request = menu.select(&["/credentialSubject/birthdate".to_string(), "/credentialSubject/field".to_string()])?;
+        assert_eq!(request.required, vec!["nationality", "identity", "field"]);
+        assert!(menu.select(&["/credentialSubject/birthdate/year".to_string()]).is_err(), "Path outside of the menu was selected.");
+
+        let disclosures: Vec<String> = request.resolve(&SdJwtInstance::issued_claims(&vc)?)?.disclosed;
+        let (vp, vp_jwt) = SdJwtInstance::issue_vp(&vc, &disclosures, &holder_private_key)?;
+        SdJwtInstance::verify_vp(&vp_jwt, &issuer_public_key, &holder_public_key)?;
+        request.check(&SdJwtInstance::disclosed_claim_names(SdJwtInstance::unwrap_presentation(&vp)?)?)?;
+
+        Ok(())
+    }
+}
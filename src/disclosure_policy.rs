@@ -0,0 +1,159 @@
+use std::collections::BTreeSet;
+use serde_json::{Map, Value};
+use crate::claim_groups::ClaimGroups;
+use crate::claim_metadata::ClaimMetadata;
+use crate::common_data::{CLAIMS, CLAIM_METADATA};
+
+/// Issuer policy marking claims as not selectively disclosable, e.g. the credential type or the issuing country: they
+/// are disclosed in clear text in every VP, whatever the disclosures chosen by the holder.
+///
+/// The issuer applies the policy by tagging the claims as mandatory in the claim metadata of every credential, so that
+/// the disclosure filter of the holder always adds them. The metadata sidecar is not covered by the proofs of the
+/// credential, hence verifiers configured with the same policy check the disclosed claims themselves, as proven by the
+/// VP, and a holder stripping the tags from its presentation is still rejected.
+///
+/// # Examples
+/// ```
+/// use serde_json::{json, Map, Value};
+/// use csd_jwt::disclosure_policy::DisclosurePolicy;
+///
+/// let policy = DisclosurePolicy::new().with_always_disclosed("nationality");
+/// let raw_vc: Map<String, Value> = serde_json::from_value(json!({"credentialSubject": {"name": "Ada", "nationality": "GB"}})).unwrap();
+/// assert_eq!(policy.apply(&raw_vc).unwrap()["claimMetadata"]["nationality"]["mandatory"], json!(true));
+///
+/// assert!(policy.check(&["name".to_string(), "nationality".to_string()]).is_ok());
+/// assert!(policy.check(&["name".to_string()]).is_err());
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DisclosurePolicy {
+    /// Names of the claims disclosed in every VP.
+    always_disclosed: BTreeSet<String>,
+}
+
+impl DisclosurePolicy {
+
+    /// Constructor for a policy leaving every claim selectively disclosable.
+    pub fn new() -> Self {
+        DisclosurePolicy { always_disclosed: BTreeSet::new() }
+    }
+
+    /// Marks a claim as disclosed in every VP.
+    ///
+    /// # Arguments
+    /// * `claim` - Name of the claim, which must not belong to a claim group.
+    ///
+    /// # Returns
+    /// The policy including the claim.
+    pub fn with_always_disclosed(mut self, claim: &str) -> Self {
+        self.always_disclosed.insert(claim.to_string());
+        self
+    }
+
+    /// Names of the claims disclosed in every VP, sorted.
+    pub fn always_disclosed(&self) -> Vec<String> {
+        self.always_disclosed.iter().cloned().collect()
+    }
+
+    /// Whether the policy leaves every claim selectively disclosable.
+    pub fn is_empty(&self) -> bool {
+        self.always_disclosed.is_empty()
+    }
+
+    /// Tags the claims of the policy as mandatory in the claim metadata of a raw VC, keeping their other metadata.
+    ///
+    /// # Arguments
+    /// * `raw_vc` - Template VC to be issued.
+    ///
+    /// # Returns
+    /// A result containing the tagged raw VC or a string containing an error if a claim of the policy is missing from
+    /// the raw VC or belongs to a claim group.
+    pub fn apply(&self, raw_vc: &Map<String, Value>) -> Result<Map<String, Value>, String> {
+        if self.is_empty() {
+            return Ok(raw_vc.clone());
+        }
+
+        let claims: &Map<String, Value> = match raw_vc.get(CLAIMS) {
+            Some(Value::Object(claims)) => { claims }
+            _ => { return Err(format!("Raw VC does not contain the {CLAIMS} object")) }
+        };
+        let groups: ClaimGroups = ClaimGroups::extract(raw_vc)?;
+        let mut metadata = ClaimMetadata::extract(raw_vc)?;
+
+        for claim in &self.always_disclosed {
+            if !claims.contains_key(claim) {
+                return Err(format!("Always-disclosed claim {claim} is not in the raw VC"));
+            }
+            if let Some(group) = groups.group_of(claim) {
+                return Err(format!("Always-disclosed claim {claim} belongs to the claim group {group}"));
+            }
+            metadata.entry(claim.clone()).or_default().mandatory = true;
+        }
+
+        let sidecar: Map<String, Value> = metadata.into_iter().map(|(claim, claim_metadata)| (claim, claim_metadata.to_json())).collect();
+        let mut raw_vc: Map<String, Value> = raw_vc.clone();
+        raw_vc.insert(CLAIM_METADATA.to_string(), Value::Object(sidecar));
+
+        Ok(raw_vc)
+    }
+
+    /// Checks that a VP discloses every claim of the policy.
+    ///
+    /// # Arguments
+    /// * `disclosed_claims` - Names of the claims disclosed by the VP, as proven by its proofs.
+    ///
+    /// # Returns
+    /// A result containing a string containing an error listing the withheld claims of the policy.
+    pub fn check(&self, disclosed_claims: &[String]) -> Result<(), String> {
+        let withheld: Vec<&str> = self.always_disclosed.iter()
+            .filter(|claim| !disclosed_claims.contains(claim))
+            .map(|claim| claim.as_str())
+            .collect();
+
+        if withheld.is_empty() {
+            Ok(())
+        } else {
+            Err(format!("Always-disclosed claims are not disclosed: [{}]", withheld.join(", ")))
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{Map, Value};
+    use crate::common_data::{CLAIM_METADATA, VC};
+    use crate::disclosure_policy::DisclosurePolicy;
+    use crate::holder::Holder;
+    use crate::issuer::Issuer;
+    use crate::roles::RoleAlgorithm;
+    use crate::sd_algorithms::hashes::sd_jwt::SdJwtInstance;
+    use crate::verifier::Verifier;
+
+    #[test]
+    fn stripped_always_disclosed_claims_are_rejected() -> Result<(), String> {
+        let raw_vc: Map<String, Value> = match serde_json::from_str::<Map<String, Value>>(VC) {
+            Ok(vc) => { vc }
+            Err(err) => { return Err(format!("Failed to parse Raw Verifiable Credential. [{err}]")); }
+        };
+
+        let policy: DisclosurePolicy = DisclosurePolicy::new().with_always_disclosed("nationality");
+        let issuer: Issuer<SdJwtInstance> = Issuer::new(13)?.with_disclosure_policy(policy.clone());
+        let holder: Holder<SdJwtInstance> = Holder::new(issuer.public_parameters().clone())?;
+        let verifier: Verifier<SdJwtInstance> = Verifier::new(issuer.public_parameters().clone()).with_disclosure_policy(policy);
+        let (vc, _) = issuer.issue_vc(&raw_vc, holder.public_key())?;
+
+        let (_, vp_jwt) = holder.issue_vp(&vc, &vec!["name".to_string()], None)?;
+        assert_eq!(SdJwtInstance::disclosed_claims(&vp_jwt)?.len(), 2);
+        verifier.verify_vp(&vp_jwt, holder.public_key())?;
+
+        // A holder dropping the mandatory tags withholds the claim, which the verifier still requires.
+        let mut stripped_vc: Map<String, Value> = vc.clone();
+        stripped_vc.remove(CLAIM_METADATA);
+        let (_, vp_jwt) = holder.issue_vp(&stripped_vc, &vec!["name".to_string()], None)?;
+        assert!(verifier.verify_vp(&vp_jwt, holder.public_key()).is_err(), "VP withholding an always-disclosed claim was accepted.");
+
+        assert!(issuer.issue_vc(&Map::new(), holder.public_key()).is_err(), "Raw VC without the always-disclosed claim was issued.");
+
+        Ok(())
+    }
+}
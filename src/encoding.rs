@@ -0,0 +1,114 @@
+use multibase::Base;
+use crate::hashing::{decode_base64url, encode_base64url};
+
+/// Text encodings supported for the binary elements (signatures, witnesses, containers, ...) embedded in VCs and VPs.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Encoding {
+    /// RFC 4648 base64url alphabet without padding, as mandated for JWTs.
+    #[default]
+    Base64UrlNoPad,
+    /// Bitcoin base58 alphabet.
+    Base58Btc,
+    /// Lowercase hexadecimal.
+    Hex,
+}
+
+impl Encoding {
+
+    /// Name of the encoding, as written in the JWT header.
+    ///
+    /// # Returns
+    /// A static string identifying the encoding.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Encoding::Base64UrlNoPad => { "base64url" }
+            Encoding::Base58Btc => { "base58btc" }
+            Encoding::Hex => { "hex" }
+        }
+    }
+
+    /// Retrieves the encoding corresponding to a name previously produced by `name`.
+    ///
+    /// # Arguments
+    /// * `name` - Name of the encoding.
+    ///
+    /// # Returns
+    /// A result containing the encoding or a string containing an error if the name is unknown.
+    pub fn from_name(name: &str) -> Result<Self, String> {
+        match name {
+            "base64url" => { Ok(Encoding::Base64UrlNoPad) }
+            "base58btc" => { Ok(Encoding::Base58Btc) }
+            "hex" => { Ok(Encoding::Hex) }
+            _ => { Err(format!("Unknown encoding {name}")) }
+        }
+    }
+
+    /// Multibase base corresponding to the encoding.
+    fn base(&self) -> Base {
+        match self {
+            Encoding::Base64UrlNoPad => { Base::Base64Url }
+            Encoding::Base58Btc => { Base::Base58Btc }
+            Encoding::Hex => { Base::Base16Lower }
+        }
+    }
+
+    /// Checks whether a character belongs to the alphabet of the encoding.
+    fn is_valid_character(&self, character: char) -> bool {
+        match self {
+            Encoding::Base64UrlNoPad => { character.is_ascii_alphanumeric() || character == '-' || character == '_' }
+            Encoding::Base58Btc => { character.is_ascii_alphanumeric() && !"0OIl".contains(character) }
+            Encoding::Hex => { character.is_ascii_digit() || ('a'..='f').contains(&character) }
+        }
+    }
+
+    /// Encodes a byte array.
+    ///
+    /// # Arguments
+    /// * `bytes` - Bytes to be encoded.
+    ///
+    /// # Returns
+    /// The encoded string.
+    ///
+    /// # Examples
+    /// ```
+    /// use csd_jwt::encoding::Encoding;
+    ///
+    /// assert_eq!(Encoding::Hex.encode(&[0xca, 0xfe]), "cafe");
+    /// ```
+    pub fn encode<T: AsRef<[u8]>>(&self, bytes: T) -> String {
+        match self {
+            Encoding::Base64UrlNoPad => { encode_base64url(bytes) }
+            _ => { self.base().encode(bytes) }
+        }
+    }
+
+    /// Strictly decodes a string, rejecting padding and characters that do not belong to the alphabet of the encoding.
+    ///
+    /// # Arguments
+    /// * `encoded` - String to be decoded.
+    ///
+    /// # Returns
+    /// A result containing the decoded bytes or a string containing an error.
+    ///
+    /// # Examples
+    /// ```
+    /// use csd_jwt::encoding::Encoding;
+    ///
+    /// assert!(Encoding::Base64UrlNoPad.decode("yv4").is_ok());
+    /// assert!(Encoding::Base64UrlNoPad.decode("yv4=").is_err());
+    /// ```
+    pub fn decode(&self, encoded: &str) -> Result<Vec<u8>, String> {
+        if let Some(character) = encoded.chars().find(|character| !self.is_valid_character(*character)) {
+            return Err(format!("Character {:?} is not allowed in {} encoding", character, self.name()));
+        }
+
+        let decoded: Result<Vec<u8>, String> = match self {
+            Encoding::Base64UrlNoPad => { decode_base64url(encoded) }
+            _ => { self.base().decode(encoded).map_err(|err| err.to_string()) }
+        };
+        match decoded {
+            Ok(bytes) => { Ok(bytes) }
+            Err(err) => { Err(format!("Failed to decode {} string: [{err}]", self.name())) }
+        }
+    }
+}
@@ -0,0 +1,240 @@
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+/// Name of the algorithm and operation of the randomness drawn outside of any scope, e.g. during the creation of an
+/// adapter.
+pub const UNSCOPED: &str = "unscoped";
+
+thread_local! {
+    /// Entropy source installed on the current thread, if any.
+    static ENTROPY_SOURCE: RefCell<Option<Arc<dyn EntropySource>>> = const { RefCell::new(None) };
+    /// Algorithm and operation currently drawing randomness on the current thread, if any.
+    static ENTROPY_SCOPE: RefCell<Option<EntropyScope>> = const { RefCell::new(None) };
+}
+
+
+/// Algorithm and operation on behalf of which random bytes are drawn.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct EntropyScope {
+    /// Name of the algorithm.
+    pub algorithm: String,
+    /// Name of the operation, as it appears in the logs of the adapters.
+    pub operation: String,
+}
+
+impl Default for EntropyScope {
+    fn default() -> Self {
+        EntropyScope { algorithm: UNSCOPED.to_string(), operation: UNSCOPED.to_string() }
+    }
+}
+
+
+/// Source of all the randomness drawn by the crate: salts, blinding factors, presentation nonces, issuer keys, setup
+/// parameters and the generators of the arkworks proofs. Randomness internal to the dependencies (the ES256 signatures
+/// of OpenSSL and the BBS+ proofs of zkryptium) does not go through it.
+pub trait EntropySource: Send + Sync {
+
+    /// Fills a buffer with random bytes.
+    ///
+    /// # Arguments
+    /// * `scope` - Algorithm and operation drawing the bytes.
+    /// * `bytes` - Buffer to be filled.
+    fn fill(&self, scope: &EntropyScope, bytes: &mut [u8]);
+
+    /// Whether the source needs the scope of the randomness it provides, so that callers only name their scope when
+    /// it is recorded.
+    fn records_scopes(&self) -> bool {
+        false
+    }
+}
+
+
+/// Cryptographically secure generator of the thread, seeded from the operating system. This is the source used when
+/// none is installed.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct OsEntropy;
+
+impl EntropySource for OsEntropy {
+    fn fill(&self, _scope: &EntropyScope, bytes: &mut [u8]) {
+        rand::rng().fill(bytes);
+    }
+}
+
+
+/// DRBG seeded with a fixed seed, so that repeated runs draw the same bytes, as used by the deterministic mode.
+#[derive(Debug)]
+pub struct SeededEntropy {
+    /// Generator, shared by the threads using the source.
+    rng: Mutex<StdRng>,
+}
+
+impl SeededEntropy {
+
+    /// Constructor for a DRBG seeded with the given seed.
+    ///
+    /// # Arguments
+    /// * `seed` - Seed of the DRBG.
+    pub fn new(seed: u64) -> Self {
+        SeededEntropy { rng: Mutex::new(StdRng::seed_from_u64(seed)) }
+    }
+}
+
+impl EntropySource for SeededEntropy {
+    fn fill(&self, _scope: &EntropyScope, bytes: &mut [u8]) {
+        match self.rng.lock() {
+            Ok(mut rng) => { rng.fill(bytes) }
+            Err(poisoned) => { poisoned.into_inner().fill(bytes) }
+        }
+    }
+}
+
+
+/// Amount of randomness drawn within a scope.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct EntropyConsumption {
+    /// Amount of requests for random bytes.
+    pub requests: usize,
+    /// Amount of random bytes.
+    pub bytes: usize,
+}
+
+
+/// Wrapper of an entropy source recording how many random bytes every operation of every algorithm consumes, e.g. to
+/// audit that salts and blinding factors have the expected length or to size a hardware RNG.
+///
+/// # Examples
+/// ```
+/// use std::sync::Arc;
+/// use csd_jwt::deterministic::random_bytes;
+/// use csd_jwt::entropy::{install_source, with_scope, AuditingEntropy, EntropyScope, OsEntropy};
+///
+/// let auditor = Arc::new(AuditingEntropy::new(Arc::new(OsEntropy)));
+/// let _guard = install_source(auditor.clone());
+/// with_scope("SD-JWT", "VC issuance", || random_bytes(16));
+/// with_scope("SD-JWT", "VC issuance", || random_bytes(16));
+///
+/// let scope = EntropyScope { algorithm: "SD-JWT".to_string(), operation: "VC issuance".to_string() };
+/// let consumption = auditor.consumption()[&scope];
+/// assert_eq!((consumption.requests, consumption.bytes), (2, 32));
+/// ```
+pub struct AuditingEntropy {
+    /// Source providing the random bytes.
+    inner: Arc<dyn EntropySource>,
+    /// Randomness drawn so far, by scope.
+    consumed: Mutex<BTreeMap<EntropyScope, EntropyConsumption>>,
+}
+
+impl AuditingEntropy {
+
+    /// Constructor for an auditor of a source.
+    ///
+    /// # Arguments
+    /// * `inner` - Source providing the random bytes, e.g. `OsEntropy` or `SeededEntropy`.
+    pub fn new(inner: Arc<dyn EntropySource>) -> Self {
+        AuditingEntropy { inner, consumed: Mutex::new(BTreeMap::new()) }
+    }
+
+    /// Randomness drawn so far, by scope.
+    pub fn consumption(&self) -> BTreeMap<EntropyScope, EntropyConsumption> {
+        match self.consumed.lock() {
+            Ok(consumed) => { consumed.clone() }
+            Err(poisoned) => { poisoned.into_inner().clone() }
+        }
+    }
+
+    /// Randomness drawn since the last call, by scope, resetting the records.
+    pub fn take(&self) -> BTreeMap<EntropyScope, EntropyConsumption> {
+        match self.consumed.lock() {
+            Ok(mut consumed) => { std::mem::take(&mut *consumed) }
+            Err(poisoned) => { std::mem::take(&mut *poisoned.into_inner()) }
+        }
+    }
+}
+
+impl EntropySource for AuditingEntropy {
+    fn fill(&self, scope: &EntropyScope, bytes: &mut [u8]) {
+        self.inner.fill(scope, bytes);
+
+        let mut consumed = match self.consumed.lock() {
+            Ok(consumed) => { consumed }
+            Err(poisoned) => { poisoned.into_inner() }
+        };
+        let consumption: &mut EntropyConsumption = consumed.entry(scope.clone()).or_default();
+        consumption.requests += 1;
+        consumption.bytes += bytes.len();
+    }
+
+    fn records_scopes(&self) -> bool {
+        true
+    }
+}
+
+
+/// Guard of an installed entropy source, restoring the previous source of the thread when dropped.
+pub struct EntropyGuard {
+    /// Source of the thread before this one was installed.
+    previous: Option<Arc<dyn EntropySource>>,
+}
+
+impl Drop for EntropyGuard {
+    fn drop(&mut self) {
+        let previous = self.previous.take();
+        ENTROPY_SOURCE.with(|current| current.replace(previous));
+    }
+}
+
+
+/// Installs an entropy source on the current thread, for the adapters and algorithm instances used on it.
+///
+/// # Arguments
+/// * `source` - Source to be installed.
+///
+/// # Returns
+/// A guard restoring the previous source of the thread when dropped.
+pub fn install_source(source: Arc<dyn EntropySource>) -> EntropyGuard {
+    EntropyGuard { previous: ENTROPY_SOURCE.with(|current| current.replace(Some(source))) }
+}
+
+
+/// Whether the entropy source of the current thread records the scope of the randomness it provides.
+pub fn records_scopes() -> bool {
+    ENTROPY_SOURCE.with(|current| current.borrow().as_ref().is_some_and(|source| source.records_scopes()))
+}
+
+
+/// Runs an operation drawing its randomness within a scope, restoring the previous scope afterwards.
+///
+/// # Arguments
+/// * `algorithm` - Name of the algorithm.
+/// * `operation` - Name of the operation.
+/// * `function` - Operation to be executed.
+///
+/// # Returns
+/// The result of the operation.
+pub fn with_scope<T, F: FnOnce() -> T>(algorithm: &str, operation: &str, function: F) -> T {
+    let scope = EntropyScope { algorithm: algorithm.to_string(), operation: operation.to_string() };
+    let previous: Option<EntropyScope> = ENTROPY_SCOPE.with(|current| current.replace(Some(scope)));
+    let result: T = function();
+    ENTROPY_SCOPE.with(|current| current.replace(previous));
+    result
+}
+
+
+/// Fills a buffer with random bytes from the entropy source of the current thread, or from `OsEntropy` if none is
+/// installed.
+///
+/// # Arguments
+/// * `bytes` - Buffer to be filled.
+pub fn fill(bytes: &mut [u8]) {
+    let source: Option<Arc<dyn EntropySource>> = ENTROPY_SOURCE.with(|current| current.borrow().clone());
+    let source: Arc<dyn EntropySource> = match source {
+        Some(source) => { source }
+        None => { return OsEntropy.fill(&EntropyScope::default(), bytes) }
+    };
+
+    let scope: EntropyScope = ENTROPY_SCOPE.with(|current| current.borrow().clone()).unwrap_or_default();
+    source.fill(&scope, bytes);
+}
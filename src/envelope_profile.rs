@@ -0,0 +1,120 @@
+use std::cell::Cell;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+use crate::benchmark::{Benchmark, Measurement};
+
+/// Nanoseconds spent encoding and decoding envelopes since the start of the process.
+static ENVELOPE_NANOS: AtomicU64 = AtomicU64::new(0);
+
+thread_local! {
+    /// Amount of envelope steps running on the current thread, so that nested steps are only timed once.
+    static ENVELOPE_DEPTH: Cell<usize> = const { Cell::new(0) };
+}
+
+
+/// Timer of an envelope step, i.e. a JSON serialization, base64 encoding or JWT encoding or decoding of a credential or
+/// of one of its components, adding its duration to the envelope time of the process when dropped.
+pub struct EnvelopeTimer {
+    /// Start of the step, None if nested in another step.
+    start: Option<Instant>,
+}
+
+impl EnvelopeTimer {
+
+    /// Starts timing an envelope step, unless it is nested in a step already being timed on the current thread.
+    pub fn start() -> Self {
+        let depth: usize = ENVELOPE_DEPTH.with(|depth| depth.replace(depth.get() + 1));
+        EnvelopeTimer { start: if depth == 0 { Some(Instant::now()) } else { None } }
+    }
+}
+
+impl Drop for EnvelopeTimer {
+    fn drop(&mut self) {
+        ENVELOPE_DEPTH.with(|depth| depth.set(depth.get().saturating_sub(1)));
+        if let Some(start) = self.start {
+            ENVELOPE_NANOS.fetch_add(start.elapsed().as_nanos() as u64, Ordering::Relaxed);
+        }
+    }
+}
+
+
+/// Duration of an operation of an algorithm split between the selective disclosure mechanism (hashing, proof
+/// generation and verification, filtering of the disclosures and witnesses) and the envelope around it (JSON
+/// serialization, base64 encoding and JWT encoding and decoding), so that algorithms are compared on their mechanism
+/// rather than on how they serialize it.
+///
+/// The envelope steps are timed where the algorithm instances encode and decode JWTs and their components. The JWS
+/// signature of a signed JWT is computed while encoding it, so the ES256 signatures of issuers and holders count as
+/// envelope, while the proofs the algorithms compute themselves (e.g. BBS+ signatures and accumulator witnesses) count
+/// as mechanism. The envelope time is shared by the whole process, so concurrent operations are timed together: split
+/// operations one at a time.
+///
+/// # Examples
+/// ```
+/// use std::thread::sleep;
+/// use std::time::Duration;
+/// use csd_jwt::envelope_profile::{EnvelopeSplit, EnvelopeTimer};
+///
+/// let ((), split) = EnvelopeSplit::measure(|| {
+///     sleep(Duration::from_millis(2));
+///     let _timer = EnvelopeTimer::start();
+///     sleep(Duration::from_millis(1));
+/// });
+/// assert!(split.envelope >= Duration::from_millis(1));
+/// assert!(split.crypto() >= Duration::from_millis(2));
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct EnvelopeSplit {
+    /// Duration of the whole operation.
+    pub total: Duration,
+    /// Time spent on the envelope.
+    pub envelope: Duration,
+}
+
+impl EnvelopeSplit {
+
+    /// Reads the envelope time of the process.
+    fn snapshot() -> Duration {
+        Duration::from_nanos(ENVELOPE_NANOS.load(Ordering::Relaxed))
+    }
+
+    /// Runs an operation once and splits its duration.
+    ///
+    /// # Arguments
+    /// * `operation` - Operation to be split.
+    ///
+    /// # Returns
+    /// The output of the operation and the split of its duration.
+    pub fn measure<T, F: FnOnce() -> T>(operation: F) -> (T, Self) {
+        let before: Duration = Self::snapshot();
+        let start: Instant = Instant::now();
+        let output: T = operation();
+        let total: Duration = start.elapsed();
+        (output, EnvelopeSplit { total, envelope: Self::snapshot().saturating_sub(before) })
+    }
+
+    /// Benchmarks an operation like `Benchmark::measure` and splits its averaged duration.
+    ///
+    /// # Arguments
+    /// * `func` - Function to be executed.
+    /// * `measurement` - How many times the function needs to be executed for average.
+    ///
+    /// # Returns
+    /// A result containing either the averaged split and the output of the function or a string containing an error.
+    pub fn benchmark<F, T>(func: F, measurement: Measurement) -> Result<(Self, T), String>
+    where
+        F: Fn() -> Result<T, String>
+    {
+        let runs: Cell<u32> = Cell::new(0);
+        let before: Duration = Self::snapshot();
+        let (total, output) = Benchmark::measure(|| { runs.set(runs.get() + 1); func() }, measurement)?;
+        let envelope: Duration = Self::snapshot().saturating_sub(before) / runs.get().max(1);
+
+        Ok((EnvelopeSplit { total, envelope: envelope.min(total) }, output))
+    }
+
+    /// Time spent on the selective disclosure mechanism, i.e. outside of the envelope.
+    pub fn crypto(&self) -> Duration {
+        self.total.saturating_sub(self.envelope)
+    }
+}
@@ -0,0 +1,172 @@
+use josekit::jws::{JwsHeader, ES256};
+use josekit::jwt;
+use josekit::jwt::JwtPayload;
+use multibase::Base;
+use serde_json::{Map, Value};
+use crate::compression::decode_payload;
+use crate::deterministic::unix_time;
+use crate::encoding::Encoding;
+use crate::verifier_config::VerifierConfig;
+
+/// Claim of a VP envelope holding the certificate of the ephemeral key that signed the VP.
+pub const EPHEMERAL_KEY_CERTIFICATE: &str = "eph_cert";
+/// Claim of a certificate holding the DER encoding of the certified ephemeral public key.
+const EPHEMERAL_KEY: &str = "eph_key";
+/// Claim of a certificate holding its issuance time in seconds since the Unix epoch.
+const ISSUED_AT: &str = "iat";
+/// Type of the certificate JWTs.
+const CERTIFICATE_TYPE: &str = "eph-key+jwt";
+/// Length in characters of the lines of a PEM body.
+const PEM_LINE_LEN: usize = 64;
+
+/// ES256 keypair generated for a single presentation, certified by the long-term key the credential is bound to
+/// through its `cnf` claim. The long-term key only signs the short certificate, so that it can stay in a secure
+/// element, and a different key signs every presentation.
+///
+/// # Examples
+/// ```
+/// use csd_jwt::common_data::CommonData;
+/// use csd_jwt::ephemeral_binding::{verify_certificate, EphemeralKey};
+///
+/// let (holder_public_key, holder_private_key) = CommonData::holder_keys().unwrap();
+/// let ephemeral_key = EphemeralKey::generate().unwrap();
+/// let certificate = ephemeral_key.certify(&holder_private_key).unwrap();
+/// assert_eq!(verify_certificate(&certificate, &holder_public_key).unwrap(), ephemeral_key.public_key());
+/// ```
+pub struct EphemeralKey {
+    /// PEM encoding of the public key.
+    public_key: Vec<u8>,
+    /// PEM encoding of the private key.
+    private_key: Vec<u8>,
+    /// DER encoding of the public key, as carried by the certificate.
+    der_public_key: Vec<u8>,
+}
+
+impl EphemeralKey {
+
+    /// Generates a fresh P-256 keypair.
+    ///
+    /// # Returns
+    /// A result containing the ephemeral key or a string containing an error.
+    pub fn generate() -> Result<Self, String> {
+        let key_pair = match ES256.generate_key_pair() {
+            Ok(key_pair) => { key_pair }
+            Err(err) => { return Err(format!("Failed to generate ephemeral key: [{err}]")) }
+        };
+
+        Ok(EphemeralKey {
+            public_key: key_pair.to_pem_public_key(),
+            private_key: key_pair.to_pem_private_key(),
+            der_public_key: key_pair.to_der_public_key(),
+        })
+    }
+
+    /// PEM encoding of the public key.
+    pub fn public_key(&self) -> &[u8] {
+        &self.public_key
+    }
+
+    /// PEM encoding of the private key, signing the presentation.
+    pub fn private_key(&self) -> &[u8] {
+        &self.private_key
+    }
+
+    /// Certifies the ephemeral public key with the long-term key of the holder.
+    ///
+    /// # Arguments
+    /// * `holder_private_key` - Long-term private key of the holder, the one the credential is bound to.
+    ///
+    /// # Returns
+    /// A result containing the certificate as a JWT or a string containing an error.
+    pub fn certify(&self, holder_private_key: &impl AsRef<[u8]>) -> Result<String, String> {
+        let issued_at: u64 = unix_time()?;
+
+        let mut claims: Map<String, Value> = Map::new();
+        claims.insert(EPHEMERAL_KEY.to_string(), Value::String(Encoding::Base64UrlNoPad.encode(&self.der_public_key)));
+        claims.insert(ISSUED_AT.to_string(), Value::from(issued_at));
+        let payload = match JwtPayload::from_map(claims) {
+            Ok(payload) => { payload }
+            Err(err) => { return Err(format!("Failed to build certificate: [{err}]")) }
+        };
+
+        let mut header = JwsHeader::new();
+        header.set_token_type(CERTIFICATE_TYPE);
+        let signer = match ES256.signer_from_pem(holder_private_key) {
+            Ok(signer) => { signer }
+            Err(err) => { return Err(format!("Failed to create signer: [{err}]")) }
+        };
+
+        match jwt::encode_with_signer(&payload, &header, &signer) {
+            Ok(certificate) => { Ok(certificate) }
+            Err(err) => { Err(format!("Failed to encode and sign certificate: [{err}]")) }
+        }
+    }
+}
+
+
+/// Verifies the certificate of an ephemeral key against the long-term key of the holder.
+///
+/// # Arguments
+/// * `certificate` - Certificate JWT.
+/// * `holder_public_key` - Long-term public key of the holder.
+///
+/// # Returns
+/// A result containing the PEM encoding of the certified ephemeral public key or a string containing an error.
+pub fn verify_certificate(certificate: &str, holder_public_key: &impl AsRef<[u8]>) -> Result<Vec<u8>, String> {
+    let verifier = match ES256.verifier_from_pem(holder_public_key) {
+        Ok(verifier) => { verifier }
+        Err(err) => { return Err(format!("Failed to create verifier: [{err}]")) }
+    };
+    let (payload, header) = match jwt::decode_with_verifier(certificate, &verifier) {
+        Ok(decoded) => { decoded }
+        Err(err) => { return Err(format!("Failed to decode and verify certificate: [{err}]")) }
+    };
+
+    if header.token_type() != Some(CERTIFICATE_TYPE) {
+        return Err(format!("Certificate is not of type {CERTIFICATE_TYPE}"));
+    }
+    let der_public_key: Vec<u8> = match payload.claim(EPHEMERAL_KEY) {
+        Some(Value::String(encoded)) => match Encoding::Base64UrlNoPad.decode(encoded) {
+            Ok(der_public_key) => { der_public_key }
+            Err(err) => { return Err(format!("Failed to decode certified key: [{err}]")) }
+        },
+        _ => { return Err(format!("Certificate does not contain {EPHEMERAL_KEY}")) }
+    };
+
+    Ok(der_to_pem(&der_public_key))
+}
+
+/// Wraps the DER encoding of a public key in PEM armor.
+fn der_to_pem(der_public_key: &[u8]) -> Vec<u8> {
+    let body: String = Base::Base64Pad.encode(der_public_key);
+    let mut pem: String = "-----BEGIN PUBLIC KEY-----\n".to_string();
+    for line in body.as_bytes().chunks(PEM_LINE_LEN) {
+        pem.push_str(&String::from_utf8_lossy(line));
+        pem.push('\n');
+    }
+    pem.push_str("-----END PUBLIC KEY-----\n");
+    pem.into_bytes()
+}
+
+/// Reads the claims of a JWT without verifying its signature, e.g. to find the key it must be verified with.
+///
+/// # Arguments
+/// * `jwt` - JWT to be read.
+///
+/// # Returns
+/// A result containing the claims or a string containing an error if the JWT is malformed.
+pub fn unverified_claims(jwt: &str) -> Result<Map<String, Value>, String> {
+    let (encoded_header, encoded_payload) = match jwt.split('.').collect::<Vec<&str>>().as_slice() {
+        [encoded_header, encoded_payload, _] => { (*encoded_header, *encoded_payload) }
+        _ => { return Err("Token is not a JWT".to_string()) }
+    };
+    let header: Vec<u8> = match Encoding::Base64UrlNoPad.decode(encoded_header) {
+        Ok(header) => { header }
+        Err(err) => { return Err(format!("Failed to decode JWT header: [{err}]")) }
+    };
+
+    match serde_json::from_slice::<Map<String, Value>>(&header) {
+        Ok(header) => { decode_payload(&header, encoded_payload, VerifierConfig::DEFAULT.max_jwt_length) }
+        Err(err) => { Err(format!("Failed to parse JWT header: [{err}]")) }
+    }
+}
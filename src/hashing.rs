@@ -0,0 +1,115 @@
+/// Name of the backend computing the SHA-256 digests and base64url encodings of the hash-based algorithms, selected at
+/// compile time.
+///
+/// With the `fast-hash` feature, base64url strings are encoded and decoded with the SIMD routines of base64-simd, and
+/// SHA-256 uses the assembly backend of sha2. In both cases, sha2 detects the hardware SHA extensions (SHA-NI on x86-64,
+/// the SHA2 instructions of ARMv8) at runtime and uses them when available. The digests and encodings are identical
+/// across backends, so that credentials issued with one are verified with the other.
+pub const BACKEND: &str = if cfg!(feature = "fast-hash") { "fast-hash" } else { "portable" };
+
+/// Length of the SHA-256 digests in bytes.
+pub const DIGEST_LEN: usize = crate::primitives::DIGEST_LEN;
+
+
+/// Computes the SHA-256 digest of some bytes.
+///
+/// # Arguments
+/// * `data` - Bytes to be hashed.
+///
+/// # Returns
+/// The digest.
+///
+/// # Examples
+/// ```
+/// use csd_jwt::hashing::{encode_base64url, sha256};
+///
+/// assert_eq!(encode_base64url(sha256(b"abc")), "ungWv48Bz-pBQUDeXa4iI7ADYaOWF3qctBD_YfIAFa0");
+/// ```
+pub fn sha256<T: AsRef<[u8]>>(data: T) -> [u8; DIGEST_LEN] {
+    crate::primitives::sha256(data.as_ref())
+}
+
+
+/// Encodes bytes in base64url without padding with the configured backend.
+///
+/// # Arguments
+/// * `bytes` - Bytes to be encoded.
+///
+/// # Returns
+/// The encoded string.
+pub fn encode_base64url<T: AsRef<[u8]>>(bytes: T) -> String {
+    #[cfg(feature = "fast-hash")]
+    {
+        base64_simd::URL_SAFE_NO_PAD.encode_to_string(bytes.as_ref())
+    }
+    #[cfg(not(feature = "fast-hash"))]
+    {
+        multibase::Base::Base64Url.encode(bytes.as_ref())
+    }
+}
+
+/// Decodes a base64url string without padding with the configured backend, rejecting non-canonical encodings.
+///
+/// # Arguments
+/// * `encoded` - String to be decoded.
+///
+/// # Returns
+/// A result containing the decoded bytes or a string containing the error of the backend.
+///
+/// # Examples
+/// ```
+/// use csd_jwt::hashing::decode_base64url;
+///
+/// assert_eq!(decode_base64url("yv4").unwrap(), vec![0xca, 0xfe]);
+/// assert!(decode_base64url("yv4=").is_err());
+/// ```
+pub fn decode_base64url(encoded: &str) -> Result<Vec<u8>, String> {
+    #[cfg(feature = "fast-hash")]
+    {
+        base64_simd::URL_SAFE_NO_PAD.decode_to_vec(encoded).map_err(|err| err.to_string())
+    }
+    #[cfg(not(feature = "fast-hash"))]
+    {
+        multibase::Base::Base64Url.decode(encoded).map_err(|err| err.to_string())
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use digest::Digest;
+    use sha2::Sha256;
+    use crate::deterministic::random_bytes;
+    use crate::hashing::{decode_base64url, encode_base64url, sha256};
+
+    /// Compares the configured backend with the scalar encoder of multibase, so that running the tests with the
+    /// `fast-hash` feature checks that the SIMD encodings are byte-identical to the portable ones.
+    #[test]
+    fn base64url_matches_scalar_encoder() -> Result<(), String> {
+        for len in 0..=256 {
+            let bytes: Vec<u8> = random_bytes(len);
+            let scalar: String = multibase::Base::Base64Url.encode(&bytes);
+
+            let encoded: String = encode_base64url(&bytes);
+            assert_eq!(encoded, scalar, "encodings of {len} bytes differ");
+            assert_eq!(decode_base64url(&scalar)?, bytes, "decodings of {len} bytes differ");
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn base64url_rejects_non_canonical_encodings() {
+        for encoded in ["yv4=", "yv5", "y", "yv4!", "yv+/"] {
+            assert!(decode_base64url(encoded).is_err(), "{encoded} was decoded");
+        }
+    }
+
+    #[test]
+    fn sha256_matches_portable_digest() {
+        for len in [0, 1, 55, 56, 63, 64, 65, 1000] {
+            let bytes: Vec<u8> = random_bytes(len);
+            assert_eq!(sha256(&bytes).as_slice(), Sha256::digest(&bytes).as_slice(), "digests of {len} bytes differ");
+        }
+    }
+}
@@ -0,0 +1,277 @@
+use std::collections::BTreeMap;
+use serde_json::{Map, Value};
+use crate::common_data::CommonData;
+use crate::holder_binding::bind_holder_key;
+use crate::presentation_cache::{PresentationCache, ReusePolicy, DEFAULT_PRESENTATION_CACHE_ENTRIES};
+use crate::presentation_validity::PresentationValidity;
+use crate::pseudonym::{issue_pseudonymous_vp, LinkSecret};
+use crate::roles::{AccumulatorWitnesses, PreHashedIssuance, RoleAlgorithm};
+use crate::sd_algorithms::sd_algorithm::signed_fields;
+
+/// Holder of an algorithm, holding its own keypair and the public parameters of the issuer, but no issuer secret.
+pub struct Holder<A: RoleAlgorithm> {
+    /// Public key of the holder, which credentials are bound to.
+    public_key: Vec<u8>,
+    /// Private key of the holder, used to sign presentations.
+    private_key: Vec<u8>,
+    /// Public parameters of the issuer.
+    issuer_public: A::IssuerPublic,
+    /// Cache of the derived presentations, following the reuse policy of the holder.
+    presentation_cache: PresentationCache,
+    /// Validity window of the derived presentations, if they expire.
+    presentation_validity: Option<PresentationValidity>,
+}
+
+/// Commitment of a holder to the claims of a credential, of which only the digests are sent to the issuer.
+pub struct ClaimCommitment {
+    /// Raw VC bound to the key of the holder.
+    raw_vc: Map<String, Value>,
+    /// Digests of the claims, sent to the issuer.
+    digests: Vec<String>,
+    /// Salts opening the digests, kept by the holder.
+    opening: Map<String, Value>,
+    /// Signed fields of the credential, e.g. its `cnf` claim, sent to the issuer with the digests.
+    signed_fields: Map<String, Value>,
+}
+
+impl ClaimCommitment {
+
+    /// Digests of the claims, to be signed by the issuer.
+    pub fn digests(&self) -> &[String] {
+        &self.digests
+    }
+
+    /// Signed fields of the credential, to be signed by the issuer with the digests.
+    pub fn signed_fields(&self) -> &Map<String, Value> {
+        &self.signed_fields
+    }
+}
+
+
+impl<A: RoleAlgorithm> Holder<A> {
+
+    /// Constructor for a holder with the keys of the repository.
+    ///
+    /// # Arguments
+    /// * `issuer_public` - Public parameters published by the issuer.
+    ///
+    /// # Returns
+    /// A result containing the holder or a string containing an error if the keys cannot be read.
+    pub fn new(issuer_public: A::IssuerPublic) -> Result<Self, String> {
+        let (public_key, private_key) = CommonData::holder_keys()?;
+        Ok(Holder::with_keys(public_key, private_key, issuer_public))
+    }
+
+    /// Constructor for a holder with a given keypair.
+    ///
+    /// # Arguments
+    /// * `public_key` - Public key of the holder.
+    /// * `private_key` - Private key of the holder.
+    /// * `issuer_public` - Public parameters published by the issuer.
+    ///
+    /// # Returns
+    /// The holder.
+    pub fn with_keys(public_key: Vec<u8>, private_key: Vec<u8>, issuer_public: A::IssuerPublic) -> Self {
+        let presentation_cache = PresentationCache::new(ReusePolicy::Never, DEFAULT_PRESENTATION_CACHE_ENTRIES);
+        Holder { public_key, private_key, issuer_public, presentation_cache, presentation_validity: None }
+    }
+
+    /// Sets the reuse policy of the derived presentations, which by default are never reused.
+    ///
+    /// # Arguments
+    /// * `policy` - Policy of reuse of the presentations.
+    /// * `max_entries` - Maximum amount of presentations kept.
+    ///
+    /// # Returns
+    /// The holder, reusing the presentations according to the policy.
+    pub fn with_presentation_reuse(mut self, policy: ReusePolicy, max_entries: usize) -> Self {
+        self.presentation_cache = PresentationCache::new(policy, max_entries);
+        self
+    }
+
+    /// Sets the validity window of the derived presentations, so that a captured VP is rejected once it expires. The
+    /// time to live of the reuse policy should stay below the lifetime of the window.
+    ///
+    /// # Arguments
+    /// * `validity` - Validity window of the presentations.
+    ///
+    /// # Returns
+    /// The holder, deriving expiring presentations.
+    pub fn with_presentation_validity(mut self, validity: PresentationValidity) -> Self {
+        self.presentation_validity = Some(validity);
+        self
+    }
+
+    /// Cache of the derived presentations, exposing its statistics.
+    pub fn presentation_cache(&self) -> &PresentationCache {
+        &self.presentation_cache
+    }
+
+    /// Name of the algorithm.
+    pub fn sd_algorithm(&self) -> String {
+        A::NAME.to_string()
+    }
+
+    /// Public key of the holder, to be handed to the issuer and to verifiers.
+    pub fn public_key(&self) -> &[u8] {
+        &self.public_key
+    }
+
+    /// Verifies a received VC against the public parameters of the issuer.
+    ///
+    /// # Arguments
+    /// * `vc` - Received VC.
+    ///
+    /// # Returns
+    /// A result containing a string in case the VC is invalid.
+    pub fn verify_vc(&self, vc: &Map<String, Value>) -> Result<(), String> {
+        A::verify_vc(vc, &self.issuer_public)
+    }
+
+    /// Derives a VP from a VC, optionally bound to an audience, or reuses a previous one for the same VC, disclosures
+    /// and audience if the reuse policy allows it.
+    ///
+    /// # Arguments
+    /// * `vc` - VC from which the VP is derived.
+    /// * `disclosures` - Claims to disclose.
+    /// * `audience` - Optional audience of the VP.
+    ///
+    /// # Returns
+    /// A result containing the VP and its JWT or a string containing an error.
+    pub fn issue_vp(&self, vc: &Map<String, Value>, disclosures: &[String], audience: Option<String>) -> Result<(Map<String, Value>, String), String> {
+        let cached_audience: Option<String> = audience.clone();
+        self.presentation_cache.get_or_derive(vc, disclosures, cached_audience.as_deref(), || {
+            A::issue_vp(vc, disclosures, audience, self.presentation_validity, &self.issuer_public, &self.private_key)
+        })
+    }
+
+    /// Derives a VP from a VC and presents it under the pseudonym of the holder at a verifier, derived from a link
+    /// secret obtained from the private key of the holder. The VP is bound to the verifier as audience whenever the
+    /// algorithm supports it.
+    ///
+    /// # Arguments
+    /// * `vc` - VC from which the VP is derived.
+    /// * `disclosures` - Claims to disclose.
+    /// * `verifier_id` - Identifier of the verifier.
+    ///
+    /// # Returns
+    /// A result containing the JWT of the pseudonymous presentation or a string containing an error.
+    pub fn issue_pseudonymous_vp(&self, vc: &Map<String, Value>, disclosures: &[String], verifier_id: &str) -> Result<String, String> {
+        let audience: Option<String> = A::AUDIENCE_BINDING.then(|| verifier_id.to_string());
+        let (_, vp_jwt) = self.issue_vp(vc, disclosures, audience)?;
+        issue_pseudonymous_vp(&vp_jwt, verifier_id, &LinkSecret::from_holder_key(&self.private_key), &self.private_key)
+    }
+}
+
+impl<A: PreHashedIssuance> Holder<A> {
+
+    /// Binds a raw VC to the key of the holder and commits to its claims, so that the issuer only receives their
+    /// digests.
+    ///
+    /// # Arguments
+    /// * `raw_vc` - Template VC containing the claims of the holder.
+    ///
+    /// # Returns
+    /// A result containing the commitment or a string containing an error.
+    ///
+    /// # Examples
+    /// ```
+    /// use serde_json::{Map, Value};
+    /// use csd_jwt::common_data::VC;
+    /// use csd_jwt::holder::Holder;
+    /// use csd_jwt::issuer::Issuer;
+    /// use csd_jwt::sd_algorithms::hashes::merkle_trees::MerkleTreeInstance;
+    ///
+    /// let issuer: Issuer<MerkleTreeInstance> = Issuer::new(4).unwrap();
+    /// let holder: Holder<MerkleTreeInstance> = Holder::new(issuer.public_parameters().clone()).unwrap();
+    ///
+    /// let raw_vc: Map<String, Value> = serde_json::from_str(VC).unwrap();
+    /// let commitment = holder.commit_claims(&raw_vc).unwrap();
+    /// let signature = issuer.sign_digests(commitment.digests(), commitment.signed_fields()).unwrap();
+    /// let (vc, _) = holder.complete_issuance(&commitment, &signature).unwrap();
+    /// holder.verify_vc(&vc).unwrap();
+    ///
+    /// // A signature over other digests does not complete the issuance.
+    /// let other_commitment = holder.commit_claims(&raw_vc).unwrap();
+    /// assert!(holder.complete_issuance(&other_commitment, &signature).is_err());
+    /// ```
+    pub fn commit_claims(&self, raw_vc: &Map<String, Value>) -> Result<ClaimCommitment, String> {
+        let raw_vc: Map<String, Value> = bind_holder_key(raw_vc, &self.public_key)?;
+        let (digests, opening) = A::commit_claims(&raw_vc)?;
+        let signed_fields: Map<String, Value> = signed_fields(&raw_vc);
+        Ok(ClaimCommitment { raw_vc, digests, opening, signed_fields })
+    }
+
+    /// Assembles the VC from a commitment and the signature the issuer returned for its digests, verifying it against
+    /// the public parameters of the issuer.
+    ///
+    /// # Arguments
+    /// * `commitment` - Commitment returned by `commit_claims`.
+    /// * `signature` - Signature of the digests returned by the issuer.
+    ///
+    /// # Returns
+    /// A result containing the VC and its JWT or a string containing an error if the signature does not match.
+    pub fn complete_issuance(&self, commitment: &ClaimCommitment, signature: &[u8]) -> Result<(Map<String, Value>, String), String> {
+        let (vc, vc_jwt) = A::assemble_vc(&commitment.raw_vc, &commitment.opening, signature)?;
+        self.verify_vc(&vc)?;
+        Ok((vc, vc_jwt))
+    }
+}
+
+impl<A: AccumulatorWitnesses> Holder<A> {
+
+    /// Checks the witness of every claim of a received VC against its accumulator, so that the holder can tell which
+    /// claims it would fail to present.
+    ///
+    /// # Arguments
+    /// * `vc` - Received VC.
+    ///
+    /// # Returns
+    /// A result containing the validity of the witness of every claim or a string containing an error if the VC
+    /// cannot be decoded.
+    pub fn check_witnesses(&self, vc: &Map<String, Value>) -> Result<BTreeMap<String, bool>, String> {
+        A::check_witnesses(vc, &self.issuer_public)
+    }
+
+    /// Accepts a received VC for storage only if the witnesses of all its claims are valid.
+    ///
+    /// # Arguments
+    /// * `vc` - Received VC.
+    ///
+    /// # Returns
+    /// A result containing a string naming the claims with an invalid witness in case the VC is rejected.
+    ///
+    /// # Examples
+    /// ```
+    /// use serde_json::{Map, Value};
+    /// use csd_jwt::common_data::VC;
+    /// use csd_jwt::holder::Holder;
+    /// use csd_jwt::issuer::Issuer;
+    /// use csd_jwt::sd_algorithms::accumulators::csd_jwt::CsdJwtInstance;
+    ///
+    /// let issuer: Issuer<CsdJwtInstance> = Issuer::new(13).unwrap();
+    /// let holder: Holder<CsdJwtInstance> = Holder::new(issuer.public_parameters().clone()).unwrap();
+    ///
+    /// let raw_vc: Map<String, Value> = serde_json::from_str(VC).unwrap();
+    /// let (vc, _) = issuer.issue_vc(&raw_vc, holder.public_key()).unwrap();
+    /// holder.accept_vc(&vc).unwrap();
+    ///
+    /// // The VCs of another issuer are not accepted.
+    /// let other_issuer: Issuer<CsdJwtInstance> = Issuer::new(13).unwrap();
+    /// let (other_vc, _) = other_issuer.issue_vc(&raw_vc, holder.public_key()).unwrap();
+    /// assert!(holder.accept_vc(&other_vc).is_err());
+    /// ```
+    pub fn accept_vc(&self, vc: &Map<String, Value>) -> Result<(), String> {
+        let invalid_claims: Vec<String> = self.check_witnesses(vc)?
+            .into_iter()
+            .filter(|(_, valid)| !valid)
+            .map(|(claim, _)| claim)
+            .collect();
+
+        if !invalid_claims.is_empty() {
+            return Err(format!("Witnesses of claims {invalid_claims:?} do not match the accumulator"));
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,514 @@
+use std::sync::Arc;
+use josekit::jwk::alg::ed::{EdCurve, EdKeyPair};
+use josekit::jwk::{Jwk, KeyPair};
+use josekit::jws::{JwsSigner, JwsVerifier, EdDSA, ES256};
+use multibase::Base;
+use serde_json::{Map, Value};
+use crate::common_data::{CommonData, CNF, JWK_THUMBPRINT};
+use crate::encoding::Encoding;
+use crate::hashing::sha256;
+
+/// DER prefix of a SubjectPublicKeyInfo holding an uncompressed P-256 point (id-ecPublicKey, prime256v1).
+const P256_SPKI_PREFIX: [u8; 27] = [
+    0x30, 0x59, 0x30, 0x13, 0x06, 0x07, 0x2a, 0x86, 0x48, 0xce, 0x3d, 0x02, 0x01, 0x06, 0x08,
+    0x2a, 0x86, 0x48, 0xce, 0x3d, 0x03, 0x01, 0x07, 0x03, 0x42, 0x00, 0x04,
+];
+/// Length in bytes of a P-256 coordinate.
+const P256_COORDINATE_LEN: usize = 32;
+
+/// Computes the RFC 7638 JWK thumbprint of a PEM encoded P-256 public key, as used by ES256 holder keys.
+///
+/// # Arguments
+/// * `public_key` - PEM encoding of the public key.
+///
+/// # Returns
+/// A result containing the base64url encoded SHA-256 thumbprint or a string containing an error.
+///
+/// # Examples
+/// ```
+/// use csd_jwt::common_data::CommonData;
+/// use csd_jwt::holder_binding::jwk_thumbprint;
+///
+/// let (holder_public_key, _) = CommonData::holder_keys().unwrap();
+/// assert_eq!(jwk_thumbprint(&holder_public_key).unwrap().len(), 43);
+/// ```
+pub fn jwk_thumbprint(public_key: &impl AsRef<[u8]>) -> Result<String, String> {
+    let pem: &str = match std::str::from_utf8(public_key.as_ref()) {
+        Ok(pem) => { pem }
+        Err(err) => { return Err(format!("Public key is not valid PEM: [{err}]")) }
+    };
+
+    let body: String = pem.lines().filter(|line| !line.starts_with("-----")).map(|line| line.trim()).collect();
+    let der: Vec<u8> = match Base::Base64Pad.decode(&body) {
+        Ok(der) => { der }
+        Err(err) => { return Err(format!("Failed to decode PEM body: [{err}]")) }
+    };
+
+    if der.len() != P256_SPKI_PREFIX.len() + 2 * P256_COORDINATE_LEN || !der.starts_with(&P256_SPKI_PREFIX) {
+        return Err("Public key is not an uncompressed P-256 key".to_string());
+    }
+
+    let (x, y) = der[P256_SPKI_PREFIX.len()..].split_at(P256_COORDINATE_LEN);
+
+    // Required members in lexicographic order and without whitespace, as mandated by RFC 7638.
+    let canonical_jwk: String = format!(r#"{{"crv":"P-256","kty":"EC","x":"{}","y":"{}"}}"#, Encoding::Base64UrlNoPad.encode(x), Encoding::Base64UrlNoPad.encode(y));
+
+    Ok(Encoding::Base64UrlNoPad.encode(sha256(canonical_jwk)))
+}
+
+/// Embeds the thumbprint of the holder key in the `cnf` claim of a raw VC, binding the credential to that key.
+///
+/// # Arguments
+/// * `raw_vc` - Template VC to be bound.
+/// * `holder_public_key` - PEM encoding of the holder public key.
+///
+/// # Returns
+/// A result containing a copy of the raw VC with the `cnf` claim or a string containing an error.
+pub fn bind_holder_key(raw_vc: &Map<String, Value>, holder_public_key: &impl AsRef<[u8]>) -> Result<Map<String, Value>, String> {
+    Ok(bind_thumbprint(raw_vc, jwk_thumbprint(holder_public_key)?))
+}
+
+/// Embeds a key thumbprint in the `cnf` claim of a raw VC.
+fn bind_thumbprint(raw_vc: &Map<String, Value>, thumbprint: String) -> Map<String, Value> {
+    let mut confirmation: Map<String, Value> = Map::new();
+    confirmation.insert(JWK_THUMBPRINT.to_string(), Value::String(thumbprint));
+
+    let mut vc: Map<String, Value> = raw_vc.clone();
+    vc.insert(CNF.to_string(), Value::Object(confirmation));
+    vc
+}
+
+/// Retrieves the thumbprint of the holder key a credential is bound to from its `cnf` claim.
+///
+/// # Arguments
+/// * `credential` - Credential, or raw VC, possibly containing the `cnf` claim.
+///
+/// # Returns
+/// A result containing the thumbprint, if the credential is bound, or a string containing an error if the claim is malformed.
+pub fn bound_thumbprint(credential: &Map<String, Value>) -> Result<Option<String>, String> {
+    match credential.get(CNF) {
+        None => { Ok(None) }
+        Some(Value::Object(confirmation)) => match confirmation.get(JWK_THUMBPRINT) {
+            Some(Value::String(thumbprint)) => { Ok(Some(thumbprint.clone())) }
+            _ => { Err(format!("{CNF} claim does not contain a {JWK_THUMBPRINT} string")) }
+        },
+        Some(_) => { Err(format!("{CNF} claim is not an object")) }
+    }
+}
+
+/// Checks that the key a presentation was signed with is the one the credential is bound to, if any.
+///
+/// # Arguments
+/// * `credential` - Credential contained in the presentation.
+/// * `holder_public_key` - PEM encoding of the public key that verified the presentation signature.
+///
+/// # Returns
+/// Returns a string containing an error if the credential is bound to a different key.
+pub fn verify_holder_binding(credential: &Map<String, Value>, holder_public_key: &impl AsRef<[u8]>) -> Result<(), String> {
+    match bound_thumbprint(credential)? {
+        None => { Ok(()) }
+        Some(expected_thumbprint) => { check_thumbprint(&expected_thumbprint, &jwk_thumbprint(holder_public_key)?) }
+    }
+}
+
+/// Retrieves the thumbprint of the holder key a credential is bound to, for the bindings that bind every credential
+/// they present, so that a credential stripped of its `cnf` claim is rejected rather than accepted as unbound.
+fn required_thumbprint(credential: &Map<String, Value>) -> Result<String, String> {
+    match bound_thumbprint(credential)? {
+        Some(thumbprint) => { Ok(thumbprint) }
+        None => { Err(format!("Credential is not bound to a holder key: it does not contain the {CNF} claim.")) }
+    }
+}
+
+/// Checks that the thumbprint a credential is bound to is the one of the key that verified the presentation.
+fn check_thumbprint(expected_thumbprint: &str, thumbprint: &str) -> Result<(), String> {
+    if expected_thumbprint != thumbprint {
+        return Err("Presentation was not signed by the key the credential is bound to.".to_string());
+    }
+    Ok(())
+}
+
+
+/// Proof of possession a holder attaches to its presentations, selected per adapter so that experiments can isolate
+/// the cost of holder binding from the cost of selective disclosure. A binding holds both halves of the holder secret:
+/// the holder side signs the presentations and binds the credentials, the verifier side verifies them.
+///
+/// The PEM encoding of an ES256 key is itself a binding, signing if it is a private key and verifying if it is a
+/// public key, so that the algorithms accept either.
+///
+/// # Examples
+/// ```
+/// use csd_jwt::adapters::adapter::{Adapter, VerifierAdapter};
+/// use csd_jwt::adapters::hashes::sd_jwt_adapter::SdJwtAdapter;
+/// use csd_jwt::common_data::VC;
+/// use csd_jwt::holder_binding::holder_binding_from_name;
+///
+/// let adapter = SdJwtAdapter::new(10).unwrap().with_holder_binding(holder_binding_from_name("eddsa").unwrap());
+/// let (vc, _) = adapter.issue_vc(&serde_json::from_str(VC).unwrap()).unwrap();
+/// let (_, vp_jwt) = adapter.issue_vp(&vc, &vec!["name".to_string()]).unwrap();
+/// adapter.verify_vp(&vp_jwt).unwrap();
+///
+/// assert!(holder_binding_from_name("unknown").is_err());
+/// ```
+pub trait HolderBinding: Send + Sync {
+
+    /// Name of the binding, as parsed by `holder_binding_from_name`.
+    fn name(&self) -> &'static str;
+
+    /// Signer of the presentations.
+    ///
+    /// # Returns
+    /// A result containing the signer, or None if presentations are not signed, or a string containing an error.
+    fn signer(&self) -> Result<Option<Box<dyn JwsSigner>>, String>;
+
+    /// Verifier of the presentations.
+    ///
+    /// # Returns
+    /// A result containing the verifier, or None if presentations are not signed, or a string containing an error.
+    fn verifier(&self) -> Result<Option<Box<dyn JwsVerifier>>, String>;
+
+    /// Length in bytes of the secret the holder stores besides its credentials.
+    fn secret_length(&self) -> usize;
+
+    /// Binds a raw VC to the holder before issuance. Credentials are not bound by default.
+    ///
+    /// # Arguments
+    /// * `raw_vc` - Template VC to be bound.
+    ///
+    /// # Returns
+    /// A result containing a copy of the bound raw VC or a string containing an error.
+    fn bind_credential(&self, raw_vc: &Map<String, Value>) -> Result<Map<String, Value>, String> {
+        Ok(raw_vc.clone())
+    }
+
+    /// Checks that the credential contained in a verified presentation is bound to the holder.
+    ///
+    /// # Arguments
+    /// * `credential` - Credential contained in the presentation.
+    /// * `disclosed_claims` - Names of the claims disclosed by the presentation.
+    ///
+    /// # Returns
+    /// Returns a string containing an error if the credential is bound to another holder.
+    fn check_credential(&self, _credential: &Map<String, Value>, _disclosed_claims: &[String]) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+impl HolderBinding for [u8] {
+
+    fn name(&self) -> &'static str {
+        "es256"
+    }
+
+    fn signer(&self) -> Result<Option<Box<dyn JwsSigner>>, String> {
+        match ES256.signer_from_pem(self) {
+            Ok(signer) => { Ok(Some(Box::new(signer))) }
+            Err(err) => { Err(format!("Failed to create signer: [{err}]")) }
+        }
+    }
+
+    fn verifier(&self) -> Result<Option<Box<dyn JwsVerifier>>, String> {
+        match ES256.verifier_from_pem(self) {
+            Ok(verifier) => { Ok(Some(Box::new(verifier))) }
+            Err(err) => { Err(format!("Failed to create verifier: [{err}]")) }
+        }
+    }
+
+    fn secret_length(&self) -> usize {
+        self.len()
+    }
+
+    fn check_credential(&self, credential: &Map<String, Value>, _disclosed_claims: &[String]) -> Result<(), String> {
+        verify_holder_binding(credential, &self)
+    }
+}
+
+impl HolderBinding for Vec<u8> {
+
+    fn name(&self) -> &'static str {
+        self.as_slice().name()
+    }
+
+    fn signer(&self) -> Result<Option<Box<dyn JwsSigner>>, String> {
+        self.as_slice().signer()
+    }
+
+    fn verifier(&self) -> Result<Option<Box<dyn JwsVerifier>>, String> {
+        self.as_slice().verifier()
+    }
+
+    fn secret_length(&self) -> usize {
+        self.len()
+    }
+
+    fn check_credential(&self, credential: &Map<String, Value>, disclosed_claims: &[String]) -> Result<(), String> {
+        self.as_slice().check_credential(credential, disclosed_claims)
+    }
+}
+
+impl<B: HolderBinding + ?Sized> HolderBinding for &B {
+
+    fn name(&self) -> &'static str {
+        (**self).name()
+    }
+
+    fn signer(&self) -> Result<Option<Box<dyn JwsSigner>>, String> {
+        (**self).signer()
+    }
+
+    fn verifier(&self) -> Result<Option<Box<dyn JwsVerifier>>, String> {
+        (**self).verifier()
+    }
+
+    fn secret_length(&self) -> usize {
+        (**self).secret_length()
+    }
+
+    fn bind_credential(&self, raw_vc: &Map<String, Value>) -> Result<Map<String, Value>, String> {
+        (**self).bind_credential(raw_vc)
+    }
+
+    fn check_credential(&self, credential: &Map<String, Value>, disclosed_claims: &[String]) -> Result<(), String> {
+        (**self).check_credential(credential, disclosed_claims)
+    }
+}
+
+
+/// ES256 key-binding JWT: presentations are signed with the P-256 key of the holder, which the credentials are bound
+/// to through the thumbprint in their `cnf` claim. Presentations of credentials without the claim are rejected. This is
+/// the default binding of every adapter.
+pub struct Es256Binding {
+    /// PEM encoding of the public key.
+    public_key: Vec<u8>,
+    /// PEM encoding of the private key.
+    private_key: Vec<u8>,
+}
+
+impl Es256Binding {
+
+    /// Creates the binding from the PEM encoded keypair of the holder.
+    ///
+    /// # Arguments
+    /// * `public_key` - PEM encoding of the public key.
+    /// * `private_key` - PEM encoding of the private key.
+    pub fn new(public_key: Vec<u8>, private_key: Vec<u8>) -> Self {
+        Es256Binding { public_key, private_key }
+    }
+
+    /// Creates the binding from the keys of the holder in `common_data.rs`.
+    pub fn from_holder_keys() -> Result<Self, String> {
+        let (public_key, private_key) = CommonData::holder_keys()?;
+        Ok(Es256Binding::new(public_key, private_key))
+    }
+}
+
+impl HolderBinding for Es256Binding {
+
+    fn name(&self) -> &'static str {
+        "es256"
+    }
+
+    fn signer(&self) -> Result<Option<Box<dyn JwsSigner>>, String> {
+        self.private_key.signer()
+    }
+
+    fn verifier(&self) -> Result<Option<Box<dyn JwsVerifier>>, String> {
+        self.public_key.verifier()
+    }
+
+    fn secret_length(&self) -> usize {
+        self.private_key.len()
+    }
+
+    fn bind_credential(&self, raw_vc: &Map<String, Value>) -> Result<Map<String, Value>, String> {
+        bind_holder_key(raw_vc, &self.public_key)
+    }
+
+    fn check_credential(&self, credential: &Map<String, Value>, _disclosed_claims: &[String]) -> Result<(), String> {
+        check_thumbprint(&required_thumbprint(credential)?, &jwk_thumbprint(&self.public_key)?)
+    }
+}
+
+
+/// EdDSA key-binding JWT: presentations are signed with a freshly generated Ed25519 key of the holder, which the
+/// credentials are bound to through the RFC 8037 thumbprint of the key in their `cnf` claim. Presentations of
+/// credentials without the claim are rejected. The key is generated by OpenSSL, so the deterministic mode does not
+/// cover it.
+pub struct EdDsaBinding {
+    /// PEM encoding of the public key.
+    public_key: Vec<u8>,
+    /// PEM encoding of the private key.
+    private_key: Vec<u8>,
+    /// Base64url encoded SHA-256 thumbprint of the public key.
+    thumbprint: String,
+}
+
+impl EdDsaBinding {
+
+    /// Generates a new Ed25519 keypair.
+    pub fn generate() -> Result<Self, String> {
+        let jwk: Jwk = match Jwk::generate_ed_key(EdCurve::Ed25519) {
+            Ok(jwk) => { jwk }
+            Err(err) => { return Err(format!("Error in generating Jwk: [{err}]")) }
+        };
+        let x: &str = match jwk.parameter("x") {
+            Some(Value::String(x)) => { x.as_str() }
+            _ => { return Err("Generated Jwk does not contain the public key".to_string()) }
+        };
+
+        // Required members in lexicographic order and without whitespace, as mandated by RFC 7638 and RFC 8037.
+        let thumbprint: String = Encoding::Base64UrlNoPad.encode(sha256(format!(r#"{{"crv":"Ed25519","kty":"OKP","x":"{x}"}}"#)));
+
+        let key_pair: EdKeyPair = match EdKeyPair::from_jwk(&jwk) {
+            Ok(key_pair) => { key_pair }
+            Err(err) => { return Err(format!("Error in converting Jwk: [{err}]")) }
+        };
+
+        Ok(EdDsaBinding { public_key: key_pair.to_pem_public_key(), private_key: key_pair.to_pem_private_key(), thumbprint })
+    }
+}
+
+impl HolderBinding for EdDsaBinding {
+
+    fn name(&self) -> &'static str {
+        "eddsa"
+    }
+
+    fn signer(&self) -> Result<Option<Box<dyn JwsSigner>>, String> {
+        match EdDSA.signer_from_pem(&self.private_key) {
+            Ok(signer) => { Ok(Some(Box::new(signer))) }
+            Err(err) => { Err(format!("Failed to create signer: [{err}]")) }
+        }
+    }
+
+    fn verifier(&self) -> Result<Option<Box<dyn JwsVerifier>>, String> {
+        match EdDSA.verifier_from_pem(&self.public_key) {
+            Ok(verifier) => { Ok(Some(Box::new(verifier))) }
+            Err(err) => { Err(format!("Failed to create verifier: [{err}]")) }
+        }
+    }
+
+    fn secret_length(&self) -> usize {
+        self.private_key.len()
+    }
+
+    fn bind_credential(&self, raw_vc: &Map<String, Value>) -> Result<Map<String, Value>, String> {
+        Ok(bind_thumbprint(raw_vc, self.thumbprint.clone()))
+    }
+
+    fn check_credential(&self, credential: &Map<String, Value>, _disclosed_claims: &[String]) -> Result<(), String> {
+        check_thumbprint(&required_thumbprint(credential)?, &self.thumbprint)
+    }
+}
+
+
+/// No holder binding: presentations are neither signed nor bound to the holder, so that the cost of selective
+/// disclosure is measured alone. Presentations can be replayed by anyone who sees them.
+pub struct NoBinding;
+
+impl HolderBinding for NoBinding {
+
+    fn name(&self) -> &'static str {
+        "none"
+    }
+
+    fn signer(&self) -> Result<Option<Box<dyn JwsSigner>>, String> {
+        Ok(None)
+    }
+
+    fn verifier(&self) -> Result<Option<Box<dyn JwsVerifier>>, String> {
+        Ok(None)
+    }
+
+    fn secret_length(&self) -> usize {
+        0
+    }
+}
+
+
+/// Creates a holder binding from its name: `es256` for the keys of the holder in `common_data.rs`, `eddsa` for a fresh
+/// Ed25519 key or `none`.
+///
+/// # Arguments
+/// * `name` - Name of the binding.
+///
+/// # Returns
+/// A result containing the binding or a string containing an error if the name is unknown.
+pub fn holder_binding_from_name(name: &str) -> Result<Arc<dyn HolderBinding>, String> {
+    match name.trim() {
+        "es256" => { Ok(Arc::new(Es256Binding::from_holder_keys()?)) }
+        "eddsa" => { Ok(Arc::new(EdDsaBinding::generate()?)) }
+        "none" => { Ok(Arc::new(NoBinding)) }
+        name => { Err(format!("Unknown holder binding {name}, expected es256, eddsa or none")) }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use serde_json::{Map, Value};
+    use crate::adapters::accumulators::csd_jwt_adapter::CsdJwtAdapter;
+    use crate::adapters::accumulators::csd_jwt_aggregated_adapter::CsdJwtAggregatedAdapter;
+    use crate::adapters::accumulators::csd_jwt_bls12_381_adapter::CsdJwtBls12381Adapter;
+    use crate::adapters::accumulators::csd_jwt_multi_show_adapter::CsdJwtMultiShowAdapter;
+    use crate::adapters::adapter::Adapter;
+    use crate::adapters::hashes::merkle_tree_adapter::MerkleTreeAdapter;
+    use crate::adapters::hashes::sd_jwt_adapter::SdJwtAdapter;
+    use crate::adapters::signatures::bbs_plus_adapter::BBSPlusAdapter;
+    use crate::common_data::{CNF, VC};
+    use crate::holder_binding::{holder_binding_from_name, EdDsaBinding, HolderBinding};
+
+    /// Issues a VC bound to the default key of the adapter, then lets another holder present it after swapping its
+    /// `cnf` claim for its own key, or after removing it, and checks that both the VC and the VP are rejected.
+    fn check_cnf_tampering<A: Adapter>(adapter: A, with_holder_binding: fn(A, Arc<dyn HolderBinding>) -> A) -> Result<(), String> {
+        let raw_vc: Map<String, Value> = match serde_json::from_str::<Map<String, Value>>(VC) {
+            Ok(vc) => { vc }
+            Err(err) => { return Err(format!("Failed to parse Raw Verifiable Credential from string. [{err}]")); }
+        };
+        let disclosures: Vec<String> = vec!["name".to_string()];
+        let (vc, _vc_jwt) = adapter.issue_vc(&raw_vc)?;
+        let (_vp, vp_jwt) = adapter.issue_vp(&vc, &disclosures)?;
+        adapter.verify_vp(&vp_jwt)?;
+
+        let binding: Arc<EdDsaBinding> = Arc::new(EdDsaBinding::generate()?);
+        let attacker: A = with_holder_binding(adapter, binding.clone());
+
+        let swapped_vc: Map<String, Value> = binding.bind_credential(&vc)?;
+        assert_ne!(swapped_vc.get(CNF), vc.get(CNF));
+        let mut removed_vc: Map<String, Value> = vc.clone();
+        removed_vc.remove(CNF);
+
+        for (tampering, tampered_vc) in [("swapped", swapped_vc), ("removed", removed_vc)] {
+            assert!(attacker.verify_vc(&tampered_vc).is_err(), "[{}] VC with a {tampering} cnf claim was verified.", attacker.sd_algorithm());
+            let (_vp, vp_jwt) = attacker.issue_vp(&tampered_vc, &disclosures)?;
+            assert!(attacker.verify_vp(&vp_jwt).is_err(), "[{}] VP of a VC with a {tampering} cnf claim was verified.", attacker.sd_algorithm());
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn tampered_cnf_is_rejected_by_every_adapter() -> Result<(), String> {
+        check_cnf_tampering(SdJwtAdapter::new(16)?, SdJwtAdapter::with_holder_binding)?;
+        check_cnf_tampering(MerkleTreeAdapter::new(16)?, MerkleTreeAdapter::with_holder_binding)?;
+        check_cnf_tampering(BBSPlusAdapter::new(16)?, BBSPlusAdapter::with_holder_binding)?;
+        check_cnf_tampering(CsdJwtAdapter::new(16)?, CsdJwtAdapter::with_holder_binding)?;
+        check_cnf_tampering(CsdJwtMultiShowAdapter::new(16)?, CsdJwtMultiShowAdapter::with_holder_binding)?;
+        check_cnf_tampering(CsdJwtAggregatedAdapter::new(16)?, CsdJwtAggregatedAdapter::with_holder_binding)?;
+        check_cnf_tampering(CsdJwtBls12381Adapter::new(16)?, CsdJwtBls12381Adapter::with_holder_binding)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn holder_binding_names() -> Result<(), String> {
+        for name in ["es256", "eddsa", "none"] {
+            assert_eq!(holder_binding_from_name(&format!(" {name}\n"))?.name(), name);
+        }
+        assert!(holder_binding_from_name("link-secret").is_err());
+        assert!(holder_binding_from_name("").is_err());
+
+        Ok(())
+    }
+}
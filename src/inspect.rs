@@ -0,0 +1,249 @@
+use std::fmt;
+use serde_json::{Map, Value};
+use crate::claim_metadata::ClaimMetadata;
+use crate::common_data::{AUDIENCE, CLAIMS, CLAIM_GROUPS, CLAIM_METADATA, CNF, CREDENTIAL_ID, PRESENTATION_NONCE, PREVIOUS_CREDENTIAL, VERIFIABLE_CREDENTIAL, VP};
+use crate::compression::decode_payload;
+use crate::encoding::Encoding;
+use crate::sd_algorithms::sd_algorithm::ENCODING_HEADER;
+use crate::verifier_config::VerifierConfig;
+
+/// Fields of a credential that are plain JSON and not algorithm-specific components.
+const PLAIN_FIELDS: [&str; 9] = ["@context", "type", "issuer", CREDENTIAL_ID, PREVIOUS_CREDENTIAL, CLAIMS, CLAIM_METADATA, CLAIM_GROUPS, CNF];
+
+/// Structure of a single algorithm-specific component (signature, container, proof, ...) of a credential.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Component {
+    /// Name of the field holding the component.
+    pub name: String,
+    /// Length in bytes of the encoded component, as embedded in the JWT.
+    pub encoded_length: usize,
+    /// Amount of entries if the decoded component is a JSON array or object.
+    pub entries: Option<usize>,
+    /// Keys of the decoded component if it is a JSON object, e.g. the claim names of a container.
+    pub keys: Vec<String>,
+}
+
+/// Decoded structure of a VC or VP produced by the crate, obtained without verifying any signature.
+///
+/// # Examples
+/// ```
+/// use csd_jwt::inspect::Inspection;
+///
+/// assert!(Inspection::from_jwt("not a jwt").is_err());
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct Inspection {
+    /// JOSE header of the JWT.
+    pub header: Map<String, Value>,
+    /// Whether the JWT carries a Verifiable Presentation envelope.
+    pub is_presentation: bool,
+    /// Nonce of the presentation, if any.
+    pub nonce: Option<String>,
+    /// Audience of the presentation, if any.
+    pub audience: Option<String>,
+    /// Claims present in clear in the `credentialSubject` field.
+    pub clear_claims: Vec<String>,
+    /// Claims tagged in the `claimMetadata` sidecar.
+    pub tagged_claims: Vec<String>,
+    /// Algorithm-specific components of the credential.
+    pub components: Vec<Component>,
+    /// Length in bytes of the JWT signature, 0 for unsecured JWTs.
+    pub signature_length: usize,
+    /// Length in bytes of the whole JWT.
+    pub jwt_length: usize,
+}
+
+impl Inspection {
+
+    /// Decodes a VC or VP JWT without verifying it and collects its structure.
+    ///
+    /// # Arguments
+    /// * `jwt` - Encoded VC or VP.
+    ///
+    /// # Returns
+    /// A result containing the inspection or a string containing an error.
+    pub fn from_jwt(jwt: &str) -> Result<Self, String> {
+        let parts: Vec<&str> = jwt.trim().split('.').collect();
+        if parts.len() != 3 {
+            return Err(format!("JWT has {} parts instead of 3", parts.len()));
+        }
+
+        let header: Map<String, Value> = Self::decode_part(parts[0], "header")?;
+        let payload: Map<String, Value> = decode_payload(&header, parts[1], VerifierConfig::DEFAULT.max_jwt_length)?;
+        let signature_length: usize = match Encoding::Base64UrlNoPad.decode(parts[2]) {
+            Ok(signature) => { signature.len() }
+            Err(err) => { return Err(format!("Failed to decode signature: [{err}]")) }
+        };
+
+        let encoding: Encoding = match header.get(ENCODING_HEADER) {
+            Some(Value::String(name)) => { Encoding::from_name(name)? }
+            _ => { Encoding::default() }
+        };
+
+        let is_presentation = payload.contains_key(VP);
+        let credential: &Map<String, Value> = if is_presentation {
+            match payload.get(VP).and_then(|vp| vp.get(VERIFIABLE_CREDENTIAL)) {
+                Some(Value::Array(credentials)) => match credentials.first() {
+                    Some(Value::Object(credential)) => { credential }
+                    _ => { return Err("Presentation does not hold a credential".to_string()) }
+                },
+                _ => { return Err(format!("Presentation does not contain the {VERIFIABLE_CREDENTIAL} array")) }
+            }
+        } else {
+            &payload
+        };
+
+        let clear_claims: Vec<String> = match credential.get(CLAIMS) {
+            Some(Value::Object(claims)) => { claims.keys().cloned().collect() }
+            _ => { vec![] }
+        };
+        let tagged_claims: Vec<String> = ClaimMetadata::extract(credential)?.into_keys().collect();
+
+        let components: Vec<Component> = credential
+            .iter()
+            .filter(|(name, _)| !PLAIN_FIELDS.contains(&name.as_str()))
+            .map(|(name, value)| Self::inspect_component(name, value, encoding))
+            .collect();
+
+        let string_field = |field: &str| match payload.get(field) {
+            Some(Value::String(value)) => { Some(value.clone()) }
+            _ => { None }
+        };
+
+        Ok(Inspection {
+            is_presentation,
+            nonce: string_field(PRESENTATION_NONCE),
+            audience: string_field(AUDIENCE),
+            header,
+            clear_claims,
+            tagged_claims,
+            components,
+            signature_length,
+            jwt_length: jwt.trim().len(),
+        })
+    }
+
+    /// Decodes a base64url JWT part into a JSON object.
+    fn decode_part(part: &str, name: &str) -> Result<Map<String, Value>, String> {
+        let bytes = match Encoding::Base64UrlNoPad.decode(part) {
+            Ok(bytes) => { bytes }
+            Err(err) => { return Err(format!("Failed to decode {name}: [{err}]")) }
+        };
+
+        match serde_json::from_slice::<Map<String, Value>>(&bytes) {
+            Ok(map) => { Ok(map) }
+            Err(err) => { Err(format!("Failed to parse {name}: [{err}]")) }
+        }
+    }
+
+    /// Collects the structure of a component, decoding it with the encoding declared in the header when possible.
+    fn inspect_component(name: &str, value: &Value, encoding: Encoding) -> Component {
+        let encoded_length: usize = match value {
+            Value::String(encoded) => { encoded.len() }
+            _ => { value.to_string().len() }
+        };
+
+        let decoded: Option<Value> = match value {
+            Value::String(encoded) => {
+                encoding.decode(encoded).ok().and_then(|bytes| serde_json::from_slice::<Value>(&bytes).ok())
+            }
+            _ => { Some(value.clone()) }
+        };
+
+        let (entries, keys) = match decoded {
+            Some(Value::Object(map)) => { (Some(map.len()), map.keys().cloned().collect()) }
+            Some(Value::Array(array)) => { (Some(array.len()), vec![]) }
+            _ => { (None, vec![]) }
+        };
+
+        Component { name: name.to_string(), encoded_length, entries, keys }
+    }
+
+    /// Name of the algorithm declared in the JWT header.
+    ///
+    /// # Returns
+    /// The value of the `alg` header parameter, if any.
+    pub fn algorithm(&self) -> Option<&str> {
+        self.header.get("alg").and_then(|alg| alg.as_str())
+    }
+
+    /// Names of the claims disclosed by the credential, either in clear or as keys of a component (e.g. the
+    /// salt-value container of SD-JWT or the witness-value container of CSD-JWT).
+    ///
+    /// # Returns
+    /// The sorted names of the disclosed claims.
+    pub fn disclosed_claims(&self) -> Vec<String> {
+        let mut claims: Vec<String> = self.clear_claims.clone();
+        for component in &self.components {
+            for key in &component.keys {
+                if !claims.contains(key) {
+                    claims.push(key.clone());
+                }
+            }
+        }
+        claims.sort();
+        claims
+    }
+
+    /// Compares this inspection, typically of a VC, with another one, typically of a VP derived from it.
+    ///
+    /// # Arguments
+    /// * `other` - Inspection to compare with.
+    ///
+    /// # Returns
+    /// A JSON object with the withheld and added claims and the components whose size changed, appeared or disappeared.
+    pub fn diff(&self, other: &Inspection) -> Value {
+        let own_claims: Vec<String> = self.disclosed_claims();
+        let other_claims: Vec<String> = other.disclosed_claims();
+
+        let withheld: Vec<Value> = own_claims.iter().filter(|claim| !other_claims.contains(claim)).map(|claim| Value::String(claim.clone())).collect();
+        let added: Vec<Value> = other_claims.iter().filter(|claim| !own_claims.contains(claim)).map(|claim| Value::String(claim.clone())).collect();
+
+        let mut components: Map<String, Value> = Map::new();
+        for component in &self.components {
+            let other_length = other.components.iter().find(|other| other.name == component.name).map(|other| other.encoded_length);
+            if other_length != Some(component.encoded_length) {
+                components.insert(component.name.clone(), serde_json::json!([component.encoded_length, other_length]));
+            }
+        }
+        for component in &other.components {
+            if !self.components.iter().any(|own| own.name == component.name) {
+                components.insert(component.name.clone(), serde_json::json!([null, component.encoded_length]));
+            }
+        }
+
+        serde_json::json!({
+            "withheld_claims": withheld,
+            "added_claims": added,
+            "components": components,
+            "jwt_length": [self.jwt_length, other.jwt_length],
+        })
+    }
+}
+
+impl fmt::Display for Inspection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{} ({} bytes)", if self.is_presentation { "Verifiable Presentation" } else { "Verifiable Credential" }, self.jwt_length)?;
+        writeln!(f, "  header:      {}", Value::Object(self.header.clone()))?;
+        writeln!(f, "  algorithm:   {}", self.algorithm().unwrap_or("-"))?;
+        if self.is_presentation {
+            writeln!(f, "  nonce:       {}", self.nonce.as_deref().unwrap_or("-"))?;
+            writeln!(f, "  audience:    {}", self.audience.as_deref().unwrap_or("-"))?;
+        }
+        writeln!(f, "  signature:   {} bytes", self.signature_length)?;
+        writeln!(f, "  in clear:    {:?}", self.clear_claims)?;
+        writeln!(f, "  disclosed:   {:?}", self.disclosed_claims())?;
+        if !self.tagged_claims.is_empty() {
+            writeln!(f, "  tagged:      {:?}", self.tagged_claims)?;
+        }
+        writeln!(f, "  components:")?;
+        for component in &self.components {
+            match component.entries {
+                Some(entries) => { writeln!(f, "    {:<20} {:>8} bytes, {} entries", component.name, component.encoded_length, entries)?; }
+                None => { writeln!(f, "    {:<20} {:>8} bytes", component.name, component.encoded_length)?; }
+            }
+        }
+
+        Ok(())
+    }
+}
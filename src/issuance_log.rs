@@ -0,0 +1,232 @@
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use std::sync::Mutex;
+use serde_json::{Map, Value};
+use crate::common_data::CLAIMS;
+use crate::deterministic::unix_time;
+use crate::encoding::Encoding;
+use crate::hashing::sha256;
+
+/// Record of a single issued credential, as kept by the issuer for later revocation and compliance audits.
+#[derive(Clone, Debug, PartialEq)]
+pub struct IssuanceRecord {
+    /// Identifier of the credential: the `id` field of the VC if present, otherwise the digest of the encoded VC.
+    pub credential_id: String,
+    /// Name of the selective disclosure algorithm used to issue the credential.
+    pub algorithm: String,
+    /// Identifier of the issuer key used to issue the credential.
+    pub key_id: String,
+    /// Digests of the claims included in the credential.
+    pub claim_digests: Vec<String>,
+    /// Issuance time, in seconds since the UNIX epoch.
+    pub issued_at: u64,
+}
+
+impl IssuanceRecord {
+
+    /// Builds the record of a credential from the raw VC it was issued from.
+    ///
+    /// # Arguments
+    /// * `raw_vc` - Template VC the credential was issued from.
+    /// * `vc_jwt` - Encoded credential.
+    /// * `algorithm` - Name of the selective disclosure algorithm.
+    /// * `issuer_public_key` - Encoding of the issuer public key.
+    ///
+    /// # Returns
+    /// The record of the issued credential.
+    pub fn new(raw_vc: &Map<String, Value>, vc_jwt: &String, algorithm: String, issuer_public_key: &String) -> Self {
+
+        let credential_id = match raw_vc.get("id") {
+            Some(Value::String(id)) => { id.clone() }
+            _ => { Self::digest(vc_jwt.as_bytes()) }
+        };
+
+        let claim_digests: Vec<String> = match raw_vc.get(CLAIMS) {
+            Some(Value::Object(claims)) => {
+                claims.iter().map(|(key, value)| Self::digest(format!("{key}:{value}").as_bytes())).collect()
+            }
+            _ => { vec![] }
+        };
+
+        let issued_at: u64 = unix_time().unwrap_or(0);
+
+        IssuanceRecord {
+            credential_id,
+            algorithm,
+            key_id: Self::digest(issuer_public_key.as_bytes()),
+            claim_digests,
+            issued_at,
+        }
+    }
+
+    /// SHA-256 digest of the input, encoded in base64url.
+    fn digest(bytes: &[u8]) -> String {
+        Encoding::Base64UrlNoPad.encode(sha256(bytes))
+    }
+
+    /// Converts the record to a JSON object.
+    ///
+    /// # Returns
+    /// The JSON object containing all the fields of the record.
+    pub fn to_json(&self) -> Value {
+        let mut map: Map<String, Value> = Map::new();
+        map.insert("credential_id".to_string(), Value::String(self.credential_id.clone()));
+        map.insert("algorithm".to_string(), Value::String(self.algorithm.clone()));
+        map.insert("key_id".to_string(), Value::String(self.key_id.clone()));
+        map.insert("claim_digests".to_string(), Value::Array(self.claim_digests.iter().map(|digest| Value::String(digest.clone())).collect()));
+        map.insert("issued_at".to_string(), Value::from(self.issued_at));
+
+        Value::Object(map)
+    }
+}
+
+/// Destination of the issuance records produced by the adapters.
+pub trait IssuanceLog {
+
+    /// Stores a record of an issued credential.
+    ///
+    /// # Arguments
+    /// * `record` - Record to be stored.
+    ///
+    /// # Returns
+    /// Returns a result containing a string illustrating an error, if it occurs.
+    fn record(&self, record: &IssuanceRecord) -> Result<(), String>;
+}
+
+/// Issuance log appending one JSON object per line to a file.
+pub struct JsonlIssuanceLog {
+    /// File the records are appended to.
+    file: Mutex<File>,
+}
+
+impl JsonlIssuanceLog {
+
+    /// Opens, or creates, the JSONL file the records are appended to.
+    ///
+    /// # Arguments
+    /// * `path` - Path of the JSONL file.
+    ///
+    /// # Returns
+    /// A result containing the log or a string containing an error.
+    pub fn new(path: &Path) -> Result<Self, String> {
+        match OpenOptions::new().create(true).append(true).open(path) {
+            Ok(file) => { Ok(JsonlIssuanceLog { file: Mutex::new(file) }) }
+            Err(err) => { Err(format!("Error in opening issuance log: [{err}]")) }
+        }
+    }
+}
+
+impl IssuanceLog for JsonlIssuanceLog {
+    fn record(&self, record: &IssuanceRecord) -> Result<(), String> {
+        let mut file = match self.file.lock() {
+            Ok(file) => { file }
+            Err(err) => { return Err(format!("Issuance log is poisoned: [{err}]")) }
+        };
+
+        match writeln!(file, "{}", record.to_json()) {
+            Ok(_) => { Ok(()) }
+            Err(err) => { Err(format!("Error in writing issuance record: [{err}]")) }
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::PathBuf;
+    use std::sync::Mutex;
+    use serde_json::{json, Map, Value};
+    use crate::adapters::adapter::{Adapter, VerifierAdapter};
+    use crate::adapters::hashes::sd_jwt_adapter::SdJwtAdapter;
+    use crate::common_data::{CLAIMS, VC};
+    use crate::deterministic::random_bytes;
+    use crate::encoding::Encoding;
+    use crate::issuance_log::{IssuanceLog, IssuanceRecord, JsonlIssuanceLog};
+
+    /// Issuance log keeping the records in memory, or refusing them.
+    struct MemoryIssuanceLog {
+        records: Mutex<Vec<IssuanceRecord>>,
+        refuse: bool,
+    }
+
+    impl IssuanceLog for MemoryIssuanceLog {
+        fn record(&self, record: &IssuanceRecord) -> Result<(), String> {
+            if self.refuse {
+                return Err("Issuance log is full".to_string());
+            }
+            self.records.lock().map_err(|err| err.to_string())?.push(record.clone());
+            Ok(())
+        }
+    }
+
+    fn raw_vc() -> Result<Map<String, Value>, String> {
+        serde_json::from_str(VC).map_err(|err| format!("Failed to parse the mock credential: [{err}]"))
+    }
+
+    #[test]
+    fn record_identifies_the_credential() -> Result<(), String> {
+        let mut raw_vc: Map<String, Value> = raw_vc()?;
+        raw_vc.insert(CLAIMS.to_string(), json!({"name": "Alice", "field": "Physics"}));
+        let vc_jwt: String = "header.payload.signature".to_string();
+
+        let record = IssuanceRecord::new(&raw_vc, &vc_jwt, "SD-JWT".to_string(), &"issuer key".to_string());
+        assert_eq!(record.claim_digests.len(), 2);
+        assert_ne!(record.claim_digests[0], record.claim_digests[1]);
+        assert_eq!(record.algorithm, "SD-JWT");
+        assert!(record.issued_at > 0);
+
+        // Without an id, the credential is identified by the digest of its encoding.
+        raw_vc.remove("id");
+        let anonymous = IssuanceRecord::new(&raw_vc, &vc_jwt, "SD-JWT".to_string(), &"issuer key".to_string());
+        assert_eq!(Encoding::Base64UrlNoPad.decode(&anonymous.credential_id)?.len(), 32);
+        assert_eq!(anonymous.key_id, record.key_id);
+
+        raw_vc.insert("id".to_string(), json!("urn:uuid:1234"));
+        assert_eq!(IssuanceRecord::new(&raw_vc, &vc_jwt, "SD-JWT".to_string(), &"issuer key".to_string()).credential_id, "urn:uuid:1234");
+
+        // Credentials without claims are recorded without digests.
+        raw_vc.remove(CLAIMS);
+        assert!(IssuanceRecord::new(&raw_vc, &vc_jwt, "SD-JWT".to_string(), &"issuer key".to_string()).claim_digests.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn jsonl_log_appends_records() -> Result<(), String> {
+        let path: PathBuf = std::env::temp_dir().join(format!("issuance_log_{}.jsonl", Encoding::Hex.encode(random_bytes(8))));
+        let record = IssuanceRecord::new(&raw_vc()?, &"header.payload.signature".to_string(), "SD-JWT".to_string(), &"issuer key".to_string());
+
+        let log = JsonlIssuanceLog::new(&path)?;
+        log.record(&record)?;
+        log.record(&record)?;
+        let content: String = fs::read_to_string(&path).map_err(|err| err.to_string())?;
+        let _ = fs::remove_file(&path);
+
+        let lines: Vec<&str> = content.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(serde_json::from_str::<Value>(lines[0]).map_err(|err| err.to_string())?, record.to_json());
+
+        assert!(JsonlIssuanceLog::new(&path.join("missing").join("log.jsonl")).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn issue_vc_logged_records_every_credential() -> Result<(), String> {
+        let adapter = SdJwtAdapter::new(13)?;
+        let log = MemoryIssuanceLog { records: Mutex::new(vec![]), refuse: false };
+
+        let (vc, _vc_jwt) = adapter.issue_vc_logged(&raw_vc()?, &log)?;
+        adapter.verify_vc(&vc)?;
+        let records = log.records.lock().map_err(|err| err.to_string())?;
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].algorithm, adapter.sd_algorithm());
+
+        let refusing_log = MemoryIssuanceLog { records: Mutex::new(vec![]), refuse: true };
+        assert!(adapter.issue_vc_logged(&raw_vc()?, &refusing_log).is_err());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,274 @@
+use std::sync::Arc;
+use serde_json::{Map, Value};
+use crate::claim_minimization::MinimizationAdvice;
+use crate::clock::{Clock, SystemClock};
+use crate::common_data::ISSUED_AT;
+use crate::convert::lower_vc;
+use crate::credential_ir::{credential_id, CredentialIr};
+use crate::disclosure_policy::DisclosurePolicy;
+use crate::holder_binding::bind_holder_key;
+use crate::issuer_config::IssuerConfig;
+use crate::metadata_disclosure::DisclosableMetadata;
+use crate::roles::{PreHashedIssuance, RoleAlgorithm};
+
+/// Issuer of an algorithm, the only role holding the secret key material used to sign credentials. Holders and
+/// verifiers receive the public parameters it publishes.
+///
+/// # Examples
+/// ```
+/// use serde_json::{Map, Value};
+/// use csd_jwt::common_data::VC;
+/// use csd_jwt::holder::Holder;
+/// use csd_jwt::issuer::Issuer;
+/// use csd_jwt::sd_algorithms::hashes::sd_jwt::SdJwtInstance;
+/// use csd_jwt::verifier::Verifier;
+///
+/// let issuer: Issuer<SdJwtInstance> = Issuer::new(4).unwrap();
+/// let holder: Holder<SdJwtInstance> = Holder::new(issuer.public_parameters().clone()).unwrap();
+/// let verifier: Verifier<SdJwtInstance> = Verifier::new(issuer.public_parameters().clone());
+///
+/// let raw_vc: Map<String, Value> = serde_json::from_str(VC).unwrap();
+/// let (vc, _) = issuer.issue_vc(&raw_vc, holder.public_key()).unwrap();
+/// holder.verify_vc(&vc).unwrap();
+/// let (_, vp_jwt) = holder.issue_vp(&vc, &vec!["name".to_string()], None).unwrap();
+/// verifier.verify_vp(&vp_jwt, holder.public_key()).unwrap();
+///
+/// // The public parameters of another issuer verify neither the VC nor the VP.
+/// let other_issuer: Issuer<SdJwtInstance> = Issuer::new(4).unwrap();
+/// let other_holder: Holder<SdJwtInstance> = Holder::new(other_issuer.public_parameters().clone()).unwrap();
+/// assert!(other_holder.verify_vc(&vc).is_err());
+/// assert!(Verifier::<SdJwtInstance>::new(other_issuer.public_parameters().clone()).verify_vp(&vp_jwt, holder.public_key()).is_err());
+/// ```
+pub struct Issuer<A: RoleAlgorithm> {
+    /// Secret key material, never handed out.
+    secret: A::IssuerSecret,
+    /// Public parameters published to holders and verifiers.
+    public: A::IssuerPublic,
+    /// Minimization applied to the raw VCs before issuance, if any.
+    minimization: Option<MinimizationAdvice>,
+    /// Policies enforced on the credentials, e.g. their maximum validity period.
+    config: IssuerConfig,
+    /// Top-level fields made selectively disclosable, empty to leave them in the clear.
+    metadata: DisclosableMetadata,
+    /// Claims disclosed in every VP, empty to leave every claim selectively disclosable.
+    disclosure_policy: DisclosurePolicy,
+    /// Source of the issuance time of the credentials that do not set one.
+    clock: Arc<dyn Clock>,
+}
+
+impl<A: RoleAlgorithm> Issuer<A> {
+
+    /// Constructor for an issuer with freshly generated key material.
+    ///
+    /// # Arguments
+    /// * `claims_len` - Maximum amount of claims of the credentials.
+    ///
+    /// # Returns
+    /// A result containing the issuer or a string containing an error.
+    pub fn new(claims_len: usize) -> Result<Self, String> {
+        let (secret, public) = A::generate_issuer_keys(claims_len)?;
+        Ok(Issuer { secret, public, minimization: None, config: IssuerConfig::DEFAULT, metadata: DisclosableMetadata::new(), disclosure_policy: DisclosurePolicy::new(), clock: Arc::new(SystemClock) })
+    }
+
+    /// Sets the minimization advice applied to every raw VC before issuance. Holders must then translate the claims
+    /// requested by verifiers with `MinimizationAdvice::disclosures`.
+    ///
+    /// # Arguments
+    /// * `minimization` - Advice computed for the policy of the verifiers.
+    ///
+    /// # Returns
+    /// The issuer applying the advice.
+    pub fn with_claim_minimization(mut self, minimization: MinimizationAdvice) -> Self {
+        self.minimization = Some(minimization);
+        self
+    }
+
+    /// Sets the policies enforced on every credential, e.g. its maximum validity period.
+    ///
+    /// # Arguments
+    /// * `config` - Policies of the issuer.
+    ///
+    /// # Returns
+    /// The issuer enforcing the policies.
+    pub fn with_config(mut self, config: IssuerConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Sets the clock providing the issuance time of the credentials whose raw VC does not set one, e.g. a
+    /// `SimulatedClock` shared with the verifiers to simulate the lifetime of credentials.
+    ///
+    /// # Arguments
+    /// * `clock` - Source of the current time.
+    ///
+    /// # Returns
+    /// The issuer reading the time from the clock.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Makes top-level fields of every credential, e.g. `issuanceDate` or `credentialStatus`, selectively disclosable,
+    /// and rounds or commits to its issuance time as configured. Holders must then translate the requested fields with
+    /// `DisclosableMetadata::resolve`.
+    ///
+    /// # Arguments
+    /// * `metadata` - Fields to be protected like claims.
+    ///
+    /// # Returns
+    /// The issuer protecting the fields.
+    pub fn with_disclosable_metadata(mut self, metadata: DisclosableMetadata) -> Self {
+        self.metadata = metadata;
+        self
+    }
+
+    /// Marks claims of every credential as not selectively disclosable, e.g. the credential type or the issuing
+    /// country, so that they are disclosed in every VP. Verifiers must be configured with the same policy with
+    /// `Verifier::with_disclosure_policy` to reject VPs withholding them.
+    ///
+    /// # Arguments
+    /// * `disclosure_policy` - Claims disclosed in every VP.
+    ///
+    /// # Returns
+    /// The issuer enforcing the policy.
+    ///
+    /// # Examples
+    /// ```
+    /// use serde_json::{Map, Value};
+    /// use csd_jwt::common_data::VC;
+    /// use csd_jwt::disclosure_policy::DisclosurePolicy;
+    /// use csd_jwt::holder::Holder;
+    /// use csd_jwt::issuer::Issuer;
+    /// use csd_jwt::sd_algorithms::hashes::merkle_trees::MerkleTreeInstance;
+    /// use csd_jwt::verifier::Verifier;
+    ///
+    /// let policy: DisclosurePolicy = DisclosurePolicy::new().with_always_disclosed("nationality");
+    /// let issuer: Issuer<MerkleTreeInstance> = Issuer::new(13).unwrap().with_disclosure_policy(policy.clone());
+    /// let holder: Holder<MerkleTreeInstance> = Holder::new(issuer.public_parameters().clone()).unwrap();
+    /// let verifier: Verifier<MerkleTreeInstance> = Verifier::new(issuer.public_parameters().clone()).with_disclosure_policy(policy);
+    ///
+    /// let raw_vc: Map<String, Value> = serde_json::from_str(VC).unwrap();
+    /// let (vc, _) = issuer.issue_vc(&raw_vc, holder.public_key()).unwrap();
+    /// // The nationality is disclosed along with the name.
+    /// let (_, vp_jwt) = holder.issue_vp(&vc, &vec!["name".to_string()], None).unwrap();
+    /// verifier.verify_vp(&vp_jwt, holder.public_key()).unwrap();
+    ///
+    /// // A verifier requiring another claim rejects the VP.
+    /// let strict_policy: DisclosurePolicy = DisclosurePolicy::new().with_always_disclosed("field");
+    /// let strict_verifier: Verifier<MerkleTreeInstance> = Verifier::new(issuer.public_parameters().clone()).with_disclosure_policy(strict_policy);
+    /// assert!(strict_verifier.verify_vp(&vp_jwt, holder.public_key()).is_err());
+    /// ```
+    pub fn with_disclosure_policy(mut self, disclosure_policy: DisclosurePolicy) -> Self {
+        self.disclosure_policy = disclosure_policy;
+        self
+    }
+
+    /// Name of the algorithm.
+    pub fn sd_algorithm(&self) -> String {
+        A::NAME.to_string()
+    }
+
+    /// Public parameters of the issuer, to be handed to holders and verifiers.
+    pub fn public_parameters(&self) -> &A::IssuerPublic {
+        &self.public
+    }
+
+    /// Issues a VC bound to the public key of its holder, valid from its `iat` to its `exp` as set by the policies of
+    /// the issuer.
+    ///
+    /// # Arguments
+    /// * `raw_vc` - Template VC to be issued.
+    /// * `holder_public_key` - Public key of the holder the VC is issued to.
+    ///
+    /// # Returns
+    /// A result containing the VC and its JWT or a string containing an error.
+    pub fn issue_vc(&self, raw_vc: &Map<String, Value>, holder_public_key: &[u8]) -> Result<(Map<String, Value>, String), String> {
+        let raw_vc: Map<String, Value> = match &self.minimization {
+            Some(minimization) => { minimization.apply(raw_vc)? }
+            None => { raw_vc.clone() }
+        };
+        self.issue_bound_vc(raw_vc, |raw_vc| bind_holder_key(raw_vc, &holder_public_key))
+    }
+
+    /// Re-issues a VC with updated claims, e.g. after a change of address, bound to the same holder key as the VC it
+    /// replaces and linked to it through its `previousCredential` field. The issuance and expiration times are set
+    /// afresh by the policies of the issuer, while the claims are issued as they are, without minimization.
+    ///
+    /// # Arguments
+    /// * `vc` - VC to be replaced, issued by this issuer.
+    /// * `updated_claims` - Claims to be added or replaced, a `null` value removing the claim.
+    ///
+    /// # Returns
+    /// A result containing the new VC and its JWT or a string containing an error if the VC is invalid.
+    ///
+    /// # Examples
+    /// ```
+    /// use serde_json::{json, Map, Value};
+    /// use csd_jwt::common_data::VC;
+    /// use csd_jwt::convert::lower_vc;
+    /// use csd_jwt::credential_ir::credential_id;
+    /// use csd_jwt::holder::Holder;
+    /// use csd_jwt::issuer::Issuer;
+    /// use csd_jwt::sd_algorithms::hashes::sd_jwt::SdJwtInstance;
+    ///
+    /// let issuer: Issuer<SdJwtInstance> = Issuer::new(13).unwrap();
+    /// let holder: Holder<SdJwtInstance> = Holder::new(issuer.public_parameters().clone()).unwrap();
+    /// let (vc, _) = issuer.issue_vc(&serde_json::from_str(VC).unwrap(), holder.public_key()).unwrap();
+    ///
+    /// let updates: Map<String, Value> = serde_json::from_value(json!({"field": "Philosophy of Science", "nationality": null})).unwrap();
+    /// let (reissued_vc, _) = issuer.reissue(&vc, &updates).unwrap();
+    /// holder.verify_vc(&reissued_vc).unwrap();
+    ///
+    /// let credential = lower_vc(&reissued_vc, "SD-JWT").unwrap();
+    /// assert_eq!(credential.claims()["field"], json!("Philosophy of Science"));
+    /// assert!(!credential.claims().contains_key("nationality"));
+    /// assert_eq!(credential.metadata()["previousCredential"], json!(credential_id(&vc)));
+    /// assert_ne!(credential_id(&reissued_vc), credential_id(&vc));
+    ///
+    /// // The VCs of another issuer are not re-issued.
+    /// let other_issuer: Issuer<SdJwtInstance> = Issuer::new(13).unwrap();
+    /// assert!(other_issuer.reissue(&vc, &updates).is_err());
+    /// ```
+    pub fn reissue(&self, vc: &Map<String, Value>, updated_claims: &Map<String, Value>) -> Result<(Map<String, Value>, String), String> {
+        if let Err(err) = A::verify_vc(vc, &self.public) {
+            return Err(format!("Failed to verify the {} VC to be re-issued: [{err}]", A::NAME));
+        }
+
+        let credential: CredentialIr = lower_vc(vc, A::NAME)?;
+        let raw_vc: Map<String, Value> = credential.reissued_raw_vc(updated_claims, &credential_id(vc))?;
+        // The raw VC keeps the `cnf` field of the replaced VC.
+        self.issue_bound_vc(raw_vc, |raw_vc| Ok(raw_vc.clone()))
+    }
+
+    /// Applies the policies of the issuer to a raw VC, binds it to the key of its holder and issues it.
+    fn issue_bound_vc<F>(&self, mut raw_vc: Map<String, Value>, bind: F) -> Result<(Map<String, Value>, String), String>
+    where
+        F: FnOnce(&Map<String, Value>) -> Result<Map<String, Value>, String>,
+    {
+        if !raw_vc.contains_key(ISSUED_AT) {
+            raw_vc.insert(ISSUED_AT.to_string(), Value::from(self.clock.now()?));
+        }
+        self.metadata.blind_issuance_time(&mut raw_vc)?;
+        let raw_vc: Map<String, Value> = self.disclosure_policy.apply(&raw_vc)?;
+        let mut raw_vc: Map<String, Value> = self.config.apply(&raw_vc)?;
+        self.metadata.insert_into(&mut raw_vc)?;
+        A::issue_vc(&bind(&raw_vc)?, &self.secret, &self.public)
+    }
+}
+
+impl<A: PreHashedIssuance> Issuer<A> {
+
+    /// Signs the digests of the claims committed by a holder with `Holder::commit_claims`, together with the signed
+    /// fields of the credential, e.g. its `cnf` claim, without seeing the claims.
+    ///
+    /// # Arguments
+    /// * `digests` - Digests committed by the holder.
+    /// * `signed_fields` - Signed fields of the credential committed by the holder.
+    ///
+    /// # Returns
+    /// A result containing the signature to be returned to the holder or a string containing an error if the digests
+    /// are malformed.
+    pub fn sign_digests(&self, digests: &[String], signed_fields: &Map<String, Value>) -> Result<Vec<u8>, String> {
+        A::sign_digests(digests, signed_fields, &self.secret)
+    }
+}
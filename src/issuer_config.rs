@@ -0,0 +1,82 @@
+use serde_json::{Map, Value};
+use crate::common_data::{EXPIRATION, ISSUED_AT};
+use crate::deterministic::unix_time;
+
+/// Policies enforced by issuers on the credentials they issue.
+///
+/// # Examples
+/// ```
+/// use serde_json::{json, Map, Value};
+/// use csd_jwt::issuer_config::IssuerConfig;
+///
+/// let config = IssuerConfig { max_validity: Some(3600) };
+/// let raw_vc: Map<String, Value> = serde_json::from_value(json!({"iat": 1000, "credentialSubject": {}})).unwrap();
+/// assert_eq!(config.apply(&raw_vc).unwrap()["exp"], json!(4600));
+///
+/// let raw_vc: Map<String, Value> = serde_json::from_value(json!({"iat": 1000, "exp": 9000, "credentialSubject": {}})).unwrap();
+/// assert!(config.apply(&raw_vc).is_err());
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct IssuerConfig {
+    /// Maximum validity period of a credential in seconds, from its `iat` to its `exp`, if limited.
+    pub max_validity: Option<u64>,
+}
+
+impl IssuerConfig {
+
+    /// Default policy, issuing credentials without an expiration unless the raw VC sets one.
+    pub const DEFAULT: IssuerConfig = IssuerConfig {
+        max_validity: None,
+    };
+
+    /// Sets the validity period of a raw VC before issuance: the issuance time defaults to the current time and the
+    /// expiration to the end of the maximum validity period, while an expiration already set in the raw VC must fall
+    /// within it.
+    ///
+    /// # Arguments
+    /// * `raw_vc` - Template VC to be issued.
+    ///
+    /// # Returns
+    /// A result containing the raw VC with its validity period, or a string containing an error if the period set in
+    /// the raw VC is malformed or exceeds the maximum.
+    pub fn apply(&self, raw_vc: &Map<String, Value>) -> Result<Map<String, Value>, String> {
+        let mut raw_vc: Map<String, Value> = raw_vc.clone();
+        let issued_at: u64 = match raw_vc.get(ISSUED_AT) {
+            Some(issued_at) => match issued_at.as_u64() {
+                Some(issued_at) => { issued_at }
+                None => { return Err(format!("Field {ISSUED_AT} of the raw VC must be a timestamp in seconds")) }
+            },
+            None => { unix_time()? }
+        };
+        raw_vc.insert(ISSUED_AT.to_string(), Value::from(issued_at));
+
+        match (raw_vc.get(EXPIRATION), self.max_validity) {
+            (None, None) => {}
+            (None, Some(max_validity)) => {
+                raw_vc.insert(EXPIRATION.to_string(), Value::from(issued_at.saturating_add(max_validity)));
+            }
+            (Some(expiration), max_validity) => {
+                let expiration: u64 = match expiration.as_u64() {
+                    Some(expiration) => { expiration }
+                    None => { return Err(format!("Field {EXPIRATION} of the raw VC must be a timestamp in seconds")) }
+                };
+                if expiration <= issued_at {
+                    return Err(format!("Credential expires at {expiration}, before its issuance at {issued_at}"));
+                }
+                if let Some(max_validity) = max_validity {
+                    if expiration - issued_at > max_validity {
+                        return Err(format!("Validity period of {} seconds exceeds the limit of {max_validity} seconds", expiration - issued_at));
+                    }
+                }
+            }
+        }
+
+        Ok(raw_vc)
+    }
+}
+
+impl Default for IssuerConfig {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
@@ -0,0 +1,75 @@
+use serde::de::DeserializeOwned;
+
+/// Name of the backend parsing JSON on the verifier side, selected at compile time.
+///
+/// With the `simd-json` feature, JWT payloads, encoded proof fields and request bodies are parsed with simd-json.
+/// Otherwise, or together with the `arbitrary_precision` feature, whose numbers simd-json cannot produce, they are
+/// parsed with serde_json, which the `preserve_order` and `arbitrary_precision` features are forwarded to. Values are
+/// always `serde_json::Value`, and the claim digests only depend on their canonical serialization (claims sorted by
+/// name, object members sorted by key and numbers in their canonical decimal encoding), so they do not depend on the
+/// backend nor on its features.
+pub const BACKEND: &str = if cfg!(all(feature = "simd-json", not(feature = "arbitrary_precision"))) { "simd-json" } else { "serde_json" };
+
+
+/// Parses JSON bytes with the configured backend.
+///
+/// # Arguments
+/// * `bytes` - JSON document.
+///
+/// # Returns
+/// A result containing the parsed value or a string containing the error of the backend.
+///
+/// # Examples
+/// ```
+/// use serde_json::{Map, Value};
+/// use csd_jwt::json;
+///
+/// let claims: Map<String, Value> = json::from_slice(br#"{"name": "Ada", "age": 36}"#).unwrap();
+/// assert_eq!(claims["age"], 36);
+/// assert!(json::from_slice::<Map<String, Value>>(b"[1, 2]").is_err());
+/// ```
+pub fn from_slice<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, String> {
+    #[cfg(all(feature = "simd-json", not(feature = "arbitrary_precision")))]
+    {
+        // simd-json parses in place, so it needs its own copy of the document.
+        let mut bytes: Vec<u8> = bytes.to_vec();
+        simd_json::serde::from_slice::<T>(&mut bytes).map_err(|err| err.to_string())
+    }
+    #[cfg(not(all(feature = "simd-json", not(feature = "arbitrary_precision"))))]
+    {
+        serde_json::from_slice::<T>(bytes).map_err(|err| err.to_string())
+    }
+}
+
+/// Parses a JSON string with the configured backend.
+///
+/// # Arguments
+/// * `json` - JSON document.
+///
+/// # Returns
+/// A result containing the parsed value or a string containing the error of the backend.
+pub fn from_str<T: DeserializeOwned>(json: &str) -> Result<T, String> {
+    from_slice(json.as_bytes())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{Map, Value};
+    use crate::json;
+    use crate::sd_algorithms::hashes::sd_jwt::SdJwtInstance;
+    use crate::sd_algorithms::sd_algorithm::SdAlgorithm;
+
+    #[test]
+    fn canonical_serialization_ignores_member_order_and_number_format() -> Result<(), String> {
+        let first: Map<String, Value> = json::from_str(r#"{"b": 1.50, "a": [{"y": 1, "x": 2e0}], "c": "1.50"}"#)?;
+        let second: Map<String, Value> = json::from_str(r#"{"c": "1.50", "a": [{"x": 2, "y": 1.0}], "b": 15e-1}"#)?;
+
+        assert_eq!(
+            SdJwtInstance::canonical_json(&Value::Object(first)),
+            SdJwtInstance::canonical_json(&Value::Object(second)),
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,234 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+
+/// Default amount of time a resolved key is kept in a `KeyCache`.
+pub const DEFAULT_KEY_TTL: Duration = Duration::from_secs(300);
+/// Default maximum amount of keys kept in a `KeyCache`.
+pub const DEFAULT_KEY_CACHE_ENTRIES: usize = 64;
+
+
+/// Resolution of the public key of an issuer from its identifier, e.g. a DID URL resolved to the verification method of
+/// its DID document, a `kid` looked up in the JWKS of the issuer or the leaf of an x5c certificate chain.
+pub trait KeyResolver: Send + Sync {
+
+    /// Resolves the public key of an issuer.
+    ///
+    /// # Arguments
+    /// * `issuer` - Identifier of the issuer or of its key.
+    ///
+    /// # Returns
+    /// A result containing the encoding of the public key or a string containing an error if it cannot be resolved.
+    fn resolve(&self, issuer: &str) -> Result<String, String>;
+}
+
+
+/// Resolver of the keys published by a fixed set of issuers, standing in for the DID documents, JWKS endpoints or
+/// certificate chains a verifier would fetch, with an optional latency simulating the fetch.
+#[derive(Clone, Debug, Default)]
+pub struct StaticKeyResolver {
+    /// Encoded public keys, by issuer.
+    keys: HashMap<String, String>,
+    /// Duration of every resolution.
+    latency: Duration,
+}
+
+impl StaticKeyResolver {
+
+    /// Constructor for a resolver without any key.
+    pub fn new() -> Self {
+        StaticKeyResolver::default()
+    }
+
+    /// Publishes the key of an issuer.
+    ///
+    /// # Arguments
+    /// * `issuer` - Identifier of the issuer or of its key.
+    /// * `public_key` - Encoding of the public key.
+    pub fn with_key(mut self, issuer: &str, public_key: &str) -> Self {
+        self.keys.insert(issuer.to_string(), public_key.to_string());
+        self
+    }
+
+    /// Sets the duration of every resolution, e.g. the round trip to a JWKS endpoint.
+    pub fn with_latency(mut self, latency: Duration) -> Self {
+        self.latency = latency;
+        self
+    }
+}
+
+impl KeyResolver for StaticKeyResolver {
+    fn resolve(&self, issuer: &str) -> Result<String, String> {
+        if !self.latency.is_zero() {
+            sleep(self.latency);
+        }
+
+        match self.keys.get(issuer) {
+            Some(public_key) => { Ok(public_key.clone()) }
+            None => { Err(format!("No key is published for issuer {issuer}")) }
+        }
+    }
+}
+
+
+/// Verifier-side cache of the keys resolved through another resolver, so that repeated presentations of the same issuer
+/// skip the resolution. Keys expire after a time to live, so that rotated keys are eventually picked up, and the
+/// least recently resolved key is evicted once the cache is full.
+///
+/// # Examples
+/// ```
+/// use std::time::Duration;
+/// use csd_jwt::key_cache::{KeyCache, KeyResolver, StaticKeyResolver};
+///
+/// let resolver = StaticKeyResolver::new().with_key("did:web:issuer.example#key-1", "-----BEGIN PUBLIC KEY-----...");
+/// let cache = KeyCache::new(resolver, Duration::from_secs(60), 16);
+/// assert_eq!(cache.resolve("did:web:issuer.example#key-1").unwrap(), "-----BEGIN PUBLIC KEY-----...");
+/// assert!(cache.resolve("did:web:issuer.example#key-1").is_ok());
+/// assert!(cache.resolve("did:web:unknown.example#key-1").is_err());
+/// assert_eq!((cache.resolutions(), cache.hits()), (2, 1));
+/// ```
+pub struct KeyCache<R: KeyResolver> {
+    /// Resolver queried on a miss.
+    resolver: R,
+    /// Amount of time a resolved key is kept.
+    ttl: Duration,
+    /// Maximum amount of keys kept.
+    max_entries: usize,
+    /// Resolved keys, by issuer, with the time of their resolution.
+    entries: Mutex<HashMap<String, (String, Instant)>>,
+    /// Amount of lookups that queried the resolver.
+    resolutions: AtomicUsize,
+    /// Amount of lookups that found a fresh key in the cache.
+    hits: AtomicUsize,
+}
+
+impl<R: KeyResolver> KeyCache<R> {
+
+    /// Constructor for an empty cache.
+    ///
+    /// # Arguments
+    /// * `resolver` - Resolver queried on a miss.
+    /// * `ttl` - Amount of time a resolved key is kept.
+    /// * `max_entries` - Maximum amount of keys kept, 0 disabling the cache.
+    ///
+    /// # Returns
+    /// An empty cache with zeroed statistics.
+    pub fn new(resolver: R, ttl: Duration, max_entries: usize) -> Self {
+        KeyCache {
+            resolver,
+            ttl,
+            max_entries,
+            entries: Mutex::new(HashMap::new()),
+            resolutions: AtomicUsize::new(0),
+            hits: AtomicUsize::new(0),
+        }
+    }
+
+    /// Removes every key and resets the statistics.
+    pub fn clear(&self) {
+        if let Ok(mut entries) = self.entries.lock() {
+            entries.clear();
+        }
+        self.resolutions.store(0, Ordering::Relaxed);
+        self.hits.store(0, Ordering::Relaxed);
+    }
+
+    /// Amount of lookups that queried the resolver, whether it succeeded or not.
+    pub fn resolutions(&self) -> usize {
+        self.resolutions.load(Ordering::Relaxed)
+    }
+
+    /// Amount of lookups that found a fresh key in the cache.
+    pub fn hits(&self) -> usize {
+        self.hits.load(Ordering::Relaxed)
+    }
+
+    /// Looks up a fresh key in the cache.
+    fn cached(&self, issuer: &str) -> Option<String> {
+        let entries = self.entries.lock().ok()?;
+        match entries.get(issuer) {
+            Some((public_key, resolved_at)) if resolved_at.elapsed() < self.ttl => { Some(public_key.clone()) }
+            _ => { None }
+        }
+    }
+
+    /// Stores a resolved key, evicting the expired keys and then the least recently resolved one if the cache is full.
+    fn store(&self, issuer: &str, public_key: &str) {
+        if self.max_entries == 0 {
+            return;
+        }
+
+        if let Ok(mut entries) = self.entries.lock() {
+            if entries.len() >= self.max_entries && !entries.contains_key(issuer) {
+                entries.retain(|_, (_, resolved_at)| resolved_at.elapsed() < self.ttl);
+            }
+            if entries.len() >= self.max_entries && !entries.contains_key(issuer) {
+                let oldest: Option<String> = entries.iter()
+                    .min_by_key(|(_, (_, resolved_at))| *resolved_at)
+                    .map(|(issuer, _)| issuer.clone());
+                if let Some(oldest) = oldest {
+                    entries.remove(&oldest);
+                }
+            }
+            entries.insert(issuer.to_string(), (public_key.to_string(), Instant::now()));
+        }
+    }
+}
+
+impl<R: KeyResolver> KeyResolver for KeyCache<R> {
+    fn resolve(&self, issuer: &str) -> Result<String, String> {
+        if let Some(public_key) = self.cached(issuer) {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+            return Ok(public_key);
+        }
+
+        self.resolutions.fetch_add(1, Ordering::Relaxed);
+        let public_key: String = self.resolver.resolve(issuer)?;
+        self.store(issuer, &public_key);
+
+        Ok(public_key)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+    use crate::key_cache::{KeyCache, KeyResolver, StaticKeyResolver};
+
+    fn resolver() -> StaticKeyResolver {
+        StaticKeyResolver::new()
+            .with_key("did:web:a.example#key-1", "key-a")
+            .with_key("did:web:b.example#key-1", "key-b")
+            .with_key("did:web:c.example#key-1", "key-c")
+    }
+
+    #[test]
+    fn expired_keys_are_resolved_again() -> Result<(), String> {
+        let cache = KeyCache::new(resolver(), Duration::ZERO, 16);
+        cache.resolve("did:web:a.example#key-1")?;
+        cache.resolve("did:web:a.example#key-1")?;
+        assert_eq!((cache.resolutions(), cache.hits()), (2, 0));
+
+        Ok(())
+    }
+
+    #[test]
+    fn full_cache_evicts_the_oldest_key() -> Result<(), String> {
+        let cache = KeyCache::new(resolver(), Duration::from_secs(60), 2);
+        cache.resolve("did:web:a.example#key-1")?;
+        std::thread::sleep(Duration::from_millis(1));
+        cache.resolve("did:web:b.example#key-1")?;
+        cache.resolve("did:web:c.example#key-1")?;
+        assert_eq!(cache.resolve("did:web:c.example#key-1")?, "key-c");
+        assert_eq!(cache.resolve("did:web:b.example#key-1")?, "key-b");
+        assert_eq!((cache.resolutions(), cache.hits()), (3, 2));
+
+        cache.resolve("did:web:a.example#key-1")?;
+        assert_eq!(cache.resolutions(), 4);
+
+        Ok(())
+    }
+}
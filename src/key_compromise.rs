@@ -0,0 +1,229 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use serde_json::{Map, Value};
+use crate::common_data::ISSUED_AT;
+
+/// Prefix of the error of every credential rejected because its issuer key is compromised, so that callers handling
+/// errors as strings can tell it apart from an invalid proof.
+pub const KEY_COMPROMISED_ERROR: &str = "Issuer key compromised";
+
+
+/// Rejection of a credential issued under a compromised key after the time of the compromise.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KeyCompromise {
+    /// ID of the compromised key.
+    pub kid: String,
+    /// Time of the compromise, in seconds since the UNIX epoch.
+    pub compromised_since: u64,
+    /// Issuance time of the credential, if it carries one.
+    pub issued_at: Option<u64>,
+}
+
+impl fmt::Display for KeyCompromise {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.issued_at {
+            Some(issued_at) => { write!(f, "{KEY_COMPROMISED_ERROR}: credential issued at {issued_at} under key {} compromised since {}", self.kid, self.compromised_since) }
+            None => { write!(f, "{KEY_COMPROMISED_ERROR}: credential without {ISSUED_AT} under key {} compromised since {}", self.kid, self.compromised_since) }
+        }
+    }
+}
+
+
+/// Registry of the compromised issuer keys trusted by a verifier, by `kid`, with the time from which each key is
+/// compromised. Credentials issued before that time remain valid, while credentials issued at or after it, or without an
+/// issuance time, are rejected, since an attacker holding the key can sign any of them.
+///
+/// The `iat` of a credential is covered by the issuer commitment, so a verified credential cannot be backdated by its
+/// holder. The check remains advisory though: whoever holds the compromised key can sign a credential with any `iat`,
+/// including one before the compromise, so an accepted credential is only as trustworthy as the key was at its `iat`.
+///
+/// # Examples
+/// ```
+/// use csd_jwt::key_compromise::CompromiseRegistry;
+///
+/// let mut registry = CompromiseRegistry::new();
+/// registry.mark_compromised("key-1", 1_700_000_000);
+///
+/// assert!(registry.check("key-1", Some(1_600_000_000)).is_ok());
+/// assert!(registry.check("key-1", Some(1_700_000_000)).is_err());
+/// assert!(registry.check("key-1", None).is_err());
+/// assert!(registry.check("key-2", Some(1_800_000_000)).is_ok());
+///
+/// let published = CompromiseRegistry::from_json(&registry.to_json().unwrap()).unwrap();
+/// assert_eq!(published, registry);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CompromiseRegistry {
+    /// Time of the compromise, by key ID.
+    compromised: BTreeMap<String, u64>,
+}
+
+impl CompromiseRegistry {
+
+    /// Constructor for a registry without any compromised key.
+    pub fn new() -> Self {
+        CompromiseRegistry::default()
+    }
+
+    /// Marks a key as compromised from a given time. Marking a key again keeps the earliest time.
+    ///
+    /// # Arguments
+    /// * `kid` - ID of the key.
+    /// * `compromised_since` - Time of the compromise, in seconds since the UNIX epoch.
+    pub fn mark_compromised(&mut self, kid: &str, compromised_since: u64) {
+        let since: &mut u64 = self.compromised.entry(kid.to_string()).or_insert(compromised_since);
+        *since = (*since).min(compromised_since);
+    }
+
+    /// Builder variant of `mark_compromised`.
+    pub fn with_compromised(mut self, kid: &str, compromised_since: u64) -> Self {
+        self.mark_compromised(kid, compromised_since);
+        self
+    }
+
+    /// Time from which a key is compromised, if it is.
+    pub fn compromised_since(&self, kid: &str) -> Option<u64> {
+        self.compromised.get(kid).copied()
+    }
+
+    /// Whether no key is compromised.
+    pub fn is_empty(&self) -> bool {
+        self.compromised.is_empty()
+    }
+
+    /// Checks a credential issued under a key.
+    ///
+    /// # Arguments
+    /// * `kid` - ID of the key the credential is issued under.
+    /// * `issued_at` - Issuance time of the credential, if it carries one.
+    ///
+    /// # Returns
+    /// Returns the compromise if the credential is issued at or after the compromise of its key.
+    pub fn check(&self, kid: &str, issued_at: Option<u64>) -> Result<(), KeyCompromise> {
+        match (self.compromised_since(kid), issued_at) {
+            (Some(compromised_since), Some(issued_at)) if issued_at < compromised_since => { Ok(()) }
+            (Some(compromised_since), _) => { Err(KeyCompromise { kid: kid.to_string(), compromised_since, issued_at }) }
+            (None, _) => { Ok(()) }
+        }
+    }
+
+    /// Checks a VC issued under a key, reading its `iat`. The VC must be verified first, so that its `iat` is the one
+    /// signed by the issuer.
+    ///
+    /// # Arguments
+    /// * `kid` - ID of the key the VC is issued under.
+    /// * `vc` - Verifiable Credential, or credential embedded in a VP.
+    ///
+    /// # Returns
+    /// Returns a string containing an error, starting with `KEY_COMPROMISED_ERROR` if the VC is issued at or after the
+    /// compromise of its key, or without it if its `iat` is malformed.
+    pub fn check_credential(&self, kid: &str, vc: &Map<String, Value>) -> Result<(), String> {
+        let issued_at: Option<u64> = match vc.get(ISSUED_AT) {
+            None => { None }
+            Some(value) => match value.as_u64() {
+                Some(issued_at) => { Some(issued_at) }
+                None => { return Err(format!("Field {ISSUED_AT} is not a timestamp in seconds")) }
+            },
+        };
+        self.check(kid, issued_at).map_err(|compromise| compromise.to_string())
+    }
+
+    /// Serializes the registry to a JSON object mapping every compromised `kid` to the time of its compromise.
+    ///
+    /// # Returns
+    /// A result containing the JSON string or a string containing an error.
+    pub fn to_json(&self) -> Result<String, String> {
+        match serde_json::to_string(&self.compromised) {
+            Ok(json) => { Ok(json) }
+            Err(err) => { Err(format!("Failed to serialize compromise registry to JSON: [{err}]")) }
+        }
+    }
+
+    /// Deserializes a registry from JSON.
+    ///
+    /// # Arguments
+    /// * `json` - JSON string produced by `to_json`.
+    ///
+    /// # Returns
+    /// A result containing the registry or a string containing an error.
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        match serde_json::from_str::<BTreeMap<String, u64>>(json) {
+            Ok(compromised) => { Ok(CompromiseRegistry { compromised }) }
+            Err(err) => { Err(format!("Failed to parse compromise registry from JSON: [{err}]")) }
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Map, Value};
+    use crate::common_data::ISSUED_AT;
+    use crate::key_compromise::{CompromiseRegistry, KEY_COMPROMISED_ERROR};
+
+    fn credential(issued_at: Value) -> Map<String, Value> {
+        let mut vc: Map<String, Value> = Map::new();
+        vc.insert(ISSUED_AT.to_string(), issued_at);
+        vc
+    }
+
+    #[test]
+    fn check_credential_before_and_after_compromise() -> Result<(), String> {
+        let registry = CompromiseRegistry::new().with_compromised("key-1", 1_700_000_000);
+
+        registry.check_credential("key-1", &credential(json!(1_699_999_999)))?;
+
+        for issued_at in [1_700_000_000u64, 1_800_000_000] {
+            match registry.check_credential("key-1", &credential(json!(issued_at))) {
+                Ok(()) => { return Err(format!("Credential issued at {issued_at} was accepted")) }
+                Err(err) => { assert!(err.starts_with(KEY_COMPROMISED_ERROR), "{err}") }
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn check_credential_without_iat() -> Result<(), String> {
+        let registry = CompromiseRegistry::new().with_compromised("key-1", 1_700_000_000);
+
+        match registry.check_credential("key-1", &Map::new()) {
+            Ok(()) => { return Err("Credential without iat was accepted".to_string()) }
+            Err(err) => { assert!(err.starts_with(KEY_COMPROMISED_ERROR), "{err}") }
+        }
+
+        registry.check_credential("key-2", &Map::new())
+    }
+
+    #[test]
+    fn check_credential_with_malformed_iat() -> Result<(), String> {
+        let registry = CompromiseRegistry::new().with_compromised("key-1", 1_700_000_000);
+
+        for issued_at in [json!("1600000000"), json!(-1), json!(1.5e9)] {
+            match registry.check_credential("key-1", &credential(issued_at.clone())) {
+                Ok(()) => { return Err(format!("Credential issued at {issued_at} was accepted")) }
+                Err(err) => { assert!(!err.starts_with(KEY_COMPROMISED_ERROR), "{err}") }
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn check_credential_with_unknown_kid() -> Result<(), String> {
+        let registry = CompromiseRegistry::new().with_compromised("key-1", 1_700_000_000);
+
+        registry.check_credential("key-2", &credential(json!(1_800_000_000)))
+    }
+
+    #[test]
+    fn mark_compromised_keeps_earliest_time() {
+        let registry = CompromiseRegistry::new()
+            .with_compromised("key-1", 1_700_000_000)
+            .with_compromised("key-1", 1_800_000_000)
+            .with_compromised("key-1", 1_600_000_000);
+
+        assert_eq!(registry.compromised_since("key-1"), Some(1_600_000_000));
+        assert_eq!(registry.compromised_since("key-2"), None);
+    }
+}
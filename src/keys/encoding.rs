@@ -0,0 +1,275 @@
+use ark_bn254::Bn254;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use multibase::Base;
+use openssl::bn::BigNumContext;
+use openssl::ec::{EcGroup, EcKey, EcPoint, PointConversionForm};
+use openssl::nid::Nid;
+use openssl::pkey::PKey;
+use vb_accumulator::setup::PublicKey;
+use zkryptium::bbsplus::keys::BBSplusPublicKey;
+
+/// Prefix of the DIDs of the `did:key` method, followed by the multibase encoding of the key.
+pub const DID_KEY_PREFIX: &str = "did:key:";
+/// Maximum length in bytes of an unsigned varint, as bounded by the multiformats specification.
+const MAX_VARINT_LEN: usize = 9;
+
+/// Types of the public keys used by the algorithms, identified by their multicodec code.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KeyType {
+    /// ES256 key of the issuer of the hash-based algorithms and of the holders, as a compressed P-256 point.
+    P256,
+    /// BBS+ issuer key, as a compressed BLS12-381 G2 point.
+    Bls12381G2,
+    /// Accumulator issuer key of CSD-JWT, as a compressed BN254 G2 point.
+    Bn254G2,
+}
+
+impl KeyType {
+
+    /// Multicodec code prefixing the key bytes. BN254 keys have no registered code, so they use one from the range
+    /// reserved for private use.
+    pub fn multicodec(&self) -> u64 {
+        match self {
+            KeyType::P256 => { 0x1200 }
+            KeyType::Bls12381G2 => { 0xeb }
+            KeyType::Bn254G2 => { 0x300001 }
+        }
+    }
+
+    /// Length in bytes of the compressed encoding of a key.
+    pub fn key_len(&self) -> usize {
+        match self {
+            KeyType::P256 => { 33 }
+            KeyType::Bls12381G2 => { 96 }
+            KeyType::Bn254G2 => { 64 }
+        }
+    }
+
+    /// Retrieves the key type corresponding to a multicodec code.
+    ///
+    /// # Arguments
+    /// * `multicodec` - Multicodec code.
+    ///
+    /// # Returns
+    /// A result containing the key type or a string containing an error if the code is not supported.
+    pub fn from_multicodec(multicodec: u64) -> Result<Self, String> {
+        match [KeyType::P256, KeyType::Bls12381G2, KeyType::Bn254G2].into_iter().find(|key_type| key_type.multicodec() == multicodec) {
+            Some(key_type) => { Ok(key_type) }
+            None => { Err(format!("Unsupported multicodec key type 0x{multicodec:x}")) }
+        }
+    }
+}
+
+
+/// Encodes the compressed bytes of a public key as a multikey: the multicodec code of its type as an unsigned varint,
+/// followed by the key, in base58btc multibase (`z...`), as used by `did:key`.
+///
+/// # Arguments
+/// * `key_type` - Type of the key.
+/// * `key` - Compressed encoding of the key.
+///
+/// # Returns
+/// A result containing the multibase string or a string containing an error if the key is not of the expected length.
+pub fn encode_multikey(key_type: KeyType, key: &[u8]) -> Result<String, String> {
+    if key.len() != key_type.key_len() {
+        return Err(format!("{key_type:?} key is {} bytes long instead of {}", key.len(), key_type.key_len()));
+    }
+
+    let mut multikey: Vec<u8> = Vec::with_capacity(MAX_VARINT_LEN + key.len());
+    let mut multicodec: u64 = key_type.multicodec();
+    loop {
+        let byte: u8 = (multicodec & 0x7f) as u8;
+        multicodec >>= 7;
+        if multicodec == 0 {
+            multikey.push(byte);
+            break;
+        }
+        multikey.push(byte | 0x80);
+    }
+    multikey.extend_from_slice(key);
+
+    Ok(multibase::encode(Base::Base58Btc, multikey))
+}
+
+/// Decodes a multikey produced by `encode_multikey`.
+///
+/// # Arguments
+/// * `multikey` - Multibase string of the key, or the `did:key` DID holding it.
+///
+/// # Returns
+/// A result containing the type and the compressed encoding of the key or a string containing an error.
+pub fn decode_multikey(multikey: &str) -> Result<(KeyType, Vec<u8>), String> {
+    let multikey: &str = multikey.strip_prefix(DID_KEY_PREFIX).unwrap_or(multikey);
+    let (base, bytes) = match multibase::decode(multikey) {
+        Ok(decoded) => { decoded }
+        Err(err) => { return Err(format!("Failed to decode multikey: [{err}]")) }
+    };
+    if base != Base::Base58Btc {
+        return Err(format!("Multikey is encoded in {base:?} instead of base58btc"));
+    }
+
+    let mut multicodec: u64 = 0;
+    let mut varint_len: usize = 0;
+    loop {
+        let byte: u8 = match bytes.get(varint_len) {
+            Some(byte) if varint_len < MAX_VARINT_LEN => { *byte }
+            _ => { return Err("Multikey does not start with a valid multicodec varint".to_string()) }
+        };
+        multicodec |= u64::from(byte & 0x7f) << (7 * varint_len);
+        varint_len += 1;
+        if byte & 0x80 == 0 {
+            break;
+        }
+    }
+
+    let key_type: KeyType = KeyType::from_multicodec(multicodec)?;
+    let key: Vec<u8> = bytes[varint_len..].to_vec();
+    if key.len() != key_type.key_len() {
+        return Err(format!("{key_type:?} key is {} bytes long instead of {}", key.len(), key_type.key_len()));
+    }
+
+    Ok((key_type, key))
+}
+
+/// Builds the `did:key` DID of a multikey.
+///
+/// # Arguments
+/// * `multikey` - Multibase string of the key.
+///
+/// # Returns
+/// The DID.
+pub fn did_key(multikey: &str) -> String {
+    format!("{DID_KEY_PREFIX}{multikey}")
+}
+
+
+/// Encodes a PEM encoded ES256 public key as a multikey.
+///
+/// # Arguments
+/// * `public_key` - PEM encoding of the P-256 public key.
+///
+/// # Returns
+/// A result containing the multibase string or a string containing an error.
+///
+/// # Examples
+/// ```
+/// use csd_jwt::common_data::CommonData;
+/// use csd_jwt::keys::encoding::{decode_es256_public_key, did_key, encode_es256_public_key};
+///
+/// let (holder_public_key, _) = CommonData::holder_keys().unwrap();
+/// let multikey = encode_es256_public_key(&holder_public_key).unwrap();
+/// assert!(did_key(&multikey).starts_with("did:key:zDn"));
+///
+/// let decoded_public_key = decode_es256_public_key(&did_key(&multikey)).unwrap();
+/// assert_eq!(encode_es256_public_key(&decoded_public_key).unwrap(), multikey);
+/// ```
+pub fn encode_es256_public_key(public_key: &impl AsRef<[u8]>) -> Result<String, String> {
+    let group: EcGroup = p256_group()?;
+    let key = match PKey::public_key_from_pem(public_key.as_ref()).and_then(|key| key.ec_key()) {
+        Ok(key) => { key }
+        Err(err) => { return Err(format!("Failed to parse P-256 public key: [{err}]")) }
+    };
+    if key.group().curve_name() != Some(Nid::X9_62_PRIME256V1) {
+        return Err("Public key is not a P-256 key".to_string());
+    }
+
+    let compressed_key: Vec<u8> = match BigNumContext::new().and_then(|mut context| key.public_key().to_bytes(&group, PointConversionForm::COMPRESSED, &mut context)) {
+        Ok(compressed_key) => { compressed_key }
+        Err(err) => { return Err(format!("Failed to compress P-256 public key: [{err}]")) }
+    };
+
+    encode_multikey(KeyType::P256, &compressed_key)
+}
+
+/// Decodes an ES256 public key from its multikey.
+///
+/// # Arguments
+/// * `multikey` - Multibase string of the key, or the `did:key` DID holding it.
+///
+/// # Returns
+/// A result containing the PEM encoding of the public key or a string containing an error.
+pub fn decode_es256_public_key(multikey: &str) -> Result<Vec<u8>, String> {
+    let compressed_key: Vec<u8> = decode_multikey_of_type(multikey, KeyType::P256)?;
+    let group: EcGroup = p256_group()?;
+
+    let point: EcPoint = match BigNumContext::new().and_then(|mut context| EcPoint::from_bytes(&group, &compressed_key, &mut context)) {
+        Ok(point) => { point }
+        Err(err) => { return Err(format!("Failed to decompress P-256 public key: [{err}]")) }
+    };
+    match EcKey::from_public_key(&group, &point).and_then(|key| key.public_key_to_pem()) {
+        Ok(public_key) => { Ok(public_key) }
+        Err(err) => { Err(format!("Failed to encode P-256 public key: [{err}]")) }
+    }
+}
+
+/// Encodes a BBS+ issuer public key as a multikey.
+///
+/// # Arguments
+/// * `public_key` - BBS+ public key.
+///
+/// # Returns
+/// A result containing the multibase string or a string containing an error.
+pub fn encode_bbs_public_key(public_key: &BBSplusPublicKey) -> Result<String, String> {
+    encode_multikey(KeyType::Bls12381G2, &public_key.to_bytes())
+}
+
+/// Decodes a BBS+ issuer public key from its multikey.
+///
+/// # Arguments
+/// * `multikey` - Multibase string of the key, or the `did:key` DID holding it.
+///
+/// # Returns
+/// A result containing the public key or a string containing an error.
+pub fn decode_bbs_public_key(multikey: &str) -> Result<BBSplusPublicKey, String> {
+    match BBSplusPublicKey::from_bytes(&decode_multikey_of_type(multikey, KeyType::Bls12381G2)?) {
+        Ok(public_key) => { Ok(public_key) }
+        Err(err) => { Err(format!("Failed to deserialize BBS+ public key: [{err}]")) }
+    }
+}
+
+/// Encodes a CSD-JWT accumulator public key as a multikey.
+///
+/// # Arguments
+/// * `public_key` - Accumulator public key.
+///
+/// # Returns
+/// A result containing the multibase string or a string containing an error.
+pub fn encode_accumulator_public_key(public_key: &PublicKey<Bn254>) -> Result<String, String> {
+    let mut compressed_key: Vec<u8> = Vec::new();
+    match public_key.serialize_compressed(&mut compressed_key) {
+        Ok(()) => { encode_multikey(KeyType::Bn254G2, &compressed_key) }
+        Err(err) => { Err(format!("Failed to serialize accumulator public key: [{err}]")) }
+    }
+}
+
+/// Decodes a CSD-JWT accumulator public key from its multikey.
+///
+/// # Arguments
+/// * `multikey` - Multibase string of the key, or the `did:key` DID holding it.
+///
+/// # Returns
+/// A result containing the public key or a string containing an error.
+pub fn decode_accumulator_public_key(multikey: &str) -> Result<PublicKey<Bn254>, String> {
+    let compressed_key: Vec<u8> = decode_multikey_of_type(multikey, KeyType::Bn254G2)?;
+    match PublicKey::<Bn254>::deserialize_compressed(compressed_key.as_slice()) {
+        Ok(public_key) => { Ok(public_key) }
+        Err(err) => { Err(format!("Failed to deserialize accumulator public key: [{err}]")) }
+    }
+}
+
+
+/// Decodes a multikey and checks that it holds a key of the expected type.
+fn decode_multikey_of_type(multikey: &str, expected_type: KeyType) -> Result<Vec<u8>, String> {
+    match decode_multikey(multikey)? {
+        (key_type, key) if key_type == expected_type => { Ok(key) }
+        (key_type, _) => { Err(format!("Multikey holds a {key_type:?} key instead of a {expected_type:?} key")) }
+    }
+}
+
+/// Group of the P-256 curve.
+fn p256_group() -> Result<EcGroup, String> {
+    match EcGroup::from_curve_name(Nid::X9_62_PRIME256V1) {
+        Ok(group) => { Ok(group) }
+        Err(err) => { Err(format!("Failed to load the P-256 group: [{err}]")) }
+    }
+}
@@ -0,0 +1,475 @@
+use ark_bls12_381::Bls12_381;
+use ark_bn254::Bn254;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use openssl::bn::{BigNum, BigNumContext};
+use openssl::ec::{EcGroup, EcKey};
+use openssl::nid::Nid;
+use openssl::pkey::PKey;
+use serde_json::{Map, Value};
+use vb_accumulator::setup::{PublicKey, SetupParams};
+use zkryptium::bbsplus::keys::BBSplusPublicKey;
+use crate::adapters::adapter::Adapter;
+use crate::encoding::Encoding;
+use crate::hashing::sha256;
+use crate::key_cache::KeyResolver;
+use crate::keys::encoding::{encode_es256_public_key, encode_multikey, KeyType};
+use crate::transport::http::http_get;
+use crate::verifier_bundle::VerifierBundle;
+
+/// Path the JWKS of an issuer is published at.
+pub const JWKS_PATH: &str = "/.well-known/jwks.json";
+/// Member of the JWKS holding the keys.
+pub const KEYS: &str = "keys";
+/// Member of a JWK holding its key ID.
+pub const KID: &str = "kid";
+/// Non-standard member of a JWK naming the selective disclosure algorithm the key verifies.
+pub const SD_ALGORITHM: &str = "sd_algorithm";
+/// Non-standard member of an accumulator JWK holding the compressed setup parameters of the accumulator.
+pub const PARAMS: &str = "params";
+
+/// Curve of the BBS+ issuer keys, as in the JOSE representation of BLS keys.
+const BLS12381G2: &str = "BLS12381G2";
+/// Non-standard curve of the accumulator issuer keys of CSD-JWT.
+const BN254G2: &str = "BN254G2";
+/// Length in bytes of a P-256 coordinate.
+const P256_COORDINATE_LEN: i32 = 32;
+
+
+/// Builds the JWK of an ES256 public key, as used by the issuers of SD-JWT and Merkle Tree VCs.
+///
+/// # Arguments
+/// * `public_key` - PEM encoding of the P-256 public key.
+/// * `sd_algorithm` - Name of the algorithm the key verifies.
+///
+/// # Returns
+/// A result containing the JWK or a string containing an error.
+pub fn es256_jwk(public_key: &impl AsRef<[u8]>, sd_algorithm: &str) -> Result<Map<String, Value>, String> {
+    let group: EcGroup = p256_group()?;
+    let key = match PKey::public_key_from_pem(public_key.as_ref()).and_then(|key| key.ec_key()) {
+        Ok(key) => { key }
+        Err(err) => { return Err(format!("Failed to parse P-256 public key: [{err}]")) }
+    };
+
+    let coordinates = BigNumContext::new().and_then(|mut context| {
+        let mut x: BigNum = BigNum::new()?;
+        let mut y: BigNum = BigNum::new()?;
+        key.public_key().affine_coordinates_gfp(&group, &mut x, &mut y, &mut context)?;
+        Ok((x.to_vec_padded(P256_COORDINATE_LEN)?, y.to_vec_padded(P256_COORDINATE_LEN)?))
+    });
+    let (x, y): (Vec<u8>, Vec<u8>) = match coordinates {
+        Ok(coordinates) => { coordinates }
+        Err(err) => { return Err(format!("Failed to retrieve the coordinates of the P-256 public key: [{err}]")) }
+    };
+
+    let mut jwk: Map<String, Value> = Map::new();
+    jwk.insert("kty".to_string(), Value::String("EC".to_string()));
+    jwk.insert("crv".to_string(), Value::String("P-256".to_string()));
+    jwk.insert("x".to_string(), Value::String(Encoding::Base64UrlNoPad.encode(x)));
+    jwk.insert("y".to_string(), Value::String(Encoding::Base64UrlNoPad.encode(y)));
+    jwk.insert("alg".to_string(), Value::String("ES256".to_string()));
+    Ok(with_metadata(jwk, sd_algorithm))
+}
+
+/// Builds the JWK of a BBS+ issuer public key, as an `OKP` key on the `BLS12381G2` curve.
+///
+/// # Arguments
+/// * `public_key` - BBS+ public key.
+/// * `sd_algorithm` - Name of the algorithm the key verifies.
+///
+/// # Returns
+/// A result containing the JWK or a string containing an error.
+pub fn bbs_jwk(public_key: &BBSplusPublicKey, sd_algorithm: &str) -> Result<Map<String, Value>, String> {
+    let mut jwk: Map<String, Value> = okp_jwk(BLS12381G2, &public_key.to_bytes());
+    jwk.insert("alg".to_string(), Value::String("BBS".to_string()));
+    Ok(with_metadata(jwk, sd_algorithm))
+}
+
+/// Builds the JWK of a CSD-JWT accumulator public key, as an `OKP` key on the non-standard `BN254G2` curve, carrying
+/// the setup parameters of the accumulator in the non-standard `params` member so that the JWK alone suffices to verify.
+///
+/// # Arguments
+/// * `public_key` - Accumulator public key.
+/// * `params` - Setup parameters of the accumulator.
+/// * `sd_algorithm` - Name of the algorithm the key verifies.
+///
+/// # Returns
+/// A result containing the JWK or a string containing an error.
+pub fn accumulator_jwk(public_key: &PublicKey<Bn254>, params: &SetupParams<Bn254>, sd_algorithm: &str) -> Result<Map<String, Value>, String> {
+    let mut jwk: Map<String, Value> = okp_jwk(BN254G2, &compress(public_key)?);
+    jwk.insert("alg".to_string(), Value::String(sd_algorithm.to_string()));
+    jwk.insert(PARAMS.to_string(), Value::String(Encoding::Base64UrlNoPad.encode(compress(params)?)));
+    Ok(with_metadata(jwk, sd_algorithm))
+}
+
+/// Builds the JWK of a CSD-JWT accumulator public key over BLS12-381, as an `OKP` key on the `BLS12381G2` curve of the
+/// BBS+ keys, carrying the setup parameters of the accumulator in the non-standard `params` member like
+/// `accumulator_jwk`.
+///
+/// # Arguments
+/// * `public_key` - Accumulator public key.
+/// * `params` - Setup parameters of the accumulator.
+/// * `sd_algorithm` - Name of the algorithm the key verifies.
+///
+/// # Returns
+/// A result containing the JWK or a string containing an error.
+pub fn bls12_381_accumulator_jwk(public_key: &PublicKey<Bls12_381>, params: &SetupParams<Bls12_381>, sd_algorithm: &str) -> Result<Map<String, Value>, String> {
+    let mut jwk: Map<String, Value> = okp_jwk(BLS12381G2, &compress(public_key)?);
+    jwk.insert("alg".to_string(), Value::String(sd_algorithm.to_string()));
+    jwk.insert(PARAMS.to_string(), Value::String(Encoding::Base64UrlNoPad.encode(compress(params)?)));
+    Ok(with_metadata(jwk, sd_algorithm))
+}
+
+
+/// Computes the RFC 7638 thumbprint of a JWK, over its `kty`, `crv`, `x` and, for EC keys, `y` members.
+///
+/// # Arguments
+/// * `jwk` - JWK.
+///
+/// # Returns
+/// A result containing the base64url encoded SHA-256 thumbprint or a string containing an error.
+pub fn jwk_kid(jwk: &Map<String, Value>) -> Result<String, String> {
+    let kty: &str = string_member(jwk, "kty")?;
+    let crv: &str = string_member(jwk, "crv")?;
+    let x: &str = string_member(jwk, "x")?;
+
+    // Required members in lexicographic order and without whitespace, as mandated by RFC 7638.
+    let canonical_jwk: String = match kty {
+        "EC" => { format!(r#"{{"crv":"{crv}","kty":"EC","x":"{x}","y":"{}"}}"#, string_member(jwk, "y")?) }
+        "OKP" => { format!(r#"{{"crv":"{crv}","kty":"OKP","x":"{x}"}}"#) }
+        _ => { return Err(format!("Unsupported key type {kty}")) }
+    };
+
+    Ok(Encoding::Base64UrlNoPad.encode(sha256(canonical_jwk)))
+}
+
+/// Recovers the PEM encoding of an ES256 public key from its JWK.
+///
+/// # Arguments
+/// * `jwk` - JWK produced by `es256_jwk`.
+///
+/// # Returns
+/// A result containing the PEM encoding or a string containing an error.
+pub fn es256_public_key(jwk: &Map<String, Value>) -> Result<Vec<u8>, String> {
+    if string_member(jwk, "kty")? != "EC" || string_member(jwk, "crv")? != "P-256" {
+        return Err("JWK is not a P-256 key".to_string());
+    }
+
+    let group: EcGroup = p256_group()?;
+    let x: Vec<u8> = decode_member(jwk, "x")?;
+    let y: Vec<u8> = decode_member(jwk, "y")?;
+    let key = BigNum::from_slice(&x)
+        .and_then(|x| BigNum::from_slice(&y).map(|y| (x, y)))
+        .and_then(|(x, y)| EcKey::from_public_key_affine_coordinates(&group, &x, &y))
+        .and_then(|key| key.public_key_to_pem());
+    match key {
+        Ok(public_key) => { Ok(public_key) }
+        Err(err) => { Err(format!("Failed to rebuild P-256 public key from JWK: [{err}]")) }
+    }
+}
+
+/// Converts the key of a JWK to its multikey, the encoding used by `did:key` and resolved by `JwksKeyResolver`.
+///
+/// # Arguments
+/// * `jwk` - JWK of an ES256, BBS+ or CSD-JWT issuer key.
+///
+/// # Returns
+/// A result containing the multibase string or a string containing an error.
+pub fn jwk_multikey(jwk: &Map<String, Value>) -> Result<String, String> {
+    match (string_member(jwk, "kty")?, string_member(jwk, "crv")?) {
+        ("EC", _) => { encode_es256_public_key(&es256_public_key(jwk)?) }
+        ("OKP", BLS12381G2) => { encode_multikey(KeyType::Bls12381G2, &decode_member(jwk, "x")?) }
+        ("OKP", BN254G2) => { encode_multikey(KeyType::Bn254G2, &decode_member(jwk, "x")?) }
+        (kty, crv) => { Err(format!("Unsupported {kty} key on curve {crv}")) }
+    }
+}
+
+/// Recovers the verifier bundle of a CSD-JWT issuer from the JWK of its accumulator key.
+///
+/// # Arguments
+/// * `jwk` - JWK produced by `accumulator_jwk`.
+///
+/// # Returns
+/// A result containing the bundle or a string containing an error.
+pub fn jwk_verifier_bundle(jwk: &Map<String, Value>) -> Result<VerifierBundle, String> {
+    if string_member(jwk, "crv")? != BN254G2 {
+        return Err(format!("JWK is not a {BN254G2} key"));
+    }
+
+    let issuer_public_key: PublicKey<Bn254> = decompress(&decode_member(jwk, "x")?)?;
+    let params: SetupParams<Bn254> = decompress(&decode_member(jwk, PARAMS)?)?;
+    Ok(VerifierBundle::new(params, issuer_public_key))
+}
+
+
+/// JWK Set publishing the verification keys of the issuers of every algorithm, each identified by the RFC 7638
+/// thumbprint of the key as its `kid`, so that verifiers can select the key of a credential by its ID. Keys without a
+/// standard JOSE representation use non-standard curves and members, see `accumulator_jwk`.
+///
+/// # Examples
+/// ```
+/// use csd_jwt::adapters::adapter::Adapter;
+/// use csd_jwt::adapters::accumulators::csd_jwt_adapter::CsdJwtAdapter;
+/// use csd_jwt::adapters::hashes::sd_jwt_adapter::SdJwtAdapter;
+/// use csd_jwt::keys::jwks::{jwk_verifier_bundle, Jwks, KID};
+///
+/// let sd_jwt = SdJwtAdapter::new(8).unwrap();
+/// let csd_jwt = CsdJwtAdapter::new(8).unwrap();
+/// let jwks = Jwks::from_adapters([&sd_jwt as &dyn Adapter, &csd_jwt as &dyn Adapter]).unwrap();
+///
+/// let published = Jwks::from_json(&jwks.to_json().unwrap()).unwrap();
+/// let kid = csd_jwt.issuer_jwk().unwrap()[KID].as_str().unwrap().to_string();
+/// let bundle = jwk_verifier_bundle(published.find(&kid).unwrap()).unwrap();
+/// assert_eq!(bundle.issuer_public_key, csd_jwt.verifier_bundle().unwrap().issuer_public_key);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Jwks {
+    /// JWKs of the keys, each with its `kid`.
+    keys: Vec<Map<String, Value>>,
+}
+
+impl Jwks {
+
+    /// Constructor for an empty JWK Set.
+    pub fn new() -> Self {
+        Jwks::default()
+    }
+
+    /// Builds the JWK Set of the issuer keys of several adapters.
+    ///
+    /// # Arguments
+    /// * `adapters` - Adapters whose issuer keys are published.
+    ///
+    /// # Returns
+    /// A result containing the JWK Set or a string containing an error if an adapter cannot publish its key.
+    pub fn from_adapters<'a>(adapters: impl IntoIterator<Item = &'a dyn Adapter>) -> Result<Self, String> {
+        let mut jwks: Jwks = Jwks::new();
+        for adapter in adapters {
+            jwks.add(adapter.issuer_jwk()?)?;
+        }
+        Ok(jwks)
+    }
+
+    /// Adds a JWK to the set, with its thumbprint as `kid` if it has none.
+    ///
+    /// # Arguments
+    /// * `jwk` - JWK to be added.
+    ///
+    /// # Returns
+    /// A result containing a string in case the JWK is malformed or its `kid` is already in the set.
+    pub fn add(&mut self, mut jwk: Map<String, Value>) -> Result<(), String> {
+        if !jwk.contains_key(KID) {
+            jwk.insert(KID.to_string(), Value::String(jwk_kid(&jwk)?));
+        }
+        let kid: &str = string_member(&jwk, KID)?;
+        if self.find(kid).is_some() {
+            return Err(format!("Key {kid} is already in the JWK Set"));
+        }
+
+        self.keys.push(jwk);
+        Ok(())
+    }
+
+    /// JWKs of the set.
+    pub fn keys(&self) -> &[Map<String, Value>] {
+        &self.keys
+    }
+
+    /// Retrieves a JWK by its `kid`.
+    pub fn find(&self, kid: &str) -> Option<&Map<String, Value>> {
+        self.keys.iter().find(|jwk| jwk.get(KID).and_then(|value| value.as_str()) == Some(kid))
+    }
+
+    /// Converts the set to the JSON object published at `JWKS_PATH`.
+    pub fn to_value(&self) -> Value {
+        let mut jwks: Map<String, Value> = Map::new();
+        jwks.insert(KEYS.to_string(), Value::Array(self.keys.iter().cloned().map(Value::Object).collect()));
+        Value::Object(jwks)
+    }
+
+    /// Serializes the set to JSON.
+    ///
+    /// # Returns
+    /// A result containing the JSON string or a string containing an error.
+    pub fn to_json(&self) -> Result<String, String> {
+        match serde_json::to_string(&self.to_value()) {
+            Ok(json) => { Ok(json) }
+            Err(err) => { Err(format!("Failed to serialize JWK Set to JSON: [{err}]")) }
+        }
+    }
+
+    /// Deserializes a set from JSON, ignoring the keys that are not JSON objects.
+    ///
+    /// # Arguments
+    /// * `json` - JSON string of a JWK Set.
+    ///
+    /// # Returns
+    /// A result containing the set or a string containing an error.
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        let jwks: Map<String, Value> = match serde_json::from_str::<Map<String, Value>>(json) {
+            Ok(jwks) => { jwks }
+            Err(err) => { return Err(format!("Failed to parse JWK Set from JSON: [{err}]")) }
+        };
+
+        match jwks.get(KEYS) {
+            Some(Value::Array(keys)) => {
+                Ok(Jwks { keys: keys.iter().filter_map(|jwk| jwk.as_object().cloned()).collect() })
+            }
+            _ => { Err(format!("JWK Set does not contain {KEYS}")) }
+        }
+    }
+}
+
+
+/// Verifier-side fetcher of the JWKS of an issuer over HTTP, resolving a `kid` to the multikey of the key. Every
+/// resolution fetches the JWKS again, so verifiers put a `KeyCache` in front of it.
+///
+/// # Examples
+/// ```
+/// use std::io::{Read, Write};
+/// use std::net::TcpListener;
+/// use std::thread;
+/// use std::time::Duration;
+/// use csd_jwt::adapters::adapter::Adapter;
+/// use csd_jwt::adapters::hashes::sd_jwt_adapter::SdJwtAdapter;
+/// use csd_jwt::key_cache::{KeyCache, KeyResolver, DEFAULT_KEY_CACHE_ENTRIES};
+/// use csd_jwt::keys::jwks::{jwk_multikey, Jwks, JwksKeyResolver, KID};
+///
+/// let adapter = SdJwtAdapter::new(4).unwrap();
+/// let jwks = Jwks::from_adapters([&adapter as &dyn Adapter]).unwrap().to_json().unwrap();
+///
+/// // Serves the JWKS once, later resolutions being answered by the cache.
+/// let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+/// let url = format!("http://{}/.well-known/jwks.json", listener.local_addr().unwrap());
+/// thread::spawn(move || {
+///     let (mut stream, _) = listener.accept().unwrap();
+///     let mut request = [0u8; 1024];
+///     stream.read(&mut request).unwrap();
+///     write!(stream, "HTTP/1.1 200 OK\r\nContent-Length: {}\r\n\r\n{jwks}", jwks.len()).unwrap();
+/// });
+///
+/// let resolver = KeyCache::new(JwksKeyResolver::new(&url), Duration::from_secs(60), DEFAULT_KEY_CACHE_ENTRIES);
+/// let issuer_jwk = adapter.issuer_jwk().unwrap();
+/// let kid = issuer_jwk[KID].as_str().unwrap();
+/// let multikey = resolver.resolve(kid).unwrap();
+/// assert_eq!(multikey, jwk_multikey(&issuer_jwk).unwrap());
+/// assert_eq!(resolver.resolve(kid).unwrap(), multikey);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct JwksKeyResolver {
+    /// URL of the JWKS.
+    url: String,
+}
+
+impl JwksKeyResolver {
+
+    /// Constructor for a fetcher of the JWKS at a URL.
+    ///
+    /// # Arguments
+    /// * `url` - `http://` URL of the JWKS.
+    pub fn new(url: &str) -> Self {
+        JwksKeyResolver { url: url.to_string() }
+    }
+
+    /// Fetches the JWKS.
+    ///
+    /// # Returns
+    /// A result containing the JWK Set or a string containing an error.
+    pub fn fetch(&self) -> Result<Jwks, String> {
+        Jwks::from_json(&http_get(&self.url)?)
+    }
+}
+
+impl KeyResolver for JwksKeyResolver {
+    fn resolve(&self, issuer: &str) -> Result<String, String> {
+        match self.fetch()?.find(issuer) {
+            Some(jwk) => { jwk_multikey(jwk) }
+            None => { Err(format!("Key {issuer} is not published in the JWKS at {}", self.url)) }
+        }
+    }
+}
+
+
+/// Builds an `OKP` JWK holding a compressed point.
+fn okp_jwk(crv: &str, x: &[u8]) -> Map<String, Value> {
+    let mut jwk: Map<String, Value> = Map::new();
+    jwk.insert("kty".to_string(), Value::String("OKP".to_string()));
+    jwk.insert("crv".to_string(), Value::String(crv.to_string()));
+    jwk.insert("x".to_string(), Value::String(Encoding::Base64UrlNoPad.encode(x)));
+    jwk
+}
+
+/// Adds the intended use, the algorithm and the `kid` to a JWK.
+fn with_metadata(mut jwk: Map<String, Value>, sd_algorithm: &str) -> Map<String, Value> {
+    jwk.insert("use".to_string(), Value::String("sig".to_string()));
+    jwk.insert(SD_ALGORITHM.to_string(), Value::String(sd_algorithm.to_string()));
+    if let Ok(kid) = jwk_kid(&jwk) {
+        jwk.insert(KID.to_string(), Value::String(kid));
+    }
+    jwk
+}
+
+/// Retrieves a string member of a JWK.
+fn string_member<'a>(jwk: &'a Map<String, Value>, member: &str) -> Result<&'a str, String> {
+    match jwk.get(member) {
+        Some(Value::String(value)) => { Ok(value) }
+        Some(_) => { Err(format!("Member {member} of the JWK must be a string")) }
+        None => { Err(format!("JWK does not contain {member}")) }
+    }
+}
+
+/// Decodes a base64url member of a JWK.
+fn decode_member(jwk: &Map<String, Value>, member: &str) -> Result<Vec<u8>, String> {
+    match Encoding::Base64UrlNoPad.decode(string_member(jwk, member)?) {
+        Ok(bytes) => { Ok(bytes) }
+        Err(err) => { Err(format!("Failed to decode member {member} of the JWK: [{err}]")) }
+    }
+}
+
+/// Compressed serialization of a curve element.
+fn compress<S: CanonicalSerialize>(element: &S) -> Result<Vec<u8>, String> {
+    let mut compressed_bytes: Vec<u8> = Vec::new();
+    match element.serialize_compressed(&mut compressed_bytes) {
+        Ok(()) => { Ok(compressed_bytes) }
+        Err(err) => { Err(format!("Failed to serialize element: [{err}]")) }
+    }
+}
+
+/// Deserializes a curve element from its compressed serialization.
+fn decompress<D: CanonicalDeserialize>(compressed_bytes: &[u8]) -> Result<D, String> {
+    match D::deserialize_compressed(compressed_bytes) {
+        Ok(element) => { Ok(element) }
+        Err(err) => { Err(format!("Failed to deserialize element: [{err}]")) }
+    }
+}
+
+/// Group of the P-256 curve.
+fn p256_group() -> Result<EcGroup, String> {
+    match EcGroup::from_curve_name(Nid::X9_62_PRIME256V1) {
+        Ok(group) => { Ok(group) }
+        Err(err) => { Err(format!("Failed to load the P-256 group: [{err}]")) }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::common_data::CommonData;
+    use crate::keys::encoding::encode_es256_public_key;
+    use crate::keys::jwks::{es256_jwk, es256_public_key, jwk_multikey, Jwks, KID};
+
+    #[test]
+    fn es256_jwk_round_trip() -> Result<(), String> {
+        let (issuer_public_key, _) = CommonData::issuer_keys()?;
+        let jwk = es256_jwk(&issuer_public_key, "SD-JWT")?;
+        assert_eq!(es256_jwk(&es256_public_key(&jwk)?, "SD-JWT")?, jwk);
+        assert_eq!(jwk_multikey(&jwk)?, encode_es256_public_key(&issuer_public_key)?);
+
+        let mut jwks = Jwks::new();
+        jwks.add(jwk.clone())?;
+        assert!(jwks.add(jwk.clone()).is_err(), "The same key was published twice.");
+        let published = Jwks::from_json(&jwks.to_json()?)?;
+        assert_eq!(published.find(jwk[KID].as_str().unwrap_or_default()), Some(&jwk));
+
+        Ok(())
+    }
+}
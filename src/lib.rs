@@ -2,6 +2,76 @@ pub mod common_data;
 pub mod adapters;
 pub mod sd_algorithms;
 pub mod csv_writer;
+pub mod result_sink;
+pub mod results_db;
+pub mod regression;
+pub mod result_format;
 pub mod benchmark;
+pub mod chart;
+pub mod cost_profile;
+pub mod envelope_profile;
+pub mod encoding;
+pub mod json;
+pub mod hashing;
+pub mod primitives;
+pub mod deterministic;
+pub mod entropy;
+pub mod clock;
+pub mod keys;
+pub mod compression;
+pub mod binary_envelope;
+pub mod verifier_config;
+pub mod key_cache;
+pub mod key_compromise;
+pub mod issuer_config;
+pub mod issuance_log;
+pub mod claim_metadata;
+pub mod typed_claims;
+pub mod claim_groups;
+pub mod claim_minimization;
+pub mod metadata_disclosure;
+pub mod disclosure;
+pub mod disclosure_policy;
+pub mod disclosure_menu;
+pub mod inspect;
+pub mod audit;
+pub mod claims_diff;
+pub mod linkability;
+pub mod holder_binding;
+pub mod claim_generator;
+pub mod dataset;
+pub mod credential_ir;
+pub mod convert;
+pub mod conformance;
+pub mod scaling_model;
+pub mod size_estimate;
+pub mod device_profile;
+pub mod protocol;
+pub mod throughput;
+pub mod robustness;
+pub mod transfer_model;
+pub mod transport;
+pub mod constraints;
+pub mod roles;
+pub mod issuer;
+pub mod holder;
+pub mod presentation_cache;
+pub mod presentation_validity;
+pub mod verifier;
+pub mod presentation_stream;
+pub mod quorum;
+pub mod verifier_bundle;
+pub mod derived_claims;
+pub mod transformations;
+pub mod pseudonym;
+pub mod ephemeral_binding;
+#[cfg(feature = "server")]
+pub mod server;
+#[cfg(feature = "server")]
+pub mod daemon;
+#[cfg(feature = "mobile")]
+pub mod mobile;
+#[cfg(feature = "mobile")]
+uniffi::setup_scaffolding!();
 
 pub mod display;
@@ -0,0 +1,346 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use serde_json::{Map, Value};
+use crate::adapters::adapter::Adapter;
+use crate::compression::decode_payload;
+use crate::csv_writer::CSVWriter;
+use crate::encoding::Encoding;
+use crate::sd_algorithms::sd_algorithm::ENCODING_HEADER;
+use crate::verifier_config::VerifierConfig;
+
+/// Default amount of presentations made to every simulated verifier.
+pub const DEFAULT_PRESENTATIONS: usize = 4;
+/// Audiences of the two colluding verifiers.
+pub const VERIFIER_AUDIENCES: [&str; 2] = ["https://verifier-a.example", "https://verifier-b.example"];
+/// Field holding the bytes of the JWS signature of a presentation.
+pub const JWS_SIGNATURE: &str = "jws_signature";
+
+/// Values found in a presentation, by field. Array indices are dropped from the paths of the fields, so that values
+/// are compared regardless of the order a holder presents them in.
+pub type PresentationFields = BTreeMap<String, BTreeSet<String>>;
+
+
+/// Extent to which a field lets two colluding verifiers link the presentations of the same credential.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Linkage {
+    /// Every presentation to a verifier shares a value of the field with every presentation to the other.
+    Linkable,
+    /// Some presentations to a verifier share a value of the field with some presentations to the other.
+    Partial,
+    /// No value of the field is shared across verifiers.
+    Unlinkable,
+    /// The shared values are also found in the presentations of another credential with the same claims, e.g. the
+    /// type of the JWT or the disclosed claims themselves, so they do not single out the credential.
+    Constant,
+}
+
+impl fmt::Display for Linkage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Linkage::Linkable => { write!(f, "linkable") }
+            Linkage::Partial => { write!(f, "partial") }
+            Linkage::Unlinkable => { write!(f, "unlinkable") }
+            Linkage::Constant => { write!(f, "constant") }
+        }
+    }
+}
+
+
+/// Collects every value a verifier can read from a VP JWT: the fields of its header and payload and of the components
+/// decoded with the encoding declared in the header, down to their leaves, and the bytes of its JWS signature.
+///
+/// # Arguments
+/// * `vp_jwt` - Encoded VP.
+///
+/// # Returns
+/// A result containing the values by field or a string containing an error.
+pub fn presentation_fields(vp_jwt: &str) -> Result<PresentationFields, String> {
+    let parts: Vec<&str> = vp_jwt.trim().split('.').collect();
+    if parts.len() != 3 {
+        return Err(format!("JWT has {} parts instead of 3", parts.len()));
+    }
+
+    let header: Map<String, Value> = match Encoding::Base64UrlNoPad.decode(parts[0]).map(|bytes| serde_json::from_slice::<Map<String, Value>>(&bytes)) {
+        Ok(Ok(header)) => { header }
+        Ok(Err(err)) => { return Err(format!("Failed to parse header: [{err}]")) }
+        Err(err) => { return Err(format!("Failed to decode header: [{err}]")) }
+    };
+    let payload: Map<String, Value> = decode_payload(&header, parts[1], VerifierConfig::DEFAULT.max_jwt_length)?;
+    let encoding: Encoding = match header.get(ENCODING_HEADER) {
+        Some(Value::String(name)) => { Encoding::from_name(name)? }
+        _ => { Encoding::default() }
+    };
+
+    let mut fields: PresentationFields = BTreeMap::new();
+    collect_leaves("header", &Value::Object(header), encoding, &mut fields);
+    collect_leaves("payload", &Value::Object(payload), encoding, &mut fields);
+    fields.entry(JWS_SIGNATURE.to_string()).or_default().insert(parts[2].to_string());
+
+    Ok(fields)
+}
+
+/// Collects the leaves of a JSON value, descending into the strings that decode to a JSON object or array.
+fn collect_leaves(path: &str, value: &Value, encoding: Encoding, fields: &mut PresentationFields) {
+    match value {
+        Value::Object(map) => {
+            for (name, member) in map {
+                collect_leaves(&format!("{path}.{name}"), member, encoding, fields);
+            }
+        }
+        Value::Array(array) => {
+            for element in array {
+                collect_leaves(&format!("{path}[]"), element, encoding, fields);
+            }
+        }
+        Value::String(encoded) => {
+            match encoding.decode(encoded).ok().and_then(|bytes| serde_json::from_slice::<Value>(&bytes).ok()) {
+                Some(decoded) if decoded.is_object() || decoded.is_array() => { collect_leaves(path, &decoded, encoding, fields) }
+                _ => { fields.entry(path.to_string()).or_default().insert(encoded.clone()); }
+            }
+        }
+        _ => { fields.entry(path.to_string()).or_default().insert(value.to_string()); }
+    }
+}
+
+
+/// Linkage of every field of the presentations of an algorithm, as observed by two colluding verifiers comparing the
+/// presentations of the same credential they received. A second credential with the same claims, issued to the same
+/// holder, serves as control: values it shares, including the binding to the holder key, are reported as constant.
+///
+/// # Examples
+/// ```
+/// use serde_json::{Map, Value};
+/// use csd_jwt::adapters::adapter::Adapter;
+/// use csd_jwt::adapters::hashes::sd_jwt_adapter::SdJwtAdapter;
+/// use csd_jwt::common_data::VC;
+/// use csd_jwt::linkability::{Linkage, LinkageReport, DEFAULT_PRESENTATIONS};
+///
+/// let adapter = SdJwtAdapter::new(13).unwrap();
+/// let raw_vc: Map<String, Value> = serde_json::from_str(VC).unwrap();
+/// let report = LinkageReport::analyze(&adapter, &raw_vc, &["name".to_string()], DEFAULT_PRESENTATIONS).unwrap();
+/// // The issuer signature of the SD-JWT hashes is the same in every presentation of the credential.
+/// assert_eq!(report.fields.get("payload.vp.verifiableCredential[].signature[]"), Some(&Linkage::Linkable));
+/// assert_eq!(report.fields.get("jws_signature"), Some(&Linkage::Unlinkable));
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LinkageReport {
+    /// Name of the algorithm.
+    pub algorithm: String,
+    /// Linkage of every field.
+    pub fields: BTreeMap<String, Linkage>,
+}
+
+impl LinkageReport {
+
+    /// Issues a credential and its control, presents the credential several times to each of the two verifiers of
+    /// `VERIFIER_AUDIENCES` and the control once, and classifies every field of the presentations.
+    ///
+    /// # Arguments
+    /// * `adapter` - Adapter of the algorithm.
+    /// * `raw_vc` - Raw VC to be issued.
+    /// * `disclosures` - Claims disclosed in every presentation.
+    /// * `n_presentations` - Amount of presentations made to every verifier.
+    ///
+    /// # Returns
+    /// A result containing the report or a string containing an error.
+    pub fn analyze(adapter: &dyn Adapter, raw_vc: &Map<String, Value>, disclosures: &[String], n_presentations: usize) -> Result<Self, String> {
+        let disclosures: Vec<String> = disclosures.to_vec();
+        let (vc, _vc_jwt) = adapter.issue_vc(raw_vc)?;
+        let (control_vc, _control_vc_jwt) = adapter.issue_vc(raw_vc)?;
+
+        let mut presentations: [Vec<PresentationFields>; 2] = [vec![], vec![]];
+        for (audience, received) in VERIFIER_AUDIENCES.iter().zip(presentations.iter_mut()) {
+            for _ in 0..n_presentations {
+                let (_vp, vp_jwt) = adapter.issue_vp_for_audience(&vc, &disclosures, Some(audience.to_string()), None)?;
+                received.push(presentation_fields(&vp_jwt)?);
+            }
+        }
+        let (_control_vp, control_vp_jwt) = adapter.issue_vp_for_audience(&control_vc, &disclosures, Some(VERIFIER_AUDIENCES[0].to_string()), None)?;
+
+        let [first, second] = presentations;
+        Ok(LinkageReport {
+            algorithm: adapter.sd_algorithm(),
+            fields: Self::classify(&first, &second, &presentation_fields(&control_vp_jwt)?),
+        })
+    }
+
+    /// Classifies every field of the presentations received by two verifiers.
+    ///
+    /// # Arguments
+    /// * `first` - Presentations received by the first verifier.
+    /// * `second` - Presentations received by the second verifier.
+    /// * `control` - Presentation of the control credential.
+    ///
+    /// # Returns
+    /// The linkage of every field found in any presentation.
+    pub fn classify(first: &[PresentationFields], second: &[PresentationFields], control: &PresentationFields) -> BTreeMap<String, Linkage> {
+        let field_names: BTreeSet<&String> = first.iter().chain(second).flat_map(|fields| fields.keys()).collect();
+        let no_values: BTreeSet<String> = BTreeSet::new();
+
+        field_names.into_iter().map(|field| {
+            let control_values: &BTreeSet<String> = control.get(field).unwrap_or(&no_values);
+            let mut pairs: usize = 0;
+            let mut linked_pairs: usize = 0;
+            let mut constant: bool = false;
+
+            for first_values in first.iter().map(|fields| fields.get(field).unwrap_or(&no_values)) {
+                for second_values in second.iter().map(|fields| fields.get(field).unwrap_or(&no_values)) {
+                    pairs += 1;
+                    let mut shared = first_values.intersection(second_values).peekable();
+                    constant |= shared.peek().is_some();
+                    if shared.any(|value| !control_values.contains(value)) {
+                        linked_pairs += 1;
+                    }
+                }
+            }
+
+            let linkage: Linkage = if pairs > 0 && linked_pairs == pairs {
+                Linkage::Linkable
+            } else if linked_pairs > 0 {
+                Linkage::Partial
+            } else if constant {
+                Linkage::Constant
+            } else {
+                Linkage::Unlinkable
+            };
+            (field.clone(), linkage)
+        }).collect()
+    }
+
+    /// Fields with a given linkage.
+    pub fn fields_with(&self, linkage: Linkage) -> Vec<&str> {
+        self.fields.iter().filter(|(_, field_linkage)| **field_linkage == linkage).map(|(field, _)| field.as_str()).collect()
+    }
+}
+
+
+/// Linkability matrix of several algorithms, with one row per field and one column per algorithm.
+///
+/// # Examples
+/// ```
+/// use std::collections::BTreeMap;
+/// use csd_jwt::linkability::{Linkage, LinkabilityMatrix, LinkageReport};
+///
+/// let mut matrix = LinkabilityMatrix::new();
+/// matrix.add(LinkageReport { algorithm: "SD-JWT".to_string(), fields: BTreeMap::from([("payload.signature".to_string(), Linkage::Linkable)]) });
+/// assert_eq!(matrix.linkage("payload.signature", "SD-JWT"), Some(Linkage::Linkable));
+/// assert_eq!(matrix.linkage("payload.signature", "BBS+"), None);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct LinkabilityMatrix {
+    /// Reports of the algorithms, in the order of the columns.
+    reports: Vec<LinkageReport>,
+}
+
+impl LinkabilityMatrix {
+
+    /// Constructor for an empty matrix.
+    pub fn new() -> Self {
+        LinkabilityMatrix::default()
+    }
+
+    /// Adds the report of an algorithm as a column.
+    pub fn add(&mut self, report: LinkageReport) {
+        self.reports.push(report);
+    }
+
+    /// Reports of the algorithms.
+    pub fn reports(&self) -> &[LinkageReport] {
+        &self.reports
+    }
+
+    /// Linkage of a field for an algorithm, none if the presentations of the algorithm do not contain the field.
+    pub fn linkage(&self, field: &str, algorithm: &str) -> Option<Linkage> {
+        self.reports.iter().find(|report| report.algorithm == algorithm).and_then(|report| report.fields.get(field).copied())
+    }
+
+    /// Writes the matrix to a CSV file, whose cells hold the linkage of the field and are empty if the presentations of
+    /// the algorithm do not contain it.
+    ///
+    /// # Arguments
+    /// * `filename` - Name of the CSV file, without extension.
+    ///
+    /// # Returns
+    /// The result of the operation or a string containing an error.
+    pub fn write_matrix(&self, filename: &String) -> Result<(), String> {
+        let mut columns: Vec<String> = vec!["field".to_string()];
+        columns.extend(self.reports.iter().map(|report| report.algorithm.clone()));
+
+        let mut writer = CSVWriter::new(columns)?;
+        writer.add_file(filename)?;
+
+        let field_names: BTreeSet<&String> = self.reports.iter().flat_map(|report| report.fields.keys()).collect();
+        for field in field_names {
+            let mut record: Vec<String> = vec![field.clone()];
+            record.extend(self.reports.iter().map(|report| report.fields.get(field).map(|linkage| linkage.to_string()).unwrap_or_default()));
+            writer.write_record_to_file(filename, record)?;
+        }
+
+        Ok(())
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{BTreeMap, BTreeSet};
+    use serde_json::json;
+    use crate::encoding::Encoding;
+    use crate::linkability::{presentation_fields, Linkage, LinkageReport, PresentationFields, JWS_SIGNATURE};
+
+    fn fields(values: &[(&str, &str)]) -> PresentationFields {
+        let mut fields: PresentationFields = BTreeMap::new();
+        for (field, value) in values {
+            fields.entry(field.to_string()).or_default().insert(value.to_string());
+        }
+        fields
+    }
+
+    #[test]
+    fn fields_are_classified_against_the_control() -> Result<(), String> {
+        let first = vec![fields(&[("signature", "s"), ("salt", "a"), ("alg", "ES256"), ("proof", "p1")])];
+        let second = vec![
+            fields(&[("signature", "s"), ("salt", "a"), ("alg", "ES256"), ("proof", "p2")]),
+            fields(&[("signature", "s"), ("salt", "b"), ("alg", "ES256"), ("proof", "p3")]),
+        ];
+        let control = fields(&[("signature", "c"), ("salt", "c"), ("alg", "ES256"), ("proof", "p4")]);
+
+        let linkage = LinkageReport::classify(&first, &second, &control);
+        assert_eq!(linkage["signature"], Linkage::Linkable);
+        assert_eq!(linkage["salt"], Linkage::Partial);
+        assert_eq!(linkage["alg"], Linkage::Constant);
+        assert_eq!(linkage["proof"], Linkage::Unlinkable);
+
+        Ok(())
+    }
+
+    #[test]
+    fn presentation_fields_descend_into_components() -> Result<(), String> {
+        let header: String = Encoding::Base64UrlNoPad.encode(json!({"alg": "ES256", "sd_enc": "base64url"}).to_string());
+        let component: String = Encoding::Base64UrlNoPad.encode(json!({"name": ["salt", "Alice"]}).to_string());
+        let payload: String = Encoding::Base64UrlNoPad.encode(json!({"nonce": "n", "vp": {"svc": component, "type": ["VP", "Extra"], "n": 3}}).to_string());
+
+        let fields: PresentationFields = presentation_fields(&format!("{header}.{payload}.c2ln"))?;
+        let values = |field: &str| fields.get(field).cloned().unwrap_or_default();
+        let set = |values: &[&str]| values.iter().map(|value| value.to_string()).collect::<BTreeSet<String>>();
+
+        assert_eq!(values("header.alg"), set(&["ES256"]));
+        assert_eq!(values("payload.nonce"), set(&["n"]));
+        assert_eq!(values("payload.vp.svc.name[]"), set(&["salt", "Alice"]));
+        assert_eq!(values("payload.vp.type[]"), set(&["VP", "Extra"]));
+        assert_eq!(values("payload.vp.n"), set(&["3"]));
+        assert_eq!(values(JWS_SIGNATURE), set(&["c2ln"]));
+        assert!(!fields.contains_key("payload.vp.svc"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn malformed_presentations_are_rejected() {
+        let payload: String = Encoding::Base64UrlNoPad.encode(json!({"nonce": "n"}).to_string());
+
+        assert!(presentation_fields("header.payload").is_err());
+        assert!(presentation_fields(&format!("not+base64.{payload}.c2ln")).is_err());
+        assert!(presentation_fields(&format!("{}.{payload}.c2ln", Encoding::Base64UrlNoPad.encode("[1]"))).is_err());
+    }
+}
@@ -1,25 +1,176 @@
+use std::cell::RefCell;
+use std::collections::BTreeMap;
 use std::time::{Duration, Instant};
 use std::env;
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
 use std::str::FromStr;
+use log::{error, info, warn, LevelFilter};
+use rand::seq::SliceRandom;
 use serde_json::{Map, Value};
 use csd_jwt::adapters::accumulators::csd_jwt_adapter::CsdJwtAdapter;
+use csd_jwt::adapters::accumulators::csd_jwt_multi_show_adapter::CsdJwtMultiShowAdapter;
+use csd_jwt::adapters::accumulators::csd_jwt_aggregated_adapter::CsdJwtAggregatedAdapter;
+use csd_jwt::adapters::accumulators::csd_jwt_bls12_381_adapter::CsdJwtBls12381Adapter;
 
-use csd_jwt::adapters::adapter::Adapter;
+use csd_jwt::adapters::adapter::{Adapter, VerifierAdapter};
 use csd_jwt::adapters::hashes::merkle_tree_adapter::MerkleTreeAdapter;
+use csd_jwt::adapters::registry::AdapterRegistry;
 use csd_jwt::adapters::hashes::sd_jwt_adapter::SdJwtAdapter;
 use csd_jwt::adapters::signatures::bbs_plus_adapter::BBSPlusAdapter;
-use csd_jwt::benchmark::Benchmark;
-use csd_jwt::common_data::{CLAIMS, VC};
-use csd_jwt::csv_writer::CSVWriter;
+use csd_jwt::binary_envelope::envelope_length;
+use csd_jwt::benchmark::{Benchmark, Measurement};
+use csd_jwt::claim_generator::{claim_generator_from_name, ClaimGenerator, FlatClaimGenerator};
+use csd_jwt::clock::SimulatedClock;
+use csd_jwt::common_data::{CommonData, CLAIMS, ISSUER, VC};
+use csd_jwt::compression::compressed_jwt_length;
+use csd_jwt::cost_profile::CostProfile;
+use csd_jwt::envelope_profile::EnvelopeSplit;
+use csd_jwt::constraints::{FeasibilityMatrix, PayloadConstraint};
+use csd_jwt::csv_writer::{CSVWriter, CSV_DIR};
+use csd_jwt::dataset::{issue_population, SubjectDataset};
+use csd_jwt::derived_claims::{DerivedClaims, DEFAULT_BIRTHDATE_CLAIM};
+use csd_jwt::deterministic::{random_bytes, rng, unix_time};
+use csd_jwt::device_profile::DeviceProfile;
+use csd_jwt::entropy::{install_source, AuditingEntropy, EntropyConsumption, EntropyScope, OsEntropy, UNSCOPED};
+use csd_jwt::hashing;
+use csd_jwt::holder::Holder;
+use csd_jwt::holder_binding::{holder_binding_from_name, HolderBinding};
+use csd_jwt::inspect::Inspection;
+use csd_jwt::issuer::Issuer;
+use csd_jwt::issuer_config::IssuerConfig;
+use csd_jwt::key_cache::{KeyCache, StaticKeyResolver, DEFAULT_KEY_CACHE_ENTRIES, DEFAULT_KEY_TTL};
+use csd_jwt::keys::jwks::Jwks;
+use csd_jwt::linkability::{Linkage, LinkabilityMatrix, LinkageReport, DEFAULT_PRESENTATIONS};
+use csd_jwt::presentation_cache::{ReusePolicy, DEFAULT_PRESENTATION_CACHE_ENTRIES};
+use csd_jwt::protocol::{run_exchange_over, ExchangeTimings};
+#[cfg(feature = "sqlite")]
+use csd_jwt::results_db::ResultsDatabase;
+use csd_jwt::regression::{RegressionReport, RegressionThresholds};
+use csd_jwt::result_format::{ResultDocument, RESULTS_FILE};
+use csd_jwt::result_sink::{write_serialized, CsvFileSink, NdjsonSink, ResultSink, SinkSet};
+use csd_jwt::results_db::{Sample, SampleLog, SAMPLES_FILE};
+use csd_jwt::robustness::{verification_robustness, Malformation, RobustnessReport};
+use csd_jwt::roles::{CsdJwtAggregated, CsdJwtMultiShow, RoleAlgorithm};
+use csd_jwt::scaling_model::ScalingAnalysis;
+use csd_jwt::size_estimate::SizeEstimate;
+use csd_jwt::sd_algorithms::accumulators::csd_jwt::CsdJwtInstance;
+use csd_jwt::sd_algorithms::hashes::merkle_trees::MerkleTreeInstance;
+use csd_jwt::sd_algorithms::hashes::sd_jwt::SdJwtInstance;
+use csd_jwt::sd_algorithms::signatures::bbs_plus::BBSPlusInstance;
+use csd_jwt::throughput::{verification_throughput, Throughput};
+use csd_jwt::transfer_model::{LinkProfile, TransferCostModel};
+use csd_jwt::transport::channel::{transport_from_name, Transport};
+use csd_jwt::transport::qr::{chunk_count, QR_CAPACITY};
+use csd_jwt::verifier::Verifier;
 
 const INITIALIZATION_DURATION: &str = "initialization_duration";
 const ISSUER_KEYPAIR_LENGTH: &str = "issuer_keypair_length";
+const VERIFIER_STATE_LENGTH: &str = "verifier_state_length";
 const VC_ISSUANCE_DURATION: &str = "vc_issuance_duration";
 const VP_ISSUANCE_DURATION: &str = "vp_issuance_duration";
 const VC_VERIFICATION_DURATION: &str = "vc_verification_duration";
 const VP_VERIFICATION_DURATION: &str = "vp_verification_duration";
 const VC_JWT_LENGTH: &str = "vc_jwt_length";
 const VP_JWT_LENGTH: &str = "vp_jwt_length";
+const VC_JWT_COMPRESSED_LENGTH: &str = "vc_jwt_compressed_length";
+const VP_JWT_COMPRESSED_LENGTH: &str = "vp_jwt_compressed_length";
+const VC_BINARY_LENGTH: &str = "vc_binary_length";
+const HOLDER_STORAGE_LENGTH: &str = "holder_storage_length";
+const VP_BINARY_LENGTH: &str = "vp_binary_length";
+const VP_QR_CHUNKS: &str = "vp_qr_chunks";
+const VP_CACHE_HIT_RATE: &str = "vp_cache_hit_rate";
+const VP_BATCH_ISSUANCE_DURATION: &str = "vp_batch_issuance_duration";
+const VP_PATTERN_ISSUANCE_DURATION: &str = "vp_pattern_issuance_duration";
+const VP_PATTERN_VERIFICATION_DURATION: &str = "vp_pattern_verification_duration";
+const VP_PATTERN_JWT_LENGTH: &str = "vp_pattern_jwt_length";
+const SCALING_MODELS: &str = "scaling_models";
+const CONSTRAINED: &str = "constrained";
+const PROTOCOL_LATENCY: &str = "protocol_latency";
+const PROTOCOL_LATENCY_STEPS: &str = "protocol_latency_steps";
+const KEY_RESOLUTIONS: &str = "key_resolutions";
+const TRANSFER_COSTS: &str = "transfer_costs";
+const PAYLOAD_FEASIBILITY: &str = "payload_feasibility";
+const VERIFICATION_THROUGHPUT: &str = "verification_throughput";
+const VERIFICATION_LATENCY_PERCENTILES: &str = "verification_latency_percentiles";
+const VERIFICATION_ROBUSTNESS: &str = "verification_robustness";
+const DERIVED_CLAIMS: &str = "derived_claims";
+const EPHEMERAL_BINDING: &str = "ephemeral_binding";
+const BBS_PRESENTATION_PHASES: &str = "bbs_presentation_phases";
+const OPERATION_COUNTS: &str = "operation_counts";
+const ENVELOPE_SPLIT: &str = "envelope_split";
+const BULK_ISSUANCE: &str = "bulk_issuance";
+const ISSUER_KEY_REUSE: &str = "issuer_key_reuse";
+const WITNESS_UPDATES: &str = "witness_updates";
+const PRESENTATION_REUSE: &str = "presentation_reuse";
+const REISSUANCE: &str = "reissuance";
+const SIZE_ESTIMATION: &str = "size_estimation";
+const ENTROPY_CONSUMPTION: &str = "entropy_consumption";
+const HASH_BACKEND: &str = "hash_backend";
+const CREDENTIAL_LIFETIME: &str = "credential_lifetime";
+const REGRESSION_REPORT: &str = "regression_report";
+const LINKABILITY: &str = "linkability";
+/// Latency percentiles recorded by the throughput benchmark.
+const LATENCY_PERCENTILES: [f64; 3] = [50.0, 95.0, 99.0];
+/// Amount of distinct presentations the workers of the throughput benchmark cycle over.
+const THROUGHPUT_PRESENTATIONS: usize = 16;
+/// Amount of identical presentation requests answered by the holders of the presentation reuse benchmark.
+const PRESENTATION_REUSE_REQUESTS: usize = 32;
+/// Audience of the presentations of the presentation reuse benchmark, for the algorithms supporting audiences.
+const PRESENTATION_REUSE_AUDIENCE: &str = "https://verifier.example";
+/// Seconds in a day of the simulated lifetime of credentials.
+const SECONDS_PER_DAY: u64 = 86_400;
+/// Largest amount of claims of the dense sweep, every amount up to it being benchmarked.
+const DENSE_MOCK_CLAIMS: usize = 100;
+/// Amount of claim counts sampled by the sparse sweep beyond the dense one, unless set through `CSD_JWT_LARGE_CLAIMS`.
+const LARGE_CLAIM_SAMPLES: usize = 8;
+/// Amount of claims of the credentials the issuers printed by `jwks` are set up for, unless passed as argument.
+const DEFAULT_JWKS_CLAIMS: usize = 32;
+/// Amount of claims of the credentials analyzed by `linkability`, unless passed as argument.
+const DEFAULT_LINKABILITY_CLAIMS: usize = 8;
+
+/// Disclosure patterns benchmarked besides the linear sweep, as Merkle path sharing and BBS+ index handling have pattern-dependent costs.
+#[derive(Clone, Copy, Debug)]
+enum DisclosurePattern {
+    FirstOnly,
+    LastOnly,
+    EveryOther,
+    RandomHalf,
+}
+
+impl DisclosurePattern {
+    const ALL: [DisclosurePattern; 4] = [
+        DisclosurePattern::FirstOnly,
+        DisclosurePattern::LastOnly,
+        DisclosurePattern::EveryOther,
+        DisclosurePattern::RandomHalf,
+    ];
+
+    fn name(&self) -> &'static str {
+        match self {
+            DisclosurePattern::FirstOnly => { "first_only" }
+            DisclosurePattern::LastOnly => { "last_only" }
+            DisclosurePattern::EveryOther => { "every_other" }
+            DisclosurePattern::RandomHalf => { "random_half" }
+        }
+    }
+
+    /// Indices (starting from 1) of the mock claims disclosed by the pattern.
+    fn indices(&self, n_mock_claims: usize) -> Vec<usize> {
+        match self {
+            DisclosurePattern::FirstOnly => { vec![1] }
+            DisclosurePattern::LastOnly => { vec![n_mock_claims] }
+            DisclosurePattern::EveryOther => { (1..=n_mock_claims).step_by(2).collect() }
+            DisclosurePattern::RandomHalf => {
+                let mut indices: Vec<usize> = (1..=n_mock_claims).collect();
+                indices.shuffle(&mut rng());
+                indices.truncate((n_mock_claims / 2).max(1));
+                indices
+            }
+        }
+    }
+}
 
 fn setup_raw_vc() -> Result<Map<String, Value>, String> {
 
@@ -34,115 +185,439 @@ fn setup_raw_vc() -> Result<Map<String, Value>, String> {
     }
 }
 
-fn initialize_sd_algorithms(claims_len: usize, iterations: i8) -> Result<(Vec<Duration>, Vec<Box<dyn Adapter>>), String> {
+/// Outcome of the initialization of a single algorithm: its averaged duration and the adapter, or the error.
+type Initialization = Result<(Duration, Box<dyn Adapter>), String>;
+
+/// Initializes every benchmarked algorithm. The adapters keep their default ES256 holder binding unless another one
+/// is given.
+fn initialize_sd_algorithms(claims_len: usize, measurement: Measurement, verification_cache: bool, prepared_pairings: bool, element_blinding: bool, holder_binding: Option<&Arc<dyn HolderBinding>>) -> Vec<Initialization> {
+
+    let mut initializations: Vec<Initialization> = vec![];
+
+    initializations.push(Benchmark::benchmark_initialization(|| {
+        let adapter = SdJwtAdapter::new(claims_len)?;
+        Ok(match holder_binding { Some(holder_binding) => { adapter.with_holder_binding(holder_binding.clone()) } None => { adapter } })
+    }, measurement).map(|(duration, algo)| (duration, algo as Box<dyn Adapter>)));
+
+    initializations.push(Benchmark::benchmark_initialization(|| {
+        let adapter = CsdJwtAdapter::new(claims_len)?;
+        let adapter = if verification_cache { adapter.with_verification_cache() } else { adapter };
+        let adapter = if prepared_pairings { adapter.prepare() } else { adapter };
+        let adapter = if element_blinding { adapter.with_element_blinding() } else { adapter };
+        Ok(match holder_binding { Some(holder_binding) => { adapter.with_holder_binding(holder_binding.clone()) } None => { adapter } })
+    }, measurement).map(|(duration, algo)| (duration, algo as Box<dyn Adapter>)));
+
+    initializations.push(Benchmark::benchmark_initialization(|| {
+        let adapter = MerkleTreeAdapter::new(claims_len)?;
+        let adapter = if verification_cache { adapter.with_verification_cache() } else { adapter };
+        Ok(match holder_binding { Some(holder_binding) => { adapter.with_holder_binding(holder_binding.clone()) } None => { adapter } })
+    }, measurement).map(|(duration, algo)| (duration, algo as Box<dyn Adapter>)));
+
+    initializations.push(Benchmark::benchmark_initialization(|| {
+        let adapter = BBSPlusAdapter::new(claims_len)?;
+        Ok(match holder_binding { Some(holder_binding) => { adapter.with_holder_binding(holder_binding.clone()) } None => { adapter } })
+    }, measurement).map(|(duration, algo)| (duration, algo as Box<dyn Adapter>)));
+
+    initializations.push(Benchmark::benchmark_initialization(|| {
+        let adapter = CsdJwtMultiShowAdapter::new(claims_len)?;
+        let adapter = if element_blinding { adapter.with_element_blinding() } else { adapter };
+        Ok(match holder_binding { Some(holder_binding) => { adapter.with_holder_binding(holder_binding.clone()) } None => { adapter } })
+    }, measurement).map(|(duration, algo)| (duration, algo as Box<dyn Adapter>)));
+
+    initializations.push(Benchmark::benchmark_initialization(|| {
+        let adapter = CsdJwtAggregatedAdapter::new(claims_len)?;
+        let adapter = if element_blinding { adapter.with_element_blinding() } else { adapter };
+        Ok(match holder_binding { Some(holder_binding) => { adapter.with_holder_binding(holder_binding.clone()) } None => { adapter } })
+    }, measurement).map(|(duration, algo)| (duration, algo as Box<dyn Adapter>)));
+
+    initializations.push(Benchmark::benchmark_initialization(|| {
+        let adapter = CsdJwtBls12381Adapter::new(claims_len)?;
+        Ok(match holder_binding { Some(holder_binding) => { adapter.with_holder_binding(holder_binding.clone()) } None => { adapter } })
+    }, measurement).map(|(duration, algo)| (duration, algo as Box<dyn Adapter>)));
+
+    initializations
+}
+
+/// Holder binding selected through the environment variable CSD_JWT_HOLDER_BINDING (`es256`, `eddsa` or `none`), if
+/// set.
+fn holder_binding_from_env() -> Result<Option<Arc<dyn HolderBinding>>, String> {
+    match env::var("CSD_JWT_HOLDER_BINDING") {
+        Ok(name) => { Ok(Some(holder_binding_from_name(&name)?)) }
+        Err(_) => { Ok(None) }
+    }
+}
+
+/// Constructor of an adapter for a given amount of claims, generating fresh keys and parameters.
+type AdapterConstructor = fn(usize) -> Result<Box<dyn Adapter>, String>;
+
+/// Constructors of every benchmarked algorithm with the default options, in the order of the sweep.
+const ADAPTER_CONSTRUCTORS: [AdapterConstructor; 7] = [
+    |claims_len| SdJwtAdapter::new(claims_len).map(|adapter| Box::new(adapter) as Box<dyn Adapter>),
+    |claims_len| CsdJwtAdapter::new(claims_len).map(|adapter| Box::new(adapter) as Box<dyn Adapter>),
+    |claims_len| MerkleTreeAdapter::new(claims_len).map(|adapter| Box::new(adapter) as Box<dyn Adapter>),
+    |claims_len| BBSPlusAdapter::new(claims_len).map(|adapter| Box::new(adapter) as Box<dyn Adapter>),
+    |claims_len| CsdJwtMultiShowAdapter::new(claims_len).map(|adapter| Box::new(adapter) as Box<dyn Adapter>),
+    |claims_len| CsdJwtAggregatedAdapter::new(claims_len).map(|adapter| Box::new(adapter) as Box<dyn Adapter>),
+    |claims_len| CsdJwtBls12381Adapter::new(claims_len).map(|adapter| Box::new(adapter) as Box<dyn Adapter>),
+];
+
+/// Failure of an algorithm during the sweep, recorded as a missing value in the CSV files.
+struct Failure {
+    n_mock_claims: usize,
+    algorithm: String,
+    operation: &'static str,
+    error: String,
+}
+
+/// Failures collected during the sweep, so that one failing algorithm does not abort the benchmark of the others.
+struct FailureLog {
+    failures: Vec<Failure>,
+}
+
+impl FailureLog {
 
-    let mut sd_algorithms: Vec<Box<dyn Adapter>> = vec![];
-    let mut durations: Vec<Duration> = vec![];
+    fn new() -> Self {
+        FailureLog { failures: vec![] }
+    }
+
+    /// Returns the value of a successful result, or logs and records the error and returns None.
+    fn check<T>(&mut self, n_mock_claims: usize, algorithm: &str, operation: &'static str, result: Result<T, String>) -> Option<T> {
+        match result {
+            Ok(value) => { Some(value) }
+            Err(error) => {
+                error!("[{}] {} failed with {} claims: {}", algorithm, operation, n_mock_claims, error);
+                self.failures.push(Failure { n_mock_claims, algorithm: algorithm.to_string(), operation, error });
+                None
+            }
+        }
+    }
+
+    /// Logs a summary of all the failures, followed by the scaling limits they reveal.
+    fn summarize(&self) {
+        if self.failures.is_empty() {
+            info!("Benchmark completed without failures.");
+            return;
+        }
+
+        warn!("Benchmark completed with {} failures:", self.failures.len());
+        for failure in &self.failures {
+            warn!("  {:>4} claims - [{}] {}: {}", failure.n_mock_claims, failure.algorithm, failure.operation, failure.error);
+        }
 
-    let (duration, algo) = Benchmark::benchmark_initialization(|| SdJwtAdapter::new(claims_len), iterations)?;
-    sd_algorithms.push(algo);
-    durations.push(duration);
+        warn!("Scaling limits (smallest amount of claims each operation failed with):");
+        for ((algorithm, operation), n_mock_claims) in self.scaling_limits() {
+            warn!("  [{}] {}: {} claims", algorithm, operation, n_mock_claims);
+        }
+    }
 
-    let (duration, algo) = Benchmark::benchmark_initialization(|| CsdJwtAdapter::new(claims_len), iterations)?;
-    sd_algorithms.push(algo);
-    durations.push(duration);
+    /// Smallest amount of claims every operation of every algorithm failed with.
+    fn scaling_limits(&self) -> BTreeMap<(String, &'static str), usize> {
+        let mut limits: BTreeMap<(String, &'static str), usize> = BTreeMap::new();
+        for failure in &self.failures {
+            let limit = limits.entry((failure.algorithm.clone(), failure.operation)).or_insert(failure.n_mock_claims);
+            *limit = (*limit).min(failure.n_mock_claims);
+        }
+        limits
+    }
+}
 
-    let (duration, algo) = Benchmark::benchmark_initialization(|| MerkleTreeAdapter::new(claims_len), iterations)?;
-    sd_algorithms.push(algo);
-    durations.push(duration);
+/// Options of the benchmark, as selected through the environment variables.
+struct BenchmarkOptions {
+    /// How many times every operation is executed.
+    measurement: Measurement,
+    /// Whether the verifier-side caches are enabled.
+    verification_cache: bool,
+    /// Whether CSD-JWT verifies with prepared pairing inputs.
+    prepared_pairings: bool,
+    /// Whether CSD-JWT blinds the accumulated elements of every VC.
+    element_blinding: bool,
+    /// Whether the durations of the operations are also split between the SD mechanism and the JWT envelope.
+    envelope_split: bool,
+    /// Holder binding replacing the default ES256 one, if any.
+    holder_binding: Option<Arc<dyn HolderBinding>>,
+    /// Constrained device profile VP issuance is also benchmarked with, if any.
+    holder_profile: Option<DeviceProfile>,
+    /// Name of the transport of the protocol exchanges.
+    transport_name: String,
+    /// Time to live of the issuer keys cached by the verifier of the protocol exchanges.
+    key_cache_ttl: Duration,
+    /// Whether the measurements of the sweep are written to CSV files.
+    csv_output: bool,
+    /// Path of the results database the samples of the sweep are stored in, if any.
+    results_db: Option<String>,
+    /// Label of the run in the results database.
+    run_label: String,
+}
 
-    let (duration, algo) = Benchmark::benchmark_initialization(|| BBSPlusAdapter::new(claims_len), iterations)?;
-    sd_algorithms.push(algo);
-    durations.push(duration);
-    Ok((durations, sd_algorithms))
+/// Reports derived from the sizes of the VCs and VPs measured during the sweep.
+struct SizeReports {
+    /// Estimated transfer times over several links.
+    transfer_costs: TransferCostModel,
+    /// Fitness of the sizes with respect to payload constraints.
+    feasibility: FeasibilityMatrix,
 }
 
-fn substitute_with_mock_claims(raw_vc: &mut Map<String, Value>, n_mock_claims: usize) -> Result<(), String> {
+/// Formats an optional measurement for the pattern CSV files, where a missing value is an empty field.
+fn format_optional<T: ToString>(value: Option<T>) -> String {
+    value.map(|value| value.to_string()).unwrap_or_default()
+}
 
-    let mut claims: Map<String, Value> = Map::new();
-    for i in 1..=n_mock_claims {
-        claims.insert(
-            String::from(format!("Claim Key {}", i)),
-            Value::String(String::from(format!("Claim Value {}", i)))
-        );
+/// Creates the sink of the sweep, writing its records to CSV files if the CSV output is enabled and streaming them to
+/// stdout if the stream is enabled.
+fn sweep_sink(csv_output: bool, stream: bool) -> Result<SinkSet, String> {
+    let mut sinks: Vec<Box<dyn ResultSink>> = vec![];
+    if csv_output {
+        sinks.push(Box::new(CsvFileSink::new()?));
     }
-    raw_vc.insert(CLAIMS.to_string(), Value::Object(claims));       // We simply ignore if previous claims were present
+    if stream {
+        sinks.push(Box::new(NdjsonSink::stdout()));
+    }
+    Ok(SinkSet::new(sinks))
+}
 
+/// Stores the samples of a sweep in the results database.
+#[cfg(feature = "sqlite")]
+fn store_samples(path: &str, run_label: &str, sample_log: &SampleLog) -> Result<(), String> {
+    let mut database = ResultsDatabase::open(path)?;
+    let run_id: i64 = database.store_run(run_label, sample_log)?;
+    info!("Stored {} samples in {} as run {} ({})", sample_log.samples().len(), path, run_id, run_label);
     Ok(())
 }
 
-fn create_mock_disclosures(disclosures: &mut Vec<String>, n_disclosures: usize) {
+/// Stores the samples of a sweep in the results database, unavailable without the `sqlite` feature.
+#[cfg(not(feature = "sqlite"))]
+fn store_samples(path: &str, _run_label: &str, _sample_log: &SampleLog) -> Result<(), String> {
+    Err(format!("Cannot store samples in {path}: the sqlite feature is disabled"))
+}
 
-    disclosures.clear();
-    for i in 1..=n_disclosures {
-        disclosures.push(format!("Claim Key {}", i));
+/// Loads the samples of a run from the results database selected by `CSD_JWT_RESULTS_DB`.
+#[cfg(feature = "sqlite")]
+fn load_run(run_id: i64) -> Result<SampleLog, String> {
+    match env::var("CSD_JWT_RESULTS_DB") {
+        Ok(path) => { ResultsDatabase::open(&path)?.load_run(run_id) }
+        Err(_) => { Err(format!("Cannot load run {run_id}: CSD_JWT_RESULTS_DB is not set")) }
     }
+}
 
+/// Loads the samples of a run from the results database, unavailable without the `sqlite` feature.
+#[cfg(not(feature = "sqlite"))]
+fn load_run(run_id: i64) -> Result<SampleLog, String> {
+    Err(format!("Cannot load run {run_id}: the sqlite feature is disabled"))
 }
 
-fn benchmark_multiple_mock_claims(max_mock_claims: usize, iterations: i8) -> Result<(), String> {
+/// Amounts of claims of the sparse sweep beyond the dense one, geometrically spaced up to `max_mock_claims` and
+/// rounded to multiples of 10, so that presentations are benchmarked for each of them.
+///
+/// # Arguments
+/// * `max_mock_claims` - Largest amount of claims, e.g. 10000.
+/// * `samples` - Amount of claim counts sampled.
+fn sparse_claim_counts(max_mock_claims: usize, samples: usize) -> Vec<usize> {
+    let mut claim_counts: Vec<usize> = vec![];
+    if max_mock_claims <= DENSE_MOCK_CLAIMS || samples == 0 {
+        return claim_counts;
+    }
 
-    let (_, algorithms) = initialize_sd_algorithms(1, iterations)?;
-    let algorithm_names: Vec<String> = algorithms
-        .iter()
-        .map(|algo| algo.sd_algorithm())
-        .collect();
+    let ratio: f64 = (max_mock_claims as f64 / DENSE_MOCK_CLAIMS as f64).powf(1.0 / samples as f64);
+    for sample in 1..=samples {
+        let n_mock_claims: usize = ((DENSE_MOCK_CLAIMS as f64 * ratio.powi(sample as i32) / 10.0).round() as usize * 10).min(max_mock_claims);
+        if n_mock_claims > claim_counts.last().copied().unwrap_or(DENSE_MOCK_CLAIMS) {
+            claim_counts.push(n_mock_claims);
+        }
+    }
+    claim_counts
+}
 
+/// Parses the sparse sweep selected by `CSD_JWT_LARGE_CLAIMS`, of the form `<max>` or `<max>:<samples>`.
+///
+/// # Arguments
+/// * `configuration` - Value of the environment variable.
+///
+/// # Returns
+/// A result containing the amounts of claims of the sparse sweep or a string containing an error.
+fn parse_large_claims(configuration: &str) -> Result<Vec<usize>, String> {
+    let (max_mock_claims, samples) = match configuration.split_once(':') {
+        Some((max_mock_claims, samples)) => { (max_mock_claims, samples) }
+        None => { (configuration, "") }
+    };
+    let max_mock_claims: usize = match max_mock_claims.trim().parse() {
+        Ok(max_mock_claims) => { max_mock_claims }
+        Err(err) => { return Err(format!("Invalid maximum amount of claims {max_mock_claims}: [{err}]")) }
+    };
+    let samples: usize = match samples.trim() {
+        "" => { LARGE_CLAIM_SAMPLES }
+        samples => match samples.parse() {
+            Ok(samples) => { samples }
+            Err(err) => { return Err(format!("Invalid amount of samples {samples}: [{err}]")) }
+        }
+    };
 
-    println!("Algorithms = {:?}", algorithm_names);
+    Ok(sparse_claim_counts(max_mock_claims, samples))
+}
 
-    let mut writer = CSVWriter::new(algorithm_names)?;
-    writer.add_file(&INITIALIZATION_DURATION.to_string())?;
-    writer.add_file(&ISSUER_KEYPAIR_LENGTH.to_string())?;
-    writer.add_file(&VC_ISSUANCE_DURATION.to_string())?;
-    writer.add_file(&VC_VERIFICATION_DURATION.to_string())?;
-    writer.add_file(&VC_JWT_LENGTH.to_string())?;
+/// Sweeps the given amounts of claims, writing every measurement to the sink.
+fn benchmark_multiple_mock_claims(claim_counts: &[usize], options: BenchmarkOptions, claim_generator: &dyn ClaimGenerator, size_reports: SizeReports, sink: &mut dyn ResultSink) -> Result<(), String> {
+
+    let BenchmarkOptions { measurement, verification_cache, prepared_pairings, element_blinding, envelope_split, holder_binding, holder_profile, transport_name, key_cache_ttl, csv_output, results_db, run_label } = options;
+
+    let mut algorithm_names: Vec<String> = vec![];
+    for initialization in initialize_sd_algorithms(1, measurement, verification_cache, prepared_pairings, element_blinding, holder_binding.as_ref()) {
+        let (_, algo) = initialization?;
+        algorithm_names.push(algo.sd_algorithm());
+    }
+
+
+    info!("Algorithms = {:?}", algorithm_names);
+
+    let mut pattern_columns: Vec<String> = vec!["claims".to_string(), "pattern".to_string()];
+    pattern_columns.extend(algorithm_names.iter().cloned());
+    sink.add_file(VP_PATTERN_ISSUANCE_DURATION, &pattern_columns)?;
+    sink.add_file(VP_PATTERN_VERIFICATION_DURATION, &pattern_columns)?;
+    sink.add_file(VP_PATTERN_JWT_LENGTH, &pattern_columns)?;
+
+    let mut protocol_columns: Vec<String> = vec!["claims".to_string(), "algorithm".to_string()];
+    protocol_columns.extend(ExchangeTimings::STEPS.iter().map(|step| step.to_string()));
+    protocol_columns.extend(["transport", "bytes_on_wire", "round_trips"].iter().map(|column| column.to_string()));
+    sink.add_file(PROTOCOL_LATENCY_STEPS, &protocol_columns)?;
+    let key_columns: Vec<String> = ["claims", "algorithm", "resolutions", "cache_hits"].iter().map(|column| column.to_string()).collect();
+    sink.add_file(KEY_RESOLUTIONS, &key_columns)?;
+
+    let mut cost_columns: Vec<String> = ["claims", "disclosures", "algorithm", "operation"].iter().map(|column| column.to_string()).collect();
+    cost_columns.extend(CostProfile::COUNTERS.iter().map(|counter| counter.to_string()));
+    sink.add_file(OPERATION_COUNTS, &cost_columns)?;
+    if envelope_split {
+        let split_columns: Vec<String> = ["claims", "disclosures", "algorithm", "operation", "duration", "crypto", "envelope"].iter().map(|column| column.to_string()).collect();
+        sink.add_file(ENVELOPE_SPLIT, &split_columns)?;
+    }
+
+    sink.add_file(INITIALIZATION_DURATION, &algorithm_names)?;
+    sink.add_file(ISSUER_KEYPAIR_LENGTH, &algorithm_names)?;
+    sink.add_file(VERIFIER_STATE_LENGTH, &algorithm_names)?;
+    sink.add_file(VC_ISSUANCE_DURATION, &algorithm_names)?;
+    sink.add_file(VC_VERIFICATION_DURATION, &algorithm_names)?;
+    sink.add_file(VC_JWT_LENGTH, &algorithm_names)?;
+    sink.add_file(VC_JWT_COMPRESSED_LENGTH, &algorithm_names)?;
+    sink.add_file(VC_BINARY_LENGTH, &algorithm_names)?;
+    sink.add_file(HOLDER_STORAGE_LENGTH, &algorithm_names)?;
+    sink.add_file(VP_BATCH_ISSUANCE_DURATION, &algorithm_names)?;
+    sink.add_file(PROTOCOL_LATENCY, &algorithm_names)?;
+    let constrained_batch_csv_name: String = format!("{VP_BATCH_ISSUANCE_DURATION}_{CONSTRAINED}");
+    if holder_profile.is_some() {
+        sink.add_file(&constrained_batch_csv_name, &algorithm_names)?;
+    }
 
     let raw_vc: &mut Map<String, Value> = &mut setup_raw_vc()?;
     let disclosures: &mut Vec<String> = &mut vec![];
+    let mut failure_log = FailureLog::new();
+    let mut scaling_analysis = ScalingAnalysis::new();
+    let mut sample_log = SampleLog::new();
+    let SizeReports { transfer_costs: mut transfer_cost_model, feasibility: mut feasibility_matrix } = size_reports;
 
-    for n_mock_claims in 1..=max_mock_claims {
+    for &n_mock_claims in claim_counts {
 
         let now = Instant::now();
-        substitute_with_mock_claims(raw_vc, n_mock_claims)?;
+        claim_generator.substitute_claims(raw_vc, n_mock_claims)?;
 
         ////////////////////////////////////////////////////////////////////////////////////////////
         /////////////////////  SETUP TIME AND ISSUER KEYPAIR LENGTH  ///////////////////////////////
         ////////////////////////////////////////////////////////////////////////////////////////////
-        let (durations, sd_algorithms) = initialize_sd_algorithms(n_mock_claims, iterations)?;
-        let issuer_keypair_length_vector: Vec<usize> = sd_algorithms
-            .iter()
-            .map(|algo| algo.issuer_keypair().unwrap())
-            .map(|(pk, sk)| pk.len() + sk.len())
-            .collect();
-        let initialization_durations: Vec<u128> = durations.iter().map(|duration| duration.as_micros()).collect();
-        writer.write_record_to_file(&INITIALIZATION_DURATION.to_string(), &initialization_durations)?;
-        writer.write_record_to_file(&ISSUER_KEYPAIR_LENGTH.to_string(), &issuer_keypair_length_vector)?;
+        let mut sd_algorithms: Vec<Option<Box<dyn Adapter>>> = vec![];
+        let mut initialization_durations: Vec<Option<u128>> = vec![];
+        let mut issuer_keypair_length_vector: Vec<Option<usize>> = vec![];
+        let mut verifier_state_lengths: Vec<Option<usize>> = vec![];
+
+        for (index, initialization) in initialize_sd_algorithms(n_mock_claims, measurement, verification_cache, prepared_pairings, element_blinding, holder_binding.as_ref()).into_iter().enumerate() {
+            let name = &algorithm_names[index];
+            match failure_log.check(n_mock_claims, name, "Initialization", initialization) {
+                Some((duration, algo)) => {
+                    let keypair = failure_log.check(n_mock_claims, name, "Issuer keypair", algo.issuer_keypair());
+                    initialization_durations.push(Some(duration.as_micros()));
+                    issuer_keypair_length_vector.push(keypair.map(|(pk, sk)| pk.len() + sk.len()));
+                    verifier_state_lengths.push(failure_log.check(n_mock_claims, name, "Verifier state", algo.verifier_state_length()));
+                    sd_algorithms.push(Some(algo));
+                }
+                None => {
+                    initialization_durations.push(None);
+                    issuer_keypair_length_vector.push(None);
+                    verifier_state_lengths.push(None);
+                    sd_algorithms.push(None);
+                }
+            }
+        }
+
+        write_serialized(sink, INITIALIZATION_DURATION, &initialization_durations)?;
+        write_serialized(sink, ISSUER_KEYPAIR_LENGTH, &issuer_keypair_length_vector)?;
+        write_serialized(sink, VERIFIER_STATE_LENGTH, &verifier_state_lengths)?;
+        sample_log.record_row(INITIALIZATION_DURATION, &algorithm_names, n_mock_claims, None, initialization_durations.iter().map(|duration| duration.map(|duration| duration as f64)));
+        sample_log.record_row(ISSUER_KEYPAIR_LENGTH, &algorithm_names, n_mock_claims, None, issuer_keypair_length_vector.iter().map(|length| length.map(|length| length as f64)));
+        sample_log.record_row(VERIFIER_STATE_LENGTH, &algorithm_names, n_mock_claims, None, verifier_state_lengths.iter().map(|length| length.map(|length| length as f64)));
+        scaling_analysis.record_row(INITIALIZATION_DURATION, &algorithm_names, n_mock_claims, initialization_durations.iter().map(|duration| duration.map(|duration| duration as f64)));
+        scaling_analysis.record_row(ISSUER_KEYPAIR_LENGTH, &algorithm_names, n_mock_claims, issuer_keypair_length_vector.iter().map(|length| length.map(|length| length as f64)));
+        scaling_analysis.record_row(VERIFIER_STATE_LENGTH, &algorithm_names, n_mock_claims, verifier_state_lengths.iter().map(|length| length.map(|length| length as f64)));
 
 
         ////////////////////////////////////////////////////////////////////////////////////////////
         /////////////////////  VC ISSUANCE TIME, JWT LENGTH AND VERIFICATION TIME  /////////////////
         ////////////////////////////////////////////////////////////////////////////////////////////
-        let mut vcs: Vec<Map<String, Value>> = vec![];
-        let mut vc_jwts: Vec<usize> = vec![];
-        let mut vc_issuance_durations: Vec<u128> = vec![];
-        let mut vc_verification_durations: Vec<u128> = vec![];
+        let mut vcs: Vec<Option<Map<String, Value>>> = vec![];
+        let mut vc_jwts: Vec<Option<usize>> = vec![];
+        let mut vc_compressed_jwts: Vec<Option<usize>> = vec![];
+        let mut vc_binary_lengths: Vec<Option<usize>> = vec![];
+        let mut holder_storage_lengths: Vec<Option<usize>> = vec![];
+        let mut vc_issuance_durations: Vec<Option<u128>> = vec![];
+        let mut vc_verification_durations: Vec<Option<u128>> = vec![];
 
-        for algo in &sd_algorithms {
-            let clone = raw_vc.clone();
-            let (duration, (vc, vc_jwt)) = Benchmark::benchmark_function(|| algo.issue_vc(&clone), iterations)?;
+        for (index, algo) in sd_algorithms.iter().enumerate() {
+            let name = &algorithm_names[index];
+            let issuance = algo.as_ref().and_then(|algo| {
+                let clone = raw_vc.clone();
+                failure_log.check(n_mock_claims, name, "VC issuance", Benchmark::measure(|| algo.issue_vc(&clone), measurement))
+            });
+
+            let verification = match (algo, &issuance) {
+                (Some(algo), Some((_, (vc, _)))) => {
+                    failure_log.check(n_mock_claims, name, "VC verification", Benchmark::measure(|| algo.verify_vc(vc), measurement))
+                }
+                _ => { None }
+            };
 
-            vcs.push(vc.clone());
-            vc_jwts.push(vc_jwt.len());
-            vc_issuance_durations.push(duration.as_micros());
+            let holder_storage = match (algo, &issuance) {
+                (Some(algo), Some((_, (_, vc_jwt)))) => {
+                    failure_log.check(n_mock_claims, name, "Holder storage", algo.holder_storage_length(vc_jwt))
+                }
+                _ => { None }
+            };
 
-            let (duration, _) = Benchmark::benchmark_function(|| algo.verify_vc(&vc), iterations)?;
-            vc_verification_durations.push(duration.as_micros());
+            vc_verification_durations.push(verification.map(|(duration, _)| duration.as_micros()));
+            holder_storage_lengths.push(holder_storage);
+            vc_issuance_durations.push(issuance.as_ref().map(|(duration, _)| duration.as_micros()));
+            vc_jwts.push(issuance.as_ref().map(|(_, (_, vc_jwt))| vc_jwt.len()));
+            vc_compressed_jwts.push(issuance.as_ref().and_then(|(_, (_, vc_jwt))| compressed_jwt_length(vc_jwt).ok()));
+            vc_binary_lengths.push(issuance.as_ref().and_then(|(_, (_, vc_jwt))| envelope_length(vc_jwt).ok()));
+            vcs.push(issuance.map(|(_, (vc, _))| vc));
         }
 
-        writer.write_record_to_file(&VC_ISSUANCE_DURATION.to_string(), &vc_issuance_durations)?;
-        writer.write_record_to_file(&VC_JWT_LENGTH.to_string(), &vc_jwts)?;
-        writer.write_record_to_file(&VC_VERIFICATION_DURATION.to_string(), &vc_verification_durations)?;
+        write_serialized(sink, VC_ISSUANCE_DURATION, &vc_issuance_durations)?;
+        write_serialized(sink, VC_JWT_LENGTH, &vc_jwts)?;
+        write_serialized(sink, VC_JWT_COMPRESSED_LENGTH, &vc_compressed_jwts)?;
+        write_serialized(sink, VC_BINARY_LENGTH, &vc_binary_lengths)?;
+        write_serialized(sink, HOLDER_STORAGE_LENGTH, &holder_storage_lengths)?;
+        write_serialized(sink, VC_VERIFICATION_DURATION, &vc_verification_durations)?;
+        sample_log.record_row(VC_ISSUANCE_DURATION, &algorithm_names, n_mock_claims, None, vc_issuance_durations.iter().map(|duration| duration.map(|duration| duration as f64)));
+        sample_log.record_row(VC_JWT_LENGTH, &algorithm_names, n_mock_claims, None, vc_jwts.iter().map(|length| length.map(|length| length as f64)));
+        sample_log.record_row(VC_JWT_COMPRESSED_LENGTH, &algorithm_names, n_mock_claims, None, vc_compressed_jwts.iter().map(|length| length.map(|length| length as f64)));
+        sample_log.record_row(VC_BINARY_LENGTH, &algorithm_names, n_mock_claims, None, vc_binary_lengths.iter().map(|length| length.map(|length| length as f64)));
+        sample_log.record_row(HOLDER_STORAGE_LENGTH, &algorithm_names, n_mock_claims, None, holder_storage_lengths.iter().map(|length| length.map(|length| length as f64)));
+        sample_log.record_row(VC_VERIFICATION_DURATION, &algorithm_names, n_mock_claims, None, vc_verification_durations.iter().map(|duration| duration.map(|duration| duration as f64)));
+        scaling_analysis.record_row(VC_ISSUANCE_DURATION, &algorithm_names, n_mock_claims, vc_issuance_durations.iter().map(|duration| duration.map(|duration| duration as f64)));
+        for (name, vc_jwt_length) in algorithm_names.iter().zip(&vc_jwts) {
+            if let Some(vc_jwt_length) = vc_jwt_length {
+                transfer_cost_model.record_vc(n_mock_claims, name, *vc_jwt_length);
+                feasibility_matrix.record("vc", n_mock_claims, n_mock_claims, name, *vc_jwt_length);
+            }
+        }
+        scaling_analysis.record_row(VC_JWT_LENGTH, &algorithm_names, n_mock_claims, vc_jwts.iter().map(|length| length.map(|length| length as f64)));
+        scaling_analysis.record_row(HOLDER_STORAGE_LENGTH, &algorithm_names, n_mock_claims, holder_storage_lengths.iter().map(|length| length.map(|length| length as f64)));
+        scaling_analysis.record_row(VC_VERIFICATION_DURATION, &algorithm_names, n_mock_claims, vc_verification_durations.iter().map(|duration| duration.map(|duration| duration as f64)));
 
 
         ////////////////////////////////////////////////////////////////////////////////////////////
@@ -154,72 +629,1788 @@ fn benchmark_multiple_mock_claims(max_mock_claims: usize, iterations: i8) -> Res
             let mut duration_csv_name: String = n_mock_claims.to_string();
             duration_csv_name.push('_');
             duration_csv_name.push_str(VP_ISSUANCE_DURATION);
-            writer.add_file(&duration_csv_name)?;
+            sink.add_file(&duration_csv_name, &algorithm_names)?;
 
             let mut length_csv_name: String = n_mock_claims.to_string();
             length_csv_name.push('_');
             length_csv_name.push_str(VP_JWT_LENGTH);
-            writer.add_file(&length_csv_name)?;
+            sink.add_file(&length_csv_name, &algorithm_names)?;
+
+            let compressed_length_csv_name: String = format!("{n_mock_claims}_{VP_JWT_COMPRESSED_LENGTH}");
+            sink.add_file(&compressed_length_csv_name, &algorithm_names)?;
+
+            let binary_length_csv_name: String = format!("{n_mock_claims}_{VP_BINARY_LENGTH}");
+            sink.add_file(&binary_length_csv_name, &algorithm_names)?;
+
+            let mut qr_chunks_csv_name: String = n_mock_claims.to_string();
+            qr_chunks_csv_name.push('_');
+            qr_chunks_csv_name.push_str(VP_QR_CHUNKS);
+            sink.add_file(&qr_chunks_csv_name, &algorithm_names)?;
 
             let mut verification_csv_name: String = n_mock_claims.to_string();
             verification_csv_name.push('_');
             verification_csv_name.push_str(VP_VERIFICATION_DURATION);
-            writer.add_file(&verification_csv_name)?;
+            sink.add_file(&verification_csv_name, &algorithm_names)?;
+
+            let mut cache_csv_name: String = n_mock_claims.to_string();
+            cache_csv_name.push('_');
+            cache_csv_name.push_str(VP_CACHE_HIT_RATE);
+            if verification_cache {
+                sink.add_file(&cache_csv_name, &algorithm_names)?;
+            }
+
+            let constrained_duration_csv_name: String = format!("{duration_csv_name}_{CONSTRAINED}");
+            if holder_profile.is_some() {
+                sink.add_file(&constrained_duration_csv_name, &algorithm_names)?;
+            }
 
             for n_disclosures in (1..=n_mock_claims).step_by(step) {
-                let mut vps: Vec<Map<String, Value>> = vec![];
-                let mut vp_jwts: Vec<usize> = vec![];
-                let mut vp_issuance_durations: Vec<u128> = vec![];
-                let mut vp_verification_durations: Vec<u128> = vec![];
-                create_mock_disclosures(disclosures, n_disclosures);
+                let mut vp_jwts: Vec<Option<usize>> = vec![];
+                let mut vp_compressed_jwts: Vec<Option<usize>> = vec![];
+                let mut vp_binary_lengths: Vec<Option<usize>> = vec![];
+                let mut vp_issuance_durations: Vec<Option<u128>> = vec![];
+                let mut vp_verification_durations: Vec<Option<u128>> = vec![];
+                let mut constrained_vp_issuance_durations: Vec<Option<u128>> = vec![];
+                *disclosures = claim_generator.disclosures(&(1..=n_disclosures).collect::<Vec<usize>>());
 
+                for (index, (algo, vc)) in sd_algorithms.iter().zip(vcs.iter()).enumerate() {
+                    let (issuance, verification) = match (algo, vc) {
+                        (Some(algo), Some(vc)) => {
+                            benchmark_vp(algo.as_ref(), vc, disclosures, measurement, n_mock_claims, &algorithm_names[index], &mut failure_log)
+                        }
+                        _ => { (None, None) }
+                    };
+
+                    vp_jwts.push(issuance.map(|(_, vp_jwt_length, _, _)| vp_jwt_length));
+                    vp_compressed_jwts.push(issuance.and_then(|(_, _, compressed_vp_jwt_length, _)| compressed_vp_jwt_length));
+                    vp_binary_lengths.push(issuance.and_then(|(_, _, _, vp_binary_length)| vp_binary_length));
+                    vp_issuance_durations.push(issuance.map(|(duration, _, _, _)| duration));
+                    vp_verification_durations.push(verification);
+
+                    if let Some(profile) = &holder_profile {
+                        let constrained_issuance = match (algo, vc) {
+                            (Some(algo), Some(vc)) => {
+                                let result = Benchmark::measure(|| profile.run(|| algo.issue_vp(vc, disclosures)), measurement);
+                                failure_log.check(n_mock_claims, &algorithm_names[index], "Constrained VP issuance", result)
+                            }
+                            _ => { None }
+                        };
+                        constrained_vp_issuance_durations.push(constrained_issuance.map(|(duration, _)| duration.as_micros()));
+                    }
+                }
+
+                write_serialized(sink, &duration_csv_name, &vp_issuance_durations)?;
+                write_serialized(sink, &length_csv_name, &vp_jwts)?;
+                write_serialized(sink, &compressed_length_csv_name, &vp_compressed_jwts)?;
+                write_serialized(sink, &binary_length_csv_name, &vp_binary_lengths)?;
+                let vp_qr_chunks: Vec<Option<usize>> = vp_jwts
+                    .iter()
+                    .map(|vp_jwt_length| vp_jwt_length.and_then(|vp_jwt_length| chunk_count(vp_jwt_length, QR_CAPACITY).ok()))
+                    .collect();
+                write_serialized(sink, &qr_chunks_csv_name, &vp_qr_chunks)?;
+                for (name, vp_jwt_length) in algorithm_names.iter().zip(&vp_jwts) {
+                    if let Some(vp_jwt_length) = vp_jwt_length {
+                        transfer_cost_model.record_vp(n_mock_claims, n_disclosures, name, *vp_jwt_length);
+                        feasibility_matrix.record("vp", n_mock_claims, n_disclosures, name, *vp_jwt_length);
+                    }
+                }
+                write_serialized(sink, &verification_csv_name, &vp_verification_durations)?;
+                sample_log.record_row(VP_ISSUANCE_DURATION, &algorithm_names, n_mock_claims, Some(n_disclosures), vp_issuance_durations.iter().map(|duration| duration.map(|duration| duration as f64)));
+                sample_log.record_row(VP_JWT_LENGTH, &algorithm_names, n_mock_claims, Some(n_disclosures), vp_jwts.iter().map(|length| length.map(|length| length as f64)));
+                sample_log.record_row(VP_JWT_COMPRESSED_LENGTH, &algorithm_names, n_mock_claims, Some(n_disclosures), vp_compressed_jwts.iter().map(|length| length.map(|length| length as f64)));
+                sample_log.record_row(VP_BINARY_LENGTH, &algorithm_names, n_mock_claims, Some(n_disclosures), vp_binary_lengths.iter().map(|length| length.map(|length| length as f64)));
+                sample_log.record_row(VP_QR_CHUNKS, &algorithm_names, n_mock_claims, Some(n_disclosures), vp_qr_chunks.iter().map(|chunks| chunks.map(|chunks| chunks as f64)));
+                sample_log.record_row(VP_VERIFICATION_DURATION, &algorithm_names, n_mock_claims, Some(n_disclosures), vp_verification_durations.iter().map(|duration| duration.map(|duration| duration as f64)));
+                if holder_profile.is_some() {
+                    write_serialized(sink, &constrained_duration_csv_name, &constrained_vp_issuance_durations)?;
+                }
+
+                if verification_cache {
+                    let vp_cache_hit_rates: Vec<Option<f64>> = sd_algorithms
+                        .iter()
+                        .map(|algo| algo.as_ref().map(|algo| match algo.verification_cache_statistics() {
+                            Some((hits, misses)) if hits + misses > 0 => { hits as f64 / (hits + misses) as f64 }
+                            _ => { 0f64 }
+                        }))
+                        .collect();
+                    write_serialized(sink, &cache_csv_name, &vp_cache_hit_rates)?;
+                }
+            }
+
+            // Amortized cost of deriving all the presentations above at once.
+            let disclosure_sets: Vec<Vec<String>> = (1..=n_mock_claims).step_by(step).map(|n_disclosures| {
+                claim_generator.disclosures(&(1..=n_disclosures).collect::<Vec<usize>>())
+            }).collect();
+            let mut vp_batch_issuance_durations: Vec<Option<u128>> = vec![];
+            let mut constrained_vp_batch_issuance_durations: Vec<Option<u128>> = vec![];
+
+            for (index, (algo, vc)) in sd_algorithms.iter().zip(vcs.iter()).enumerate() {
+                let duration = match (algo, vc) {
+                    (Some(algo), Some(vc)) => {
+                        let result = Benchmark::measure(|| algo.issue_vp_batch(vc, &disclosure_sets), measurement);
+                        failure_log.check(n_mock_claims, &algorithm_names[index], "VP batch issuance", result)
+                    }
+                    _ => { None }
+                };
+                vp_batch_issuance_durations.push(duration.map(|(duration, _)| duration.as_micros() / disclosure_sets.len() as u128));
+
+                if let Some(profile) = &holder_profile {
+                    let constrained_duration = match (algo, vc) {
+                        (Some(algo), Some(vc)) => {
+                            let result = Benchmark::measure(|| profile.run(|| algo.issue_vp_batch(vc, &disclosure_sets)), measurement);
+                            failure_log.check(n_mock_claims, &algorithm_names[index], "Constrained VP batch issuance", result)
+                        }
+                        _ => { None }
+                    };
+                    constrained_vp_batch_issuance_durations.push(constrained_duration.map(|(duration, _)| duration.as_micros() / disclosure_sets.len() as u128));
+                }
+            }
+
+            write_serialized(sink, VP_BATCH_ISSUANCE_DURATION, &vp_batch_issuance_durations)?;
+
+            // Operation counts of a single run of every operation, disclosing half of the claims.
+            *disclosures = claim_generator.disclosures(&(1..=n_mock_claims / 2).collect::<Vec<usize>>());
+            for (index, algo) in sd_algorithms.iter().enumerate() {
+                if let Some(algo) = algo {
+                    record_operation_counts(algo.as_ref(), raw_vc, disclosures, n_mock_claims, &algorithm_names[index], sink, &mut failure_log)?;
+                }
+            }
+            if envelope_split {
                 for (index, algo) in sd_algorithms.iter().enumerate() {
-                    let clone = vcs.get(index).unwrap().clone();
-                    let (duration, (vp, vp_jwt)) = Benchmark::benchmark_function(|| algo.issue_vp(&clone, &disclosures), iterations)?;
+                    if let Some(algo) = algo {
+                        let name: &String = &algorithm_names[index];
+                        for (operation, split) in split_envelope(algo.as_ref(), raw_vc, disclosures, measurement, n_mock_claims, name, &mut failure_log) {
+                            let n_disclosures: Option<usize> = operation.starts_with("vp").then_some(disclosures.len());
+                            write_serialized(sink, ENVELOPE_SPLIT, vec![
+                                n_mock_claims.to_string(), disclosures.len().to_string(), name.clone(), operation.to_string(),
+                                split.total.as_micros().to_string(), split.crypto().as_micros().to_string(), split.envelope.as_micros().to_string(),
+                            ])?;
+                            for (suffix, duration) in [("crypto", split.crypto()), ("envelope", split.envelope)] {
+                                sample_log.push(Sample { metric: format!("{operation}_duration_{suffix}"), algorithm: name.clone(), claims: n_mock_claims, disclosures: n_disclosures, value: duration.as_micros() as f64 });
+                            }
+                        }
+                    }
+                }
+            }
+            if holder_profile.is_some() {
+                write_serialized(sink, &constrained_batch_csv_name, &constrained_vp_batch_issuance_durations)?;
+            }
+            sample_log.record_row(VP_BATCH_ISSUANCE_DURATION, &algorithm_names, n_mock_claims, None, vp_batch_issuance_durations.iter().map(|duration| duration.map(|duration| duration as f64)));
+            scaling_analysis.record_row(VP_BATCH_ISSUANCE_DURATION, &algorithm_names, n_mock_claims, vp_batch_issuance_durations.iter().map(|duration| duration.map(|duration| duration as f64)));
+
+            for pattern in DisclosurePattern::ALL {
+                let prefix: Vec<String> = vec![n_mock_claims.to_string(), pattern.name().to_string()];
+                let mut vp_jwts: Vec<String> = prefix.clone();
+                let mut vp_issuance_durations: Vec<String> = prefix.clone();
+                let mut vp_verification_durations: Vec<String> = prefix;
+                *disclosures = claim_generator.disclosures(&pattern.indices(n_mock_claims));
 
-                    vps.push(vp.clone());
-                    vp_jwts.push(vp_jwt.len());
-                    vp_issuance_durations.push(duration.as_micros());
+                for (index, (algo, vc)) in sd_algorithms.iter().zip(vcs.iter()).enumerate() {
+                    let (issuance, verification) = match (algo, vc) {
+                        (Some(algo), Some(vc)) => {
+                            benchmark_vp(algo.as_ref(), vc, disclosures, measurement, n_mock_claims, &algorithm_names[index], &mut failure_log)
+                        }
+                        _ => { (None, None) }
+                    };
 
-                    let (duration, _) = Benchmark::benchmark_function(|| algo.verify_vp(&vp_jwt), iterations)?;
-                    vp_verification_durations.push(duration.as_micros());
+                    let name = &algorithm_names[index];
+                    if let Some((duration, vp_jwt_length, _, _)) = issuance {
+                        scaling_analysis.record(&format!("{VP_PATTERN_ISSUANCE_DURATION}_{}", pattern.name()), name, n_mock_claims, duration as f64);
+                        scaling_analysis.record(&format!("{VP_PATTERN_JWT_LENGTH}_{}", pattern.name()), name, n_mock_claims, vp_jwt_length as f64);
+                    }
+                    if let Some(duration) = verification {
+                        scaling_analysis.record(&format!("{VP_PATTERN_VERIFICATION_DURATION}_{}", pattern.name()), name, n_mock_claims, duration as f64);
+                    }
+
+                    vp_jwts.push(format_optional(issuance.map(|(_, vp_jwt_length, _, _)| vp_jwt_length)));
+                    vp_issuance_durations.push(format_optional(issuance.map(|(duration, _, _, _)| duration)));
+                    vp_verification_durations.push(format_optional(verification));
                 }
 
-                writer.write_record_to_file(&duration_csv_name, &vp_issuance_durations)?;
-                writer.write_record_to_file(&length_csv_name, &vp_jwts)?;
-                writer.write_record_to_file(&verification_csv_name, &vp_verification_durations)?;
+                write_serialized(sink, VP_PATTERN_ISSUANCE_DURATION, &vp_issuance_durations)?;
+                write_serialized(sink, VP_PATTERN_VERIFICATION_DURATION, &vp_verification_durations)?;
+                write_serialized(sink, VP_PATTERN_JWT_LENGTH, &vp_jwts)?;
+            }
+
+            // Complete exchange disclosing half of the claims, including the encoding overheads of every message.
+            *disclosures = claim_generator.disclosures(&(1..=(n_mock_claims / 2).max(1)).collect::<Vec<usize>>());
+            let mut protocol_latencies: Vec<Option<u128>> = vec![];
+
+            for (index, algo) in sd_algorithms.iter().enumerate() {
+                let name = &algorithm_names[index];
+                let exchange = algo.as_ref().and_then(|algo| {
+                    let result = transport_from_name(&transport_name).and_then(|transport| {
+                        let transport: RefCell<Box<dyn Transport>> = RefCell::new(transport);
+                        let key_cache = key_cache_for(algo.as_ref(), raw_vc, key_cache_ttl)?;
+                        let exchange = Benchmark::measure(|| run_exchange_over(algo.as_ref(), raw_vc, disclosures, None, transport.borrow_mut().as_mut(), Some(&key_cache)), measurement)?;
+                        write_serialized(sink, KEY_RESOLUTIONS, [n_mock_claims.to_string(), name.clone(), key_cache.resolutions().to_string(), key_cache.hits().to_string()])?;
+                        Ok(exchange)
+                    });
+                    failure_log.check(n_mock_claims, name, "Protocol exchange", result)
+                });
+
+                if let Some((_, (timings, statistics))) = &exchange {
+                    let mut steps: Vec<String> = vec![n_mock_claims.to_string(), name.clone()];
+                    steps.extend(timings.durations().iter().map(|duration| duration.as_micros().to_string()));
+                    steps.extend([transport_name.to_string(), statistics.bytes_on_wire.to_string(), statistics.round_trips.to_string()]);
+                    write_serialized(sink, PROTOCOL_LATENCY_STEPS, &steps)?;
+                }
+                protocol_latencies.push(exchange.map(|(duration, _)| duration.as_micros()));
             }
 
+            write_serialized(sink, PROTOCOL_LATENCY, &protocol_latencies)?;
+            sample_log.record_row(PROTOCOL_LATENCY, &algorithm_names, n_mock_claims, Some((n_mock_claims / 2).max(1)), protocol_latencies.iter().map(|duration| duration.map(|duration| duration as f64)));
+            scaling_analysis.record_row(PROTOCOL_LATENCY, &algorithm_names, n_mock_claims, protocol_latencies.iter().map(|duration| duration.map(|duration| duration as f64)));
         }
         let elapsed = now.elapsed();
-        println!("Iteration:{:>4} - Total time: {:>12?}", n_mock_claims, elapsed);
+        info!("Iteration:{:>4} - Total time: {:>12?}", n_mock_claims, elapsed);
+    }
+
+    sink.flush()?;
+    failure_log.summarize();
+    if let Some(path) = &results_db {
+        store_samples(path, &run_label, &sample_log)?;
+    }
+    if csv_output {
+        sample_log.write_csv(&SAMPLES_FILE.to_string())?;
+        ResultDocument::new(&run_label, sample_log)
+            .with_setting("measurement", &format!("{measurement:?}"))
+            .with_setting("claim_generator", claim_generator.name())
+            .with_setting("transport", &transport_name)
+            .with_setting("key_cache_ttl_ms", &key_cache_ttl.as_millis().to_string())
+            .with_setting("verification_cache", &verification_cache.to_string())
+            .with_setting("prepared_pairings", &prepared_pairings.to_string())
+            .with_setting("element_blinding", &element_blinding.to_string())
+            .with_setting("envelope_split", &envelope_split.to_string())
+            .with_setting("holder_binding", holder_binding.as_ref().map_or("es256", |holder_binding| holder_binding.name()))
+            .write(&Path::new(CSV_DIR).join(RESULTS_FILE))?;
+    }
+    scaling_analysis.write_summary(&SCALING_MODELS.to_string())?;
+    transfer_cost_model.write_summary(&TRANSFER_COSTS.to_string())?;
+    feasibility_matrix.write_matrix(&PAYLOAD_FEASIBILITY.to_string(), &algorithm_names)?;
+    for constraint in feasibility_matrix.constraints() {
+        for name in &algorithm_names {
+            match feasibility_matrix.max_feasible_claims(&constraint.name, "vp", name) {
+                Some(n_claims) => { info!("[{}] VPs fit in {} ({} bytes) up to {} claims", name, constraint.name, constraint.max_bytes, n_claims) }
+                None => { info!("[{}] No VP fits in {} ({} bytes)", name, constraint.name, constraint.max_bytes) }
+            }
+        }
     }
 
     Ok(())
 }
 
 
-pub fn main() -> Result<(), String> {
+/// Counts the cryptographic operations of a single VC issuance, VC verification, VP issuance and VP verification, and
+/// writes them to the operation counts CSV file.
+fn record_operation_counts(algo: &dyn Adapter, raw_vc: &Map<String, Value>, disclosures: &Vec<String>, n_mock_claims: usize, name: &str, sink: &mut dyn ResultSink, failure_log: &mut FailureLog) -> Result<(), String> {
 
-    match env::var("CSD_JWT_ITERATIONS") {
-        Ok(iterations_string) => {
-            println!("The environment variable CSD_JWT_ITERATIONS is set. Its string value is: \"{}\"", iterations_string);
-            let iterations = match i8::from_str(iterations_string.as_str()) {
-                Ok(iterations) => iterations,
-                Err(e) => {
-                    println!("The environment variable CSD_JWT_ITERATIONS cannot be parsed to i8. Exiting.");
-                    return Err(e.to_string())
-                }
-            };
-            println!("The benchmark will be executed for: {}", iterations);
-            benchmark_multiple_mock_claims(100, iterations)
+    let mut write_profile = |operation: &str, profile: CostProfile| {
+        let mut record: Vec<String> = vec![n_mock_claims.to_string(), disclosures.len().to_string(), name.to_string(), operation.to_string()];
+        record.extend(profile.values().iter().map(|count| count.to_string()));
+        write_serialized(sink, OPERATION_COUNTS, record)
+    };
+
+    let (issuance, profile) = CostProfile::measure(|| algo.issue_vc(raw_vc));
+    let (vc, _vc_jwt) = match failure_log.check(n_mock_claims, name, "Profiled VC issuance", issuance) {
+        Some(issuance) => { issuance }
+        None => { return Ok(()) }
+    };
+    write_profile("vc_issuance", profile)?;
+
+    let (verification, profile) = CostProfile::measure(|| algo.verify_vc(&vc));
+    if failure_log.check(n_mock_claims, name, "Profiled VC verification", verification).is_some() {
+        write_profile("vc_verification", profile)?;
+    }
+
+    let (issuance, profile) = CostProfile::measure(|| algo.issue_vp(&vc, disclosures));
+    let (_vp, vp_jwt) = match failure_log.check(n_mock_claims, name, "Profiled VP issuance", issuance) {
+        Some(issuance) => { issuance }
+        None => { return Ok(()) }
+    };
+    write_profile("vp_issuance", profile)?;
+
+    let (verification, profile) = CostProfile::measure(|| algo.verify_vp(&vp_jwt));
+    if failure_log.check(n_mock_claims, name, "Profiled VP verification", verification).is_some() {
+        write_profile("vp_verification", profile)?;
+    }
+
+    Ok(())
+}
+
+
+/// Benchmarks every operation of an algorithm again, splitting its duration between the selective disclosure mechanism
+/// and the JWT envelope, and recording any failure.
+///
+/// # Returns
+/// The operations that succeeded, each with the split of its averaged duration.
+fn split_envelope(algo: &dyn Adapter, raw_vc: &Map<String, Value>, disclosures: &Vec<String>, measurement: Measurement, n_mock_claims: usize, name: &str, failure_log: &mut FailureLog) -> Vec<(&'static str, EnvelopeSplit)> {
+    let mut splits: Vec<(&'static str, EnvelopeSplit)> = vec![];
+
+    let issuance = EnvelopeSplit::benchmark(|| algo.issue_vc(raw_vc), measurement);
+    let (split, (vc, _vc_jwt)) = match failure_log.check(n_mock_claims, name, "Split VC issuance", issuance) {
+        Some(issuance) => { issuance }
+        None => { return splits }
+    };
+    splits.push(("vc_issuance", split));
+
+    let verification = EnvelopeSplit::benchmark(|| algo.verify_vc(&vc), measurement);
+    if let Some((split, _)) = failure_log.check(n_mock_claims, name, "Split VC verification", verification) {
+        splits.push(("vc_verification", split));
+    }
+
+    let issuance = EnvelopeSplit::benchmark(|| algo.issue_vp(&vc, disclosures), measurement);
+    let (split, (_vp, vp_jwt)) = match failure_log.check(n_mock_claims, name, "Split VP issuance", issuance) {
+        Some(issuance) => { issuance }
+        None => { return splits }
+    };
+    splits.push(("vp_issuance", split));
+
+    let verification = EnvelopeSplit::benchmark(|| algo.verify_vp(&vp_jwt), measurement);
+    if let Some((split, _)) = failure_log.check(n_mock_claims, name, "Split VP verification", verification) {
+        splits.push(("vp_verification", split));
+    }
+
+    splits
+}
+
+
+/// Key cache of the verifier of the protocol exchanges with an algorithm, resolving the issuer named in the raw VC to the
+/// public key of the adapter.
+///
+/// # Returns
+/// A result containing the cache or a string containing an error if the raw VC does not name its issuer.
+fn key_cache_for(algo: &dyn Adapter, raw_vc: &Map<String, Value>, ttl: Duration) -> Result<KeyCache<StaticKeyResolver>, String> {
+    let issuer: &str = match raw_vc.get(ISSUER).and_then(|issuer| issuer.as_str()) {
+        Some(issuer) => { issuer }
+        None => { return Err(format!("Raw VC does not contain {ISSUER}")) }
+    };
+    let (issuer_public_key, _) = algo.issuer_keypair()?;
+
+    Ok(KeyCache::new(StaticKeyResolver::new().with_key(issuer, &issuer_public_key), ttl, DEFAULT_KEY_CACHE_ENTRIES))
+}
+
+
+/// Issuance duration in microseconds, VP JWT length, compressed length and binary envelope length of a VP.
+type VpIssuance = (u128, usize, Option<usize>, Option<usize>);
+
+/// Benchmarks the issuance and verification of a VP, recording any failure.
+///
+/// # Returns
+/// The issuance duration in microseconds, the VP JWT length, its length once compressed and as a binary envelope if the
+/// issuance succeeded, and the verification duration in microseconds if the verification succeeded.
+fn benchmark_vp(algo: &dyn Adapter, vc: &Map<String, Value>, disclosures: &Vec<String>, measurement: Measurement, n_mock_claims: usize, name: &str, failure_log: &mut FailureLog) -> (Option<VpIssuance>, Option<u128>) {
+
+    let issuance = Benchmark::measure(|| algo.issue_vp(vc, disclosures), measurement);
+    let (duration, (_vp, vp_jwt)) = match failure_log.check(n_mock_claims, name, "VP issuance", issuance) {
+        Some(issuance) => { issuance }
+        None => { return (None, None) }
+    };
+
+    let verification = Benchmark::measure(|| algo.verify_vp(&vp_jwt), measurement);
+    let verification_duration = failure_log.check(n_mock_claims, name, "VP verification", verification).map(|(duration, _)| duration.as_micros());
+
+    (Some((duration.as_micros(), vp_jwt.len(), compressed_jwt_length(&vp_jwt).ok(), envelope_length(&vp_jwt).ok())), verification_duration)
+}
+
+
+/// Benchmarks the presentation of every claim derived by the issuer with the hash-based algorithms, next to the
+/// presentation of the birthdate it is derived from, on the mock VC.
+fn benchmark_derived_claims(derived_claims: &DerivedClaims, measurement: Measurement) -> Result<(), String> {
 
+    let raw_vc: Map<String, Value> = setup_raw_vc()?;
+    let n_claims = raw_vc.get(CLAIMS).and_then(|claims| claims.as_object()).map(|claims| claims.len()).unwrap_or_default();
+    let adapters: Vec<Box<dyn Adapter>> = vec![
+        Box::new(SdJwtAdapter::new(n_claims)?.with_derived_claims(derived_claims.clone())),
+        Box::new(MerkleTreeAdapter::new(n_claims)?.with_derived_claims(derived_claims.clone())),
+    ];
+
+    let columns: Vec<String> = ["algorithm", "disclosed", "vp_issuance_duration", "vp_jwt_length", "vp_verification_duration"]
+        .iter()
+        .map(|column| column.to_string())
+        .collect();
+    let mut writer = CSVWriter::new(columns)?;
+    writer.add_file(&DERIVED_CLAIMS.to_string())?;
+    let mut failure_log = FailureLog::new();
+
+    let mut disclosed_claims: Vec<String> = derived_claims.claim_names();
+    disclosed_claims.push(DEFAULT_BIRTHDATE_CLAIM.to_string());
+
+    for algo in &adapters {
+        let name = algo.sd_algorithm();
+        let vc = match failure_log.check(n_claims, &name, "VC issuance", algo.issue_vc(&raw_vc)) {
+            Some((vc, _)) => { vc }
+            None => { continue }
+        };
+
+        for disclosed in &disclosed_claims {
+            let (issuance, verification) = benchmark_vp(algo.as_ref(), &vc, &vec![disclosed.clone()], measurement, n_claims, &name, &mut failure_log);
+            info!("[{}] VP disclosing {}: {} bytes", name, disclosed, format_optional(issuance.map(|(_, vp_len, _, _)| vp_len)));
+            writer.write_record_to_file(&DERIVED_CLAIMS.to_string(), vec![
+                name.clone(),
+                disclosed.clone(),
+                format_optional(issuance.map(|(duration, _, _, _)| duration)),
+                format_optional(issuance.map(|(_, vp_len, _, _)| vp_len)),
+                format_optional(verification),
+            ])?;
         }
-        Err(e) => {
-            println!("The environment variable CSD_JWT_ITERATIONS is not set. Exiting with error: {}", e);
-            Err(e.to_string())
-        },
+    }
+    failure_log.summarize();
+
+    Ok(())
+}
+
+
+/// Benchmarks CSD-JWT presentations signed with a certified ephemeral key against presentations signed with the
+/// long-term holder key, disclosing an increasing amount of claims of the mock VC, to measure the overhead of the
+/// certificate in size and time. The ephemeral issuance includes the generation and certification of the key.
+fn benchmark_ephemeral_binding(measurement: Measurement) -> Result<(), String> {
+
+    let raw_vc: Map<String, Value> = setup_raw_vc()?;
+    let claim_names: Vec<String> = raw_vc.get(CLAIMS).and_then(|claims| claims.as_object()).map(|claims| claims.keys().cloned().collect()).unwrap_or_default();
+    let adapter = CsdJwtAdapter::new(claim_names.len())?;
+    let name = adapter.sd_algorithm();
+
+    let columns: Vec<String> = [
+        "disclosed_claims", "vp_jwt_length", "ephemeral_vp_jwt_length",
+        "vp_issuance_duration", "ephemeral_vp_issuance_duration",
+        "vp_verification_duration", "ephemeral_vp_verification_duration",
+    ]
+        .iter()
+        .map(|column| column.to_string())
+        .collect();
+    let mut writer = CSVWriter::new(columns)?;
+    writer.add_file(&EPHEMERAL_BINDING.to_string())?;
+    let mut failure_log = FailureLog::new();
+
+    let vc = match failure_log.check(claim_names.len(), &name, "VC issuance", adapter.issue_vc(&raw_vc)) {
+        Some((vc, _)) => { vc }
+        None => {
+            failure_log.summarize();
+            return Ok(())
+        }
+    };
+
+    for disclosed_claims in 1..=claim_names.len() {
+        let disclosures: Vec<String> = claim_names[..disclosed_claims].to_vec();
+        let (issuance, verification) = benchmark_vp(&adapter, &vc, &disclosures, measurement, disclosed_claims, &name, &mut failure_log);
+
+        let ephemeral_issuance = Benchmark::measure(|| adapter.issue_vp_with_ephemeral_key(&vc, &disclosures), measurement);
+        let (ephemeral_issuance, ephemeral_verification) = match failure_log.check(disclosed_claims, &name, "VP issuance with ephemeral key", ephemeral_issuance) {
+            Some((duration, (_vp, vp_jwt))) => {
+                let verification = Benchmark::measure(|| adapter.verify_vp_with_ephemeral_key(&vp_jwt), measurement);
+                let verification = failure_log.check(disclosed_claims, &name, "VP verification with ephemeral key", verification).map(|(duration, _)| duration.as_micros());
+                (Some((duration.as_micros(), vp_jwt.len())), verification)
+            }
+            None => { (None, None) }
+        };
+
+        info!("[{}] VP disclosing {} claims: {} bytes, {} bytes with an ephemeral key", name, disclosed_claims, format_optional(issuance.map(|(_, vp_len, _, _)| vp_len)), format_optional(ephemeral_issuance.map(|(_, vp_len)| vp_len)));
+        writer.write_record_to_file(&EPHEMERAL_BINDING.to_string(), vec![
+            disclosed_claims.to_string(),
+            format_optional(issuance.map(|(_, vp_len, _, _)| vp_len)),
+            format_optional(ephemeral_issuance.map(|(_, vp_len)| vp_len)),
+            format_optional(issuance.map(|(duration, _, _, _)| duration)),
+            format_optional(ephemeral_issuance.map(|(duration, _)| duration)),
+            format_optional(verification),
+            format_optional(ephemeral_verification),
+        ])?;
+    }
+    failure_log.summarize();
+
+    Ok(())
+}
+
+
+/// Prints the structure of the VCs and VPs passed as arguments, either as JWTs or as paths of files containing them,
+/// and the differences between the first two.
+fn inspect(arguments: &[String]) -> Result<(), String> {
+
+    if arguments.is_empty() {
+        return Err("Usage: csd_jwt inspect <jwt or file> [<jwt or file>]".to_string());
+    }
+
+    let mut inspections: Vec<Inspection> = vec![];
+    for argument in arguments {
+        let jwt: String = if Path::new(argument).is_file() {
+            match fs::read_to_string(argument) {
+                Ok(jwt) => { jwt }
+                Err(err) => { return Err(format!("Failed to read {argument}: [{err}]")) }
+            }
+        } else {
+            argument.clone()
+        };
+
+        let inspection = Inspection::from_jwt(&jwt)?;
+        println!("{}", inspection);
+        inspections.push(inspection);
+    }
+
+    if let [first, second, ..] = inspections.as_slice() {
+        match serde_json::to_string_pretty(&first.diff(second)) {
+            Ok(diff) => { println!("Diff = {}", diff) }
+            Err(err) => { return Err(format!("Failed to serialize diff: [{err}]")) }
+        };
+    }
+
+    Ok(())
+}
+
+
+/// Prints the JWKS publishing the issuer keys of every algorithm, for credentials of the amount of claims passed as
+/// argument.
+fn jwks(arguments: &[String]) -> Result<(), String> {
+
+    let claims_len: usize = match arguments.first().map(|argument| usize::from_str(argument)) {
+        Some(Ok(claims_len)) if claims_len > 0 => { claims_len }
+        None => { DEFAULT_JWKS_CLAIMS }
+        _ => { return Err("Usage: csd_jwt jwks [<claims>]".to_string()) }
+    };
+
+    let registry = AdapterRegistry::with_all_adapters(claims_len)?;
+    let algorithms: Vec<String> = registry.algorithms();
+    let jwks = Jwks::from_adapters(algorithms.iter().filter_map(|algorithm| registry.get(algorithm)))?;
+    match serde_json::to_string_pretty(&jwks.to_value()) {
+        Ok(jwks) => { println!("{}", jwks) }
+        Err(err) => { return Err(format!("Failed to serialize JWKS: [{err}]")) }
+    };
+
+    Ok(())
+}
+
+
+/// Simulates two colluding verifiers receiving several presentations of the same credential from every algorithm, for
+/// the amount of presentations per verifier and, optionally, claims passed as arguments, and writes which fields of
+/// the presentations link them to the linkability matrix CSV file.
+fn linkability(arguments: &[String]) -> Result<(), String> {
+
+    let usage = "Usage: csd_jwt linkability [<presentations>] [<claims>]";
+    let parse = |index: usize, default: usize| -> Result<usize, String> {
+        match arguments.get(index) {
+            Some(argument) => match usize::from_str(argument) {
+                Ok(value) if value > 0 => { Ok(value) }
+                _ => { Err(format!("Invalid argument {argument}. {usage}")) }
+            },
+            None => { Ok(default) }
+        }
+    };
+    let n_presentations = parse(0, DEFAULT_PRESENTATIONS)?;
+    let n_claims = parse(1, DEFAULT_LINKABILITY_CLAIMS)?;
+
+    let claim_generator = FlatClaimGenerator;
+    let raw_vc: &mut Map<String, Value> = &mut setup_raw_vc()?;
+    claim_generator.substitute_claims(raw_vc, n_claims)?;
+    let disclosures: Vec<String> = claim_generator.disclosures(&(1..=(n_claims / 2).max(1)).collect::<Vec<usize>>());
+
+    let mut matrix = LinkabilityMatrix::new();
+    let mut failure_log = FailureLog::new();
+    for constructor in ADAPTER_CONSTRUCTORS {
+        let algo = match failure_log.check(n_claims, "unknown", "Initialization", constructor(n_claims)) {
+            Some(algo) => { algo }
+            None => { continue }
+        };
+        let name = algo.sd_algorithm();
+
+        if let Some(report) = failure_log.check(n_claims, &name, "Linkability analysis", LinkageReport::analyze(algo.as_ref(), raw_vc, &disclosures, n_presentations)) {
+            info!("[{}] Fields linking {} presentations to each verifier: {:?}", name, n_presentations, report.fields_with(Linkage::Linkable));
+            matrix.add(report);
+        }
+    }
+
+    matrix.write_matrix(&LINKABILITY.to_string())?;
+    failure_log.summarize();
+
+    Ok(())
+}
+
+
+/// Measures how many presentations per second every algorithm verifies on concurrent worker threads sharing the same
+/// verifier, for the amount of workers, seconds and, optionally, claims passed as arguments.
+fn throughput(arguments: &[String]) -> Result<(), String> {
+
+    let usage = "Usage: csd_jwt throughput <workers> <seconds> [<claims>]";
+    let parse = |index: usize, default: Option<usize>| -> Result<usize, String> {
+        match (arguments.get(index), default) {
+            (Some(argument), _) => match usize::from_str(argument) {
+                Ok(value) if value > 0 => { Ok(value) }
+                _ => { Err(format!("Invalid argument {argument}. {usage}")) }
+            },
+            (None, Some(default)) => { Ok(default) }
+            (None, None) => { Err(usage.to_string()) }
+        }
+    };
+    let workers = parse(0, None)?;
+    let seconds = parse(1, None)?;
+    let n_claims = parse(2, Some(10))?;
+
+    let verification_cache = env::var("CSD_JWT_VERIFICATION_CACHE").is_ok();
+    let prepared_pairings = env::var("CSD_JWT_PREPARED_PAIRINGS").is_ok();
+    let element_blinding = env::var("CSD_JWT_ELEMENT_BLINDING").is_ok();
+    let holder_binding: Option<Arc<dyn HolderBinding>> = holder_binding_from_env()?;
+    let claim_generator = FlatClaimGenerator;
+    let raw_vc: &mut Map<String, Value> = &mut setup_raw_vc()?;
+    claim_generator.substitute_claims(raw_vc, n_claims)?;
+    let disclosures: Vec<String> = claim_generator.disclosures(&(1..=(n_claims / 2).max(1)).collect::<Vec<usize>>());
+
+    let mut algorithm_names: Vec<String> = vec![];
+    let mut throughputs: Vec<Option<f64>> = vec![];
+    let mut percentile_rows: Vec<Vec<String>> = LATENCY_PERCENTILES.iter().map(|percentile| vec![format!("p{percentile}")]).collect();
+    let mut failure_log = FailureLog::new();
+
+    for initialization in initialize_sd_algorithms(n_claims, Measurement::Iterations(1), verification_cache, prepared_pairings, element_blinding, holder_binding.as_ref()) {
+        let (_, algo) = initialization?;
+        let name = algo.sd_algorithm();
+
+        let run = || -> Result<Throughput, String> {
+            let (vc, _) = algo.issue_vc(raw_vc)?;
+            let vp_jwts: Vec<String> = (0..THROUGHPUT_PRESENTATIONS)
+                .map(|_| algo.issue_vp(&vc, &disclosures).map(|(_, vp_jwt)| vp_jwt))
+                .collect::<Result<Vec<String>, String>>()?;
+
+            let throughput = verification_throughput(algo.as_ref(), &vp_jwts, workers, Duration::from_secs(seconds as u64))?;
+            info!("[{}] {} verifications in {:?} on {} workers: {:.1} verifications/s", name, throughput.verifications, throughput.elapsed, workers, throughput.per_second());
+            Ok(throughput)
+        };
+
+        let throughput = failure_log.check(n_claims, &name, "Verification throughput", run());
+        for (row, percentile) in percentile_rows.iter_mut().zip(LATENCY_PERCENTILES) {
+            let latency = throughput.as_ref().and_then(|throughput| throughput.percentile(percentile));
+            if let Some(latency) = latency {
+                info!("[{}] p{} verification latency under load: {:?}", name, percentile, latency);
+            }
+            row.push(format_optional(latency.map(|latency| latency.as_micros())));
+        }
+        throughputs.push(throughput.map(|throughput| throughput.per_second()));
+        algorithm_names.push(name);
     }
 
+    let mut percentile_columns: Vec<String> = vec!["percentile".to_string()];
+    percentile_columns.extend(algorithm_names.iter().cloned());
+    let mut percentile_writer = CSVWriter::new(percentile_columns)?;
+    percentile_writer.add_file(&VERIFICATION_LATENCY_PERCENTILES.to_string())?;
+    for row in &percentile_rows {
+        percentile_writer.write_record_to_file(&VERIFICATION_LATENCY_PERCENTILES.to_string(), row)?;
+    }
 
+    let mut writer = CSVWriter::new(algorithm_names)?;
+    writer.add_file(&VERIFICATION_THROUGHPUT.to_string())?;
+    writer.write_record_to_file(&VERIFICATION_THROUGHPUT.to_string(), &throughputs)?;
+    failure_log.summarize();
+
+    Ok(())
+}
+
+
+/// Measures how every algorithm copes with a verifier load mixing valid presentations with malformed and tampered
+/// ones, for the amount of workers, seconds, percentage of malformed presentations and, optionally, claims passed as
+/// arguments. For every algorithm, the throughput and latency percentiles of accepting the valid presentations and of
+/// rejecting every kind of malformed one are written, together with the presentations misclassified.
+fn robustness(arguments: &[String]) -> Result<(), String> {
+
+    let usage = "Usage: csd_jwt robustness <workers> <seconds> <malformed percent> [<claims>]";
+    let parse = |index: usize, default: Option<usize>| -> Result<usize, String> {
+        match (arguments.get(index), default) {
+            (Some(argument), _) => match usize::from_str(argument) {
+                Ok(value) => { Ok(value) }
+                _ => { Err(format!("Invalid argument {argument}. {usage}")) }
+            },
+            (None, Some(default)) => { Ok(default) }
+            (None, None) => { Err(usage.to_string()) }
+        }
+    };
+    let workers = parse(0, None)?;
+    let seconds = parse(1, None)?;
+    let malformed_percent = parse(2, None)?;
+    let n_claims = parse(3, Some(10))?;
+    if workers == 0 || seconds == 0 || n_claims == 0 || malformed_percent > 100 {
+        return Err(format!("Invalid arguments. {usage}"));
+    }
+
+    let claim_generator = FlatClaimGenerator;
+    let raw_vc: &mut Map<String, Value> = &mut setup_raw_vc()?;
+    claim_generator.substitute_claims(raw_vc, n_claims)?;
+    let disclosures: Vec<String> = claim_generator.disclosures(&(1..=(n_claims / 2).max(1)).collect::<Vec<usize>>());
+
+    let mut columns: Vec<String> = vec!["algorithm".to_string(), "input".to_string(), "verifications".to_string(), "verifications_per_second".to_string()];
+    columns.extend(LATENCY_PERCENTILES.iter().map(|percentile| format!("p{percentile}_us")));
+    columns.push("misclassified".to_string());
+    let mut writer = CSVWriter::new(columns)?;
+    writer.add_file(&VERIFICATION_ROBUSTNESS.to_string())?;
+    let mut failure_log = FailureLog::new();
+
+    for constructor in ADAPTER_CONSTRUCTORS {
+        let algo = match failure_log.check(n_claims, "unknown", "Initialization", constructor(n_claims)) {
+            Some(algo) => { algo }
+            None => { continue }
+        };
+        let name = algo.sd_algorithm();
+
+        let run = || -> Result<RobustnessReport, String> {
+            let (vc, _) = algo.issue_vc(raw_vc)?;
+            let vp_jwts: Vec<String> = (0..THROUGHPUT_PRESENTATIONS)
+                .map(|_| algo.issue_vp(&vc, &disclosures).map(|(_, vp_jwt)| vp_jwt))
+                .collect::<Result<Vec<String>, String>>()?;
+
+            verification_robustness(algo.as_ref(), &vp_jwts, malformed_percent as f64 / 100f64, workers, Duration::from_secs(seconds as u64))
+        };
+
+        let report = match failure_log.check(n_claims, &name, "Verification robustness", run()) {
+            Some(report) => { report }
+            None => { continue }
+        };
+        info!("[{}] {:.1} verifications/s with {}% malformed presentations on {} workers", name, report.per_second(), malformed_percent, workers);
+        if !report.is_sound() {
+            warn!("[{}] {} valid presentations rejected, {} malformed presentations accepted, {} verifications panicked", name,
+                report.false_rejections, report.false_acceptances.values().sum::<usize>(), report.panics);
+        }
+
+        let mut inputs: Vec<(&str, Option<&Throughput>, usize)> = vec![("valid", Some(&report.accepted), report.false_rejections)];
+        for malformation in Malformation::ALL {
+            inputs.push((malformation.name(), report.rejected.get(&malformation), report.false_acceptances.get(&malformation).copied().unwrap_or_default()));
+        }
+
+        for (input, throughput, misclassified) in inputs {
+            let mut record: Vec<String> = vec![
+                name.clone(),
+                input.to_string(),
+                throughput.map(|throughput| throughput.verifications).unwrap_or_default().to_string(),
+                format_optional(throughput.map(|throughput| throughput.per_second())),
+            ];
+            record.extend(LATENCY_PERCENTILES.iter().map(|percentile| format_optional(throughput.and_then(|throughput| throughput.percentile(*percentile)).map(|latency| latency.as_micros()))));
+            record.push(misclassified.to_string());
+            writer.write_record_to_file(&VERIFICATION_ROBUSTNESS.to_string(), &record)?;
+        }
+    }
+
+    failure_log.summarize();
+
+    Ok(())
+}
+
+
+/// Compares the issuance throughput of every algorithm when the issuer generates fresh keys and parameters for every
+/// credential, as every adapter of the sweep does, with the one of an issuer reusing the same keys and parameters for
+/// all the credentials. Both regimes issue the same amount of credentials of the mock VC and include their setups.
+fn benchmark_issuer_key_reuse(credentials: usize) -> Result<(), String> {
+
+    let raw_vc: Map<String, Value> = setup_raw_vc()?;
+    let n_claims: usize = raw_vc.get(CLAIMS).and_then(|claims| claims.as_object()).map(|claims| claims.len()).unwrap_or_default();
+
+    let columns: Vec<String> = ["algorithm", "credentials", "fresh_credentials_per_second", "reused_credentials_per_second", "speedup"]
+        .iter()
+        .map(|column| column.to_string())
+        .collect();
+    let mut writer = CSVWriter::new(columns)?;
+    writer.add_file(&ISSUER_KEY_REUSE.to_string())?;
+    let mut failure_log = FailureLog::new();
+
+    for constructor in ADAPTER_CONSTRUCTORS {
+        let name = match failure_log.check(n_claims, "unknown", "Initialization", constructor(n_claims)) {
+            Some(adapter) => { adapter.sd_algorithm() }
+            None => { continue }
+        };
+
+        let fresh = || -> Result<f64, String> {
+            let start = Instant::now();
+            for _ in 0..credentials {
+                constructor(n_claims)?.issue_vc(&raw_vc)?;
+            }
+            Ok(credentials as f64 / start.elapsed().as_secs_f64())
+        };
+        let reused = || -> Result<f64, String> {
+            let start = Instant::now();
+            let adapter = constructor(n_claims)?;
+            for _ in 0..credentials {
+                adapter.issue_vc(&raw_vc)?;
+            }
+            Ok(credentials as f64 / start.elapsed().as_secs_f64())
+        };
+
+        let fresh = failure_log.check(n_claims, &name, "Issuance with fresh keys", fresh());
+        let reused = failure_log.check(n_claims, &name, "Issuance with reused keys", reused());
+        let speedup = fresh.zip(reused).map(|(fresh, reused)| reused / fresh);
+        info!("[{}] {} credentials: {} credentials/s with fresh keys, {} credentials/s with reused keys", name, credentials, format_optional(fresh), format_optional(reused));
+        writer.write_record_to_file(&ISSUER_KEY_REUSE.to_string(), vec![
+            name,
+            credentials.to_string(),
+            format_optional(fresh),
+            format_optional(reused),
+            format_optional(speedup),
+        ])?;
+    }
+    failure_log.summarize();
+
+    Ok(())
+}
+
+
+/// Benchmarks the update of a credential for every algorithm: the first issuance of the mock VC next to its
+/// re-issuance with one updated claim, which also verifies the replaced VC and keeps its holder binding.
+fn benchmark_reissuance(measurement: Measurement) -> Result<(), String> {
+
+    let raw_vc: Map<String, Value> = setup_raw_vc()?;
+    let n_claims: usize = raw_vc.get(CLAIMS).and_then(|claims| claims.as_object()).map(|claims| claims.len()).unwrap_or_default();
+    let updated_claims: Map<String, Value> = raw_vc.get(CLAIMS)
+        .and_then(|claims| claims.as_object())
+        .and_then(|claims| claims.keys().next())
+        .map(|name| Map::from_iter([(name.clone(), Value::String("Updated value".to_string()))]))
+        .unwrap_or_default();
+
+    let columns: Vec<String> = ["algorithm", "vc_issuance_duration", "vc_reissuance_duration", "vc_jwt_length", "reissued_vc_jwt_length"]
+        .iter()
+        .map(|column| column.to_string())
+        .collect();
+    let mut writer = CSVWriter::new(columns)?;
+    writer.add_file(&REISSUANCE.to_string())?;
+    let mut failure_log = FailureLog::new();
+
+    for constructor in ADAPTER_CONSTRUCTORS {
+        let adapter = match failure_log.check(n_claims, "unknown", "Initialization", constructor(n_claims)) {
+            Some(adapter) => { adapter }
+            None => { continue }
+        };
+        let name = adapter.sd_algorithm();
+
+        let issuance = failure_log.check(n_claims, &name, "VC issuance", Benchmark::measure(|| adapter.issue_vc(&raw_vc), measurement));
+        let reissuance = match &issuance {
+            Some((_, (vc, _))) => { failure_log.check(n_claims, &name, "VC re-issuance", Benchmark::measure(|| adapter.reissue_vc(vc, &updated_claims), measurement)) }
+            None => { None }
+        };
+
+        info!("[{}] Issuance in {} us, re-issuance in {} us", name, format_optional(issuance.as_ref().map(|(duration, _)| duration.as_micros())), format_optional(reissuance.as_ref().map(|(duration, _)| duration.as_micros())));
+        writer.write_record_to_file(&REISSUANCE.to_string(), vec![
+            name,
+            format_optional(issuance.as_ref().map(|(duration, _)| duration.as_micros())),
+            format_optional(reissuance.as_ref().map(|(duration, _)| duration.as_micros())),
+            format_optional(issuance.as_ref().map(|(_, (_, vc_jwt))| vc_jwt.len())),
+            format_optional(reissuance.as_ref().map(|(_, (_, vc_jwt))| vc_jwt.len())),
+        ])?;
+    }
+    failure_log.summarize();
+
+    Ok(())
+}
+
+
+/// Validates the size estimation of every algorithm against the lengths of the VCs and VPs it actually issues, for VPs
+/// disclosing one claim, half of the claims and all of them, recording the relative errors and the duration of the
+/// estimation.
+fn benchmark_size_estimation(measurement: Measurement) -> Result<(), String> {
+
+    let raw_vc: Map<String, Value> = setup_raw_vc()?;
+    let claim_names: Vec<String> = raw_vc.get(CLAIMS).and_then(|claims| claims.as_object()).map(|claims| claims.keys().cloned().collect()).unwrap_or_default();
+    let n_claims: usize = claim_names.len();
+    let disclosure_sets: Vec<Vec<String>> = [1, n_claims.div_ceil(2), n_claims]
+        .iter()
+        .map(|n_disclosures| claim_names[..(*n_disclosures).min(n_claims)].to_vec())
+        .collect();
+
+    let columns: Vec<String> = ["algorithm", "n_disclosures", "estimation_duration", "estimated_vc_jwt_length", "vc_jwt_length", "vc_error", "estimated_vp_jwt_length", "vp_jwt_length", "vp_error"]
+        .iter()
+        .map(|column| column.to_string())
+        .collect();
+    let mut writer = CSVWriter::new(columns)?;
+    writer.add_file(&SIZE_ESTIMATION.to_string())?;
+    let mut failure_log = FailureLog::new();
+
+    for constructor in ADAPTER_CONSTRUCTORS {
+        let adapter = match failure_log.check(n_claims, "unknown", "Initialization", constructor(n_claims)) {
+            Some(adapter) => { adapter }
+            None => { continue }
+        };
+        let name = adapter.sd_algorithm();
+        let issued = failure_log.check(n_claims, &name, "VC issuance", adapter.issue_vc(&raw_vc));
+
+        for disclosures in &disclosure_sets {
+            let estimate = failure_log.check(n_claims, &name, "Size estimation", Benchmark::measure(|| adapter.estimate_sizes(&raw_vc, disclosures), measurement));
+            let vp_jwt_length: Option<usize> = match &issued {
+                Some((vc, _)) => { failure_log.check(n_claims, &name, "VP issuance", adapter.issue_vp(vc, disclosures)).map(|(_, vp_jwt)| vp_jwt.len()) }
+                None => { None }
+            };
+            let vc_jwt_length: Option<usize> = issued.as_ref().map(|(_, vc_jwt)| vc_jwt.len());
+            let vc_error: Option<f64> = estimate.as_ref().zip(vc_jwt_length).map(|((_, estimate), measured)| SizeEstimate::relative_error(estimate.vc_jwt_length, measured));
+            let vp_error: Option<f64> = estimate.as_ref().zip(vp_jwt_length).map(|((_, estimate), measured)| SizeEstimate::relative_error(estimate.vp_jwt_length, measured));
+
+            info!("[{}] Size estimation with {} disclosures off by {} for the VC and {} for the VP", name, disclosures.len(), format_optional(vc_error), format_optional(vp_error));
+            writer.write_record_to_file(&SIZE_ESTIMATION.to_string(), vec![
+                name.clone(),
+                disclosures.len().to_string(),
+                format_optional(estimate.as_ref().map(|(duration, _)| duration.as_micros())),
+                format_optional(estimate.as_ref().map(|(_, estimate)| estimate.vc_jwt_length)),
+                format_optional(vc_jwt_length),
+                format_optional(vc_error),
+                format_optional(estimate.as_ref().map(|(_, estimate)| estimate.vp_jwt_length)),
+                format_optional(vp_jwt_length),
+                format_optional(vp_error),
+            ])?;
+        }
+    }
+    failure_log.summarize();
+
+    Ok(())
+}
+
+
+/// Audits the randomness consumed by every algorithm: the amount of requests for random bytes and of bytes drawn by
+/// the creation of the adapter (keys and setup parameters), the issuance of a VC, and the issuance and verification of
+/// a VP disclosing every claim.
+fn benchmark_entropy_consumption() -> Result<(), String> {
+
+    let raw_vc: Map<String, Value> = setup_raw_vc()?;
+    let claim_names: Vec<String> = raw_vc.get(CLAIMS).and_then(|claims| claims.as_object()).map(|claims| claims.keys().cloned().collect()).unwrap_or_default();
+    let n_claims: usize = claim_names.len();
+
+    let columns: Vec<String> = ["algorithm", "operation", "requests", "bytes"]
+        .iter()
+        .map(|column| column.to_string())
+        .collect();
+    let mut writer = CSVWriter::new(columns)?;
+    writer.add_file(&ENTROPY_CONSUMPTION.to_string())?;
+    let mut failure_log = FailureLog::new();
+
+    let auditor: Arc<AuditingEntropy> = Arc::new(AuditingEntropy::new(Arc::new(OsEntropy)));
+    let _guard = install_source(auditor.clone());
+
+    for constructor in ADAPTER_CONSTRUCTORS {
+        auditor.take();
+        let adapter = match failure_log.check(n_claims, "unknown", "Initialization", constructor(n_claims)) {
+            Some(adapter) => { adapter }
+            None => { continue }
+        };
+        let name = adapter.sd_algorithm();
+        // The creation of the adapter runs outside of any scope, since its algorithm is only known afterwards.
+        let mut consumption: BTreeMap<EntropyScope, EntropyConsumption> = auditor.take()
+            .into_iter()
+            .map(|(scope, consumption)| if scope == EntropyScope::default() {
+                (EntropyScope { algorithm: name.clone(), operation: "Initialization".to_string() }, consumption)
+            } else {
+                (scope, consumption)
+            })
+            .collect();
+
+        if let Some((vc, _vc_jwt)) = failure_log.check(n_claims, &name, "VC issuance", adapter.issue_vc(&raw_vc)) {
+            if let Some((_vp, vp_jwt)) = failure_log.check(n_claims, &name, "VP issuance", adapter.issue_vp(&vc, &claim_names)) {
+                failure_log.check(n_claims, &name, "VP verification", adapter.verify_vp(&vp_jwt));
+            }
+        }
+        consumption.extend(auditor.take());
+
+        for (scope, consumption) in consumption {
+            let algorithm: &str = if scope.algorithm == UNSCOPED { &name } else { &scope.algorithm };
+            info!("[{}] {} drew {} random bytes in {} requests", algorithm, scope.operation, consumption.bytes, consumption.requests);
+            writer.write_record_to_file(&ENTROPY_CONSUMPTION.to_string(), vec![
+                algorithm.to_string(),
+                scope.operation.clone(),
+                consumption.requests.to_string(),
+                consumption.bytes.to_string(),
+            ])?;
+        }
+    }
+    failure_log.summarize();
+
+    Ok(())
+}
+
+
+/// Benchmarks the digest-heavy hash-based algorithms, SD-JWT and Merkle Tree, for amounts of claims doubling from 64 up
+/// to the given amount, recording the VC issuance and VP verification durations with the hashing backend of the build.
+/// The backend is part of the file name, so that running the benchmark with and without the `fast-hash` feature yields
+/// two files to compare.
+fn benchmark_hash_backend(max_claims: usize, measurement: Measurement) -> Result<(), String> {
+
+    let hash_algorithms: [AdapterConstructor; 2] = [
+        |claims_len| SdJwtAdapter::new(claims_len).map(|adapter| Box::new(adapter) as Box<dyn Adapter>),
+        |claims_len| MerkleTreeAdapter::new(claims_len).map(|adapter| Box::new(adapter) as Box<dyn Adapter>),
+    ];
+    let filename: String = format!("{HASH_BACKEND}_{}", hashing::BACKEND);
+
+    let columns: Vec<String> = ["backend", "algorithm", "claims", "vc_issuance_duration", "vp_verification_duration"]
+        .iter()
+        .map(|column| column.to_string())
+        .collect();
+    let mut writer = CSVWriter::new(columns)?;
+    writer.add_file(&filename)?;
+    let mut failure_log = FailureLog::new();
+    let claim_generator = FlatClaimGenerator;
+
+    let claim_counts: Vec<usize> = std::iter::successors(Some(64usize), |n_claims| Some(n_claims * 2))
+        .take_while(|n_claims| *n_claims <= max_claims)
+        .collect();
+    for n_claims in claim_counts {
+        let raw_vc: &mut Map<String, Value> = &mut setup_raw_vc()?;
+        claim_generator.substitute_claims(raw_vc, n_claims)?;
+        let disclosures: Vec<String> = claim_generator.disclosures(&(1..=n_claims / 2).collect::<Vec<usize>>());
+
+        for constructor in hash_algorithms {
+            let adapter = match failure_log.check(n_claims, "unknown", "Initialization", constructor(n_claims)) {
+                Some(adapter) => { adapter }
+                None => { continue }
+            };
+            let name = adapter.sd_algorithm();
+
+            let issuance = failure_log.check(n_claims, &name, "VC issuance", Benchmark::measure(|| adapter.issue_vc(raw_vc), measurement));
+            let verification = issuance.as_ref()
+                .and_then(|(_, (vc, _))| failure_log.check(n_claims, &name, "VP issuance", adapter.issue_vp(vc, &disclosures)))
+                .and_then(|(_, vp_jwt)| failure_log.check(n_claims, &name, "VP verification", Benchmark::measure(|| adapter.verify_vp(&vp_jwt), measurement)));
+
+            let issuance: Option<u128> = issuance.map(|(duration, _)| duration.as_micros());
+            let verification: Option<u128> = verification.map(|(duration, _)| duration.as_micros());
+            info!("[{}] {} claims with the {} hashing backend: VC issuance in {} us, VP verification in {} us", name, n_claims, hashing::BACKEND, format_optional(issuance), format_optional(verification));
+            writer.write_record_to_file(&filename, vec![
+                hashing::BACKEND.to_string(),
+                name,
+                n_claims.to_string(),
+                format_optional(issuance),
+                format_optional(verification),
+            ])?;
+        }
+    }
+    failure_log.summarize();
+
+    Ok(())
+}
+
+
+/// Benchmarks the revocation of an increasing amount of claims of a CSD-JWT credential: the length of the witness update
+/// message the issuer publishes, the time taken by the issuer to create it and by the holder to apply it.
+fn benchmark_witness_updates(n_claims: usize, measurement: Measurement) -> Result<(), String> {
+
+    let claim_generator = FlatClaimGenerator;
+    let raw_vc: &mut Map<String, Value> = &mut setup_raw_vc()?;
+    claim_generator.substitute_claims(raw_vc, n_claims)?;
+    let claim_names: Vec<String> = raw_vc.get(CLAIMS).and_then(|claims| claims.as_object()).map(|claims| claims.keys().cloned().collect()).unwrap_or_default();
+    let adapter = CsdJwtAdapter::new(n_claims)?;
+    let name = adapter.sd_algorithm();
+    let (signing_public_key, signing_private_key) = CommonData::issuer_keys()?;
+
+    let columns: Vec<String> = ["revoked_claims", "update_message_length", "revocation_duration", "witness_update_duration"]
+        .iter()
+        .map(|column| column.to_string())
+        .collect();
+    let mut writer = CSVWriter::new(columns)?;
+    writer.add_file(&WITNESS_UPDATES.to_string())?;
+    let mut failure_log = FailureLog::new();
+
+    let vc = match failure_log.check(n_claims, &name, "VC issuance", adapter.issue_vc(raw_vc)) {
+        Some((vc, _)) => { vc }
+        None => {
+            failure_log.summarize();
+            return Ok(())
+        }
+    };
+
+    // At least one claim is kept, so that the updated VC can still be presented.
+    for revoked_claims in 1..claim_names.len() {
+        let revoked: &[String] = &claim_names[..revoked_claims];
+        let revocation = Benchmark::measure(|| adapter.revoke_claims(&vc, revoked, &signing_private_key), measurement);
+        let (revocation, update) = match failure_log.check(revoked_claims, &name, "Claim revocation", revocation) {
+            Some((duration, update_jwt)) => {
+                let update = Benchmark::measure(|| adapter.apply_witness_update(&vc, &update_jwt, &signing_public_key), measurement);
+                let update = failure_log.check(revoked_claims, &name, "Witness update", update).map(|(duration, _)| duration.as_micros());
+                (Some((duration.as_micros(), update_jwt.len())), update)
+            }
+            None => { (None, None) }
+        };
+
+        info!("[{}] Revoking {} of {} claims: update message of {} bytes", name, revoked_claims, claim_names.len(), format_optional(revocation.map(|(_, length)| length)));
+        writer.write_record_to_file(&WITNESS_UPDATES.to_string(), vec![
+            revoked_claims.to_string(),
+            format_optional(revocation.map(|(_, length)| length)),
+            format_optional(revocation.map(|(duration, _)| duration)),
+            format_optional(update),
+        ])?;
+    }
+    failure_log.summarize();
+
+    Ok(())
+}
+
+
+/// Benchmarks the holders of every algorithm answering the same presentation request repeatedly, disclosing half the
+/// claims of the mock VC to the same verifier, once deriving a presentation for every request and once reusing them
+/// according to a policy.
+fn benchmark_presentation_reuse(policy: ReusePolicy) -> Result<(), String> {
+
+    let raw_vc: Map<String, Value> = setup_raw_vc()?;
+    let claim_names: Vec<String> = raw_vc.get(CLAIMS).and_then(|claims| claims.as_object()).map(|claims| claims.keys().cloned().collect()).unwrap_or_default();
+    let n_claims: usize = claim_names.len();
+    let disclosures: Vec<String> = claim_names[..n_claims.div_ceil(2)].to_vec();
+    let ttl: String = match policy {
+        ReusePolicy::Never => { "0".to_string() }
+        ReusePolicy::WithinTtl(ttl) => { ttl.as_millis().to_string() }
+    };
+
+    let columns: Vec<String> = ["algorithm", "ttl_ms", "requests", "uncached_vp_issuance_duration", "cached_vp_issuance_duration", "derived_presentations"]
+        .iter()
+        .map(|column| column.to_string())
+        .collect();
+    let mut writer = CSVWriter::new(columns)?;
+    writer.add_file(&PRESENTATION_REUSE.to_string())?;
+    let mut failure_log = FailureLog::new();
+
+    let results = [
+        (SdJwtInstance::NAME, presentation_reuse_durations::<SdJwtInstance>(&raw_vc, &disclosures, policy)),
+        (MerkleTreeInstance::NAME, presentation_reuse_durations::<MerkleTreeInstance>(&raw_vc, &disclosures, policy)),
+        (BBSPlusInstance::NAME, presentation_reuse_durations::<BBSPlusInstance>(&raw_vc, &disclosures, policy)),
+        (CsdJwtInstance::NAME, presentation_reuse_durations::<CsdJwtInstance>(&raw_vc, &disclosures, policy)),
+        (CsdJwtMultiShow::NAME, presentation_reuse_durations::<CsdJwtMultiShow>(&raw_vc, &disclosures, policy)),
+        (CsdJwtAggregated::NAME, presentation_reuse_durations::<CsdJwtAggregated>(&raw_vc, &disclosures, policy)),
+    ];
+    for (name, result) in results {
+        let durations = failure_log.check(n_claims, name, "Presentation reuse", result);
+        info!("[{}] {} identical requests: {} us per uncached presentation, {} us per presentation under the reuse policy", name, PRESENTATION_REUSE_REQUESTS, format_optional(durations.map(|(uncached, _, _)| uncached)), format_optional(durations.map(|(_, cached, _)| cached)));
+        writer.write_record_to_file(&PRESENTATION_REUSE.to_string(), vec![
+            name.to_string(),
+            ttl.clone(),
+            PRESENTATION_REUSE_REQUESTS.to_string(),
+            format_optional(durations.map(|(uncached, _, _)| uncached)),
+            format_optional(durations.map(|(_, cached, _)| cached)),
+            format_optional(durations.map(|(_, _, derived)| derived)),
+        ])?;
+    }
+    failure_log.summarize();
+
+    Ok(())
+}
+
+/// Issues the mock VC to a holder of an algorithm, which then answers the same presentation request repeatedly, first
+/// without reusing presentations and then under the reuse policy.
+///
+/// # Returns
+/// A result containing the mean durations of a request in microseconds without and with the policy, and the amount of
+/// presentations derived under the policy, or a string containing an error.
+fn presentation_reuse_durations<A: RoleAlgorithm>(raw_vc: &Map<String, Value>, disclosures: &Vec<String>, policy: ReusePolicy) -> Result<(u128, u128, usize), String> {
+    let claims_len: usize = raw_vc.get(CLAIMS).and_then(|claims| claims.as_object()).map(|claims| claims.len()).unwrap_or_default();
+    let issuer: Issuer<A> = Issuer::new(claims_len)?;
+    let holder: Holder<A> = Holder::new(issuer.public_parameters().clone())?;
+    let (vc, _vc_jwt) = issuer.issue_vc(raw_vc, holder.public_key())?;
+    let audience: Option<String> = A::AUDIENCE_BINDING.then(|| PRESENTATION_REUSE_AUDIENCE.to_string());
+
+    let mean_duration = |holder: &Holder<A>| -> Result<u128, String> {
+        let start = Instant::now();
+        for _ in 0..PRESENTATION_REUSE_REQUESTS {
+            holder.issue_vp(&vc, disclosures, audience.clone())?;
+        }
+        Ok(start.elapsed().as_micros() / PRESENTATION_REUSE_REQUESTS as u128)
+    };
+
+    let uncached: u128 = mean_duration(&holder)?;
+    let holder: Holder<A> = holder.with_presentation_reuse(policy, DEFAULT_PRESENTATION_CACHE_ENTRIES);
+    let cached: u128 = mean_duration(&holder)?;
+
+    Ok((uncached, cached, holder.presentation_cache().derivations()))
+}
+
+
+/// Simulates the lifetime of credentials of every algorithm valid for a given amount of days: the mock VC is issued and
+/// presented once, and the presentation is verified every simulated day until the day after its expiration, without
+/// sleeping, writing whether it is accepted and the violations found to `credential_lifetime.csv`.
+fn benchmark_credential_lifetime(validity_days: u64) -> Result<(), String> {
+
+    let raw_vc: Map<String, Value> = setup_raw_vc()?;
+    let claim_names: Vec<String> = raw_vc.get(CLAIMS).and_then(|claims| claims.as_object()).map(|claims| claims.keys().cloned().collect()).unwrap_or_default();
+    let n_claims: usize = claim_names.len();
+    let disclosures: Vec<String> = claim_names[..n_claims.div_ceil(2)].to_vec();
+
+    let columns: Vec<String> = ["algorithm", "validity_days", "day", "valid", "violations"]
+        .iter()
+        .map(|column| column.to_string())
+        .collect();
+    let mut writer = CSVWriter::new(columns)?;
+    writer.add_file(&CREDENTIAL_LIFETIME.to_string())?;
+    let mut failure_log = FailureLog::new();
+
+    let results = [
+        (SdJwtInstance::NAME, credential_lifetime_reports::<SdJwtInstance>(&raw_vc, &disclosures, validity_days)),
+        (MerkleTreeInstance::NAME, credential_lifetime_reports::<MerkleTreeInstance>(&raw_vc, &disclosures, validity_days)),
+        (BBSPlusInstance::NAME, credential_lifetime_reports::<BBSPlusInstance>(&raw_vc, &disclosures, validity_days)),
+        (CsdJwtInstance::NAME, credential_lifetime_reports::<CsdJwtInstance>(&raw_vc, &disclosures, validity_days)),
+        (CsdJwtMultiShow::NAME, credential_lifetime_reports::<CsdJwtMultiShow>(&raw_vc, &disclosures, validity_days)),
+        (CsdJwtAggregated::NAME, credential_lifetime_reports::<CsdJwtAggregated>(&raw_vc, &disclosures, validity_days)),
+    ];
+    for (name, result) in results {
+        let reports = match failure_log.check(n_claims, name, "Credential lifetime", result) {
+            Some(reports) => { reports }
+            None => { continue }
+        };
+        let accepted_days: usize = reports.iter().filter(|(_, valid, _)| *valid).count();
+        info!("[{}] Presentation accepted on {} of {} simulated days", name, accepted_days, reports.len());
+        for (day, valid, violations) in reports {
+            writer.write_record_to_file(&CREDENTIAL_LIFETIME.to_string(), vec![
+                name.to_string(),
+                validity_days.to_string(),
+                day.to_string(),
+                valid.to_string(),
+                violations.to_string(),
+            ])?;
+        }
+    }
+    failure_log.summarize();
+
+    Ok(())
+}
+
+/// Issues the mock VC of an algorithm with the given validity under a simulated clock shared by the issuer and the
+/// verifier, then verifies a single presentation of it every simulated day.
+///
+/// # Returns
+/// A result containing, for every simulated day, whether the presentation is accepted and the amount of violations
+/// found, or a string containing an error.
+fn credential_lifetime_reports<A: RoleAlgorithm>(raw_vc: &Map<String, Value>, disclosures: &Vec<String>, validity_days: u64) -> Result<Vec<(u64, bool, usize)>, String> {
+    let claims_len: usize = raw_vc.get(CLAIMS).and_then(|claims| claims.as_object()).map(|claims| claims.len()).unwrap_or_default();
+    let clock: Arc<SimulatedClock> = Arc::new(SimulatedClock::new(unix_time()?));
+    let issuer: Issuer<A> = Issuer::new(claims_len)?
+        .with_config(IssuerConfig { max_validity: Some(validity_days * SECONDS_PER_DAY) })
+        .with_clock(clock.clone());
+    let holder: Holder<A> = Holder::new(issuer.public_parameters().clone())?;
+    let verifier: Verifier<A> = Verifier::new(issuer.public_parameters().clone()).with_clock(clock.clone());
+
+    let (vc, _vc_jwt) = issuer.issue_vc(raw_vc, holder.public_key())?;
+    let audience: Option<String> = A::AUDIENCE_BINDING.then(|| PRESENTATION_REUSE_AUDIENCE.to_string());
+    let (_vp, vp_jwt) = holder.issue_vp(&vc, disclosures, audience)?;
+
+    let mut reports: Vec<(u64, bool, usize)> = Vec::new();
+    for day in 0..=validity_days + 1 {
+        let report = verifier.verify_vp_report(&vp_jwt, holder.public_key());
+        report.proof.clone()?;
+        reports.push((day, report.is_valid(), report.violations.len()));
+        clock.advance(Duration::from_secs(SECONDS_PER_DAY));
+    }
+
+    Ok(reports)
+}
+
+
+/// Benchmarks BBS+ presentations split into an offline phase, prepared from the VC before the verifier's request, and
+/// an online phase, generating the proof once the verifier's nonce is received, disclosing an increasing amount of
+/// claims of the mock VC, next to the presentations issued in one go.
+fn benchmark_bbs_precomputation(measurement: Measurement) -> Result<(), String> {
+
+    let raw_vc: Map<String, Value> = setup_raw_vc()?;
+    let claim_names: Vec<String> = raw_vc.get(CLAIMS).and_then(|claims| claims.as_object()).map(|claims| claims.keys().cloned().collect()).unwrap_or_default();
+    let adapter = BBSPlusAdapter::new(claim_names.len())?;
+    let name = adapter.sd_algorithm();
+
+    let columns: Vec<String> = ["disclosed_claims", "offline_duration", "online_duration", "vp_issuance_duration"]
+        .iter()
+        .map(|column| column.to_string())
+        .collect();
+    let mut writer = CSVWriter::new(columns)?;
+    writer.add_file(&BBS_PRESENTATION_PHASES.to_string())?;
+    let mut failure_log = FailureLog::new();
+
+    let vc = match failure_log.check(claim_names.len(), &name, "VC issuance", adapter.issue_vc(&raw_vc)) {
+        Some((vc, _)) => { vc }
+        None => {
+            failure_log.summarize();
+            return Ok(())
+        }
+    };
+    let offline = failure_log.check(claim_names.len(), &name, "VP preparation", Benchmark::measure(|| adapter.prepare_presentation(&vc), measurement));
+    let (offline_duration, prepared) = match offline {
+        Some((duration, prepared)) => { (duration.as_micros(), prepared) }
+        None => {
+            failure_log.summarize();
+            return Ok(())
+        }
+    };
+    let verifier_nonce: Vec<u8> = random_bytes(32);
+
+    for disclosed_claims in 1..=claim_names.len() {
+        let disclosures: Vec<String> = claim_names[..disclosed_claims].to_vec();
+        let online = Benchmark::measure(|| adapter.issue_prepared_vp(&prepared, &disclosures, &verifier_nonce), measurement);
+        let online_duration = failure_log.check(disclosed_claims, &name, "Prepared VP issuance", online).map(|(duration, _)| duration.as_micros());
+        let (issuance, _) = benchmark_vp(&adapter, &vc, &disclosures, measurement, disclosed_claims, &name, &mut failure_log);
+
+        info!("[{}] VP disclosing {} claims: {} µs online after {} µs offline, {} µs in one go", name, disclosed_claims, format_optional(online_duration), offline_duration, format_optional(issuance.map(|(duration, _, _, _)| duration)));
+        writer.write_record_to_file(&BBS_PRESENTATION_PHASES.to_string(), vec![
+            disclosed_claims.to_string(),
+            offline_duration.to_string(),
+            format_optional(online_duration),
+            format_optional(issuance.map(|(duration, _, _, _)| duration)),
+        ])?;
+    }
+    failure_log.summarize();
+
+    Ok(())
+}
+
+/// Issues a credential to every subject of a CSV or JSON dataset with every algorithm, as an issuer provisioning a
+/// population would, and writes the aggregate issuance throughput and storage of every algorithm.
+fn issue_dataset(arguments: &[String]) -> Result<(), String> {
+
+    let path: &String = match arguments.first() {
+        Some(path) => { path }
+        None => { return Err("Usage: csd_jwt issue-dataset <dataset.csv|dataset.json>".to_string()) }
+    };
+    let dataset = SubjectDataset::read(Path::new(path))?;
+    let template: Map<String, Value> = setup_raw_vc()?;
+    info!("Issuing {} credentials of up to {} claims from {}", dataset.len(), dataset.max_claims(), path);
+
+    let columns: Vec<String> = ["algorithm", "credentials", "elapsed", "credentials_per_second", "total_vc_jwt_length", "mean_vc_jwt_length"]
+        .iter()
+        .map(|column| column.to_string())
+        .collect();
+    let mut writer = CSVWriter::new(columns)?;
+    writer.add_file(&BULK_ISSUANCE.to_string())?;
+    let mut failure_log = FailureLog::new();
+
+    for initialization in initialize_sd_algorithms(dataset.max_claims(), Measurement::Iterations(1), false, false, false, None) {
+        let (_, algo) = initialization?;
+        let name = algo.sd_algorithm();
+
+        if let Some(issuance) = failure_log.check(dataset.max_claims(), &name, "Bulk issuance", issue_population(algo.as_ref(), &template, &dataset)) {
+            info!("[{}] {} credentials in {:?}: {:.1} credentials/s, {} bytes in total", name, issuance.credentials, issuance.elapsed, issuance.per_second(), issuance.total_length);
+            writer.write_record_to_file(&BULK_ISSUANCE.to_string(), vec![
+                name,
+                issuance.credentials.to_string(),
+                issuance.elapsed.as_micros().to_string(),
+                issuance.per_second().to_string(),
+                issuance.total_length.to_string(),
+                issuance.mean_length().to_string(),
+            ])?;
+        }
+    }
+    failure_log.summarize();
+
+    Ok(())
+}
+
+/// Compares the samples of two runs, each given as a result directory holding `results.json` or `samples.csv` or as a
+/// run id of the results database, and writes the regression report. Fails if any metric regressed beyond the
+/// threshold (5% by default), so that it can gate algorithm or dependency changes.
+fn compare(arguments: &[String]) -> Result<(), String> {
+
+    let usage = "Usage: csd_jwt compare <baseline directory or run id> <candidate directory or run id> [<threshold %>]";
+    let load = |argument: &String| -> Result<SampleLog, String> {
+        let directory: &Path = Path::new(argument);
+        if directory.is_dir() && directory.join(RESULTS_FILE).is_file() {
+            return ResultDocument::read(&directory.join(RESULTS_FILE)).map(|document| document.samples);
+        }
+        if directory.is_dir() {
+            return SampleLog::read_csv(&directory.join(format!("{SAMPLES_FILE}.csv")));
+        }
+        match i64::from_str(argument) {
+            Ok(run_id) => { load_run(run_id) }
+            Err(_) => { Err(format!("{argument} is neither a directory nor a run id. {usage}")) }
+        }
+    };
+    let (baseline, candidate) = match (arguments.first(), arguments.get(1)) {
+        (Some(baseline), Some(candidate)) => { (load(baseline)?, load(candidate)?) }
+        _ => { return Err(usage.to_string()) }
+    };
+
+    let mut thresholds = RegressionThresholds::default();
+    if let Some(argument) = arguments.get(2) {
+        match f64::from_str(argument) {
+            Ok(percentage) if percentage >= 0.0 => {
+                thresholds.max_slowdown = percentage / 100.0;
+                thresholds.max_size_increase = percentage / 100.0;
+            }
+            _ => { return Err(format!("Invalid threshold {argument}. {usage}")) }
+        }
+    }
+
+    let report = RegressionReport::compare(&baseline, &candidate, thresholds);
+    for comparison in report.comparisons() {
+        info!("[{}] {}: {:+.2}% over {} points (p = {:.3})", comparison.algorithm, comparison.metric, comparison.relative_delta * 100.0, comparison.points, comparison.p_value);
+    }
+    report.write_report(&REGRESSION_REPORT.to_string())?;
+
+    let regressions = report.regressions();
+    for regression in &regressions {
+        warn!("[{}] {} regressed by {:+.2}%: {:.1} -> {:.1}", regression.algorithm, regression.metric, regression.relative_delta * 100.0, regression.baseline_mean, regression.candidate_mean);
+    }
+    match regressions.len() {
+        0 => { Ok(()) }
+        n_regressions => { Err(format!("{n_regressions} metrics regressed beyond the threshold")) }
+    }
+}
+
+/// Runs the benchmark as a daemon, executing the scenarios submitted over HTTP on the address passed as argument and
+/// writing their results to the optional directory passed as second argument.
+#[cfg(feature = "server")]
+fn daemon(arguments: &[String]) -> Result<(), String> {
+
+    let usage = "Usage: csd_jwt daemon <address> [<results directory>]";
+    let address: &String = match arguments.first() {
+        Some(address) => { address }
+        None => { return Err(usage.to_string()) }
+    };
+    let results_dir = arguments.get(1).map(std::path::PathBuf::from);
+
+    let runtime = match tokio::runtime::Runtime::new() {
+        Ok(runtime) => { runtime }
+        Err(err) => { return Err(format!("Failed to start the runtime: [{err}]")) }
+    };
+    runtime.block_on(csd_jwt::daemon::serve(address, results_dir))
+}
+
+
+/// Initializes the logger with the verbosity selected by the `-q`, `-v` and `-vv` flags, which are removed from the
+/// arguments. The `RUST_LOG` environment variable, if set, takes precedence.
+fn initialize_logger(arguments: &mut Vec<String>) {
+
+    env_logger::Builder::new()
+        .filter_level(verbosity_level(arguments))
+        .parse_default_env()
+        .init();
+}
+
+
+/// Removes the `-q`, `-v` and `-vv` flags from the arguments and returns the log level they select, the last one
+/// winning, or Info if none is given.
+fn verbosity_level(arguments: &mut Vec<String>) -> LevelFilter {
+
+    let mut level: LevelFilter = LevelFilter::Info;
+    arguments.retain(|argument| {
+        match argument.as_str() {
+            "-q" | "--quiet" => { level = LevelFilter::Error; false }
+            "-v" | "--verbose" => { level = LevelFilter::Debug; false }
+            "-vv" => { level = LevelFilter::Trace; false }
+            _ => { true }
+        }
+    });
+    level
+}
+
+
+pub fn main() -> Result<(), String> {
+
+    let mut arguments: Vec<String> = env::args().collect();
+    initialize_logger(&mut arguments);
+
+    match arguments.get(1).map(|argument| argument.as_str()) {
+        Some("inspect") => { return inspect(&arguments[2..]) }
+        Some("throughput") => { return throughput(&arguments[2..]) }
+        Some("robustness") => { return robustness(&arguments[2..]) }
+        Some("compare") => { return compare(&arguments[2..]) }
+        Some("issue-dataset") => { return issue_dataset(&arguments[2..]) }
+        Some("jwks") => { return jwks(&arguments[2..]) }
+        Some("linkability") => { return linkability(&arguments[2..]) }
+        #[cfg(feature = "server")]
+        Some("daemon") => { return daemon(&arguments[2..]) }
+        _ => {}
+    }
+
+    let measurement: Measurement = match (env::var("CSD_JWT_TIME_BUDGET_MS"), env::var("CSD_JWT_ITERATIONS")) {
+        (Ok(budget_string), _) => {
+            info!("The environment variable CSD_JWT_TIME_BUDGET_MS is set. Its string value is: \"{}\"", budget_string);
+            let budget = match u64::from_str(budget_string.as_str()) {
+                Ok(budget) => budget,
+                Err(e) => {
+                    error!("The environment variable CSD_JWT_TIME_BUDGET_MS cannot be parsed to u64. Exiting.");
+                    return Err(e.to_string())
+                }
+            };
+            info!("Every measurement of the benchmark will be executed for: {} ms", budget);
+            Measurement::TimeBudget(Duration::from_millis(budget))
+        }
+        (Err(_), Ok(iterations_string)) => {
+            info!("The environment variable CSD_JWT_ITERATIONS is set. Its string value is: \"{}\"", iterations_string);
+            let iterations = match i8::from_str(iterations_string.as_str()) {
+                Ok(iterations) => iterations,
+                Err(e) => {
+                    error!("The environment variable CSD_JWT_ITERATIONS cannot be parsed to i8. Exiting.");
+                    return Err(e.to_string())
+                }
+            };
+            info!("The benchmark will be executed for: {}", iterations);
+            Measurement::Iterations(iterations)
+        }
+        (Err(_), Err(e)) => {
+            error!("Neither CSD_JWT_TIME_BUDGET_MS nor CSD_JWT_ITERATIONS is set. Exiting with error: {}", e);
+            return Err(e.to_string())
+        }
+    };
+
+    let verification_cache = env::var("CSD_JWT_VERIFICATION_CACHE").is_ok();
+    if verification_cache {
+        info!("The environment variable CSD_JWT_VERIFICATION_CACHE is set. Verifier-side caching is enabled.");
+    }
+    let prepared_pairings = env::var("CSD_JWT_PREPARED_PAIRINGS").is_ok();
+    if prepared_pairings {
+        info!("The environment variable CSD_JWT_PREPARED_PAIRINGS is set. CSD-JWT verifies with precomputed pairing inputs.");
+    }
+    let element_blinding = env::var("CSD_JWT_ELEMENT_BLINDING").is_ok();
+    if element_blinding {
+        info!("The environment variable CSD_JWT_ELEMENT_BLINDING is set. CSD-JWT blinds the accumulated elements of every VC.");
+    }
+    let envelope_split = env::var("CSD_JWT_ENVELOPE_SPLIT").is_ok();
+    if envelope_split {
+        info!("The environment variable CSD_JWT_ENVELOPE_SPLIT is set. Durations are also split between the SD mechanism and the JWT envelope.");
+    }
+    let holder_binding: Option<Arc<dyn HolderBinding>> = holder_binding_from_env()?;
+    if let Some(holder_binding) = &holder_binding {
+        info!("The environment variable CSD_JWT_HOLDER_BINDING is set. Presentations are bound to the holder through the {} binding.", holder_binding.name());
+    }
+    let claim_generator_name = env::var("CSD_JWT_CLAIM_GENERATOR").unwrap_or("flat".to_string());
+    let claim_generator: Box<dyn ClaimGenerator> = claim_generator_from_name(&claim_generator_name)?;
+    info!("Claims are generated by the {} generator.", claim_generator.name());
+    let holder_profile: Option<DeviceProfile> = match env::var("CSD_JWT_CONSTRAINED_HOLDER") {
+        Ok(configuration) => {
+            let profile = DeviceProfile::from_configuration(&configuration)?;
+            info!("The environment variable CSD_JWT_CONSTRAINED_HOLDER is set. VP issuance is also benchmarked with a slowdown factor of {}.", profile.slowdown_factor());
+            Some(profile)
+        }
+        Err(_) => { None }
+    };
+    let link_profiles: Vec<LinkProfile> = match env::var("CSD_JWT_LINK_PROFILES") {
+        Ok(configuration) => { LinkProfile::parse_list(&configuration)? }
+        Err(_) => { LinkProfile::defaults() }
+    };
+    let payload_constraints: Vec<PayloadConstraint> = match env::var("CSD_JWT_PAYLOAD_LIMITS") {
+        Ok(configuration) => { PayloadConstraint::parse_list(&configuration)? }
+        Err(_) => { PayloadConstraint::defaults() }
+    };
+    let size_reports = SizeReports {
+        transfer_costs: TransferCostModel::new(link_profiles, QR_CAPACITY),
+        feasibility: FeasibilityMatrix::new(payload_constraints),
+    };
+    if let Ok(configuration) = env::var("CSD_JWT_DERIVED_CLAIMS") {
+        let derived_claims = DerivedClaims::parse(&configuration)?;
+        info!("The environment variable CSD_JWT_DERIVED_CLAIMS is set. Presentations of {:?} are benchmarked for the hash-based algorithms.", derived_claims.claim_names());
+        benchmark_derived_claims(&derived_claims, measurement)?;
+    }
+    if env::var("CSD_JWT_EPHEMERAL_KEYS").is_ok() {
+        info!("The environment variable CSD_JWT_EPHEMERAL_KEYS is set. CSD-JWT presentations signed with certified ephemeral keys are benchmarked.");
+        benchmark_ephemeral_binding(measurement)?;
+    }
+    if env::var("CSD_JWT_BBS_PRECOMPUTATION").is_ok() {
+        info!("The environment variable CSD_JWT_BBS_PRECOMPUTATION is set. The offline and online phases of BBS+ presentations are benchmarked.");
+        benchmark_bbs_precomputation(measurement)?;
+    }
+    if let Ok(credentials_string) = env::var("CSD_JWT_KEY_REUSE") {
+        let credentials = match usize::from_str(credentials_string.as_str()) {
+            Ok(credentials) if credentials > 0 => { credentials }
+            _ => {
+                error!("The environment variable CSD_JWT_KEY_REUSE cannot be parsed to a positive amount of credentials. Exiting.");
+                return Err(format!("Invalid amount of credentials {credentials_string}"));
+            }
+        };
+        info!("The environment variable CSD_JWT_KEY_REUSE is set. The issuance of {} credentials with fresh and reused keys is benchmarked.", credentials);
+        benchmark_issuer_key_reuse(credentials)?;
+    }
+    if env::var("CSD_JWT_REISSUANCE").is_ok() {
+        info!("The environment variable CSD_JWT_REISSUANCE is set. The re-issuance of a credential with an updated claim is benchmarked.");
+        benchmark_reissuance(measurement)?;
+    }
+    if env::var("CSD_JWT_SIZE_ESTIMATION").is_ok() {
+        info!("The environment variable CSD_JWT_SIZE_ESTIMATION is set. The estimated lengths of VCs and VPs are validated against the issued ones.");
+        benchmark_size_estimation(measurement)?;
+    }
+    if env::var("CSD_JWT_ENTROPY_AUDIT").is_ok() {
+        info!("The environment variable CSD_JWT_ENTROPY_AUDIT is set. The random bytes drawn by every operation of every algorithm are audited.");
+        benchmark_entropy_consumption()?;
+    }
+    if let Ok(claims_string) = env::var("CSD_JWT_HASH_BACKEND") {
+        let max_claims = match usize::from_str(claims_string.as_str()) {
+            Ok(max_claims) if max_claims >= 64 => { max_claims }
+            _ => {
+                error!("The environment variable CSD_JWT_HASH_BACKEND cannot be parsed to an amount of claims of at least 64. Exiting.");
+                return Err(format!("Invalid amount of claims {claims_string}"));
+            }
+        };
+        info!("The environment variable CSD_JWT_HASH_BACKEND is set. The hash-based algorithms are benchmarked with the {} hashing backend up to {} claims.", hashing::BACKEND, max_claims);
+        benchmark_hash_backend(max_claims, measurement)?;
+    }
+    if let Ok(claims_string) = env::var("CSD_JWT_WITNESS_UPDATES") {
+        let n_claims = match usize::from_str(claims_string.as_str()) {
+            Ok(n_claims) if n_claims > 1 => { n_claims }
+            _ => {
+                error!("The environment variable CSD_JWT_WITNESS_UPDATES cannot be parsed to an amount of claims greater than 1. Exiting.");
+                return Err(format!("Invalid amount of claims {claims_string}"));
+            }
+        };
+        info!("The environment variable CSD_JWT_WITNESS_UPDATES is set. The revocation of up to {} claims of a CSD-JWT credential is benchmarked.", n_claims - 1);
+        benchmark_witness_updates(n_claims, measurement)?;
+    }
+    if let Ok(policy_string) = env::var("CSD_JWT_PRESENTATION_REUSE") {
+        let policy = match ReusePolicy::parse(&policy_string) {
+            Ok(policy) => { policy }
+            Err(err) => {
+                error!("The environment variable CSD_JWT_PRESENTATION_REUSE cannot be parsed to a reuse policy. Exiting.");
+                return Err(err);
+            }
+        };
+        info!("The environment variable CSD_JWT_PRESENTATION_REUSE is set. Holders answering identical presentation requests under the {:?} reuse policy are benchmarked.", policy);
+        benchmark_presentation_reuse(policy)?;
+    }
+    if let Ok(days_string) = env::var("CSD_JWT_CREDENTIAL_LIFETIME") {
+        let validity_days = match u64::from_str(days_string.as_str()) {
+            Ok(validity_days) if validity_days > 0 => { validity_days }
+            _ => {
+                error!("The environment variable CSD_JWT_CREDENTIAL_LIFETIME cannot be parsed to a positive amount of days. Exiting.");
+                return Err(format!("Invalid amount of days {days_string}"));
+            }
+        };
+        info!("The environment variable CSD_JWT_CREDENTIAL_LIFETIME is set. The lifetime of credentials valid for {} days is simulated.", validity_days);
+        benchmark_credential_lifetime(validity_days)?;
+    }
+    let transport_name = env::var("CSD_JWT_TRANSPORT").unwrap_or("memory".to_string());
+    info!("Protocol exchanges move messages over the {} transport.", transport_name);
+    let key_cache_ttl: Duration = match env::var("CSD_JWT_KEY_CACHE_TTL_MS") {
+        Ok(ttl_string) => match u64::from_str(ttl_string.as_str()) {
+            Ok(ttl) => {
+                info!("The environment variable CSD_JWT_KEY_CACHE_TTL_MS is set. Verifiers cache issuer keys for {} ms.", ttl);
+                Duration::from_millis(ttl)
+            }
+            Err(err) => {
+                error!("The environment variable CSD_JWT_KEY_CACHE_TTL_MS cannot be parsed to milliseconds. Exiting.");
+                return Err(format!("Invalid key cache time to live {ttl_string}: [{err}]"));
+            }
+        },
+        Err(_) => { DEFAULT_KEY_TTL }
+    };
+    let csv_output = env::var("CSD_JWT_CSV_OUTPUT").map(|output| output != "off").unwrap_or(true);
+    if !csv_output {
+        info!("The environment variable CSD_JWT_CSV_OUTPUT is off. The measurements of the sweep are not written to CSV files.");
+    }
+    let stream = env::var("CSD_JWT_STREAM").map(|stream| stream == "ndjson").unwrap_or(false);
+    if stream {
+        info!("The environment variable CSD_JWT_STREAM is ndjson. Every measurement of the sweep is streamed to stdout as a JSON line.");
+    }
+    let results_db = env::var("CSD_JWT_RESULTS_DB").ok();
+    if let Some(path) = &results_db {
+        if cfg!(not(feature = "sqlite")) {
+            error!("The environment variable CSD_JWT_RESULTS_DB is set but the sqlite feature is disabled. Exiting.");
+            return Err(format!("Cannot store samples in {path}: the sqlite feature is disabled"));
+        }
+        info!("The environment variable CSD_JWT_RESULTS_DB is set. Every sample is stored in {}.", path);
+    }
+    let run_label = env::var("CSD_JWT_RUN_LABEL").unwrap_or("unlabeled".to_string());
+    let options = BenchmarkOptions { measurement, verification_cache, prepared_pairings, element_blinding, envelope_split, holder_binding, holder_profile, transport_name, key_cache_ttl, csv_output, results_db, run_label };
+    let mut sink: SinkSet = sweep_sink(csv_output, stream)?;
+    let mut claim_counts: Vec<usize> = (1..=DENSE_MOCK_CLAIMS).collect();
+    if let Ok(configuration) = env::var("CSD_JWT_LARGE_CLAIMS") {
+        let large_claim_counts: Vec<usize> = parse_large_claims(&configuration)?;
+        info!("The environment variable CSD_JWT_LARGE_CLAIMS is set. The sweep continues with {:?} claims.", large_claim_counts);
+        claim_counts.extend(large_claim_counts);
+    }
+    benchmark_multiple_mock_claims(&claim_counts, options, claim_generator.as_ref(), size_reports, &mut sink)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+    use serde_json::{Map, Value};
+    use csd_jwt::adapters::adapter::Adapter;
+    use csd_jwt::adapters::hashes::merkle_tree_adapter::MerkleTreeAdapter;
+    use csd_jwt::adapters::hashes::sd_jwt_adapter::SdJwtAdapter;
+    use csd_jwt::adapters::signatures::bbs_plus_adapter::BBSPlusAdapter;
+    use csd_jwt::benchmark::Measurement;
+    use csd_jwt::claim_generator::{ClaimGenerator, FlatClaimGenerator};
+    use log::LevelFilter;
+    use super::{benchmark_vp, parse_large_claims, setup_raw_vc, sparse_claim_counts, verbosity_level, DisclosurePattern, FailureLog, DENSE_MOCK_CLAIMS, LARGE_CLAIM_SAMPLES};
+
+    #[test]
+    fn disclosure_pattern_indices() {
+        assert_eq!(DisclosurePattern::FirstOnly.indices(10), vec![1]);
+        assert_eq!(DisclosurePattern::LastOnly.indices(10), vec![10]);
+        assert_eq!(DisclosurePattern::EveryOther.indices(10), vec![1, 3, 5, 7, 9]);
+        assert_eq!(DisclosurePattern::EveryOther.indices(1), vec![1]);
+
+        let random_half: Vec<usize> = DisclosurePattern::RandomHalf.indices(10);
+        assert_eq!(random_half.len(), 5);
+        assert_eq!(random_half.iter().collect::<HashSet<_>>().len(), 5);
+        assert!(random_half.iter().all(|index| (1..=10).contains(index)));
+        assert_eq!(DisclosurePattern::RandomHalf.indices(1), vec![1]);
+
+        let names: HashSet<&str> = DisclosurePattern::ALL.iter().map(DisclosurePattern::name).collect();
+        assert_eq!(names.len(), DisclosurePattern::ALL.len());
+    }
+
+    #[test]
+    fn disclosure_patterns_are_presented() -> Result<(), String> {
+        let n_mock_claims: usize = 6;
+        let mut raw_vc: Map<String, Value> = setup_raw_vc()?;
+        FlatClaimGenerator.substitute_claims(&mut raw_vc, n_mock_claims)?;
+        let adapters: Vec<Box<dyn Adapter>> = vec![
+            Box::new(SdJwtAdapter::new(n_mock_claims)?),
+            Box::new(MerkleTreeAdapter::new(n_mock_claims)?),
+            Box::new(BBSPlusAdapter::new(n_mock_claims)?),
+        ];
+
+        let mut failure_log = FailureLog::new();
+        for algo in &adapters {
+            let name = algo.sd_algorithm();
+            let (vc, _) = algo.issue_vc(&raw_vc)?;
+            for pattern in DisclosurePattern::ALL {
+                let disclosures: Vec<String> = FlatClaimGenerator.disclosures(&pattern.indices(n_mock_claims));
+                let (issuance, verification) = benchmark_vp(algo.as_ref(), &vc, &disclosures, Measurement::Iterations(1), n_mock_claims, &name, &mut failure_log);
+                assert!(issuance.is_some() && verification.is_some(), "[{name}] {} was not presented", pattern.name());
+            }
+
+            // A pattern beyond the claims of the VC is recorded as a failure instead of aborting the benchmark.
+            let disclosures: Vec<String> = FlatClaimGenerator.disclosures(&DisclosurePattern::LastOnly.indices(n_mock_claims + 1));
+            let (issuance, verification) = benchmark_vp(algo.as_ref(), &vc, &disclosures, Measurement::Iterations(1), n_mock_claims, &name, &mut failure_log);
+            assert!(issuance.is_none() && verification.is_none());
+        }
+        assert_eq!(failure_log.failures.len(), adapters.len());
+
+        Ok(())
+    }
+
+    #[test]
+    fn verbosity_flags_select_the_log_level() {
+        let arguments = |arguments: &[&str]| -> Vec<String> { arguments.iter().map(|argument| argument.to_string()).collect() };
+
+        let mut plain: Vec<String> = arguments(&["csd_jwt", "inspect", "vc.jwt"]);
+        assert_eq!(verbosity_level(&mut plain), LevelFilter::Info);
+        assert_eq!(plain, arguments(&["csd_jwt", "inspect", "vc.jwt"]));
+
+        for (flag, level) in [("-q", LevelFilter::Error), ("--quiet", LevelFilter::Error), ("-v", LevelFilter::Debug), ("--verbose", LevelFilter::Debug), ("-vv", LevelFilter::Trace)] {
+            let mut flagged: Vec<String> = arguments(&["csd_jwt", flag, "inspect"]);
+            assert_eq!(verbosity_level(&mut flagged), level, "{flag}");
+            assert_eq!(flagged, arguments(&["csd_jwt", "inspect"]));
+        }
+
+        let mut repeated: Vec<String> = arguments(&["csd_jwt", "-vv", "-q"]);
+        assert_eq!(verbosity_level(&mut repeated), LevelFilter::Error);
+        assert_eq!(repeated, arguments(&["csd_jwt"]));
+    }
+
+    #[test]
+    fn failures_are_isolated_per_algorithm() {
+        let mut failure_log = FailureLog::new();
+        assert_eq!(failure_log.check(10, "SD-JWT", "VP issuance", Ok::<usize, String>(3)), Some(3));
+        assert!(failure_log.failures.is_empty());
+
+        assert_eq!(failure_log.check(40, "BBS+", "VP issuance", Err::<usize, String>("too many claims".to_string())), None);
+        assert_eq!(failure_log.check(20, "BBS+", "VP issuance", Err::<usize, String>("too many claims".to_string())), None);
+        assert_eq!(failure_log.check(30, "BBS+", "VP verification", Err::<usize, String>("invalid proof".to_string())), None);
+        assert_eq!(failure_log.check(50, "CSD-JWT", "VP issuance", Err::<usize, String>("too many claims".to_string())), None);
+        assert_eq!(failure_log.failures.len(), 4);
+        assert_eq!(failure_log.failures[0].error, "too many claims");
+
+        let limits: Vec<((String, &str), usize)> = failure_log.scaling_limits().into_iter().collect();
+        assert_eq!(limits, vec![
+            (("BBS+".to_string(), "VP issuance"), 20),
+            (("BBS+".to_string(), "VP verification"), 30),
+            (("CSD-JWT".to_string(), "VP issuance"), 50),
+        ]);
+    }
+
+    #[test]
+    fn sparse_sweep_claim_counts() -> Result<(), String> {
+        let claim_counts: Vec<usize> = sparse_claim_counts(10_000, 8);
+        assert_eq!(claim_counts.len(), 8);
+        assert_eq!(claim_counts.last(), Some(&10_000));
+        assert!(claim_counts.windows(2).all(|pair| pair[0] < pair[1]));
+        assert!(claim_counts.iter().all(|n_mock_claims| *n_mock_claims > DENSE_MOCK_CLAIMS && n_mock_claims % 10 == 0));
+
+        // Samples rounding to the same count are only benchmarked once, and nothing is added to the dense sweep.
+        assert_eq!(sparse_claim_counts(120, 8), vec![110, 120]);
+        assert!(sparse_claim_counts(DENSE_MOCK_CLAIMS, 8).is_empty());
+        assert!(sparse_claim_counts(10_000, 0).is_empty());
+
+        assert_eq!(parse_large_claims("10000")?.len(), LARGE_CLAIM_SAMPLES);
+        assert_eq!(parse_large_claims(" 1000 : 3 ")?, sparse_claim_counts(1000, 3));
+        assert!(parse_large_claims("many").is_err());
+        assert!(parse_large_claims("1000:few").is_err());
+        assert!(parse_large_claims("-1000").is_err());
+
+        Ok(())
+    }
 }
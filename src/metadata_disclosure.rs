@@ -0,0 +1,363 @@
+use serde_json::{Map, Value};
+use crate::common_data::{CLAIMS, CLAIM_GROUPS, CLAIM_METADATA, CNF, EXPIRATION, ISSUED_AT, ISSUER};
+use crate::derived_claims::Date;
+use crate::deterministic::unix_time;
+
+/// Prefix of the names of the claims holding the disclosable metadata fields, e.g. `metadata:issuanceDate`.
+pub const METADATA_PREFIX: &str = "metadata:";
+/// Key for the issuance date of a VC, as an ISO 8601 date and time.
+pub const ISSUANCE_DATE: &str = "issuanceDate";
+/// Top-level fields that cannot be made disclosable, since holders and verifiers need them to process the credential.
+const RESERVED_FIELDS: [&str; 9] = ["@context", "type", ISSUER, CLAIMS, CLAIM_METADATA, CLAIM_GROUPS, CNF, ISSUED_AT, EXPIRATION];
+
+/// Claims of the subject and disclosed metadata fields, by name, as restored from the disclosed claims of a VP.
+pub type RestoredClaims = (Map<String, Value>, Map<String, Value>);
+
+/// Name of the claim holding a disclosable metadata field.
+///
+/// # Arguments
+/// * `field` - Name of the top-level field.
+///
+/// # Returns
+/// The name of the claim, i.e. the field prefixed with `METADATA_PREFIX`.
+pub fn metadata_claim(field: &str) -> String {
+    format!("{METADATA_PREFIX}{field}")
+}
+
+
+/// Granularity issuance times are rounded down to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TimeGranularity {
+    /// Midnight UTC of the day of issuance.
+    Day,
+    /// Midnight UTC of the first day of the month of issuance.
+    Month,
+}
+
+impl TimeGranularity {
+
+    /// Rounds a timestamp down to the granularity.
+    ///
+    /// # Arguments
+    /// * `seconds` - Timestamp in seconds since the UNIX epoch.
+    ///
+    /// # Returns
+    /// The rounded timestamp.
+    pub fn round_timestamp(&self, seconds: u64) -> u64 {
+        let days: i64 = (seconds / 86_400) as i64;
+        let days: i64 = match self {
+            TimeGranularity::Day => { days }
+            TimeGranularity::Month => { Date { day: 1, ..Date::from_days(days) }.days_since_epoch() }
+        };
+        days as u64 * 86_400
+    }
+
+    /// Rounds an ISO 8601 date and time, e.g. `2024-01-15T10:30:00Z`, down to the granularity.
+    ///
+    /// # Arguments
+    /// * `date_time` - Date and time starting with a date of the form `YYYY-MM-DD`.
+    ///
+    /// # Returns
+    /// A result containing the rounded date and time in UTC or a string containing an error.
+    pub fn round_date_time(&self, date_time: &str) -> Result<String, String> {
+        let date: Date = match date_time.get(..10) {
+            Some(date) => { Date::parse(date)? }
+            None => { return Err(format!("Date and time {date_time} does not start with a date")) }
+        };
+        let day: u32 = match self {
+            TimeGranularity::Day => { date.day }
+            TimeGranularity::Month => { 1 }
+        };
+        Ok(format!("{:04}-{:02}-{day:02}T00:00:00Z", date.year, date.month))
+    }
+}
+
+
+/// Treatment of the issuance time of a credential, i.e. its `iat` and `issuanceDate`, which otherwise let verifiers
+/// link the presentations of a holder through the exact second its credential was issued at.
+///
+/// # Examples
+/// ```
+/// use serde_json::{json, Map, Value};
+/// use csd_jwt::metadata_disclosure::{DisclosableMetadata, IssuanceTime, TimeGranularity};
+///
+/// let mut raw_vc: Map<String, Value> = json!({
+///     "iat": 1705314600, "issuanceDate": "2024-01-15T10:30:00Z", "credentialSubject": {"name": "Alice"}
+/// }).as_object().unwrap().clone();
+/// let metadata = DisclosableMetadata::new().with_issuance_time(IssuanceTime::parse("month").unwrap());
+/// assert_eq!(metadata.issuance_time(), IssuanceTime::Rounded(TimeGranularity::Month));
+/// metadata.blind_issuance_time(&mut raw_vc).unwrap();
+/// assert_eq!(raw_vc["iat"], json!(1704067200));
+/// assert_eq!(raw_vc["issuanceDate"], json!("2024-01-01T00:00:00Z"));
+///
+/// let metadata = DisclosableMetadata::new().with_issuance_time(IssuanceTime::Disclosable);
+/// metadata.insert_into(&mut raw_vc).unwrap();
+/// assert!(!raw_vc.contains_key("iat"));
+/// assert_eq!(raw_vc["credentialSubject"]["metadata:iat"], json!("1704067200"));
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum IssuanceTime {
+    /// Issuance time in the clear, to the second.
+    #[default]
+    Exact,
+    /// Issuance time in the clear, rounded down, so that the credentials issued in the same period share it. The
+    /// expiration derived from `IssuerConfig::max_validity` is then rounded as well. The rounded `iat` and
+    /// `issuanceDate` are covered by the issuer commitment, so the holder cannot alter them.
+    Rounded(TimeGranularity),
+    /// Issuance time committed to as the `metadata:iat` and `metadata:issuanceDate` claims, disclosed only when the
+    /// holder chooses to. Verifiers cannot check that an undisclosed issuance time is not in the future, and an
+    /// expiration derived from `IssuerConfig::max_validity` still reveals it.
+    Disclosable,
+}
+
+impl IssuanceTime {
+
+    /// Parses a treatment of the issuance time: `exact`, `day`, `month` or `disclosable`.
+    ///
+    /// # Arguments
+    /// * `treatment` - Treatment to be parsed.
+    ///
+    /// # Returns
+    /// A result containing the treatment or a string containing an error.
+    pub fn parse(treatment: &str) -> Result<Self, String> {
+        match treatment.trim() {
+            "exact" => { Ok(IssuanceTime::Exact) }
+            "day" => { Ok(IssuanceTime::Rounded(TimeGranularity::Day)) }
+            "month" => { Ok(IssuanceTime::Rounded(TimeGranularity::Month)) }
+            "disclosable" => { Ok(IssuanceTime::Disclosable) }
+            _ => { Err(format!("Unknown issuance time treatment {treatment}, expected exact, day, month or disclosable")) }
+        }
+    }
+}
+
+
+/// Top-level fields of a VC outside `credentialSubject`, e.g. `issuanceDate`, `credentialStatus` or custom metadata,
+/// made selectively disclosable at issuance. Every field is moved into the claims under the name returned by
+/// `metadata_claim`, holding the JSON serialization of its value, so that every algorithm protects it like any other
+/// claim instead of leaking it in the clear. Holders disclose a field by naming it and verifiers lift the disclosed
+/// fields back to the top level.
+///
+/// # Examples
+/// ```
+/// use serde_json::{json, Map, Value};
+/// use csd_jwt::metadata_disclosure::DisclosableMetadata;
+///
+/// let mut raw_vc: Map<String, Value> = json!({
+///     "issuanceDate": "2024-01-01T00:00:00Z",
+///     "credentialStatus": {"type": "StatusList2021Entry", "statusListIndex": "94567"},
+///     "credentialSubject": {"name": "Alice"}
+/// }).as_object().unwrap().clone();
+/// let metadata = DisclosableMetadata::parse("issuanceDate,credentialStatus").unwrap();
+/// metadata.insert_into(&mut raw_vc).unwrap();
+/// assert!(!raw_vc.contains_key("issuanceDate"));
+/// assert_eq!(raw_vc["credentialSubject"]["metadata:issuanceDate"], json!("\"2024-01-01T00:00:00Z\""));
+///
+/// let disclosures = metadata.resolve(&vec!["name".to_string(), "credentialStatus".to_string()]);
+/// assert_eq!(disclosures, vec!["name".to_string(), "metadata:credentialStatus".to_string()]);
+///
+/// let (claims, fields) = DisclosableMetadata::restore(raw_vc["credentialSubject"].as_object().unwrap()).unwrap();
+/// assert_eq!(claims.keys().collect::<Vec<&String>>(), vec!["name"]);
+/// assert_eq!(fields["credentialStatus"]["statusListIndex"], "94567");
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DisclosableMetadata {
+    /// Names of the disclosable top-level fields.
+    fields: Vec<String>,
+    /// Treatment of the issuance time.
+    issuance_time: IssuanceTime,
+}
+
+impl DisclosableMetadata {
+
+    /// Constructor for an empty set of fields, leaving every top-level field in the clear.
+    pub fn new() -> Self {
+        DisclosableMetadata::default()
+    }
+
+    /// Parses a comma separated list of top-level fields, e.g. `issuanceDate,credentialStatus`.
+    ///
+    /// # Arguments
+    /// * `configuration` - List of fields.
+    ///
+    /// # Returns
+    /// A result containing the disclosable fields or a string containing an error if a field is reserved.
+    pub fn parse(configuration: &str) -> Result<Self, String> {
+        let mut metadata: DisclosableMetadata = DisclosableMetadata::new();
+        for field in configuration.split(',').map(str::trim).filter(|field| !field.is_empty()) {
+            metadata = metadata.with_field(field)?;
+        }
+
+        Ok(metadata)
+    }
+
+    /// Makes a top-level field disclosable.
+    ///
+    /// # Arguments
+    /// * `field` - Name of the field.
+    ///
+    /// # Returns
+    /// A result containing the set including the field or a string containing an error if the field is reserved.
+    pub fn with_field(mut self, field: &str) -> Result<Self, String> {
+        if RESERVED_FIELDS.contains(&field) {
+            return Err(format!("Field {field} is needed to process the credential, it cannot be made disclosable"));
+        }
+        if !self.fields.iter().any(|configured| configured == field) {
+            self.fields.push(field.to_string());
+        }
+        Ok(self)
+    }
+
+    /// Sets the treatment of the issuance time.
+    ///
+    /// # Arguments
+    /// * `issuance_time` - Treatment of `iat` and `issuanceDate`.
+    ///
+    /// # Returns
+    /// The set treating the issuance time accordingly.
+    pub fn with_issuance_time(mut self, issuance_time: IssuanceTime) -> Self {
+        self.issuance_time = issuance_time;
+        self
+    }
+
+    /// Whether the set contains no field and leaves the issuance time exact.
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty() && self.issuance_time == IssuanceTime::Exact
+    }
+
+    /// Treatment of the issuance time.
+    pub fn issuance_time(&self) -> IssuanceTime {
+        self.issuance_time
+    }
+
+    /// Rounds the issuance time of a raw VC down if so configured, before its validity period is set, so that the
+    /// expiration derived from it is rounded too. The issuance time defaults to the current time.
+    ///
+    /// # Arguments
+    /// * `raw_vc` - Raw VC whose issuance time is rounded.
+    ///
+    /// # Returns
+    /// A result containing a string in case the issuance time is malformed.
+    pub fn blind_issuance_time(&self, raw_vc: &mut Map<String, Value>) -> Result<(), String> {
+        let granularity: TimeGranularity = match self.issuance_time {
+            IssuanceTime::Rounded(granularity) => { granularity }
+            IssuanceTime::Exact | IssuanceTime::Disclosable => { return Ok(()) }
+        };
+
+        let issued_at: u64 = match raw_vc.get(ISSUED_AT) {
+            Some(issued_at) => match issued_at.as_u64() {
+                Some(issued_at) => { issued_at }
+                None => { return Err(format!("Field {ISSUED_AT} of the raw VC must be a timestamp in seconds")) }
+            },
+            None => { unix_time()? }
+        };
+        raw_vc.insert(ISSUED_AT.to_string(), Value::from(granularity.round_timestamp(issued_at)));
+
+        match raw_vc.get(ISSUANCE_DATE) {
+            Some(Value::String(issuance_date)) => {
+                let rounded: String = granularity.round_date_time(issuance_date)?;
+                raw_vc.insert(ISSUANCE_DATE.to_string(), Value::String(rounded));
+            }
+            Some(_) => { return Err(format!("Field {ISSUANCE_DATE} of the raw VC must be a date and time")) }
+            None => {}
+        }
+
+        Ok(())
+    }
+
+    /// Names of the disclosable fields.
+    pub fn fields(&self) -> &[String] {
+        &self.fields
+    }
+
+    /// Moves the disclosable fields of a raw VC, and its issuance time if disclosable, into its claims, after its
+    /// validity period is set. Fields missing from the raw VC are skipped, so that optional metadata such as
+    /// `credentialStatus` can be configured once for every credential.
+    ///
+    /// # Arguments
+    /// * `raw_vc` - Raw VC whose fields are made disclosable.
+    ///
+    /// # Returns
+    /// A result containing a string in case the raw VC has no claims or a field clashes with an existing claim.
+    pub fn insert_into(&self, raw_vc: &mut Map<String, Value>) -> Result<(), String> {
+        let mut moved: Vec<(String, Value)> = vec![];
+        for field in self.disclosable_fields() {
+            if let Some(value) = raw_vc.remove(field) {
+                moved.push((metadata_claim(field), Value::String(value.to_string())));
+            }
+        }
+
+        let claims = match raw_vc.get_mut(CLAIMS) {
+            Some(Value::Object(claims)) => { claims }
+            _ => { return Err(format!("Raw VC does not contain {CLAIMS}")) }
+        };
+        for (name, value) in moved {
+            if claims.contains_key(&name) {
+                return Err(format!("Metadata claim {name} is already a claim of the credential"));
+            }
+            claims.insert(name, value);
+        }
+
+        Ok(())
+    }
+
+    /// Holder-side translation of the disclosures naming a disclosable field to the claim holding it.
+    ///
+    /// # Arguments
+    /// * `disclosures` - Names of the claims or fields to be disclosed.
+    ///
+    /// # Returns
+    /// The disclosures with the fields replaced by their claims.
+    pub fn resolve(&self, disclosures: &[String]) -> Vec<String> {
+        let fields: Vec<&str> = self.disclosable_fields();
+        disclosures.iter()
+            .map(|disclosure| if fields.contains(&disclosure.as_str()) { metadata_claim(disclosure) } else { disclosure.clone() })
+            .collect()
+    }
+
+    /// Names of the fields moved into the claims, including the issuance time if disclosable.
+    fn disclosable_fields(&self) -> Vec<&str> {
+        let mut fields: Vec<&str> = self.fields.iter().map(String::as_str).collect();
+        if self.issuance_time == IssuanceTime::Disclosable {
+            for field in [ISSUED_AT, ISSUANCE_DATE] {
+                if !fields.contains(&field) {
+                    fields.push(field);
+                }
+            }
+        }
+        fields
+    }
+
+    /// Verifier-side split of the disclosed claims into the claims of the subject and the disclosed metadata fields.
+    ///
+    /// # Arguments
+    /// * `claims` - Disclosed claims.
+    ///
+    /// # Returns
+    /// A result containing the claims of the subject and the disclosed fields, by name, or a string containing an
+    /// error if a field does not hold a JSON serialization.
+    pub fn restore(claims: &Map<String, Value>) -> Result<RestoredClaims, String> {
+        let mut subject_claims: Map<String, Value> = Map::new();
+        let mut fields: Map<String, Value> = Map::new();
+
+        for (name, value) in claims {
+            let field: &str = match name.strip_prefix(METADATA_PREFIX) {
+                Some(field) => { field }
+                None => {
+                    subject_claims.insert(name.clone(), value.clone());
+                    continue
+                }
+            };
+
+            let value: Value = match value {
+                Value::String(serialized) => match serde_json::from_str::<Value>(serialized) {
+                    Ok(value) => { value }
+                    Err(err) => { return Err(format!("Failed to parse metadata field {field}: [{err}]")) }
+                },
+                _ => { return Err(format!("Metadata field {field} is not a string")) }
+            };
+            fields.insert(field.to_string(), value);
+        }
+
+        Ok((subject_claims, fields))
+    }
+}
@@ -0,0 +1,176 @@
+use std::fmt;
+use std::sync::Arc;
+use serde_json::{Map, Value};
+use crate::adapters::adapter::Adapter;
+use crate::adapters::registry::AdapterRegistry;
+
+/// Error returned to the mobile bindings, carrying the reason of the failure.
+#[derive(Debug, uniffi::Error)]
+pub enum MobileError {
+    /// No adapter is registered for the requested algorithm.
+    UnknownAlgorithm { algorithm: String },
+    /// The operation failed.
+    Failed { reason: String },
+}
+
+impl fmt::Display for MobileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MobileError::UnknownAlgorithm { algorithm } => { write!(f, "Unknown algorithm {algorithm}") }
+            MobileError::Failed { reason } => { write!(f, "{reason}") }
+        }
+    }
+}
+
+impl std::error::Error for MobileError {}
+
+impl From<String> for MobileError {
+    fn from(reason: String) -> Self {
+        MobileError::Failed { reason }
+    }
+}
+
+
+/// Adapters of every algorithm, exported to Kotlin and Swift through UniFFI so that mobile wallet prototypes can issue,
+/// present and verify credentials with the same code as the benchmark. VCs are exchanged as JSON strings and VPs as JWTs.
+#[derive(uniffi::Object)]
+pub struct MobileAdapters {
+    /// Adapters, by the name of their algorithm.
+    registry: AdapterRegistry,
+}
+
+impl MobileAdapters {
+
+    /// Retrieves the adapter of an algorithm.
+    fn adapter(&self, algorithm: &str) -> Result<&dyn Adapter, MobileError> {
+        match self.registry.get(algorithm) {
+            Some(adapter) => { Ok(adapter) }
+            None => { Err(MobileError::UnknownAlgorithm { algorithm: algorithm.to_string() }) }
+        }
+    }
+}
+
+#[uniffi::export]
+impl MobileAdapters {
+
+    /// Constructor generating fresh keys and parameters for every algorithm.
+    ///
+    /// # Arguments
+    /// * `claims_len` - Maximum amount of claims of the credentials handled by the adapters.
+    #[uniffi::constructor]
+    pub fn new(claims_len: u32) -> Result<Arc<Self>, MobileError> {
+        Ok(Arc::new(MobileAdapters { registry: AdapterRegistry::with_all_adapters(claims_len as usize)? }))
+    }
+
+    /// Names of the available algorithms, in alphabetical order.
+    pub fn algorithms(&self) -> Vec<String> {
+        self.registry.algorithms()
+    }
+
+    /// Issues a VC.
+    ///
+    /// # Arguments
+    /// * `algorithm` - Name of the algorithm.
+    /// * `raw_vc` - JSON of the raw VC.
+    ///
+    /// # Returns
+    /// A result containing the JSON of the VC or an error.
+    pub fn issue_vc(&self, algorithm: String, raw_vc: String) -> Result<String, MobileError> {
+        let raw_vc: Map<String, Value> = parse_object(&raw_vc)?;
+        let (vc, _vc_jwt) = self.adapter(&algorithm)?.issue_vc(&raw_vc)?;
+        Ok(Value::Object(vc).to_string())
+    }
+
+    /// Derives a VP disclosing some claims of a VC.
+    ///
+    /// # Arguments
+    /// * `algorithm` - Name of the algorithm.
+    /// * `vc` - JSON of the VC.
+    /// * `disclosures` - Names of the claims to be disclosed.
+    ///
+    /// # Returns
+    /// A result containing the JWT of the VP or an error.
+    pub fn issue_vp(&self, algorithm: String, vc: String, disclosures: Vec<String>) -> Result<String, MobileError> {
+        let vc: Map<String, Value> = parse_object(&vc)?;
+        let (_vp, vp_jwt) = self.adapter(&algorithm)?.issue_vp(&vc, &disclosures)?;
+        Ok(vp_jwt)
+    }
+
+    /// Verifies a VP.
+    ///
+    /// # Arguments
+    /// * `algorithm` - Name of the algorithm.
+    /// * `vp_jwt` - JWT of the VP.
+    ///
+    /// # Returns
+    /// A result containing an error if the VP is invalid.
+    pub fn verify_vp(&self, algorithm: String, vp_jwt: String) -> Result<(), MobileError> {
+        Ok(self.adapter(&algorithm)?.verify_vp(&vp_jwt)?)
+    }
+}
+
+
+/// Parses a JSON object received from the bindings.
+fn parse_object(json: &str) -> Result<Map<String, Value>, MobileError> {
+    match serde_json::from_str::<Map<String, Value>>(json) {
+        Ok(object) => { Ok(object) }
+        Err(err) => { Err(MobileError::Failed { reason: format!("Failed to parse JSON object: [{err}]") }) }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+    use std::sync::Arc;
+    use crate::common_data::VC;
+    use crate::csv_writer::{CSVWriter, CSV_DIR};
+    use crate::mobile::{MobileAdapters, MobileError};
+
+    #[test]
+    fn every_algorithm_round_trips() -> Result<(), MobileError> {
+        let adapters: Arc<MobileAdapters> = MobileAdapters::new(16)?;
+        let algorithms: Vec<String> = adapters.algorithms();
+        assert_eq!(algorithms.len(), 7);
+
+        for algorithm in &algorithms {
+            let vc: String = adapters.issue_vc(algorithm.clone(), VC.to_string())?;
+            let vp_jwt: String = adapters.issue_vp(algorithm.clone(), vc, vec!["name".to_string()])?;
+            adapters.verify_vp(algorithm.clone(), vp_jwt.clone())?;
+
+            let others: Vec<&String> = algorithms.iter().filter(|other| *other != algorithm).collect();
+            for other in others {
+                assert!(adapters.verify_vp(other.clone(), vp_jwt.clone()).is_err(), "{other} verified a VP of {algorithm}");
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn errors_reach_the_bindings() -> Result<(), MobileError> {
+        let adapters: Arc<MobileAdapters> = MobileAdapters::new(16)?;
+        let algorithm: String = adapters.algorithms()[0].clone();
+
+        match adapters.issue_vc("unknown".to_string(), VC.to_string()) {
+            Err(err @ MobileError::UnknownAlgorithm { .. }) => { assert_eq!(err.to_string(), "Unknown algorithm unknown") }
+            other => { panic!("Unexpected outcome {other:?}") }
+        }
+        assert!(matches!(adapters.issue_vc(algorithm.clone(), "[]".to_string()), Err(MobileError::Failed { .. })));
+        assert!(matches!(adapters.issue_vp(algorithm.clone(), "{".to_string(), vec![]), Err(MobileError::Failed { .. })));
+        assert!(matches!(adapters.verify_vp(algorithm, "not a jwt".to_string()), Err(MobileError::Failed { .. })));
+
+        Ok(())
+    }
+
+    #[test]
+    fn measurements_are_not_written() -> Result<(), MobileError> {
+        let filename: String = "mobile_discarded".to_string();
+        let mut writer: CSVWriter = CSVWriter::new(vec!["column".to_string()])?;
+        writer.add_file(&filename)?;
+        writer.write_record_to_file(&filename, vec!["value"])?;
+        assert!(!Path::new(CSV_DIR).join(format!("{filename}.csv")).exists());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,240 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use serde_json::{Map, Value};
+use crate::encoding::Encoding;
+use crate::hashing::sha256;
+
+/// Default maximum amount of presentations kept in a `PresentationCache`.
+pub const DEFAULT_PRESENTATION_CACHE_ENTRIES: usize = 64;
+
+/// Presentation, both in form of a Map and in form of a signed JWT.
+type Presentation = (Map<String, Value>, String);
+
+/// Key of a cached presentation: digest of the credential, sorted disclosures and audience.
+type PresentationKey = (String, Vec<String>, Option<String>);
+
+
+/// Policy of reuse of the presentations derived by a holder.
+///
+/// A reused presentation carries the nonce and the proofs of the first one, so every verifier receiving it can link it
+/// to the other presentations of the same key, and it cannot answer a verifier requiring a fresh nonce or a recent `iat`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReusePolicy {
+    /// A presentation is derived for every request.
+    Never,
+    /// A presentation is reused for the same credential, disclosures and audience until it is older than the duration.
+    WithinTtl(Duration),
+}
+
+impl ReusePolicy {
+
+    /// Parses a policy from `never` or a time to live in milliseconds.
+    ///
+    /// # Arguments
+    /// * `configuration` - Either `never` or a positive amount of milliseconds.
+    ///
+    /// # Returns
+    /// A result containing the policy or a string containing an error.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::time::Duration;
+    /// use csd_jwt::presentation_cache::ReusePolicy;
+    ///
+    /// assert_eq!(ReusePolicy::parse("never").unwrap(), ReusePolicy::Never);
+    /// assert_eq!(ReusePolicy::parse("500").unwrap(), ReusePolicy::WithinTtl(Duration::from_millis(500)));
+    /// assert!(ReusePolicy::parse("0").is_err());
+    /// ```
+    pub fn parse(configuration: &str) -> Result<Self, String> {
+        match configuration.trim() {
+            "never" => { Ok(ReusePolicy::Never) }
+            ttl => match ttl.parse::<u64>() {
+                Ok(ttl) if ttl > 0 => { Ok(ReusePolicy::WithinTtl(Duration::from_millis(ttl))) }
+                _ => { Err(format!("Invalid presentation reuse policy {configuration}: expected never or a positive amount of milliseconds")) }
+            }
+        }
+    }
+}
+
+
+/// Holder-side cache of derived presentations keyed by credential, disclosure set and audience, so that repeated
+/// requests for the same presentation skip the proof generation, which is expensive for BBS+ and the accumulators.
+/// The least recently derived presentation is evicted once the cache is full.
+///
+/// # Examples
+/// ```
+/// use std::time::Duration;
+/// use serde_json::Map;
+/// use csd_jwt::presentation_cache::{PresentationCache, ReusePolicy};
+///
+/// let cache = PresentationCache::new(ReusePolicy::WithinTtl(Duration::from_secs(60)), 16);
+/// let vc = Map::new();
+/// let disclosures = vec!["name".to_string()];
+/// let derive = || Ok((Map::new(), "vp".to_string()));
+/// cache.get_or_derive(&vc, &disclosures, None, derive).unwrap();
+/// cache.get_or_derive(&vc, &disclosures, None, derive).unwrap();
+/// cache.get_or_derive(&vc, &disclosures, Some("https://verifier.example"), derive).unwrap();
+/// assert_eq!((cache.derivations(), cache.hits()), (2, 1));
+/// ```
+pub struct PresentationCache {
+    /// Policy of reuse of the presentations.
+    policy: ReusePolicy,
+    /// Maximum amount of presentations kept.
+    max_entries: usize,
+    /// Derived presentations, by key, with the time of their derivation.
+    entries: Mutex<HashMap<PresentationKey, (Presentation, Instant)>>,
+    /// Amount of requests that derived a presentation.
+    derivations: AtomicUsize,
+    /// Amount of requests that reused a cached presentation.
+    hits: AtomicUsize,
+}
+
+impl PresentationCache {
+
+    /// Constructor for an empty cache.
+    ///
+    /// # Arguments
+    /// * `policy` - Policy of reuse of the presentations.
+    /// * `max_entries` - Maximum amount of presentations kept.
+    ///
+    /// # Returns
+    /// An empty cache with zeroed statistics.
+    pub fn new(policy: ReusePolicy, max_entries: usize) -> Self {
+        PresentationCache {
+            policy,
+            max_entries,
+            entries: Mutex::new(HashMap::new()),
+            derivations: AtomicUsize::new(0),
+            hits: AtomicUsize::new(0),
+        }
+    }
+
+    /// Policy of reuse of the presentations.
+    pub fn policy(&self) -> ReusePolicy {
+        self.policy
+    }
+
+    /// Amount of requests that derived a presentation, whether it succeeded or not.
+    pub fn derivations(&self) -> usize {
+        self.derivations.load(Ordering::Relaxed)
+    }
+
+    /// Amount of requests that reused a cached presentation.
+    pub fn hits(&self) -> usize {
+        self.hits.load(Ordering::Relaxed)
+    }
+
+    /// Returns the cached presentation of a credential for the disclosures and the audience if the policy allows its
+    /// reuse, or derives and caches a new one otherwise.
+    ///
+    /// # Arguments
+    /// * `vc` - Credential the presentation is derived from.
+    /// * `disclosures` - Claims to disclose, in any order.
+    /// * `audience` - Optional audience of the presentation.
+    /// * `derive` - Derivation of the presentation.
+    ///
+    /// # Returns
+    /// A result containing the presentation or a string containing an error if the derivation fails.
+    pub fn get_or_derive<F>(&self, vc: &Map<String, Value>, disclosures: &[String], audience: Option<&str>, derive: F) -> Result<Presentation, String>
+    where
+        F: FnOnce() -> Result<Presentation, String>,
+    {
+        let ttl: Duration = match self.policy {
+            ReusePolicy::Never => {
+                self.derivations.fetch_add(1, Ordering::Relaxed);
+                return derive();
+            }
+            ReusePolicy::WithinTtl(ttl) => { ttl }
+        };
+
+        let key: PresentationKey = Self::key(vc, disclosures, audience);
+        if let Some(presentation) = self.cached(&key, ttl) {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+            return Ok(presentation);
+        }
+
+        self.derivations.fetch_add(1, Ordering::Relaxed);
+        let presentation: Presentation = derive()?;
+        self.store(key, &presentation, ttl);
+
+        Ok(presentation)
+    }
+
+    /// Computes the key of a presentation, so that the credential is not kept twice in memory.
+    fn key(vc: &Map<String, Value>, disclosures: &[String], audience: Option<&str>) -> PresentationKey {
+        let credential: String = Encoding::Base64UrlNoPad.encode(sha256(Value::Object(vc.clone()).to_string()));
+        let mut disclosures: Vec<String> = disclosures.to_vec();
+        disclosures.sort();
+        disclosures.dedup();
+        (credential, disclosures, audience.map(str::to_string))
+    }
+
+    /// Looks up a presentation younger than the time to live.
+    fn cached(&self, key: &PresentationKey, ttl: Duration) -> Option<Presentation> {
+        let entries = self.entries.lock().ok()?;
+        match entries.get(key) {
+            Some((presentation, derived_at)) if derived_at.elapsed() < ttl => { Some(presentation.clone()) }
+            _ => { None }
+        }
+    }
+
+    /// Stores a derived presentation, evicting the expired presentations and then the least recently derived one if the
+    /// cache is full.
+    fn store(&self, key: PresentationKey, presentation: &Presentation, ttl: Duration) {
+        if self.max_entries == 0 {
+            return;
+        }
+
+        if let Ok(mut entries) = self.entries.lock() {
+            if entries.len() >= self.max_entries && !entries.contains_key(&key) {
+                entries.retain(|_, (_, derived_at)| derived_at.elapsed() < ttl);
+            }
+            if entries.len() >= self.max_entries && !entries.contains_key(&key) {
+                let oldest: Option<PresentationKey> = entries.iter()
+                    .min_by_key(|(_, (_, derived_at))| *derived_at)
+                    .map(|(key, _)| key.clone());
+                if let Some(oldest) = oldest {
+                    entries.remove(&oldest);
+                }
+            }
+            entries.insert(key, (presentation.clone(), Instant::now()));
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+    use serde_json::{Map, Value};
+    use crate::presentation_cache::{PresentationCache, ReusePolicy};
+
+    fn derive() -> Result<(Map<String, Value>, String), String> {
+        Ok((Map::new(), "vp".to_string()))
+    }
+
+    #[test]
+    fn presentations_are_reused_for_the_same_key_only() -> Result<(), String> {
+        let cache = PresentationCache::new(ReusePolicy::WithinTtl(Duration::from_secs(60)), 16);
+        let mut vc: Map<String, Value> = Map::new();
+        vc.insert("name".to_string(), Value::String("Ada".to_string()));
+        let other_vc: Map<String, Value> = Map::new();
+
+        cache.get_or_derive(&vc, &["name".to_string(), "birthdate".to_string()], None, derive)?;
+        cache.get_or_derive(&vc, &["birthdate".to_string(), "name".to_string()], None, derive)?;
+        assert_eq!((cache.derivations(), cache.hits()), (1, 1));
+
+        cache.get_or_derive(&vc, &["name".to_string()], None, derive)?;
+        cache.get_or_derive(&other_vc, &["name".to_string()], None, derive)?;
+        assert_eq!((cache.derivations(), cache.hits()), (3, 1));
+
+        let cache = PresentationCache::new(ReusePolicy::Never, 16);
+        cache.get_or_derive(&vc, &["name".to_string()], None, derive)?;
+        cache.get_or_derive(&vc, &["name".to_string()], None, derive)?;
+        assert_eq!((cache.derivations(), cache.hits()), (2, 0));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,287 @@
+use std::fmt;
+use std::io::Read;
+use serde::de::{DeserializeSeed, Deserializer, Error, IgnoredAny, MapAccess, SeqAccess, Visitor};
+use serde_json::{Map, Value};
+use crate::adapters::adapter::VerifierAdapter;
+use crate::common_data::{VERIFIABLE_CREDENTIAL, VP, VP_CONTEXT, VP_TYPE};
+
+/// Field of a bundle entry holding the name of the algorithm of its VP.
+pub const ALGORITHM: &str = "algorithm";
+/// Default maximum amount of credentials in a bundle.
+pub const DEFAULT_MAX_CREDENTIALS: usize = 256;
+
+
+/// Bundles the VPs of several credentials, possibly of different algorithms, in a single presentation, whose
+/// `verifiableCredential` array holds an entry with the name of the algorithm and the JWT of the VP of every credential.
+///
+/// # Arguments
+/// * `presentations` - Name of the algorithm and JWT of the VP of every credential.
+///
+/// # Returns
+/// A result containing the JSON of the bundle or a string containing an error.
+pub fn bundle_presentations(presentations: &[(String, String)]) -> Result<String, String> {
+    let credentials: Vec<Value> = presentations.iter().map(|(algorithm, vp_jwt)| {
+        let mut entry: Map<String, Value> = Map::new();
+        entry.insert(ALGORITHM.to_string(), Value::String(algorithm.clone()));
+        entry.insert(VP.to_string(), Value::String(vp_jwt.clone()));
+        Value::Object(entry)
+    }).collect();
+
+    let mut bundle: Map<String, Value> = Map::new();
+    bundle.insert("@context".to_string(), Value::Array(vec![Value::String(VP_CONTEXT.to_string())]));
+    bundle.insert("type".to_string(), Value::Array(vec![Value::String(VP_TYPE.to_string())]));
+    bundle.insert(VERIFIABLE_CREDENTIAL.to_string(), Value::Array(credentials));
+
+    match serde_json::to_string(&bundle) {
+        Ok(json) => { Ok(json) }
+        Err(err) => { Err(format!("Failed to serialize presentation bundle: [{err}]")) }
+    }
+}
+
+
+/// Outcome of the verification of a credential of a bundle.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CredentialReport {
+    /// Position of the credential in the bundle.
+    pub index: usize,
+    /// Name of the algorithm of its VP, empty if the entry does not declare it.
+    pub algorithm: String,
+    /// Outcome of the verification of its VP.
+    pub result: Result<(), String>,
+}
+
+
+/// Outcome of the verification of every credential of a bundle, in the order they were presented.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct StreamReport {
+    /// Report of every credential.
+    pub credentials: Vec<CredentialReport>,
+}
+
+impl StreamReport {
+
+    /// Whether every credential of the bundle is valid.
+    pub fn is_valid(&self) -> bool {
+        self.credentials.iter().all(|report| report.result.is_ok())
+    }
+
+    /// Converts the report to a result, reporting the first invalid credential.
+    pub fn into_result(self) -> Result<(), String> {
+        match self.credentials.into_iter().find(|report| report.result.is_err()) {
+            Some(CredentialReport { index, algorithm, result: Err(err) }) => { Err(format!("Credential {index} ({algorithm}) is invalid: [{err}]")) }
+            _ => { Ok(()) }
+        }
+    }
+}
+
+
+/// Verifier of presentation bundles, which verifies every credential as soon as its entry is parsed and then drops it,
+/// so that only one entry and the reports are held in memory regardless of the size of the bundle.
+///
+/// # Examples
+/// ```
+/// use csd_jwt::adapters::adapter::{Adapter, VerifierAdapter};
+/// use csd_jwt::adapters::hashes::sd_jwt_adapter::SdJwtAdapter;
+/// use csd_jwt::common_data::VC;
+/// use csd_jwt::presentation_stream::{bundle_presentations, StreamVerifier};
+///
+/// let adapter = SdJwtAdapter::new(2).unwrap();
+/// let (vc, _vc_jwt) = adapter.issue_vc(&serde_json::from_str(VC).unwrap()).unwrap();
+/// let (_vp, vp_jwt) = adapter.issue_vp(&vc, &vec!["name".to_string()]).unwrap();
+/// let bundle = bundle_presentations(&[(adapter.sd_algorithm(), vp_jwt.clone()), (adapter.sd_algorithm(), vp_jwt.clone())]).unwrap();
+///
+/// let verifiers: [&dyn VerifierAdapter; 1] = [&adapter];
+/// let report = StreamVerifier::new(&verifiers).verify_reader(bundle.as_bytes()).unwrap();
+/// assert_eq!(report.credentials.len(), 2);
+/// assert!(report.is_valid());
+///
+/// // A VP of an algorithm without a verifier fails on its own, without stopping the stream.
+/// let bundle = bundle_presentations(&[("MERKLE".to_string(), vp_jwt.clone()), (adapter.sd_algorithm(), vp_jwt)]).unwrap();
+/// let report = StreamVerifier::new(&verifiers).verify_reader(bundle.as_bytes()).unwrap();
+/// assert!(report.credentials[0].result.is_err());
+/// assert!(report.credentials[1].result.is_ok());
+/// assert!(report.into_result().is_err());
+/// ```
+pub struct StreamVerifier<'a> {
+    /// Verifiers of the algorithms accepted in a bundle.
+    verifiers: &'a [&'a dyn VerifierAdapter],
+    /// Maximum amount of credentials in a bundle.
+    max_credentials: usize,
+}
+
+impl<'a> StreamVerifier<'a> {
+
+    /// Constructor for a verifier of bundles.
+    ///
+    /// # Arguments
+    /// * `verifiers` - Verifiers of the algorithms accepted in a bundle, matched by the name of their algorithm.
+    ///
+    /// # Returns
+    /// The verifier, accepting at most `DEFAULT_MAX_CREDENTIALS` credentials per bundle.
+    pub fn new(verifiers: &'a [&'a dyn VerifierAdapter]) -> Self {
+        StreamVerifier { verifiers, max_credentials: DEFAULT_MAX_CREDENTIALS }
+    }
+
+    /// Sets the maximum amount of credentials in a bundle, past which parsing stops.
+    pub fn with_max_credentials(mut self, max_credentials: usize) -> Self {
+        self.max_credentials = max_credentials;
+        self
+    }
+
+    /// Verifies a bundle read from a stream, e.g. the body of a request.
+    ///
+    /// # Arguments
+    /// * `reader` - Stream of the JSON of the bundle.
+    ///
+    /// # Returns
+    /// A result containing the report of every credential, or a string containing an error if the bundle is malformed
+    /// or holds too many credentials.
+    pub fn verify_reader<R: Read>(&self, reader: R) -> Result<StreamReport, String> {
+        let mut report: StreamReport = StreamReport::default();
+        let mut deserializer = serde_json::Deserializer::from_reader(reader);
+
+        match deserializer.deserialize_map(BundleVisitor { verifier: self, report: &mut report }) {
+            Ok(_) => {}
+            Err(err) => { return Err(format!("Failed to parse presentation bundle: [{err}]")) }
+        };
+        match deserializer.end() {
+            Ok(_) => {}
+            Err(err) => { return Err(format!("Trailing data after presentation bundle: [{err}]")) }
+        };
+
+        Ok(report)
+    }
+
+    /// Verifies a single entry of a bundle.
+    fn verify_entry(&self, index: usize, entry: &Value) -> CredentialReport {
+        let algorithm: String = entry.get(ALGORITHM).and_then(Value::as_str).unwrap_or_default().to_string();
+        let result: Result<(), String> = match (self.verifiers.iter().find(|verifier| verifier.sd_algorithm() == algorithm), entry.get(VP)) {
+            (Some(verifier), Some(Value::String(vp_jwt))) => { verifier.verify_vp(vp_jwt) }
+            (None, _) => { Err(format!("No verifier for algorithm {algorithm:?}")) }
+            (_, _) => { Err(format!("Entry does not contain the {VP} string")) }
+        };
+
+        CredentialReport { index, algorithm, result }
+    }
+}
+
+
+/// Visitor of the object of a bundle, which checks its type and verifies its credentials as they are parsed.
+struct BundleVisitor<'a, 'b> {
+    verifier: &'b StreamVerifier<'a>,
+    report: &'b mut StreamReport,
+}
+
+impl<'de> Visitor<'de> for BundleVisitor<'_, '_> {
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a presentation bundle")
+    }
+
+    fn visit_map<M: MapAccess<'de>>(self, mut map: M) -> Result<(), M::Error> {
+        let mut typed: bool = false;
+        let mut credentials: bool = false;
+
+        while let Some(key) = map.next_key::<String>()? {
+            if key == "type" {
+                let types: Vec<String> = map.next_value()?;
+                typed = types.iter().any(|bundle_type| bundle_type == VP_TYPE);
+            } else if key == VERIFIABLE_CREDENTIAL {
+                map.next_value_seed(CredentialsSeed { verifier: self.verifier, report: &mut *self.report })?;
+                credentials = true;
+            } else {
+                map.next_value::<IgnoredAny>()?;
+            }
+        }
+
+        if !typed {
+            return Err(M::Error::custom(format!("bundle type does not include {VP_TYPE}")));
+        }
+        if !credentials {
+            return Err(M::Error::custom(format!("bundle does not contain the {VERIFIABLE_CREDENTIAL} array")));
+        }
+
+        Ok(())
+    }
+}
+
+
+/// Seed of the `verifiableCredential` array of a bundle, verifying every entry and dropping it before parsing the next.
+struct CredentialsSeed<'a, 'b> {
+    verifier: &'b StreamVerifier<'a>,
+    report: &'b mut StreamReport,
+}
+
+impl<'de> DeserializeSeed<'de> for CredentialsSeed<'_, '_> {
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+impl<'de> Visitor<'de> for CredentialsSeed<'_, '_> {
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("an array of presented credentials")
+    }
+
+    fn visit_seq<S: SeqAccess<'de>>(self, mut seq: S) -> Result<(), S::Error> {
+        while let Some(entry) = seq.next_element::<Value>()? {
+            let index: usize = self.report.credentials.len();
+            if index >= self.verifier.max_credentials {
+                return Err(S::Error::custom(format!("bundle holds more than {} credentials", self.verifier.max_credentials)));
+            }
+            self.report.credentials.push(self.verifier.verify_entry(index, &entry));
+        }
+
+        Ok(())
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{Map, Value};
+    use crate::adapters::adapter::{Adapter, VerifierAdapter};
+    use crate::adapters::accumulators::csd_jwt_adapter::CsdJwtAdapter;
+    use crate::adapters::hashes::sd_jwt_adapter::SdJwtAdapter;
+    use crate::common_data::VC;
+    use crate::presentation_stream::{bundle_presentations, StreamVerifier};
+
+    #[test]
+    fn credentials_are_reported_individually() -> Result<(), String> {
+        let raw_vc: Map<String, Value> = match serde_json::from_str::<Map<String, Value>>(VC) {
+            Ok(vc) => { vc }
+            Err(err) => { return Err(format!("Failed to parse Raw Verifiable Credential. [{err}]")); }
+        };
+        let disclosures: Vec<String> = vec!["name".to_string()];
+
+        let sd_jwt = SdJwtAdapter::new(13)?;
+        let csd_jwt = CsdJwtAdapter::new(13)?;
+        let (vc, _vc_jwt) = sd_jwt.issue_vc(&raw_vc)?;
+        let (_vp, sd_jwt_vp) = sd_jwt.issue_vp(&vc, &disclosures)?;
+        let (vc, _vc_jwt) = csd_jwt.issue_vc(&raw_vc)?;
+        let (_vp, csd_jwt_vp) = csd_jwt.issue_vp(&vc, &disclosures)?;
+
+        let bundle = bundle_presentations(&[
+            (sd_jwt.sd_algorithm(), sd_jwt_vp.clone()),
+            (csd_jwt.sd_algorithm(), csd_jwt_vp),
+            (csd_jwt.sd_algorithm(), sd_jwt_vp.clone()),
+            ("unknown".to_string(), sd_jwt_vp),
+        ])?;
+
+        let verifiers: [&dyn VerifierAdapter; 2] = [&sd_jwt, &csd_jwt];
+        let report = StreamVerifier::new(&verifiers).verify_reader(bundle.as_bytes())?;
+        let valid: Vec<bool> = report.credentials.iter().map(|credential| credential.result.is_ok()).collect();
+        assert_eq!(valid, vec![true, true, false, false]);
+        assert!(report.into_result().is_err());
+
+        assert!(StreamVerifier::new(&verifiers).with_max_credentials(3).verify_reader(bundle.as_bytes()).is_err(), "Bundle with too many credentials was parsed.");
+        assert!(StreamVerifier::new(&verifiers).verify_reader(&bundle.as_bytes()[..bundle.len() - 1]).is_err(), "Truncated bundle was parsed.");
+
+        Ok(())
+    }
+}
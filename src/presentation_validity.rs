@@ -0,0 +1,134 @@
+use serde_json::{Map, Value};
+use crate::common_data::{EXPIRATION, NOT_BEFORE};
+
+
+/// Validity window chosen by the holder for its VPs, so that a captured VP stops being accepted once it expires. The
+/// VP is valid from its `nbf`, by default its issuance time, for `lifetime` seconds until its `exp`; both are signed by
+/// the holder together with the rest of the envelope and checked by the verifiers, which can also bound the lifetime
+/// they accept with `VerifierConfig::max_presentation_lifetime`.
+///
+/// The window is passed to the derivation of each VP next to its audience, e.g. to `Adapter::issue_vp_for_audience`.
+/// Holders reusing their presentations should keep the time to live of the reuse policy below the lifetime.
+///
+/// # Examples
+/// ```
+/// use serde_json::{Map, Value};
+/// use csd_jwt::adapters::adapter::{Adapter, VerifierAdapter};
+/// use csd_jwt::adapters::hashes::sd_jwt_adapter::SdJwtAdapter;
+/// use csd_jwt::common_data::VC;
+/// use csd_jwt::ephemeral_binding::unverified_claims;
+/// use csd_jwt::presentation_validity::PresentationValidity;
+///
+/// let adapter = SdJwtAdapter::new(13).unwrap();
+/// let raw_vc: Map<String, Value> = serde_json::from_str(VC).unwrap();
+/// let (vc, _) = adapter.issue_vc(&raw_vc).unwrap();
+///
+/// let validity = PresentationValidity::new(300);
+/// let (_, vp_jwt) = adapter.issue_vp_for_audience(&vc, &vec!["name".to_string()], None, Some(validity)).unwrap();
+/// let envelope = unverified_claims(&vp_jwt).unwrap();
+/// assert_eq!(envelope["exp"].as_u64().unwrap() - envelope["nbf"].as_u64().unwrap(), 300);
+/// adapter.verify_vp(&vp_jwt).unwrap();
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PresentationValidity {
+    /// Length of the window in seconds.
+    pub lifetime: u64,
+    /// Start of the window in seconds since the UNIX epoch, or none to start at the issuance of the VP.
+    pub not_before: Option<u64>,
+}
+
+impl PresentationValidity {
+
+    /// Constructor for a window starting at the issuance of the VP.
+    ///
+    /// # Arguments
+    /// * `lifetime` - Length of the window in seconds.
+    pub fn new(lifetime: u64) -> Self {
+        PresentationValidity { lifetime, not_before: None }
+    }
+
+    /// Sets the start of the window, e.g. the time of a scheduled check-in.
+    ///
+    /// # Arguments
+    /// * `not_before` - Start of the window in seconds since the UNIX epoch.
+    pub fn with_not_before(mut self, not_before: u64) -> Self {
+        self.not_before = Some(not_before);
+        self
+    }
+
+    /// Writes the `nbf` and `exp` of the window in the envelope of a VP.
+    ///
+    /// # Arguments
+    /// * `envelope` - VP JWT payload.
+    /// * `issued_at` - Issuance time of the VP, in seconds since the UNIX epoch.
+    pub fn insert_into(&self, envelope: &mut Map<String, Value>, issued_at: u64) {
+        let not_before: u64 = self.not_before.unwrap_or(issued_at);
+        envelope.insert(NOT_BEFORE.to_string(), Value::from(not_before));
+        envelope.insert(EXPIRATION.to_string(), Value::from(not_before.saturating_add(self.lifetime)));
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{Map, Value};
+    use crate::common_data::{CommonData, EXPIRATION, ISSUED_AT, NOT_BEFORE, VC};
+    use crate::presentation_validity::PresentationValidity;
+    use crate::sd_algorithms::hashes::sd_jwt::SdJwtInstance;
+    use crate::verifier_config::{FreshnessViolation, VerifierConfig};
+
+    #[test]
+    fn presentation_window_is_enforced() -> Result<(), String> {
+        let config = VerifierConfig { max_presentation_lifetime: Some(600), clock_skew: 0, ..VerifierConfig::default() };
+        let envelope = |validity: PresentationValidity| {
+            let mut envelope: Map<String, Value> = Map::new();
+            envelope.insert(ISSUED_AT.to_string(), Value::from(1000));
+            validity.insert_into(&mut envelope, 1000);
+            envelope
+        };
+
+        let short_lived: Map<String, Value> = envelope(PresentationValidity::new(300));
+        assert!(config.freshness_violations(&short_lived, 1200).is_empty());
+        assert_eq!(config.freshness_violations(&short_lived, 1301), vec![FreshnessViolation::PresentationExpired { expiration: 1300 }]);
+
+        let scheduled: Map<String, Value> = envelope(PresentationValidity::new(300).with_not_before(5000));
+        assert_eq!(config.freshness_violations(&scheduled, 1200), vec![FreshnessViolation::PresentationNotYetValid { not_before: 5000 }]);
+        assert!(config.freshness_violations(&scheduled, 5100).is_empty());
+
+        let long_lived: Map<String, Value> = envelope(PresentationValidity::new(3600));
+        assert_eq!(config.freshness_violations(&long_lived, 1200), vec![FreshnessViolation::PresentationLifetimeTooLong { lifetime: 3600, max_lifetime: 600 }]);
+
+        let mut unbounded: Map<String, Value> = Map::new();
+        unbounded.insert(ISSUED_AT.to_string(), Value::from(1000));
+        assert_eq!(config.freshness_violations(&unbounded, 1200), vec![FreshnessViolation::MissingPresentationExpiration]);
+        assert!(VerifierConfig::default().freshness_violations(&unbounded, 1200).is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn presentation_window_applies_on_every_thread() -> Result<(), String> {
+        let raw_vc: Map<String, Value> = match serde_json::from_str::<Map<String, Value>>(VC) {
+            Ok(vc) => { vc }
+            Err(err) => { return Err(format!("Failed to parse Raw Verifiable Credential from string. [{err}]")); }
+        };
+        let (_holder_public_key, holder_private_key) = CommonData::holder_keys()?;
+        let (_issuer_public_key, issuer_private_key) = CommonData::issuer_keys()?;
+        let (vc, _vc_jwt) = SdJwtInstance::issue_vc(&raw_vc, &issuer_private_key)?;
+        let disclosures: Vec<String> = vec!["name".to_string()];
+
+        let derivation = std::thread::spawn(move || {
+            SdJwtInstance::issue_vp_for_audience(&vc, &disclosures, None, Some(PresentationValidity::new(60)), &holder_private_key)
+        });
+        let (vp, _vp_jwt) = match derivation.join() {
+            Ok(result) => { result? }
+            Err(_) => { return Err("Derivation thread panicked.".to_string()) }
+        };
+
+        let not_before: Option<u64> = vp.get(NOT_BEFORE).and_then(Value::as_u64);
+        let expiration: Option<u64> = vp.get(EXPIRATION).and_then(Value::as_u64);
+        assert_eq!(not_before.zip(expiration).map(|(not_before, expiration)| expiration - not_before), Some(60));
+
+        Ok(())
+    }
+}
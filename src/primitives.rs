@@ -0,0 +1,5 @@
+//! Re-export of `csd_jwt_core`, the verification half of the hash-based algorithms building without std.
+//!
+//! The SD-JWT and MERKLE verifiers of this crate check the disclosed claims through these functions, so that embedded
+//! verifiers depending on `csd_jwt_core` alone accept exactly the same VPs.
+pub use csd_jwt_core::*;
@@ -0,0 +1,203 @@
+use std::time::{Duration, Instant};
+use serde_json::{Map, Value};
+use crate::adapters::adapter::Adapter;
+use crate::common_data::{AUDIENCE, ISSUER};
+use crate::key_cache::KeyResolver;
+use crate::transport::channel::{InMemoryTransport, Role, Transport, TransportStatistics};
+
+/// Field of the presentation request listing the claims the verifier asks for.
+pub const REQUESTED_CLAIMS: &str = "requested_claims";
+/// Field of the presentation response carrying the VP JWT.
+pub const VP_TOKEN: &str = "vp_token";
+
+/// Duration of every step of a complete exchange between issuer, holder and verifier.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ExchangeTimings {
+    /// Issuance of the VC by the issuer.
+    pub issuance: Duration,
+    /// Serialization of the VC by the issuer, transfer and deserialization by the holder.
+    pub credential_transfer: Duration,
+    /// Verification of the received VC by the holder.
+    pub credential_check: Duration,
+    /// Serialization of the presentation request by the verifier, transfer and deserialization by the holder.
+    pub presentation_request: Duration,
+    /// Creation of the VP by the holder.
+    pub presentation: Duration,
+    /// Serialization of the presentation response by the holder, transfer and deserialization by the verifier.
+    pub presentation_transfer: Duration,
+    /// Resolution of the issuer key and verification of the VP by the verifier.
+    pub verification: Duration,
+}
+
+impl ExchangeTimings {
+
+    /// Names of the steps, in the order of `durations`.
+    pub const STEPS: [&'static str; 7] = [
+        "issuance",
+        "credential_transfer",
+        "credential_check",
+        "presentation_request",
+        "presentation",
+        "presentation_transfer",
+        "verification",
+    ];
+
+    /// Durations of the steps, in the order of `STEPS`.
+    pub fn durations(&self) -> [Duration; 7] {
+        [
+            self.issuance,
+            self.credential_transfer,
+            self.credential_check,
+            self.presentation_request,
+            self.presentation,
+            self.presentation_transfer,
+            self.verification,
+        ]
+    }
+
+    /// Duration of the whole exchange.
+    pub fn total(&self) -> Duration {
+        self.durations().iter().sum()
+    }
+}
+
+
+/// Executes a step of the exchange, accumulating its duration.
+fn timed<T, F>(elapsed: &mut Duration, func: F) -> Result<T, String>
+where
+    F: FnOnce() -> Result<T, String>
+{
+    let start = Instant::now();
+    let result = func();
+    *elapsed += start.elapsed();
+    result
+}
+
+/// Serializes a message to JSON, as it would be sent over the wire.
+fn serialize(message: &Map<String, Value>, name: &str) -> Result<String, String> {
+    match serde_json::to_string(message) {
+        Ok(serialized) => { Ok(serialized) }
+        Err(err) => { Err(format!("Failed to serialize {name}: [{err}]")) }
+    }
+}
+
+/// Deserializes a message received as JSON.
+fn deserialize(serialized: &str, name: &str) -> Result<Map<String, Value>, String> {
+    match serde_json::from_str::<Map<String, Value>>(serialized) {
+        Ok(message) => { Ok(message) }
+        Err(err) => { Err(format!("Failed to deserialize {name}: [{err}]")) }
+    }
+}
+
+/// Runs a complete exchange with an adapter, from the issuance of a VC to the verification of a VP derived from it,
+/// including the JSON encoding and decoding of every message exchanged by issuer, holder and verifier, which the
+/// benchmarks of the single operations exclude.
+///
+/// # Arguments
+/// * `adapter` - Adapter of the algorithm.
+/// * `raw_vc` - Template VC to be issued.
+/// * `disclosures` - Claims requested by the verifier.
+/// * `audience` - Optional audience the verifier asks the VP to be bound to.
+///
+/// # Returns
+/// A result containing the duration of every step of the exchange or a string containing an error.
+///
+/// # Examples
+/// ```
+/// use std::time::Duration;
+/// use serde_json::{Map, Value};
+/// use csd_jwt::adapters::adapter::Adapter;
+/// use csd_jwt::adapters::hashes::sd_jwt_adapter::SdJwtAdapter;
+/// use csd_jwt::common_data::VC;
+/// use csd_jwt::protocol::run_exchange;
+///
+/// let adapter = SdJwtAdapter::new(4).unwrap();
+/// let raw_vc: Map<String, Value> = serde_json::from_str(VC).unwrap();
+/// let timings = run_exchange(&adapter, &raw_vc, &vec!["name".to_string()], None).unwrap();
+/// assert_eq!(timings.total(), timings.durations().iter().sum::<Duration>());
+/// assert!(timings.issuance > Duration::ZERO && timings.verification > Duration::ZERO);
+///
+/// // An unknown claim aborts the exchange at the holder.
+/// assert!(run_exchange(&adapter, &raw_vc, &vec!["unknown".to_string()], None).is_err());
+/// ```
+pub fn run_exchange(adapter: &dyn Adapter, raw_vc: &Map<String, Value>, disclosures: &[String], audience: Option<String>) -> Result<ExchangeTimings, String> {
+    let (timings, _) = run_exchange_over(adapter, raw_vc, disclosures, audience, &mut InMemoryTransport::new(), None)?;
+    Ok(timings)
+}
+
+/// Runs a complete exchange with an adapter as `run_exchange`, moving every message between the roles over a transport.
+///
+/// # Arguments
+/// * `adapter` - Adapter of the algorithm.
+/// * `raw_vc` - Template VC to be issued.
+/// * `disclosures` - Claims requested by the verifier.
+/// * `audience` - Optional audience the verifier asks the VP to be bound to.
+/// * `transport` - Transport moving the messages.
+/// * `key_resolver` - Resolver of the key of the issuer named in the raw VC, queried by the verifier before verifying the
+///   VP, if any. The adapter verifies with the key it holds, so the resolution only adds its cost to the verification.
+///
+/// # Returns
+/// A result containing the duration of every step of the exchange and the traffic it generated on the transport, or a
+/// string containing an error.
+pub fn run_exchange_over(adapter: &dyn Adapter, raw_vc: &Map<String, Value>, disclosures: &[String], audience: Option<String>, transport: &mut dyn Transport, key_resolver: Option<&dyn KeyResolver>) -> Result<(ExchangeTimings, TransportStatistics), String> {
+
+    let mut timings = ExchangeTimings::default();
+    let initial_statistics: TransportStatistics = transport.statistics();
+
+    // Issuer side.
+    let (vc, _) = timed(&mut timings.issuance, || adapter.issue_vc(raw_vc))?;
+
+    // Issuer to holder.
+    let received_vc: Map<String, Value> = timed(&mut timings.credential_transfer, || {
+        deserialize(&transport.transfer(Role::Issuer, Role::Holder, &serialize(&vc, "VC")?)?, "VC")
+    })?;
+    timed(&mut timings.credential_check, || adapter.verify_vc(&received_vc))?;
+
+    // Verifier to holder.
+    let (requested_claims, requested_audience): (Vec<String>, Option<String>) = timed(&mut timings.presentation_request, || {
+        let mut request: Map<String, Value> = Map::new();
+        request.insert(REQUESTED_CLAIMS.to_string(), Value::Array(disclosures.iter().map(|claim| Value::String(claim.clone())).collect()));
+        if let Some(audience) = &audience {
+            request.insert(AUDIENCE.to_string(), Value::String(audience.clone()));
+        }
+
+        let received_request = deserialize(&transport.transfer(Role::Verifier, Role::Holder, &serialize(&request, "presentation request")?)?, "presentation request")?;
+        let requested_claims: Vec<String> = match received_request.get(REQUESTED_CLAIMS) {
+            Some(Value::Array(claims)) => {
+                claims.iter().filter_map(|claim| claim.as_str().map(|claim| claim.to_string())).collect()
+            }
+            _ => { return Err(format!("Presentation request does not contain {REQUESTED_CLAIMS}")) }
+        };
+        let requested_audience: Option<String> = received_request.get(AUDIENCE).and_then(|audience| audience.as_str()).map(|audience| audience.to_string());
+
+        Ok((requested_claims, requested_audience))
+    })?;
+
+    // Holder side.
+    let (_, vp_jwt) = timed(&mut timings.presentation, || adapter.issue_vp_for_audience(&received_vc, &requested_claims, requested_audience, None))?;
+
+    // Holder to verifier.
+    let received_vp_jwt: String = timed(&mut timings.presentation_transfer, || {
+        let mut response: Map<String, Value> = Map::new();
+        response.insert(VP_TOKEN.to_string(), Value::String(vp_jwt));
+
+        let received_response = transport.transfer(Role::Holder, Role::Verifier, &serialize(&response, "presentation response")?)?;
+        match deserialize(&received_response, "presentation response")?.remove(VP_TOKEN) {
+            Some(Value::String(vp_jwt)) => { Ok(vp_jwt) }
+            _ => { Err(format!("Presentation response does not contain {VP_TOKEN}")) }
+        }
+    })?;
+
+    // Verifier side.
+    timed(&mut timings.verification, || {
+        if let Some(key_resolver) = key_resolver {
+            match raw_vc.get(ISSUER) {
+                Some(Value::String(issuer)) => { key_resolver.resolve(issuer)?; }
+                _ => { return Err(format!("Raw VC does not contain {ISSUER}, its key cannot be resolved")) }
+            }
+        }
+        adapter.verify_vp(&received_vp_jwt)
+    })?;
+
+    Ok((timings, transport.statistics().since(&initial_statistics)))
+}
@@ -0,0 +1,220 @@
+use std::collections::HashMap;
+use hmac::{Hmac, Mac};
+use josekit::jws::{JwsHeader, ES256};
+use josekit::jwt;
+use josekit::jwt::JwtPayload;
+use serde_json::{Map, Value};
+use sha2::Sha256;
+use crate::common_data::AUDIENCE;
+use crate::deterministic::fill_random;
+use crate::encoding::Encoding;
+use crate::hashing::sha256;
+use crate::ephemeral_binding::unverified_claims;
+use crate::holder_binding::jwk_thumbprint;
+use crate::protocol::VP_TOKEN;
+
+/// Claim of a pseudonymous presentation holding the pseudonym of the holder at the verifier.
+pub const PSEUDONYM: &str = "nym";
+/// Domain separation tag of the link secrets derived from a holder key.
+const LINK_SECRET_DOMAIN: &[u8] = b"csd-jwt/link-secret/v1";
+/// Domain separation tag of the pseudonyms.
+const PSEUDONYM_DOMAIN: &[u8] = b"csd-jwt/pseudonym/v1:";
+/// Length in bytes of a link secret.
+const LINK_SECRET_LEN: usize = 32;
+
+/// Secret of the holder from which its pseudonyms are derived. The pseudonym at a verifier is the HMAC-SHA256 of the
+/// identifier of the verifier under the link secret, so that the same holder is recognized by a verifier across
+/// presentations, while the pseudonyms used at different verifiers cannot be linked without the secret.
+///
+/// # Examples
+/// ```
+/// use csd_jwt::pseudonym::LinkSecret;
+///
+/// let link_secret = LinkSecret::generate();
+/// assert_eq!(link_secret.pseudonym("https://verifier.example"), link_secret.pseudonym("https://verifier.example"));
+/// assert_ne!(link_secret.pseudonym("https://verifier.example"), link_secret.pseudonym("https://other.example"));
+/// assert_ne!(link_secret.pseudonym("https://verifier.example"), LinkSecret::generate().pseudonym("https://verifier.example"));
+/// ```
+#[derive(Clone)]
+pub struct LinkSecret {
+    secret: [u8; LINK_SECRET_LEN],
+}
+
+impl LinkSecret {
+
+    /// Generates a random link secret.
+    pub fn generate() -> Self {
+        let mut secret = [0u8; LINK_SECRET_LEN];
+        fill_random(&mut secret[..]);
+        LinkSecret { secret }
+    }
+
+    /// Derives the link secret from the private key of the holder, so that the holder does not need to store another
+    /// secret.
+    ///
+    /// # Arguments
+    /// * `holder_private_key` - Private key of the holder.
+    ///
+    /// # Returns
+    /// The link secret.
+    pub fn from_holder_key(holder_private_key: &impl AsRef<[u8]>) -> Self {
+        LinkSecret { secret: sha256([LINK_SECRET_DOMAIN, holder_private_key.as_ref()].concat()) }
+    }
+
+    /// Derives the pseudonym of the holder at a verifier.
+    ///
+    /// # Arguments
+    /// * `verifier_id` - Identifier of the verifier.
+    ///
+    /// # Returns
+    /// The base64url encoding of the pseudonym.
+    pub fn pseudonym(&self, verifier_id: &str) -> String {
+        let mut mac = match <Hmac<Sha256> as Mac>::new_from_slice(&self.secret) {
+            Ok(mac) => { mac }
+            Err(_) => { unreachable!("HMAC accepts keys of any length") }
+        };
+        mac.update(PSEUDONYM_DOMAIN);
+        mac.update(verifier_id.as_bytes());
+        Encoding::Base64UrlNoPad.encode(mac.finalize().into_bytes())
+    }
+}
+
+
+/// Wraps a VP in a pseudonymous presentation for a verifier: a JWT signed by the holder carrying the VP, the pseudonym
+/// of the holder at the verifier and the identifier of the verifier as audience.
+///
+/// # Arguments
+/// * `vp_jwt` - VP to be presented, ideally bound to the verifier as audience.
+/// * `verifier_id` - Identifier of the verifier.
+/// * `link_secret` - Link secret of the holder.
+/// * `holder_private_key` - Private key of the holder.
+///
+/// # Returns
+/// A result containing the JWT of the pseudonymous presentation or a string containing an error.
+pub fn issue_pseudonymous_vp(vp_jwt: &str, verifier_id: &str, link_secret: &LinkSecret, holder_private_key: &impl AsRef<[u8]>) -> Result<String, String> {
+    let mut claims: Map<String, Value> = Map::new();
+    claims.insert(VP_TOKEN.to_string(), Value::String(vp_jwt.to_string()));
+    claims.insert(PSEUDONYM.to_string(), Value::String(link_secret.pseudonym(verifier_id)));
+    claims.insert(AUDIENCE.to_string(), Value::String(verifier_id.to_string()));
+
+    let payload = match JwtPayload::from_map(claims) {
+        Ok(payload) => { payload }
+        Err(err) => { return Err(format!("Failed to build pseudonymous presentation: [{err}]")) }
+    };
+    let signer = match ES256.signer_from_pem(holder_private_key) {
+        Ok(signer) => { signer }
+        Err(err) => { return Err(format!("Failed to create signer: [{err}]")) }
+    };
+
+    match jwt::encode_with_signer(&payload, &JwsHeader::new(), &signer) {
+        Ok(jwt) => { Ok(jwt) }
+        Err(err) => { Err(format!("Failed to encode and sign pseudonymous presentation: [{err}]")) }
+    }
+}
+
+/// Checks the envelope of a pseudonymous presentation: the signature of the holder, the audience and, if the VP it
+/// carries is bound to an audience, that it is bound to the same verifier. The VP itself must then be verified with
+/// the verifier of its algorithm.
+///
+/// # Arguments
+/// * `jwt` - JWT of the pseudonymous presentation.
+/// * `verifier_id` - Identifier of the verifier.
+/// * `holder_public_key` - Public key of the holder.
+///
+/// # Returns
+/// A result containing the pseudonym and the VP JWT, or a string containing an error.
+pub fn open_pseudonymous_vp(jwt: &str, verifier_id: &str, holder_public_key: &impl AsRef<[u8]>) -> Result<(String, String), String> {
+    let verifier = match ES256.verifier_from_pem(holder_public_key) {
+        Ok(verifier) => { verifier }
+        Err(err) => { return Err(format!("Failed to create verifier: [{err}]")) }
+    };
+    let claims: Map<String, Value> = match jwt::decode_with_verifier(jwt, &verifier) {
+        Ok((payload, _)) => { payload.claims_set().clone() }
+        Err(err) => { return Err(format!("Failed to decode and verify pseudonymous presentation: [{err}]")) }
+    };
+
+    if claims.get(AUDIENCE) != Some(&Value::String(verifier_id.to_string())) {
+        return Err(format!("Pseudonymous presentation is not addressed to {verifier_id}"));
+    }
+    let pseudonym: String = match claims.get(PSEUDONYM) {
+        Some(Value::String(pseudonym)) if Encoding::Base64UrlNoPad.decode(pseudonym).is_ok_and(|bytes| bytes.len() == 32) => { pseudonym.clone() }
+        _ => { return Err(format!("Pseudonymous presentation does not contain a valid {PSEUDONYM}")) }
+    };
+    let vp_jwt: String = match claims.get(VP_TOKEN) {
+        Some(Value::String(vp_jwt)) => { vp_jwt.clone() }
+        _ => { return Err(format!("Pseudonymous presentation does not contain {VP_TOKEN}")) }
+    };
+
+    // The VP itself is verified by its algorithm, its audience is only compared here.
+    match unverified_claims(&vp_jwt)?.get(AUDIENCE) {
+        Some(Value::String(audience)) if audience != verifier_id => { Err(format!("VP is bound to {audience}, not to {verifier_id}")) }
+        _ => { Ok((pseudonym, vp_jwt)) }
+    }
+}
+
+
+/// Pseudonyms seen by a verifier, to recognize returning holders and to check that every holder key always presents
+/// the same pseudonym, i.e. that the pseudonyms are consistently derived from a single link secret.
+///
+/// # Examples
+/// ```
+/// use csd_jwt::common_data::CommonData;
+/// use csd_jwt::pseudonym::{LinkSecret, PseudonymRegistry};
+///
+/// let (holder_public_key, holder_private_key) = CommonData::holder_keys().unwrap();
+/// let link_secret = LinkSecret::from_holder_key(&holder_private_key);
+/// let mut registry = PseudonymRegistry::new();
+///
+/// // Pseudonyms are stable for a verifier and unlinkable across verifiers.
+/// let pseudonym = link_secret.pseudonym("https://verifier.example");
+/// assert_eq!(pseudonym, LinkSecret::from_holder_key(&holder_private_key).pseudonym("https://verifier.example"));
+/// assert_ne!(pseudonym, link_secret.pseudonym("https://other-verifier.example"));
+///
+/// assert!(!registry.recognize(&pseudonym, &holder_public_key).unwrap());
+/// assert!(registry.recognize(&pseudonym, &holder_public_key).unwrap());
+/// assert!(registry.recognize(&LinkSecret::generate().pseudonym("https://verifier.example"), &holder_public_key).is_err());
+/// ```
+#[derive(Default)]
+pub struct PseudonymRegistry {
+    /// Pseudonym presented by every holder key, keyed by the thumbprint of the key.
+    pseudonyms: HashMap<String, String>,
+}
+
+impl PseudonymRegistry {
+
+    /// Constructor for an empty registry.
+    pub fn new() -> Self {
+        PseudonymRegistry { pseudonyms: HashMap::new() }
+    }
+
+    /// Records the pseudonym presented with a holder key.
+    ///
+    /// # Arguments
+    /// * `pseudonym` - Pseudonym of the presentation.
+    /// * `holder_public_key` - Public key of the holder that signed the presentation.
+    ///
+    /// # Returns
+    /// A result containing true if the holder was already seen, or a string containing an error if the holder key
+    /// previously presented a different pseudonym.
+    pub fn recognize(&mut self, pseudonym: &str, holder_public_key: &impl AsRef<[u8]>) -> Result<bool, String> {
+        let thumbprint = jwk_thumbprint(holder_public_key)?;
+        match self.pseudonyms.get(&thumbprint) {
+            Some(known) if known == pseudonym => { Ok(true) }
+            Some(_) => { Err("Holder presented a pseudonym inconsistent with its previous presentations".to_string()) }
+            None => {
+                self.pseudonyms.insert(thumbprint, pseudonym.to_string());
+                Ok(false)
+            }
+        }
+    }
+
+    /// Amount of distinct holders seen.
+    pub fn len(&self) -> usize {
+        self.pseudonyms.len()
+    }
+
+    /// Whether no holder was seen yet.
+    pub fn is_empty(&self) -> bool {
+        self.pseudonyms.is_empty()
+    }
+}
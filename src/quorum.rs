@@ -0,0 +1,185 @@
+use std::fmt;
+use crate::adapters::adapter::VerifierAdapter;
+
+/// Outcome of the verification of a presentation by one authority of a quorum.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AuthorityReport {
+    /// Name of the authority, e.g. the issuer or trust registry its key is published by.
+    pub name: String,
+    /// Result of the verification, with the error of the authority if it rejected the presentation.
+    pub result: Result<(), String>,
+}
+
+
+/// Report of the verification of a presentation by every authority of a quorum, in the order they were configured.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct QuorumReport {
+    /// Amount of authorities that must accept the presentation.
+    pub threshold: usize,
+    /// Outcome of every authority.
+    pub authorities: Vec<AuthorityReport>,
+}
+
+impl QuorumReport {
+
+    /// Names of the authorities that accepted the presentation.
+    pub fn accepted(&self) -> Vec<&str> {
+        self.authorities.iter().filter(|authority| authority.result.is_ok()).map(|authority| authority.name.as_str()).collect()
+    }
+
+    /// Names of the authorities that rejected the presentation.
+    pub fn rejected(&self) -> Vec<&str> {
+        self.authorities.iter().filter(|authority| authority.result.is_err()).map(|authority| authority.name.as_str()).collect()
+    }
+
+    /// Whether at least `threshold` authorities accepted the presentation.
+    pub fn is_met(&self) -> bool {
+        self.accepted().len() >= self.threshold
+    }
+
+    /// Converts the report to a result, listing the error of every authority that rejected the presentation if the
+    /// quorum is not met.
+    pub fn into_result(self) -> Result<(), String> {
+        if self.is_met() {
+            return Ok(());
+        }
+        Err(format!("Presentation accepted by {} of {} authorities, {} required: [{self}]", self.accepted().len(), self.authorities.len(), self.threshold))
+    }
+}
+
+impl fmt::Display for QuorumReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let outcomes: Vec<String> = self.authorities.iter().map(|authority| match &authority.result {
+            Ok(()) => { format!("{} accepted", authority.name) }
+            Err(err) => { format!("{} rejected: {err}", authority.name) }
+        }).collect();
+        write!(f, "{}", outcomes.join("; "))
+    }
+}
+
+
+/// Verifier of presentations in federated issuance scenarios, accepting a presentation when at least `k` of the `n`
+/// configured authorities accept it. Every authority is a verifier holding the issuer key, and possibly the registry,
+/// it trusts, so the quorum logic is the same for every algorithm. Every authority is always consulted, so that the
+/// report tells which of them accepted even when the quorum is met early.
+///
+/// # Examples
+/// ```
+/// use csd_jwt::adapters::adapter::Adapter;
+/// use csd_jwt::adapters::hashes::sd_jwt_adapter::SdJwtAdapter;
+/// use csd_jwt::common_data::VC;
+/// use csd_jwt::quorum::QuorumVerifier;
+///
+/// let national = SdJwtAdapter::new(2).unwrap();
+/// let regional = SdJwtAdapter::new(2).unwrap();
+/// let (vc, _vc_jwt) = national.issue_vc(&serde_json::from_str(VC).unwrap()).unwrap();
+/// let (_vp, vp_jwt) = national.issue_vp(&vc, &vec!["name".to_string()]).unwrap();
+///
+/// let quorum = QuorumVerifier::new(1)
+///     .with_authority("national", &national)
+///     .with_authority("regional", &regional);
+/// let report = quorum.verify_vp(&vp_jwt).unwrap();
+/// assert_eq!(report.accepted(), vec!["national"]);
+/// assert_eq!(report.rejected(), vec!["regional"]);
+/// assert!(report.is_met());
+///
+/// let quorum = QuorumVerifier::new(2)
+///     .with_authority("national", &national)
+///     .with_authority("regional", &regional);
+/// assert!(quorum.verify_vp(&vp_jwt).unwrap().into_result().is_err());
+/// ```
+pub struct QuorumVerifier<'a> {
+    /// Amount of authorities that must accept a presentation.
+    threshold: usize,
+    /// Name and verifier of every authority.
+    authorities: Vec<(String, &'a dyn VerifierAdapter)>,
+}
+
+impl<'a> QuorumVerifier<'a> {
+
+    /// Constructor for a quorum without any authority.
+    ///
+    /// # Arguments
+    /// * `threshold` - Amount of authorities that must accept a presentation, between 1 and the amount of authorities.
+    ///
+    /// # Returns
+    /// The verifier, to which authorities are added with `with_authority`.
+    pub fn new(threshold: usize) -> Self {
+        QuorumVerifier { threshold, authorities: Vec::new() }
+    }
+
+    /// Adds an authority to the quorum.
+    ///
+    /// # Arguments
+    /// * `name` - Name of the authority, reported in the outcome of every verification.
+    /// * `verifier` - Verifier holding the issuer key and registry trusted by the authority.
+    pub fn with_authority(mut self, name: &str, verifier: &'a dyn VerifierAdapter) -> Self {
+        self.authorities.push((name.to_string(), verifier));
+        self
+    }
+
+    /// Verifies a presentation against every authority of the quorum.
+    ///
+    /// # Arguments
+    /// * `vp_jwt` - JWT of the Verifiable Presentation.
+    ///
+    /// # Returns
+    /// A result containing the report of every authority, or a string containing an error if the threshold is not
+    /// between 1 and the amount of authorities or if two authorities share a name.
+    pub fn verify_vp(&self, vp_jwt: &str) -> Result<QuorumReport, String> {
+        if self.threshold == 0 || self.threshold > self.authorities.len() {
+            return Err(format!("Quorum threshold {} is not between 1 and the amount of authorities {}", self.threshold, self.authorities.len()));
+        }
+        for (index, (name, _)) in self.authorities.iter().enumerate() {
+            if self.authorities[..index].iter().any(|(other, _)| other == name) {
+                return Err(format!("Authority {name} is configured more than once"));
+            }
+        }
+
+        let authorities: Vec<AuthorityReport> = self.authorities.iter().map(|(name, verifier)| {
+            AuthorityReport { name: name.clone(), result: verifier.verify_vp(vp_jwt) }
+        }).collect();
+
+        Ok(QuorumReport { threshold: self.threshold, authorities })
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{Map, Value};
+    use crate::adapters::adapter::Adapter;
+    use crate::adapters::hashes::sd_jwt_adapter::SdJwtAdapter;
+    use crate::common_data::VC;
+    use crate::quorum::QuorumVerifier;
+
+    #[test]
+    fn quorum_counts_accepting_authorities() -> Result<(), String> {
+        let raw_vc: Map<String, Value> = match serde_json::from_str::<Map<String, Value>>(VC) {
+            Ok(vc) => { vc }
+            Err(err) => { return Err(format!("Failed to parse Raw Verifiable Credential. [{err}]")); }
+        };
+
+        let issuer = SdJwtAdapter::new(13)?;
+        let other = SdJwtAdapter::new(13)?;
+        let (vc, _vc_jwt) = issuer.issue_vc(&raw_vc)?;
+        let (_vp, vp_jwt) = issuer.issue_vp(&vc, &vec!["name".to_string()])?;
+
+        let quorum = |threshold: usize| QuorumVerifier::new(threshold)
+            .with_authority("first", &issuer)
+            .with_authority("second", &other)
+            .with_authority("third", &issuer);
+
+        let report = quorum(2).verify_vp(&vp_jwt)?;
+        assert_eq!(report.accepted(), vec!["first", "third"]);
+        assert_eq!(report.rejected(), vec!["second"]);
+        assert!(report.into_result().is_ok());
+
+        assert!(quorum(3).verify_vp(&vp_jwt)?.into_result().is_err(), "Quorum met without every authority accepting.");
+        assert!(quorum(0).verify_vp(&vp_jwt).is_err(), "Empty threshold was accepted.");
+        assert!(quorum(4).verify_vp(&vp_jwt).is_err(), "Threshold above the amount of authorities was accepted.");
+        assert!(QuorumVerifier::new(1).with_authority("first", &issuer).with_authority("first", &other).verify_vp(&vp_jwt).is_err(), "Duplicate authority was accepted.");
+
+        Ok(())
+    }
+}
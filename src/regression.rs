@@ -0,0 +1,292 @@
+use std::collections::BTreeMap;
+use std::f64::consts::PI;
+use crate::csv_writer::CSVWriter;
+use crate::results_db::SampleLog;
+
+/// Default relative increase of a metric above which a change is a regression.
+pub const DEFAULT_THRESHOLD: f64 = 0.05;
+/// Default significance level of the test on the durations.
+pub const DEFAULT_SIGNIFICANCE: f64 = 0.05;
+
+/// Thresholds above which a change between two runs is reported as a regression.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RegressionThresholds {
+    /// Relative increase of a duration, e.g. 0.05 for 5% slower.
+    pub max_slowdown: f64,
+    /// Relative increase of a size, e.g. 0.01 for 1% larger.
+    pub max_size_increase: f64,
+    /// Significance level the slowdowns are tested at, as durations are noisy.
+    pub significance: f64,
+}
+
+impl Default for RegressionThresholds {
+    fn default() -> Self {
+        RegressionThresholds { max_slowdown: DEFAULT_THRESHOLD, max_size_increase: DEFAULT_THRESHOLD, significance: DEFAULT_SIGNIFICANCE }
+    }
+}
+
+/// Change of a metric of an algorithm between a baseline and a candidate run.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MetricComparison {
+    /// Name of the metric.
+    pub metric: String,
+    /// Name of the algorithm.
+    pub algorithm: String,
+    /// Amount of points, i.e. amounts of claims and disclosures, measured in both runs.
+    pub points: usize,
+    /// Mean value of the metric over the points in the baseline.
+    pub baseline_mean: f64,
+    /// Mean value of the metric over the points in the candidate.
+    pub candidate_mean: f64,
+    /// Mean relative change of the points, positive when the candidate is slower or larger.
+    pub relative_delta: f64,
+    /// Two-sided p-value of the paired t-test of the relative changes, 1 with less than two points.
+    pub p_value: f64,
+    /// Whether the change is a regression beyond the thresholds.
+    pub regression: bool,
+}
+
+impl MetricComparison {
+
+    /// Whether the metric is a size, which is deterministic, rather than a duration.
+    pub fn is_size(&self) -> bool {
+        is_size_metric(&self.metric)
+    }
+}
+
+/// Comparison of every metric of two runs, flagging the slowdowns and size increases beyond the thresholds.
+///
+/// # Examples
+/// ```
+/// use csd_jwt::regression::{RegressionReport, RegressionThresholds};
+/// use csd_jwt::results_db::SampleLog;
+///
+/// let algorithms = vec!["SD-JWT".to_string()];
+/// let mut baseline = SampleLog::new();
+/// let mut candidate = SampleLog::new();
+/// for n_claims in 1..=10 {
+///     baseline.record_row("vp_jwt_length", &algorithms, n_claims, Some(1), [Some(100.0 * n_claims as f64)]);
+///     candidate.record_row("vp_jwt_length", &algorithms, n_claims, Some(1), [Some(120.0 * n_claims as f64)]);
+/// }
+///
+/// let report = RegressionReport::compare(&baseline, &candidate, RegressionThresholds::default());
+/// assert_eq!(report.regressions().len(), 1);
+/// assert!((report.comparisons()[0].relative_delta - 0.2).abs() < 1e-9);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct RegressionReport {
+    /// Comparisons, in order of metric and algorithm.
+    comparisons: Vec<MetricComparison>,
+}
+
+/// Metric and algorithm of a series of points.
+type Series = (String, String);
+/// Point of a series, i.e. amount of claims and, for presentations, of disclosures.
+type Point = (usize, Option<usize>);
+/// Values of a metric of an algorithm in a run, indexed by point.
+type Points = BTreeMap<Point, f64>;
+
+impl RegressionReport {
+
+    /// Compares the metrics measured in both runs. Every point, i.e. amount of claims and disclosures, measured in both
+    /// runs is paired, and the relative changes of the points are averaged. Durations are a regression if they are
+    /// slower beyond the threshold and the paired t-test of the changes is significant; sizes, being deterministic,
+    /// only need to exceed the threshold.
+    ///
+    /// # Arguments
+    /// * `baseline` - Samples of the reference run.
+    /// * `candidate` - Samples of the run to be evaluated.
+    /// * `thresholds` - Thresholds of the regressions.
+    ///
+    /// # Returns
+    /// The report.
+    pub fn compare(baseline: &SampleLog, candidate: &SampleLog, thresholds: RegressionThresholds) -> Self {
+        let baseline_points = index_points(baseline);
+        let candidate_points = index_points(candidate);
+        let mut comparisons: Vec<MetricComparison> = vec![];
+
+        for ((metric, algorithm), baseline_values) in &baseline_points {
+            let candidate_values: &Points = match candidate_points.get(&(metric.clone(), algorithm.clone())) {
+                Some(candidate_values) => { candidate_values }
+                None => { continue }
+            };
+
+            let pairs: Vec<(f64, f64)> = baseline_values
+                .iter()
+                .filter_map(|(point, baseline_value)| candidate_values.get(point).map(|candidate_value| (*baseline_value, *candidate_value)))
+                .filter(|(baseline_value, _)| *baseline_value > 0.0)
+                .collect();
+            if pairs.is_empty() {
+                continue;
+            }
+
+            let deltas: Vec<f64> = pairs.iter().map(|(baseline_value, candidate_value)| (candidate_value - baseline_value) / baseline_value).collect();
+            let relative_delta: f64 = mean(&deltas);
+            let p_value: f64 = paired_p_value(&deltas);
+            let regression: bool = if is_size_metric(metric) {
+                relative_delta > thresholds.max_size_increase
+            } else {
+                relative_delta > thresholds.max_slowdown && p_value < thresholds.significance
+            };
+
+            comparisons.push(MetricComparison {
+                metric: metric.clone(),
+                algorithm: algorithm.clone(),
+                points: pairs.len(),
+                baseline_mean: mean(&pairs.iter().map(|(baseline_value, _)| *baseline_value).collect::<Vec<f64>>()),
+                candidate_mean: mean(&pairs.iter().map(|(_, candidate_value)| *candidate_value).collect::<Vec<f64>>()),
+                relative_delta,
+                p_value,
+                regression,
+            });
+        }
+
+        RegressionReport { comparisons }
+    }
+
+    /// Comparisons of every metric and algorithm measured in both runs.
+    pub fn comparisons(&self) -> &[MetricComparison] {
+        &self.comparisons
+    }
+
+    /// Comparisons flagged as regressions.
+    pub fn regressions(&self) -> Vec<&MetricComparison> {
+        self.comparisons.iter().filter(|comparison| comparison.regression).collect()
+    }
+
+    /// Writes the report to a CSV file, one comparison per row.
+    ///
+    /// # Arguments
+    /// * `filename` - Name of the CSV file.
+    ///
+    /// # Returns
+    /// The result of the operation or a string containing an error.
+    pub fn write_report(&self, filename: &String) -> Result<(), String> {
+        let columns: Vec<String> = ["metric", "algorithm", "points", "baseline_mean", "candidate_mean", "relative_delta", "p_value", "regression"]
+            .iter()
+            .map(|column| column.to_string())
+            .collect();
+        let mut writer = CSVWriter::new(columns)?;
+        writer.add_file(filename)?;
+
+        for comparison in &self.comparisons {
+            writer.write_record_to_file(filename, vec![
+                comparison.metric.clone(),
+                comparison.algorithm.clone(),
+                comparison.points.to_string(),
+                comparison.baseline_mean.to_string(),
+                comparison.candidate_mean.to_string(),
+                comparison.relative_delta.to_string(),
+                comparison.p_value.to_string(),
+                comparison.regression.to_string(),
+            ])?;
+        }
+
+        Ok(())
+    }
+}
+
+
+/// Whether a metric is a size (`*_length`, `*_chunks`) rather than a duration.
+fn is_size_metric(metric: &str) -> bool {
+    metric.ends_with("_length") || metric.ends_with("_chunks")
+}
+
+/// Groups the samples of a run by metric and algorithm, averaging the samples of the same point.
+fn index_points(sample_log: &SampleLog) -> BTreeMap<Series, Points> {
+    let mut sums: BTreeMap<Series, BTreeMap<Point, (f64, usize)>> = BTreeMap::new();
+    for sample in sample_log.samples() {
+        let sum = sums
+            .entry((sample.metric.clone(), sample.algorithm.clone()))
+            .or_default()
+            .entry((sample.claims, sample.disclosures))
+            .or_insert((0.0, 0));
+        sum.0 += sample.value;
+        sum.1 += 1;
+    }
+
+    sums.into_iter()
+        .map(|(series, points)| (series, points.into_iter().map(|(point, (sum, count))| (point, sum / count as f64)).collect()))
+        .collect()
+}
+
+/// Arithmetic mean of non-empty values.
+fn mean(values: &[f64]) -> f64 {
+    values.iter().sum::<f64>() / values.len() as f64
+}
+
+/// Two-sided p-value of the paired t-test of the changes against no change. Without variance, the changes are either
+/// certainly significant or null.
+fn paired_p_value(deltas: &[f64]) -> f64 {
+    let n: f64 = deltas.len() as f64;
+    if deltas.len() < 2 {
+        return 1.0;
+    }
+
+    let delta_mean: f64 = mean(deltas);
+    let variance: f64 = deltas.iter().map(|delta| (delta - delta_mean).powi(2)).sum::<f64>() / (n - 1.0);
+    if variance == 0.0 {
+        return if delta_mean == 0.0 { 1.0 } else { 0.0 };
+    }
+
+    let t: f64 = delta_mean / (variance / n).sqrt();
+    let degrees: f64 = n - 1.0;
+    regularized_incomplete_beta(degrees / (degrees + t * t), degrees / 2.0, 0.5)
+}
+
+/// Regularized incomplete beta function I_x(a, b), evaluated through its continued fraction.
+fn regularized_incomplete_beta(x: f64, a: f64, b: f64) -> f64 {
+    if x <= 0.0 {
+        return 0.0;
+    }
+    if x >= 1.0 {
+        return 1.0;
+    }
+
+    let front: f64 = (ln_gamma(a + b) - ln_gamma(a) - ln_gamma(b) + a * x.ln() + b * (1.0 - x).ln()).exp();
+    if x < (a + 1.0) / (a + b + 2.0) {
+        front * beta_continued_fraction(x, a, b) / a
+    } else {
+        1.0 - front * beta_continued_fraction(1.0 - x, b, a) / b
+    }
+}
+
+/// Continued fraction of the incomplete beta function, by the modified Lentz method.
+fn beta_continued_fraction(x: f64, a: f64, b: f64) -> f64 {
+    const MAX_ITERATIONS: usize = 200;
+    const EPSILON: f64 = 1e-12;
+    const TINY: f64 = 1e-300;
+
+    let mut c: f64 = 1.0;
+    let mut d: f64 = 1.0 - (a + b) * x / (a + 1.0);
+    d = if d.abs() < TINY { TINY } else { d }.recip();
+    let mut fraction: f64 = d;
+
+    for m in 1..=MAX_ITERATIONS {
+        let m: f64 = m as f64;
+        for numerator in [
+            m * (b - m) * x / ((a + 2.0 * m - 1.0) * (a + 2.0 * m)),
+            -(a + m) * (a + b + m) * x / ((a + 2.0 * m) * (a + 2.0 * m + 1.0)),
+        ] {
+            d = 1.0 + numerator * d;
+            d = if d.abs() < TINY { TINY } else { d }.recip();
+            c = 1.0 + numerator / c;
+            c = if c.abs() < TINY { TINY } else { c };
+            fraction *= c * d;
+        }
+        if (c * d - 1.0).abs() < EPSILON {
+            break;
+        }
+    }
+
+    fraction
+}
+
+/// Natural logarithm of the gamma function, by the Lanczos approximation.
+fn ln_gamma(x: f64) -> f64 {
+    const COEFFICIENTS: [f64; 6] = [76.18009172947146, -86.50532032941677, 24.01409824083091, -1.231739572450155, 1.208650973866179e-3, -5.395239384953e-6];
+
+    let tmp: f64 = x + 5.5;
+    let series: f64 = COEFFICIENTS.iter().enumerate().fold(1.000000000190015, |series, (index, coefficient)| series + coefficient / (x + 1.0 + index as f64));
+    ((2.0 * PI).sqrt() * series / x).ln() - tmp + (x + 0.5) * tmp.ln()
+}
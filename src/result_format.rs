@@ -0,0 +1,249 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use serde_json::{Map, Value};
+use crate::results_db::{Sample, SampleLog};
+
+/// Type of the document, written in every result document.
+pub const RESULT_TYPE: &str = "csd-jwt-benchmark-results";
+/// Version of the result format. It only changes when fields are added, as fields are never removed or redefined, so
+/// that readers of any version can read documents of any other version.
+pub const FORMAT_VERSION: u64 = 1;
+/// Name of the JSON file holding the result document of a sweep, next to the CSV files.
+pub const RESULTS_FILE: &str = "results.json";
+
+/// Field holding the type of the document.
+const TYPE_FIELD: &str = "type";
+/// Field holding the version of the format the document was written with.
+pub const FORMAT_VERSION_FIELD: &str = "format_version";
+/// Field holding the label of the run.
+const LABEL_FIELD: &str = "label";
+/// Field holding the version of the crate that produced the document.
+const CRATE_VERSION_FIELD: &str = "crate_version";
+/// Field holding the settings of the run.
+const SETTINGS_FIELD: &str = "settings";
+/// Field holding the samples of the run.
+const SAMPLES_FIELD: &str = "samples";
+
+/// Versioned, self-describing document holding every sample of a benchmark run, along with the settings it ran with.
+/// Samples are one entry per metric, algorithm and point, so that new metrics add entries rather than columns and do
+/// not break the scripts analysing the results.
+///
+/// Reading is lenient to keep older and newer documents readable: unknown fields and unknown metrics are ignored,
+/// optional fields missing from older documents take their default, and only the type and the samples are required.
+///
+/// # Examples
+/// ```
+/// use csd_jwt::result_format::{ResultDocument, FORMAT_VERSION};
+/// use csd_jwt::results_db::SampleLog;
+///
+/// let mut samples = SampleLog::new();
+/// samples.record_row("vc_jwt_length", &["SD-JWT".to_string()], 10, None, [Some(1024.0)]);
+/// let document = ResultDocument::new("nightly", samples).with_setting("transport", "memory");
+///
+/// let json = document.to_json().unwrap();
+/// let read_document = ResultDocument::from_json(&json.replace("\"label\":\"nightly\",", "\"new_field\":[],")).unwrap();
+/// assert_eq!(read_document.format_version, FORMAT_VERSION);
+/// assert_eq!(read_document.label, "");
+/// assert_eq!(read_document.samples.samples(), document.samples.samples());
+/// ```
+#[derive(Clone, Debug)]
+pub struct ResultDocument {
+    /// Version of the format the document was written with.
+    pub format_version: u64,
+    /// Label of the run, e.g. the benchmarked commit.
+    pub label: String,
+    /// Version of the crate that produced the document.
+    pub crate_version: String,
+    /// Settings of the run, e.g. the measurement or the transport, by name.
+    pub settings: BTreeMap<String, String>,
+    /// Samples of the run.
+    pub samples: SampleLog,
+}
+
+impl ResultDocument {
+
+    /// Constructor for a document of the current format version.
+    ///
+    /// # Arguments
+    /// * `label` - Label of the run.
+    /// * `samples` - Samples of the run.
+    ///
+    /// # Returns
+    /// The document, without settings.
+    pub fn new(label: &str, samples: SampleLog) -> Self {
+        ResultDocument {
+            format_version: FORMAT_VERSION,
+            label: label.to_string(),
+            crate_version: env!("CARGO_PKG_VERSION").to_string(),
+            settings: BTreeMap::new(),
+            samples,
+        }
+    }
+
+    /// Adds a setting of the run to the document.
+    ///
+    /// # Arguments
+    /// * `name` - Name of the setting.
+    /// * `value` - Value of the setting.
+    ///
+    /// # Returns
+    /// The document including the setting.
+    pub fn with_setting(mut self, name: &str, value: &str) -> Self {
+        self.settings.insert(name.to_string(), value.to_string());
+        self
+    }
+
+    /// Converts the document to a JSON object.
+    pub fn to_map(&self) -> Map<String, Value> {
+        let mut document: Map<String, Value> = Map::new();
+        document.insert(TYPE_FIELD.to_string(), Value::String(RESULT_TYPE.to_string()));
+        document.insert(FORMAT_VERSION_FIELD.to_string(), Value::from(self.format_version));
+        document.insert(LABEL_FIELD.to_string(), Value::String(self.label.clone()));
+        document.insert(CRATE_VERSION_FIELD.to_string(), Value::String(self.crate_version.clone()));
+        document.insert(SETTINGS_FIELD.to_string(), Value::Object(self.settings.iter().map(|(name, value)| (name.clone(), Value::String(value.clone()))).collect()));
+        document.insert(SAMPLES_FIELD.to_string(), Value::Array(self.samples.samples().iter().map(sample_to_json).collect()));
+
+        document
+    }
+
+    /// Reads a document from a JSON object of any format version.
+    ///
+    /// # Arguments
+    /// * `document` - Object produced by `to_map`.
+    ///
+    /// # Returns
+    /// A result containing the document or a string containing an error.
+    pub fn from_map(document: &Map<String, Value>) -> Result<Self, String> {
+        match document.get(TYPE_FIELD) {
+            Some(Value::String(document_type)) if document_type == RESULT_TYPE => {}
+            Some(document_type) => { return Err(format!("Unsupported {TYPE_FIELD} {document_type} in result document, expected {RESULT_TYPE}")) }
+            None => { return Err(format!("Result document does not contain {TYPE_FIELD}")) }
+        }
+
+        let format_version: u64 = match document.get(FORMAT_VERSION_FIELD) {
+            Some(format_version) => match format_version.as_u64() {
+                Some(format_version) => { format_version }
+                None => { return Err(format!("{FORMAT_VERSION_FIELD} of the result document is not an unsigned integer")) }
+            },
+            None => { return Err(format!("Result document does not contain {FORMAT_VERSION_FIELD}")) }
+        };
+        let optional_string = |field: &str| -> String {
+            document.get(field).and_then(Value::as_str).unwrap_or_default().to_string()
+        };
+        let settings: BTreeMap<String, String> = match document.get(SETTINGS_FIELD) {
+            Some(Value::Object(settings)) => {
+                settings.iter().filter_map(|(name, value)| value.as_str().map(|value| (name.clone(), value.to_string()))).collect()
+            }
+            _ => { BTreeMap::new() }
+        };
+
+        let mut samples: SampleLog = SampleLog::new();
+        match document.get(SAMPLES_FIELD) {
+            Some(Value::Array(entries)) => for (index, entry) in entries.iter().enumerate() {
+                match sample_from_json(entry) {
+                    Ok(sample) => { samples.push(sample) }
+                    Err(err) => { return Err(format!("Invalid sample {index} in result document: [{err}]")) }
+                }
+            },
+            _ => { return Err(format!("Result document does not contain an array of {SAMPLES_FIELD}")) }
+        }
+
+        Ok(ResultDocument { format_version, label: optional_string(LABEL_FIELD), crate_version: optional_string(CRATE_VERSION_FIELD), settings, samples })
+    }
+
+    /// Serializes the document to JSON.
+    ///
+    /// # Returns
+    /// A result containing the JSON string or a string containing an error.
+    pub fn to_json(&self) -> Result<String, String> {
+        match serde_json::to_string(&self.to_map()) {
+            Ok(json) => { Ok(json) }
+            Err(err) => { Err(format!("Failed to serialize result document to JSON: [{err}]")) }
+        }
+    }
+
+    /// Deserializes a document from JSON.
+    ///
+    /// # Arguments
+    /// * `json` - JSON string produced by `to_json`, by any format version.
+    ///
+    /// # Returns
+    /// A result containing the document or a string containing an error.
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        match serde_json::from_str::<Map<String, Value>>(json) {
+            Ok(document) => { Self::from_map(&document) }
+            Err(err) => { Err(format!("Failed to parse result document from JSON: [{err}]")) }
+        }
+    }
+
+    /// Writes the document to a file.
+    ///
+    /// # Arguments
+    /// * `path` - Path of the file.
+    ///
+    /// # Returns
+    /// The result of the operation or a string containing an error.
+    pub fn write(&self, path: &Path) -> Result<(), String> {
+        match fs::write(path, self.to_json()?) {
+            Ok(()) => { Ok(()) }
+            Err(err) => { Err(format!("Failed to write result document {}: [{err}]", path.display())) }
+        }
+    }
+
+    /// Reads a document from a file.
+    ///
+    /// # Arguments
+    /// * `path` - Path of the file.
+    ///
+    /// # Returns
+    /// A result containing the document or a string containing an error.
+    pub fn read(path: &Path) -> Result<Self, String> {
+        match fs::read_to_string(path) {
+            Ok(json) => { Self::from_json(&json) }
+            Err(err) => { Err(format!("Failed to read result document {}: [{err}]", path.display())) }
+        }
+    }
+}
+
+
+/// Converts a sample to its entry in the document, without the disclosures of the metrics of credentials.
+fn sample_to_json(sample: &Sample) -> Value {
+    let mut entry: Map<String, Value> = Map::new();
+    entry.insert("metric".to_string(), Value::String(sample.metric.clone()));
+    entry.insert("algorithm".to_string(), Value::String(sample.algorithm.clone()));
+    entry.insert("claims".to_string(), Value::from(sample.claims));
+    if let Some(disclosures) = sample.disclosures {
+        entry.insert("disclosures".to_string(), Value::from(disclosures));
+    }
+    entry.insert("value".to_string(), Value::from(sample.value));
+
+    Value::Object(entry)
+}
+
+/// Reads a sample from its entry in the document, ignoring unknown fields.
+fn sample_from_json(entry: &Value) -> Result<Sample, String> {
+    let string = |field: &str| -> Result<String, String> {
+        match entry.get(field).and_then(Value::as_str) {
+            Some(value) => { Ok(value.to_string()) }
+            None => { Err(format!("{field} is not a string")) }
+        }
+    };
+    let claims: usize = match entry.get("claims").and_then(Value::as_u64) {
+        Some(claims) => { claims as usize }
+        None => { return Err("claims is not an unsigned integer".to_string()) }
+    };
+    let disclosures: Option<usize> = match entry.get("disclosures") {
+        None | Some(Value::Null) => { None }
+        Some(disclosures) => match disclosures.as_u64() {
+            Some(disclosures) => { Some(disclosures as usize) }
+            None => { return Err("disclosures is not an unsigned integer".to_string()) }
+        }
+    };
+    let value: f64 = match entry.get("value").and_then(Value::as_f64) {
+        Some(value) => { value }
+        None => { return Err("value is not a number".to_string()) }
+    };
+
+    Ok(Sample { metric: string("metric")?, algorithm: string("algorithm")?, claims, disclosures, value })
+}
@@ -0,0 +1,402 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fs::{self, create_dir_all, File};
+use std::io::{self, Stdout, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use csv::{ReaderBuilder, Writer, WriterBuilder};
+use serde::Serialize;
+use serde_json::{Map, Number, Value};
+use crate::csv_writer::CSV_DIR;
+
+/// Extension of csv files.
+const CSV_EXT: &str = ".csv";
+
+/// Destination of the results of the benchmarks, e.g. CSV files, a live stream or memory. Records are grouped in named
+/// files, each with its own columns, and arrive as the fields of a CSV row.
+pub trait ResultSink {
+
+    /// Starts a new file.
+    ///
+    /// # Arguments
+    /// * `filename` - Name of the file, without extension.
+    /// * `columns` - Names of the columns of the file.
+    ///
+    /// # Returns
+    /// The result of the operation or a string containing an error.
+    fn add_file(&mut self, filename: &str, columns: &[String]) -> Result<(), String>;
+
+    /// Writes a record to a file previously started with `add_file`.
+    ///
+    /// # Arguments
+    /// * `filename` - Name of the file.
+    /// * `fields` - Fields of the record, as written in a CSV row.
+    ///
+    /// # Returns
+    /// The result of the operation or a string containing an error.
+    fn write_record(&mut self, filename: &str, fields: &[String]) -> Result<(), String>;
+
+    /// Flushes the records written so far.
+    ///
+    /// # Returns
+    /// The result of the operation or a string containing an error.
+    fn flush(&mut self) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+
+/// Sink writing every file as a CSV file in the csv directory, with the columns as header.
+pub struct CsvFileSink {
+    /// A Map containing the writers for all the files being written.
+    writers: HashMap<String, Writer<File>>,
+}
+
+impl CsvFileSink {
+
+    /// Constructor for the sink, creating the csv directory if needed.
+    ///
+    /// # Returns
+    /// A result containing the sink or a string containing an error.
+    pub fn new() -> Result<Self, String> {
+        check_dir_existence_or_create(Path::new(CSV_DIR))?;
+        Ok(CsvFileSink { writers: HashMap::new() })
+    }
+}
+
+impl ResultSink for CsvFileSink {
+
+    fn add_file(&mut self, filename: &str, columns: &[String]) -> Result<(), String> {
+        let csv_dir: &Path = Path::new(CSV_DIR);
+        check_dir_existence_or_create(csv_dir)?;
+        let full_path = csv_dir.join(format!("{filename}{CSV_EXT}"));
+
+        let file = match File::create(full_path) {
+            Ok(file) => { file }
+            Err(err) => { return Err(format!("Error in creating file for CSV Writer: [{err}]")) }
+        };
+
+        self.writers.insert(filename.to_string(), Writer::from_writer(file));
+        self.write_record(filename, columns)
+    }
+
+    fn write_record(&mut self, filename: &str, fields: &[String]) -> Result<(), String> {
+        let writer: &mut Writer<File> = match self.writers.get_mut(filename) {
+            None => { return Err(format!("Filename {filename} was not found in map")) }
+            Some(writer) => { writer }
+        };
+
+        match writer.write_record(fields) {
+            Ok(_) => { Ok(()) }
+            Err(err) => { Err(format!("Error in writing record: [{err}]")) }
+        }
+    }
+
+    fn flush(&mut self) -> Result<(), String> {
+        for (filename, writer) in self.writers.iter_mut() {
+            if let Err(err) = writer.flush() {
+                return Err(format!("Error in flushing {filename}: [{err}]"));
+            }
+        }
+        Ok(())
+    }
+}
+
+
+/// Sink streaming every record as a line of newline-delimited JSON, e.g. to stdout for a live dashboard following a
+/// long sweep. Every line is an object holding the name of the file, the time of the record in milliseconds since the
+/// Unix epoch, and the fields of the record by column: numeric fields are numbers, empty fields are null.
+///
+/// # Examples
+/// ```
+/// use serde_json::{json, Value};
+/// use csd_jwt::result_sink::{NdjsonSink, ResultSink};
+///
+/// let mut sink = NdjsonSink::new(Vec::new());
+/// sink.add_file("vc_jwt_length", &["SD-JWT".to_string(), "BBS+".to_string()]).unwrap();
+/// sink.write_record("vc_jwt_length", &["1024".to_string(), "".to_string()]).unwrap();
+///
+/// let line: Value = serde_json::from_slice(&sink.into_inner()).unwrap();
+/// assert_eq!(line["file"], "vc_jwt_length");
+/// assert_eq!(line["values"], json!({"SD-JWT": 1024, "BBS+": null}));
+/// ```
+pub struct NdjsonSink<W: Write> {
+    /// Destination of the lines.
+    output: W,
+    /// Columns of every started file.
+    columns: HashMap<String, Vec<String>>,
+}
+
+impl NdjsonSink<Stdout> {
+
+    /// Constructor for a sink streaming to stdout.
+    pub fn stdout() -> Self {
+        NdjsonSink::new(io::stdout())
+    }
+}
+
+impl<W: Write> NdjsonSink<W> {
+
+    /// Constructor for a sink streaming to any writer.
+    ///
+    /// # Arguments
+    /// * `output` - Destination of the lines.
+    pub fn new(output: W) -> Self {
+        NdjsonSink { output, columns: HashMap::new() }
+    }
+
+    /// Consumes the sink, returning its writer.
+    pub fn into_inner(self) -> W {
+        self.output
+    }
+}
+
+impl<W: Write> ResultSink for NdjsonSink<W> {
+
+    fn add_file(&mut self, filename: &str, columns: &[String]) -> Result<(), String> {
+        self.columns.insert(filename.to_string(), columns.to_vec());
+        Ok(())
+    }
+
+    fn write_record(&mut self, filename: &str, fields: &[String]) -> Result<(), String> {
+        let columns: &Vec<String> = match self.columns.get(filename) {
+            None => { return Err(format!("Filename {filename} was not found in map")) }
+            Some(columns) => { columns }
+        };
+        let values: Map<String, Value> = columns.iter().cloned().zip(fields.iter().map(|field| field_to_json(field))).collect();
+        let timestamp: u128 = SystemTime::now().duration_since(UNIX_EPOCH).map(|elapsed| elapsed.as_millis()).unwrap_or(0);
+
+        let mut line: Map<String, Value> = Map::new();
+        line.insert("file".to_string(), Value::String(filename.to_string()));
+        line.insert("timestamp_ms".to_string(), Value::from(timestamp as u64));
+        line.insert("values".to_string(), Value::Object(values));
+
+        // Every line is flushed as it is written, so that readers see the record as soon as it is measured.
+        match writeln!(self.output, "{}", Value::Object(line)).and_then(|_| self.output.flush()) {
+            Ok(()) => { Ok(()) }
+            Err(err) => { Err(format!("Error in streaming record of {filename}: [{err}]")) }
+        }
+    }
+
+    fn flush(&mut self) -> Result<(), String> {
+        match self.output.flush() {
+            Ok(()) => { Ok(()) }
+            Err(err) => { Err(format!("Error in flushing stream: [{err}]")) }
+        }
+    }
+}
+
+
+/// Sink forwarding every file and record to several sinks, e.g. the CSV files and a live stream.
+pub struct SinkSet {
+    /// Sinks every file and record is forwarded to.
+    sinks: Vec<Box<dyn ResultSink>>,
+}
+
+impl SinkSet {
+
+    /// Constructor for the set.
+    ///
+    /// # Arguments
+    /// * `sinks` - Sinks every file and record is forwarded to, none to discard them.
+    pub fn new(sinks: Vec<Box<dyn ResultSink>>) -> Self {
+        SinkSet { sinks }
+    }
+
+    /// Whether the set has no sink, i.e. discards every record.
+    pub fn is_empty(&self) -> bool {
+        self.sinks.is_empty()
+    }
+}
+
+impl ResultSink for SinkSet {
+
+    fn add_file(&mut self, filename: &str, columns: &[String]) -> Result<(), String> {
+        for sink in self.sinks.iter_mut() {
+            sink.add_file(filename, columns)?;
+        }
+        Ok(())
+    }
+
+    fn write_record(&mut self, filename: &str, fields: &[String]) -> Result<(), String> {
+        for sink in self.sinks.iter_mut() {
+            sink.write_record(filename, fields)?;
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<(), String> {
+        for sink in self.sinks.iter_mut() {
+            sink.flush()?;
+        }
+        Ok(())
+    }
+}
+
+
+/// Columns and records of a file kept by a `MemorySink`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MemoryFile {
+    /// Names of the columns.
+    pub columns: Vec<String>,
+    /// Records, in order of writing.
+    pub records: Vec<Vec<String>>,
+}
+
+/// Sink keeping every file in memory, so that library users and tests can inspect the results programmatically.
+///
+/// # Examples
+/// ```
+/// use csd_jwt::result_sink::{write_serialized, MemorySink, ResultSink};
+///
+/// let mut sink = MemorySink::new();
+/// sink.add_file("vc_jwt_length", &["SD-JWT".to_string(), "BBS+".to_string()]).unwrap();
+/// write_serialized(&mut sink, "vc_jwt_length", &vec![Some(1024), None]).unwrap();
+///
+/// assert_eq!(sink.file("vc_jwt_length").unwrap().records, vec![vec!["1024".to_string(), "".to_string()]]);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct MemorySink {
+    /// Files written so far, by name.
+    files: BTreeMap<String, MemoryFile>,
+}
+
+impl MemorySink {
+
+    /// Constructor for an empty sink.
+    pub fn new() -> Self {
+        MemorySink { files: BTreeMap::new() }
+    }
+
+    /// Retrieves a file written to the sink.
+    ///
+    /// # Arguments
+    /// * `filename` - Name of the file.
+    ///
+    /// # Returns
+    /// The file, if it was added.
+    pub fn file(&self, filename: &str) -> Option<&MemoryFile> {
+        self.files.get(filename)
+    }
+
+    /// Files written to the sink, by name.
+    pub fn files(&self) -> &BTreeMap<String, MemoryFile> {
+        &self.files
+    }
+}
+
+impl ResultSink for MemorySink {
+
+    fn add_file(&mut self, filename: &str, columns: &[String]) -> Result<(), String> {
+        self.files.insert(filename.to_string(), MemoryFile { columns: columns.to_vec(), records: vec![] });
+        Ok(())
+    }
+
+    fn write_record(&mut self, filename: &str, fields: &[String]) -> Result<(), String> {
+        match self.files.get_mut(filename) {
+            Some(file) => { file.records.push(fields.to_vec()); Ok(()) }
+            None => { Err(format!("Filename {filename} was not found in map")) }
+        }
+    }
+}
+
+
+/// Sink writing every file to a single JSON document when flushed, as an object holding the columns and the records of
+/// every file by name. Fields are converted as in `NdjsonSink`.
+pub struct JsonFileSink {
+    /// Path of the JSON document.
+    path: PathBuf,
+    /// Files written so far.
+    files: MemorySink,
+}
+
+impl JsonFileSink {
+
+    /// Constructor for the sink.
+    ///
+    /// # Arguments
+    /// * `path` - Path of the JSON document, written when the sink is flushed.
+    pub fn new(path: &Path) -> Self {
+        JsonFileSink { path: path.to_path_buf(), files: MemorySink::new() }
+    }
+}
+
+impl ResultSink for JsonFileSink {
+
+    fn add_file(&mut self, filename: &str, columns: &[String]) -> Result<(), String> {
+        self.files.add_file(filename, columns)
+    }
+
+    fn write_record(&mut self, filename: &str, fields: &[String]) -> Result<(), String> {
+        self.files.write_record(filename, fields)
+    }
+
+    fn flush(&mut self) -> Result<(), String> {
+        let document: Map<String, Value> = self.files.files().iter().map(|(filename, file)| {
+            let mut entry: Map<String, Value> = Map::new();
+            entry.insert("columns".to_string(), Value::from(file.columns.clone()));
+            entry.insert("records".to_string(), Value::Array(file.records.iter().map(|fields| Value::Array(fields.iter().map(|field| field_to_json(field)).collect())).collect()));
+            (filename.clone(), Value::Object(entry))
+        }).collect();
+
+        match fs::write(&self.path, Value::Object(document).to_string()) {
+            Ok(()) => { Ok(()) }
+            Err(err) => { Err(format!("Failed to write results to {}: [{err}]", self.path.display())) }
+        }
+    }
+}
+
+
+/// Serializes a record, e.g. a vector of optional measurements, to the fields of a CSV row and writes it to a sink.
+///
+/// # Arguments
+/// * `sink` - Sink the record is written to.
+/// * `filename` - Name of the file, previously started with `add_file`.
+/// * `record` - Record containing the data to be serialized.
+///
+/// # Returns
+/// The result of the operation or a string containing an error.
+pub fn write_serialized<S: Serialize>(sink: &mut dyn ResultSink, filename: &str, record: S) -> Result<(), String> {
+    let fields: Vec<String> = record_fields(record)?;
+    sink.write_record(filename, &fields)
+}
+
+/// Serializes a record to the fields of a CSV row, so that every sink receives the same fields as the CSV files.
+pub(crate) fn record_fields<S: Serialize>(record: S) -> Result<Vec<String>, String> {
+    let mut writer = WriterBuilder::new().has_headers(false).from_writer(vec![]);
+    if let Err(err) = writer.serialize(record) {
+        return Err(format!("Error in writing record: [{err}]"));
+    }
+    let row: Vec<u8> = match writer.into_inner() {
+        Ok(row) => { row }
+        Err(err) => { return Err(format!("Error in writing record: [{err}]")) }
+    };
+
+    let mut reader = ReaderBuilder::new().has_headers(false).from_reader(row.as_slice());
+    match reader.records().next() {
+        Some(Ok(fields)) => { Ok(fields.iter().map(String::from).collect()) }
+        Some(Err(err)) => { Err(format!("Error in reading back record: [{err}]")) }
+        None => { Ok(vec![]) }
+    }
+}
+
+/// A utility function to check whether the csv directory exists or not, creating it if needed.
+fn check_dir_existence_or_create(csv_dir: &Path) -> Result<(), String> {
+    match create_dir_all(csv_dir) {
+        Ok(_) => { Ok(()) }
+        Err(err) => { Err(format!("Error in creating CSV folder: [{err}]")) }
+    }
+}
+
+/// Converts a CSV field to JSON: empty fields are null, numeric fields are numbers and any other field is a string.
+fn field_to_json(field: &str) -> Value {
+    if field.is_empty() {
+        return Value::Null;
+    }
+    if let Ok(integer) = field.parse::<i64>() {
+        return Value::from(integer);
+    }
+    match field.parse::<f64>().ok().and_then(Number::from_f64) {
+        Some(number) => { Value::Number(number) }
+        None => { Value::String(field.to_string()) }
+    }
+}
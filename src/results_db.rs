@@ -0,0 +1,511 @@
+use std::path::Path;
+#[cfg(feature = "sqlite")]
+use std::time::{SystemTime, UNIX_EPOCH};
+#[cfg(feature = "sqlite")]
+use std::collections::HashMap;
+#[cfg(feature = "sqlite")]
+use rusqlite::{params, Connection};
+use crate::csv_writer::CSVWriter;
+#[cfg(feature = "sqlite")]
+use crate::result_sink::ResultSink;
+
+/// Name of the CSV file holding the samples of a sweep, one per row, next to the other CSV files.
+pub const SAMPLES_FILE: &str = "samples";
+/// Columns of the samples CSV file.
+const SAMPLE_COLUMNS: [&str; 5] = ["metric", "algorithm", "claims", "disclosures", "value"];
+
+/// Single measurement of a benchmark run.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Sample {
+    /// Name of the metric, e.g. `vc_issuance_duration`, as in the names of the CSV files.
+    pub metric: String,
+    /// Name of the algorithm.
+    pub algorithm: String,
+    /// Amount of claims in the credential.
+    pub claims: usize,
+    /// Amount of disclosed claims, for the metrics of presentations.
+    pub disclosures: Option<usize>,
+    /// Measured value, in the unit of the metric.
+    pub value: f64,
+}
+
+/// Samples of a benchmark run, collected next to the CSV files so that they can be stored in the results database.
+///
+/// # Examples
+/// ```
+/// use csd_jwt::results_db::SampleLog;
+///
+/// let mut sample_log = SampleLog::new();
+/// let algorithms = vec!["SD-JWT".to_string(), "BBS+".to_string()];
+/// sample_log.record_row("vp_jwt_length", &algorithms, 10, Some(5), [Some(812.0), None]);
+/// assert_eq!(sample_log.samples().len(), 1);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct SampleLog {
+    /// Samples, in order of measurement.
+    samples: Vec<Sample>,
+}
+
+impl SampleLog {
+
+    /// Constructor for an empty log.
+    pub fn new() -> Self {
+        SampleLog::default()
+    }
+
+    /// Adds a row of samples, one per algorithm, skipping the missing values.
+    ///
+    /// # Arguments
+    /// * `metric` - Name of the metric.
+    /// * `algorithms` - Names of the algorithms, in the order of the values.
+    /// * `claims` - Amount of claims in the credential.
+    /// * `disclosures` - Amount of disclosed claims, for the metrics of presentations.
+    /// * `values` - Measured values, None if the measurement failed.
+    pub fn record_row(&mut self, metric: &str, algorithms: &[String], claims: usize, disclosures: Option<usize>, values: impl IntoIterator<Item = Option<f64>>) {
+        for (algorithm, value) in algorithms.iter().zip(values) {
+            if let Some(value) = value {
+                self.samples.push(Sample { metric: metric.to_string(), algorithm: algorithm.clone(), claims, disclosures, value });
+            }
+        }
+    }
+
+    /// Adds a single sample.
+    ///
+    /// # Arguments
+    /// * `sample` - Sample to be added.
+    pub fn push(&mut self, sample: Sample) {
+        self.samples.push(sample);
+    }
+
+    /// Samples, in order of measurement.
+    pub fn samples(&self) -> &[Sample] {
+        &self.samples
+    }
+
+    /// Writes the samples to a CSV file, one per row, so that the result directory of a run can be compared with
+    /// others without the results database.
+    ///
+    /// # Arguments
+    /// * `filename` - Name of the CSV file.
+    ///
+    /// # Returns
+    /// The result of the operation or a string containing an error.
+    pub fn write_csv(&self, filename: &String) -> Result<(), String> {
+        let mut writer = CSVWriter::new(SAMPLE_COLUMNS.iter().map(|column| column.to_string()).collect())?;
+        writer.add_file(filename)?;
+
+        for sample in &self.samples {
+            writer.write_record_to_file(filename, vec![
+                sample.metric.clone(),
+                sample.algorithm.clone(),
+                sample.claims.to_string(),
+                sample.disclosures.map(|disclosures| disclosures.to_string()).unwrap_or_default(),
+                sample.value.to_string(),
+            ])?;
+        }
+
+        Ok(())
+    }
+
+    /// Reads the samples written by `write_csv`.
+    ///
+    /// # Arguments
+    /// * `path` - Path of the CSV file.
+    ///
+    /// # Returns
+    /// A result containing the samples or a string containing an error.
+    pub fn read_csv(path: &Path) -> Result<Self, String> {
+        let mut reader = match csv::Reader::from_path(path) {
+            Ok(reader) => { reader }
+            Err(err) => { return Err(format!("Failed to open samples file {}: [{err}]", path.display())) }
+        };
+
+        let mut samples: Vec<Sample> = vec![];
+        for (line, record) in reader.records().enumerate() {
+            let record = match record {
+                Ok(record) => { record }
+                Err(err) => { return Err(format!("Failed to read sample {line} of {}: [{err}]", path.display())) }
+            };
+            if record.len() != SAMPLE_COLUMNS.len() {
+                return Err(format!("Sample {line} of {} has {} fields instead of {}", path.display(), record.len(), SAMPLE_COLUMNS.len()));
+            }
+
+            let claims: usize = match record[2].parse::<usize>() {
+                Ok(claims) => { claims }
+                Err(err) => { return Err(format!("Invalid amount of claims in sample {line}: [{err}]")) }
+            };
+            let disclosures: Option<usize> = match &record[3] {
+                "" => { None }
+                disclosures => match disclosures.parse::<usize>() {
+                    Ok(disclosures) => { Some(disclosures) }
+                    Err(err) => { return Err(format!("Invalid amount of disclosures in sample {line}: [{err}]")) }
+                }
+            };
+            let value: f64 = match record[4].parse::<f64>() {
+                Ok(value) => { value }
+                Err(err) => { return Err(format!("Invalid value in sample {line}: [{err}]")) }
+            };
+
+            samples.push(Sample { metric: record[0].to_string(), algorithm: record[1].to_string(), claims, disclosures, value });
+        }
+
+        Ok(SampleLog { samples })
+    }
+}
+
+
+/// Benchmark run stored in the results database.
+#[cfg(feature = "sqlite")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Run {
+    /// Identifier of the run, increasing with the order of storage.
+    pub id: i64,
+    /// Label of the run, e.g. the commit that was benchmarked.
+    pub label: String,
+    /// Storage time, in seconds since the Unix epoch.
+    pub stored_at: i64,
+    /// Amount of samples of the run.
+    pub samples: usize,
+}
+
+/// Value of a metric of an algorithm in a run, as compared across runs.
+#[cfg(feature = "sqlite")]
+#[derive(Clone, Debug, PartialEq)]
+pub struct RunValue {
+    /// Identifier of the run.
+    pub run_id: i64,
+    /// Label of the run.
+    pub label: String,
+    /// Name of the algorithm.
+    pub algorithm: String,
+    /// Average of the samples of the run matching the comparison.
+    pub value: f64,
+}
+
+/// SQLite database storing the samples of every benchmark run, with their run, algorithm, amount of claims and
+/// disclosures, and metric, so that experiments can be compared across runs without collecting CSV files.
+///
+/// # Examples
+/// ```
+/// use csd_jwt::results_db::{ResultsDatabase, SampleLog};
+///
+/// let mut sample_log = SampleLog::new();
+/// sample_log.record_row("vc_issuance_duration", &["SD-JWT".to_string()], 10, None, [Some(250.0)]);
+///
+/// // Runs are usually stored in a file, e.g. results.sqlite, kept across benchmark runs.
+/// let mut database = ResultsDatabase::open(":memory:").unwrap();
+/// let first_run = database.store_run("4557bb2", &sample_log).unwrap();
+/// database.store_run("8c1d2e0", &SampleLog::new()).unwrap();
+///
+/// let values = database.compare("vc_issuance_duration", 10, None).unwrap();
+/// assert_eq!(values.len(), 1);
+/// assert_eq!((values[0].label.as_str(), values[0].algorithm.as_str(), values[0].value), ("4557bb2", "SD-JWT", 250.0));
+/// assert_eq!(database.runs().unwrap().len(), 2);
+/// assert_eq!(database.load_run(first_run).unwrap().samples(), sample_log.samples());
+/// ```
+#[cfg(feature = "sqlite")]
+pub struct ResultsDatabase {
+    /// Connection to the database file.
+    connection: Connection,
+}
+
+#[cfg(feature = "sqlite")]
+impl ResultsDatabase {
+
+    /// Opens a database file, creating it and its tables if needed.
+    ///
+    /// # Arguments
+    /// * `path` - Path of the database file.
+    ///
+    /// # Returns
+    /// A result containing the database or a string containing an error.
+    pub fn open(path: &str) -> Result<Self, String> {
+        let connection = match Connection::open(path) {
+            Ok(connection) => { connection }
+            Err(err) => { return Err(format!("Failed to open results database {path}: [{err}]")) }
+        };
+
+        let schema = "
+            CREATE TABLE IF NOT EXISTS runs (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                label TEXT NOT NULL,
+                stored_at INTEGER NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS samples (
+                run_id INTEGER NOT NULL REFERENCES runs (id),
+                algorithm TEXT NOT NULL,
+                claims INTEGER NOT NULL,
+                disclosures INTEGER,
+                metric TEXT NOT NULL,
+                value REAL NOT NULL
+            );
+            CREATE INDEX IF NOT EXISTS samples_by_metric ON samples (metric, claims, disclosures, algorithm);
+            CREATE TABLE IF NOT EXISTS records (
+                run_id INTEGER NOT NULL REFERENCES runs (id),
+                file TEXT NOT NULL,
+                position INTEGER NOT NULL,
+                field TEXT NOT NULL,
+                value TEXT NOT NULL
+            );
+        ";
+        match connection.execute_batch(schema) {
+            Ok(()) => { Ok(ResultsDatabase { connection }) }
+            Err(err) => { Err(format!("Failed to create the tables of the results database: [{err}]")) }
+        }
+    }
+
+    /// Stores the samples of a run in a single transaction.
+    ///
+    /// # Arguments
+    /// * `label` - Label of the run, e.g. the commit that was benchmarked.
+    /// * `sample_log` - Samples of the run.
+    ///
+    /// # Returns
+    /// A result containing the identifier of the run or a string containing an error.
+    pub fn store_run(&mut self, label: &str, sample_log: &SampleLog) -> Result<i64, String> {
+        let transaction = match self.connection.transaction() {
+            Ok(transaction) => { transaction }
+            Err(err) => { return Err(format!("Failed to start transaction: [{err}]")) }
+        };
+
+        let run_id: i64 = insert_run(&transaction, label)?;
+
+        {
+            let mut statement = match transaction.prepare("INSERT INTO samples (run_id, algorithm, claims, disclosures, metric, value) VALUES (?1, ?2, ?3, ?4, ?5, ?6)") {
+                Ok(statement) => { statement }
+                Err(err) => { return Err(format!("Failed to prepare sample insertion: [{err}]")) }
+            };
+            for sample in sample_log.samples() {
+                let disclosures: Option<i64> = sample.disclosures.map(|disclosures| disclosures as i64);
+                if let Err(err) = statement.execute(params![run_id, sample.algorithm, sample.claims as i64, disclosures, sample.metric, sample.value]) {
+                    return Err(format!("Failed to store sample of {}: [{err}]", sample.metric));
+                }
+            }
+        }
+
+        match transaction.commit() {
+            Ok(()) => { Ok(run_id) }
+            Err(err) => { Err(format!("Failed to commit run {label}: [{err}]")) }
+        }
+    }
+
+    /// Lists the stored runs.
+    ///
+    /// # Returns
+    /// A result containing the runs, in order of storage, or a string containing an error.
+    pub fn runs(&self) -> Result<Vec<Run>, String> {
+        let query = "
+            SELECT runs.id, runs.label, runs.stored_at, COUNT(samples.run_id)
+            FROM runs LEFT JOIN samples ON samples.run_id = runs.id
+            GROUP BY runs.id ORDER BY runs.id
+        ";
+        let mut statement = match self.connection.prepare(query) {
+            Ok(statement) => { statement }
+            Err(err) => { return Err(format!("Failed to prepare run listing: [{err}]")) }
+        };
+        let runs = statement.query_map([], |row| {
+            Ok(Run { id: row.get(0)?, label: row.get(1)?, stored_at: row.get(2)?, samples: row.get::<_, i64>(3)? as usize })
+        });
+
+        match runs.and_then(|runs| runs.collect::<Result<Vec<Run>, rusqlite::Error>>()) {
+            Ok(runs) => { Ok(runs) }
+            Err(err) => { Err(format!("Failed to list runs: [{err}]")) }
+        }
+    }
+
+    /// Compares a metric across runs, for a given amount of claims and, for the metrics of presentations, of disclosures.
+    ///
+    /// # Arguments
+    /// * `metric` - Name of the metric.
+    /// * `claims` - Amount of claims in the credential.
+    /// * `disclosures` - Amount of disclosed claims, or None for the metrics of credentials.
+    ///
+    /// # Returns
+    /// A result containing the average value of every algorithm in every run, in order of run and algorithm, or a
+    /// string containing an error.
+    pub fn compare(&self, metric: &str, claims: usize, disclosures: Option<usize>) -> Result<Vec<RunValue>, String> {
+        let query = "
+            SELECT runs.id, runs.label, samples.algorithm, AVG(samples.value)
+            FROM samples JOIN runs ON samples.run_id = runs.id
+            WHERE samples.metric = ?1 AND samples.claims = ?2 AND samples.disclosures IS ?3
+            GROUP BY runs.id, samples.algorithm ORDER BY runs.id, samples.algorithm
+        ";
+        let disclosures: Option<i64> = disclosures.map(|disclosures| disclosures as i64);
+        let mut statement = match self.connection.prepare(query) {
+            Ok(statement) => { statement }
+            Err(err) => { return Err(format!("Failed to prepare comparison: [{err}]")) }
+        };
+        let values = statement.query_map(params![metric, claims as i64, disclosures], |row| {
+            Ok(RunValue { run_id: row.get(0)?, label: row.get(1)?, algorithm: row.get(2)?, value: row.get(3)? })
+        });
+
+        match values.and_then(|values| values.collect::<Result<Vec<RunValue>, rusqlite::Error>>()) {
+            Ok(values) => { Ok(values) }
+            Err(err) => { Err(format!("Failed to compare {metric} across runs: [{err}]")) }
+        }
+    }
+
+    /// Loads every sample of a run, e.g. to compare it with another run.
+    ///
+    /// # Arguments
+    /// * `run_id` - Identifier of the run.
+    ///
+    /// # Returns
+    /// A result containing the samples or a string containing an error, also if the run does not exist.
+    pub fn load_run(&self, run_id: i64) -> Result<SampleLog, String> {
+        if !self.runs()?.iter().any(|run| run.id == run_id) {
+            return Err(format!("Run {run_id} is not in the results database"));
+        }
+
+        let query = "SELECT metric, algorithm, claims, disclosures, value FROM samples WHERE run_id = ?1 ORDER BY rowid";
+        let mut statement = match self.connection.prepare(query) {
+            Ok(statement) => { statement }
+            Err(err) => { return Err(format!("Failed to prepare run loading: [{err}]")) }
+        };
+        let samples = statement.query_map(params![run_id], |row| {
+            Ok(Sample {
+                metric: row.get(0)?,
+                algorithm: row.get(1)?,
+                claims: row.get::<_, i64>(2)? as usize,
+                disclosures: row.get::<_, Option<i64>>(3)?.map(|disclosures| disclosures as usize),
+                value: row.get(4)?,
+            })
+        });
+
+        match samples.and_then(|samples| samples.collect::<Result<Vec<Sample>, rusqlite::Error>>()) {
+            Ok(samples) => { Ok(SampleLog { samples }) }
+            Err(err) => { Err(format!("Failed to load run {run_id}: [{err}]")) }
+        }
+    }
+
+    /// Retrieves the samples of a metric of an algorithm in a run, e.g. to plot it against the amount of claims.
+    ///
+    /// # Arguments
+    /// * `run_id` - Identifier of the run.
+    /// * `metric` - Name of the metric.
+    /// * `algorithm` - Name of the algorithm.
+    ///
+    /// # Returns
+    /// A result containing the samples, in order of claims and disclosures, or a string containing an error.
+    pub fn series(&self, run_id: i64, metric: &str, algorithm: &str) -> Result<Vec<Sample>, String> {
+        let query = "
+            SELECT claims, disclosures, value FROM samples
+            WHERE run_id = ?1 AND metric = ?2 AND algorithm = ?3
+            ORDER BY claims, disclosures
+        ";
+        let mut statement = match self.connection.prepare(query) {
+            Ok(statement) => { statement }
+            Err(err) => { return Err(format!("Failed to prepare series retrieval: [{err}]")) }
+        };
+        let samples = statement.query_map(params![run_id, metric, algorithm], |row| {
+            Ok(Sample {
+                metric: metric.to_string(),
+                algorithm: algorithm.to_string(),
+                claims: row.get::<_, i64>(0)? as usize,
+                disclosures: row.get::<_, Option<i64>>(1)?.map(|disclosures| disclosures as usize),
+                value: row.get(2)?,
+            })
+        });
+
+        match samples.and_then(|samples| samples.collect::<Result<Vec<Sample>, rusqlite::Error>>()) {
+            Ok(samples) => { Ok(samples) }
+            Err(err) => { Err(format!("Failed to retrieve {metric} of {algorithm} in run {run_id}: [{err}]")) }
+        }
+    }
+}
+
+
+/// Stores a new run, returning its identifier.
+#[cfg(feature = "sqlite")]
+fn insert_run(connection: &Connection, label: &str) -> Result<i64, String> {
+    let stored_at: i64 = SystemTime::now().duration_since(UNIX_EPOCH).map(|elapsed| elapsed.as_secs() as i64).unwrap_or(0);
+    match connection.execute("INSERT INTO runs (label, stored_at) VALUES (?1, ?2)", params![label, stored_at]) {
+        Ok(_) => { Ok(connection.last_insert_rowid()) }
+        Err(err) => { Err(format!("Failed to store run {label}: [{err}]")) }
+    }
+}
+
+
+/// Sink storing the records of a run in the results database as they are written, one row per field in the `records`
+/// table, so that the raw records are kept next to the samples of the runs.
+///
+/// # Examples
+/// ```
+/// use csd_jwt::result_sink::ResultSink;
+/// use csd_jwt::results_db::SqliteSink;
+///
+/// let mut sink = SqliteSink::open(":memory:", "nightly").unwrap();
+/// assert_eq!(sink.run_id(), 1);
+/// sink.add_file("vc_jwt_length", &["SD-JWT".to_string()]).unwrap();
+/// sink.write_record("vc_jwt_length", &["1024".to_string()]).unwrap();
+///
+/// // Records are only accepted for the files that were started.
+/// assert!(sink.write_record("vp_jwt_length", &["512".to_string()]).is_err());
+/// ```
+#[cfg(feature = "sqlite")]
+pub struct SqliteSink {
+    /// Database the records are stored in.
+    database: ResultsDatabase,
+    /// Identifier of the run the records belong to.
+    run_id: i64,
+    /// Columns of every started file.
+    columns: HashMap<String, Vec<String>>,
+    /// Amount of records written to every started file.
+    positions: HashMap<String, i64>,
+}
+
+#[cfg(feature = "sqlite")]
+impl SqliteSink {
+
+    /// Opens a database file and stores a new run the records will belong to.
+    ///
+    /// # Arguments
+    /// * `path` - Path of the database file.
+    /// * `label` - Label of the run.
+    ///
+    /// # Returns
+    /// A result containing the sink or a string containing an error.
+    pub fn open(path: &str, label: &str) -> Result<Self, String> {
+        let database: ResultsDatabase = ResultsDatabase::open(path)?;
+        let run_id: i64 = insert_run(&database.connection, label)?;
+        Ok(SqliteSink { database, run_id, columns: HashMap::new(), positions: HashMap::new() })
+    }
+
+    /// Identifier of the run the records belong to.
+    pub fn run_id(&self) -> i64 {
+        self.run_id
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl ResultSink for SqliteSink {
+
+    fn add_file(&mut self, filename: &str, columns: &[String]) -> Result<(), String> {
+        self.columns.insert(filename.to_string(), columns.to_vec());
+        self.positions.insert(filename.to_string(), 0);
+        Ok(())
+    }
+
+    fn write_record(&mut self, filename: &str, fields: &[String]) -> Result<(), String> {
+        let (columns, position) = match (self.columns.get(filename), self.positions.get_mut(filename)) {
+            (Some(columns), Some(position)) => { (columns, position) }
+            _ => { return Err(format!("Filename {filename} was not found in map")) }
+        };
+
+        let transaction = match self.database.connection.transaction() {
+            Ok(transaction) => { transaction }
+            Err(err) => { return Err(format!("Failed to start transaction: [{err}]")) }
+        };
+        for (column, value) in columns.iter().zip(fields) {
+            if let Err(err) = transaction.execute("INSERT INTO records (run_id, file, position, field, value) VALUES (?1, ?2, ?3, ?4, ?5)", params![self.run_id, filename, *position, column, value]) {
+                return Err(format!("Failed to store record of {filename}: [{err}]"));
+            }
+        }
+        if let Err(err) = transaction.commit() {
+            return Err(format!("Failed to commit record of {filename}: [{err}]"));
+        }
+
+        *position += 1;
+        Ok(())
+    }
+}
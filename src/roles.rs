@@ -0,0 +1,405 @@
+use std::collections::BTreeMap;
+use ark_bn254::{Bn254, Fr, G1Affine, G2Affine};
+use ark_std::rand::rngs::StdRng as ArkStdRng;
+use rand::Rng;
+use serde_json::{Map, Value};
+use vb_accumulator::setup::{Keypair, MembershipProvingKey, PublicKey, SecretKey, SetupParams};
+use zkryptium::bbsplus::ciphersuites::{BbsCiphersuite, Bls12381Sha256};
+use zkryptium::bbsplus::keys::{BBSplusPublicKey, BBSplusSecretKey};
+use zkryptium::keys::pair::KeyPair;
+use zkryptium::schemes::algorithms::BBSplus;
+use crate::common_data::CommonData;
+use crate::deterministic;
+use crate::deterministic::ark_rng;
+use crate::ephemeral_binding::unverified_claims;
+use crate::presentation_validity::PresentationValidity;
+use crate::sd_algorithms::accumulators::csd_jwt::{CsdJwtInstance, AGGREGATED_ALGORITHM, MULTI_SHOW_ALGORITHM};
+use crate::sd_algorithms::hashes::merkle_trees::MerkleTreeInstance;
+use crate::sd_algorithms::hashes::sd_jwt::SdJwtInstance;
+use crate::sd_algorithms::sd_algorithm::SdAlgorithm;
+use crate::sd_algorithms::signatures::bbs_plus::BBSPlusInstance;
+use crate::verifier_config::VerifierConfig;
+
+/// Operations of an algorithm split by the key material they need, so that the issuer, holder and verifier facades
+/// only ever hold what their role legitimately has: the issuer keeps its secret, while the public parameters it
+/// publishes are all that holders and verifiers receive.
+pub trait RoleAlgorithm {
+
+    /// Name of the algorithm.
+    const NAME: &'static str;
+
+    /// Whether presentations can be bound to an audience.
+    const AUDIENCE_BINDING: bool = true;
+
+    /// Secret material of the issuer.
+    type IssuerSecret;
+
+    /// Public material published by the issuer, needed by holders and verifiers.
+    type IssuerPublic: Clone;
+
+    /// Generates the issuer key material.
+    ///
+    /// # Arguments
+    /// * `claims_len` - Maximum amount of claims of the credentials, needed by some algorithms to size their keys.
+    ///
+    /// # Returns
+    /// A result containing the secret and public material of the issuer or a string containing an error.
+    fn generate_issuer_keys(claims_len: usize) -> Result<(Self::IssuerSecret, Self::IssuerPublic), String>;
+
+    /// Issues a VC from a raw VC already bound to the key of its holder.
+    fn issue_vc(raw_vc: &Map<String, Value>, issuer_secret: &Self::IssuerSecret, issuer_public: &Self::IssuerPublic) -> Result<(Map<String, Value>, String), String>;
+
+    /// Verifies a VC against the public material of its issuer.
+    fn verify_vc(vc: &Map<String, Value>, issuer_public: &Self::IssuerPublic) -> Result<(), String>;
+
+    /// Derives a VP from a VC with the private key of the holder, optionally bound to an audience and valid within a window.
+    fn issue_vp(vc: &Map<String, Value>, disclosures: &[String], audience: Option<String>, validity: Option<PresentationValidity>, issuer_public: &Self::IssuerPublic, holder_private_key: &[u8]) -> Result<(Map<String, Value>, String), String>;
+
+    /// Verifies a VP against the public material of the issuer and the public key of the holder presenting it, enforcing
+    /// the limits of the verifier.
+    fn verify_vp(vp_jwt: &str, issuer_public: &Self::IssuerPublic, holder_public_key: &[u8], config: &VerifierConfig) -> Result<(), String>;
+
+    /// Names of the claims disclosed by a VP, as proven by its proofs once verified with `verify_vp`.
+    fn disclosed_claims(vp_jwt: &str) -> Result<Vec<String>, String>;
+}
+
+/// Reads the names of the claims disclosed by a VP of an algorithm.
+fn disclosed_claims_of<S: SdAlgorithm>(vp_jwt: &str) -> Result<Vec<String>, String> {
+    let envelope: Map<String, Value> = unverified_claims(vp_jwt)?;
+    S::disclosed_claim_names(S::unwrap_presentation(&envelope)?)
+}
+
+/// Rejects audience-bound presentations for algorithms that do not support them.
+fn unbound_presentation(algorithm: &str, audience: Option<String>) -> Result<(), String> {
+    match audience {
+        None => { Ok(()) }
+        Some(_) => { Err(format!("{algorithm} does not support audience-bound presentations.")) }
+    }
+}
+
+
+impl RoleAlgorithm for SdJwtInstance {
+
+    const NAME: &'static str = SdJwtInstance::ALGORITHM;
+    type IssuerSecret = Vec<u8>;
+    type IssuerPublic = Vec<u8>;
+
+    fn generate_issuer_keys(_claims_len: usize) -> Result<(Self::IssuerSecret, Self::IssuerPublic), String> {
+        let (issuer_public_key, issuer_private_key) = CommonData::issuer_keys()?;
+        Ok((issuer_private_key, issuer_public_key))
+    }
+
+    fn issue_vc(raw_vc: &Map<String, Value>, issuer_secret: &Self::IssuerSecret, _issuer_public: &Self::IssuerPublic) -> Result<(Map<String, Value>, String), String> {
+        SdJwtInstance::issue_vc(raw_vc, issuer_secret)
+    }
+
+    fn verify_vc(vc: &Map<String, Value>, issuer_public: &Self::IssuerPublic) -> Result<(), String> {
+        SdJwtInstance::verify_vc(vc, issuer_public)
+    }
+
+    fn issue_vp(vc: &Map<String, Value>, disclosures: &[String], audience: Option<String>, validity: Option<PresentationValidity>, _issuer_public: &Self::IssuerPublic, holder_private_key: &[u8]) -> Result<(Map<String, Value>, String), String> {
+        SdJwtInstance::issue_vp_for_audience(vc, disclosures, audience, validity, holder_private_key)
+    }
+
+    fn verify_vp(vp_jwt: &str, issuer_public: &Self::IssuerPublic, holder_public_key: &[u8], config: &VerifierConfig) -> Result<(), String> {
+        SdJwtInstance::verify_vp_with_config(vp_jwt, issuer_public, holder_public_key, config)
+    }
+
+    fn disclosed_claims(vp_jwt: &str) -> Result<Vec<String>, String> {
+        disclosed_claims_of::<SdJwtInstance>(vp_jwt)
+    }
+}
+
+
+impl RoleAlgorithm for MerkleTreeInstance {
+
+    const NAME: &'static str = MerkleTreeInstance::ALGORITHM;
+    type IssuerSecret = Vec<u8>;
+    type IssuerPublic = Vec<u8>;
+
+    fn generate_issuer_keys(_claims_len: usize) -> Result<(Self::IssuerSecret, Self::IssuerPublic), String> {
+        let (issuer_public_key, issuer_private_key) = CommonData::issuer_keys()?;
+        Ok((issuer_private_key, issuer_public_key))
+    }
+
+    fn issue_vc(raw_vc: &Map<String, Value>, issuer_secret: &Self::IssuerSecret, _issuer_public: &Self::IssuerPublic) -> Result<(Map<String, Value>, String), String> {
+        MerkleTreeInstance::issue_vc(raw_vc, issuer_secret)
+    }
+
+    fn verify_vc(vc: &Map<String, Value>, issuer_public: &Self::IssuerPublic) -> Result<(), String> {
+        MerkleTreeInstance::verify_vc(vc, issuer_public)
+    }
+
+    fn issue_vp(vc: &Map<String, Value>, disclosures: &[String], audience: Option<String>, validity: Option<PresentationValidity>, _issuer_public: &Self::IssuerPublic, holder_private_key: &[u8]) -> Result<(Map<String, Value>, String), String> {
+        MerkleTreeInstance::issue_vp_for_audience(vc, disclosures, audience, validity, holder_private_key)
+    }
+
+    fn verify_vp(vp_jwt: &str, issuer_public: &Self::IssuerPublic, holder_public_key: &[u8], config: &VerifierConfig) -> Result<(), String> {
+        MerkleTreeInstance::verify_vp_with_cache(vp_jwt, issuer_public, holder_public_key, None, config)
+    }
+
+    fn disclosed_claims(vp_jwt: &str) -> Result<Vec<String>, String> {
+        disclosed_claims_of::<MerkleTreeInstance>(vp_jwt)
+    }
+}
+
+
+/// Holder-side check of the accumulator witnesses of a received VC, for the CSD-JWT algorithms.
+pub trait AccumulatorWitnesses: RoleAlgorithm {
+
+    /// Checks the witness of every claim of a VC against its accumulator.
+    ///
+    /// # Arguments
+    /// * `vc` - Received VC.
+    /// * `issuer_public` - Public material of the issuer.
+    ///
+    /// # Returns
+    /// A result containing the validity of the witness of every claim or a string containing an error if the VC
+    /// cannot be decoded.
+    fn check_witnesses(vc: &Map<String, Value>, issuer_public: &Self::IssuerPublic) -> Result<BTreeMap<String, bool>, String>;
+}
+
+impl AccumulatorWitnesses for CsdJwtInstance {
+    fn check_witnesses(vc: &Map<String, Value>, issuer_public: &Self::IssuerPublic) -> Result<BTreeMap<String, bool>, String> {
+        CsdJwtInstance::check_witnesses(vc, &issuer_public.public_key, &issuer_public.params)
+    }
+}
+
+impl AccumulatorWitnesses for CsdJwtMultiShow {
+    fn check_witnesses(vc: &Map<String, Value>, issuer_public: &Self::IssuerPublic) -> Result<BTreeMap<String, bool>, String> {
+        CsdJwtInstance::check_witnesses(vc, &issuer_public.public_key, &issuer_public.params)
+    }
+}
+
+impl AccumulatorWitnesses for CsdJwtAggregated {
+    fn check_witnesses(vc: &Map<String, Value>, issuer_public: &Self::IssuerPublic) -> Result<BTreeMap<String, bool>, String> {
+        CsdJwtInstance::check_witnesses(vc, &issuer_public.public_key, &issuer_public.params)
+    }
+}
+
+/// Issuance over pre-hashed claims for the hash-based algorithms: the holder commits to salted digests of its claims
+/// and the issuer signs the digests only, never seeing the plaintext of the claims.
+pub trait PreHashedIssuance: RoleAlgorithm {
+
+    /// Salts and hashes the claims of a raw VC, returning the digests for the issuer and the opening kept by the holder.
+    fn commit_claims(raw_vc: &Map<String, Value>) -> Result<(Vec<String>, Map<String, Value>), String>;
+
+    /// Checks that the committed digests are well formed and signs them, together with the signed fields of the credential.
+    fn sign_digests(digests: &[String], signed_fields: &Map<String, Value>, issuer_secret: &Self::IssuerSecret) -> Result<Vec<u8>, String>;
+
+    /// Assembles the VC from the raw VC, the opening of its digests and the signature of the issuer.
+    fn assemble_vc(raw_vc: &Map<String, Value>, opening: &Map<String, Value>, signature: &[u8]) -> Result<(Map<String, Value>, String), String>;
+}
+
+impl PreHashedIssuance for SdJwtInstance {
+
+    fn commit_claims(raw_vc: &Map<String, Value>) -> Result<(Vec<String>, Map<String, Value>), String> {
+        SdJwtInstance::commit_claims(raw_vc)
+    }
+
+    fn sign_digests(digests: &[String], signed_fields: &Map<String, Value>, issuer_secret: &Self::IssuerSecret) -> Result<Vec<u8>, String> {
+        SdJwtInstance::sign_digests(digests, signed_fields, issuer_secret)
+    }
+
+    fn assemble_vc(raw_vc: &Map<String, Value>, opening: &Map<String, Value>, signature: &[u8]) -> Result<(Map<String, Value>, String), String> {
+        SdJwtInstance::assemble_vc(raw_vc, opening, signature)
+    }
+}
+
+impl PreHashedIssuance for MerkleTreeInstance {
+
+    fn commit_claims(raw_vc: &Map<String, Value>) -> Result<(Vec<String>, Map<String, Value>), String> {
+        MerkleTreeInstance::commit_claims(raw_vc)
+    }
+
+    fn sign_digests(digests: &[String], signed_fields: &Map<String, Value>, issuer_secret: &Self::IssuerSecret) -> Result<Vec<u8>, String> {
+        MerkleTreeInstance::sign_digests(digests, signed_fields, issuer_secret)
+    }
+
+    fn assemble_vc(raw_vc: &Map<String, Value>, opening: &Map<String, Value>, signature: &[u8]) -> Result<(Map<String, Value>, String), String> {
+        MerkleTreeInstance::assemble_vc(raw_vc, opening, signature)
+    }
+}
+
+
+/// Public material of a CSD-JWT issuer: the public key and the setup parameters of the accumulator.
+#[derive(Clone)]
+pub struct CsdJwtPublicParameters {
+    pub public_key: PublicKey<Bn254>,
+    pub params: SetupParams<Bn254>,
+}
+
+/// Generates the accumulator keypair and setup parameters of a CSD-JWT issuer.
+fn csd_jwt_issuer_keys(rng: &mut ArkStdRng) -> (SecretKey<Fr>, CsdJwtPublicParameters) {
+    let (params, Keypair { ref secret_key, ref public_key }) = CsdJwtInstance::initialize_params(rng);
+    (secret_key.clone(), CsdJwtPublicParameters { public_key: public_key.clone(), params })
+}
+
+impl RoleAlgorithm for CsdJwtInstance {
+
+    const NAME: &'static str = CsdJwtInstance::ALGORITHM;
+    type IssuerSecret = SecretKey<Fr>;
+    type IssuerPublic = CsdJwtPublicParameters;
+
+    fn generate_issuer_keys(_claims_len: usize) -> Result<(Self::IssuerSecret, Self::IssuerPublic), String> {
+        let mut rng = ark_rng();
+        Ok(csd_jwt_issuer_keys(&mut rng))
+    }
+
+    fn issue_vc(raw_vc: &Map<String, Value>, issuer_secret: &Self::IssuerSecret, issuer_public: &Self::IssuerPublic) -> Result<(Map<String, Value>, String), String> {
+        CsdJwtInstance::issue_vc(raw_vc, issuer_secret, &issuer_public.params)
+    }
+
+    fn verify_vc(vc: &Map<String, Value>, issuer_public: &Self::IssuerPublic) -> Result<(), String> {
+        CsdJwtInstance::verify_vc(vc, &issuer_public.public_key, &issuer_public.params)
+    }
+
+    fn issue_vp(vc: &Map<String, Value>, disclosures: &[String], audience: Option<String>, validity: Option<PresentationValidity>, _issuer_public: &Self::IssuerPublic, holder_private_key: &[u8]) -> Result<(Map<String, Value>, String), String> {
+        CsdJwtInstance::issue_vp_for_audience(vc, disclosures, audience, validity, holder_private_key)
+    }
+
+    fn verify_vp(vp_jwt: &str, issuer_public: &Self::IssuerPublic, holder_public_key: &[u8], config: &VerifierConfig) -> Result<(), String> {
+        CsdJwtInstance::verify_vp_with_cache(vp_jwt, &issuer_public.public_key, holder_public_key, &issuer_public.params, None, None, config)
+    }
+
+    fn disclosed_claims(vp_jwt: &str) -> Result<Vec<String>, String> {
+        disclosed_claims_of::<CsdJwtInstance>(vp_jwt)
+    }
+}
+
+
+/// CSD-JWT where every presentation carries fresh zero-knowledge membership proofs instead of the witnesses.
+pub struct CsdJwtMultiShow;
+
+/// Public material of a multi-show CSD-JWT issuer, which includes the key used to prove and verify membership.
+#[derive(Clone)]
+pub struct CsdJwtMultiShowPublicParameters {
+    pub public_key: PublicKey<Bn254>,
+    pub params: SetupParams<Bn254>,
+    pub proving_key: MembershipProvingKey<G1Affine>,
+}
+
+impl RoleAlgorithm for CsdJwtMultiShow {
+
+    const NAME: &'static str = MULTI_SHOW_ALGORITHM;
+    const AUDIENCE_BINDING: bool = false;
+    type IssuerSecret = SecretKey<Fr>;
+    type IssuerPublic = CsdJwtMultiShowPublicParameters;
+
+    fn generate_issuer_keys(_claims_len: usize) -> Result<(Self::IssuerSecret, Self::IssuerPublic), String> {
+        let mut rng = ark_rng();
+        let (issuer_secret, CsdJwtPublicParameters { public_key, params }) = csd_jwt_issuer_keys(&mut rng);
+        let proving_key = CsdJwtInstance::initialize_proving_key(&mut rng);
+        Ok((issuer_secret, CsdJwtMultiShowPublicParameters { public_key, params, proving_key }))
+    }
+
+    fn issue_vc(raw_vc: &Map<String, Value>, issuer_secret: &Self::IssuerSecret, issuer_public: &Self::IssuerPublic) -> Result<(Map<String, Value>, String), String> {
+        CsdJwtInstance::issue_vc(raw_vc, issuer_secret, &issuer_public.params)
+    }
+
+    fn verify_vc(vc: &Map<String, Value>, issuer_public: &Self::IssuerPublic) -> Result<(), String> {
+        CsdJwtInstance::verify_vc(vc, &issuer_public.public_key, &issuer_public.params)
+    }
+
+    fn issue_vp(vc: &Map<String, Value>, disclosures: &[String], audience: Option<String>, validity: Option<PresentationValidity>, issuer_public: &Self::IssuerPublic, holder_private_key: &[u8]) -> Result<(Map<String, Value>, String), String> {
+        unbound_presentation(Self::NAME, audience)?;
+        CsdJwtInstance::issue_multi_show_vp(vc, disclosures, validity, holder_private_key, &issuer_public.public_key, &issuer_public.params, &issuer_public.proving_key)
+    }
+
+    fn verify_vp(vp_jwt: &str, issuer_public: &Self::IssuerPublic, holder_public_key: &[u8], config: &VerifierConfig) -> Result<(), String> {
+        CsdJwtInstance::verify_multi_show_vp(vp_jwt, &issuer_public.public_key, holder_public_key, &issuer_public.params, &issuer_public.proving_key, config)
+    }
+
+    fn disclosed_claims(vp_jwt: &str) -> Result<Vec<String>, String> {
+        disclosed_claims_of::<CsdJwtInstance>(vp_jwt)
+    }
+}
+
+
+/// CSD-JWT where every presentation carries a single aggregated witness for all the disclosed claims.
+pub struct CsdJwtAggregated;
+
+/// Public material of an aggregated CSD-JWT issuer, which includes the key used to verify aggregated witnesses.
+#[derive(Clone)]
+pub struct CsdJwtAggregatedPublicParameters {
+    pub public_key: PublicKey<Bn254>,
+    pub params: SetupParams<Bn254>,
+    pub aggregation_key: Vec<G2Affine>,
+}
+
+impl RoleAlgorithm for CsdJwtAggregated {
+
+    const NAME: &'static str = AGGREGATED_ALGORITHM;
+    const AUDIENCE_BINDING: bool = false;
+    type IssuerSecret = SecretKey<Fr>;
+    type IssuerPublic = CsdJwtAggregatedPublicParameters;
+
+    fn generate_issuer_keys(claims_len: usize) -> Result<(Self::IssuerSecret, Self::IssuerPublic), String> {
+        let mut rng = ark_rng();
+        let (issuer_secret, CsdJwtPublicParameters { public_key, params }) = csd_jwt_issuer_keys(&mut rng);
+        let aggregation_key = CsdJwtInstance::initialize_aggregation_key(&issuer_secret, &params, claims_len);
+        Ok((issuer_secret, CsdJwtAggregatedPublicParameters { public_key, params, aggregation_key }))
+    }
+
+    fn issue_vc(raw_vc: &Map<String, Value>, issuer_secret: &Self::IssuerSecret, issuer_public: &Self::IssuerPublic) -> Result<(Map<String, Value>, String), String> {
+        CsdJwtInstance::check_capacity(raw_vc, CsdJwtInstance::aggregation_capacity(&issuer_public.aggregation_key))?;
+        CsdJwtInstance::issue_vc(raw_vc, issuer_secret, &issuer_public.params)
+    }
+
+    fn verify_vc(vc: &Map<String, Value>, issuer_public: &Self::IssuerPublic) -> Result<(), String> {
+        CsdJwtInstance::verify_vc(vc, &issuer_public.public_key, &issuer_public.params)
+    }
+
+    fn issue_vp(vc: &Map<String, Value>, disclosures: &[String], audience: Option<String>, validity: Option<PresentationValidity>, _issuer_public: &Self::IssuerPublic, holder_private_key: &[u8]) -> Result<(Map<String, Value>, String), String> {
+        unbound_presentation(Self::NAME, audience)?;
+        CsdJwtInstance::issue_aggregated_vp(vc, disclosures, validity, holder_private_key)
+    }
+
+    fn verify_vp(vp_jwt: &str, issuer_public: &Self::IssuerPublic, holder_public_key: &[u8], config: &VerifierConfig) -> Result<(), String> {
+        CsdJwtInstance::verify_aggregated_vp(vp_jwt, &issuer_public.public_key, holder_public_key, &issuer_public.params, &issuer_public.aggregation_key, config)
+    }
+
+    fn disclosed_claims(vp_jwt: &str) -> Result<Vec<String>, String> {
+        disclosed_claims_of::<CsdJwtInstance>(vp_jwt)
+    }
+}
+
+
+impl RoleAlgorithm for BBSPlusInstance {
+
+    const NAME: &'static str = BBSPlusInstance::ALGORITHM;
+    type IssuerSecret = BBSplusSecretKey;
+    type IssuerPublic = BBSplusPublicKey;
+
+    fn generate_issuer_keys(_claims_len: usize) -> Result<(Self::IssuerSecret, Self::IssuerPublic), String> {
+        let mut rng = deterministic::rng();
+        let key_material: Vec<u8> = (0..Bls12381Sha256::IKM_LEN).map(|_| rng.random()).collect();
+
+        let issuer_keypair = match KeyPair::<BBSplus<Bls12381Sha256>>::generate(&key_material, None, None) {
+            Ok(keypair) => { keypair }
+            Err(err) => { return Err(format!("Error in issuing BBS+ keypair [{err}]")) }
+        };
+
+        Ok((issuer_keypair.private_key().clone(), issuer_keypair.public_key().clone()))
+    }
+
+    fn issue_vc(raw_vc: &Map<String, Value>, issuer_secret: &Self::IssuerSecret, issuer_public: &Self::IssuerPublic) -> Result<(Map<String, Value>, String), String> {
+        BBSPlusInstance::issue_vc(raw_vc, issuer_public, issuer_secret)
+    }
+
+    fn verify_vc(vc: &Map<String, Value>, issuer_public: &Self::IssuerPublic) -> Result<(), String> {
+        BBSPlusInstance::verify_vc(vc, issuer_public)
+    }
+
+    fn issue_vp(vc: &Map<String, Value>, disclosures: &[String], audience: Option<String>, validity: Option<PresentationValidity>, issuer_public: &Self::IssuerPublic, holder_private_key: &[u8]) -> Result<(Map<String, Value>, String), String> {
+        BBSPlusInstance::issue_vp_for_audience(vc, disclosures, audience, validity, issuer_public, holder_private_key)
+    }
+
+    fn verify_vp(vp_jwt: &str, issuer_public: &Self::IssuerPublic, holder_public_key: &[u8], config: &VerifierConfig) -> Result<(), String> {
+        BBSPlusInstance::verify_vp_with_config(vp_jwt, issuer_public, holder_public_key, config)
+    }
+
+    fn disclosed_claims(vp_jwt: &str) -> Result<Vec<String>, String> {
+        disclosed_claims_of::<BBSPlusInstance>(vp_jwt)
+    }
+}
@@ -0,0 +1,260 @@
+use std::collections::BTreeMap;
+use crate::csv_writer::CSVWriter;
+
+/// Complexity models fitted to a benchmark series, each of the form `y = intercept + slope * term(n)`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ComplexityModel {
+    Constant,
+    Linear,
+    Linearithmic,
+    Quadratic,
+}
+
+impl ComplexityModel {
+
+    /// All the models, from the simplest to the most complex.
+    pub const ALL: [ComplexityModel; 4] = [
+        ComplexityModel::Constant,
+        ComplexityModel::Linear,
+        ComplexityModel::Linearithmic,
+        ComplexityModel::Quadratic,
+    ];
+
+    /// Name of the model, as written in the summary file.
+    pub fn name(&self) -> &'static str {
+        match self {
+            ComplexityModel::Constant => { "O(1)" }
+            ComplexityModel::Linear => { "O(n)" }
+            ComplexityModel::Linearithmic => { "O(n log n)" }
+            ComplexityModel::Quadratic => { "O(n^2)" }
+        }
+    }
+
+    /// Term of the model multiplied by the slope, with logarithms in base 2.
+    fn term(&self, n: f64) -> f64 {
+        match self {
+            ComplexityModel::Constant => { 0f64 }
+            ComplexityModel::Linear => { n }
+            ComplexityModel::Linearithmic => { n * n.log2() }
+            ComplexityModel::Quadratic => { n * n }
+        }
+    }
+}
+
+
+/// Least squares fit of a complexity model to a series.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ModelFit {
+    /// Fitted model.
+    pub model: ComplexityModel,
+    /// Fitted constant coefficient.
+    pub intercept: f64,
+    /// Fitted coefficient of the term of the model, always 0 for the constant model.
+    pub slope: f64,
+    /// Coefficient of determination of the fit.
+    pub r_squared: f64,
+}
+
+impl ModelFit {
+
+    /// Fits a model to a series through ordinary least squares.
+    ///
+    /// # Arguments
+    /// * `model` - Model to be fitted.
+    /// * `samples` - Pairs of amount of claims and measured value.
+    ///
+    /// # Returns
+    /// The fit, or None if the series is empty or, for non-constant models, has fewer than two distinct amounts of claims.
+    ///
+    /// # Examples
+    /// ```
+    /// use csd_jwt::scaling_model::{ComplexityModel, ModelFit};
+    ///
+    /// let samples: Vec<(f64, f64)> = (1..=10).map(|n| (n as f64, 3.0 + 2.0 * n as f64)).collect();
+    /// let fit = ModelFit::fit(ComplexityModel::Linear, &samples).unwrap();
+    /// assert!((fit.slope - 2.0).abs() < 1e-9 && (fit.intercept - 3.0).abs() < 1e-9);
+    /// assert!((fit.r_squared - 1.0).abs() < 1e-9);
+    /// ```
+    pub fn fit(model: ComplexityModel, samples: &[(f64, f64)]) -> Option<Self> {
+
+        if samples.is_empty() {
+            return None;
+        }
+
+        let len = samples.len() as f64;
+        let terms: Vec<f64> = samples.iter().map(|(n, _)| model.term(*n)).collect();
+        let mean_term: f64 = terms.iter().sum::<f64>() / len;
+        let mean_value: f64 = samples.iter().map(|(_, value)| value).sum::<f64>() / len;
+
+        let (intercept, slope) = match model {
+            ComplexityModel::Constant => { (mean_value, 0f64) }
+            _ => {
+                let term_variance: f64 = terms.iter().map(|term| (term - mean_term).powi(2)).sum();
+                if term_variance == 0f64 {
+                    return None;
+                }
+                let covariance: f64 = terms.iter().zip(samples).map(|(term, (_, value))| (term - mean_term) * (value - mean_value)).sum();
+                let slope = covariance / term_variance;
+                (mean_value - slope * mean_term, slope)
+            }
+        };
+
+        let total_sum_of_squares: f64 = samples.iter().map(|(_, value)| (value - mean_value).powi(2)).sum();
+        let residual_sum_of_squares: f64 = terms.iter().zip(samples).map(|(term, (_, value))| (value - intercept - slope * term).powi(2)).sum();
+
+        // A flat series is perfectly explained by any model matching it, and by no other.
+        let r_squared = if total_sum_of_squares == 0f64 {
+            if residual_sum_of_squares == 0f64 { 1f64 } else { 0f64 }
+        } else {
+            1f64 - residual_sum_of_squares / total_sum_of_squares
+        };
+
+        Some(ModelFit { model, intercept, slope, r_squared })
+    }
+}
+
+
+/// Fits of all the models to the series of a metric for an algorithm.
+#[derive(Clone, Debug)]
+pub struct SeriesFit {
+    /// Name of the metric, e.g. the name of the CSV file it is written to.
+    pub metric: String,
+    /// Name of the algorithm.
+    pub algorithm: String,
+    /// Amount of samples in the series.
+    pub samples: usize,
+    /// Fits of the models that could be fitted, from the simplest to the most complex.
+    pub fits: Vec<ModelFit>,
+}
+
+impl SeriesFit {
+
+    /// Model explaining the series best, i.e. with the highest R², preferring the simplest among equally good models.
+    ///
+    /// # Returns
+    /// The best fit, or None if no model could be fitted.
+    pub fn best(&self) -> Option<&ModelFit> {
+        self.fits.iter().fold(None, |best: Option<&ModelFit>, fit| match best {
+            Some(best) if best.r_squared >= fit.r_squared => { Some(best) }
+            _ => { Some(fit) }
+        })
+    }
+}
+
+
+/// Collects the series of the benchmark against the amount of claims and characterizes their asymptotic behaviour by
+/// fitting the complexity models to each of them.
+///
+/// # Examples
+/// ```
+/// use csd_jwt::scaling_model::{ComplexityModel, ScalingAnalysis};
+///
+/// let mut analysis = ScalingAnalysis::new();
+/// for n in 1..=20 {
+///     analysis.record("vc_jwt_length", "SD-JWT", n, 100.0 + 50.0 * n as f64);
+///     analysis.record("vc_jwt_length", "CSD-JWT", n, 400.0);
+/// }
+///
+/// let fits = analysis.fits();
+/// assert_eq!(fits.len(), 2);
+/// assert_eq!(fits.iter().find(|fit| fit.algorithm == "SD-JWT").unwrap().best().unwrap().model, ComplexityModel::Linear);
+/// assert_eq!(fits.iter().find(|fit| fit.algorithm == "CSD-JWT").unwrap().best().unwrap().model, ComplexityModel::Constant);
+/// ```
+pub struct ScalingAnalysis {
+    /// Samples of every series, keyed by metric and algorithm.
+    series: BTreeMap<(String, String), Vec<(f64, f64)>>,
+}
+
+impl ScalingAnalysis {
+
+    /// Constructor for an empty analysis.
+    ///
+    /// # Returns
+    /// An analysis without any series.
+    pub fn new() -> Self {
+        ScalingAnalysis { series: BTreeMap::new() }
+    }
+
+    /// Adds a sample to the series of a metric for an algorithm.
+    ///
+    /// # Arguments
+    /// * `metric` - Name of the metric.
+    /// * `algorithm` - Name of the algorithm.
+    /// * `n_claims` - Amount of claims in the credential.
+    /// * `value` - Measured value.
+    pub fn record(&mut self, metric: &str, algorithm: &str, n_claims: usize, value: f64) {
+        self.series
+            .entry((metric.to_string(), algorithm.to_string()))
+            .or_default()
+            .push((n_claims as f64, value));
+    }
+
+    /// Adds a row of samples, one per algorithm, to the series of a metric, skipping the missing values.
+    ///
+    /// # Arguments
+    /// * `metric` - Name of the metric.
+    /// * `algorithms` - Names of the algorithms, in the order of the values.
+    /// * `n_claims` - Amount of claims in the credential.
+    /// * `values` - Measured values, None if the measurement failed.
+    pub fn record_row(&mut self, metric: &str, algorithms: &[String], n_claims: usize, values: impl IntoIterator<Item = Option<f64>>) {
+        for (algorithm, value) in algorithms.iter().zip(values) {
+            if let Some(value) = value {
+                self.record(metric, algorithm, n_claims, value);
+            }
+        }
+    }
+
+    /// Fits all the models to every series.
+    ///
+    /// # Returns
+    /// The fits of every series, ordered by metric and algorithm.
+    pub fn fits(&self) -> Vec<SeriesFit> {
+        self.series.iter().map(|((metric, algorithm), samples)| SeriesFit {
+            metric: metric.clone(),
+            algorithm: algorithm.clone(),
+            samples: samples.len(),
+            fits: ComplexityModel::ALL.iter().filter_map(|model| ModelFit::fit(*model, samples)).collect(),
+        }).collect()
+    }
+
+    /// Writes the fitted coefficients and R² of every model and series to a CSV file, flagging the best model of each series.
+    ///
+    /// # Arguments
+    /// * `filename` - Name of the CSV file, without extension.
+    ///
+    /// # Returns
+    /// The result of the operation or a string containing an error.
+    pub fn write_summary(&self, filename: &String) -> Result<(), String> {
+
+        let columns: Vec<String> = ["metric", "algorithm", "samples", "model", "intercept", "slope", "r_squared", "best"]
+            .iter()
+            .map(|column| column.to_string())
+            .collect();
+        let mut writer = CSVWriter::new(columns)?;
+        writer.add_file(filename)?;
+
+        for series_fit in self.fits() {
+            let best = series_fit.best().map(|fit| fit.model);
+            for fit in &series_fit.fits {
+                writer.write_record_to_file(filename, vec![
+                    series_fit.metric.clone(),
+                    series_fit.algorithm.clone(),
+                    series_fit.samples.to_string(),
+                    fit.model.name().to_string(),
+                    fit.intercept.to_string(),
+                    fit.slope.to_string(),
+                    fit.r_squared.to_string(),
+                    (best == Some(fit.model)).to_string(),
+                ])?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for ScalingAnalysis {
+    fn default() -> Self {
+        Self::new()
+    }
+}
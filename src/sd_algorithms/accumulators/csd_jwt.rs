@@ -1,33 +1,150 @@
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashSet};
 use std::hash::Hash;
+use std::sync::Arc;
 use std::thread;
-use std::thread::JoinHandle;
-use ark_bn254::{Bn254, Fr, G1Affine};
-use ark_ff::PrimeField;
+use std::thread::ScopedJoinHandle;
+use ark_bn254::{Bn254, Fr, G1Affine, G1Projective, G2Affine, G2Projective};
+use ark_ec::pairing::Pairing;
+use ark_ec::CurveGroup;
+use ark_ff::{Field, PrimeField, Zero};
 use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
 use ark_std::rand::rngs::StdRng;
-use digest::Digest;
+use ark_std::UniformRand;
 use serde_json::{Map, Value};
-use sha2::Sha256;
+use vb_accumulator::batch_utils::Omega;
 use vb_accumulator::persistence::{State, UniversalAccumulatorState};
 use vb_accumulator::positive::Accumulator;
 use vb_accumulator::prelude::PositiveAccumulator;
-use vb_accumulator::setup::{Keypair, PublicKey, SecretKey, SetupParams};
+use vb_accumulator::proofs::{MembershipProof, MembershipProofProtocol};
+use vb_accumulator::setup::{Keypair, MembershipProvingKey, PublicKey, SecretKey, SetupParams};
 use vb_accumulator::witness::MembershipWitness;
 
-use crate::sd_algorithms::sd_algorithm::SdAlgorithm;
+use crate::audit::{claim_proofs_document, shared_signed_fields, split_claim_proofs, SIGNED_FIELDS, VALUE};
+use crate::common_data::ISSUED_AT;
+use crate::cost_profile::{record, Operation};
+use crate::deterministic::{ark_rng, random_bytes, unix_time};
+use crate::verifier_config::{check_all, VerifierConfig};
+use crate::credential_ir::CredentialIr;
+use crate::encoding::Encoding;
+use crate::ephemeral_binding::EphemeralKey;
+use crate::hashing::sha256;
+use crate::holder_binding::HolderBinding;
+use crate::presentation_validity::PresentationValidity;
+use crate::sd_algorithms::claim_protector::{bind_claim, ClaimProtector};
+use crate::sd_algorithms::sd_algorithm::{signed_fields, PresentationBatch, SdAlgorithm};
+use crate::sd_algorithms::verification_cache::VerificationCache;
 
 /// Identifier for the accumulator value in the VC/VP.
-const ACCUMULATOR: &str = "accumulator";
+pub(crate) const ACCUMULATOR: &str = "accumulator";
 /// Identifier for the Witness-Value Container in the VC/VP.
-const WVC: &str = "wvc";
+pub(crate) const WVC: &str = "wvc";
+/// Identifier for the Proof-Value Container in multi-show VPs.
+const PVC: &str = "pvc";
+/// Identifier for the container of the disclosed claims in aggregated VPs.
+const DVC: &str = "dvc";
+/// Identifier for the aggregated membership witness in aggregated VPs.
+const AGGREGATED_WITNESS: &str = "aggregated_witness";
+/// Identifier for the per-credential blinding factor of the accumulated elements in the VC/VP.
+const BLINDING: &str = "blinding";
+/// Length in bytes of the per-credential blinding factor of the accumulated elements.
+pub const BLINDING_LEN: usize = 16;
+/// Identifier for the accumulator value after the removals of a witness update message.
+const UPDATED_ACCUMULATOR: &str = "updated_accumulator";
+/// Identifier for the removed elements of a witness update message.
+const REMOVALS: &str = "removals";
+/// Identifier for the public update information (Omega) of a witness update message.
+const OMEGA: &str = "omega";
+/// Name of the aggregated-witness variant of the algorithm, used to tell it apart in the benchmark.
+pub const AGGREGATED_ALGORITHM: &str = "CSD-JWT-AGG";
+/// Name of the multi-show variant of the algorithm, used to tell it apart in the benchmark.
+pub const MULTI_SHOW_ALGORITHM: &str = "CSD-JWT-MS";
 
 
 /// Struct for an instance of the CSD-JWT algorithm.
 pub struct CsdJwtInstance;
 
+
+/// Verifier-side precomputation of the fixed G2 pairing inputs, i.e. the generator `P_tilde` of the setup parameters and
+/// the accumulator public key `Q_tilde`.
+///
+/// A membership witness `C` of an element `y` is valid for the accumulator value `V` if `e(C, y*P_tilde + Q_tilde) = e(V, P_tilde)`,
+/// which is checked as `e(y*C - V, P_tilde) * e(C, Q_tilde) = 1`, so that both G2 inputs are independent of the claim and their
+/// line coefficients are computed only once instead of at every verification.
+#[derive(Clone)]
+pub struct PreparedVerificationKey {
+    /// Prepared generator `P_tilde` of the setup parameters.
+    p_tilde: <Bn254 as Pairing>::G2Prepared,
+    /// Prepared accumulator public key `Q_tilde`.
+    public_key: <Bn254 as Pairing>::G2Prepared,
+}
+
+impl PreparedVerificationKey {
+
+    /// Prepares the G2 pairing inputs of an accumulator public key.
+    ///
+    /// # Arguments
+    /// * `issuer_public_key` - Accumulator public key of the issuer.
+    /// * `params` - Setup parameters the public key was generated with.
+    ///
+    /// # Returns
+    /// The prepared verification key.
+    pub fn new(issuer_public_key: &PublicKey<Bn254>, params: &SetupParams<Bn254>) -> Self {
+        PreparedVerificationKey {
+            p_tilde: params.P_tilde.into(),
+            public_key: issuer_public_key.0.into(),
+        }
+    }
+
+    /// Verifies a membership witness using the prepared pairing inputs.
+    ///
+    /// # Arguments
+    /// * `accumulator_value` - Value of the accumulator.
+    /// * `element` - Accumulated element.
+    /// * `witness` - Membership witness of the element.
+    ///
+    /// # Returns
+    /// True if the witness is valid for the element and the accumulator value, false otherwise.
+    pub fn verify_membership(&self, accumulator_value: &G1Affine, element: &Fr, witness: &MembershipWitness<G1Affine>) -> bool {
+        record(Operation::Exponentiation, 1);
+        record(Operation::Pairing, 2);
+        let shifted_witness: G1Affine = (witness.0 * *element - accumulator_value).into_affine();
+        let miller_loop = Bn254::multi_miller_loop([shifted_witness, witness.0], [self.p_tilde.clone(), self.public_key.clone()]);
+
+        match Bn254::final_exponentiation(miller_loop) {
+            Some(output) => { output.is_zero() }
+            None => { false }
+        }
+    }
+}
+
 impl SdAlgorithm for CsdJwtInstance {
     const ALGORITHM: &'static str = "CSD-JWT";
+    // The accumulator value is only bound to the issuer through the membership of the disclosed claims.
+    const SUPPORTS_EMPTY_DISCLOSURE: bool = false;
+    const PROOF_FIELDS: &'static [&'static str] = &[ACCUMULATOR, WVC, BLINDING];
+
+    fn issued_claims(vc: &Map<String, Value>) -> Result<Map<String, Value>, String> {
+        let witness_value_container: Map<String, Value> = Self::get_and_decode(vc, WVC.to_string())?;
+        Self::container_values(&witness_value_container)
+    }
+
+    fn disclosed_claim_names(credential: &Map<String, Value>) -> Result<Vec<String>, String> {
+        let container: &str = match [WVC, PVC, DVC].into_iter().find(|container| credential.contains_key(*container)) {
+            Some(container) => { container }
+            None => { return Err("Credential does not contain any claim container.".to_string()) }
+        };
+
+        let value_container: Map<String, Value> = Self::get_and_decode(credential, container.to_string())?;
+        Ok(value_container.keys().cloned().collect())
+    }
+}
+
+impl ClaimProtector for CsdJwtInstance {
+    type Element = Fr;
+
+    fn protect_claim(name: &str, value: &Value, opening: Option<&String>) -> Result<Fr, String> {
+        Ok(Self::convert_claim_to_scalar(name, value, opening.map(String::as_str)))
+    }
 }
 
 impl CsdJwtInstance {
@@ -48,6 +165,72 @@ impl CsdJwtInstance {
     }
 
 
+    /// Generates the proving key used by holders to produce zero-knowledge membership proofs in multi-show presentations.
+    ///
+    /// # Arguments
+    /// * `rng` - Random Number Generator for producing the key.
+    ///
+    /// # Returns
+    /// This function returns the membership proving key, which is public and shared by issuer, holders and verifiers.
+    pub fn initialize_proving_key(rng: &mut StdRng) -> MembershipProvingKey<G1Affine> {
+        MembershipProvingKey::<G1Affine>::generate_using_rng(rng)
+    }
+
+
+    /// Generates the aggregation key needed to verify aggregated witnesses, i.e. the powers of the accumulator secret
+    /// key in G2. The key is public and allows the verification of aggregated witnesses for up to `claims_len` claims.
+    ///
+    /// # Arguments
+    /// * `issuer_private_key` - Accumulator secret key.
+    /// * `params` - Setup parameters of the accumulator.
+    /// * `claims_len` - Maximum amount of claims that can be aggregated in a single witness.
+    ///
+    /// # Returns
+    /// This function returns the vector of powers of the secret key in G2, starting from the power zero.
+    pub fn initialize_aggregation_key(issuer_private_key: &SecretKey<Fr>, params: &SetupParams<Bn254>, claims_len: usize) -> Vec<G2Affine> {
+        let mut aggregation_key: Vec<G2Affine> = Vec::with_capacity(claims_len + 1);
+        let mut power: Fr = Fr::from(1u64);
+
+        for _ in 0..=claims_len {
+            record(Operation::Exponentiation, 1);
+            aggregation_key.push((params.P_tilde * power).into_affine());
+            power *= issuer_private_key.0;
+        }
+
+        aggregation_key
+    }
+
+    /// Maximum amount of claims whose witnesses can be aggregated and verified with an aggregation key.
+    ///
+    /// # Arguments
+    /// * `aggregation_key` - Powers of the accumulator secret key in G2, as published by the issuer.
+    ///
+    /// # Returns
+    /// This function returns the amount of claims, zero if the key is empty.
+    pub fn aggregation_capacity(aggregation_key: &[G2Affine]) -> usize {
+        aggregation_key.len().saturating_sub(1)
+    }
+
+    /// Checks that a credential does not exceed the maximum amount of claims a setup was provisioned for, so that it is
+    /// rejected at issuance with an explicit error rather than failing inside the accumulator or only once its
+    /// presentations are verified.
+    ///
+    /// # Arguments
+    /// * `raw_vc` - Template VC containing a credential.
+    /// * `max_claims` - Maximum amount of claims supported by the setup.
+    ///
+    /// # Returns
+    /// This function returns a string containing an error if the credential has more claims than the setup supports.
+    pub fn check_capacity(raw_vc: &Map<String, Value>, max_claims: usize) -> Result<(), String> {
+        let claims_len: usize = Self::lower(raw_vc)?.claims().len();
+        if claims_len > max_claims {
+            return Err(format!("Credential has {claims_len} claims, but the CSD-JWT setup supports at most {max_claims} claims."));
+        }
+
+        Ok(())
+    }
+
+
     /// Utility function to serialize structs that implement CanonicalSerialize like accumulators and witnesses.
     ///
     /// # Arguments
@@ -56,6 +239,20 @@ impl CsdJwtInstance {
     /// # Returns
     /// This function returns a result wrapping the encoding of the element or a string illustrating the error, if it occurs.
     pub fn serialize<S>(element: &S) -> Result<String, String>
+    where S: CanonicalSerialize {
+        Self::serialize_with_encoding(element, Self::ENCODING)
+    }
+
+
+    /// Same as `serialize`, but with an explicit encoding instead of the one of the algorithm.
+    ///
+    /// # Arguments
+    /// * `element` - Element to be serialized.
+    /// * `encoding` - Encoding of the serialized element.
+    ///
+    /// # Returns
+    /// This function returns a result wrapping the encoding of the element or a string illustrating the error, if it occurs.
+    pub fn serialize_with_encoding<S>(element: &S, encoding: Encoding) -> Result<String, String>
     where S: CanonicalSerialize {
         let mut compressed_bytes: Vec<u8> = Vec::new();
         match element.serialize_compressed(&mut compressed_bytes) {
@@ -63,7 +260,7 @@ impl CsdJwtInstance {
             Err(err) => { return Err(format!("Error in serialization of element: [{err}]")) }
         };
 
-        Ok(multibase::Base::Base64Url.encode(compressed_bytes))
+        Ok(encoding.encode(compressed_bytes))
     }
 
 
@@ -75,9 +272,9 @@ impl CsdJwtInstance {
     ///
     /// # Returns
     /// This function returns a result wrapping the deserialization of element or a string illustrating the error, if it occurs.
-    pub fn deserialize<D>(encoded_element: &String) -> Result<D, String>
+    pub fn deserialize<D>(encoded_element: &str) -> Result<D, String>
     where D: CanonicalDeserialize {
-        let decoded = match multibase::Base::Base64Url.decode(encoded_element) {
+        let decoded = match Self::ENCODING.decode(encoded_element) {
             Ok(byte_array) => { byte_array }
             Err(err) => { return Err(format!("Error in decoding element: [{err}]")) }
         };
@@ -90,27 +287,166 @@ impl CsdJwtInstance {
     }
 
 
-    /// Maps claims to scalar values by concatenating key and value and hashing them.
+    /// Maps claims to scalar values by hashing the unambiguous encoding of their key and value, so that the accumulated
+    /// element, and thus its witness, is bound to the key of the claim. The context of the credential is hashed as
+    /// well: its blinding factor, if blinded, so that the same claim maps to unrelated elements in different
+    /// credentials, and its signed fields, so that they cannot be swapped or removed without invalidating the witnesses.
     ///
     /// # Arguments
     ///
     /// * `key` - Name of the element.
     /// * `value` - Value of the element.
+    /// * `context` - Optional context of the credential, as returned by `element_context`.
     ///
     /// # Returns
     /// This function returns the converted scalar.
-    pub fn convert_claim_to_scalar(key: &String, value: &Value) -> Fr {
+    pub fn convert_claim_to_scalar(key: &str, value: &Value, context: Option<&str>) -> Fr {
+
+        record(Operation::Hash, 1);
+        Fr::from_be_bytes_mod_order(&sha256(bind_claim(key, &Self::canonical_json(value), context)))
+
+    }
+
+
+    /// Reads the blinding factor of the accumulated elements of a VC or VP.
+    ///
+    /// # Arguments
+    /// * `credential` - Verifiable Credential or credential of a Verifiable Presentation.
+    /// * `config` - Limits enforced on the blinding factor by a verifier, or none for the holder's own credentials.
+    ///
+    /// # Returns
+    /// This function returns a result containing the blinding factor, or None if the credential is not blinded, or a
+    /// string representing an error if the blinding factor cannot be decoded.
+    fn blinding_factor(credential: &Map<String, Value>, config: Option<&VerifierConfig>) -> Result<Option<String>, String> {
+        if !credential.contains_key(BLINDING) {
+            return Ok(None);
+        }
+
+        let blinding: String = Self::get_and_decode_with_encoding(credential, BLINDING.to_string(), Self::ENCODING, config)?;
+        Ok(Some(blinding))
+    }
+
+
+    /// Reads the context the accumulated elements of a VC or VP are bound to: its blinding factor followed by its
+    /// signed fields.
+    ///
+    /// # Arguments
+    /// * `credential` - Verifiable Credential or credential of a Verifiable Presentation.
+    /// * `config` - Limits enforced on the blinding factor by a verifier, or none for the holder's own credentials.
+    ///
+    /// # Returns
+    /// This function returns a result containing the context, or None if the credential is neither blinded nor has
+    /// signed fields, or a string representing an error if the blinding factor cannot be decoded.
+    fn element_context(credential: &Map<String, Value>, config: Option<&VerifierConfig>) -> Result<Option<String>, String> {
+        let blinding: Option<String> = Self::blinding_factor(credential, config)?;
+        Ok(Self::combine_context(blinding.as_deref(), Self::serialize_signed_fields(credential)))
+    }
+
+
+    /// Concatenates the blinding factor and the serialized signed fields of a credential into the context of its
+    /// elements. The blinding factor is base64url encoded and the signed fields a JSON object, so the concatenation is
+    /// unambiguous.
+    fn combine_context(blinding: Option<&str>, signed_fields: Option<String>) -> Option<String> {
+        match (blinding, signed_fields) {
+            (None, None) => { None }
+            (blinding, signed_fields) => { Some(format!("{}{}", blinding.unwrap_or_default(), signed_fields.unwrap_or_default())) }
+        }
+    }
+
 
-        let mut hasher = Sha256::new();
-        let mut hash_input = key.clone();
-        hash_input.push(':');
-        hash_input.push_str(&*value.to_string());
+    /// Splits an entry of the Witness-Value Container into the decoded witness and the claim value.
+    ///
+    /// # Arguments
+    /// * `array_value` - Entry of the Witness-Value Container.
+    ///
+    /// # Returns
+    /// This function returns a result containing the witness and the claim value or a string representing an error.
+    fn split_witness_and_value(array_value: &Value) -> Result<(MembershipWitness<G1Affine>, Value), String> {
+        let array = match array_value {
+            Value::Array(array) => { array }
+            _ => { return Err("Error, array field in Witness value container is not an array".to_string()) }
+        };
+        let witness = match array.first() {
+            Some(Value::String(witness_string)) => { Self::deserialize(witness_string)? }
+            Some(_) => { return Err("Witness in Witness value container is not a string.".to_string()) }
+            None => { return Err("Witness not found in Witness value container.".to_string()) }
+        };
+        let claim_value = match array.get(1) {
+            Some(value) => { value.clone() }
+            None => { return Err("Value not found in Witness value container.".to_string()) }
+        };
+
+        Ok((witness, claim_value))
+    }
+
+
+    /// Derives the Fiat-Shamir challenge shared by all the membership proofs of a multi-show presentation.
+    ///
+    /// # Arguments
+    /// * `contributions` - Concatenation of the challenge contributions of every membership proof.
+    ///
+    /// # Returns
+    /// This function returns the challenge as a scalar.
+    fn membership_challenge(contributions: &[u8]) -> Fr {
+        record(Operation::Hash, 1);
+        Fr::from_be_bytes_mod_order(&sha256(contributions))
+    }
 
-        hasher.update(hash_input);
-        let result = hasher.finalize();
 
-        Fr::from_be_bytes_mod_order(&result.as_slice())
+    /// Aggregates the membership witnesses of several elements into a single witness for the whole set.
+    ///
+    /// Given the witnesses `C_i = V/(y_i + a)`, the aggregated witness `V/prod(y_i + a)` is obtained through partial
+    /// fraction decomposition as `sum(C_i / prod_{j != i}(y_j - y_i))`, so no secret key is needed.
+    ///
+    /// # Arguments
+    /// * `elements` - Elements whose membership is to be proven.
+    /// * `witnesses` - Membership witnesses of the elements, in the same order.
+    /// * `accumulator` - Accumulator value, used as the aggregated witness of the empty set.
+    ///
+    /// # Returns
+    /// This function returns the aggregated witness or a string representing an error.
+    fn aggregate_witnesses(elements: &[Fr], witnesses: &Vec<MembershipWitness<G1Affine>>, accumulator: &PositiveAccumulator<Bn254>) -> Result<G1Affine, String> {
+        if elements.is_empty() {
+            return Ok(*accumulator.value());
+        }
+
+        let mut aggregated = G1Projective::zero();
+        for (i, (element, witness)) in elements.iter().zip(witnesses.iter()).enumerate() {
+            let mut denominator: Fr = Fr::from(1u64);
+            for (j, other) in elements.iter().enumerate() {
+                if i != j {
+                    denominator *= *other - *element;
+                }
+            }
+            let coefficient = match denominator.inverse() {
+                Some(coefficient) => { coefficient }
+                None => { return Err("Duplicate elements cannot be aggregated.".to_string()) }
+            };
+            record(Operation::Exponentiation, 1);
+            aggregated += witness.0 * coefficient;
+        }
+
+        Ok(aggregated.into_affine())
+    }
 
+
+    /// Checks the amount of claims of a Witness-Value Container and the length of their witnesses against the limits
+    /// of the verifier.
+    ///
+    /// # Arguments
+    /// * `wvc` - Witness-Value Container.
+    /// * `config` - Limits enforced on the container.
+    ///
+    /// # Returns
+    /// This function returns a result containing a string representing an error if a limit is exceeded.
+    fn check_witness_value_container_limits(wvc: &Map<String, Value>, config: &VerifierConfig) -> Result<(), String> {
+        config.check_claims_len(wvc.len())?;
+        for array_value in wvc.values() {
+            if let Some(Value::String(witness_string)) = array_value.get(0) {
+                config.check_witness_length(witness_string)?;
+            }
+        }
+        Ok(())
     }
 
 
@@ -119,46 +455,113 @@ impl CsdJwtInstance {
     /// # Arguments
     /// * `wvc` - Witness-Value Container.
     /// * `accumulator` - Accumulator value.
+    /// * `context` - Optional context of the credential, as returned by `element_context`.
     /// * `issuer_public_key` - Issuer's public key used to validate the signature with.
+    /// * `cache` - Optional verifier-side cache used to skip claims already verified against the same accumulator.
+    /// * `prepared_key` - Optional precomputed pairing inputs of the issuer's public key.
     ///
     /// # Returns
     /// This function returns a result containing a string representing an error in case of failure.
-    fn verify_witness_value_container(wvc: &Map<String, Value>, accumulator: &PositiveAccumulator<Bn254>, issuer_public_key: &PublicKey<Bn254>, params: &SetupParams<Bn254>) -> Result<(), String> {
-
-        let mut threads: Vec<JoinHandle<Result<(), String>>> = vec![];
-
-        for (claim_key, array_value) in wvc.clone() {
-
-            let thread_accumulator = accumulator.clone();
-            let thread_pk = issuer_public_key.clone();
-            let thread_params = params.clone();
-            let thread = thread::spawn(move || {
-                if let Value::Array(array) = array_value {
-                    let witness_value = match array.get(0) {
-                        None => { return Err("Salt not found in salt value container.".to_string()) }
-                        Some(key) => { key }
-                    };
-                    let claim_value = match array.get(1) {
-                        None => { return Err("Value not found in salt value container.".to_string()) }
-                        Some(value) => { value }
-                    };
-
-                    let element: Fr;
-                    let witness: MembershipWitness<G1Affine>;
-                    match witness_value {
-                        Value::String(witness_string) => {
-                            witness = Self::deserialize(witness_string)?;
-                            element = Self::convert_claim_to_scalar(&claim_key, claim_value);
-                            thread_accumulator.verify_membership(&element, &witness, &thread_pk, &thread_params);
-                        }
-                        _ => { return Err("Either witnesses or values are not strings.".to_string()) }
+    fn verify_witness_value_container(wvc: &Map<String, Value>, accumulator: &PositiveAccumulator<Bn254>, context: Option<&str>, issuer_public_key: &PublicKey<Bn254>, params: &SetupParams<Bn254>, cache: Option<&Arc<VerificationCache>>, prepared_key: Option<&Arc<PreparedVerificationKey>>) -> Result<(), String> {
+
+        let scope: String = match cache {
+            Some(_) => { Self::serialize(accumulator)? }
+            None => { String::new() }
+        };
+
+        let mut pending: Vec<(&String, &Value, Option<String>)> = vec![];
+        for (claim_key, array_value) in wvc {
+            let digest: Option<String> = match (cache, array_value) {
+                (Some(cache), Value::Array(array)) if array.len() > 1 => {
+                    let digest = Self::serialize(&Self::convert_claim_to_scalar(claim_key, &array[1], context))?;
+                    if cache.contains(&scope, &digest) {
+                        continue;
                     }
-                } else {
-                    return Err("Error, array field in Witness value container is not an array".to_string())
+                    Some(digest)
                 }
-                Ok(())
-            });
-            threads.push(thread);
+                _ => { None }
+            };
+            pending.push((claim_key, array_value, digest));
+        }
+
+        let verify = |(claim_key, array_value, digest): &(&String, &Value, Option<String>)| -> Result<(), String> {
+            Self::verify_witness(claim_key, array_value, accumulator, context, issuer_public_key, params, prepared_key)?;
+            if let (Some(cache), Some(digest)) = (cache, digest) {
+                cache.insert(&scope, digest);
+            }
+            Ok(())
+        };
+
+        // Mobile builds verify on the calling thread, as wallets run the verification off their UI thread already.
+        if cfg!(feature = "mobile") {
+            return check_all(pending.iter().map(verify));
+        }
+
+        // A bounded amount of workers verifies contiguous chunks of claims, rather than a thread per claim, so that
+        // credentials with thousands of claims do not exhaust the threads available to the process.
+        let workers: usize = thread::available_parallelism().map(|workers| workers.get()).unwrap_or(1);
+        let chunk_size: usize = pending.len().div_ceil(workers).max(1);
+        let results: Vec<Result<(), String>> = thread::scope(|pool| {
+            let handles: Vec<ScopedJoinHandle<Vec<Result<(), String>>>> = pending.chunks(chunk_size).map(|chunk| {
+                let verify = &verify;
+                pool.spawn(move || chunk.iter().map(verify).collect())
+            }).collect();
+
+            handles.into_iter().flat_map(|handle| match handle.join() {
+                Ok(results) => { results }
+                Err(_) => { vec![Err("Membership verification thread panicked.".to_string())] }
+            }).collect()
+        });
+
+        // Every witness is verified before reporting a failure, so that the time taken does not reveal which claim failed.
+        check_all(results)
+    }
+
+
+    /// Verifies the membership witness of a single claim of a Witness-Value Container.
+    ///
+    /// # Arguments
+    /// * `claim_key` - Name of the claim.
+    /// * `array_value` - Witness and value of the claim.
+    /// * `accumulator` - Accumulator value.
+    /// * `context` - Optional context of the credential, as returned by `element_context`.
+    /// * `issuer_public_key` - Issuer's public key used to validate the signature with.
+    /// * `params` - Additional parameters needed for correct handling of the accumulator value.
+    /// * `prepared_key` - Optional precomputed pairing inputs of the issuer's public key.
+    ///
+    /// # Returns
+    /// This function returns a result containing a string representing an error in case of failure.
+    fn verify_witness(claim_key: &String, array_value: &Value, accumulator: &PositiveAccumulator<Bn254>, context: Option<&str>, issuer_public_key: &PublicKey<Bn254>, params: &SetupParams<Bn254>, prepared_key: Option<&Arc<PreparedVerificationKey>>) -> Result<(), String> {
+        let array = match array_value {
+            Value::Array(array) => { array }
+            _ => { return Err("Error, array field in Witness value container is not an array".to_string()) }
+        };
+        let witness_value = match array.first() {
+            None => { return Err("Salt not found in salt value container.".to_string()) }
+            Some(key) => { key }
+        };
+        let claim_value = match array.get(1) {
+            None => { return Err("Value not found in salt value container.".to_string()) }
+            Some(value) => { value }
+        };
+
+        let witness_string = match witness_value {
+            Value::String(witness_string) => { witness_string }
+            _ => { return Err("Either witnesses or values are not strings.".to_string()) }
+        };
+        let witness: MembershipWitness<G1Affine> = Self::deserialize(witness_string)?;
+        let element: Fr = Self::convert_claim_to_scalar(claim_key, claim_value, context);
+        let verified: bool = match prepared_key {
+            Some(prepared_key) => { prepared_key.verify_membership(accumulator.value(), &element, &witness) }
+            None => {
+                // e(C, y*P_tilde + Q_tilde) = e(V, P_tilde)
+                record(Operation::Exponentiation, 1);
+                record(Operation::Pairing, 2);
+                accumulator.verify_membership(&element, &witness, issuer_public_key, params)
+            }
+        };
+        if !verified {
+            return Err(format!("Membership verification failed for claim {claim_key}"))
         }
 
         Ok(())
@@ -175,20 +578,45 @@ impl CsdJwtInstance {
     /// # Returns
     /// This function returns a VC both in the form of a Map and in the form of an unsigned JWT.
     pub fn issue_vc(raw_vc: &Map<String, Value>, issuer_private_key: &SecretKey<Fr>, params: &SetupParams<Bn254>) -> Result<(Map<String, Value>, String), String> {
+        Self::issue_vc_with_blinding(raw_vc, issuer_private_key, params, false)
+    }
+
 
-        let mut vc = raw_vc.clone();
+    /// Same as `issue_vc`, optionally blinding the accumulated elements with a random factor chosen for this credential
+    /// only. The factor is stored in the VC next to the witnesses and hashed into every element, so that identical
+    /// claims of different credentials are accumulated as unrelated elements and the accumulator values, witnesses and
+    /// elements of two credentials cannot be correlated through the claims they share.
+    ///
+    /// # Arguments
+    /// * `raw_vc` - Template VC containing a credential.
+    /// * `issuer_private_key` - Private key of the issuer used to generate the signature of the list of hashes.
+    /// * `params` - Additional parameters needed for correct handling of the accumulator value.
+    /// * `blinded` - Whether the accumulated elements are blinded.
+    ///
+    /// # Returns
+    /// This function returns a VC both in the form of a Map and in the form of an unsigned JWT.
+    pub fn issue_vc_with_blinding(raw_vc: &Map<String, Value>, issuer_private_key: &SecretKey<Fr>, params: &SetupParams<Bn254>, blinded: bool) -> Result<(Map<String, Value>, String), String> {
 
-        let claims: &Map<String, Value> = Self::extract_claims(&vc)?;
+        let credential: CredentialIr = Self::lower(raw_vc)?;
+        let mut vc = credential.envelope();
 
         let accumulator: PositiveAccumulator<Bn254> = PositiveAccumulator::initialize(params);
         let mut state: InMemoryState<Fr> = InMemoryState::new();
 
-        let mut elements: Vec<Fr> = vec![];
-
-        for (field, value) in claims {
-            elements.push(Self::convert_claim_to_scalar(field, value));
-        }
+        // The context, i.e. the blinding factor and the signed fields, is the opening of every claim of the credential.
+        let blinding: Option<String> = if blinded { Some(Encoding::Base64UrlNoPad.encode(random_bytes(BLINDING_LEN))) } else { None };
+        let context: Option<String> = Self::combine_context(blinding.as_deref(), Self::serialize_signed_fields(&vc));
+        let elements: Vec<Fr> = match &context {
+            Some(context) => {
+                let openings: Map<String, Value> = credential.claims().keys().map(|name| (name.clone(), Value::String(context.clone()))).collect();
+                Self::protected_elements(credential.claims(), &openings)?
+            }
+            None => { Self::protect_claims(credential.claims())?.0 }
+        };
+        let claims: Vec<(&String, &Value)> = Self::canonical_claims(credential.claims());
 
+        // V' = V * prod(y_i + a), then C_i = V' / (y_i + a) for every claim.
+        record(Operation::Exponentiation, 1 + elements.len() as u64);
         let accumulator = match accumulator.add_batch(elements.clone(), issuer_private_key, &mut state) {
             Ok(accumulator) => { accumulator }
             Err(err) => { return Err(format!("Error in adding batch claims: [{:?}]", err)) }
@@ -201,7 +629,7 @@ impl CsdJwtInstance {
         };
 
         let mut witness;
-        for (index, (key, value)) in claims.iter().enumerate() {
+        for (index, (key, value)) in claims.into_iter().enumerate() {
             witness = Self::serialize(witnesses.get(index).unwrap())?;
             witness_value_container.insert(key.clone(), Value::Array(vec![Value::String(witness), value.clone()]));
         }
@@ -209,7 +637,9 @@ impl CsdJwtInstance {
         let serialized_accumulator = Self::serialize(&accumulator)?;
         Self::serialize_and_insert(&mut vc, ACCUMULATOR.to_string(), &serialized_accumulator)?;
         Self::serialize_and_insert(&mut vc, WVC.to_string(), &witness_value_container)?;
-        Self::remove_claims(&mut vc)?;
+        if let Some(blinding) = blinding {
+            Self::serialize_and_insert(&mut vc, BLINDING.to_string(), &blinding)?;
+        }
 
         let jwt = Self::encode_jwt(&vc)?;
 
@@ -226,96 +656,777 @@ impl CsdJwtInstance {
     /// # Returns
     /// This function returns a string containing an error in case of failure.
     pub fn verify_vc(vc: &Map<String, Value>, issuer_public_key: &PublicKey<Bn254>, params: &SetupParams<Bn254>) -> Result<(), String> {
+        Self::verify_vc_with_config(vc, issuer_public_key, params, &Self::VERIFIER_CONFIG)
+    }
 
-        let witness_value_container: Map<String, Value> = Self::get_and_decode(vc, WVC.to_string())?;
-        let serialized_accumulator: String = Self::get_and_decode(vc, ACCUMULATOR.to_string())?;
+
+    /// Same as `verify_vc`, but enforcing the limits of the verifier on the amount of claims and on the witnesses of
+    /// the VC.
+    ///
+    /// # Arguments
+    /// * `vc` - Verifiable Credential.
+    /// * `issuer_public_key` - Issuer's public key to verify the signature of the list of hashes.
+    /// * `params` - Additional parameters needed for correct handling of the accumulator value.
+    /// * `config` - Limits enforced on the VC.
+    ///
+    /// # Returns
+    /// This function returns a string containing an error in case of failure.
+    pub fn verify_vc_with_config(vc: &Map<String, Value>, issuer_public_key: &PublicKey<Bn254>, params: &SetupParams<Bn254>, config: &VerifierConfig) -> Result<(), String> {
+
+        let witness_value_container: Map<String, Value> = Self::get_and_decode_with_config(vc, WVC.to_string(), config)?;
+        let serialized_accumulator: String = Self::get_and_decode_with_config(vc, ACCUMULATOR.to_string(), config)?;
 
         let accumulator: PositiveAccumulator<Bn254> = Self::deserialize(&serialized_accumulator)?;
+        let context: Option<String> = Self::element_context(vc, Some(config))?;
 
-        Self::verify_witness_value_container(&witness_value_container, &accumulator, issuer_public_key, params)?;
+        Self::check_witness_value_container_limits(&witness_value_container, config)?;
+        Self::verify_witness_value_container(&witness_value_container, &accumulator, context.as_deref(), issuer_public_key, params, None, None)?;
 
         Ok(())
     }
 
 
-    /// Given a VC, and a set of disclosures, create a Verifiable Presentation accordingly.
+    /// Checks every witness of a received VC against its accumulator on the holder side, before storing it, so that a
+    /// malformed credential is rejected at issuance rather than when presenting the affected claims. Unlike `verify_vc`,
+    /// a malformed or invalid witness does not stop the check but marks its claim as invalid.
     ///
     /// # Arguments
     /// * `vc` - Verifiable Credential.
-    /// * `disclosures` - List of strings containing the names of the claims that are to be disclosed.
-    /// * `holder_private_key` - Holder's private key necessary for proof of possession.
+    /// * `issuer_public_key` - Issuer's public key the accumulator was built with.
+    /// * `params` - Additional parameters needed for correct handling of the accumulator value.
     ///
     /// # Returns
-    /// This function returns the VP both in form of a Map and in form of a signed JWT.
-    pub fn issue_vp(vc: &Map<String, Value>, disclosures: &Vec<String>, holder_private_key: &impl AsRef<[u8]>) -> Result<(Map<String, Value>, String), String> {
+    /// This function returns a result containing the validity of the witness of every claim, or a string representing
+    /// an error if the VC does not contain a decodable accumulator and Witness-Value Container.
+    pub fn check_witnesses(vc: &Map<String, Value>, issuer_public_key: &PublicKey<Bn254>, params: &SetupParams<Bn254>) -> Result<BTreeMap<String, bool>, String> {
 
-        let mut vp: Map<String, Value> = vc.clone();
+        let witness_value_container: Map<String, Value> = Self::get_and_decode(vc, WVC.to_string())?;
+        let serialized_accumulator: String = Self::get_and_decode(vc, ACCUMULATOR.to_string())?;
+        let accumulator: PositiveAccumulator<Bn254> = Self::deserialize(&serialized_accumulator)?;
+        let context: Option<String> = Self::element_context(vc, None)?;
+        let prepared_key = PreparedVerificationKey::new(issuer_public_key, params);
+
+        let mut validity: BTreeMap<String, bool> = BTreeMap::new();
+        for (claim_key, array_value) in &witness_value_container {
+            let valid: bool = match Self::split_witness_and_value(array_value) {
+                Ok((witness, claim_value)) => {
+                    let element: Fr = Self::convert_claim_to_scalar(claim_key, &claim_value, context.as_deref());
+                    prepared_key.verify_membership(accumulator.value(), &element, &witness)
+                }
+                Err(_) => { false }
+            };
+            validity.insert(claim_key.clone(), valid);
+        }
 
-        let witness_value_container: Map<String, Value> = Self::get_and_decode(&mut vp, WVC.to_string())?;
-        let mut new_witness_value_container: Map<String, Value> = Map::new();
+        Ok(validity)
+    }
+
+
+    /// Revokes some claims of a VC by removing their elements from its accumulator, and creates the witness update
+    /// message the issuer publishes for the holder: the superseded and the updated accumulator values, the removed
+    /// elements and the public update information Omega, signed by the issuer. The message has one element per removed
+    /// claim and a single Omega for the whole batch, so that the holder updates every remaining witness at once.
+    ///
+    /// # Arguments
+    /// * `vc` - Verifiable Credential, as issued.
+    /// * `revoked` - Names of the claims to be revoked.
+    /// * `issuer_private_key` - Private key of the accumulator.
+    /// * `signing_key` - Private key of the issuer signing the message.
+    ///
+    /// # Returns
+    /// This function returns a result containing the signed message or a string representing an error.
+    pub fn revoke_claims(vc: &Map<String, Value>, revoked: &[String], issuer_private_key: &SecretKey<Fr>, signing_key: &impl AsRef<[u8]>) -> Result<String, String> {
+
+        let witness_value_container: Map<String, Value> = Self::get_and_decode(vc, WVC.to_string())?;
+        let serialized_accumulator: String = Self::get_and_decode(vc, ACCUMULATOR.to_string())?;
+        let accumulator: PositiveAccumulator<Bn254> = Self::deserialize(&serialized_accumulator)?;
+        let context: Option<String> = Self::element_context(vc, None)?;
+
+        if let Some(claim) = revoked.iter().find(|claim| !witness_value_container.contains_key(*claim)) {
+            return Err(format!("Claim {claim} is not in the VC."));
+        }
+        if revoked.is_empty() {
+            return Err("No claim to revoke.".to_string());
+        }
 
-        for (field, value) in witness_value_container {
-            if disclosures.contains(&field) {
-                new_witness_value_container.insert(field, value);
+        let mut state: InMemoryState<Fr> = InMemoryState::new();
+        let mut removals: Vec<Fr> = vec![];
+        for (claim_key, array_value) in &witness_value_container {
+            let (_, claim_value) = Self::split_witness_and_value(array_value)?;
+            let element: Fr = Self::convert_claim_to_scalar(claim_key, &claim_value, context.as_deref());
+            state.add(element);
+            if revoked.contains(claim_key) {
+                removals.push(element);
             }
         }
 
-        Self::serialize_and_insert(&mut vp, WVC.to_string(), &new_witness_value_container)?;
-        let jwt: String = Self::encode_and_sign_jwt(&mut vp, holder_private_key)?;
+        // V' = V / prod(y_i + a), and Omega holds one point per removed element.
+        record(Operation::Exponentiation, 1 + removals.len() as u64);
+        let omega: Omega<G1Affine> = Omega::new(&[], &removals, accumulator.value(), issuer_private_key);
+        let updated_accumulator = match accumulator.remove_batch(&removals, issuer_private_key, &mut state) {
+            Ok(updated_accumulator) => { updated_accumulator }
+            Err(err) => { return Err(format!("Error in removing batch claims: [{:?}]", err)) }
+        };
 
-        Ok((vp, jwt))
+        let mut message: Map<String, Value> = Map::new();
+        message.insert(ACCUMULATOR.to_string(), Value::String(serialized_accumulator));
+        message.insert(UPDATED_ACCUMULATOR.to_string(), Value::String(Self::serialize(&updated_accumulator)?));
+        message.insert(REMOVALS.to_string(), Value::String(Self::serialize(&removals)?));
+        message.insert(OMEGA.to_string(), Value::String(Self::serialize(&omega.0)?));
+        message.insert(ISSUED_AT.to_string(), Value::from(unix_time()?));
+
+        Self::encode_and_sign_jwt(&message, signing_key)
     }
 
 
-    /// Given a VP, verify it using all the necessary data.
+    /// Applies a witness update message to a stored VC on the holder side: the revoked claims are dropped and the
+    /// witnesses of the remaining ones are updated to the new accumulator value with the public update information.
     ///
     /// # Arguments
-    /// * `jwt` - Verifiable Presentation encoded as a jwt.
-    /// * `issuer_public_key` - Issuer's public key to verify the signature of the list of hashes.
-    /// * `holder_public_key` - Holder's public key to verify the proof of possession.
-    /// * `params` - Additional parameters needed for correct handling of the accumulator value.
+    /// * `vc` - Verifiable Credential, holding the accumulator value superseded by the message.
+    /// * `update_jwt` - Witness update message signed by the issuer.
+    /// * `signing_public_key` - Public key of the issuer signing the message.
     ///
     /// # Returns
-    /// This function returns a string containing an error in case of failure.
-    pub fn verify_vp(jwt: &String, issuer_public_key: &PublicKey<Bn254>, holder_public_key: &impl AsRef<[u8]>, params: &SetupParams<Bn254>) -> Result<(), String> {
-
-        let vp = Self::decode_and_verify_jwt(jwt, holder_public_key)?;
-        let witness_value_container: Map<String, Value> = Self::get_and_decode(&vp, WVC.to_string())?;
-        let serialized_accumulator: String = Self::get_and_decode(&vp, ACCUMULATOR.to_string())?;
-        let accumulator: PositiveAccumulator<Bn254> = Self::deserialize(&serialized_accumulator)?;
-        
-        Self::verify_witness_value_container(&witness_value_container, &accumulator, issuer_public_key, params)?;
+    /// This function returns the updated VC both in the form of a Map and in the form of an unsigned JWT.
+    pub fn apply_witness_update(vc: &Map<String, Value>, update_jwt: &str, signing_public_key: &impl AsRef<[u8]>) -> Result<(Map<String, Value>, String), String> {
 
-        Ok(())
-    }
+        let message: Map<String, Value> = Self::decode_and_verify_jwt(update_jwt, signing_public_key, &Self::VERIFIER_CONFIG)?;
+        let serialized_accumulator: String = Self::get_and_decode(vc, ACCUMULATOR.to_string())?;
+        if Self::update_field(&message, ACCUMULATOR)? != &serialized_accumulator {
+            return Err("Witness update message does not apply to the accumulator value of the VC.".to_string());
+        }
 
-}
+        let updated_accumulator: PositiveAccumulator<Bn254> = Self::deserialize(Self::update_field(&message, UPDATED_ACCUMULATOR)?)?;
+        let removals: Vec<Fr> = Self::deserialize(Self::update_field(&message, REMOVALS)?)?;
+        let omega: Omega<G1Affine> = Omega(Self::deserialize(Self::update_field(&message, OMEGA)?)?);
+        let context: Option<String> = Self::element_context(vc, None)?;
 
+        let witness_value_container: Map<String, Value> = Self::get_and_decode(vc, WVC.to_string())?;
+        let mut updated_witness_value_container: Map<String, Value> = Map::new();
+        for (claim_key, array_value) in witness_value_container {
+            let (witness, claim_value) = Self::split_witness_and_value(&array_value)?;
+            let element: Fr = Self::convert_claim_to_scalar(&claim_key, &claim_value, context.as_deref());
+            if removals.contains(&element) {
+                continue;
+            }
 
+            record(Operation::Exponentiation, omega.0.len() as u64);
+            let updated_witness: MembershipWitness<G1Affine> = match witness.update_using_public_info_after_batch_updates(&[], &removals, &omega, &element) {
+                Ok(updated_witness) => { updated_witness }
+                Err(err) => { return Err(format!("Error in updating the witness of {claim_key}: [{:?}]", err)) }
+            };
+            updated_witness_value_container.insert(claim_key, Value::Array(vec![Value::String(Self::serialize(&updated_witness)?), claim_value]));
+        }
 
-#[derive(Clone, Debug)]
-pub struct InMemoryState<T: Clone> {
-    pub db: HashSet<T>,
-}
+        let mut updated_vc: Map<String, Value> = vc.clone();
+        Self::serialize_and_insert(&mut updated_vc, ACCUMULATOR.to_string(), &Self::serialize(&updated_accumulator)?)?;
+        Self::serialize_and_insert(&mut updated_vc, WVC.to_string(), &updated_witness_value_container)?;
+        let jwt = Self::encode_jwt(&updated_vc)?;
 
-impl<T: Clone> InMemoryState<T> {
-    pub fn new() -> Self {
-        let db = HashSet::<T>::new();
-        Self { db }
+        Ok((updated_vc, jwt))
     }
-}
 
-impl<T: Clone + Hash + Eq + Sized> State<T> for InMemoryState<T> {
-    fn add(&mut self, element: T) {
-        self.db.insert(element);
-    }
 
-    fn remove(&mut self, element: &T) {
-        self.db.remove(element);
+    /// Reads a string field of a witness update message.
+    fn update_field<'a>(message: &'a Map<String, Value>, field: &str) -> Result<&'a String, String> {
+        match message.get(field) {
+            Some(Value::String(value)) => { Ok(value) }
+            _ => { Err(format!("Witness update message does not contain {field}.")) }
+        }
     }
 
-    fn has(&self, element: &T) -> bool {
-        self.db.get(element).is_some()
+
+    /// Given a VC, and a set of disclosures, create a Verifiable Presentation accordingly.
+    ///
+    /// # Arguments
+    /// * `vc` - Verifiable Credential.
+    /// * `disclosures` - List of strings containing the names of the claims that are to be disclosed.
+    /// * `holder_binding` - Holder binding signing the VP as proof of possession.
+    ///
+    /// # Returns
+    /// This function returns the VP both in form of a Map and in form of a signed JWT.
+    pub fn issue_vp(vc: &Map<String, Value>, disclosures: &[String], holder_binding: &(impl HolderBinding + ?Sized)) -> Result<(Map<String, Value>, String), String> {
+        Self::issue_vp_for_audience(vc, disclosures, None, None, holder_binding)
+    }
+
+
+    /// Same as `issue_vp`, but binding the presentation to an optional audience, as required to disclose sensitive claims.
+    ///
+    /// # Arguments
+    /// * `vc` - Verifiable Credential.
+    /// * `disclosures` - List of strings containing the names of the claims that are to be disclosed.
+    /// * `audience` - Optional intended audience of the presentation.
+    /// * `validity` - Optional validity window of the presentation.
+    /// * `holder_binding` - Holder binding signing the VP as proof of possession.
+    ///
+    /// # Returns
+    /// This function returns the VP both in form of a Map and in form of a signed JWT.
+    pub fn issue_vp_for_audience(vc: &Map<String, Value>, disclosures: &[String], audience: Option<String>, validity: Option<PresentationValidity>, holder_binding: &(impl HolderBinding + ?Sized)) -> Result<(Map<String, Value>, String), String> {
+        let vp: Map<String, Value> = Self::derive_presentation_credential(vc, disclosures)?;
+        Self::encode_and_sign_presentation_for_audience(vp, audience, validity, holder_binding)
+    }
+
+
+    /// Same as `issue_vp_for_audience`, but signing the VP with an ephemeral key certified by the long-term key of the
+    /// holder, so that the key signing the presentations can be rotated at every presentation.
+    ///
+    /// # Arguments
+    /// * `vc` - Verifiable Credential.
+    /// * `disclosures` - List of strings containing the names of the claims that are to be disclosed.
+    /// * `audience` - Optional intended audience of the presentation.
+    /// * `validity` - Optional validity window of the presentation.
+    /// * `ephemeral_key` - Ephemeral key signing the VP.
+    /// * `certificate` - Certificate of the ephemeral key, signed by the key the VC is bound to.
+    ///
+    /// # Returns
+    /// This function returns the VP both in form of a Map and in form of a signed JWT.
+    pub fn issue_vp_with_ephemeral_key(vc: &Map<String, Value>, disclosures: &[String], audience: Option<String>, validity: Option<PresentationValidity>, ephemeral_key: &EphemeralKey, certificate: &str) -> Result<(Map<String, Value>, String), String> {
+        let vp: Map<String, Value> = Self::derive_presentation_credential(vc, disclosures)?;
+        Self::encode_and_sign_presentation_with_ephemeral_key(vp, audience, validity, ephemeral_key, certificate)
+    }
+
+
+    /// Derives the credential to be presented from a VC, keeping the witnesses of the disclosed claims only.
+    ///
+    /// # Arguments
+    /// * `vc` - Verifiable Credential.
+    /// * `disclosures` - List of strings containing the names of the claims that are to be disclosed.
+    ///
+    /// # Returns
+    /// This function returns the derived credential or a string containing an error in case of failure.
+    fn derive_presentation_credential(vc: &Map<String, Value>, disclosures: &[String]) -> Result<Map<String, Value>, String> {
+
+        let mut vp: Map<String, Value> = vc.clone();
+        let disclosures: &Vec<String> = &Self::resolve_disclosures(vc, disclosures)?;
+
+        let witness_value_container: Map<String, Value> = Self::get_and_decode(&mut vp, WVC.to_string())?;
+        let mut new_witness_value_container: Map<String, Value> = Map::new();
+
+        for disclosure in disclosures {
+            if let Some(value) = witness_value_container.get(disclosure) {
+                new_witness_value_container.insert(disclosure.clone(), value.clone());
+            }
+        }
+
+        Self::serialize_and_insert(&mut vp, WVC.to_string(), &new_witness_value_container)?;
+        Ok(vp)
+    }
+
+
+    /// Given a VC, and several sets of disclosures, create one Verifiable Presentation per set decoding the VC only once.
+    ///
+    /// # Arguments
+    /// * `vc` - Verifiable Credential.
+    /// * `disclosure_sets` - Lists of strings containing the names of the claims that are to be disclosed in each VP.
+    /// * `holder_binding` - Holder binding signing the VP as proof of possession.
+    ///
+    /// # Returns
+    /// This function returns the VPs both in form of a Map and in form of a signed JWT.
+    pub fn issue_vp_batch(vc: &Map<String, Value>, disclosure_sets: &[Vec<String>], holder_binding: &(impl HolderBinding + ?Sized)) -> Result<PresentationBatch, String> {
+
+        let witness_value_container: Map<String, Value> = Self::get_and_decode(vc, WVC.to_string())?;
+        let mut vps: Vec<(Map<String, Value>, String)> = Vec::with_capacity(disclosure_sets.len());
+
+        for disclosures in disclosure_sets {
+            let mut vp: Map<String, Value> = vc.clone();
+            let disclosures: &Vec<String> = &Self::resolve_disclosures(vc, disclosures)?;
+            let new_witness_value_container: Map<String, Value> = disclosures
+                .iter()
+                .filter_map(|field| witness_value_container.get(field).map(|value| (field.clone(), value.clone())))
+                .collect();
+
+            Self::serialize_and_insert(&mut vp, WVC.to_string(), &new_witness_value_container)?;
+            vps.push(Self::encode_and_sign_presentation(vp, holder_binding)?);
+        }
+
+        Ok(vps)
+    }
+
+
+    /// Extracts the accumulator value and, depending on the kind of VC or VP, the membership witnesses, the membership
+    /// proofs or the aggregated witness, all as compressed points encoded in base64url.
+    ///
+    /// # Arguments
+    /// * `vp` - Verifiable Credential or Verifiable Presentation.
+    ///
+    /// # Returns
+    /// This function returns a map containing the artifacts or a string containing an error in case of failure.
+    pub fn proof_artifacts(vp: &Map<String, Value>) -> Result<Map<String, Value>, String> {
+
+        let vp: &Map<String, Value> = Self::credential_or_presentation(vp)?;
+
+        let serialized_accumulator: String = Self::get_and_decode(vp, ACCUMULATOR.to_string())?;
+        let accumulator: PositiveAccumulator<Bn254> = Self::deserialize(&serialized_accumulator)?;
+        let context: Option<String> = Self::element_context(vp, None)?;
+
+        let mut artifacts: Map<String, Value> = Map::new();
+        artifacts.insert("accumulator".to_string(), Value::String(Self::serialize_with_encoding(accumulator.value(), Encoding::Base64UrlNoPad)?));
+
+        if vp.contains_key(WVC) {
+            let witness_value_container: Map<String, Value> = Self::get_and_decode(vp, WVC.to_string())?;
+            let mut witnesses: Map<String, Value> = Map::new();
+            let mut elements: Map<String, Value> = Map::new();
+            for (field, array_value) in witness_value_container {
+                let (witness, claim_value) = Self::split_witness_and_value(&array_value)?;
+                let element: Fr = Self::convert_claim_to_scalar(&field, &claim_value, context.as_deref());
+                witnesses.insert(field.clone(), Value::String(Self::serialize_with_encoding(&witness, Encoding::Base64UrlNoPad)?));
+                elements.insert(field, Value::String(Self::serialize_with_encoding(&element, Encoding::Base64UrlNoPad)?));
+            }
+            artifacts.insert("witnesses".to_string(), Value::Object(witnesses));
+            artifacts.insert("elements".to_string(), Value::Object(elements));
+        }
+
+        if vp.contains_key(PVC) {
+            let proof_value_container: Map<String, Value> = Self::get_and_decode(vp, PVC.to_string())?;
+            let mut proofs: Map<String, Value> = Map::new();
+            for (field, array_value) in proof_value_container {
+                let proof: MembershipProof<Bn254> = match array_value.get(0) {
+                    Some(Value::String(proof_string)) => { Self::deserialize(proof_string)? }
+                    _ => { return Err(format!("Malformed entry for {field} in Proof value container.")) }
+                };
+                proofs.insert(field, Value::String(Self::serialize_with_encoding(&proof, Encoding::Base64UrlNoPad)?));
+            }
+            artifacts.insert("membership_proofs".to_string(), Value::Object(proofs));
+        }
+
+        if vp.contains_key(AGGREGATED_WITNESS) {
+            let serialized_witness: String = Self::get_and_decode(vp, AGGREGATED_WITNESS.to_string())?;
+            let aggregated_witness: G1Affine = Self::deserialize(&serialized_witness)?;
+            artifacts.insert("aggregated_witness".to_string(), Value::String(Self::serialize_with_encoding(&aggregated_witness, Encoding::Base64UrlNoPad)?));
+        }
+        artifacts.insert(SIGNED_FIELDS.to_string(), Value::Object(signed_fields(vp)));
+
+        Ok(artifacts)
+    }
+
+
+    /// Extracts the proof of every disclosed claim of a VC or VP: its accumulated element and its membership witness,
+    /// or its membership proof for multi-show presentations. The accumulator value, the blinding factor and the
+    /// aggregated witness of aggregated presentations are shared by all the claims.
+    ///
+    /// # Arguments
+    /// * `vp` - Verifiable Credential or Verifiable Presentation.
+    ///
+    /// # Returns
+    /// This function returns the `shared` and `claims` objects of the audit document or a string containing an error in
+    /// case of failure.
+    pub fn claim_proofs(vp: &Map<String, Value>) -> Result<Map<String, Value>, String> {
+
+        let vp: &Map<String, Value> = Self::credential_or_presentation(vp)?;
+
+        let serialized_accumulator: String = Self::get_and_decode(vp, ACCUMULATOR.to_string())?;
+        let blinding: Option<String> = Self::blinding_factor(vp, None)?;
+        let context: Option<String> = Self::element_context(vp, None)?;
+
+        let mut shared: Map<String, Value> = Map::new();
+        shared.insert(ACCUMULATOR.to_string(), Value::String(serialized_accumulator));
+        if let Some(blinding) = &blinding {
+            shared.insert(BLINDING.to_string(), Value::String(blinding.clone()));
+        }
+        shared.insert(SIGNED_FIELDS.to_string(), Value::Object(signed_fields(vp)));
+
+        // Every container maps a claim to its value, preceded by its witness, or by its membership proof and the
+        // blinding of its element, if it has them.
+        let (container, proof_fields): (Map<String, Value>, &[&str]) = if vp.contains_key(WVC) {
+            (Self::get_and_decode(vp, WVC.to_string())?, &["witness"])
+        } else if vp.contains_key(PVC) {
+            (Self::get_and_decode(vp, PVC.to_string())?, &["membership_proof", "element_blinding"])
+        } else {
+            let serialized_witness: String = Self::get_and_decode(vp, AGGREGATED_WITNESS.to_string())?;
+            shared.insert(AGGREGATED_WITNESS.to_string(), Value::String(serialized_witness));
+            (Self::get_and_decode(vp, DVC.to_string())?, &[])
+        };
+
+        let mut claims: Map<String, Value> = Map::new();
+        for (field, entry) in container {
+            let mut proof: Map<String, Value> = Map::new();
+            let value: Value = if proof_fields.is_empty() {
+                entry
+            } else {
+                match entry.as_array() {
+                    Some(parts) if parts.len() == proof_fields.len() + 1 => {
+                        for (proof_field, proof_value) in proof_fields.iter().zip(parts) {
+                            proof.insert(proof_field.to_string(), proof_value.clone());
+                        }
+                        parts[proof_fields.len()].clone()
+                    }
+                    _ => { return Err(format!("Malformed entry for {field} in the value container.")) }
+                }
+            };
+            let element: Fr = Self::convert_claim_to_scalar(&field, &value, context.as_deref());
+            proof.insert("element".to_string(), Value::String(Self::serialize(&element)?));
+            proof.insert(VALUE.to_string(), value);
+            claims.insert(field, Value::Object(proof));
+        }
+
+        Ok(claim_proofs_document(shared, claims))
+    }
+
+
+    /// Re-verifies the claims of an audit document carrying membership witnesses independently of their presentation:
+    /// the element of every claim is recomputed from its name, value and the blinding factor, and its witness is checked
+    /// against the accumulator value with the public key of the issuer. The membership proofs of multi-show
+    /// presentations and aggregated witnesses are not covered, as they are checked against all the disclosed claims.
+    ///
+    /// # Arguments
+    /// * `document` - Audit document, or audit document of a single claim.
+    /// * `issuer_public_key` - Issuer's public key of the accumulator.
+    /// * `params` - Additional parameters needed for correct handling of the accumulator value.
+    ///
+    /// # Returns
+    /// This function returns a string containing an error in case of failure.
+    pub fn verify_claim_proofs(document: &Map<String, Value>, issuer_public_key: &PublicKey<Bn254>, params: &SetupParams<Bn254>) -> Result<(), String> {
+
+        let (shared, claims) = split_claim_proofs(document)?;
+        let accumulator: PositiveAccumulator<Bn254> = match shared.get(ACCUMULATOR) {
+            Some(Value::String(serialized_accumulator)) => { Self::deserialize(serialized_accumulator)? }
+            _ => { return Err("Audit document does not contain the accumulator value.".to_string()) }
+        };
+        let blinding: Option<&str> = shared.get(BLINDING).and_then(Value::as_str);
+        let context: Option<String> = Self::combine_context(blinding, Self::serialize_signed_fields(&shared_signed_fields(shared)?));
+
+        check_all(claims.iter().map(|(field, proof)| {
+            match (proof.get("witness"), proof.get(VALUE)) {
+                (Some(witness), Some(value)) => {
+                    let array_value: Value = Value::Array(vec![witness.clone(), value.clone()]);
+                    Self::verify_witness(field, &array_value, &accumulator, context.as_deref(), issuer_public_key, params, None)
+                }
+                _ => { Err(format!("Proof of claim {field} does not contain its witness and value.")) }
+            }
+        }))
+    }
+
+
+    /// Given a VP, verify it using all the necessary data.
+    ///
+    /// # Arguments
+    /// * `jwt` - Verifiable Presentation encoded as a jwt.
+    /// * `issuer_public_key` - Issuer's public key to verify the signature of the list of hashes.
+    /// * `holder_binding` - Holder binding verifying the proof of possession.
+    /// * `params` - Additional parameters needed for correct handling of the accumulator value.
+    ///
+    /// # Returns
+    /// This function returns a string containing an error in case of failure.
+    pub fn verify_vp(jwt: &str, issuer_public_key: &PublicKey<Bn254>, holder_binding: &(impl HolderBinding + ?Sized), params: &SetupParams<Bn254>) -> Result<(), String> {
+        Self::verify_vp_with_cache(jwt, issuer_public_key, holder_binding, params, None, None, &Self::VERIFIER_CONFIG)
+    }
+
+
+    /// Given a VP, verify it skipping the claims that were already verified against the same accumulator value.
+    ///
+    /// # Arguments
+    /// * `jwt` - Verifiable Presentation encoded as a jwt.
+    /// * `issuer_public_key` - Issuer's public key to verify the signature of the list of hashes.
+    /// * `holder_binding` - Holder binding verifying the proof of possession.
+    /// * `params` - Additional parameters needed for correct handling of the accumulator value.
+    /// * `cache` - Optional cache keyed by (accumulator value, claim digest).
+    /// * `prepared_key` - Optional precomputed pairing inputs of the issuer's public key, see [`PreparedVerificationKey`].
+    /// * `config` - Limits enforced on the VP.
+    ///
+    /// # Returns
+    /// This function returns a string containing an error in case of failure.
+    pub fn verify_vp_with_cache(jwt: &str, issuer_public_key: &PublicKey<Bn254>, holder_binding: &(impl HolderBinding + ?Sized), params: &SetupParams<Bn254>, cache: Option<&Arc<VerificationCache>>, prepared_key: Option<&Arc<PreparedVerificationKey>>, config: &VerifierConfig) -> Result<(), String> {
+
+        let vp = Self::decode_and_verify_presentation(jwt, holder_binding, config)?;
+        Self::verify_presentation_credential(&vp, issuer_public_key, params, cache, prepared_key, config)
+    }
+
+
+    /// Given a VP signed with a certified ephemeral key, verify the certificate against the long-term key of the holder,
+    /// the VP against the certified key, and the disclosed claims.
+    ///
+    /// # Arguments
+    /// * `jwt` - Verifiable Presentation encoded as a jwt.
+    /// * `issuer_public_key` - Issuer's public key to verify the signature of the list of hashes.
+    /// * `holder_public_key` - Long-term public key of the holder, which the VC is bound to.
+    /// * `params` - Additional parameters needed for correct handling of the accumulator value.
+    /// * `config` - Limits enforced on the VP.
+    ///
+    /// # Returns
+    /// This function returns a string containing an error in case of failure.
+    pub fn verify_vp_with_ephemeral_key(jwt: &str, issuer_public_key: &PublicKey<Bn254>, holder_public_key: &impl AsRef<[u8]>, params: &SetupParams<Bn254>, config: &VerifierConfig) -> Result<(), String> {
+        let vp = Self::decode_and_verify_presentation_with_ephemeral_key(jwt, holder_public_key, config)?;
+        Self::verify_presentation_credential(&vp, issuer_public_key, params, None, None, config)
+    }
+
+
+    /// Verifies the witnesses of the claims disclosed by the credential of a VP whose signature was already verified.
+    fn verify_presentation_credential(vp: &Map<String, Value>, issuer_public_key: &PublicKey<Bn254>, params: &SetupParams<Bn254>, cache: Option<&Arc<VerificationCache>>, prepared_key: Option<&Arc<PreparedVerificationKey>>, config: &VerifierConfig) -> Result<(), String> {
+        let witness_value_container: Map<String, Value> = Self::get_and_decode_with_config(vp, WVC.to_string(), config)?;
+        Self::check_disclosed_claims_len(witness_value_container.len())?;
+        Self::check_witness_value_container_limits(&witness_value_container, config)?;
+        let serialized_accumulator: String = Self::get_and_decode_with_config(vp, ACCUMULATOR.to_string(), config)?;
+        let accumulator: PositiveAccumulator<Bn254> = Self::deserialize(&serialized_accumulator)?;
+        let context: Option<String> = Self::element_context(vp, Some(config))?;
+
+        Self::verify_witness_value_container(&witness_value_container, &accumulator, context.as_deref(), issuer_public_key, params, cache, prepared_key)?;
+
+        Ok(())
+    }
+
+
+
+    /// Given a VC, and a set of disclosures, create a multi-show Verifiable Presentation.
+    ///
+    /// Instead of disclosing the membership witnesses, which are fixed at issuance and therefore identical in every
+    /// presentation, the holder proves knowledge of each witness in zero-knowledge with fresh randomness, so two VPs
+    /// derived from the same VC do not share any witness. The blinding of each element is revealed together with the
+    /// claim value, as the value is disclosed anyway. The accumulator value is still disclosed as is.
+    ///
+    /// # Arguments
+    /// * `vc` - Verifiable Credential.
+    /// * `disclosures` - List of strings containing the names of the claims that are to be disclosed.
+    /// * `validity` - Optional validity window of the presentation.
+    /// * `holder_binding` - Holder binding signing the VP as proof of possession.
+    /// * `issuer_public_key` - Issuer's public key of the accumulator.
+    /// * `params` - Additional parameters needed for correct handling of the accumulator value.
+    /// * `proving_key` - Public proving key for the membership proofs.
+    ///
+    /// # Returns
+    /// This function returns the VP both in form of a Map and in form of a signed JWT.
+    pub fn issue_multi_show_vp(vc: &Map<String, Value>, disclosures: &[String], validity: Option<PresentationValidity>, holder_binding: &(impl HolderBinding + ?Sized), issuer_public_key: &PublicKey<Bn254>, params: &SetupParams<Bn254>, proving_key: &MembershipProvingKey<G1Affine>) -> Result<(Map<String, Value>, String), String> {
+
+        let mut vp: Map<String, Value> = vc.clone();
+        let disclosures: &Vec<String> = &Self::resolve_disclosures(vc, disclosures)?;
+        let mut rng = ark_rng();
+
+        let witness_value_container: Map<String, Value> = Self::get_and_decode(&vp, WVC.to_string())?;
+        let serialized_accumulator: String = Self::get_and_decode(&vp, ACCUMULATOR.to_string())?;
+        let accumulator: PositiveAccumulator<Bn254> = Self::deserialize(&serialized_accumulator)?;
+        let context: Option<String> = Self::element_context(&vp, None)?;
+
+        let mut protocols: Vec<(String, Value, Fr, MembershipProofProtocol<Bn254>)> = vec![];
+        let mut contributions: Vec<u8> = vec![];
+
+        let disclosed: HashSet<&String> = disclosures.iter().collect();
+        for (field, array_value) in witness_value_container {
+            if !disclosed.contains(&field) {
+                continue;
+            }
+
+            let (witness, claim_value) = Self::split_witness_and_value(&array_value)?;
+            let element: Fr = Self::convert_claim_to_scalar(&field, &claim_value, context.as_deref());
+            let element_blinding: Fr = Fr::rand(&mut rng);
+            let protocol = MembershipProofProtocol::init(&mut rng, element, Some(element_blinding), &witness, issuer_public_key, params, proving_key);
+
+            match protocol.challenge_contribution(accumulator.value(), issuer_public_key, params, proving_key, &mut contributions) {
+                Ok(_) => {}
+                Err(err) => { return Err(format!("Error in computing challenge contribution: [{:?}]", err)) }
+            };
+            protocols.push((field, claim_value, element_blinding, protocol));
+        }
+
+        let challenge: Fr = Self::membership_challenge(&contributions);
+        let mut proof_value_container: Map<String, Value> = Map::new();
+
+        for (field, claim_value, element_blinding, protocol) in protocols {
+            let proof: MembershipProof<Bn254> = match protocol.gen_proof(&challenge) {
+                Ok(proof) => { proof }
+                Err(err) => { return Err(format!("Error in generating membership proof: [{:?}]", err)) }
+            };
+            proof_value_container.insert(field, Value::Array(vec![
+                Value::String(Self::serialize(&proof)?),
+                Value::String(Self::serialize(&element_blinding)?),
+                claim_value,
+            ]));
+        }
+
+        vp.remove(WVC);
+        Self::serialize_and_insert(&mut vp, PVC.to_string(), &proof_value_container)?;
+        Self::encode_and_sign_presentation_for_audience(vp, None, validity, holder_binding)
+    }
+
+
+    /// Given a multi-show VP, verify it using all the necessary data.
+    ///
+    /// # Arguments
+    /// * `jwt` - Verifiable Presentation encoded as a jwt.
+    /// * `issuer_public_key` - Issuer's public key of the accumulator.
+    /// * `holder_binding` - Holder binding verifying the proof of possession.
+    /// * `params` - Additional parameters needed for correct handling of the accumulator value.
+    /// * `proving_key` - Public proving key for the membership proofs.
+    /// * `config` - Limits enforced on the VP.
+    ///
+    /// # Returns
+    /// This function returns a string containing an error in case of failure.
+    pub fn verify_multi_show_vp(jwt: &str, issuer_public_key: &PublicKey<Bn254>, holder_binding: &(impl HolderBinding + ?Sized), params: &SetupParams<Bn254>, proving_key: &MembershipProvingKey<G1Affine>, config: &VerifierConfig) -> Result<(), String> {
+
+        let vp = Self::decode_and_verify_presentation(jwt, holder_binding, config)?;
+        let proof_value_container: Map<String, Value> = Self::get_and_decode_with_config(&vp, PVC.to_string(), config)?;
+        let serialized_accumulator: String = Self::get_and_decode_with_config(&vp, ACCUMULATOR.to_string(), config)?;
+        let accumulator: PositiveAccumulator<Bn254> = Self::deserialize(&serialized_accumulator)?;
+        let context: Option<String> = Self::element_context(&vp, Some(config))?;
+
+        config.check_claims_len(proof_value_container.len())?;
+        Self::check_disclosed_claims_len(proof_value_container.len())?;
+        let mut proofs: Vec<(String, Fr, Fr, MembershipProof<Bn254>)> = vec![];
+        let mut contributions: Vec<u8> = vec![];
+
+        for (field, array_value) in proof_value_container {
+            let (proof, element_blinding, claim_value): (MembershipProof<Bn254>, Fr, Value) = match array_value {
+                Value::Array(array) => match (array.first(), array.get(1), array.get(2)) {
+                    (Some(Value::String(proof_string)), Some(Value::String(blinding_string)), Some(value)) => {
+                        config.check_witness_length(proof_string)?;
+                        (Self::deserialize(proof_string)?, Self::deserialize(blinding_string)?, value.clone())
+                    }
+                    _ => { return Err(format!("Malformed entry for {field} in Proof value container.")) }
+                },
+                _ => { return Err("Error, array field in Proof value container is not an array".to_string()) }
+            };
+
+            match proof.challenge_contribution(accumulator.value(), issuer_public_key, params, proving_key, &mut contributions) {
+                Ok(_) => {}
+                Err(err) => { return Err(format!("Error in computing challenge contribution: [{:?}]", err)) }
+            };
+            let element: Fr = Self::convert_claim_to_scalar(&field, &claim_value, context.as_deref());
+            proofs.push((field, element, element_blinding, proof));
+        }
+
+        let challenge: Fr = Self::membership_challenge(&contributions);
+
+        check_all(proofs.into_iter().map(|(field, element, element_blinding, proof)| {
+            // The response for the element binds the proof to the disclosed claim value.
+            if proof.get_schnorr_response_for_element() != Some(&(element_blinding + challenge * element)) {
+                return Err(format!("Membership proof for {field} does not match the disclosed value."));
+            }
+            match proof.verify(accumulator.value(), &challenge, issuer_public_key.clone(), params.clone(), proving_key) {
+                Ok(_) => { Ok(()) }
+                Err(err) => { Err(format!("Membership proof verification failed for {field}: [{:?}]", err)) }
+            }
+        }))
+    }
+
+
+
+    /// Given a VC, and a set of disclosures, create a VP carrying a single aggregated witness for all the disclosed claims.
+    ///
+    /// # Arguments
+    /// * `vc` - Verifiable Credential.
+    /// * `disclosures` - List of strings containing the names of the claims that are to be disclosed.
+    /// * `validity` - Optional validity window of the presentation.
+    /// * `holder_binding` - Holder binding signing the VP as proof of possession.
+    ///
+    /// # Returns
+    /// This function returns the VP both in form of a Map and in form of a signed JWT.
+    pub fn issue_aggregated_vp(vc: &Map<String, Value>, disclosures: &[String], validity: Option<PresentationValidity>, holder_binding: &(impl HolderBinding + ?Sized)) -> Result<(Map<String, Value>, String), String> {
+
+        let mut vp: Map<String, Value> = vc.clone();
+        let disclosures: &Vec<String> = &Self::resolve_disclosures(vc, disclosures)?;
+
+        let witness_value_container: Map<String, Value> = Self::get_and_decode(&vp, WVC.to_string())?;
+        let serialized_accumulator: String = Self::get_and_decode(&vp, ACCUMULATOR.to_string())?;
+        let accumulator: PositiveAccumulator<Bn254> = Self::deserialize(&serialized_accumulator)?;
+        let context: Option<String> = Self::element_context(&vp, None)?;
+
+        let mut disclosed_value_container: Map<String, Value> = Map::new();
+        let mut elements: Vec<Fr> = vec![];
+        let mut witnesses: Vec<MembershipWitness<G1Affine>> = vec![];
+
+        let disclosed: HashSet<&String> = disclosures.iter().collect();
+        for (field, array_value) in witness_value_container {
+            if disclosed.contains(&field) {
+                let (witness, claim_value) = Self::split_witness_and_value(&array_value)?;
+                elements.push(Self::convert_claim_to_scalar(&field, &claim_value, context.as_deref()));
+                witnesses.push(witness);
+                disclosed_value_container.insert(field, claim_value);
+            }
+        }
+
+        let aggregated_witness: G1Affine = Self::aggregate_witnesses(&elements, &witnesses, &accumulator)?;
+
+        vp.remove(WVC);
+        Self::serialize_and_insert(&mut vp, DVC.to_string(), &disclosed_value_container)?;
+        Self::serialize_and_insert(&mut vp, AGGREGATED_WITNESS.to_string(), &Self::serialize(&aggregated_witness)?)?;
+        Self::encode_and_sign_presentation_for_audience(vp, None, validity, holder_binding)
+    }
+
+
+    /// Given a VP with an aggregated witness, verify it using all the necessary data.
+    ///
+    /// # Arguments
+    /// * `jwt` - Verifiable Presentation encoded as a jwt.
+    /// * `issuer_public_key` - Issuer's public key of the accumulator.
+    /// * `holder_binding` - Holder binding verifying the proof of possession.
+    /// * `params` - Additional parameters needed for correct handling of the accumulator value.
+    /// * `aggregation_key` - Powers of the accumulator secret key in G2, as published by the issuer.
+    /// * `config` - Limits enforced on the VP.
+    ///
+    /// # Returns
+    /// This function returns a string containing an error in case of failure.
+    pub fn verify_aggregated_vp(jwt: &str, issuer_public_key: &PublicKey<Bn254>, holder_binding: &(impl HolderBinding + ?Sized), params: &SetupParams<Bn254>, aggregation_key: &Vec<G2Affine>, config: &VerifierConfig) -> Result<(), String> {
+
+        let vp = Self::decode_and_verify_presentation(jwt, holder_binding, config)?;
+        let disclosed_value_container: Map<String, Value> = Self::get_and_decode_with_config(&vp, DVC.to_string(), config)?;
+        let serialized_witness: String = Self::get_and_decode_with_config(&vp, AGGREGATED_WITNESS.to_string(), config)?;
+        config.check_claims_len(disclosed_value_container.len())?;
+        Self::check_disclosed_claims_len(disclosed_value_container.len())?;
+        config.check_witness_length(&serialized_witness)?;
+        let aggregated_witness: G1Affine = Self::deserialize(&serialized_witness)?;
+        let serialized_accumulator: String = Self::get_and_decode_with_config(&vp, ACCUMULATOR.to_string(), config)?;
+        let accumulator: PositiveAccumulator<Bn254> = Self::deserialize(&serialized_accumulator)?;
+        let context: Option<String> = Self::element_context(&vp, Some(config))?;
+
+        if disclosed_value_container.len() > Self::aggregation_capacity(aggregation_key) {
+            return Err(format!("Aggregation key supports at most {} claims.", Self::aggregation_capacity(aggregation_key)));
+        }
+        if aggregation_key.get(1) != Some(&issuer_public_key.0) {
+            return Err("Aggregation key does not match the issuer public key.".to_string());
+        }
+
+        // Coefficients of prod(y_i + X), lowest degree first.
+        let mut coefficients: Vec<Fr> = vec![Fr::from(1u64)];
+        for (field, claim_value) in &disclosed_value_container {
+            let element: Fr = Self::convert_claim_to_scalar(field, claim_value, context.as_deref());
+            let mut next: Vec<Fr> = vec![Fr::zero(); coefficients.len() + 1];
+            for (k, coefficient) in coefficients.iter().enumerate() {
+                next[k] += *coefficient * element;
+                next[k + 1] += *coefficient;
+            }
+            coefficients = next;
+        }
+
+        let mut exponent = G2Projective::zero();
+        for (coefficient, power) in coefficients.iter().zip(aggregation_key.iter()) {
+            exponent += *power * *coefficient;
+        }
+
+        record(Operation::Exponentiation, coefficients.len() as u64);
+        record(Operation::Pairing, 2);
+        if Bn254::pairing(aggregated_witness, exponent.into_affine()) == Bn254::pairing(*accumulator.value(), params.P_tilde) {
+            Ok(())
+        } else {
+            Err("Aggregated witness verification failed.".to_string())
+        }
+    }
+
+}
+
+
+
+#[derive(Clone, Debug)]
+pub struct InMemoryState<T: Clone> {
+    pub db: HashSet<T>,
+}
+
+impl<T: Clone> InMemoryState<T> {
+    pub fn new() -> Self {
+        let db = HashSet::<T>::new();
+        Self { db }
+    }
+}
+
+impl<T: Clone + Hash + Eq + Sized> State<T> for InMemoryState<T> {
+    fn add(&mut self, element: T) {
+        self.db.insert(element);
+    }
+
+    fn remove(&mut self, element: &T) {
+        self.db.remove(element);
+    }
+
+    fn has(&self, element: &T) -> bool {
+        self.db.get(element).is_some()
     }
 
     fn size(&self) -> u64 {
@@ -334,10 +1445,15 @@ impl<'a, T: Clone + Hash + Eq + Sized + 'a> UniversalAccumulatorState<'a, T> for
 
 #[cfg(test)]
 mod tests {
-    use ark_std::rand::SeedableRng;
+    use log::debug;
     use serde_json::{Map, Value};
 
+    use crate::binary_envelope::{envelope_from_jwt, envelope_length, verify_envelope};
+    use crate::claim_generator::{ClaimGenerator, FlatClaimGenerator};
     use crate::common_data::{CommonData, VC};
+    use crate::deterministic::DeterministicMode;
+    use crate::ephemeral_binding::unverified_claims;
+    use crate::holder_binding::bind_holder_key;
 
     use super::*;
 
@@ -355,7 +1471,7 @@ mod tests {
         };
 
         let raw_vc = &mut raw_vc;
-        let mut rng = StdRng::from_entropy();
+        let mut rng = ark_rng();
         let (holder_public_key, holder_private_key) = CommonData::holder_keys()?;
         let (params, Keypair { secret_key: ref issuer_private_key, public_key: ref issuer_public_key}) = CsdJwtInstance::initialize_params(&mut rng);
 
@@ -365,11 +1481,11 @@ mod tests {
         };
 
         match CsdJwtInstance::verify_vc(&vc, &issuer_public_key, &params) {
-            Ok(_) => { println!("[CSD-JWT] Successfully verified vc.")}
+            Ok(_) => { debug!("[CSD-JWT] Successfully verified vc.")}
             Err(err) => { return Err(format!("[CSD-JWT] Failed to verify vc [{err}]."))}
         };
 
-        let disclosures = vec!["name", "birthdate"].iter().map(|x| x.to_string()).collect();
+        let disclosures: Vec<String> = vec!["name", "birthdate"].iter().map(|x| x.to_string()).collect();
 
         let (_vp, vp_jwt) = match CsdJwtInstance::issue_vp(&vc, &disclosures, &holder_private_key) {
             Ok(vp_jwt) => { vp_jwt }
@@ -377,10 +1493,540 @@ mod tests {
         };
 
         match CsdJwtInstance::verify_vp(&vp_jwt, &issuer_public_key, &holder_public_key, &params) {
-            Ok(_) => { println!("[CSD-JWT] Successfully verified vp.")}
+            Ok(_) => { debug!("[CSD-JWT] Successfully verified vp.")}
             Err(err) => { return Err(format!("[CSD-JWT] Failed to verify vp [{err}].")) }
         };
 
         Ok(())
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn lower_issued_vc() -> Result<(), String> {
+
+        let raw_vc: Map<String, Value> = match serde_json::from_str::<Map<String, Value>>(VC) {
+            Ok(vc) => { vc }
+            Err(err) => { return Err(format!("[CSD-JWT] Failed to parse Raw Verifiable Credential. [{err}]")); }
+        };
+
+        let mut rng = ark_rng();
+        let (params, Keypair { secret_key: ref issuer_private_key, public_key: _ }) = CsdJwtInstance::initialize_params(&mut rng);
+        let (vc, _vc_jwt) = CsdJwtInstance::issue_vc(&raw_vc, &issuer_private_key, &params)?;
+
+        if CsdJwtInstance::lower_vc(&vc)? != CsdJwtInstance::lower(&raw_vc)? {
+            return Err("[CSD-JWT] Issued VC does not lower to the representation of its raw VC.".to_string());
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn deterministic_issuance() -> Result<(), String> {
+
+        let raw_vc: Map<String, Value> = match serde_json::from_str::<Map<String, Value>>(VC) {
+            Ok(vc) => { vc }
+            Err(err) => { return Err(format!("[CSD-JWT] Failed to parse Raw Verifiable Credential. [{err}]")); }
+        };
+
+        let issue = || -> Result<String, String> {
+            let _guard = DeterministicMode::new(7).with_timestamp(1_700_000_000).enable();
+            let mut rng = ark_rng();
+            let (params, Keypair { secret_key: ref issuer_private_key, public_key: _ }) = CsdJwtInstance::initialize_params(&mut rng);
+            let (_vc, vc_jwt) = CsdJwtInstance::issue_vc(&raw_vc, &issuer_private_key, &params)?;
+            Ok(vc_jwt)
+        };
+
+        if issue()? != issue()? {
+            return Err("[CSD-JWT] VCs issued with the same seed differ.".to_string());
+        }
+        if DeterministicMode::is_enabled() {
+            return Err("[CSD-JWT] Deterministic mode is still enabled after dropping its guard.".to_string());
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn multi_show() -> Result<(), String> {
+
+        let mut raw_vc: Map<String, Value> = match serde_json::from_str::<Map<String, Value>>(VC) {
+            Ok(vc) => { vc }
+            Err(err) => { return Err(format!("[CSD-JWT-MS] Failed to parse Raw Verifiable Credential. [{err}]")); }
+        };
+
+        let raw_vc = &mut raw_vc;
+        let mut rng = ark_rng();
+        let (holder_public_key, holder_private_key) = CommonData::holder_keys()?;
+        let (params, Keypair { secret_key: ref issuer_private_key, public_key: ref issuer_public_key}) = CsdJwtInstance::initialize_params(&mut rng);
+        let proving_key = CsdJwtInstance::initialize_proving_key(&mut rng);
+
+        let (vc, _vc_jwt) = CsdJwtInstance::issue_vc(raw_vc, &issuer_private_key, &params)?;
+        let disclosures: Vec<String> = vec!["name", "birthdate"].iter().map(|x| x.to_string()).collect();
+
+        let (first_vp, first_vp_jwt) = CsdJwtInstance::issue_multi_show_vp(&vc, &disclosures, None, &holder_private_key, &issuer_public_key, &params, &proving_key)?;
+        let (second_vp, second_vp_jwt) = CsdJwtInstance::issue_multi_show_vp(&vc, &disclosures, None, &holder_private_key, &issuer_public_key, &params, &proving_key)?;
+
+        if CsdJwtInstance::unwrap_presentation(&first_vp)?.get(PVC) == CsdJwtInstance::unwrap_presentation(&second_vp)?.get(PVC) {
+            return Err("[CSD-JWT-MS] Two presentations share the same membership proofs.".to_string());
+        }
+
+        CsdJwtInstance::verify_multi_show_vp(&first_vp_jwt, &issuer_public_key, &holder_public_key, &params, &proving_key, &CsdJwtInstance::VERIFIER_CONFIG)?;
+        CsdJwtInstance::verify_multi_show_vp(&second_vp_jwt, &issuer_public_key, &holder_public_key, &params, &proving_key, &CsdJwtInstance::VERIFIER_CONFIG)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn claim_proofs() -> Result<(), String> {
+
+        let raw_vc: Map<String, Value> = match serde_json::from_str::<Map<String, Value>>(VC) {
+            Ok(vc) => { vc }
+            Err(err) => { return Err(format!("[CSD-JWT] Failed to parse Raw Verifiable Credential. [{err}]")); }
+        };
+
+        let mut rng = ark_rng();
+        let (_, holder_private_key) = CommonData::holder_keys()?;
+        let (params, Keypair { secret_key: ref issuer_private_key, public_key: ref issuer_public_key}) = CsdJwtInstance::initialize_params(&mut rng);
+
+        let (vc, _vc_jwt) = CsdJwtInstance::issue_vc_with_blinding(&raw_vc, issuer_private_key, &params, true)?;
+        let disclosures: Vec<String> = vec!["name", "birthdate"].iter().map(|x| x.to_string()).collect();
+        let (vp, _vp_jwt) = CsdJwtInstance::issue_vp(&vc, &disclosures, &holder_private_key)?;
+
+        let document = CsdJwtInstance::claim_proofs(&vp)?;
+        let mut proof = crate::audit::claim_proof(&document, "birthdate")?;
+        CsdJwtInstance::verify_claim_proofs(&proof, issuer_public_key, &params)?;
+
+        proof["claims"]["birthdate"]["value"] = Value::String("1642-12-25".to_string());
+        assert!(CsdJwtInstance::verify_claim_proofs(&proof, issuer_public_key, &params).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn capacity() -> Result<(), String> {
+
+        let raw_vc: Map<String, Value> = match serde_json::from_str::<Map<String, Value>>(VC) {
+            Ok(vc) => { vc }
+            Err(err) => { return Err(format!("[CSD-JWT] Failed to parse Raw Verifiable Credential. [{err}]")); }
+        };
+
+        let mut rng = ark_rng();
+        let (params, Keypair { secret_key: ref issuer_private_key, public_key: _ }) = CsdJwtInstance::initialize_params(&mut rng);
+        let aggregation_key = CsdJwtInstance::initialize_aggregation_key(issuer_private_key, &params, 4);
+
+        assert_eq!(CsdJwtInstance::aggregation_capacity(&aggregation_key), 4);
+        assert_eq!(CsdJwtInstance::aggregation_capacity(&[]), 0);
+        CsdJwtInstance::check_capacity(&raw_vc, 13)?;
+        assert!(CsdJwtInstance::check_capacity(&raw_vc, CsdJwtInstance::aggregation_capacity(&aggregation_key)).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn aggregated() -> Result<(), String> {
+
+        let mut raw_vc: Map<String, Value> = match serde_json::from_str::<Map<String, Value>>(VC) {
+            Ok(vc) => { vc }
+            Err(err) => { return Err(format!("[CSD-JWT-AGG] Failed to parse Raw Verifiable Credential. [{err}]")); }
+        };
+
+        let raw_vc = &mut raw_vc;
+        let mut rng = ark_rng();
+        let (holder_public_key, holder_private_key) = CommonData::holder_keys()?;
+        let (params, Keypair { secret_key: ref issuer_private_key, public_key: ref issuer_public_key}) = CsdJwtInstance::initialize_params(&mut rng);
+        let aggregation_key = CsdJwtInstance::initialize_aggregation_key(issuer_private_key, &params, 13);
+
+        let (vc, _vc_jwt) = CsdJwtInstance::issue_vc(raw_vc, &issuer_private_key, &params)?;
+        let disclosures: Vec<String> = vec!["name", "birthdate", "field"].iter().map(|x| x.to_string()).collect();
+
+        let (_vp, vp_jwt) = CsdJwtInstance::issue_aggregated_vp(&vc, &disclosures, None, &holder_private_key)?;
+        CsdJwtInstance::verify_aggregated_vp(&vp_jwt, &issuer_public_key, &holder_public_key, &params, &aggregation_key, &CsdJwtInstance::VERIFIER_CONFIG)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn disclosure_extremes() -> Result<(), String> {
+
+        let raw_vc: Map<String, Value> = match serde_json::from_str::<Map<String, Value>>(VC) {
+            Ok(vc) => { vc }
+            Err(err) => { return Err(format!("[CSD-JWT] Failed to parse Raw Verifiable Credential. [{err}]")); }
+        };
+
+        let mut rng = ark_rng();
+        let (holder_public_key, holder_private_key) = CommonData::holder_keys()?;
+        let (params, Keypair { secret_key: ref issuer_private_key, public_key: ref issuer_public_key}) = CsdJwtInstance::initialize_params(&mut rng);
+        let proving_key = CsdJwtInstance::initialize_proving_key(&mut rng);
+        let (vc, _vc_jwt) = CsdJwtInstance::issue_vc(&raw_vc, issuer_private_key, &params)?;
+
+        // The accumulator value alone does not prove anything about the issuer.
+        assert!(CsdJwtInstance::issue_vp(&vc, &vec![], &holder_private_key).is_err(), "[CSD-JWT] Empty VP was issued.");
+        assert!(CsdJwtInstance::issue_multi_show_vp(&vc, &vec![], None, &holder_private_key, issuer_public_key, &params, &proving_key).is_err(), "[CSD-JWT-MS] Empty VP was issued.");
+        assert!(CsdJwtInstance::issue_aggregated_vp(&vc, &vec![], None, &holder_private_key).is_err(), "[CSD-JWT-AGG] Empty VP was issued.");
+
+        let all_claims: Vec<String> = CsdJwtInstance::extract_claims(&raw_vc)?.keys().cloned().collect();
+        let (_vp, vp_jwt) = CsdJwtInstance::issue_vp(&vc, &all_claims, &holder_private_key)?;
+        CsdJwtInstance::verify_vp(&vp_jwt, issuer_public_key, &holder_public_key, &params)?;
+        let (_vp, vp_jwt) = CsdJwtInstance::issue_multi_show_vp(&vc, &all_claims, None, &holder_private_key, issuer_public_key, &params, &proving_key)?;
+        CsdJwtInstance::verify_multi_show_vp(&vp_jwt, issuer_public_key, &holder_public_key, &params, &proving_key, &CsdJwtInstance::VERIFIER_CONFIG)?;
+
+        let unknown_claim: Vec<String> = vec!["not a claim".to_string()];
+        assert!(CsdJwtInstance::issue_vp(&vc, &unknown_claim, &holder_private_key).is_err(), "[CSD-JWT] Unknown claim was disclosed.");
+
+        Ok(())
+    }
+
+    #[test]
+    fn duplicate_disclosures() -> Result<(), String> {
+
+        let raw_vc: Map<String, Value> = match serde_json::from_str::<Map<String, Value>>(VC) {
+            Ok(vc) => { vc }
+            Err(err) => { return Err(format!("[CSD-JWT] Failed to parse Raw Verifiable Credential. [{err}]")); }
+        };
+
+        let mut rng = ark_rng();
+        let (holder_public_key, holder_private_key) = CommonData::holder_keys()?;
+        let (params, Keypair { secret_key: ref issuer_private_key, public_key: ref issuer_public_key}) = CsdJwtInstance::initialize_params(&mut rng);
+        let proving_key = CsdJwtInstance::initialize_proving_key(&mut rng);
+        let aggregation_key = CsdJwtInstance::initialize_aggregation_key(issuer_private_key, &params, 13);
+        let (vc, _vc_jwt) = CsdJwtInstance::issue_vc(&raw_vc, issuer_private_key, &params)?;
+
+        // A claim disclosed more than once is presented, and counted by the verifier, only once.
+        let disclosures: Vec<String> = vec!["name", "birthdate", "name", "name"].iter().map(|x| x.to_string()).collect();
+        let (vp, vp_jwt) = CsdJwtInstance::issue_vp(&vc, &disclosures, &holder_private_key)?;
+        let witness_value_container: Map<String, Value> = CsdJwtInstance::get_and_decode(CsdJwtInstance::credential_or_presentation(&vp)?, WVC.to_string())?;
+        assert_eq!(witness_value_container.len(), 2, "[CSD-JWT] Duplicate disclosure was presented twice.");
+        CsdJwtInstance::verify_vp(&vp_jwt, issuer_public_key, &holder_public_key, &params)?;
+
+        let (_vp, vp_jwt) = CsdJwtInstance::issue_multi_show_vp(&vc, &disclosures, None, &holder_private_key, issuer_public_key, &params, &proving_key)?;
+        CsdJwtInstance::verify_multi_show_vp(&vp_jwt, issuer_public_key, &holder_public_key, &params, &proving_key, &CsdJwtInstance::VERIFIER_CONFIG)?;
+        let (_vp, vp_jwt) = CsdJwtInstance::issue_aggregated_vp(&vc, &disclosures, None, &holder_private_key)?;
+        CsdJwtInstance::verify_aggregated_vp(&vp_jwt, issuer_public_key, &holder_public_key, &params, &aggregation_key, &CsdJwtInstance::VERIFIER_CONFIG)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn many_claims() -> Result<(), String> {
+
+        let mut raw_vc: Map<String, Value> = match serde_json::from_str::<Map<String, Value>>(VC) {
+            Ok(vc) => { vc }
+            Err(err) => { return Err(format!("[CSD-JWT] Failed to parse Raw Verifiable Credential. [{err}]")); }
+        };
+        FlatClaimGenerator.substitute_claims(&mut raw_vc, 300)?;
+
+        let mut rng = ark_rng();
+        let (holder_public_key, holder_private_key) = CommonData::holder_keys()?;
+        let (params, Keypair { secret_key: ref issuer_private_key, public_key: ref issuer_public_key}) = CsdJwtInstance::initialize_params(&mut rng);
+        let (vc, _vc_jwt) = CsdJwtInstance::issue_vc(&raw_vc, issuer_private_key, &params)?;
+        CsdJwtInstance::verify_vc(&vc, issuer_public_key, &params)?;
+
+        // Disclosures are looked up by name, in any order, and more witnesses than workers are verified.
+        let disclosures: Vec<String> = FlatClaimGenerator.disclosures(&(1..=300).rev().step_by(3).collect::<Vec<usize>>());
+        let (vp, vp_jwt) = CsdJwtInstance::issue_vp(&vc, &disclosures, &holder_private_key)?;
+        let witness_value_container: Map<String, Value> = CsdJwtInstance::get_and_decode(CsdJwtInstance::credential_or_presentation(&vp)?, WVC.to_string())?;
+        assert_eq!(witness_value_container.len(), 100);
+        assert!(disclosures.iter().all(|disclosure| witness_value_container.contains_key(disclosure)));
+        CsdJwtInstance::verify_vp(&vp_jwt, issuer_public_key, &holder_public_key, &params)?;
+
+        // A single tampered value among hundreds is still caught.
+        let mut witness_value_container: Map<String, Value> = CsdJwtInstance::get_and_decode(&vc, WVC.to_string())?;
+        witness_value_container["Claim Key 250"][1] = Value::String("Tampered".to_string());
+        let mut tampered_vc: Map<String, Value> = vc.clone();
+        CsdJwtInstance::serialize_and_insert(&mut tampered_vc, WVC.to_string(), &witness_value_container)?;
+        assert!(CsdJwtInstance::verify_vc(&tampered_vc, issuer_public_key, &params).is_err(), "[CSD-JWT] Tampered claim was verified.");
+
+        Ok(())
+    }
+
+    #[test]
+    fn malformed_witnesses() -> Result<(), String> {
+
+        let raw_vc: Map<String, Value> = match serde_json::from_str::<Map<String, Value>>(VC) {
+            Ok(vc) => { vc }
+            Err(err) => { return Err(format!("[CSD-JWT] Failed to parse Raw Verifiable Credential. [{err}]")); }
+        };
+
+        let mut rng = ark_rng();
+        let (params, Keypair { secret_key: ref issuer_private_key, public_key: ref issuer_public_key}) = CsdJwtInstance::initialize_params(&mut rng);
+        let (vc, _vc_jwt) = CsdJwtInstance::issue_vc(&raw_vc, issuer_private_key, &params)?;
+        let serialized_accumulator: String = CsdJwtInstance::get_and_decode(&vc, ACCUMULATOR.to_string())?;
+        let accumulator: PositiveAccumulator<Bn254> = CsdJwtInstance::deserialize(&serialized_accumulator)?;
+        let witness_value_container: Map<String, Value> = CsdJwtInstance::get_and_decode(&vc, WVC.to_string())?;
+        let name: String = "name".to_string();
+        let witness: &Value = &witness_value_container[&name][0];
+
+        let malformed: [Value; 4] = [
+            Value::String("not an array".to_string()),
+            Value::Array(vec![]),
+            Value::Array(vec![witness.clone()]),
+            Value::Array(vec![Value::Bool(true), witness_value_container[&name][1].clone()]),
+        ];
+        for array_value in &malformed {
+            assert!(CsdJwtInstance::verify_witness(&name, array_value, &accumulator, None, issuer_public_key, &params, None).is_err(), "[CSD-JWT] {array_value} was verified.");
+        }
+        CsdJwtInstance::verify_witness(&name, &witness_value_container[&name], &accumulator, None, issuer_public_key, &params, None)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn prepared_verification_key() -> Result<(), String> {
+
+        let raw_vc: Map<String, Value> = match serde_json::from_str::<Map<String, Value>>(VC) {
+            Ok(vc) => { vc }
+            Err(err) => { return Err(format!("[CSD-JWT] Failed to parse Raw Verifiable Credential. [{err}]")); }
+        };
+
+        let mut rng = ark_rng();
+        let (params, Keypair { secret_key: ref issuer_private_key, public_key: ref issuer_public_key}) = CsdJwtInstance::initialize_params(&mut rng);
+        let prepared_key = PreparedVerificationKey::new(issuer_public_key, &params);
+
+        let (vc, _vc_jwt) = CsdJwtInstance::issue_vc(&raw_vc, issuer_private_key, &params)?;
+        let witness_value_container: Map<String, Value> = CsdJwtInstance::get_and_decode(&vc, WVC.to_string())?;
+        let serialized_accumulator: String = CsdJwtInstance::get_and_decode(&vc, ACCUMULATOR.to_string())?;
+        let accumulator: PositiveAccumulator<Bn254> = CsdJwtInstance::deserialize(&serialized_accumulator)?;
+
+        let mut cold_duration = std::time::Duration::ZERO;
+        let mut warm_duration = std::time::Duration::ZERO;
+        for (claim_key, array_value) in &witness_value_container {
+            let (witness, claim_value) = CsdJwtInstance::split_witness_and_value(array_value)?;
+            let element: Fr = CsdJwtInstance::convert_claim_to_scalar(claim_key, &claim_value, None);
+
+            let start = std::time::Instant::now();
+            assert!(accumulator.verify_membership(&element, &witness, issuer_public_key, &params));
+            cold_duration += start.elapsed();
+
+            let start = std::time::Instant::now();
+            assert!(prepared_key.verify_membership(accumulator.value(), &element, &witness), "[CSD-JWT] Prepared key rejected a valid witness of {claim_key}.");
+            warm_duration += start.elapsed();
+
+            let wrong_element: Fr = CsdJwtInstance::convert_claim_to_scalar(claim_key, &Value::Null, None);
+            assert!(!prepared_key.verify_membership(accumulator.value(), &wrong_element, &witness), "[CSD-JWT] Prepared key accepted an invalid witness of {claim_key}.");
+        }
+
+        debug!("[CSD-JWT] Membership verification of {} claims: {:?} unprepared, {:?} prepared.", witness_value_container.len(), cold_duration, warm_duration);
+
+        Ok(())
+    }
+
+    #[test]
+    fn element_blinding() -> Result<(), String> {
+
+        let raw_vc: Map<String, Value> = match serde_json::from_str::<Map<String, Value>>(VC) {
+            Ok(vc) => { vc }
+            Err(err) => { return Err(format!("[CSD-JWT] Failed to parse Raw Verifiable Credential. [{err}]")); }
+        };
+
+        let mut rng = ark_rng();
+        let (holder_public_key, holder_private_key) = CommonData::holder_keys()?;
+        let (params, Keypair { secret_key: ref issuer_private_key, public_key: ref issuer_public_key}) = CsdJwtInstance::initialize_params(&mut rng);
+        let proving_key = CsdJwtInstance::initialize_proving_key(&mut rng);
+        let aggregation_key = CsdJwtInstance::initialize_aggregation_key(issuer_private_key, &params, 13);
+
+        let elements = |blinded: bool| -> Result<Option<Value>, String> {
+            let (vc, _vc_jwt) = CsdJwtInstance::issue_vc_with_blinding(&raw_vc, issuer_private_key, &params, blinded)?;
+            Ok(CsdJwtInstance::proof_artifacts(&vc)?.get("elements").cloned())
+        };
+
+        // The same claims are accumulated as the same elements in different VCs unless they are blinded.
+        assert_eq!(elements(false)?, elements(false)?);
+        assert_ne!(elements(true)?, elements(true)?, "[CSD-JWT] Two blinded VCs share the same elements.");
+
+        let (vc, _vc_jwt) = CsdJwtInstance::issue_vc_with_blinding(&raw_vc, issuer_private_key, &params, true)?;
+        CsdJwtInstance::verify_vc(&vc, issuer_public_key, &params)?;
+        assert!(CsdJwtInstance::check_witnesses(&vc, issuer_public_key, &params)?.values().all(|valid| *valid), "[CSD-JWT] Valid blinded witness was rejected.");
+        assert_eq!(CsdJwtInstance::lower_vc(&vc)?, CsdJwtInstance::lower(&raw_vc)?);
+
+        let disclosures: Vec<String> = vec!["name".to_string(), "birthdate".to_string()];
+        let (_vp, vp_jwt) = CsdJwtInstance::issue_vp(&vc, &disclosures, &holder_private_key)?;
+        CsdJwtInstance::verify_vp(&vp_jwt, issuer_public_key, &holder_public_key, &params)?;
+        let (_vp, vp_jwt) = CsdJwtInstance::issue_multi_show_vp(&vc, &disclosures, None, &holder_private_key, issuer_public_key, &params, &proving_key)?;
+        CsdJwtInstance::verify_multi_show_vp(&vp_jwt, issuer_public_key, &holder_public_key, &params, &proving_key, &CsdJwtInstance::VERIFIER_CONFIG)?;
+        let (_vp, vp_jwt) = CsdJwtInstance::issue_aggregated_vp(&vc, &disclosures, None, &holder_private_key)?;
+        CsdJwtInstance::verify_aggregated_vp(&vp_jwt, issuer_public_key, &holder_public_key, &params, &aggregation_key, &CsdJwtInstance::VERIFIER_CONFIG)?;
+
+        // The witnesses are only valid for the blinding factor chosen by the issuer.
+        let mut unblinded_vc: Map<String, Value> = vc.clone();
+        unblinded_vc.remove(BLINDING);
+        assert!(CsdJwtInstance::verify_vc(&unblinded_vc, issuer_public_key, &params).is_err(), "[CSD-JWT] VC without its blinding factor was verified.");
+
+        Ok(())
+    }
+
+    #[test]
+    fn witness_update() -> Result<(), String> {
+
+        let raw_vc: Map<String, Value> = match serde_json::from_str::<Map<String, Value>>(VC) {
+            Ok(vc) => { vc }
+            Err(err) => { return Err(format!("[CSD-JWT] Failed to parse Raw Verifiable Credential. [{err}]")); }
+        };
+
+        let mut rng = ark_rng();
+        let (holder_public_key, holder_private_key) = CommonData::holder_keys()?;
+        let (signing_public_key, signing_private_key) = CommonData::issuer_keys()?;
+        let (params, Keypair { secret_key: ref issuer_private_key, public_key: ref issuer_public_key}) = CsdJwtInstance::initialize_params(&mut rng);
+        let (vc, _vc_jwt) = CsdJwtInstance::issue_vc_with_blinding(&raw_vc, issuer_private_key, &params, true)?;
+
+        let revoked: [String; 2] = ["name".to_string(), "birthdate".to_string()];
+        let update_jwt = CsdJwtInstance::revoke_claims(&vc, &revoked, issuer_private_key, &signing_private_key)?;
+        let (updated_vc, _updated_vc_jwt) = CsdJwtInstance::apply_witness_update(&vc, &update_jwt, &signing_public_key)?;
+        CsdJwtInstance::verify_vc(&updated_vc, issuer_public_key, &params)?;
+
+        let updated_claims: Map<String, Value> = CsdJwtInstance::issued_claims(&updated_vc)?;
+        assert_eq!(updated_claims.len() + revoked.len(), CsdJwtInstance::issued_claims(&vc)?.len());
+        assert!(revoked.iter().all(|claim| !updated_claims.contains_key(claim)), "[CSD-JWT] Revoked claim is still in the updated VC.");
+
+        let disclosures: Vec<String> = vec!["field".to_string()];
+        let (_vp, vp_jwt) = CsdJwtInstance::issue_vp(&updated_vc, &disclosures, &holder_private_key)?;
+        CsdJwtInstance::verify_vp(&vp_jwt, issuer_public_key, &holder_public_key, &params)?;
+
+        // The message only applies once, and only if signed by the issuer.
+        assert!(CsdJwtInstance::apply_witness_update(&updated_vc, &update_jwt, &signing_public_key).is_err(), "[CSD-JWT] Witness update was applied twice.");
+        assert!(CsdJwtInstance::apply_witness_update(&vc, &update_jwt, &holder_public_key).is_err(), "[CSD-JWT] Witness update signed by another key was applied.");
+        assert!(CsdJwtInstance::revoke_claims(&vc, &["not a claim".to_string()], issuer_private_key, &signing_private_key).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn check_witnesses() -> Result<(), String> {
+
+        let raw_vc: Map<String, Value> = match serde_json::from_str::<Map<String, Value>>(VC) {
+            Ok(vc) => { vc }
+            Err(err) => { return Err(format!("[CSD-JWT] Failed to parse Raw Verifiable Credential. [{err}]")); }
+        };
+
+        let mut rng = ark_rng();
+        let (params, Keypair { secret_key: ref issuer_private_key, public_key: ref issuer_public_key}) = CsdJwtInstance::initialize_params(&mut rng);
+        let (vc, _vc_jwt) = CsdJwtInstance::issue_vc(&raw_vc, issuer_private_key, &params)?;
+
+        let validity = CsdJwtInstance::check_witnesses(&vc, issuer_public_key, &params)?;
+        assert_eq!(validity.len(), CsdJwtInstance::extract_claims(&raw_vc)?.len());
+        assert!(validity.values().all(|valid| *valid), "[CSD-JWT] Valid witness was rejected.");
+
+        // The value of a claim is altered and the witness of another is not a witness.
+        let mut witness_value_container: Map<String, Value> = CsdJwtInstance::get_and_decode(&vc, WVC.to_string())?;
+        let altered_entry: Value = match &witness_value_container["name"] {
+            Value::Array(array) => { Value::Array(vec![array[0].clone(), Value::String("Mileva Marić".to_string())]) }
+            _ => { return Err("[CSD-JWT] Witness-Value Container entry is not an array.".to_string()) }
+        };
+        witness_value_container.insert("name".to_string(), altered_entry);
+        witness_value_container.insert("birthdate".to_string(), Value::Array(vec![Value::String("not a witness".to_string()), Value::String("1879-03-14".to_string())]));
+        let mut tampered_vc: Map<String, Value> = vc.clone();
+        CsdJwtInstance::serialize_and_insert(&mut tampered_vc, WVC.to_string(), &witness_value_container)?;
+
+        let validity = CsdJwtInstance::check_witnesses(&tampered_vc, issuer_public_key, &params)?;
+        let invalid_claims: Vec<&String> = validity.iter().filter(|(_, valid)| !**valid).map(|(claim, _)| claim).collect();
+        assert_eq!(invalid_claims, vec!["birthdate", "name"]);
+        assert!(CsdJwtInstance::verify_vc(&tampered_vc, issuer_public_key, &params).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn verification_cache() -> Result<(), String> {
+
+        let raw_vc: Map<String, Value> = match serde_json::from_str::<Map<String, Value>>(VC) {
+            Ok(vc) => { vc }
+            Err(err) => { return Err(format!("[CSD-JWT] Failed to parse Raw Verifiable Credential. [{err}]")); }
+        };
+
+        let mut rng = ark_rng();
+        let (params, Keypair { secret_key: ref issuer_private_key, public_key: ref issuer_public_key}) = CsdJwtInstance::initialize_params(&mut rng);
+        let (holder_public_key, holder_private_key) = CommonData::holder_keys()?;
+        let raw_vc: Map<String, Value> = bind_holder_key(&raw_vc, &holder_public_key)?;
+        let (vc, _vc_jwt) = CsdJwtInstance::issue_vc(&raw_vc, issuer_private_key, &params)?;
+
+        let disclosures: Vec<String> = vec!["name".to_string(), "birthdate".to_string()];
+        let (vp, vp_jwt) = CsdJwtInstance::issue_vp(&vc, &disclosures, &holder_private_key)?;
+        let cache: Arc<VerificationCache> = Arc::new(VerificationCache::new());
+
+        // The first verification checks every witness, the second one finds every (accumulator, digest) pair cached.
+        CsdJwtInstance::verify_vp_with_cache(&vp_jwt, issuer_public_key, &holder_public_key, &params, Some(&cache), None, &CsdJwtInstance::VERIFIER_CONFIG)?;
+        assert_eq!((cache.hits(), cache.misses(), cache.len()), (0, 2, 2));
+        CsdJwtInstance::verify_vp_with_cache(&vp_jwt, issuer_public_key, &holder_public_key, &params, Some(&cache), None, &CsdJwtInstance::VERIFIER_CONFIG)?;
+        assert_eq!((cache.hits(), cache.misses(), cache.len()), (2, 2, 2));
+
+        // A VP re-signed by the holder with an altered value shares the cached accumulator but not the digest.
+        let mut credential: Map<String, Value> = CsdJwtInstance::unwrap_presentation(&vp)?.clone();
+        let mut witness_value_container: Map<String, Value> = CsdJwtInstance::get_and_decode(&credential, WVC.to_string())?;
+        let altered_entry: Value = match &witness_value_container["name"] {
+            Value::Array(array) => { Value::Array(vec![array[0].clone(), Value::String("Mileva Marić".to_string())]) }
+            _ => { return Err("[CSD-JWT] Witness-Value Container entry is not an array.".to_string()) }
+        };
+        witness_value_container.insert("name".to_string(), altered_entry);
+        CsdJwtInstance::serialize_and_insert(&mut credential, WVC.to_string(), &witness_value_container)?;
+        let (_tampered_vp, tampered_vp_jwt) = CsdJwtInstance::encode_and_sign_presentation(credential, &holder_private_key)?;
+
+        assert!(CsdJwtInstance::verify_vp_with_cache(&tampered_vp_jwt, issuer_public_key, &holder_public_key, &params, Some(&cache), None, &CsdJwtInstance::VERIFIER_CONFIG).is_err(), "[CSD-JWT] Tampered VP sharing a cached accumulator was verified.");
+        assert_eq!((cache.hits(), cache.misses(), cache.len()), (3, 3, 2));
+
+        Ok(())
+    }
+
+    #[test]
+    fn ephemeral_key() -> Result<(), String> {
+
+        let raw_vc: Map<String, Value> = match serde_json::from_str::<Map<String, Value>>(VC) {
+            Ok(vc) => { vc }
+            Err(err) => { return Err(format!("[CSD-JWT] Failed to parse Raw Verifiable Credential. [{err}]")); }
+        };
+
+        let mut rng = ark_rng();
+        let (holder_public_key, holder_private_key) = CommonData::holder_keys()?;
+        let (other_public_key, other_private_key) = CommonData::issuer_keys()?;
+        let (params, Keypair { secret_key: ref issuer_private_key, public_key: ref issuer_public_key}) = CsdJwtInstance::initialize_params(&mut rng);
+        let (vc, _vc_jwt) = CsdJwtInstance::issue_vc(&bind_holder_key(&raw_vc, &holder_public_key)?, issuer_private_key, &params)?;
+        let disclosures: Vec<String> = vec!["name".to_string()];
+
+        let ephemeral_key = EphemeralKey::generate()?;
+        let certificate = ephemeral_key.certify(&holder_private_key)?;
+        let (_vp, vp_jwt) = CsdJwtInstance::issue_vp_with_ephemeral_key(&vc, &disclosures, None, None, &ephemeral_key, &certificate)?;
+        CsdJwtInstance::verify_vp_with_ephemeral_key(&vp_jwt, issuer_public_key, &holder_public_key, &params, &CsdJwtInstance::VERIFIER_CONFIG)?;
+
+        // The certificate verifies against its signer, but the VC is bound to another key.
+        let certificate = ephemeral_key.certify(&other_private_key)?;
+        let (_vp, vp_jwt) = CsdJwtInstance::issue_vp_with_ephemeral_key(&vc, &disclosures, None, None, &ephemeral_key, &certificate)?;
+        assert!(CsdJwtInstance::verify_vp_with_ephemeral_key(&vp_jwt, issuer_public_key, &other_public_key, &params, &CsdJwtInstance::VERIFIER_CONFIG).is_err(), "[CSD-JWT] VP certified by an unbound key was verified.");
+
+        // The VP is signed by a key other than the certified one.
+        let certificate = EphemeralKey::generate()?.certify(&holder_private_key)?;
+        let (_vp, vp_jwt) = CsdJwtInstance::issue_vp_with_ephemeral_key(&vc, &disclosures, None, None, &ephemeral_key, &certificate)?;
+        assert!(CsdJwtInstance::verify_vp_with_ephemeral_key(&vp_jwt, issuer_public_key, &holder_public_key, &params, &CsdJwtInstance::VERIFIER_CONFIG).is_err(), "[CSD-JWT] VP signed by an uncertified key was verified.");
+
+        Ok(())
+    }
+
+
+    #[test]
+    fn binary_envelope() -> Result<(), String> {
+
+        let raw_vc: Map<String, Value> = match serde_json::from_str::<Map<String, Value>>(VC) {
+            Ok(vc) => { vc }
+            Err(err) => { return Err(format!("[CSD-JWT] Failed to parse Raw Verifiable Credential. [{err}]")); }
+        };
+
+        let mut rng = ark_rng();
+        let (holder_public_key, holder_private_key) = CommonData::holder_keys()?;
+        let (params, Keypair { secret_key: ref issuer_private_key, public_key: _ }) = CsdJwtInstance::initialize_params(&mut rng);
+        let (vc, vc_jwt) = CsdJwtInstance::issue_vc(&raw_vc, issuer_private_key, &params)?;
+        let disclosures: Vec<String> = vec!["name".to_string(), "birthdate".to_string()];
+        let (_vp, vp_jwt) = CsdJwtInstance::issue_vp(&vc, &disclosures, &holder_private_key)?;
+
+        let vc_envelope = envelope_from_jwt(&vc_jwt, None)?;
+        assert_eq!(verify_envelope(&vc_envelope, None, &CsdJwtInstance::VERIFIER_CONFIG)?, unverified_claims(&vc_jwt)?);
+        assert!(vc_envelope.len() < vc_jwt.len(), "[CSD-JWT] Binary VC is not smaller than its JWT.");
+
+        let vp_envelope = envelope_from_jwt(&vp_jwt, Some(holder_private_key.as_slice()))?;
+        assert_eq!(verify_envelope(&vp_envelope, Some(holder_public_key.as_slice()), &CsdJwtInstance::VERIFIER_CONFIG)?, unverified_claims(&vp_jwt)?);
+        assert_eq!(envelope_length(&vp_jwt)?, vp_envelope.len());
+        assert!(vp_envelope.len() < vp_jwt.len(), "[CSD-JWT] Binary VP is not smaller than its JWT.");
+
+        let mut tampered_envelope = vp_envelope.clone();
+        let last = tampered_envelope.len() - 1;
+        tampered_envelope[last] ^= 1;
+        assert!(verify_envelope(&tampered_envelope, Some(holder_public_key.as_slice()), &CsdJwtInstance::VERIFIER_CONFIG).is_err(), "[CSD-JWT] Tampered binary VP was verified.");
+
+        Ok(())
+    }
+}
@@ -0,0 +1,418 @@
+use std::thread;
+use std::thread::ScopedJoinHandle;
+use ark_bls12_381::{Bls12_381, Fr, G1Affine};
+use ark_ff::PrimeField;
+use ark_std::rand::rngs::StdRng;
+use serde_json::{Map, Value};
+use vb_accumulator::positive::Accumulator;
+use vb_accumulator::prelude::PositiveAccumulator;
+use vb_accumulator::setup::{Keypair, PublicKey, SecretKey, SetupParams};
+use vb_accumulator::witness::MembershipWitness;
+
+use crate::audit::{claim_proofs_document, SIGNED_FIELDS, VALUE};
+use crate::cost_profile::{record, Operation};
+use crate::credential_ir::CredentialIr;
+use crate::encoding::Encoding;
+use crate::hashing::sha256;
+use crate::holder_binding::HolderBinding;
+use crate::presentation_validity::PresentationValidity;
+use crate::sd_algorithms::accumulators::csd_jwt::{CsdJwtInstance, InMemoryState, ACCUMULATOR, WVC};
+use crate::sd_algorithms::claim_protector::{bind_claim, ClaimProtector};
+use crate::sd_algorithms::sd_algorithm::{signed_fields, SdAlgorithm};
+use crate::verifier_config::{check_all, VerifierConfig};
+
+
+/// Struct for an instance of the CSD-JWT algorithm over the BLS12-381 pairing curve, which most VC ecosystems
+/// standardize on, instead of BN254.
+///
+/// Credentials and presentations have the same structure as the ones of `CsdJwtInstance`, an accumulator value and a
+/// Witness-Value Container, with group elements of BLS12-381: accumulators and witnesses are compressed G1 points of 48
+/// bytes instead of 32. Only the single-show presentations are supported, without element blinding.
+pub struct CsdJwtBls12381Instance;
+
+impl SdAlgorithm for CsdJwtBls12381Instance {
+    const ALGORITHM: &'static str = "CSD-JWT-BLS12-381";
+    // The accumulator value is only bound to the issuer through the membership of the disclosed claims.
+    const SUPPORTS_EMPTY_DISCLOSURE: bool = false;
+    const PROOF_FIELDS: &'static [&'static str] = &[ACCUMULATOR, WVC];
+
+    fn issued_claims(vc: &Map<String, Value>) -> Result<Map<String, Value>, String> {
+        let witness_value_container: Map<String, Value> = Self::get_and_decode(vc, WVC.to_string())?;
+        Self::container_values(&witness_value_container)
+    }
+
+    fn disclosed_claim_names(credential: &Map<String, Value>) -> Result<Vec<String>, String> {
+        let witness_value_container: Map<String, Value> = Self::get_and_decode(credential, WVC.to_string())?;
+        Ok(witness_value_container.keys().cloned().collect())
+    }
+}
+
+impl ClaimProtector for CsdJwtBls12381Instance {
+    type Element = Fr;
+
+    fn protect_claim(name: &str, value: &Value, opening: Option<&String>) -> Result<Fr, String> {
+        Ok(Self::convert_claim_to_scalar(name, value, opening.map(String::as_str)))
+    }
+}
+
+impl CsdJwtBls12381Instance {
+
+    /// Gathers the necessary parameters for the algorithm to work.
+    ///
+    /// # Arguments
+    /// * `rng` - Random Number Generator for producing random data and keying material.
+    ///
+    /// # Returns
+    /// This function returns a struct containing setup parameters and the cryptographic accumulator keys.
+    pub fn initialize_params(rng: &mut StdRng) -> (SetupParams<Bls12_381>, Keypair<Bls12_381>) {
+
+        let params = SetupParams::<Bls12_381>::generate_using_rng(rng);
+        let keypair = Keypair::<Bls12_381>::generate_using_rng(rng, &params);
+
+        (params, keypair)
+    }
+
+
+    /// Maps claims to scalar values of BLS12-381 by hashing the unambiguous encoding of their key and value, bound to
+    /// the serialized signed fields of the credential, as `CsdJwtInstance::convert_claim_to_scalar` does for BN254.
+    ///
+    /// # Arguments
+    /// * `key` - Name of the element.
+    /// * `value` - Value of the element.
+    /// * `context` - Serialized signed fields of the credential, if any.
+    ///
+    /// # Returns
+    /// This function returns the converted scalar.
+    pub fn convert_claim_to_scalar(key: &str, value: &Value, context: Option<&str>) -> Fr {
+        record(Operation::Hash, 1);
+        Fr::from_be_bytes_mod_order(&sha256(bind_claim(key, &Self::canonical_json(value), context)))
+    }
+
+
+    /// Given a raw VC containing a few fields and the credentialSubject field to include claims, create all the necessary data to create a VC using this algorithm.
+    ///
+    /// # Arguments
+    /// * `raw_vc` - Template VC containing a credential.
+    /// * `issuer_private_key` - Accumulator secret key of the issuer.
+    /// * `params` - Additional parameters needed for correct handling of the accumulator value.
+    ///
+    /// # Returns
+    /// This function returns a VC both in the form of a Map and in the form of an unsigned JWT.
+    pub fn issue_vc(raw_vc: &Map<String, Value>, issuer_private_key: &SecretKey<Fr>, params: &SetupParams<Bls12_381>) -> Result<(Map<String, Value>, String), String> {
+
+        let credential: CredentialIr = Self::lower(raw_vc)?;
+        let mut vc = credential.envelope();
+
+        let accumulator: PositiveAccumulator<Bls12_381> = PositiveAccumulator::initialize(params);
+        let mut state: InMemoryState<Fr> = InMemoryState::new();
+        // The serialized signed fields are the opening of every claim of the credential.
+        let elements: Vec<Fr> = match Self::serialize_signed_fields(&vc) {
+            Some(context) => {
+                let openings: Map<String, Value> = credential.claims().keys().map(|name| (name.clone(), Value::String(context.clone()))).collect();
+                Self::protected_elements(credential.claims(), &openings)?
+            }
+            None => { Self::protect_claims(credential.claims())?.0 }
+        };
+        let claims: Vec<(&String, &Value)> = Self::canonical_claims(credential.claims());
+
+        // V' = V * prod(y_i + a), then C_i = V' / (y_i + a) for every claim.
+        record(Operation::Exponentiation, 1 + elements.len() as u64);
+        let accumulator = match accumulator.add_batch(elements.clone(), issuer_private_key, &mut state) {
+            Ok(accumulator) => { accumulator }
+            Err(err) => { return Err(format!("Error in adding batch claims: [{:?}]", err)) }
+        };
+        let witnesses: Vec<MembershipWitness<G1Affine>> = match accumulator.get_membership_witnesses_for_batch(&elements, issuer_private_key, &state) {
+            Ok(witnesses) => { witnesses }
+            Err(err) => { return Err(format!("Error in producing batch witnesses: [{:?}]", err)) }
+        };
+
+        let mut witness_value_container: Map<String, Value> = Map::new();
+        for ((key, value), witness) in claims.into_iter().zip(witnesses.iter()) {
+            witness_value_container.insert(key.clone(), Value::Array(vec![Value::String(CsdJwtInstance::serialize(witness)?), value.clone()]));
+        }
+
+        Self::serialize_and_insert(&mut vc, ACCUMULATOR.to_string(), &CsdJwtInstance::serialize(&accumulator)?)?;
+        Self::serialize_and_insert(&mut vc, WVC.to_string(), &witness_value_container)?;
+
+        let jwt = Self::encode_jwt(&vc)?;
+
+        Ok((vc, jwt))
+    }
+
+
+    /// Given a VC, verify it using all the necessary data.
+    ///
+    /// # Arguments
+    /// * `vc` - Verifiable Credential.
+    /// * `issuer_public_key` - Accumulator public key of the issuer.
+    /// * `params` - Additional parameters needed for correct handling of the accumulator value.
+    ///
+    /// # Returns
+    /// This function returns a string containing an error in case of failure.
+    pub fn verify_vc(vc: &Map<String, Value>, issuer_public_key: &PublicKey<Bls12_381>, params: &SetupParams<Bls12_381>) -> Result<(), String> {
+        Self::verify_vc_with_config(vc, issuer_public_key, params, &Self::VERIFIER_CONFIG)
+    }
+
+
+    /// Given a VC, verify it enforcing the limits of a verifier.
+    ///
+    /// # Arguments
+    /// * `vc` - Verifiable Credential.
+    /// * `issuer_public_key` - Accumulator public key of the issuer.
+    /// * `params` - Additional parameters needed for correct handling of the accumulator value.
+    /// * `config` - Limits enforced on the VC.
+    ///
+    /// # Returns
+    /// This function returns a string containing an error in case of failure.
+    pub fn verify_vc_with_config(vc: &Map<String, Value>, issuer_public_key: &PublicKey<Bls12_381>, params: &SetupParams<Bls12_381>, config: &VerifierConfig) -> Result<(), String> {
+        Self::verify_witness_value_container(vc, issuer_public_key, params, config)
+    }
+
+
+    /// Given a VC, and a set of disclosures, create a Verifiable Presentation keeping the witnesses of the disclosed
+    /// claims only.
+    ///
+    /// # Arguments
+    /// * `vc` - Verifiable Credential.
+    /// * `disclosures` - List of strings containing the names of the claims that are to be disclosed.
+    /// * `audience` - Optional intended audience of the presentation.
+    /// * `validity` - Optional validity window of the presentation.
+    /// * `holder_binding` - Holder binding signing the VP as proof of possession.
+    ///
+    /// # Returns
+    /// This function returns the VP both in form of a Map and in form of a signed JWT.
+    pub fn issue_vp(vc: &Map<String, Value>, disclosures: &[String], audience: Option<String>, validity: Option<PresentationValidity>, holder_binding: &(impl HolderBinding + ?Sized)) -> Result<(Map<String, Value>, String), String> {
+
+        let mut vp: Map<String, Value> = vc.clone();
+        let disclosures: Vec<String> = Self::resolve_disclosures(vc, disclosures)?;
+
+        let witness_value_container: Map<String, Value> = Self::get_and_decode(vc, WVC.to_string())?;
+        let disclosed_container: Map<String, Value> = disclosures
+            .iter()
+            .filter_map(|field| witness_value_container.get(field).map(|value| (field.clone(), value.clone())))
+            .collect();
+
+        Self::serialize_and_insert(&mut vp, WVC.to_string(), &disclosed_container)?;
+        Self::encode_and_sign_presentation_for_audience(vp, audience, validity, holder_binding)
+    }
+
+
+    /// Given a VP, verify it using all the necessary data.
+    ///
+    /// # Arguments
+    /// * `jwt` - Verifiable Presentation encoded as a jwt.
+    /// * `issuer_public_key` - Accumulator public key of the issuer.
+    /// * `holder_binding` - Holder binding verifying the proof of possession.
+    /// * `params` - Additional parameters needed for correct handling of the accumulator value.
+    ///
+    /// # Returns
+    /// This function returns a string containing an error in case of failure.
+    pub fn verify_vp(jwt: &str, issuer_public_key: &PublicKey<Bls12_381>, holder_binding: &(impl HolderBinding + ?Sized), params: &SetupParams<Bls12_381>) -> Result<(), String> {
+        Self::verify_vp_with_config(jwt, issuer_public_key, holder_binding, params, &Self::VERIFIER_CONFIG)
+    }
+
+
+    /// Given a VP, verify it enforcing the limits of a verifier.
+    ///
+    /// # Arguments
+    /// * `jwt` - Verifiable Presentation encoded as a jwt.
+    /// * `issuer_public_key` - Accumulator public key of the issuer.
+    /// * `holder_binding` - Holder binding verifying the proof of possession.
+    /// * `params` - Additional parameters needed for correct handling of the accumulator value.
+    /// * `config` - Limits enforced on the VP.
+    ///
+    /// # Returns
+    /// This function returns a string containing an error in case of failure.
+    pub fn verify_vp_with_config(jwt: &str, issuer_public_key: &PublicKey<Bls12_381>, holder_binding: &(impl HolderBinding + ?Sized), params: &SetupParams<Bls12_381>, config: &VerifierConfig) -> Result<(), String> {
+        let vp: Map<String, Value> = Self::decode_and_verify_presentation(jwt, holder_binding, config)?;
+        Self::check_disclosed_claims_len(Self::disclosed_claim_names(&vp)?.len())?;
+
+        Self::verify_witness_value_container(&vp, issuer_public_key, params, config)
+    }
+
+
+    /// Verifies the witness of every claim of the Witness-Value Container of a VC or VP against its accumulator value,
+    /// on a bounded amount of worker threads as `CsdJwtInstance` does.
+    ///
+    /// # Arguments
+    /// * `credential` - Verifiable Credential or credential of a Verifiable Presentation.
+    /// * `issuer_public_key` - Accumulator public key of the issuer.
+    /// * `params` - Additional parameters needed for correct handling of the accumulator value.
+    /// * `config` - Limits enforced on the container.
+    ///
+    /// # Returns
+    /// This function returns a result containing a string representing an error in case of failure.
+    fn verify_witness_value_container(credential: &Map<String, Value>, issuer_public_key: &PublicKey<Bls12_381>, params: &SetupParams<Bls12_381>, config: &VerifierConfig) -> Result<(), String> {
+
+        let witness_value_container: Map<String, Value> = Self::get_and_decode_with_config(credential, WVC.to_string(), config)?;
+        let serialized_accumulator: String = Self::get_and_decode_with_config(credential, ACCUMULATOR.to_string(), config)?;
+        let accumulator: PositiveAccumulator<Bls12_381> = CsdJwtInstance::deserialize(&serialized_accumulator)?;
+        let context: Option<String> = Self::serialize_signed_fields(credential);
+
+        config.check_claims_len(witness_value_container.len())?;
+        for array_value in witness_value_container.values() {
+            if let Some(Value::String(witness_string)) = array_value.get(0) {
+                config.check_witness_length(witness_string)?;
+            }
+        }
+
+        let entries: Vec<(&String, &Value)> = witness_value_container.iter().collect();
+        let verify = |(claim_key, array_value): &(&String, &Value)| -> Result<(), String> {
+            let (witness, claim_value) = Self::split_witness_and_value(array_value)?;
+            let element: Fr = Self::convert_claim_to_scalar(claim_key, &claim_value, context.as_deref());
+
+            // e(C, y*P_tilde + Q_tilde) = e(V, P_tilde)
+            record(Operation::Exponentiation, 1);
+            record(Operation::Pairing, 2);
+            if !accumulator.verify_membership(&element, &witness, issuer_public_key, params) {
+                return Err(format!("Membership verification failed for claim {claim_key}"))
+            }
+            Ok(())
+        };
+
+        if cfg!(feature = "mobile") {
+            return check_all(entries.iter().map(verify));
+        }
+
+        let workers: usize = thread::available_parallelism().map(|workers| workers.get()).unwrap_or(1);
+        let chunk_size: usize = entries.len().div_ceil(workers).max(1);
+        let results: Vec<Result<(), String>> = thread::scope(|pool| {
+            let handles: Vec<ScopedJoinHandle<Vec<Result<(), String>>>> = entries.chunks(chunk_size).map(|chunk| {
+                let verify = &verify;
+                pool.spawn(move || chunk.iter().map(verify).collect())
+            }).collect();
+
+            handles.into_iter().flat_map(|handle| match handle.join() {
+                Ok(results) => { results }
+                Err(_) => { vec![Err("Membership verification thread panicked.".to_string())] }
+            }).collect()
+        });
+
+        // Every witness is verified before reporting a failure, so that the time taken does not reveal which claim failed.
+        check_all(results)
+    }
+
+
+    /// Splits an entry of the Witness-Value Container into the decoded witness and the claim value.
+    ///
+    /// # Arguments
+    /// * `array_value` - Entry of the Witness-Value Container.
+    ///
+    /// # Returns
+    /// This function returns a result containing the witness and the claim value or a string representing an error.
+    fn split_witness_and_value(array_value: &Value) -> Result<(MembershipWitness<G1Affine>, Value), String> {
+        match (array_value.get(0), array_value.get(1)) {
+            (Some(Value::String(witness_string)), Some(claim_value)) => { Ok((CsdJwtInstance::deserialize(witness_string)?, claim_value.clone())) }
+            _ => { Err("Entry of the Witness value container is not an array of a witness and a value.".to_string()) }
+        }
+    }
+
+
+    /// Extracts the accumulator value, the membership witnesses and the accumulated elements of a VC or VP, all as
+    /// compressed BLS12-381 points or scalars encoded in base64url.
+    ///
+    /// # Arguments
+    /// * `vp` - Verifiable Credential or Verifiable Presentation.
+    ///
+    /// # Returns
+    /// This function returns a map containing the artifacts or a string containing an error in case of failure.
+    pub fn proof_artifacts(vp: &Map<String, Value>) -> Result<Map<String, Value>, String> {
+
+        let vp: &Map<String, Value> = Self::credential_or_presentation(vp)?;
+        let serialized_accumulator: String = Self::get_and_decode(vp, ACCUMULATOR.to_string())?;
+        let accumulator: PositiveAccumulator<Bls12_381> = CsdJwtInstance::deserialize(&serialized_accumulator)?;
+        let witness_value_container: Map<String, Value> = Self::get_and_decode(vp, WVC.to_string())?;
+        let context: Option<String> = Self::serialize_signed_fields(vp);
+
+        let mut witnesses: Map<String, Value> = Map::new();
+        let mut elements: Map<String, Value> = Map::new();
+        for (field, array_value) in witness_value_container {
+            let (witness, claim_value) = Self::split_witness_and_value(&array_value)?;
+            let element: Fr = Self::convert_claim_to_scalar(&field, &claim_value, context.as_deref());
+            witnesses.insert(field.clone(), Value::String(CsdJwtInstance::serialize_with_encoding(&witness, Encoding::Base64UrlNoPad)?));
+            elements.insert(field, Value::String(CsdJwtInstance::serialize_with_encoding(&element, Encoding::Base64UrlNoPad)?));
+        }
+
+        let mut artifacts: Map<String, Value> = Map::new();
+        artifacts.insert("accumulator".to_string(), Value::String(CsdJwtInstance::serialize_with_encoding(accumulator.value(), Encoding::Base64UrlNoPad)?));
+        artifacts.insert("witnesses".to_string(), Value::Object(witnesses));
+        artifacts.insert("elements".to_string(), Value::Object(elements));
+        artifacts.insert(SIGNED_FIELDS.to_string(), Value::Object(signed_fields(vp)));
+
+        Ok(artifacts)
+    }
+
+
+    /// Extracts the proof of every disclosed claim of a VC or VP: its accumulated element and its membership witness.
+    /// The accumulator value is shared by all the claims.
+    ///
+    /// # Arguments
+    /// * `vp` - Verifiable Credential or Verifiable Presentation.
+    ///
+    /// # Returns
+    /// This function returns the `shared` and `claims` objects of the audit document or a string containing an error in
+    /// case of failure.
+    pub fn claim_proofs(vp: &Map<String, Value>) -> Result<Map<String, Value>, String> {
+
+        let vp: &Map<String, Value> = Self::credential_or_presentation(vp)?;
+        let serialized_accumulator: String = Self::get_and_decode(vp, ACCUMULATOR.to_string())?;
+        let witness_value_container: Map<String, Value> = Self::get_and_decode(vp, WVC.to_string())?;
+        let context: Option<String> = Self::serialize_signed_fields(vp);
+
+        let mut shared: Map<String, Value> = Map::new();
+        shared.insert(ACCUMULATOR.to_string(), Value::String(serialized_accumulator));
+        shared.insert(SIGNED_FIELDS.to_string(), Value::Object(signed_fields(vp)));
+
+        let mut claims: Map<String, Value> = Map::new();
+        for (field, entry) in witness_value_container {
+            let (witness, value) = match (entry.get(0), entry.get(1)) {
+                (Some(witness), Some(value)) => { (witness.clone(), value.clone()) }
+                _ => { return Err(format!("Malformed entry for {field} in the value container.")) }
+            };
+            let mut proof: Map<String, Value> = Map::new();
+            proof.insert("witness".to_string(), witness);
+            proof.insert("element".to_string(), Value::String(CsdJwtInstance::serialize(&Self::convert_claim_to_scalar(&field, &value, context.as_deref()))?));
+            proof.insert(VALUE.to_string(), value);
+            claims.insert(field, Value::Object(proof));
+        }
+
+        Ok(claim_proofs_document(shared, claims))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{Map, Value};
+    use vb_accumulator::setup::Keypair;
+    use crate::common_data::{CommonData, VC};
+    use crate::deterministic::ark_rng;
+    use crate::sd_algorithms::accumulators::csd_jwt::CsdJwtInstance;
+    use crate::sd_algorithms::accumulators::csd_jwt_bls12_381::CsdJwtBls12381Instance;
+
+    #[test]
+    fn bls12_381() -> Result<(), String> {
+        let raw_vc: Map<String, Value> = match serde_json::from_str::<Map<String, Value>>(VC) {
+            Ok(vc) => { vc }
+            Err(err) => { return Err(format!("Failed to parse Raw Verifiable Credential. [{err}]")); }
+        };
+        let (holder_public_key, holder_private_key) = CommonData::holder_keys()?;
+        let (params, Keypair { ref secret_key, ref public_key }) = CsdJwtBls12381Instance::initialize_params(&mut ark_rng());
+
+        let (vc, _) = CsdJwtBls12381Instance::issue_vc(&raw_vc, secret_key, &params)?;
+        CsdJwtBls12381Instance::verify_vc(&vc, public_key, &params)?;
+
+        let (_, vp_jwt) = CsdJwtBls12381Instance::issue_vp(&vc, &vec!["name".to_string(), "birthdate".to_string()], None, None, &holder_private_key)?;
+        CsdJwtBls12381Instance::verify_vp(&vp_jwt, public_key, &holder_public_key, &params)?;
+
+        // BLS12-381 witnesses are compressed G1 points of 48 bytes, instead of 32 for BN254.
+        let artifacts = CsdJwtBls12381Instance::proof_artifacts(&vc)?;
+        assert_eq!(artifacts["accumulator"].as_str().map(str::len), Some(64));
+
+        let (_, other_keypair) = CsdJwtBls12381Instance::initialize_params(&mut ark_rng());
+        assert!(CsdJwtBls12381Instance::verify_vp(&vp_jwt, &other_keypair.public_key, &holder_public_key, &params).is_err(), "VP verified with another accumulator key.");
+        assert!(CsdJwtInstance::serialize(public_key)?.len() > CsdJwtInstance::serialize(&CsdJwtInstance::initialize_params(&mut ark_rng()).1.public_key)?.len());
+
+        Ok(())
+    }
+}
@@ -1 +1,2 @@
-pub mod csd_jwt;
\ No newline at end of file
+pub mod csd_jwt;
+pub mod csd_jwt_bls12_381;
\ No newline at end of file
@@ -0,0 +1,98 @@
+use serde_json::{Map, Value};
+use crate::sd_algorithms::sd_algorithm::SdAlgorithm;
+use crate::verifier_config::check_all;
+
+pub use crate::primitives::bind_claim;
+
+/// Elements of the protected claims of a credential, in canonical order, and their openings, by name.
+pub type ProtectedClaims<E> = (Vec<E>, Map<String, Value>);
+
+/// Trait that isolates how an algorithm protects a single claim: the element the issuer commits to for every claim
+/// (a salted hash, a Merkle leaf, an accumulator element or a BBS+ message) and the opening, if any, that the holder
+/// keeps and reveals along with the claim. Issuance, presentation and verification iterate over the claims through the
+/// functions of this trait, so that the handling of the claim set is shared by all the algorithms.
+pub trait ClaimProtector: SdAlgorithm {
+
+    /// Element the issuer commits to for every claim.
+    type Element;
+
+    /// Generates the opening of a new claim, e.g. its salt, or None if the algorithm commits to the claims without
+    /// randomness.
+    fn open_claim() -> Option<String> {
+        None
+    }
+
+
+    /// Whether the algorithm protects a claim at all. Claims that are not protected are left out of the commitment and
+    /// cannot be disclosed.
+    ///
+    /// # Arguments
+    /// * `value` - Value of the claim.
+    fn protects(_value: &Value) -> bool {
+        true
+    }
+
+
+    /// Maps a claim and its opening to the element the issuer commits to, derived from `bind_claim` so that the element
+    /// binds the name of the claim.
+    ///
+    /// # Arguments
+    /// * `name` - Name of the claim.
+    /// * `value` - Value of the claim.
+    /// * `opening` - Opening of the claim, as generated by `open_claim`.
+    ///
+    /// # Returns
+    /// Returns a result containing the element or a string representing an error.
+    fn protect_claim(name: &str, value: &Value, opening: Option<&String>) -> Result<Self::Element, String>;
+
+
+    /// Protects every claim of a credential at issuance, in canonical order, generating their openings.
+    ///
+    /// # Arguments
+    /// * `claims` - Claims of the credential.
+    ///
+    /// # Returns
+    /// Returns a result containing the elements and the openings of the protected claims, by name, or a string
+    /// representing an error.
+    fn protect_claims(claims: &Map<String, Value>) -> Result<ProtectedClaims<Self::Element>, String> {
+        let mut openings: Map<String, Value> = Map::new();
+        for (name, value) in Self::canonical_claims(claims) {
+            if !Self::protects(value) {
+                continue
+            }
+            if let Some(opening) = Self::open_claim() {
+                openings.insert(name.clone(), Value::String(opening));
+            }
+        }
+
+        let elements: Vec<Self::Element> = Self::protected_elements(claims, &openings)?;
+        Ok((elements, openings))
+    }
+
+
+    /// Recomputes the elements of claims whose openings are known, in canonical order, e.g. to verify the disclosed
+    /// claims of a presentation. Every claim is processed before reporting a failure, so that the time taken does not
+    /// reveal which claim failed.
+    ///
+    /// # Arguments
+    /// * `claims` - Claims to be protected.
+    /// * `openings` - Openings of the claims, by name.
+    ///
+    /// # Returns
+    /// Returns a result containing the elements of the protected claims or a string representing an error.
+    fn protected_elements(claims: &Map<String, Value>, openings: &Map<String, Value>) -> Result<Vec<Self::Element>, String> {
+        let mut elements: Vec<Self::Element> = vec![];
+        let checks = Self::canonical_claims(claims).into_iter().filter(|(_, value)| Self::protects(value)).map(|(name, value)| {
+            let opening: Option<&String> = match openings.get(name) {
+                None => { None }
+                Some(Value::String(opening)) => { Some(opening) }
+                Some(_) => { return Err(format!("Opening of claim {name} is not a string")) }
+            };
+            elements.push(Self::protect_claim(name, value, opening)?);
+            Ok(())
+        });
+        check_all(checks)?;
+
+        Ok(elements)
+    }
+}
@@ -1,22 +1,51 @@
+use std::collections::HashSet;
 use josekit::jws::ES256;
-use rand::Rng;
+use crate::deterministic::random_bytes;
+use crate::sd_algorithms::sd_algorithm::SdAlgorithm;
 
 /// Trait that implements several methods shared across different algorithm instances.
-pub trait HashSdAlgorithm {
+pub trait HashSdAlgorithm: SdAlgorithm {
 
     /// Salt dimension in bytes.
     const SALT_DIMENSION: usize = 16;   // 16 u8 = 16 * 8 = 128 bits
 
+    /// Digest dimension in bytes.
+    const DIGEST_DIMENSION: usize = 32;  // SHA-256
+
     /// A function to randomly generate salts.
     ///
     /// # Returns
     /// The vector of salts created.
     fn generate_random_salt() -> String {
-        let mut bytes = vec![0; Self::SALT_DIMENSION];
-        let mut rng = rand::rng();
+        Self::ENCODING.encode(random_bytes(Self::SALT_DIMENSION))
+    }
+
+    /// Checks that a list of claim digests committed by a holder is well formed before the issuer signs it without
+    /// seeing the claims: every digest must be a SHA-256 output, no digest may repeat and the list must respect the
+    /// claim limits of the verifiers.
+    ///
+    /// # Arguments
+    /// * `digests` - Encoded digests of the claims.
+    ///
+    /// # Returns
+    /// Returns the decoded digests, or a string containing an error in case the list is malformed.
+    fn check_digests(digests: &[String]) -> Result<Vec<Vec<u8>>, String> {
+        Self::VERIFIER_CONFIG.check_claims_len(digests.len())?;
+
+        let mut decoded_digests: Vec<Vec<u8>> = Vec::with_capacity(digests.len());
+        let mut committed: HashSet<Vec<u8>> = HashSet::with_capacity(digests.len());
+        for digest in digests {
+            let decoded_digest: Vec<u8> = Self::ENCODING.decode(digest)?;
+            if decoded_digest.len() != Self::DIGEST_DIMENSION {
+                return Err(format!("Digest {digest} is not {} bytes long", Self::DIGEST_DIMENSION));
+            }
+            if !committed.insert(decoded_digest.clone()) {
+                return Err(format!("Digest {digest} is committed more than once"));
+            }
+            decoded_digests.push(decoded_digest);
+        }
 
-        rng.fill(&mut bytes[..]);
-        multibase::Base::Base64Url.encode(bytes)
+        Ok(decoded_digests)
     }
 
     /// Given an array of bytes to be signed, and a private key, returns a ES256 signature.
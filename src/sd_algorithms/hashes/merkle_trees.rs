@@ -1,8 +1,18 @@
 use rs_merkle::{Hasher, MerkleProof, MerkleTree};
-use rs_merkle::algorithms::Sha256;
 use serde_json::{Map, Value};
+use crate::audit::{claim_proofs_document, SIGNED_FIELDS, VALUE};
+use crate::primitives::{merkle_leaf, verify_merkle_proof};
+use crate::cost_profile::{record, Operation};
+use crate::credential_ir::CredentialIr;
+use crate::encoding::Encoding;
+use crate::hashing::sha256;
+use crate::holder_binding::HolderBinding;
+use crate::presentation_validity::PresentationValidity;
 use crate::sd_algorithms::hashes::hash_sd_algorithm::HashSdAlgorithm;
-use crate::sd_algorithms::sd_algorithm::SdAlgorithm;
+use crate::sd_algorithms::claim_protector::ClaimProtector;
+use crate::sd_algorithms::sd_algorithm::{signed_fields, PresentationBatch, SdAlgorithm};
+use crate::sd_algorithms::verification_cache::VerificationCache;
+use crate::verifier_config::VerifierConfig;
 
 /// Identifier for the root of the merkle tree as a field of the VC/VP.
 const ROOT: &str = "root";
@@ -20,31 +30,65 @@ const DISCLOSED_INDICES: &str = "disclosed_indices";
 const HASH_LEN: usize = 32;
 
 
+/// SHA-256 hasher of the leaves and nodes of the Merkle trees, counting its invocations in the cost profile. It hashes
+/// like the `Sha256Hasher` of the no_std core, so that the trees verify on either side.
+#[derive(Clone)]
+struct CountingSha256;
+
+impl Hasher for CountingSha256 {
+    type Hash = [u8; HASH_LEN];
+
+    fn hash(data: &[u8]) -> Self::Hash {
+        record(Operation::Hash, 1);
+        sha256(data)
+    }
+}
+
+
 /// Struct to contain an instance of the Merkle Tree algorithm for selective disclosure.
 pub struct MerkleTreeInstance;
 
 impl SdAlgorithm for MerkleTreeInstance {
     const ALGORITHM: &'static str = "MERKLE";
+    const PROOF_FIELDS: &'static [&'static str] = &[ROOT, LEN, SALTS, ROOT_SIGNATURE];
 }
 
 impl HashSdAlgorithm for MerkleTreeInstance {}
 
+impl ClaimProtector for MerkleTreeInstance {
+    type Element = [u8; HASH_LEN];
+
+    fn open_claim() -> Option<String> {
+        Some(Self::generate_random_salt())
+    }
+
+    fn protect_claim(name: &str, value: &Value, opening: Option<&String>) -> Result<[u8; HASH_LEN], String> {
+        let claim = match value {
+            Value::String(claim) => claim,
+            _ => return Err(format!("Claim in key {} is not a string", name))
+        };
+        let salt = match opening {
+            Some(salt) => salt,
+            None => return Err(format!("Salt {} not found in claims", name))
+        };
+
+        Ok(Self::map_key_value_to_sha256(name, claim, salt))
+    }
+}
+
 impl MerkleTreeInstance {
 
-    /// A simple function to map key-value pairs to a string before passing it to a SHA256 hashing algorithm instance.
+    /// A simple function to map a salted key-value pair to a string before passing it to a SHA256 hashing algorithm instance.
     ///
     /// # Arguments
     /// * `key` - Name of the element.
     /// * `value` - Value of the element.
+    /// * `salt` - Salt of the element.
     ///
     /// # Returns
-    /// Returns the hash of the concatenation of key-value.
-    fn map_key_value_to_sha256(key: String, value: String) -> [u8; HASH_LEN] {
-        let mut result = key.clone();
-        result.push(':');
-        result.push_str(value.as_str());
-
-        Sha256::hash(result.as_bytes())
+    /// Returns the hash of the encoding of key, value and salt.
+    fn map_key_value_to_sha256(key: &str, value: &str, salt: &str) -> [u8; HASH_LEN] {
+        merkle_leaf::<CountingSha256>(key, value, salt)
     }
 
 
@@ -57,29 +101,7 @@ impl MerkleTreeInstance {
     /// # Returns
     /// A vector containing the hashes of the leaves encoded as byte arrays.
     fn convert_claims_and_salts_to_leaves(claims: &Map<String, Value>, salts: &Map<String, Value>) -> Result<Vec<[u8; HASH_LEN]>, String> {
-        let mut leaves = vec![];
-
-        for (key, claim) in claims {
-            let claim = match claim {
-                Value::String(claim) => claim.clone(),
-                _ => return Err(format!("Claim in key {} is not a string", key))
-            };
-
-            let salt_value = match salts.get(key) {
-                Some(salt) => salt.clone(),
-                _ => return Err(format!("Salt {} not found in claims", key))
-            };
-
-            let salt = match salt_value {
-                Value::String(salt) => salt.clone(),
-                _ => return Err(format!("Salt {} is not a string", key))
-            };
-
-            claim.clone().push_str(salt.as_str());
-            leaves.push(Self::map_key_value_to_sha256(key.clone(), claim));
-        }
-
-        Ok(leaves)
+        Self::protected_elements(claims, salts)
     }
 
     /// Filters the VC or VP passed as input to only include the salts corresponding to the
@@ -95,15 +117,10 @@ impl MerkleTreeInstance {
 
         let salts: &Map<String, Value> = &Self::get_and_decode(map, SALTS.to_string())?;
         let mut disclosed_salts: Map<String, Value> = Map::new();
-        let mut disclosed_indices: Vec<usize> = vec![];
-
-        'disclosure_loop: for disclosure in disclosures {
-            for (i, (key, value)) in salts.iter().enumerate() {
-                if *key == *disclosure {
-                    disclosed_salts.insert(key.clone(), value.clone());
-                    disclosed_indices.push(i);
-                    continue 'disclosure_loop;
-                }
+
+        for disclosure in disclosures {
+            if let Some(salt) = salts.get(disclosure) {
+                disclosed_salts.insert(disclosure.clone(), salt.clone());
             }
         }
 
@@ -118,14 +135,15 @@ impl MerkleTreeInstance {
     /// # Arguments
     /// * `map` - Key-Value map of either the VC or the VP containing the root and its signature.
     /// * `issuer_public_key` - Issuer's public key to verify the signature with.
+    /// * `config` - Limits enforced on the root and its signature.
     ///
     /// # Returns
     /// Returns a result containing the verified root of the merkle tree.
-    fn verify_root_signature(map: &Map<String, Value>, issuer_public_key: &impl AsRef<[u8]>) -> Result<Vec<u8>, String> {
-        let serialized_merkle_root: [u8; HASH_LEN] = Self::get_and_decode(map, ROOT.to_string())?;
-        let root_signature: Vec<u8> = Self::get_and_decode(map, ROOT_SIGNATURE.to_string())?;
+    fn verify_root_signature(map: &Map<String, Value>, issuer_public_key: &impl AsRef<[u8]>, config: &VerifierConfig) -> Result<Vec<u8>, String> {
+        let serialized_merkle_root: [u8; HASH_LEN] = Self::get_and_decode_with_config(map, ROOT.to_string(), config)?;
+        let root_signature: Vec<u8> = Self::get_and_decode_with_config(map, ROOT_SIGNATURE.to_string(), config)?;
 
-        Self::verify_signature(serialized_merkle_root.as_slice(), &root_signature, issuer_public_key)?;
+        Self::verify_signature(&Self::signed_bytes(serialized_merkle_root.as_slice(), map), &root_signature, issuer_public_key)?;
         Ok(serialized_merkle_root.to_vec())
     }
 
@@ -138,7 +156,7 @@ impl MerkleTreeInstance {
     /// # Returns
     /// Returns the root of the merkle tree.
     fn derive_root_from_leaves(leaves: &Vec<[u8; HASH_LEN]>) -> Result<[u8; HASH_LEN], String> {
-        let merkle_tree = MerkleTree::<Sha256>::from_leaves(&leaves);
+        let merkle_tree = MerkleTree::<CountingSha256>::from_leaves(leaves);
         match merkle_tree.root() {
             None => { Err("Could not retrieve root of Merkle Trees".to_string()) }
             Some(root) => { Ok(root) }
@@ -156,21 +174,17 @@ impl MerkleTreeInstance {
     /// Returns a VC both in the form of a Map and in the form of an unsigned JWT.
     pub fn issue_vc(raw_vc: &Map<String, Value>, issuer_private_key: &impl AsRef<[u8]>) -> Result<(Map<String, Value>, String), String> {
 
-        let mut vc = raw_vc.clone();
+        let credential: CredentialIr = Self::lower(raw_vc)?;
+        let mut vc = credential.to_vc();
 
-        let claims: &Map<String, Value> = Self::extract_claims(&vc)?;
-        let salts: &Map<String, Value> = &claims.into_iter().map(|(key, _)|{
-            (key.clone(), Value::String(Self::generate_random_salt()))
-        }).collect();
-
-        let leaves = Self::convert_claims_and_salts_to_leaves(claims, salts)?;
+        let (leaves, salts) = Self::protect_claims(credential.claims())?;
         let merkle_root: [u8; HASH_LEN] = Self::derive_root_from_leaves(&leaves)?;
 
         Self::serialize_and_insert(&mut vc, ROOT.to_string(), &merkle_root)?;
         Self::serialize_and_insert(&mut vc, LEN.to_string(), &leaves.len())?;
         Self::serialize_and_insert(&mut vc, SALTS.to_string(), &salts)?;
         
-        let signature: Vec<u8> = Self::derive_signature(merkle_root.as_slice(), issuer_private_key)?;
+        let signature: Vec<u8> = Self::derive_signature(&Self::signed_bytes(merkle_root.as_slice(), &vc), issuer_private_key)?;
 
         Self::serialize_and_insert(&mut vc, ROOT_SIGNATURE.to_string(), &signature)?;
         let json_credential = Self::encode_jwt(&vc)?;
@@ -179,6 +193,78 @@ impl MerkleTreeInstance {
     }
 
 
+    /// Holder side of the issuance over pre-hashed claims: salts and hashes the claims of a raw VC into the leaves of
+    /// the Merkle tree, so that only the leaves have to be sent to the issuer.
+    ///
+    /// # Arguments
+    /// * `raw_vc` - Template VC containing the claims of the holder.
+    ///
+    /// # Returns
+    /// Returns the encoded leaves to be signed by the issuer and the salts opening them, to be kept by the holder, or a
+    /// string containing an error in case of failure.
+    pub fn commit_claims(raw_vc: &Map<String, Value>) -> Result<(Vec<String>, Map<String, Value>), String> {
+
+        let credential: CredentialIr = Self::lower(raw_vc)?;
+        let (leaves, salts) = Self::protect_claims(credential.claims())?;
+
+        Ok((leaves.iter().map(|leaf| Self::ENCODING.encode(leaf)).collect(), salts))
+    }
+
+
+    /// Issuer side of the issuance over pre-hashed claims: builds the Merkle tree over the leaves committed by the
+    /// holder after checking that they are well formed, and signs its root, together with the signed fields of the
+    /// credential, without ever seeing the claims.
+    ///
+    /// # Arguments
+    /// * `leaves` - Encoded leaves committed by the holder.
+    /// * `signed_fields` - Signed fields of the credential, e.g. its `cnf` claim.
+    /// * `issuer_private_key` - Private key of the issuer used to generate the signature of the root.
+    ///
+    /// # Returns
+    /// Returns the signature of the root or a string containing an error in case of failure.
+    pub fn sign_digests(leaves: &[String], signed_fields: &Map<String, Value>, issuer_private_key: &impl AsRef<[u8]>) -> Result<Vec<u8>, String> {
+
+        let mut decoded_leaves: Vec<[u8; HASH_LEN]> = Vec::with_capacity(leaves.len());
+        for leaf in Self::check_digests(leaves)? {
+            match leaf.try_into() {
+                Ok(leaf) => { decoded_leaves.push(leaf) }
+                Err(_) => { return Err(format!("Leaf is not {HASH_LEN} bytes long")) }
+            }
+        }
+        let merkle_root: [u8; HASH_LEN] = Self::derive_root_from_leaves(&decoded_leaves)?;
+
+        Self::derive_signature(&Self::signed_bytes(merkle_root.as_slice(), signed_fields), issuer_private_key)
+    }
+
+
+    /// Holder side of the issuance over pre-hashed claims: assembles the VC from the raw VC, the salts returned by
+    /// `commit_claims` and the signature of the issuer.
+    ///
+    /// # Arguments
+    /// * `raw_vc` - Template VC passed to `commit_claims`.
+    /// * `salts` - Salts returned by `commit_claims`.
+    /// * `signature` - Signature of the root returned by the issuer.
+    ///
+    /// # Returns
+    /// Returns a VC both in the form of a Map and in the form of an unsigned JWT.
+    pub fn assemble_vc(raw_vc: &Map<String, Value>, salts: &Map<String, Value>, signature: &[u8]) -> Result<(Map<String, Value>, String), String> {
+
+        let credential: CredentialIr = CredentialIr::from_raw_vc(&Self::group_claims(raw_vc)?)?;
+        let mut vc = credential.to_vc();
+        let claims: &Map<String, Value> = credential.claims();
+        let leaves = Self::convert_claims_and_salts_to_leaves(claims, salts)?;
+        let merkle_root: [u8; HASH_LEN] = Self::derive_root_from_leaves(&leaves)?;
+
+        Self::serialize_and_insert(&mut vc, ROOT.to_string(), &merkle_root)?;
+        Self::serialize_and_insert(&mut vc, LEN.to_string(), &leaves.len())?;
+        Self::serialize_and_insert(&mut vc, SALTS.to_string(), salts)?;
+        Self::serialize_and_insert(&mut vc, ROOT_SIGNATURE.to_string(), signature)?;
+        let json_credential = Self::encode_jwt(&vc)?;
+
+        Ok((vc, json_credential))
+    }
+
+
     /// Given a VC, verify it using all the necessary data.
     ///
     /// # Arguments
@@ -188,19 +274,33 @@ impl MerkleTreeInstance {
     /// # Returns
     /// Returns a string containing an error in case of failure.
     pub fn verify_vc(vc: &Map<String, Value>, issuer_public_key: &impl AsRef<[u8]>) -> Result<(), String> {
+        Self::verify_vc_with_config(vc, issuer_public_key, &Self::VERIFIER_CONFIG)
+    }
+
+
+    /// Same as `verify_vc`, but enforcing the limits of the verifier on the amount of claims and on the encoded elements
+    /// of the VC.
+    ///
+    /// # Arguments
+    /// * `vc` - Verifiable Credential.
+    /// * `issuer_public_key` - Issuer's public key to verify the signature of the merkle tree.
+    /// * `config` - Limits enforced on the VC.
+    ///
+    /// # Returns
+    /// Returns a string containing an error in case of failure.
+    pub fn verify_vc_with_config(vc: &Map<String, Value>, issuer_public_key: &impl AsRef<[u8]>, config: &VerifierConfig) -> Result<(), String> {
 
         let claims: &Map<String, Value> = Self::extract_claims(vc)?;
-        let salts: &Map<String, Value> = &Self::get_and_decode(vc, SALTS.to_string())?;
+        config.check_claims_len(claims.len())?;
+        let salts: &Map<String, Value> = &Self::get_and_decode_with_config(vc, SALTS.to_string(), config)?;
         let leaves: Vec<[u8; HASH_LEN]> = Self::convert_claims_and_salts_to_leaves(claims, salts)?;
         let computed_root: [u8; HASH_LEN] = Self::derive_root_from_leaves(&leaves)?;
-        let vc_root: [u8; HASH_LEN] = Self::derive_root_from_leaves(&leaves)?;
+        let vc_root: Vec<u8> = Self::verify_root_signature(vc, issuer_public_key, config)?;
 
-        if computed_root != vc_root {
+        if computed_root.as_slice() != vc_root.as_slice() {
             return Err(format!("Root in vc and root computed do not match {:?} - {:?}", computed_root, vc_root))
         }
 
-        Self::verify_root_signature(&vc, issuer_public_key)?;
-
         Ok(())
     }
 
@@ -210,29 +310,164 @@ impl MerkleTreeInstance {
     /// # Arguments
     /// * `vc` - Verifiable Credential.
     /// * `disclosures` - List of strings containing the names of the claims that are to be disclosed.
-    /// * `holder_private_key` - Holder's private key necessary for proof of possession.
+    /// * `holder_binding` - Holder binding signing the VP as proof of possession.
     ///
     /// # Returns
     /// Returns the VP both in form of a Map and in form of a signed JWT.
-    pub fn issue_vp(vc: &Map<String, Value>, disclosures: &Vec<String>, holder_private_key: &impl AsRef<[u8]>) -> Result<(Map<String, Value>, String), String> {
+    pub fn issue_vp(vc: &Map<String, Value>, disclosures: &[String], holder_binding: &(impl HolderBinding + ?Sized)) -> Result<(Map<String, Value>, String), String> {
+        Self::issue_vp_for_audience(vc, disclosures, None, None, holder_binding)
+    }
+
+
+    /// Same as `issue_vp`, but binding the presentation to an optional audience, as required to disclose sensitive claims.
+    ///
+    /// # Arguments
+    /// * `vc` - Verifiable Credential.
+    /// * `disclosures` - List of strings containing the names of the claims that are to be disclosed.
+    /// * `audience` - Optional intended audience of the presentation.
+    /// * `validity` - Optional validity window of the presentation.
+    /// * `holder_binding` - Holder binding signing the VP as proof of possession.
+    ///
+    /// # Returns
+    /// Returns the VP both in form of a Map and in form of a signed JWT.
+    pub fn issue_vp_for_audience(vc: &Map<String, Value>, disclosures: &[String], audience: Option<String>, validity: Option<PresentationValidity>, holder_binding: &(impl HolderBinding + ?Sized)) -> Result<(Map<String, Value>, String), String> {
 
         let mut vp: Map<String, Value> = vc.clone();
+        let disclosures: &Vec<String> = &Self::resolve_disclosures(vc, disclosures)?;
         let claims: &Map<String, Value> = Self::extract_claims(vc)?;
         let salts: &Map<String, Value> = &Self::get_and_decode(vc, SALTS.to_string())?;
         let leaves: Vec<[u8; HASH_LEN]> = Self::convert_claims_and_salts_to_leaves(claims, salts)?;
-        let merkle_tree: MerkleTree<Sha256> = MerkleTree::from_leaves(leaves.as_slice());
+        let merkle_tree: MerkleTree<CountingSha256> = MerkleTree::from_leaves(leaves.as_slice());
 
         Self::filter_salts_by_disclosure_and_insert(&mut vp, disclosures)?;
         let disclosed_indices = Self::filter_claims_by_disclosure_and_insert(&mut vp, disclosures)?;
 
-        let merkle_proof: MerkleProof<Sha256> = merkle_tree.proof(&disclosed_indices);
+        let merkle_proof: MerkleProof<CountingSha256> = merkle_tree.proof(&disclosed_indices);
         let proof_bytes = merkle_proof.to_bytes();
 
         Self::serialize_and_insert(&mut vp, MERKLE_PROOF.to_string(), &proof_bytes)?;
         Self::serialize_and_insert(&mut vp, DISCLOSED_INDICES.to_string(), &disclosed_indices)?;
-        let jwt = Self::encode_and_sign_jwt(&mut vp, &holder_private_key)?;
+        Self::encode_and_sign_presentation_for_audience(vp, audience, validity, holder_binding)
+    }
 
-        Ok((vp, jwt))
+
+    /// Given a VC, and several sets of disclosures, create one Verifiable Presentation per set building the Merkle tree only once.
+    ///
+    /// # Arguments
+    /// * `vc` - Verifiable Credential.
+    /// * `disclosure_sets` - Lists of strings containing the names of the claims that are to be disclosed in each VP.
+    /// * `holder_binding` - Holder binding signing the VP as proof of possession.
+    ///
+    /// # Returns
+    /// Returns the VPs both in form of a Map and in form of a signed JWT.
+    pub fn issue_vp_batch(vc: &Map<String, Value>, disclosure_sets: &[Vec<String>], holder_binding: &(impl HolderBinding + ?Sized)) -> Result<PresentationBatch, String> {
+
+        let claims: &Map<String, Value> = Self::extract_claims(vc)?;
+        let salts: Map<String, Value> = Self::get_and_decode(vc, SALTS.to_string())?;
+        let leaves: Vec<[u8; HASH_LEN]> = Self::convert_claims_and_salts_to_leaves(claims, &salts)?;
+        let merkle_tree: MerkleTree<CountingSha256> = MerkleTree::from_leaves(leaves.as_slice());
+        let mut vps: Vec<(Map<String, Value>, String)> = Vec::with_capacity(disclosure_sets.len());
+
+        for disclosures in disclosure_sets {
+            let mut vp: Map<String, Value> = vc.clone();
+            let disclosures: &Vec<String> = &Self::resolve_disclosures(vc, disclosures)?;
+            let disclosed_salts: Map<String, Value> = disclosures
+                .iter()
+                .filter_map(|key| salts.get(key).map(|value| (key.clone(), value.clone())))
+                .collect();
+
+            Self::serialize_and_insert(&mut vp, SALTS.to_string(), &disclosed_salts)?;
+            let disclosed_indices = Self::filter_claims_by_disclosure_and_insert(&mut vp, disclosures)?;
+
+            let proof_bytes = merkle_tree.proof(&disclosed_indices).to_bytes();
+
+            Self::serialize_and_insert(&mut vp, MERKLE_PROOF.to_string(), &proof_bytes)?;
+            Self::serialize_and_insert(&mut vp, DISCLOSED_INDICES.to_string(), &disclosed_indices)?;
+            vps.push(Self::encode_and_sign_presentation(vp, holder_binding)?);
+        }
+
+        Ok(vps)
+    }
+
+
+    /// Extracts the signed root, and, for VPs, the multi-proof with the disclosed indices and leaves from a VC or VP.
+    ///
+    /// # Arguments
+    /// * `vp` - Verifiable Credential or Verifiable Presentation.
+    ///
+    /// # Returns
+    /// Returns a map containing the artifacts or a string containing an error in case of failure.
+    pub fn proof_artifacts(vp: &Map<String, Value>) -> Result<Map<String, Value>, String> {
+
+        let vp: &Map<String, Value> = Self::credential_or_presentation(vp)?;
+
+        let root: [u8; HASH_LEN] = Self::get_and_decode(vp, ROOT.to_string())?;
+        let root_signature: Vec<u8> = Self::get_and_decode(vp, ROOT_SIGNATURE.to_string())?;
+        let leaves_len: usize = Self::get_and_decode(vp, LEN.to_string())?;
+        let claims: &Map<String, Value> = Self::extract_claims(vp)?;
+        let salts: Map<String, Value> = Self::get_and_decode(vp, SALTS.to_string())?;
+        let leaves: Vec<Value> = Self::convert_claims_and_salts_to_leaves(claims, &salts)?
+            .iter()
+            .map(|leaf| Value::String(Encoding::Base64UrlNoPad.encode(leaf)))
+            .collect();
+
+        let mut artifacts: Map<String, Value> = Map::new();
+        artifacts.insert("root".to_string(), Value::String(Encoding::Base64UrlNoPad.encode(root)));
+        artifacts.insert("root_signature".to_string(), Value::String(Encoding::Base64UrlNoPad.encode(root_signature)));
+        artifacts.insert("leaves_len".to_string(), Value::from(leaves_len));
+        artifacts.insert("leaves".to_string(), Value::Array(leaves));
+        artifacts.insert(SIGNED_FIELDS.to_string(), Value::Object(signed_fields(vp)));
+
+        if vp.contains_key(MERKLE_PROOF) {
+            let proof_bytes: Vec<u8> = Self::get_and_decode(vp, MERKLE_PROOF.to_string())?;
+            let disclosed_indices: Vec<usize> = Self::get_and_decode(vp, DISCLOSED_INDICES.to_string())?;
+            artifacts.insert("proof".to_string(), Value::String(Encoding::Base64UrlNoPad.encode(proof_bytes)));
+            artifacts.insert("disclosed_indices".to_string(), Value::Array(disclosed_indices.into_iter().map(Value::from).collect()));
+        }
+
+        Ok(artifacts)
+    }
+
+
+    /// Extracts the proof of every disclosed claim of a VC or VP: its salt, leaf and leaf index. The signed root and, for
+    /// VPs, the multi-proof are shared by all the claims, as the multi-proof authenticates all the disclosed leaves at
+    /// once.
+    ///
+    /// # Arguments
+    /// * `vp` - Verifiable Credential or Verifiable Presentation.
+    ///
+    /// # Returns
+    /// Returns the `shared` and `claims` objects of the audit document or a string containing an error in case of failure.
+    pub fn claim_proofs(vp: &Map<String, Value>) -> Result<Map<String, Value>, String> {
+
+        let shared: Map<String, Value> = Self::proof_artifacts(vp)?;
+        let vp: &Map<String, Value> = Self::credential_or_presentation(vp)?;
+
+        let claims: &Map<String, Value> = Self::extract_claims(vp)?;
+        let salts: Map<String, Value> = Self::get_and_decode(vp, SALTS.to_string())?;
+        let leaves: Vec<[u8; HASH_LEN]> = Self::convert_claims_and_salts_to_leaves(claims, &salts)?;
+        let indices: Vec<usize> = if vp.contains_key(DISCLOSED_INDICES) {
+            Self::get_and_decode(vp, DISCLOSED_INDICES.to_string())?
+        } else {
+            (0..leaves.len()).collect()
+        };
+        // Leaves are computed from the protected claims in canonical order, as are the disclosed indices.
+        let protected_claims: Vec<(&String, &Value)> = Self::canonical_claims(claims).into_iter().filter(|(_, value)| Self::protects(value)).collect();
+        if protected_claims.len() != leaves.len() || indices.len() != leaves.len() {
+            return Err(format!("{} claims are disclosed with {} leaves and {} indices.", protected_claims.len(), leaves.len(), indices.len()));
+        }
+
+        let mut claim_proofs: Map<String, Value> = Map::new();
+        for (((name, value), leaf), index) in protected_claims.into_iter().zip(leaves).zip(indices) {
+            let mut proof: Map<String, Value> = Map::new();
+            proof.insert(VALUE.to_string(), value.clone());
+            proof.insert("salt".to_string(), salts.get(name).cloned().unwrap_or(Value::Null));
+            proof.insert("index".to_string(), Value::from(index));
+            proof.insert("leaf".to_string(), Value::String(Encoding::Base64UrlNoPad.encode(leaf)));
+            claim_proofs.insert(name.clone(), Value::Object(proof));
+        }
+
+        Ok(claim_proofs_document(shared, claim_proofs))
     }
 
 
@@ -241,26 +476,43 @@ impl MerkleTreeInstance {
     /// # Arguments
     /// * `jwt` - Verifiable Presentation encoded as a jwt.
     /// * `issuer_public_key` - Issuer's public key to verify the signature of the merkle tree.
-    /// * `holder_public_key` - Holder's public key to verify the proof of possession.
+    /// * `holder_binding` - Holder binding verifying the proof of possession.
     ///
     /// # Returns
     /// Returns a string containing an error in case of failure.
-    pub fn verify_vp(jwt: &String, issuer_public_key: &impl AsRef<[u8]>, holder_public_key: &impl AsRef<[u8]>) -> Result<(), String> {
+    pub fn verify_vp(jwt: &str, issuer_public_key: &impl AsRef<[u8]>, holder_binding: &(impl HolderBinding + ?Sized)) -> Result<(), String> {
+        Self::verify_vp_with_cache(jwt, issuer_public_key, holder_binding, None, &Self::VERIFIER_CONFIG)
+    }
+
 
-        let vp = Self::decode_and_verify_jwt(&jwt, &holder_public_key)?;
+    /// Given a VP, verify it skipping the Merkle proof if all the disclosed leaves were already verified against the same root.
+    ///
+    /// # Arguments
+    /// * `jwt` - Verifiable Presentation encoded as a jwt.
+    /// * `issuer_public_key` - Issuer's public key to verify the signature of the merkle tree.
+    /// * `holder_binding` - Holder binding verifying the proof of possession.
+    /// * `cache` - Optional cache keyed by (Merkle root, leaf hash).
+    /// * `config` - Limits enforced on the VP.
+    ///
+    /// # Returns
+    /// Returns a string containing an error in case of failure.
+    pub fn verify_vp_with_cache(jwt: &str, issuer_public_key: &impl AsRef<[u8]>, holder_binding: &(impl HolderBinding + ?Sized), cache: Option<&VerificationCache>, config: &VerifierConfig) -> Result<(), String> {
+
+        let vp = Self::decode_and_verify_presentation(jwt, holder_binding, config)?;
         let disclosed_claims = Self::extract_claims(&vp)?;
-        let disclosed_salts = &Self::get_and_decode(&vp, SALTS.to_string())?;
+        config.check_claims_len(disclosed_claims.len())?;
+        let disclosed_salts = &Self::get_and_decode_with_config(&vp, SALTS.to_string(), config)?;
 
-        let proof_bytes: Vec<u8> = Self::get_and_decode(&vp, MERKLE_PROOF.to_string())?;
-        let proof: MerkleProof<Sha256> = match MerkleProof::from_bytes(proof_bytes.as_slice()) {
-            Ok(proof) => { proof }
-            Err(err) => { return Err(format!("Could not decode proof from bytes: [{err}]")) }
-        };
+        let proof_bytes: Vec<u8> = Self::get_and_decode_with_config(&vp, MERKLE_PROOF.to_string(), config)?;
 
-        let disclosed_indices: Vec<usize> = Self::get_and_decode(&vp, DISCLOSED_INDICES.to_string())?;
-        let leaves_len: usize = Self::get_and_decode(&vp, LEN.to_string())?;
+        let disclosed_indices: Vec<usize> = Self::get_and_decode_with_config(&vp, DISCLOSED_INDICES.to_string(), config)?;
+        let leaves_len: usize = Self::get_and_decode_with_config(&vp, LEN.to_string(), config)?;
+        config.check_claims_len(leaves_len)?;
         let disclosed_leaves = Self::convert_claims_and_salts_to_leaves(&disclosed_claims, &disclosed_salts)?;
-        let merkle_root_vec: Vec<u8> = Self::verify_root_signature(&vp, issuer_public_key)?;
+        if disclosed_indices.len() != disclosed_leaves.len() {
+            return Err(format!("VP discloses {} claims but {} indices.", disclosed_leaves.len(), disclosed_indices.len()));
+        }
+        let merkle_root_vec: Vec<u8> = Self::verify_root_signature(&vp, issuer_public_key, config)?;
         let mut merkle_root: [u8; HASH_LEN] = [0u8; HASH_LEN];
 
         if merkle_root_vec.len() != HASH_LEN {
@@ -271,21 +523,47 @@ impl MerkleTreeInstance {
             }
         }
 
-        if proof.verify(merkle_root, disclosed_indices.as_slice(), disclosed_leaves.as_slice(), leaves_len) {
-            Ok(())
-        } else {
-            Err("Proof verification failed.".to_string())
+        // An empty presentation only proves possession of a credential whose root was signed by the issuer.
+        if disclosed_indices.is_empty() {
+            return Ok(());
         }
 
+        let cache = match cache {
+            None => {
+                return verify_merkle_proof::<CountingSha256>(&proof_bytes, merkle_root, &disclosed_indices, &disclosed_leaves, leaves_len)
+            }
+            Some(cache) => { cache }
+        };
+
+        let scope: String = Encoding::Base64UrlNoPad.encode(merkle_root);
+        let digests: Vec<String> = disclosed_indices.iter().zip(disclosed_leaves.iter()).map(|(index, leaf)| {
+            format!("{index}:{}", Encoding::Base64UrlNoPad.encode(leaf))
+        }).collect();
+
+        let mut all_cached = true;
+        for digest in &digests {
+            all_cached &= cache.contains(&scope, digest);
+        }
+        if all_cached && digests.len() == disclosed_indices.len() {
+            return Ok(());
+        }
+
+        verify_merkle_proof::<CountingSha256>(&proof_bytes, merkle_root, &disclosed_indices, &disclosed_leaves, leaves_len)?;
+        for digest in &digests {
+            cache.insert(&scope, digest);
+        }
+        Ok(())
+
     }
 }
 
 
 #[cfg(test)]
 mod tests {
+    use log::debug;
     use serde_json::{Map, Value};
 
-    use crate::common_data::{CommonData, VC};
+    use crate::common_data::{CommonData, CLAIMS, VC};
 
     use super::*;
 
@@ -311,21 +589,96 @@ mod tests {
         };
 
         match MerkleTreeInstance::verify_vc(&vc, &issuer_public_key) {
-            Ok(_) => { println!("[Merkle] Successfully verified vc.")}
+            Ok(_) => { debug!("[Merkle] Successfully verified vc.")}
             Err(err) => { return Err(format!("[Merkle] Failed to verify vc [{err}]."))}
         };
 
-        let disclosures = vec!["name", "birthdate"].iter().map(|x| x.to_string()).collect();
+        let disclosures: Vec<String> = vec!["name", "birthdate"].iter().map(|x| x.to_string()).collect();
         let (_vp, vp_jwt) = match MerkleTreeInstance::issue_vp(&vc, &disclosures, &holder_private_key) {
             Ok(result) => { result }
             Err(err) => { return Err(format!("[Merkle] Failed to issue verifiable presentation: [{err}].")) }
         };
 
         match MerkleTreeInstance::verify_vp(&vp_jwt, &issuer_public_key, &holder_public_key) {
-            Ok(_) => { println!("[Merkle] Successfully verified vp.")}
+            Ok(_) => { debug!("[Merkle] Successfully verified vp.")}
             Err(err) => { return Err(format!("[Merkle] Failed to verify vp [{err}].")) }
         };
 
         Ok(())
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn tampered_vc() -> Result<(), String> {
+        let raw_vc: Map<String, Value> = serde_json::from_str(VC).map_err(|err| format!("[Merkle] Failed to parse Raw Verifiable Credential. [{err}]"))?;
+        let (issuer_public_key, issuer_private_key) = CommonData::issuer_keys()?;
+        let (vc, _jwt) = MerkleTreeInstance::issue_vc(&raw_vc, &issuer_private_key)?;
+
+        let mut tampered_vc: Map<String, Value> = vc.clone();
+        match tampered_vc.get_mut(CLAIMS) {
+            Some(Value::Object(claims)) => { claims.insert("name".to_string(), Value::String("Isaac Newton".to_string())); }
+            _ => { return Err("[Merkle] VC does not contain its claims.".to_string()) }
+        }
+        assert!(MerkleTreeInstance::verify_vc(&tampered_vc, &issuer_public_key).is_err(), "[Merkle] VC with a tampered claim was verified.");
+
+        Ok(())
+    }
+
+    #[test]
+    fn salted_leaves() -> Result<(), String> {
+        let raw_vc: Map<String, Value> = serde_json::from_str(VC).map_err(|err| format!("[Merkle] Failed to parse Raw Verifiable Credential. [{err}]"))?;
+        let (holder_public_key, holder_private_key) = CommonData::holder_keys()?;
+        let (issuer_public_key, issuer_private_key) = CommonData::issuer_keys()?;
+
+        // Two issuances of the same claims draw different salts, so none of their leaves match.
+        let (leaves, _salts) = MerkleTreeInstance::commit_claims(&raw_vc)?;
+        let (other_leaves, _other_salts) = MerkleTreeInstance::commit_claims(&raw_vc)?;
+        assert_eq!(leaves.len(), other_leaves.len());
+        assert!(leaves.iter().all(|leaf| !other_leaves.contains(leaf)), "[Merkle] Two issuances of the same claims share a leaf.");
+
+        // The leaf depends on the salt, not only on the name and the value of the claim.
+        let value: Value = Value::String("Albert Einstein".to_string());
+        let (salt, other_salt) = (MerkleTreeInstance::generate_random_salt(), MerkleTreeInstance::generate_random_salt());
+        let leaf = MerkleTreeInstance::protect_claim(&"name".to_string(), &value, Some(&salt))?;
+        assert_eq!(leaf, MerkleTreeInstance::protect_claim(&"name".to_string(), &value, Some(&salt))?);
+        assert_ne!(leaf, MerkleTreeInstance::protect_claim(&"name".to_string(), &value, Some(&other_salt))?);
+        assert!(MerkleTreeInstance::protect_claim(&"name".to_string(), &value, None).is_err());
+
+        // Presentations of salted credentials still verify.
+        let (vc, _jwt) = MerkleTreeInstance::issue_vc(&raw_vc, &issuer_private_key)?;
+        let (_vp, vp_jwt) = MerkleTreeInstance::issue_vp(&vc, &vec!["name".to_string(), "birthdate".to_string()], &holder_private_key)?;
+        MerkleTreeInstance::verify_vp(&vp_jwt, &issuer_public_key, &holder_public_key)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn pre_hashed_issuance() -> Result<(), String> {
+
+        let raw_vc: Map<String, Value> = match serde_json::from_str::<Map<String, Value>>(VC) {
+            Ok(vc) => { vc }
+            Err(err) => { return Err(format!("[Merkle] Failed to parse Raw Verifiable Credential from string. [{err}]")); }
+        };
+
+        let (holder_public_key, holder_private_key) = CommonData::holder_keys()?;
+        let (issuer_public_key, issuer_private_key) = CommonData::issuer_keys()?;
+
+        let (leaves, salts) = MerkleTreeInstance::commit_claims(&raw_vc)?;
+        let signature = MerkleTreeInstance::sign_digests(&leaves, &signed_fields(&raw_vc), &issuer_private_key)?;
+        let (vc, _jwt) = MerkleTreeInstance::assemble_vc(&raw_vc, &salts, &signature)?;
+        MerkleTreeInstance::verify_vc(&vc, &issuer_public_key)?;
+
+        let disclosures: Vec<String> = vec!["name".to_string()];
+        let (_vp, vp_jwt) = MerkleTreeInstance::issue_vp(&vc, &disclosures, &holder_private_key)?;
+        MerkleTreeInstance::verify_vp(&vp_jwt, &issuer_public_key, &holder_public_key)?;
+
+        // The issuer signed the leaves of different claims, so the resulting root does not match.
+        let mut other_raw_vc: Map<String, Value> = raw_vc.clone();
+        other_raw_vc.insert(CLAIMS.to_string(), serde_json::json!({ "name": "Mallory" }));
+        let (other_leaves, _) = MerkleTreeInstance::commit_claims(&other_raw_vc)?;
+        let other_signature = MerkleTreeInstance::sign_digests(&other_leaves, &signed_fields(&other_raw_vc), &issuer_private_key)?;
+        let (vc, _jwt) = MerkleTreeInstance::assemble_vc(&raw_vc, &salts, &other_signature)?;
+        assert!(MerkleTreeInstance::verify_vc(&vc, &issuer_public_key).is_err(), "[Merkle] VC assembled with the signature of other leaves was verified.");
+
+        Ok(())
+    }
+}
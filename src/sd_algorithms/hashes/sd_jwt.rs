@@ -1,9 +1,16 @@
 use crate::common_data::{SIGNATURE};
+use crate::primitives::{verify_salted_claims, SaltedClaim, DIGEST_LEN};
 use serde_json::{Map, Value};
-use digest::Digest;
-use sha2::Sha256;
+use crate::audit::{claim_proofs_document, shared_signed_fields, split_claim_proofs, SIGNED_FIELDS, VALUE};
+use crate::cost_profile::{record, Operation};
+use crate::credential_ir::CredentialIr;
+use crate::encoding::Encoding;
+use crate::holder_binding::HolderBinding;
+use crate::presentation_validity::PresentationValidity;
 use crate::sd_algorithms::hashes::hash_sd_algorithm::HashSdAlgorithm;
-use crate::sd_algorithms::sd_algorithm::SdAlgorithm;
+use crate::sd_algorithms::claim_protector::ClaimProtector;
+use crate::sd_algorithms::sd_algorithm::{signed_fields, PresentationBatch, SdAlgorithm};
+use crate::verifier_config::VerifierConfig;
 
 /// Name of the list of hashes as a field of the VC.
 const HASHES: &str = "hashes";
@@ -16,12 +23,58 @@ pub struct SdJwtInstance;
 
 impl SdAlgorithm for SdJwtInstance {
     const ALGORITHM: &'static str = "SD-JWT";
+    const PROOF_FIELDS: &'static [&'static str] = &[SIGNATURE, HASHES, SVC];
+
+    fn issued_claims(vc: &Map<String, Value>) -> Result<Map<String, Value>, String> {
+        let salt_value_container: Map<String, Value> = Self::get_and_decode(vc, SVC.to_string())?;
+        Self::container_values(&salt_value_container)
+    }
+
+    fn disclosed_claim_names(credential: &Map<String, Value>) -> Result<Vec<String>, String> {
+        let salt_value_container: Map<String, Value> = Self::get_and_decode(credential, SVC.to_string())?;
+        Ok(salt_value_container.keys().cloned().collect())
+    }
 }
 
 impl HashSdAlgorithm for SdJwtInstance {}
 
+impl ClaimProtector for SdJwtInstance {
+    type Element = String;
+
+    fn open_claim() -> Option<String> {
+        Some(Self::generate_random_salt())
+    }
+
+    // Only works with strings
+    fn protects(value: &Value) -> bool {
+        value.is_string()
+    }
+
+    fn protect_claim(name: &str, value: &Value, opening: Option<&String>) -> Result<String, String> {
+        match (value, opening) {
+            (Value::String(value), Some(salt)) => { Ok(Self::hash_from_value_and_salt(name, value, salt)) }
+            (Value::String(_), None) => { Err(format!("Claim {name} is missing from the salt value container")) }
+            _ => { Err("Either salts or values are not strings.".to_string()) }
+        }
+    }
+}
+
 impl SdJwtInstance {
 
+    /// Builds the Salt-Value Container of the protected claims, mapping every claim to its salt and its value.
+    ///
+    /// # Arguments
+    /// * `claims` - Claims of the credential.
+    /// * `salts` - Salts of the protected claims, as returned by `protect_claims`.
+    ///
+    /// # Returns
+    /// Returns the Salt-Value Container.
+    fn salt_value_container(claims: &Map<String, Value>, salts: &Map<String, Value>) -> Map<String, Value> {
+        salts.iter()
+            .filter_map(|(field, salt)| claims.get(field).map(|value| (field.clone(), Value::Array(vec![salt.clone(), value.clone()]))))
+            .collect()
+    }
+
     /// Function to map a claim name, a claim value and a salt into a hash.
     ///
     /// # Arguments
@@ -31,15 +84,11 @@ impl SdJwtInstance {
     ///
     /// # Returns
     /// Returns the hash encoded as a string.
-    fn hash_from_value_and_salt(key: &String, value: &String, salt: &String) -> String {
-        let mut hasher = Sha256::new();
-        let mut hasher_input = key.clone();
-
-        hasher_input.push_str(value.as_str());
-        hasher_input.push_str(salt.as_str());
-        hasher.update(hasher_input);
+    fn hash_from_value_and_salt(key: &str, value: &str, salt: &str) -> String {
+        record(Operation::Hash, 1);
+        let digest = SaltedClaim { name: key, value, salt }.digest();
 
-        let encoded_result = multibase::Base::Base64Url.encode(hasher.finalize());
+        let encoded_result = Self::ENCODING.encode(digest);
         encoded_result
     }
 
@@ -48,17 +97,21 @@ impl SdJwtInstance {
     /// # Arguments
     /// * `svc` - Salt-Value Container.
     /// * `hashes` - List of hashes that are to be matched with the hashes produced in this function.
+    /// * `config` - Limits enforced on the amount of claims.
     ///
     /// # Returns
     /// Returns a result containing an error string in case of failure.
-    fn verify_salt_value_container(svc: &Map<String, Value>, hashes_value: &Value) -> Result<(), String> {
-        fn decode_hashes_value(hashes_value: &Value) -> Result<Vec<String>, String> {
+    fn verify_salt_value_container(svc: &Map<String, Value>, hashes_value: &Value, config: &VerifierConfig) -> Result<(), String> {
+        fn decode_hashes_value(hashes_value: &Value) -> Result<Vec<[u8; DIGEST_LEN]>, String> {
 
             let mut hashes = vec![];
             if let Value::Array(array) = hashes_value {
                 for element in array {
                     if let Value::String(hash) = element {
-                        hashes.push(hash.clone());
+                        match SdJwtInstance::ENCODING.decode(hash).map(<[u8; DIGEST_LEN]>::try_from) {
+                            Ok(Ok(hash)) => { hashes.push(hash); }
+                            _ => { return Err(format!("Hash {hash} is not an encoded SHA-256 digest")); }
+                        }
                     } else {
                         return Err("Non-String element in hashes array".to_string());
                     }
@@ -70,38 +123,26 @@ impl SdJwtInstance {
             Ok(hashes)
         }
 
-        let hashes: Vec<String> = decode_hashes_value(&hashes_value)?;
+        let hashes: Vec<[u8; DIGEST_LEN]> = decode_hashes_value(hashes_value)?;
+        config.check_claims_len(hashes.len())?;
+        config.check_claims_len(svc.len())?;
 
+        let mut salted_claims: Vec<SaltedClaim> = Vec::with_capacity(svc.len());
         for (field, array_value) in svc {
-
-            if let Value::Array(array) = array_value {
-                let salt = match array.get(0) {
-                    None => { return Err("Salt not found in salt value container.".to_string()) }
-                    Some(key) => { key }
-                };
-                let value = match array.get(1) {
-                    None => { return Err("Value not found in salt value container.".to_string()) }
-                    Some(value) => { value }
-                };
-
-                match (salt, value) {
-                    (Value::String(salt), Value::String(value)) => {
-
-
-                        let hash = Self::hash_from_value_and_salt(field, value, salt);
-                        if !hashes.contains(&hash) {
-                            return Err("Hashes array does not contain hash".to_string());
-                        }
-                    }
-                    _ => { return Err("Either salts or values are not strings.".to_string())}
+            match array_value.as_array().map(|array| (array.first(), array.get(1))) {
+                Some((Some(Value::String(salt)), Some(Value::String(value)))) => {
+                    salted_claims.push(SaltedClaim { name: field, value, salt });
                 }
-
-            } else {
-                return Err("Error, array field in salt value container is not an array".to_string());
+                Some((None, _)) => { return Err("Salt not found in salt value container.".to_string()) }
+                Some((_, None)) => { return Err("Value not found in salt value container.".to_string()) }
+                Some(_) => { return Err("Either salts or values are not strings.".to_string()) }
+                None => { return Err("Error, array field in salt value container is not an array".to_string()) }
             }
         }
 
-        Ok(())
+        // The digests are checked by the no_std core, so that embedded verifiers accept exactly the same VPs.
+        record(Operation::Hash, salted_claims.len() as u64);
+        verify_salted_claims(&salted_claims, &hashes)
     }
 
 
@@ -115,32 +156,87 @@ impl SdJwtInstance {
     /// Returns a VC both in the form of a Map and in the form of an unsigned JWT.
     pub fn issue_vc(raw_vc: &Map<String, Value>, issuer_private_key: &impl AsRef<[u8]>) -> Result<(Map<String, Value>, String), String> {
 
-        let mut vc = raw_vc.clone();
-
-        let claims: &Map<String, Value> = Self::extract_claims(&vc)?;
-        let mut salt_value_container: Map<String, Value> = Map::new();
-        let mut hashes: Vec<Value> = vec![];
-        let mut hash: String;
-
-        for (field, value) in claims {
-            if let Value::String(val) = value { // Only works with strings
-                let salt: String = Self::generate_random_salt();
+        let credential: CredentialIr = Self::lower(raw_vc)?;
+        let mut vc = credential.envelope();
 
-                hash = Self::hash_from_value_and_salt(field, val, &salt);
-                hashes.push(Value::String(hash));
+        let (hashes, salts) = Self::protect_claims(credential.claims())?;
+        let salt_value_container: Map<String, Value> = Self::salt_value_container(credential.claims(), &salts);
 
-                salt_value_container.insert(field.clone(), Value::Array(vec![Value::String(salt), Value::String(val.clone())]));
-            }
-        }
-
-        let hashes_value: Value = Value::Array(hashes);
-        let signature: Vec<u8> = Self::derive_signature(hashes_value.to_string().as_bytes(), issuer_private_key)?;
+        let hashes_value: Value = Value::Array(hashes.into_iter().map(Value::String).collect());
+        let signature: Vec<u8> = Self::derive_signature(&Self::signed_bytes(Self::canonical_json(&hashes_value).as_bytes(), &vc), issuer_private_key)?;
 
         Self::serialize_and_insert(&mut vc, SIGNATURE.to_string(), &signature)?;
         Self::serialize_and_insert(&mut vc, HASHES.to_string(), &hashes_value)?;
         Self::serialize_and_insert(&mut vc, SVC.to_string(), &salt_value_container)?;
 
-        Self::remove_claims(&mut vc)?;
+        let jwt = Self::encode_jwt(&vc)?;
+
+        Ok((vc, jwt))
+    }
+
+    /// Holder side of the issuance over pre-hashed claims: salts and hashes the claims of a raw VC, so that only the
+    /// list of hashes has to be sent to the issuer.
+    ///
+    /// # Arguments
+    /// * `raw_vc` - Template VC containing the claims of the holder.
+    ///
+    /// # Returns
+    /// Returns the list of hashes to be signed by the issuer and the Salt-Value Container opening them, to be kept by
+    /// the holder, or a string containing an error in case of failure.
+    pub fn commit_claims(raw_vc: &Map<String, Value>) -> Result<(Vec<String>, Map<String, Value>), String> {
+
+        let credential: CredentialIr = Self::lower(raw_vc)?;
+        let (hashes, salts) = Self::protect_claims(credential.claims())?;
+
+        Ok((hashes, Self::salt_value_container(credential.claims(), &salts)))
+    }
+
+
+    /// Issuer side of the issuance over pre-hashed claims: signs a list of hashes committed by the holder, together with
+    /// the signed fields of the credential, after checking that it is well formed, without ever seeing the claims.
+    ///
+    /// # Arguments
+    /// * `hashes` - List of hashes committed by the holder.
+    /// * `signed_fields` - Signed fields of the credential, e.g. its `cnf` claim.
+    /// * `issuer_private_key` - Private key of the issuer used to generate the signature of the list of hashes.
+    ///
+    /// # Returns
+    /// Returns the signature of the list of hashes or a string containing an error in case of failure.
+    pub fn sign_digests(hashes: &[String], signed_fields: &Map<String, Value>, issuer_private_key: &impl AsRef<[u8]>) -> Result<Vec<u8>, String> {
+
+        Self::check_digests(hashes)?;
+        let hashes_value: Value = Value::Array(hashes.iter().map(|hash| Value::String(hash.clone())).collect());
+
+        Self::derive_signature(&Self::signed_bytes(Self::canonical_json(&hashes_value).as_bytes(), signed_fields), issuer_private_key)
+    }
+
+
+    /// Holder side of the issuance over pre-hashed claims: assembles the VC from the raw VC, the Salt-Value Container
+    /// returned by `commit_claims` and the signature of the issuer.
+    ///
+    /// # Arguments
+    /// * `raw_vc` - Template VC passed to `commit_claims`.
+    /// * `salt_value_container` - Salt-Value Container returned by `commit_claims`.
+    /// * `signature` - Signature of the list of hashes returned by the issuer.
+    ///
+    /// # Returns
+    /// Returns a VC both in the form of a Map and in the form of an unsigned JWT.
+    pub fn assemble_vc(raw_vc: &Map<String, Value>, salt_value_container: &Map<String, Value>, signature: &[u8]) -> Result<(Map<String, Value>, String), String> {
+
+        let credential: CredentialIr = CredentialIr::from_raw_vc(&Self::group_claims(raw_vc)?)?;
+        let mut vc = credential.envelope();
+        let mut salts: Map<String, Value> = Map::new();
+        for (field, array_value) in salt_value_container {
+            match array_value {
+                Value::Array(array) if matches!(array.first(), Some(Value::String(_))) => { salts.insert(field.clone(), array[0].clone()); }
+                _ => { return Err(format!("Salt of claim {field} is not a string")) }
+            }
+        }
+        let hashes: Vec<String> = Self::protected_elements(credential.claims(), &salts)?;
+
+        Self::serialize_and_insert(&mut vc, SIGNATURE.to_string(), signature)?;
+        Self::serialize_and_insert(&mut vc, HASHES.to_string(), &Value::Array(hashes.into_iter().map(Value::String).collect()))?;
+        Self::serialize_and_insert(&mut vc, SVC.to_string(), salt_value_container)?;
 
         let jwt = Self::encode_jwt(&vc)?;
 
@@ -156,13 +252,27 @@ impl SdJwtInstance {
     /// # Returns
     /// Returns a string containing an error in case of failure.
     pub fn verify_vc(vc: &Map<String, Value>, issuer_public_key: &impl AsRef<[u8]>) -> Result<(), String> {
+        Self::verify_vc_with_config(vc, issuer_public_key, &Self::VERIFIER_CONFIG)
+    }
 
-        let salt_value_container: Map<String, Value> = Self::get_and_decode(vc, SVC.to_string())?;
-        let hashes_value: Value = Self::get_and_decode(vc, HASHES.to_string())?;
-        let signature: Vec<u8> = Self::get_and_decode(vc, SIGNATURE.to_string())?;
+    /// Same as `verify_vc`, but enforcing the limits of the verifier on the amount of claims and on the encoded elements
+    /// of the VC.
+    ///
+    /// # Arguments
+    /// * `vc` - Verifiable Credential.
+    /// * `issuer_public_key` - Issuer's public key to verify the signature of the list of hashes.
+    /// * `config` - Limits enforced on the VC.
+    ///
+    /// # Returns
+    /// Returns a string containing an error in case of failure.
+    pub fn verify_vc_with_config(vc: &Map<String, Value>, issuer_public_key: &impl AsRef<[u8]>, config: &VerifierConfig) -> Result<(), String> {
+
+        let salt_value_container: Map<String, Value> = Self::get_and_decode_with_config(vc, SVC.to_string(), config)?;
+        let hashes_value: Value = Self::get_and_decode_with_config(vc, HASHES.to_string(), config)?;
+        let signature: Vec<u8> = Self::get_and_decode_with_config(vc, SIGNATURE.to_string(), config)?;
 
-        Self::verify_salt_value_container(&salt_value_container, &hashes_value)?;
-        Self::verify_signature(hashes_value.to_string().as_bytes(), &signature, issuer_public_key)?;
+        Self::verify_salt_value_container(&salt_value_container, &hashes_value, config)?;
+        Self::verify_signature(&Self::signed_bytes(Self::canonical_json(&hashes_value).as_bytes(), vc), &signature, issuer_public_key)?;
 
         Ok(())
     }
@@ -172,28 +282,192 @@ impl SdJwtInstance {
     /// # Arguments
     /// * `vc` - Verifiable Credential.
     /// * `disclosures` - List of strings containing the names of the claims that are to be disclosed.
-    /// * `holder_private_key` - Holder's private key necessary for proof of possession.
+    /// * `holder_binding` - Holder binding signing the VP as proof of possession.
     ///
     /// # Returns
     /// Returns the VP both in form of a Map and in form of a signed JWT.
-    pub fn issue_vp(vc: &Map<String, Value>, disclosures: &Vec<String>, holder_private_key: &impl AsRef<[u8]>) -> Result<(Map<String, Value>, String), String> {
+    pub fn issue_vp(vc: &Map<String, Value>, disclosures: &[String], holder_binding: &(impl HolderBinding + ?Sized)) -> Result<(Map<String, Value>, String), String> {
+        Self::issue_vp_for_audience(vc, disclosures, None, None, holder_binding)
+    }
+
+
+    /// Same as `issue_vp`, but binding the presentation to an optional audience, as required to disclose sensitive claims.
+    ///
+    /// # Arguments
+    /// * `vc` - Verifiable Credential.
+    /// * `disclosures` - List of strings containing the names of the claims that are to be disclosed.
+    /// * `audience` - Optional intended audience of the presentation.
+    /// * `validity` - Optional validity window of the presentation.
+    /// * `holder_binding` - Holder binding signing the VP as proof of possession.
+    ///
+    /// # Returns
+    /// Returns the VP both in form of a Map and in form of a signed JWT.
+    pub fn issue_vp_for_audience(vc: &Map<String, Value>, disclosures: &[String], audience: Option<String>, validity: Option<PresentationValidity>, holder_binding: &(impl HolderBinding + ?Sized)) -> Result<(Map<String, Value>, String), String> {
 
         let mut vp: Map<String, Value> = vc.clone();
+        let disclosures: &Vec<String> = &Self::resolve_disclosures(vc, disclosures)?;
 
         let salt_value_container: Map<String, Value> = Self::get_and_decode(&mut vp, SVC.to_string())?;
         let mut new_salt_value_container: Map<String, Value> = Map::new();
 
-        for (field, value) in salt_value_container {
-            if disclosures.contains(&field) {
-                new_salt_value_container.insert(field, value);
+        for disclosure in disclosures {
+            if let Some(value) = salt_value_container.get(disclosure) {
+                new_salt_value_container.insert(disclosure.clone(), value.clone());
             }
         }
 
         Self::serialize_and_insert(&mut vp, SVC.to_string(), &new_salt_value_container)?;
 
-        let jwt: String = Self::encode_and_sign_jwt(&mut vp, holder_private_key)?;
+        Self::encode_and_sign_presentation_for_audience(vp, audience, validity, holder_binding)
+    }
+
 
-        Ok((vp, jwt))
+    /// Given a VC, and several sets of disclosures, create one Verifiable Presentation per set decoding the VC only once.
+    ///
+    /// # Arguments
+    /// * `vc` - Verifiable Credential.
+    /// * `disclosure_sets` - Lists of strings containing the names of the claims that are to be disclosed in each VP.
+    /// * `holder_binding` - Holder binding signing the VP as proof of possession.
+    ///
+    /// # Returns
+    /// Returns the VPs both in form of a Map and in form of a signed JWT.
+    pub fn issue_vp_batch(vc: &Map<String, Value>, disclosure_sets: &[Vec<String>], holder_binding: &(impl HolderBinding + ?Sized)) -> Result<PresentationBatch, String> {
+
+        let salt_value_container: Map<String, Value> = Self::get_and_decode(vc, SVC.to_string())?;
+        let mut vps: Vec<(Map<String, Value>, String)> = Vec::with_capacity(disclosure_sets.len());
+
+        for disclosures in disclosure_sets {
+            let mut vp: Map<String, Value> = vc.clone();
+            let disclosures: &Vec<String> = &Self::resolve_disclosures(vc, disclosures)?;
+            let new_salt_value_container: Map<String, Value> = disclosures
+                .iter()
+                .filter_map(|field| salt_value_container.get(field).map(|value| (field.clone(), value.clone())))
+                .collect();
+
+            Self::serialize_and_insert(&mut vp, SVC.to_string(), &new_salt_value_container)?;
+            vps.push(Self::encode_and_sign_presentation(vp, holder_binding)?);
+        }
+
+        Ok(vps)
+    }
+
+
+    /// Extracts the issuer signature, the list of hashes and the disclosures from a VC or VP. Each disclosure is
+    /// encoded, as in the SD-JWT specification, as the base64url encoding of the JSON array `[salt, name, value]`.
+    ///
+    /// # Arguments
+    /// * `vp` - Verifiable Credential or Verifiable Presentation.
+    ///
+    /// # Returns
+    /// Returns a map containing the artifacts or a string containing an error in case of failure.
+    pub fn proof_artifacts(vp: &Map<String, Value>) -> Result<Map<String, Value>, String> {
+
+        let vp: &Map<String, Value> = Self::credential_or_presentation(vp)?;
+
+        let salt_value_container: Map<String, Value> = Self::get_and_decode(vp, SVC.to_string())?;
+        let hashes_value: Value = Self::get_and_decode(vp, HASHES.to_string())?;
+        let signature: Vec<u8> = Self::get_and_decode(vp, SIGNATURE.to_string())?;
+
+        let mut disclosures: Vec<Value> = vec![];
+        for (field, array_value) in salt_value_container {
+            let disclosure = match array_value {
+                Value::Array(array) if array.len() == 2 => { Value::Array(vec![array[0].clone(), Value::String(field), array[1].clone()]) }
+                _ => { return Err("Error, array field in salt value container is not a pair".to_string()) }
+            };
+            disclosures.push(Value::String(Encoding::Base64UrlNoPad.encode(disclosure.to_string())));
+        }
+
+        let mut artifacts: Map<String, Value> = Map::new();
+        artifacts.insert("issuer_signature".to_string(), Value::String(Encoding::Base64UrlNoPad.encode(signature)));
+        artifacts.insert("hashes".to_string(), hashes_value);
+        artifacts.insert("disclosures".to_string(), Value::Array(disclosures));
+        artifacts.insert(SIGNED_FIELDS.to_string(), Value::Object(signed_fields(vp)));
+
+        Ok(artifacts)
+    }
+
+
+    /// Extracts the proof of every disclosed claim of a VC or VP: its salt and digest, checked against the list of hashes
+    /// signed by the issuer, which is shared by all the claims.
+    ///
+    /// # Arguments
+    /// * `vp` - Verifiable Credential or Verifiable Presentation.
+    ///
+    /// # Returns
+    /// Returns the `shared` and `claims` objects of the audit document or a string containing an error in case of failure.
+    pub fn claim_proofs(vp: &Map<String, Value>) -> Result<Map<String, Value>, String> {
+
+        let vp: &Map<String, Value> = Self::credential_or_presentation(vp)?;
+
+        let salt_value_container: Map<String, Value> = Self::get_and_decode(vp, SVC.to_string())?;
+        let hashes_value: Value = Self::get_and_decode(vp, HASHES.to_string())?;
+        let signature: Vec<u8> = Self::get_and_decode(vp, SIGNATURE.to_string())?;
+
+        let mut claims: Map<String, Value> = Map::new();
+        for (field, array_value) in salt_value_container {
+            let (salt, value) = match array_value {
+                Value::Array(array) if array.len() == 2 => { (array[0].clone(), array[1].clone()) }
+                _ => { return Err("Error, array field in salt value container is not a pair".to_string()) }
+            };
+            let digest: String = match &salt {
+                Value::String(salt) => { Self::protect_claim(&field, &value, Some(salt))? }
+                _ => { return Err("Either salts or values are not strings.".to_string()) }
+            };
+
+            let mut proof: Map<String, Value> = Map::new();
+            proof.insert(VALUE.to_string(), value);
+            proof.insert("salt".to_string(), salt);
+            proof.insert("digest".to_string(), Value::String(digest));
+            claims.insert(field, Value::Object(proof));
+        }
+
+        let mut shared: Map<String, Value> = Map::new();
+        shared.insert("issuer_signature".to_string(), Value::String(Encoding::Base64UrlNoPad.encode(signature)));
+        shared.insert("hashes".to_string(), hashes_value);
+        shared.insert(SIGNED_FIELDS.to_string(), Value::Object(signed_fields(vp)));
+
+        Ok(claim_proofs_document(shared, claims))
+    }
+
+
+    /// Re-verifies the claims of an audit document independently of their presentation: the digest of every claim is
+    /// recomputed from its name, value and salt and looked up in the list of hashes, whose signature is checked.
+    ///
+    /// # Arguments
+    /// * `document` - Audit document, or audit document of a single claim.
+    /// * `issuer_public_key` - Issuer's public key to verify the signature of the list of hashes.
+    /// * `config` - Limits enforced on the list of hashes and on the claims.
+    ///
+    /// # Returns
+    /// Returns a string containing an error in case of failure.
+    pub fn verify_claim_proofs(document: &Map<String, Value>, issuer_public_key: &impl AsRef<[u8]>, config: &VerifierConfig) -> Result<(), String> {
+
+        let (shared, claims) = split_claim_proofs(document)?;
+        let hashes_value: &Value = match shared.get("hashes") {
+            Some(hashes_value) => { hashes_value }
+            None => { return Err("Audit document does not contain the list of hashes.".to_string()) }
+        };
+        let signature: Vec<u8> = match shared.get("issuer_signature") {
+            Some(Value::String(signature)) => match Encoding::Base64UrlNoPad.decode(signature) {
+                Ok(signature) => { signature }
+                Err(err) => { return Err(format!("Failed to decode issuer signature: [{err}]")) }
+            }
+            _ => { return Err("Audit document does not contain the issuer signature.".to_string()) }
+        };
+
+        let mut salt_value_container: Map<String, Value> = Map::new();
+        for (field, proof) in claims {
+            match (proof.get("salt"), proof.get(VALUE)) {
+                (Some(salt), Some(value)) => { salt_value_container.insert(field.clone(), Value::Array(vec![salt.clone(), value.clone()])); }
+                _ => { return Err(format!("Proof of claim {field} does not contain its salt and value.")) }
+            }
+        }
+
+        Self::verify_salt_value_container(&salt_value_container, hashes_value, config)?;
+        let signed_fields: Map<String, Value> = shared_signed_fields(shared)?;
+        Self::verify_signature(&Self::signed_bytes(Self::canonical_json(hashes_value).as_bytes(), &signed_fields), &signature, issuer_public_key)?;
+
+        Ok(())
     }
 
 
@@ -202,19 +476,34 @@ impl SdJwtInstance {
     /// # Arguments
     /// * `jwt` - Verifiable Presentation encoded as a jwt.
     /// * `issuer_public_key` - Issuer's public key to verify the signature of the list of hashes.
-    /// * `holder_public_key` - Holder's public key to verify the proof of possession.
+    /// * `holder_binding` - Holder binding verifying the proof of possession.
+    ///
+    /// # Returns
+    /// Returns a string containing an error in case of failure.
+    pub fn verify_vp(jwt: &str, issuer_public_key: &impl AsRef<[u8]>, holder_binding: &(impl HolderBinding + ?Sized)) -> Result<(), String> {
+        Self::verify_vp_with_config(jwt, issuer_public_key, holder_binding, &Self::VERIFIER_CONFIG)
+    }
+
+
+    /// Same as `verify_vp`, but enforcing the limits of the verifier on the size, depth and amount of claims of the VP.
+    ///
+    /// # Arguments
+    /// * `jwt` - Verifiable Presentation encoded as a jwt.
+    /// * `issuer_public_key` - Issuer's public key to verify the signature of the list of hashes.
+    /// * `holder_binding` - Holder binding verifying the proof of possession.
+    /// * `config` - Limits enforced on the VP.
     ///
     /// # Returns
     /// Returns a string containing an error in case of failure.
-    pub fn verify_vp(jwt: &String, issuer_public_key: &impl AsRef<[u8]>, holder_public_key: &impl AsRef<[u8]>) -> Result<(), String> {
+    pub fn verify_vp_with_config(jwt: &str, issuer_public_key: &impl AsRef<[u8]>, holder_binding: &(impl HolderBinding + ?Sized), config: &VerifierConfig) -> Result<(), String> {
 
-        let vp = Self::decode_and_verify_jwt(jwt, holder_public_key)?;
-        let salt_value_container: Map<String, Value> = Self::get_and_decode(&vp, SVC.to_string())?;
-        let hashes_value: Value = Self::get_and_decode(&vp, HASHES.to_string())?;
-        let signature: Vec<u8> = Self::get_and_decode(&vp, SIGNATURE.to_string())?;
+        let vp = Self::decode_and_verify_presentation(jwt, holder_binding, config)?;
+        let salt_value_container: Map<String, Value> = Self::get_and_decode_with_config(&vp, SVC.to_string(), config)?;
+        let hashes_value: Value = Self::get_and_decode_with_config(&vp, HASHES.to_string(), config)?;
+        let signature: Vec<u8> = Self::get_and_decode_with_config(&vp, SIGNATURE.to_string(), config)?;
 
-        Self::verify_salt_value_container(&salt_value_container, &hashes_value)?;
-        Self::verify_signature(hashes_value.to_string().as_bytes(), &signature, issuer_public_key)?;
+        Self::verify_salt_value_container(&salt_value_container, &hashes_value, config)?;
+        Self::verify_signature(&Self::signed_bytes(Self::canonical_json(&hashes_value).as_bytes(), &vp), &signature, issuer_public_key)?;
 
         Ok(())
     }
@@ -224,8 +513,12 @@ impl SdJwtInstance {
 
 #[cfg(test)]
 mod tests {
+    use log::debug;
     use super::*;
-    use crate::common_data::{CommonData, VC};
+    use crate::claim_groups::ClaimGroups;
+    use crate::common_data::{CommonData, CLAIM_METADATA, VC};
+    use crate::holder_binding::{bind_holder_key, EdDsaBinding, NoBinding};
+    use crate::metadata_disclosure::DisclosableMetadata;
     use serde_json::{Map, Value};
 
     #[test]
@@ -251,11 +544,11 @@ mod tests {
         };
 
         match SdJwtInstance::verify_vc(&vc, &issuer_public_key) {
-            Ok(_) => { println!("[SD-JWT] Successfully verified vc.")}
+            Ok(_) => { debug!("[SD-JWT] Successfully verified vc.")}
             Err(err) => { return Err(format!("[SD-JWT] Failed to verify vc [{err}]."))}
         };
 
-        let disclosures = vec!["name", "birthdate"].iter().map(|x| x.to_string()).collect();
+        let disclosures: Vec<String> = vec!["name", "birthdate"].iter().map(|x| x.to_string()).collect();
 
         let (_vp, vp_jwt) = match SdJwtInstance::issue_vp(&vc, &disclosures, &holder_private_key) {
             Ok(vp_jwt) => { vp_jwt }
@@ -263,10 +556,211 @@ mod tests {
         };
 
         match SdJwtInstance::verify_vp(&vp_jwt, &issuer_public_key, &holder_public_key) {
-            Ok(_) => { println!("[SD-JWT] Successfully verified vp.")}
+            Ok(_) => { debug!("[SD-JWT] Successfully verified vp.")}
             Err(err) => { return Err(format!("[SD-JWT] Failed to verify vp [{err}].")) }
         };
 
         Ok(())
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn claim_proofs() -> Result<(), String> {
+
+        let raw_vc: Map<String, Value> = match serde_json::from_str::<Map<String, Value>>(VC) {
+            Ok(vc) => { vc }
+            Err(err) => { return Err(format!("[SD-JWT] Failed to parse Raw Verifiable Credential. [{err}]")); }
+        };
+        let (_, holder_private_key) = CommonData::holder_keys()?;
+        let (issuer_public_key, issuer_private_key) = CommonData::issuer_keys()?;
+
+        let (vc, _vc_jwt) = SdJwtInstance::issue_vc(&raw_vc, &issuer_private_key)?;
+        let disclosures: Vec<String> = vec!["name", "birthdate"].iter().map(|x| x.to_string()).collect();
+        let (vp, _vp_jwt) = SdJwtInstance::issue_vp(&vc, &disclosures, &holder_private_key)?;
+
+        let document = crate::audit::claim_proofs_document(Map::new(), Map::new());
+        assert!(SdJwtInstance::verify_claim_proofs(&document, &issuer_public_key, &VerifierConfig::DEFAULT).is_err());
+
+        let document = SdJwtInstance::claim_proofs(&vp)?;
+        let mut proof = crate::audit::claim_proof(&document, "name")?;
+        SdJwtInstance::verify_claim_proofs(&proof, &issuer_public_key, &VerifierConfig::DEFAULT)?;
+
+        proof["claims"]["name"]["value"] = Value::String("Isaac Newton".to_string());
+        assert!(SdJwtInstance::verify_claim_proofs(&proof, &issuer_public_key, &VerifierConfig::DEFAULT).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn claim_metadata() -> Result<(), String> {
+
+        let mut raw_vc: Map<String, Value> = match serde_json::from_str::<Map<String, Value>>(VC) {
+            Ok(vc) => { vc }
+            Err(err) => { return Err(format!("[SD-JWT] Failed to parse Raw Verifiable Credential from string. [{err}]")); }
+        };
+        raw_vc.insert(CLAIM_METADATA.to_string(), serde_json::json!({
+            "name": { "mandatory": true, "type": "string" },
+            "birthdate": { "sensitive": true },
+        }));
+
+        let (holder_public_key, holder_private_key) = CommonData::holder_keys()?;
+        let (issuer_public_key, issuer_private_key) = CommonData::issuer_keys()?;
+        let (vc, _vc_jwt) = SdJwtInstance::issue_vc(&raw_vc, &issuer_private_key)?;
+
+        let disclosures: Vec<String> = vec!["field".to_string()];
+        let (vp, vp_jwt) = SdJwtInstance::issue_vp(&vc, &disclosures, &holder_private_key)?;
+        let disclosed_claims = SdJwtInstance::disclosed_claim_names(SdJwtInstance::unwrap_presentation(&vp)?)?;
+        assert!(disclosed_claims.contains(&"name".to_string()), "[SD-JWT] Mandatory claim was not disclosed.");
+        SdJwtInstance::verify_vp(&vp_jwt, &issuer_public_key, &holder_public_key)?;
+
+        let disclosures: Vec<String> = vec!["birthdate".to_string()];
+        assert!(SdJwtInstance::issue_vp(&vc, &disclosures, &holder_private_key).is_err(), "[SD-JWT] Sensitive claim disclosed without audience.");
+
+        let (_vp, vp_jwt) = SdJwtInstance::issue_vp_for_audience(&vc, &disclosures, Some("https://verifier.example".to_string()), None, &holder_private_key)?;
+        SdJwtInstance::verify_vp(&vp_jwt, &issuer_public_key, &holder_public_key)?;
+
+        raw_vc.insert(CLAIM_METADATA.to_string(), serde_json::json!({ "name": { "type": "number" } }));
+        assert!(SdJwtInstance::issue_vc(&raw_vc, &issuer_private_key).is_err(), "[SD-JWT] Claim of the wrong type was issued.");
+
+        Ok(())
+    }
+
+    #[test]
+    fn claim_groups() -> Result<(), String> {
+
+        let mut raw_vc: Map<String, Value> = match serde_json::from_str::<Map<String, Value>>(VC) {
+            Ok(vc) => { vc }
+            Err(err) => { return Err(format!("[SD-JWT] Failed to parse Raw Verifiable Credential from string. [{err}]")); }
+        };
+        let groups: ClaimGroups = ClaimGroups::new().with_group("identity", &["name", "birthdate"]);
+        groups.insert_into(&mut raw_vc)?;
+
+        let (holder_public_key, holder_private_key) = CommonData::holder_keys()?;
+        let (issuer_public_key, issuer_private_key) = CommonData::issuer_keys()?;
+        let (vc, _vc_jwt) = SdJwtInstance::issue_vc(&raw_vc, &issuer_private_key)?;
+        let hashes: Vec<String> = SdJwtInstance::get_and_decode(&vc, HASHES.to_string())?;
+        assert_eq!(hashes.len(), SdJwtInstance::extract_claims(&raw_vc)?.len() - 1, "[SD-JWT] Grouped claims were not committed to as one.");
+
+        let disclosures: Vec<String> = vec!["birthdate".to_string()];
+        let (vp, vp_jwt) = SdJwtInstance::issue_vp(&vc, &disclosures, &holder_private_key)?;
+        let disclosed_claims = SdJwtInstance::disclosed_claim_names(SdJwtInstance::unwrap_presentation(&vp)?)?;
+        assert_eq!(disclosed_claims, vec!["identity".to_string()], "[SD-JWT] Group was not disclosed as a whole.");
+        SdJwtInstance::verify_vp(&vp_jwt, &issuer_public_key, &holder_public_key)?;
+
+        let mut group: Map<String, Value> = Map::new();
+        group.insert("identity".to_string(), SdJwtInstance::extract_claims(&SdJwtInstance::group_claims(&raw_vc)?)?["identity"].clone());
+        let expanded_claims: Map<String, Value> = ClaimGroups::extract(&vc)?.expand(&group)?;
+        assert_eq!(expanded_claims.get("name"), SdJwtInstance::extract_claims(&raw_vc)?.get("name"));
+
+        let mut raw_vc: Map<String, Value> = raw_vc.clone();
+        assert!(ClaimGroups::new().with_group("identity", &["nickname"]).insert_into(&mut raw_vc).is_err(), "[SD-JWT] Group of a missing claim was configured.");
+
+        Ok(())
+    }
+
+    #[test]
+    fn disclosable_metadata() -> Result<(), String> {
+
+        let mut raw_vc: Map<String, Value> = match serde_json::from_str::<Map<String, Value>>(VC) {
+            Ok(vc) => { vc }
+            Err(err) => { return Err(format!("[SD-JWT] Failed to parse Raw Verifiable Credential from string. [{err}]")); }
+        };
+        raw_vc.insert("issuanceDate".to_string(), Value::String("2024-01-01T00:00:00Z".to_string()));
+        let metadata: DisclosableMetadata = DisclosableMetadata::parse("issuanceDate")?;
+        metadata.insert_into(&mut raw_vc)?;
+
+        let (holder_public_key, holder_private_key) = CommonData::holder_keys()?;
+        let (issuer_public_key, issuer_private_key) = CommonData::issuer_keys()?;
+        let (vc, _vc_jwt) = SdJwtInstance::issue_vc(&raw_vc, &issuer_private_key)?;
+        assert!(!vc.contains_key("issuanceDate"), "[SD-JWT] Disclosable metadata was issued in the clear.");
+
+        let disclosures: Vec<String> = metadata.resolve(&["name".to_string(), "issuanceDate".to_string()]);
+        let (vp, vp_jwt) = SdJwtInstance::issue_vp(&vc, &disclosures, &holder_private_key)?;
+        let disclosed_claims = SdJwtInstance::disclosed_claim_names(SdJwtInstance::unwrap_presentation(&vp)?)?;
+        assert!(disclosed_claims.contains(&"metadata:issuanceDate".to_string()), "[SD-JWT] Metadata field was not disclosed.");
+        SdJwtInstance::verify_vp(&vp_jwt, &issuer_public_key, &holder_public_key)?;
+
+        let (_claims, fields) = DisclosableMetadata::restore(SdJwtInstance::extract_claims(&raw_vc)?)?;
+        assert_eq!(fields["issuanceDate"], "2024-01-01T00:00:00Z");
+        assert!(DisclosableMetadata::parse("issuer").is_err(), "[SD-JWT] Issuer was made disclosable.");
+
+        Ok(())
+    }
+
+    #[test]
+    fn holder_binding() -> Result<(), String> {
+
+        let raw_vc: Map<String, Value> = match serde_json::from_str::<Map<String, Value>>(VC) {
+            Ok(vc) => { vc }
+            Err(err) => { return Err(format!("[SD-JWT] Failed to parse Raw Verifiable Credential from string. [{err}]")); }
+        };
+
+        let (holder_public_key, holder_private_key) = CommonData::holder_keys()?;
+        let (issuer_public_key, issuer_private_key) = CommonData::issuer_keys()?;
+        let disclosures: Vec<String> = vec!["name".to_string()];
+
+        let (vc, _vc_jwt) = SdJwtInstance::issue_vc(&bind_holder_key(&raw_vc, &holder_public_key)?, &issuer_private_key)?;
+        let (_vp, vp_jwt) = SdJwtInstance::issue_vp(&vc, &disclosures, &holder_private_key)?;
+        SdJwtInstance::verify_vp(&vp_jwt, &issuer_public_key, &holder_public_key)?;
+
+        // The VP is correctly signed, but by a key other than the one the VC is bound to.
+        let (vc, _vc_jwt) = SdJwtInstance::issue_vc(&bind_holder_key(&raw_vc, &issuer_public_key)?, &issuer_private_key)?;
+        let (_vp, vp_jwt) = SdJwtInstance::issue_vp(&vc, &disclosures, &holder_private_key)?;
+        assert!(SdJwtInstance::verify_vp(&vp_jwt, &issuer_public_key, &holder_public_key).is_err(), "[SD-JWT] VP signed by an unbound key was verified.");
+
+        Ok(())
+    }
+
+    #[test]
+    fn pluggable_holder_binding() -> Result<(), String> {
+
+        let raw_vc: Map<String, Value> = match serde_json::from_str::<Map<String, Value>>(VC) {
+            Ok(vc) => { vc }
+            Err(err) => { return Err(format!("[SD-JWT] Failed to parse Raw Verifiable Credential from string. [{err}]")); }
+        };
+
+        let (holder_public_key, _holder_private_key) = CommonData::holder_keys()?;
+        let (issuer_public_key, issuer_private_key) = CommonData::issuer_keys()?;
+        let disclosures: Vec<String> = vec!["name".to_string()];
+
+        let eddsa_binding = EdDsaBinding::generate()?;
+        let (vc, _vc_jwt) = SdJwtInstance::issue_vc(&eddsa_binding.bind_credential(&raw_vc)?, &issuer_private_key)?;
+        let (_vp, vp_jwt) = SdJwtInstance::issue_vp(&vc, &disclosures, &eddsa_binding)?;
+        SdJwtInstance::verify_vp(&vp_jwt, &issuer_public_key, &eddsa_binding)?;
+        assert!(SdJwtInstance::verify_vp(&vp_jwt, &issuer_public_key, &EdDsaBinding::generate()?).is_err(), "[SD-JWT] VP signed by another EdDSA key was verified.");
+
+        // Unsigned presentations are only accepted by a verifier that does not expect a signature.
+        let (vc, _vc_jwt) = SdJwtInstance::issue_vc(&raw_vc, &issuer_private_key)?;
+        let (_vp, vp_jwt) = SdJwtInstance::issue_vp(&vc, &disclosures, &NoBinding)?;
+        SdJwtInstance::verify_vp(&vp_jwt, &issuer_public_key, &NoBinding)?;
+        assert!(SdJwtInstance::verify_vp(&vp_jwt, &issuer_public_key, &holder_public_key).is_err(), "[SD-JWT] Unsigned VP was verified with a holder key.");
+
+        Ok(())
+    }
+
+    #[test]
+    fn pre_hashed_issuance() -> Result<(), String> {
+
+        let raw_vc: Map<String, Value> = match serde_json::from_str::<Map<String, Value>>(VC) {
+            Ok(vc) => { vc }
+            Err(err) => { return Err(format!("[SD-JWT] Failed to parse Raw Verifiable Credential from string. [{err}]")); }
+        };
+
+        let (holder_public_key, holder_private_key) = CommonData::holder_keys()?;
+        let (issuer_public_key, issuer_private_key) = CommonData::issuer_keys()?;
+
+        let (hashes, salt_value_container) = SdJwtInstance::commit_claims(&raw_vc)?;
+        let signature = SdJwtInstance::sign_digests(&hashes, &signed_fields(&raw_vc), &issuer_private_key)?;
+        let (vc, _vc_jwt) = SdJwtInstance::assemble_vc(&raw_vc, &salt_value_container, &signature)?;
+        SdJwtInstance::verify_vc(&vc, &issuer_public_key)?;
+
+        let disclosures: Vec<String> = vec!["name".to_string()];
+        let (_vp, vp_jwt) = SdJwtInstance::issue_vp(&vc, &disclosures, &holder_private_key)?;
+        SdJwtInstance::verify_vp(&vp_jwt, &issuer_public_key, &holder_public_key)?;
+
+        let duplicated_hashes: Vec<String> = vec![hashes[0].clone(), hashes[0].clone()];
+        assert!(SdJwtInstance::sign_digests(&duplicated_hashes, &Map::new(), &issuer_private_key).is_err(), "[SD-JWT] Duplicated hashes were signed.");
+        assert!(SdJwtInstance::sign_digests(&vec!["name".to_string()], &Map::new(), &issuer_private_key).is_err(), "[SD-JWT] Malformed hash was signed.");
+
+        Ok(())
+    }
+}
@@ -1,4 +1,6 @@
 pub mod hashes;
 pub mod sd_algorithm;
+pub mod claim_protector;
 pub mod accumulators;
-pub mod signatures;
\ No newline at end of file
+pub mod signatures;
+pub mod verification_cache;
\ No newline at end of file
@@ -1,10 +1,49 @@
-use josekit::jws::{JwsHeader, ES256};
+use std::collections::HashSet;
+use josekit::jws::{JwsHeader, JwsSigner, JwsVerifier, ES256};
 use josekit::jwt;
 use josekit::jwt::JwtPayload;
+use log::{debug, log_enabled, trace, Level};
 use serde::de::DeserializeOwned;
 use serde::Serialize;
 use serde_json::{Map, Value};
-use crate::common_data::CLAIMS;
+use crate::claim_groups::ClaimGroups;
+use crate::claim_metadata::ClaimMetadata;
+use crate::credential_ir::CredentialIr;
+use crate::common_data::{AUDIENCE, CLAIMS, CNF, EXPIRATION, ISSUED_AT, NOT_BEFORE, PRESENTATION_NONCE, VERIFIABLE_CREDENTIAL, VP, VP_CONTEXT, VP_TYPE};
+use crate::compression::{decode_compact, encode_compact, Compression};
+use crate::deterministic::{fill_random, unix_time};
+use crate::encoding::Encoding;
+use crate::envelope_profile::EnvelopeTimer;
+use crate::ephemeral_binding::{unverified_claims, verify_certificate, EphemeralKey, EPHEMERAL_KEY_CERTIFICATE};
+use crate::holder_binding::{verify_holder_binding, HolderBinding};
+use crate::json;
+use crate::metadata_disclosure::ISSUANCE_DATE;
+use crate::presentation_validity::PresentationValidity;
+use crate::typed_claims::{canonical_number, canonicalize_typed_claims};
+use crate::verifier_config::VerifierConfig;
+
+/// Name of the JWT header parameter carrying the encoding of the embedded elements.
+pub const ENCODING_HEADER: &str = "sd_enc";
+/// Fields of a credential outside of its claims that every algorithm binds to the commitment signed by the issuer, so
+/// that they cannot be swapped or removed without invalidating the credential: the holder binding, the validity
+/// period, which verifiers check freshness against, and the issuance date, possibly rounded.
+pub const SIGNED_FIELDS: [&str; 5] = [CNF, EXPIRATION, ISSUANCE_DATE, ISSUED_AT, NOT_BEFORE];
+
+/// VPs derived in a batch, each as its map and its encoded JWT, in the order of the disclosure sets.
+pub type PresentationBatch = Vec<(Map<String, Value>, String)>;
+
+/// Retrieves the `SIGNED_FIELDS` present in a VC, or in the credential of a VP.
+///
+/// # Arguments
+/// * `credential` - VC, raw VC or credential of a VP.
+///
+/// # Returns
+/// Returns the signed fields, by name.
+pub fn signed_fields(credential: &Map<String, Value>) -> Map<String, Value> {
+    SIGNED_FIELDS.iter()
+        .filter_map(|field| credential.get(*field).map(|value| (field.to_string(), value.clone())))
+        .collect()
+}
 
 /// Trait that implements several methods shared across different algorithm instances.
 pub trait SdAlgorithm {
@@ -12,6 +51,23 @@ pub trait SdAlgorithm {
     /// Each algorithm is identified by this unique string.
     const ALGORITHM: &'static str;
 
+    /// Encoding used for the elements embedded in VCs and VPs.
+    const ENCODING: Encoding = Encoding::Base64UrlNoPad;
+
+    /// Compression of the payloads of the encoded VCs and VPs. Compressed JWTs are always accepted on decoding.
+    const COMPRESSION: Compression = Compression::None;
+
+    /// Limits enforced when decoding and verifying untrusted VCs and VPs, unless the verifier passes its own.
+    const VERIFIER_CONFIG: VerifierConfig = VerifierConfig::DEFAULT;
+
+    /// Whether a VP disclosing no claim still proves that it is derived from a credential of the issuer, i.e. whether
+    /// possession-only presentations are supported.
+    const SUPPORTS_EMPTY_DISCLOSURE: bool = true;
+
+    /// Fields the issuance of the algorithm adds to the raw VC, i.e. its proof material, dropped when the VC is
+    /// converted to another algorithm.
+    const PROOF_FIELDS: &'static [&'static str] = &[];
+
     /// A function that given either a VC or a VP in the form of a Map, returns the claims included in it.
     ///
     /// # Arguments
@@ -63,6 +119,198 @@ pub trait SdAlgorithm {
     }
 
 
+    /// Merges the claims of every group configured in the `claimGroups` sidecar of a raw VC into a single claim before
+    /// issuance, so that the group is committed to, and disclosed, as one unit.
+    ///
+    /// # Arguments
+    /// * `raw_vc` - Template VC containing the claims and, optionally, their groups.
+    ///
+    /// # Returns
+    /// Returns a result containing the raw VC to be issued or a string representing an error if the groups do not match the claims.
+    fn group_claims(raw_vc: &Map<String, Value>) -> Result<Map<String, Value>, String> {
+        ClaimGroups::group_claims(raw_vc)
+    }
+
+
+    /// Returns the claims of a VC as issued, i.e. after the claim groups were merged. Algorithms keeping the claims
+    /// outside of the `credentialSubject` field override this function.
+    ///
+    /// # Arguments
+    /// * `vc` - Verifiable Credential issued by the algorithm.
+    ///
+    /// # Returns
+    /// Returns a result containing the claims or a string representing an error.
+    fn issued_claims(vc: &Map<String, Value>) -> Result<Map<String, Value>, String> {
+        Ok(Self::extract_claims(vc)?.clone())
+    }
+
+
+    /// Lowers a raw VC to the intermediate representation the algorithm protects: merges its claim groups, checks its
+    /// per-claim metadata, encodes its typed claims canonically and separates the claims from the metadata and the
+    /// holder binding.
+    ///
+    /// # Arguments
+    /// * `raw_vc` - Template VC containing the claims and, optionally, their groups and metadata.
+    ///
+    /// # Returns
+    /// Returns a result containing the intermediate representation or a string representing an error.
+    fn lower(raw_vc: &Map<String, Value>) -> Result<CredentialIr, String> {
+        let raw_vc: Map<String, Value> = Self::group_claims(raw_vc)?;
+        Self::check_claim_metadata(&raw_vc)?;
+        CredentialIr::from_raw_vc(&canonicalize_typed_claims(&raw_vc)?)
+    }
+
+
+    /// Lowers a VC issued by the algorithm back to the intermediate representation, dropping its proof material.
+    ///
+    /// # Arguments
+    /// * `vc` - Verifiable Credential issued by the algorithm.
+    ///
+    /// # Returns
+    /// Returns a result containing the intermediate representation or a string representing an error.
+    fn lower_vc(vc: &Map<String, Value>) -> Result<CredentialIr, String> {
+        let claims: Map<String, Value> = Self::issued_claims(vc)?;
+        let metadata: Map<String, Value> = vc.iter()
+            .filter(|(field, _)| field.as_str() != CLAIMS && !Self::PROOF_FIELDS.contains(&field.as_str()))
+            .map(|(field, value)| (field.clone(), value.clone()))
+            .collect();
+
+        CredentialIr::from_parts(metadata, claims)
+    }
+
+
+    /// Retrieves the values of a container mapping every claim to a pair, whose second element is the value of the
+    /// claim, such as the Salt-Value Container of SD-JWT.
+    ///
+    /// # Arguments
+    /// * `container` - Container of pairs.
+    ///
+    /// # Returns
+    /// Returns a result containing the claims or a string representing an error.
+    fn container_values(container: &Map<String, Value>) -> Result<Map<String, Value>, String> {
+        let mut claims: Map<String, Value> = Map::new();
+        for (name, pair) in container {
+            match pair {
+                Value::Array(pair) if pair.len() == 2 => { claims.insert(name.clone(), pair[1].clone()); }
+                _ => { return Err(format!("Field {name} of the container is not a pair")) }
+            }
+        }
+        Ok(claims)
+    }
+
+
+    /// Checks the per-claim metadata sidecar of a raw VC against its claims before issuance.
+    ///
+    /// # Arguments
+    /// * `raw_vc` - Template VC containing the claims and, optionally, their metadata.
+    ///
+    /// # Returns
+    /// Returns a result containing a string representing an error if a tag does not match the claims.
+    fn check_claim_metadata(raw_vc: &Map<String, Value>) -> Result<(), String> {
+        let metadata = ClaimMetadata::extract(raw_vc)?;
+        if metadata.is_empty() {
+            return Ok(());
+        }
+
+        ClaimMetadata::check_claims(&metadata, Self::extract_claims(raw_vc)?)
+    }
+
+
+    /// Extends the disclosures chosen by the holder with the claims tagged as mandatory in the credential, and checks
+    /// that they can be presented: a claim of a group discloses the whole group, every disclosure must name a claim of
+    /// the credential, duplicates are dropped, and an empty set of disclosures is only accepted by algorithms
+    /// supporting possession-only presentations.
+    ///
+    /// # Arguments
+    /// * `credential` - VC the presentation is derived from.
+    /// * `disclosures` - A vector of strings that contains the disclosures chosen by the holder.
+    ///
+    /// # Returns
+    /// Returns a result containing the disclosures to be inserted in the VP or a string representing an error.
+    fn resolve_disclosures(credential: &Map<String, Value>, disclosures: &[String]) -> Result<Vec<String>, String> {
+        let claims: HashSet<String> = Self::disclosed_claim_names(credential)?.into_iter().collect();
+        let mut resolved_disclosures: Vec<String> = vec![];
+        let mut resolved: HashSet<String> = HashSet::new();
+
+        for disclosure in ClaimGroups::extract(credential)?.resolve(disclosures) {
+            if !claims.contains(&disclosure) {
+                return Err(format!("Claim {disclosure} is not in the credential."));
+            }
+            if resolved.insert(disclosure.clone()) {
+                resolved_disclosures.push(disclosure);
+            }
+        }
+
+        for (claim, claim_metadata) in ClaimMetadata::extract(credential)? {
+            if claim_metadata.mandatory && resolved.insert(claim.clone()) {
+                resolved_disclosures.push(claim);
+            }
+        }
+
+        Self::check_disclosed_claims_len(resolved_disclosures.len())?;
+
+        Ok(resolved_disclosures)
+    }
+
+
+    /// Rejects presentations disclosing no claim for algorithms that cannot prove the origin of such a presentation.
+    ///
+    /// # Arguments
+    /// * `disclosed_claims_len` - Amount of claims disclosed by the presentation.
+    ///
+    /// # Returns
+    /// Returns a result containing a string representing an error if the presentation is empty and unsupported.
+    fn check_disclosed_claims_len(disclosed_claims_len: usize) -> Result<(), String> {
+        if disclosed_claims_len == 0 && !Self::SUPPORTS_EMPTY_DISCLOSURE {
+            return Err(format!("{} presentations must disclose at least one claim.", Self::ALGORITHM));
+        }
+        Ok(())
+    }
+
+
+    /// Returns the names of the claims disclosed by a derived credential. Algorithms keeping the disclosed claims outside
+    /// of the `credentialSubject` field override this function.
+    ///
+    /// # Arguments
+    /// * `credential` - Derived credential contained in a VP.
+    ///
+    /// # Returns
+    /// Returns a result containing the names of the disclosed claims or a string representing an error.
+    fn disclosed_claim_names(credential: &Map<String, Value>) -> Result<Vec<String>, String> {
+        Ok(Self::extract_claims(credential)?.keys().cloned().collect())
+    }
+
+
+    /// Enforces the per-claim metadata on a VP: every mandatory claim must be disclosed, and sensitive claims can
+    /// only be disclosed if the presentation is bound to an audience.
+    ///
+    /// # Arguments
+    /// * `envelope` - VP JWT payload.
+    ///
+    /// # Returns
+    /// Returns a result containing a string representing an error if the metadata is not respected.
+    fn check_presentation_metadata(envelope: &Map<String, Value>) -> Result<(), String> {
+        let credential = Self::unwrap_presentation(envelope)?;
+        let metadata = ClaimMetadata::extract(credential)?;
+        if metadata.is_empty() {
+            return Ok(());
+        }
+
+        let disclosed_claims: HashSet<String> = Self::disclosed_claim_names(credential)?.into_iter().collect();
+        for (claim, claim_metadata) in metadata {
+            let disclosed = disclosed_claims.contains(&claim);
+            if claim_metadata.mandatory && !disclosed {
+                return Err(format!("Mandatory claim {claim} is not disclosed."));
+            }
+            if claim_metadata.sensitive && disclosed && !envelope.contains_key(AUDIENCE) {
+                return Err(format!("Sensitive claim {claim} can only be disclosed to an audience."));
+            }
+        }
+
+        Ok(())
+    }
+
+
     /// Filters the VC or VP passed as input to only include the disclosures already present in the disclosure vector.
     ///
     /// # Arguments
@@ -71,19 +319,17 @@ pub trait SdAlgorithm {
     ///
     /// # Returns
     /// Returns a result containing an array of disclosed indices or a string representing an error.
-    fn filter_claims_by_disclosure_and_insert(map: &mut Map<String, Value>, disclosures: &Vec<String>) -> Result<Vec<usize>, String> {
+    fn filter_claims_by_disclosure_and_insert(map: &mut Map<String, Value>, disclosures: &[String]) -> Result<Vec<usize>, String> {
 
         let claims = Self::extract_claims(map)?;
+        let disclosures: HashSet<&String> = disclosures.iter().collect();
         let mut disclosed_claims: Map<String, Value> = Map::new();
         let mut disclosed_indices: Vec<usize> = vec![];
 
-        'disclosure_loop: for disclosure in disclosures {
-            for (i, (key, value)) in claims.iter().enumerate() {
-                if *key == *disclosure {
-                    disclosed_claims.insert(key.clone(), value.clone());
-                    disclosed_indices.push(i);
-                    continue 'disclosure_loop;
-                }
+        for (i, (key, value)) in Self::canonical_claims(claims).into_iter().enumerate() {
+            if disclosures.contains(key) {
+                disclosed_claims.insert(key.clone(), value.clone());
+                disclosed_indices.push(i);
             }
         }
 
@@ -93,31 +339,79 @@ pub trait SdAlgorithm {
     }
 
 
-    /// Encodes the claims passed as argument to be a vector of vectors of bytes. Currently only works with Values that are strings.
+    /// Returns the claims sorted by key. This is the canonical order in which claims are indexed, hashed and signed,
+    /// so that issuer and verifier agree regardless of the iteration order of the underlying map.
     ///
     /// # Arguments
     /// * `claims` - A map containing the claims.
     ///
     /// # Returns
-    /// Returns a result containing the encoding of claims as bytes or a string representing an error.
-    fn convert_claims_to_bytes(claims: &Map<String, Value>) -> Result<Vec<Vec<u8>>, String> {
-        let mut messages: Vec<String> = vec![];
-        let mut message;
+    /// Returns the key-value pairs of the claims sorted by key.
+    fn canonical_claims(claims: &Map<String, Value>) -> Vec<(&String, &Value)> {
+        let mut sorted_claims: Vec<(&String, &Value)> = claims.iter().collect();
+        sorted_claims.sort_by_key(|(name, _)| *name);
+        sorted_claims
+    }
 
-        for (key, value) in claims {
-            if let Value::String(val) = value { // Only works with strings
-                message = key.clone();
-                message.push(':');
-                message.push_str(val);
-                messages.push(message);
+
+    /// Serializes a JSON value canonically: no whitespace, object members sorted by key at every nesting level and
+    /// numbers in their canonical decimal encoding, so that e.g. `1.50` and `15e-1` are serialized alike.
+    ///
+    /// # Arguments
+    /// * `value` - Value to be serialized.
+    ///
+    /// # Returns
+    /// Returns the canonical serialization of the value.
+    fn canonical_json(value: &Value) -> String {
+        match value {
+            Value::Array(array) => {
+                let elements: Vec<String> = array.iter().map(|element| Self::canonical_json(element)).collect();
+                format!("[{}]", elements.join(","))
+            }
+            Value::Object(map) => {
+                let members: Vec<String> = Self::canonical_claims(map).into_iter().map(|(key, member)| {
+                    format!("{}:{}", Value::String(key.clone()), Self::canonical_json(member))
+                }).collect();
+                format!("{{{}}}", members.join(","))
             }
+            Value::Number(number) => { canonical_number(&number.to_string()).unwrap_or_else(|_| number.to_string()) }
+            _ => { value.to_string() }
+        }
+    }
+
+
+    /// Serializes canonically the `SIGNED_FIELDS` present in a VC, or in the credential of a VP, which the issuer binds
+    /// to its commitment to the claims and verifiers rebuild from the credential they receive.
+    ///
+    /// # Arguments
+    /// * `credential` - VC or credential of a VP.
+    ///
+    /// # Returns
+    /// Returns the canonical serialization of the signed fields, or None if the credential has none.
+    fn serialize_signed_fields(credential: &Map<String, Value>) -> Option<String> {
+        let fields: Map<String, Value> = signed_fields(credential);
+        if fields.is_empty() {
+            return None;
         }
+        Some(Self::canonical_json(&Value::Object(fields)))
+    }
 
-        let byte_messages: Vec<Vec<u8>> = messages.iter().map(|message| {
-            message.clone().into_bytes()
-        }).collect();
 
-        Ok(byte_messages)
+    /// Appends the serialized signed fields of a credential to the commitment to its claims, e.g. the list of hashes or
+    /// the Merkle root, giving the bytes signed by the issuer.
+    ///
+    /// # Arguments
+    /// * `commitment` - Commitment to the claims of the credential.
+    /// * `credential` - VC or credential of a VP.
+    ///
+    /// # Returns
+    /// Returns the bytes signed by the issuer.
+    fn signed_bytes(commitment: &[u8], credential: &Map<String, Value>) -> Vec<u8> {
+        let mut bytes: Vec<u8> = commitment.to_vec();
+        if let Some(signed_fields) = Self::serialize_signed_fields(credential) {
+            bytes.extend_from_slice(signed_fields.as_bytes());
+        }
+        bytes
     }
 
 
@@ -131,6 +425,10 @@ pub trait SdAlgorithm {
     fn convert_map_to_payload_and_header(map: &Map<String, Value>) -> Result<(JwsHeader, JwtPayload), String> {
         let mut header: JwsHeader = JwsHeader::new();
         header.set_algorithm(Self::ALGORITHM);
+        match header.set_claim(ENCODING_HEADER, Some(Value::String(Self::ENCODING.name().to_string()))) {
+            Ok(_) => {}
+            Err(err) => { return Err(format!("Failed to set encoding in header: [{err}]")); }
+        };
 
         let payload: JwtPayload = match JwtPayload::from_map(map.clone()) {
             Ok(payload) => { payload }
@@ -141,6 +439,29 @@ pub trait SdAlgorithm {
     }
 
 
+    /// Checks that the encoding declared in a JWT header matches the encoding of the algorithm, so that elements
+    /// encoded differently are never mixed in the same VC or VP.
+    ///
+    /// # Arguments
+    /// * `header` - Decoded JWT header.
+    ///
+    /// # Returns
+    /// Returns a string containing an error in case of mismatch.
+    fn check_header_encoding(header: &JwsHeader) -> Result<(), String> {
+        match header.claim(ENCODING_HEADER) {
+            None => { Ok(()) }
+            Some(Value::String(name)) => {
+                if Encoding::from_name(name)? == Self::ENCODING {
+                    Ok(())
+                } else {
+                    Err(format!("JWT encoding {name} does not match expected encoding {}", Self::ENCODING.name()))
+                }
+            }
+            Some(_) => { Err("Encoding in JWT header is not a string".to_string()) }
+        }
+    }
+
+
     /// Encodes the map passed in input as a jwt
     ///
     /// # Arguments
@@ -149,14 +470,21 @@ pub trait SdAlgorithm {
     /// # Returns
     /// Returns a string containing the encoded jwt or a string containing an error in case of failure.
     fn encode_jwt(map: &Map<String, Value>) -> Result<String, String> {
+        let _envelope = EnvelopeTimer::start();
 
         let (header, payload) = Self::convert_map_to_payload_and_header(map)?;
 
-        let jwt = match jwt::encode_unsecured(&payload, &header) {
-            Ok(jwt) => { jwt }
-            Err(err) => { return Err(format!("Failed to encode jwt: [{err}]")); }
+        let jwt = match Self::COMPRESSION {
+            Compression::None => match jwt::encode_unsecured(&payload, &header) {
+                Ok(jwt) => { jwt }
+                Err(err) => { return Err(format!("Failed to encode jwt: [{err}]")); }
+            },
+            compression => { encode_compact(&header, &payload, compression, None)? }
         };
 
+        debug!("[{}] Encoded VC of {} bytes", Self::ALGORITHM, jwt.len());
+        Self::log_component_sizes(map);
+
         Ok(jwt)
     }
 
@@ -165,16 +493,18 @@ pub trait SdAlgorithm {
     ///
     /// # Arguments
     /// * `jwt` - The jwt to be decoded.
+    /// * `config` - Limits enforced on the jwt.
     ///
     /// # Returns
     /// Returns the map decoded from the jwt.
-    fn decode_jwt(jwt: &String) -> Result<Map<String, Value>, String> {
-        let (payload, _header) = match jwt::decode_unsecured(&jwt) {
-            Ok((vc, header)) => { (vc, header) }
-            Err(err) => { return Err(format!("Failed to decode jwt: [{err}]")); }
-        };
+    fn decode_jwt(jwt: &str, config: &VerifierConfig) -> Result<Map<String, Value>, String> {
+        let _envelope = EnvelopeTimer::start();
+        config.check_jwt_length(jwt)?;
+        let (payload, header) = decode_compact(jwt, None, config.max_jwt_length)?;
+        Self::check_header_encoding(&header)?;
+        config.check_map_depth(&payload)?;
 
-        Ok(payload.claims_set().clone())
+        Ok(payload)
     }
 
 
@@ -187,17 +517,40 @@ pub trait SdAlgorithm {
     /// # Returns
     /// Returns a string containing the encoded and signed jwt or a string containing an error in case of failure.
     fn encode_and_sign_jwt(map: &Map<String, Value>, private_key: &impl AsRef<[u8]>) -> Result<String, String> {
-
-        let (header, payload) = Self::convert_map_to_payload_and_header(map)?;
+        let _envelope = EnvelopeTimer::start();
 
         let signer = match ES256.signer_from_pem(private_key) {
             Ok(signer) => { signer }
             Err(err) => { return Err(format!("Failed to create signer: [{err}]"));}
         };
 
-        let jwt = match jwt::encode_with_signer(&payload, &header, &signer) {
-            Ok(jwt) => { jwt }
-            Err(err) => { return Err(format!("Failed to encode and sign jwt: [{err}]")); }
+        Self::encode_and_sign_jwt_with(map, Some(&signer as &dyn JwsSigner))
+    }
+
+
+    /// Same as `encode_and_sign_jwt`, but signing with any signer, or encoding an unsecured jwt if there is none.
+    ///
+    /// # Arguments
+    /// * `map` - A VC or a VP to be encoded as a jwt.
+    /// * `signer` - Optional signer of the jwt.
+    ///
+    /// # Returns
+    /// Returns a string containing the encoded jwt or a string containing an error in case of failure.
+    fn encode_and_sign_jwt_with(map: &Map<String, Value>, signer: Option<&dyn JwsSigner>) -> Result<String, String> {
+        let _envelope = EnvelopeTimer::start();
+
+        let (header, payload) = Self::convert_map_to_payload_and_header(map)?;
+
+        let jwt = match (Self::COMPRESSION, signer) {
+            (Compression::None, Some(signer)) => match jwt::encode_with_signer(&payload, &header, signer) {
+                Ok(jwt) => { jwt }
+                Err(err) => { return Err(format!("Failed to encode and sign jwt: [{err}]")); }
+            },
+            (Compression::None, None) => match jwt::encode_unsecured(&payload, &header) {
+                Ok(jwt) => { jwt }
+                Err(err) => { return Err(format!("Failed to encode jwt: [{err}]")); }
+            },
+            (compression, signer) => { encode_compact(&header, &payload, compression, signer)? }
         };
 
         Ok(jwt)
@@ -209,22 +562,278 @@ pub trait SdAlgorithm {
     /// # Arguments
     /// * `jwt` - A VC or a VP to be encoded as a jwt.
     /// * `public_key` - A byte array containing the encoding of a public key to verify the encoded jwt.
+    /// * `config` - Limits enforced on the jwt.
     ///
     /// # Returns
     /// Returns the decoded and verified payload or a string containing an error in case of failure.
-    fn decode_and_verify_jwt(jwt: &String, public_key: &impl AsRef<[u8]>) -> Result<Map<String, Value>, String> {
+    fn decode_and_verify_jwt(jwt: &str, public_key: &impl AsRef<[u8]>, config: &VerifierConfig) -> Result<Map<String, Value>, String> {
+        let _envelope = EnvelopeTimer::start();
 
         let verifier = match ES256.verifier_from_pem(public_key) {
             Ok(verifier) => { verifier }
             Err(err) => { return Err(format!("Failed to create verifier: [{err}]")); }
         };
 
-        let (payload, _header) = match jwt::decode_with_verifier(&jwt, &verifier) {
-            Ok(jwt) => { jwt }
-            Err(err) => { return Err(format!("Failed to decode and verify jwt: [{err}]")); }
+        Self::decode_and_verify_jwt_with(jwt, Some(&verifier as &dyn JwsVerifier), config)
+    }
+
+
+    /// Same as `decode_and_verify_jwt`, but verifying with any verifier, or only accepting an unsecured jwt if there is
+    /// none.
+    ///
+    /// # Arguments
+    /// * `jwt` - A VC or a VP to be encoded as a jwt.
+    /// * `verifier` - Optional verifier of the jwt.
+    /// * `config` - Limits enforced on the jwt.
+    ///
+    /// # Returns
+    /// Returns the decoded and verified payload or a string containing an error in case of failure.
+    fn decode_and_verify_jwt_with(jwt: &str, verifier: Option<&dyn JwsVerifier>, config: &VerifierConfig) -> Result<Map<String, Value>, String> {
+        let _envelope = EnvelopeTimer::start();
+
+        config.check_jwt_length(jwt)?;
+
+        let (payload, header) = decode_compact(jwt, verifier, config.max_jwt_length)?;
+        Self::check_header_encoding(&header)?;
+        config.check_map_depth(&payload)?;
+
+        Ok(payload)
+    }
+
+
+    /// Logs at trace level the encoded size of each algorithm-specific component of a credential.
+    ///
+    /// # Arguments
+    /// * `credential` - VC or derived credential.
+    fn log_component_sizes(credential: &Map<String, Value>) {
+        if !log_enabled!(Level::Trace) {
+            return;
+        }
+
+        for (field, value) in credential {
+            if let Value::String(encoded) = value {
+                trace!("[{}] Component {} is {} bytes", Self::ALGORITHM, field, encoded.len());
+            }
+        }
+    }
+
+
+    /// Generates a random nonce for a presentation.
+    ///
+    /// # Returns
+    /// The encoded nonce.
+    fn generate_presentation_nonce() -> String {
+        let mut bytes = vec![0u8; 16];
+        fill_random(&mut bytes[..]);
+        Self::ENCODING.encode(bytes)
+    }
+
+
+    /// Wraps a derived credential in a Verifiable Presentation envelope: a `vp` claim containing the context, the
+    /// `VerifiablePresentation` type and the `verifiableCredential` array, next to the `nonce`, `aud` and `iat` claims,
+    /// and the `nbf` and `exp` claims of the validity window chosen by the holder, if any.
+    ///
+    /// # Arguments
+    /// * `credential` - Derived credential to be presented.
+    /// * `nonce` - Nonce of the presentation.
+    /// * `audience` - Optional intended audience of the presentation.
+    /// * `validity` - Optional validity window of the presentation.
+    ///
+    /// # Returns
+    /// Returns the VP JWT payload as a map.
+    fn wrap_presentation(credential: Map<String, Value>, nonce: String, audience: Option<String>, validity: Option<PresentationValidity>) -> Map<String, Value> {
+        let mut presentation: Map<String, Value> = Map::new();
+        presentation.insert("@context".to_string(), Value::Array(vec![Value::String(VP_CONTEXT.to_string())]));
+        presentation.insert("type".to_string(), Value::Array(vec![Value::String(VP_TYPE.to_string())]));
+        presentation.insert(VERIFIABLE_CREDENTIAL.to_string(), Value::Array(vec![Value::Object(credential)]));
+
+        let mut envelope: Map<String, Value> = Map::new();
+        envelope.insert(VP.to_string(), Value::Object(presentation));
+        envelope.insert(PRESENTATION_NONCE.to_string(), Value::String(nonce));
+        if let Some(audience) = audience {
+            envelope.insert(AUDIENCE.to_string(), Value::String(audience));
+        }
+        if let Ok(issued_at) = unix_time() {
+            envelope.insert(ISSUED_AT.to_string(), Value::from(issued_at));
+            if let Some(validity) = validity {
+                validity.insert_into(&mut envelope, issued_at);
+            }
+        }
+
+        envelope
+    }
+
+
+    /// Checks the Verifiable Presentation envelope and returns the derived credential it contains.
+    ///
+    /// # Arguments
+    /// * `envelope` - VP JWT payload.
+    ///
+    /// # Returns
+    /// Returns the derived credential or a string containing an error if the envelope is not compliant.
+    fn unwrap_presentation(envelope: &Map<String, Value>) -> Result<&Map<String, Value>, String> {
+        let presentation = match envelope.get(VP) {
+            Some(Value::Object(presentation)) => { presentation }
+            _ => { return Err("VP does not contain the vp claim.".to_string()) }
+        };
+
+        match envelope.get(PRESENTATION_NONCE) {
+            Some(Value::String(_)) => {}
+            _ => { return Err("VP does not contain a nonce.".to_string()) }
+        };
+
+        match presentation.get("@context") {
+            Some(Value::Array(contexts)) if contexts.first() == Some(&Value::String(VP_CONTEXT.to_string())) => {}
+            _ => { return Err("VP does not contain the expected @context.".to_string()) }
+        };
+
+        match presentation.get("type") {
+            Some(Value::Array(types)) if types.contains(&Value::String(VP_TYPE.to_string())) => {}
+            _ => { return Err(format!("VP type does not include {VP_TYPE}.")) }
+        };
+
+        match presentation.get(VERIFIABLE_CREDENTIAL) {
+            Some(Value::Array(credentials)) => match credentials.as_slice() {
+                [Value::Object(credential)] => { Ok(credential) }
+                _ => { Err("VP must hold exactly one derived credential.".to_string()) }
+            },
+            _ => { Err(format!("VP does not contain the {VERIFIABLE_CREDENTIAL} array.")) }
+        }
+    }
+
+
+    /// Returns the derived credential if the map is a VP envelope, or the map itself otherwise.
+    ///
+    /// # Arguments
+    /// * `map` - Either a VC or a VP JWT payload.
+    ///
+    /// # Returns
+    /// Returns the credential or a string containing an error if the envelope is not compliant.
+    fn credential_or_presentation(map: &Map<String, Value>) -> Result<&Map<String, Value>, String> {
+        if map.contains_key(VP) {
+            Self::unwrap_presentation(map)
+        } else {
+            Ok(map)
+        }
+    }
+
+
+    /// Wraps a derived credential in a Verifiable Presentation envelope with a fresh nonce and signs it.
+    ///
+    /// # Arguments
+    /// * `credential` - Derived credential to be presented.
+    /// * `holder_binding` - Holder binding signing the presentation, e.g. a byte vector containing a ES256 private key.
+    ///
+    /// # Returns
+    /// Returns the VP JWT payload and the signed jwt, or a string containing an error in case of failure.
+    fn encode_and_sign_presentation(credential: Map<String, Value>, holder_binding: &(impl HolderBinding + ?Sized)) -> Result<(Map<String, Value>, String), String> {
+        Self::encode_and_sign_presentation_for_audience(credential, None, None, holder_binding)
+    }
+
+
+    /// Same as `encode_and_sign_presentation`, but binding the presentation to an optional audience and validity window.
+    ///
+    /// # Arguments
+    /// * `credential` - Derived credential to be presented.
+    /// * `audience` - Optional intended audience of the presentation, required to disclose sensitive claims.
+    /// * `validity` - Optional validity window of the presentation.
+    /// * `holder_binding` - Holder binding signing the presentation, e.g. a byte vector containing a ES256 private key.
+    ///
+    /// # Returns
+    /// Returns the VP JWT payload and the signed jwt, or a string containing an error in case of failure.
+    fn encode_and_sign_presentation_for_audience(credential: Map<String, Value>, audience: Option<String>, validity: Option<PresentationValidity>, holder_binding: &(impl HolderBinding + ?Sized)) -> Result<(Map<String, Value>, String), String> {
+        let envelope: Map<String, Value> = Self::wrap_presentation(credential, Self::generate_presentation_nonce(), audience, validity);
+        Self::check_presentation_metadata(&envelope)?;
+        let jwt: String = Self::encode_and_sign_jwt_with(&envelope, holder_binding.signer()?.as_deref())?;
+
+        if log_enabled!(Level::Debug) {
+            let credential = Self::unwrap_presentation(&envelope)?;
+            debug!("[{}] Encoded VP of {} bytes disclosing {} claims", Self::ALGORITHM, jwt.len(), Self::disclosed_claim_names(credential)?.len());
+            Self::log_component_sizes(credential);
+        }
+
+        Ok((envelope, jwt))
+    }
+
+
+    /// Decodes and verifies a VP jwt and returns the derived credential contained in its envelope. The holder binding
+    /// must accept the credential, e.g. if the credential is bound to a holder key through its `cnf` claim, the key
+    /// must be the one that signed the VP. The VP must be within the validity window set by the holder, if any.
+    ///
+    /// # Arguments
+    /// * `jwt` - The VP encoded as a jwt.
+    /// * `holder_binding` - Holder binding verifying the presentation, e.g. a byte array containing the encoding of a ES256 public key.
+    /// * `config` - Limits enforced on the VP, and time its validity window is checked at.
+    ///
+    /// # Returns
+    /// Returns the derived credential or a string containing an error in case of failure.
+    fn decode_and_verify_presentation(jwt: &str, holder_binding: &(impl HolderBinding + ?Sized), config: &VerifierConfig) -> Result<Map<String, Value>, String> {
+        let envelope: Map<String, Value> = Self::decode_and_verify_jwt_with(jwt, holder_binding.verifier()?.as_deref(), config)?;
+        Self::check_presentation_metadata(&envelope)?;
+        config.check_presentation_window(&envelope)?;
+        let credential: &Map<String, Value> = Self::unwrap_presentation(&envelope)?;
+        holder_binding.check_credential(credential, &Self::disclosed_claim_names(credential)?)?;
+
+        if log_enabled!(Level::Debug) {
+            debug!("[{}] Decoded VP of {} bytes disclosing {} claims", Self::ALGORITHM, jwt.len(), Self::disclosed_claim_names(credential)?.len());
+        }
+
+        Ok(credential.clone())
+    }
+
+
+    /// Same as `encode_and_sign_presentation_for_audience`, but signing the presentation with an ephemeral key whose
+    /// certificate, signed by the long-term key of the holder, is carried in the envelope.
+    ///
+    /// # Arguments
+    /// * `credential` - Derived credential to be presented.
+    /// * `audience` - Optional intended audience of the presentation, required to disclose sensitive claims.
+    /// * `validity` - Optional validity window of the presentation.
+    /// * `ephemeral_key` - Ephemeral key signing the presentation.
+    /// * `certificate` - Certificate of the ephemeral key.
+    ///
+    /// # Returns
+    /// Returns the VP JWT payload and the signed jwt, or a string containing an error in case of failure.
+    fn encode_and_sign_presentation_with_ephemeral_key(credential: Map<String, Value>, audience: Option<String>, validity: Option<PresentationValidity>, ephemeral_key: &EphemeralKey, certificate: &str) -> Result<(Map<String, Value>, String), String> {
+        let mut envelope: Map<String, Value> = Self::wrap_presentation(credential, Self::generate_presentation_nonce(), audience, validity);
+        envelope.insert(EPHEMERAL_KEY_CERTIFICATE.to_string(), Value::String(certificate.to_string()));
+        Self::check_presentation_metadata(&envelope)?;
+        let jwt: String = Self::encode_and_sign_jwt(&envelope, &ephemeral_key.private_key())?;
+
+        if log_enabled!(Level::Debug) {
+            debug!("[{}] Encoded VP of {} bytes with an ephemeral key certificate of {} bytes", Self::ALGORITHM, jwt.len(), certificate.len());
+        }
+
+        Ok((envelope, jwt))
+    }
+
+
+    /// Same as `decode_and_verify_presentation`, for presentations signed with a certified ephemeral key: the
+    /// certificate must be signed by the long-term key of the holder, which the credential must be bound to, and the
+    /// VP by the certified key.
+    ///
+    /// # Arguments
+    /// * `jwt` - The VP encoded as a jwt.
+    /// * `holder_public_key` - Long-term public key of the holder.
+    /// * `config` - Limits enforced on the VP.
+    ///
+    /// # Returns
+    /// Returns the derived credential or a string containing an error in case of failure.
+    fn decode_and_verify_presentation_with_ephemeral_key(jwt: &str, holder_public_key: &impl AsRef<[u8]>, config: &VerifierConfig) -> Result<Map<String, Value>, String> {
+        config.check_jwt_length(jwt)?;
+        let certificate: String = match unverified_claims(jwt)?.remove(EPHEMERAL_KEY_CERTIFICATE) {
+            Some(Value::String(certificate)) => { certificate }
+            _ => { return Err(format!("VP does not contain {EPHEMERAL_KEY_CERTIFICATE}.")) }
         };
+        let ephemeral_public_key: Vec<u8> = verify_certificate(&certificate, holder_public_key)?;
 
-        Ok(payload.claims_set().clone())
+        let envelope: Map<String, Value> = Self::decode_and_verify_jwt(jwt, &ephemeral_public_key, config)?;
+        Self::check_presentation_metadata(&envelope)?;
+        config.check_presentation_window(&envelope)?;
+        let credential: &Map<String, Value> = Self::unwrap_presentation(&envelope)?;
+        verify_holder_binding(credential, holder_public_key)?;
+
+        Ok(credential.clone())
     }
 
 
@@ -241,19 +850,39 @@ pub trait SdAlgorithm {
     where
         T: ?Sized + Serialize,
     {
+        Self::serialize_and_insert_with_encoding(map, field, element, Self::ENCODING)
+    }
+
+    /// Same as `serialize_and_insert`, but with an explicit encoding instead of the one of the algorithm.
+    ///
+    /// # Arguments
+    /// * `map` - The VC or VP to manipulate.
+    /// * `field` - Name of the element to be serialized and inserted.
+    /// * `element` - Value of the element to be serialized and inserted.
+    /// * `encoding` - Encoding of the serialized element.
+    ///
+    /// # Returns
+    /// Returns a result wrapping a string that displays information about the error in case of failure.
+    fn serialize_and_insert_with_encoding<T>(map: &mut Map<String, Value>, field: String, element: &T, encoding: Encoding) -> Result<(), String>
+    where
+        T: ?Sized + Serialize,
+    {
+        let _envelope = EnvelopeTimer::start();
         let serialized_element = match serde_json::to_string(&element) {
             Ok(serialized_element) => { serialized_element }
             Err(err) => { return Err(format!("Failed to serialize {field}: [{err}]")); }
         };
 
-        let encoded_element = multibase::Base::Base64Url.encode(serialized_element);
+        let encoded_element = encoding.encode(serialized_element);
 
         map.insert(field.to_string(), Value::String(encoded_element));       // We just ignore if another field was present
 
         Ok(())
     }
 
-    /// Extracts an element from a VC or VP map and deserializes it into an object.
+    /// Extracts an element from a VC or VP map and deserializes it into an object. No limit is enforced on the element,
+    /// as issuers and holders only decode their own credentials: verifiers decode the elements of untrusted VCs and VPs
+    /// with `get_and_decode_with_config`.
     ///
     /// # Arguments
     /// * `map` - The VC or VP from which the element must be extracted from.
@@ -265,6 +894,40 @@ pub trait SdAlgorithm {
     where
         T: DeserializeOwned,
     {
+        Self::get_and_decode_with_encoding(map, field, Self::ENCODING, None)
+    }
+
+    /// Same as `get_and_decode`, but enforcing the limits of the verifier on the encoded element.
+    ///
+    /// # Arguments
+    /// * `map` - The VC or VP from which the element must be extracted from.
+    /// * `field` - Name of the element to be extracted.
+    /// * `config` - Limits enforced on the element.
+    ///
+    /// # Returns
+    /// Returns the decoded value of the element or a string containing an error in case of failure.
+    fn get_and_decode_with_config<T>(map: &Map<String, Value>, field: String, config: &VerifierConfig) -> Result<T, String>
+    where
+        T: DeserializeOwned,
+    {
+        Self::get_and_decode_with_encoding(map, field, Self::ENCODING, Some(config))
+    }
+
+    /// Same as `get_and_decode`, but with an explicit encoding instead of the one of the algorithm.
+    ///
+    /// # Arguments
+    /// * `map` - The VC or VP from which the element must be extracted from.
+    /// * `field` - Name of the element to be extracted.
+    /// * `encoding` - Encoding of the serialized element.
+    /// * `config` - Limits enforced on the element, if any.
+    ///
+    /// # Returns
+    /// Returns the decoded value of the element or a string containing an error in case of failure.
+    fn get_and_decode_with_encoding<T>(map: &Map<String, Value>, field: String, encoding: Encoding, config: Option<&VerifierConfig>) -> Result<T, String>
+    where
+        T: DeserializeOwned,
+    {
+        let _envelope = EnvelopeTimer::start();
         let encoded_element: String = match map.get(&field) {
             None => return Err(format!("Failed to retrieve {field} from {:?}", map)),
             Some(value) => match value {
@@ -273,7 +936,10 @@ pub trait SdAlgorithm {
             },
         };
 
-        let serialized_element_byte_vector = match multibase::Base::Base64Url.decode(&encoded_element) {
+        if let Some(config) = config {
+            config.check_jwt_length(&encoded_element)?;
+        }
+        let serialized_element_byte_vector = match encoding.decode(&encoded_element) {
             Ok(serialized_element) => { serialized_element }
             Err(err) => { return Err(format!("Failed to decode {field} [{err}].")); }
         };
@@ -283,7 +949,7 @@ pub trait SdAlgorithm {
             Err(err) => { return Err(format!("Failed to to convert from byte vector {field}. Failed  [{err}].")); }
         };
 
-        let element: T = match serde_json::from_str::<T>(&serialized_element) {
+        let element: T = match json::from_str::<T>(&serialized_element) {
             Ok(element) => { element }
             Err(err) => { return Err(format!("Failed to deserialize {field} [{err}].")) }
         };
@@ -291,4 +957,171 @@ pub trait SdAlgorithm {
         Ok(element)
     }
 
-}
\ No newline at end of file
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+    use log::{LevelFilter, Log, Metadata, Record};
+    use serde_json::{json, Map, Value};
+    use crate::adapters::adapter::Adapter;
+    use crate::adapters::hashes::sd_jwt_adapter::SdJwtAdapter;
+    use crate::common_data::{AUDIENCE, CLAIMS, ISSUED_AT, PRESENTATION_NONCE, SIGNATURE, VC, VERIFIABLE_CREDENTIAL, VP};
+    use crate::sd_algorithms::hashes::sd_jwt::SdJwtInstance;
+    use crate::sd_algorithms::sd_algorithm::SdAlgorithm;
+
+    /// Logger keeping every message, as other tests may log concurrently.
+    struct CapturingLogger(Mutex<Vec<String>>);
+
+    impl Log for CapturingLogger {
+        fn enabled(&self, _metadata: &Metadata) -> bool { true }
+
+        fn log(&self, record: &Record) {
+            if let Ok(mut messages) = self.0.lock() {
+                messages.push(record.args().to_string());
+            }
+        }
+
+        fn flush(&self) {}
+    }
+
+    static LOGGER: CapturingLogger = CapturingLogger(Mutex::new(vec![]));
+
+    fn credential() -> Map<String, Value> {
+        Map::from_iter([(CLAIMS.to_string(), json!({"name": "Alice"}))])
+    }
+
+    #[test]
+    fn presentation_envelope_round_trip() -> Result<(), String> {
+        let nonce: String = SdJwtInstance::generate_presentation_nonce();
+        assert_ne!(nonce, SdJwtInstance::generate_presentation_nonce());
+
+        let envelope: Map<String, Value> = SdJwtInstance::wrap_presentation(credential(), nonce.clone(), Some("https://verifier.example".to_string()), None);
+        assert_eq!(envelope.get(PRESENTATION_NONCE), Some(&Value::String(nonce)));
+        assert_eq!(envelope.get(AUDIENCE), Some(&json!("https://verifier.example")));
+        assert!(envelope.get(ISSUED_AT).is_some_and(Value::is_u64));
+        assert_eq!(SdJwtInstance::unwrap_presentation(&envelope)?, &credential());
+        assert_eq!(SdJwtInstance::credential_or_presentation(&envelope)?, &credential());
+
+        // A VC is returned as is, without an audience the envelope has none.
+        assert_eq!(SdJwtInstance::credential_or_presentation(&credential())?, &credential());
+        let envelope: Map<String, Value> = SdJwtInstance::wrap_presentation(credential(), "nonce".to_string(), None, None);
+        assert!(!envelope.contains_key(AUDIENCE));
+
+        Ok(())
+    }
+
+    #[test]
+    fn non_compliant_envelopes_are_rejected() {
+        let envelope: Map<String, Value> = SdJwtInstance::wrap_presentation(credential(), "nonce".to_string(), None, None);
+        let tampered = |tamper: &dyn Fn(&mut Map<String, Value>)| {
+            let mut tampered: Map<String, Value> = envelope.clone();
+            tamper(&mut tampered);
+            tampered
+        };
+
+        let malformed: [Map<String, Value>; 7] = [
+            tampered(&|envelope| { envelope.remove(VP); }),
+            tampered(&|envelope| { envelope.remove(PRESENTATION_NONCE); }),
+            tampered(&|envelope| { envelope[VP]["@context"] = json!(["https://example.org"]); }),
+            tampered(&|envelope| { envelope[VP]["type"] = json!(["VerifiableCredential"]); }),
+            tampered(&|envelope| { envelope[VP][VERIFIABLE_CREDENTIAL] = json!([]); }),
+            tampered(&|envelope| { envelope[VP][VERIFIABLE_CREDENTIAL] = json!([credential(), credential()]); }),
+            tampered(&|envelope| { envelope[VP].as_object_mut().map(|presentation| presentation.remove(VERIFIABLE_CREDENTIAL)); }),
+        ];
+        for envelope in malformed {
+            assert!(SdJwtInstance::unwrap_presentation(&envelope).is_err(), "{envelope:?} was unwrapped");
+        }
+
+        // Once the vp claim is present, a VP is never taken for a VC.
+        let mut missing_nonce: Map<String, Value> = envelope.clone();
+        missing_nonce.remove(PRESENTATION_NONCE);
+        assert!(SdJwtInstance::credential_or_presentation(&missing_nonce).is_err());
+    }
+
+    #[test]
+    fn claims_are_ordered_canonically() -> Result<(), String> {
+        let mut claims: Map<String, Value> = Map::new();
+        for key in ["c", "a", "b"] {
+            claims.insert(key.to_string(), Value::String(key.to_uppercase()));
+        }
+        let keys: Vec<&String> = SdJwtInstance::canonical_claims(&claims).into_iter().map(|(key, _)| key).collect();
+        assert_eq!(keys, ["a", "b", "c"]);
+
+        // Indices follow the canonical order, not the order of the disclosures.
+        let mut vc: Map<String, Value> = Map::from_iter([(CLAIMS.to_string(), Value::Object(claims))]);
+        let disclosures: Vec<String> = vec!["c".to_string(), "a".to_string(), "unknown".to_string()];
+        assert_eq!(SdJwtInstance::filter_claims_by_disclosure_and_insert(&mut vc, &disclosures)?, vec![0, 2]);
+        assert_eq!(vc.get(CLAIMS), Some(&json!({"a": "A", "c": "C"})));
+
+        assert!(SdJwtInstance::filter_claims_by_disclosure_and_insert(&mut Map::new(), &disclosures).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn json_is_serialized_canonically() -> Result<(), String> {
+        let value: Value = match serde_json::from_str(r#"{ "b": [2, 1, {"y": true, "x": null}], "a": "quote \" here", "n": 1.50 }"#) {
+            Ok(value) => { value }
+            Err(err) => { return Err(format!("Failed to parse the test value: [{err}]")) }
+        };
+        assert_eq!(SdJwtInstance::canonical_json(&value), r#"{"a":"quote \" here","b":[2,1,{"x":null,"y":true}],"n":1.5}"#);
+
+        assert_eq!(SdJwtInstance::canonical_json(&json!(1.50)), SdJwtInstance::canonical_json(&json!(15e-1)));
+        assert_eq!(SdJwtInstance::canonical_json(&json!({"b": 1, "a": 2})), SdJwtInstance::canonical_json(&json!({"a": 2, "b": 1})));
+        assert_ne!(SdJwtInstance::canonical_json(&json!([1, 2])), SdJwtInstance::canonical_json(&json!([2, 1])));
+
+        Ok(())
+    }
+
+    #[test]
+    fn sizes_are_logged_by_level() -> Result<(), String> {
+        let raw_vc: Map<String, Value> = match serde_json::from_str::<Map<String, Value>>(VC) {
+            Ok(vc) => { vc }
+            Err(err) => { return Err(format!("Failed to parse Raw Verifiable Credential from string. [{err}]")); }
+        };
+        let adapter = SdJwtAdapter::new(16)?;
+        let logged = |message: &str| LOGGER.0.lock().map(|messages| messages.iter().any(|logged| logged == message)).unwrap_or(false);
+
+        if log::set_logger(&LOGGER).is_err() {
+            return Err("Another logger is already installed".to_string());
+        }
+
+        // Component sizes are only logged at trace level.
+        log::set_max_level(LevelFilter::Debug);
+        let (vc, vc_jwt) = adapter.issue_vc(&raw_vc)?;
+        assert!(logged(&format!("[SD-JWT] Encoded VC of {} bytes", vc_jwt.len())));
+        let signature_length: usize = vc.get(SIGNATURE).and_then(Value::as_str).map(str::len).unwrap_or_default();
+        assert!(!logged(&format!("[SD-JWT] Component {SIGNATURE} is {signature_length} bytes")));
+
+        log::set_max_level(LevelFilter::Trace);
+        let (vp, vp_jwt) = adapter.issue_vp(&vc, &vec!["name".to_string()])?;
+        assert!(logged(&format!("[SD-JWT] Encoded VP of {} bytes disclosing 1 claims", vp_jwt.len())));
+        let credential: &Map<String, Value> = SdJwtInstance::unwrap_presentation(&vp)?;
+        for (field, value) in credential {
+            if let Value::String(encoded) = value {
+                assert!(logged(&format!("[SD-JWT] Component {field} is {} bytes", encoded.len())), "{field} size was not logged");
+            }
+        }
+        log::set_max_level(LevelFilter::Off);
+
+        Ok(())
+    }
+
+    #[test]
+    fn disclosures_are_resolved_once_in_order() -> Result<(), String> {
+        let raw_vc: Map<String, Value> = match serde_json::from_str::<Map<String, Value>>(VC) {
+            Ok(vc) => { vc }
+            Err(err) => { return Err(format!("Failed to parse Raw Verifiable Credential from string. [{err}]")); }
+        };
+        let (vc, _vc_jwt) = SdJwtAdapter::new(16)?.issue_vc(&raw_vc)?;
+
+        let disclosures: Vec<String> = ["name", "birthdate", "name"].iter().map(|disclosure| disclosure.to_string()).collect();
+        assert_eq!(SdJwtInstance::resolve_disclosures(&vc, &disclosures)?, vec!["name".to_string(), "birthdate".to_string()]);
+        assert!(SdJwtInstance::resolve_disclosures(&vc, &vec![])?.is_empty());
+        assert!(SdJwtInstance::resolve_disclosures(&vc, &vec!["name".to_string(), "unknown".to_string()]).is_err());
+
+        Ok(())
+    }
+}
@@ -2,10 +2,18 @@ use serde_json::{Map, Value};
 use zkryptium::bbsplus::keys::{BBSplusPublicKey, BBSplusSecretKey};
 use zkryptium::schemes::algorithms::{BbsBls12381Sha256};
 use zkryptium::schemes::generics::{PoKSignature, Signature};
-use zkryptium::utils::util::bbsplus_utils::generate_random_secret;
+use crate::audit::{claim_proofs_document, SIGNED_FIELDS, VALUE};
 use crate::common_data::SIGNATURE;
-use crate::sd_algorithms::sd_algorithm::SdAlgorithm;
+use crate::cost_profile::{record, Operation};
+use crate::deterministic::random_bytes;
+use crate::credential_ir::CredentialIr;
+use crate::encoding::Encoding;
+use crate::holder_binding::HolderBinding;
+use crate::presentation_validity::PresentationValidity;
+use crate::sd_algorithms::claim_protector::{bind_claim, ClaimProtector};
+use crate::sd_algorithms::sd_algorithm::{signed_fields, PresentationBatch, SdAlgorithm};
 use crate::sd_algorithms::signatures::signature_sd_algorithm::SignatureSdAlgorithm;
+use crate::verifier_config::VerifierConfig;
 
 /// Identifier for the nonce in the VC/VP.
 pub const NONCE: &str = "nonce";
@@ -16,14 +24,55 @@ pub const INDICES: &str = "indices";
 /// Struct that hosts an instance of a BBSPlus algorithm.
 pub struct BBSPlusInstance;
 
+/// Presentation of a BBS+ VC prepared ahead of the verifier's request, holding the decoded signature and the messages.
+pub struct PreparedPresentation {
+    /// Verifiable Credential the presentation is derived from.
+    vc: Map<String, Value>,
+    /// Claims of the credential encoded as messages, in order of signature.
+    claims: Vec<Vec<u8>>,
+    /// BBS+ signature of the issuer.
+    signature: Signature<BbsBls12381Sha256>,
+}
+
 impl SdAlgorithm for BBSPlusInstance {
     const ALGORITHM: &'static str = "BBS+";
+    const PROOF_FIELDS: &'static [&'static str] = &[SIGNATURE];
 }
 
 impl SignatureSdAlgorithm for BBSPlusInstance {}
 
+impl ClaimProtector for BBSPlusInstance {
+    type Element = Vec<u8>;
+
+    // String values are encoded as they are, other values through their canonical JSON serialization.
+    fn protect_claim(name: &str, value: &Value, _opening: Option<&String>) -> Result<Vec<u8>, String> {
+        let message: Vec<u8> = match value {
+            Value::String(val) => { bind_claim(name, val, None) }
+            _ => { bind_claim(name, &Self::canonical_json(value), None) }
+        };
+        Ok(message)
+    }
+}
+
 impl BBSPlusInstance {
 
+    /// Encodes the claims passed as argument as the messages signed by the issuer, in canonical order.
+    ///
+    /// # Arguments
+    /// * `claims` - A map containing the claims.
+    ///
+    /// # Returns
+    /// Returns a result containing the messages or a string representing an error.
+    fn claim_messages(claims: &Map<String, Value>) -> Result<Vec<Vec<u8>>, String> {
+        Self::protected_elements(claims, &Map::new())
+    }
+
+
+    /// Header of the BBS+ signature of a credential: its serialized signed fields, if any, so that the signature and
+    /// the proofs derived from it cover them.
+    fn signature_header(credential: &Map<String, Value>) -> Option<Vec<u8>> {
+        Self::serialize_signed_fields(credential).map(String::into_bytes)
+    }
 
     /// Given a raw VC containing a few fields and the credentialSubject field to include claims, create all the necessary data to create a VC using this algorithm.
     ///
@@ -36,16 +85,17 @@ impl BBSPlusInstance {
     /// Returns a VC both in the form of a Map and in the form of an unsigned JWT.
     pub fn issue_vc(raw_vc: &Map<String, Value>, issuer_public_key: &BBSplusPublicKey, issuer_private_key: &BBSplusSecretKey) -> Result<(Map<String, Value>, String), String> {
 
-        let mut vc = raw_vc.clone();
+        let credential: CredentialIr = Self::lower(raw_vc)?;
+        let mut vc = credential.to_vc();
 
-        let claims = Self::extract_claims(&vc)?;
-        let claims_bytes = Self::convert_claims_to_bytes(claims)?;
+        let claims_bytes = Self::claim_messages(credential.claims())?;
+        let header: Option<Vec<u8>> = Self::signature_header(&vc);
 
         let signature = match Signature::<BbsBls12381Sha256>::sign(
             Some(&claims_bytes),
             issuer_private_key,
             issuer_public_key,
-            None,
+            header.as_deref(),
         ) {
             Ok(signature) => { signature }
             Err(err) => { return Err(format!("Error in producing signature [{}]", err.to_string()).to_string()) }
@@ -67,12 +117,29 @@ impl BBSPlusInstance {
     /// # Returns
     /// Returns a string containing an error in case of failure.
     pub fn verify_vc(vc: &Map<String, Value>, issuer_public_key: &BBSplusPublicKey) -> Result<(), String> {
+        Self::verify_vc_with_config(vc, issuer_public_key, &Self::VERIFIER_CONFIG)
+    }
 
-        let signature: Signature<BbsBls12381Sha256> = Self::get_and_decode(vc, SIGNATURE.to_string())?;
+
+    /// Same as `verify_vc`, but enforcing the limits of the verifier on the amount of claims and on the signature.
+    ///
+    /// # Arguments
+    /// * `vc` - Verifiable Credential.
+    /// * `issuer_public_key` - Issuer's public key to verify the BBS+ signature.
+    /// * `config` - Limits enforced on the VC.
+    ///
+    /// # Returns
+    /// Returns a string containing an error in case of failure.
+    pub fn verify_vc_with_config(vc: &Map<String, Value>, issuer_public_key: &BBSplusPublicKey, config: &VerifierConfig) -> Result<(), String> {
+
+        let signature: Signature<BbsBls12381Sha256> = Self::get_and_decode_with_config(vc, SIGNATURE.to_string(), config)?;
         let claims = Self::extract_claims(vc)?;
-        let claims_bytes = Self::convert_claims_to_bytes(claims)?;
+        config.check_claims_len(claims.len())?;
+        let claims_bytes = Self::claim_messages(claims)?;
+        let header: Option<Vec<u8>> = Self::signature_header(vc);
 
-        match signature.verify(issuer_public_key, Some(&claims_bytes), None) {
+        record(Operation::Pairing, 2);
+        match signature.verify(issuer_public_key, Some(&claims_bytes), header.as_deref()) {
             Ok(_) => { Ok(()) }
             Err(err) => { Err(format!("Signature verification failed [{err}]")) }
         }
@@ -86,26 +153,100 @@ impl BBSPlusInstance {
     /// * `vp` - Verifiable Credential.
     /// * `disclosures` - List of strings containing the names of the claims that are to be disclosed.
     /// * `issuer_public_key` - Issuer's public key necessary for computing the derived signature.
-    /// * `holder_private_key` - Holder's private key necessary for proof of possession.
+    /// * `holder_binding` - Holder binding signing the VP as proof of possession.
     ///
     /// # Returns
     /// Returns the VP both in form of a Map and in form of a signed JWT.
-    pub fn issue_vp(vc: &Map<String, Value>, disclosures: &Vec<String>, issuer_public_key: &BBSplusPublicKey, holder_private_key: &impl AsRef<[u8]>) -> Result<(Map<String, Value>, String), String> {
+    pub fn issue_vp(vc: &Map<String, Value>, disclosures: &[String], issuer_public_key: &BBSplusPublicKey, holder_binding: &(impl HolderBinding + ?Sized)) -> Result<(Map<String, Value>, String), String> {
+        Self::issue_vp_for_audience(vc, disclosures, None, None, issuer_public_key, holder_binding)
+    }
 
-        let mut vp: Map<String, Value> = vc.clone();
-        let claims = Self::extract_claims(&mut vp)?.clone();
-        let disclosed_indices = Self::filter_claims_by_disclosure_and_insert(&mut vp, disclosures)?;
 
-        let nonce = generate_random_secret(32);
-        let bbs_signature: Signature<BbsBls12381Sha256> = Self::get_and_decode(&mut vp, SIGNATURE.to_string())?;
-        let claims = Self::convert_claims_to_bytes(&claims)?;
+    /// Same as `issue_vp`, but binding the presentation to an optional audience, as required to disclose sensitive claims.
+    ///
+    /// # Arguments
+    /// * `vc` - Verifiable Credential.
+    /// * `disclosures` - List of strings containing the names of the claims that are to be disclosed.
+    /// * `audience` - Optional intended audience of the presentation.
+    /// * `validity` - Optional validity window of the presentation.
+    /// * `issuer_public_key` - Issuer's public key necessary for computing the derived signature.
+    /// * `holder_binding` - Holder binding signing the VP as proof of possession.
+    ///
+    /// # Returns
+    /// Returns the VP both in form of a Map and in form of a signed JWT.
+    pub fn issue_vp_for_audience(vc: &Map<String, Value>, disclosures: &[String], audience: Option<String>, validity: Option<PresentationValidity>, issuer_public_key: &BBSplusPublicKey, holder_binding: &(impl HolderBinding + ?Sized)) -> Result<(Map<String, Value>, String), String> {
+        let prepared: PreparedPresentation = Self::prepare_presentation(vc)?;
+        Self::issue_prepared_vp(&prepared, disclosures, &random_bytes(32), audience, validity, issuer_public_key, holder_binding)
+    }
+
+
+    /// Performs the offline phase of a presentation, i.e. the work that depends neither on the disclosures nor on the
+    /// nonce: decoding the signature and encoding every claim as a message. The proof itself is generated by
+    /// `issue_prepared_vp` once the verifier's nonce is known, as zkryptium computes the blinding scalars and the
+    /// commitments of the proof within `proof_gen` and does not expose them.
+    ///
+    /// # Arguments
+    /// * `vc` - Verifiable Credential.
+    ///
+    /// # Returns
+    /// Returns the prepared presentation or a string containing an error in case of failure.
+    ///
+    /// # Examples
+    /// ```
+    /// use serde_json::{Map, Value};
+    /// use csd_jwt::adapters::adapter::{Adapter, VerifierAdapter};
+    /// use csd_jwt::adapters::signatures::bbs_plus_adapter::BBSPlusAdapter;
+    /// use csd_jwt::common_data::VC;
+    ///
+    /// let raw_vc: Map<String, Value> = serde_json::from_str(VC).unwrap();
+    /// let adapter = BBSPlusAdapter::new(13).unwrap();
+    /// let (vc, _) = adapter.issue_vc(&raw_vc).unwrap();
+    /// let prepared = adapter.prepare_presentation(&vc).unwrap();
+    ///
+    /// let verifier_nonce: Vec<u8> = b"verifier nonce".to_vec();
+    /// let (_, vp_jwt) = adapter.issue_prepared_vp(&prepared, &vec!["name".to_string()], &verifier_nonce).unwrap();
+    /// adapter.verify_vp(&vp_jwt).unwrap();
+    ///
+    /// // The prepared VC only discloses its own claims.
+    /// assert!(adapter.issue_prepared_vp(&prepared, &vec!["unknown".to_string()], &verifier_nonce).is_err());
+    /// ```
+    pub fn prepare_presentation(vc: &Map<String, Value>) -> Result<PreparedPresentation, String> {
+        let claims: Vec<Vec<u8>> = Self::claim_messages(Self::extract_claims(vc)?)?;
+        let signature: Signature<BbsBls12381Sha256> = Self::get_and_decode(vc, SIGNATURE.to_string())?;
+
+        Ok(PreparedPresentation { vc: vc.clone(), claims, signature })
+    }
+
+
+    /// Performs the online phase of a presentation prepared with `prepare_presentation`, generating the proof of
+    /// knowledge of the signature bound to the verifier's nonce. A prepared presentation can be reused for any amount
+    /// of presentations.
+    ///
+    /// # Arguments
+    /// * `prepared` - Presentation prepared from the Verifiable Credential.
+    /// * `disclosures` - List of strings containing the names of the claims that are to be disclosed.
+    /// * `nonce` - Nonce the proof is bound to, usually received from the verifier.
+    /// * `audience` - Optional intended audience of the presentation.
+    /// * `validity` - Optional validity window of the presentation.
+    /// * `issuer_public_key` - Issuer's public key necessary for computing the derived signature.
+    /// * `holder_binding` - Holder binding signing the VP as proof of possession.
+    ///
+    /// # Returns
+    /// Returns the VP both in form of a Map and in form of a signed JWT.
+    pub fn issue_prepared_vp(prepared: &PreparedPresentation, disclosures: &[String], nonce: &[u8], audience: Option<String>, validity: Option<PresentationValidity>, issuer_public_key: &BBSplusPublicKey, holder_binding: &(impl HolderBinding + ?Sized)) -> Result<(Map<String, Value>, String), String> {
+
+        let mut vp: Map<String, Value> = prepared.vc.clone();
+        let disclosures: &Vec<String> = &Self::resolve_disclosures(&prepared.vc, disclosures)?;
+        let disclosed_indices = Self::filter_claims_by_disclosure_and_insert(&mut vp, disclosures)?;
+        let nonce: Vec<u8> = nonce.to_vec();
+        let header: Option<Vec<u8>> = Self::signature_header(&prepared.vc);
 
         let proof: PoKSignature<BbsBls12381Sha256> = match PoKSignature::<BbsBls12381Sha256>::proof_gen(
             &issuer_public_key,
-            &bbs_signature.to_bytes(),
-            None,
+            &prepared.signature.to_bytes(),
+            header.as_deref(),
             Some(&nonce),
-            Some(&claims),
+            Some(&prepared.claims),
             Some(&disclosed_indices),
         ) {
             Ok(proof) => { proof }
@@ -116,10 +257,104 @@ impl BBSPlusInstance {
         Self::serialize_and_insert(&mut vp, INDICES.to_string(), &disclosed_indices)?;
         Self::serialize_and_insert(&mut vp, NONCE.to_string(), &nonce)?;
 
-        let jwt = Self::encode_and_sign_jwt(&mut vp, &holder_private_key)?;
+        Self::encode_and_sign_presentation_for_audience(vp, audience, validity, holder_binding)
+    }
 
-        Ok((vp, jwt))
 
+    /// Given a VC, and several sets of disclosures, create one Verifiable Presentation per set decoding the signature
+    /// and encoding the claims only once.
+    ///
+    /// # Arguments
+    /// * `vc` - Verifiable Credential.
+    /// * `disclosure_sets` - Lists of strings containing the names of the claims that are to be disclosed in each VP.
+    /// * `issuer_public_key` - Issuer's public key necessary for computing the derived signatures.
+    /// * `holder_binding` - Holder binding signing the VP as proof of possession.
+    ///
+    /// # Returns
+    /// Returns the VPs both in form of a Map and in form of a signed JWT.
+    pub fn issue_vp_batch(vc: &Map<String, Value>, disclosure_sets: &[Vec<String>], issuer_public_key: &BBSplusPublicKey, holder_binding: &(impl HolderBinding + ?Sized)) -> Result<PresentationBatch, String> {
+
+        let prepared: PreparedPresentation = Self::prepare_presentation(vc)?;
+        disclosure_sets
+            .iter()
+            .map(|disclosures| Self::issue_prepared_vp(&prepared, disclosures, &random_bytes(32), None, None, issuer_public_key, holder_binding))
+            .collect()
+    }
+
+
+    /// Extracts the BBS+ signature, or the proof of knowledge of the signature for VPs, together with the signed messages.
+    ///
+    /// # Arguments
+    /// * `vp` - Verifiable Credential or Verifiable Presentation.
+    ///
+    /// # Returns
+    /// Returns a map containing the artifacts or a string containing an error in case of failure.
+    pub fn proof_artifacts(vp: &Map<String, Value>) -> Result<Map<String, Value>, String> {
+
+        let vp: &Map<String, Value> = Self::credential_or_presentation(vp)?;
+
+        let messages: Vec<Value> = Self::claim_messages(Self::extract_claims(vp)?)?
+            .iter()
+            .map(|message| Value::String(Encoding::Base64UrlNoPad.encode(message)))
+            .collect();
+
+        let mut artifacts: Map<String, Value> = Map::new();
+        artifacts.insert("messages".to_string(), Value::Array(messages));
+        artifacts.insert(SIGNED_FIELDS.to_string(), Value::Object(signed_fields(vp)));
+
+        if vp.contains_key(INDICES) {
+            let proof: PoKSignature<BbsBls12381Sha256> = Self::get_and_decode(vp, SIGNATURE.to_string())?;
+            let disclosed_indices: Vec<usize> = Self::get_and_decode(vp, INDICES.to_string())?;
+            let nonce: Vec<u8> = Self::get_and_decode(vp, NONCE.to_string())?;
+            artifacts.insert("proof".to_string(), Value::String(Encoding::Base64UrlNoPad.encode(proof.to_bytes())));
+            artifacts.insert("disclosed_indices".to_string(), Value::Array(disclosed_indices.into_iter().map(Value::from).collect()));
+            artifacts.insert("nonce".to_string(), Value::String(Encoding::Base64UrlNoPad.encode(nonce)));
+        } else {
+            let signature: Signature<BbsBls12381Sha256> = Self::get_and_decode(vp, SIGNATURE.to_string())?;
+            artifacts.insert("signature".to_string(), Value::String(Encoding::Base64UrlNoPad.encode(signature.to_bytes())));
+        }
+
+        Ok(artifacts)
+    }
+
+
+    /// Extracts the proof of every disclosed claim of a VC or VP: its signed message and the index of the message. The
+    /// signature, or for VPs the proof of knowledge of the signature with its nonce, is shared by all the claims.
+    ///
+    /// # Arguments
+    /// * `vp` - Verifiable Credential or Verifiable Presentation.
+    ///
+    /// # Returns
+    /// Returns the `shared` and `claims` objects of the audit document or a string containing an error in case of failure.
+    pub fn claim_proofs(vp: &Map<String, Value>) -> Result<Map<String, Value>, String> {
+
+        let mut shared: Map<String, Value> = Self::proof_artifacts(vp)?;
+        shared.remove("messages");
+        let vp: &Map<String, Value> = Self::credential_or_presentation(vp)?;
+
+        let claims: &Map<String, Value> = Self::extract_claims(vp)?;
+        let messages: Vec<Vec<u8>> = Self::claim_messages(claims)?;
+        let indices: Vec<usize> = if vp.contains_key(INDICES) {
+            Self::get_and_decode(vp, INDICES.to_string())?
+        } else {
+            (0..messages.len()).collect()
+        };
+        // Messages are computed from the protected claims in canonical order, as are the disclosed indices.
+        let protected_claims: Vec<(&String, &Value)> = Self::canonical_claims(claims).into_iter().filter(|(_, value)| Self::protects(value)).collect();
+        if protected_claims.len() != messages.len() || indices.len() != messages.len() {
+            return Err(format!("{} claims are disclosed with {} messages and {} indices.", protected_claims.len(), messages.len(), indices.len()));
+        }
+
+        let mut claim_proofs: Map<String, Value> = Map::new();
+        for (((name, value), message), index) in protected_claims.into_iter().zip(messages).zip(indices) {
+            let mut proof: Map<String, Value> = Map::new();
+            proof.insert(VALUE.to_string(), value.clone());
+            proof.insert("index".to_string(), Value::from(index));
+            proof.insert("message".to_string(), Value::String(Encoding::Base64UrlNoPad.encode(message)));
+            claim_proofs.insert(name.clone(), Value::Object(proof));
+        }
+
+        Ok(claim_proofs_document(shared, claim_proofs))
     }
 
 
@@ -128,25 +363,49 @@ impl BBSPlusInstance {
     /// # Arguments
     /// * `jwt` - Verifiable Presentation encoded as a jwt.
     /// * `issuer_public_key` - Issuer's public key to verify the BBS+ signature.
-    /// * `holder_public_key` - Holder's public key to verify the proof of possession.
+    /// * `holder_binding` - Holder binding verifying the proof of possession.
     ///
     /// # Returns
     /// Returns a string containing an error in case of failure.
-    pub fn verify_vp(signed_jwt: &String, issuer_public_key: &BBSplusPublicKey, holder_public_key: &impl AsRef<[u8]>) -> Result<(), String> {
+    pub fn verify_vp(signed_jwt: &str, issuer_public_key: &BBSplusPublicKey, holder_binding: &(impl HolderBinding + ?Sized)) -> Result<(), String> {
+        Self::verify_vp_with_config(signed_jwt, issuer_public_key, holder_binding, &Self::VERIFIER_CONFIG)
+    }
+
+
+    /// Same as `verify_vp`, but enforcing the limits of the verifier on the size, depth and amount of claims of the VP.
+    ///
+    /// # Arguments
+    /// * `jwt` - Verifiable Presentation encoded as a jwt.
+    /// * `issuer_public_key` - Issuer's public key to verify the BBS+ signature.
+    /// * `holder_binding` - Holder binding verifying the proof of possession.
+    /// * `config` - Limits enforced on the VP.
+    ///
+    /// # Returns
+    /// Returns a string containing an error in case of failure.
+    pub fn verify_vp_with_config(signed_jwt: &str, issuer_public_key: &BBSplusPublicKey, holder_binding: &(impl HolderBinding + ?Sized), config: &VerifierConfig) -> Result<(), String> {
 
-        let vp: Map<String, Value> = Self::decode_and_verify_jwt(signed_jwt, &holder_public_key)?;
-        let bbs_signature: PoKSignature<BbsBls12381Sha256> = Self::get_and_decode(&vp, SIGNATURE.to_string())?;
-        let disclosed_indices: Vec<usize> = Self::get_and_decode(&vp, INDICES.to_string())?;
-        let nonce: Vec<u8> = Self::get_and_decode(&vp, NONCE.to_string())?;
+        let vp: Map<String, Value> = Self::decode_and_verify_presentation(signed_jwt, holder_binding, config)?;
+        let bbs_signature: PoKSignature<BbsBls12381Sha256> = Self::get_and_decode_with_config(&vp, SIGNATURE.to_string(), config)?;
+        let disclosed_indices: Vec<usize> = Self::get_and_decode_with_config(&vp, INDICES.to_string(), config)?;
+        let nonce: Vec<u8> = Self::get_and_decode_with_config(&vp, NONCE.to_string(), config)?;
 
         let disclosed_claims: &Map<String, Value> = Self::extract_claims(&vp)?;
-        let disclosed_claims: Vec<Vec<u8>> = Self::convert_claims_to_bytes(disclosed_claims)?;
+        config.check_claims_len(disclosed_claims.len())?;
+        if disclosed_indices.len() != disclosed_claims.len() {
+            return Err(format!("VP discloses {} claims but {} indices.", disclosed_claims.len(), disclosed_indices.len()));
+        }
+        if disclosed_indices.windows(2).any(|pair| pair[0] >= pair[1]) {
+            return Err("Disclosed indices are not strictly increasing.".to_string());
+        }
+        let disclosed_claims: Vec<Vec<u8>> = Self::claim_messages(disclosed_claims)?;
+        let header: Option<Vec<u8>> = Self::signature_header(&vp);
 
+        record(Operation::Pairing, 2);
         let result = bbs_signature.proof_verify(
             &issuer_public_key,
             Some(&disclosed_claims),
             Some(disclosed_indices.as_slice()),
-            None,
+            header.as_deref(),
             Some(nonce.as_slice()),
         );
 
@@ -161,13 +420,15 @@ impl BBSPlusInstance {
 
 #[cfg(test)]
 mod tests {
-    use rand::Rng;
+    use log::debug;
     use serde_json::{Map, Value};
     use zkryptium::bbsplus::ciphersuites::{BbsCiphersuite, Bls12381Sha256};
     use zkryptium::keys::pair::KeyPair;
     use zkryptium::schemes::algorithms::BBSplus;
 
     use crate::common_data::{CommonData, VC};
+    use crate::deterministic::random_bytes;
+    use crate::sd_algorithms::sd_algorithm::SdAlgorithm;
     use crate::sd_algorithms::signatures::bbs_plus::BBSPlusInstance;
 
     #[test]
@@ -184,8 +445,7 @@ mod tests {
         };
 
         let raw_vc = &mut raw_vc;
-        let mut rng = rand::rng();
-        let key_material: Vec<u8> = (0..Bls12381Sha256::IKM_LEN).map(|_| rng.random()).collect();
+        let key_material: Vec<u8> = random_bytes(Bls12381Sha256::IKM_LEN);
 
         let issuer_keypair = match KeyPair::<BBSplus<Bls12381Sha256>>::generate(&key_material, None, None) {
             Ok(keypair) => { keypair }
@@ -202,11 +462,11 @@ mod tests {
         };
 
         match BBSPlusInstance::verify_vc(&vc, &issuer_pk) {
-            Ok(_) => { println!("[BBS+] Successfully verified vc.")}
+            Ok(_) => { debug!("[BBS+] Successfully verified vc.")}
             Err(err) => { return Err(format!("[BBS+] Failed to verify vc [{err}]."))}
         };
 
-        let disclosures = vec!["name", "birthdate"].iter().map(|x| x.to_string()).collect();
+        let disclosures: Vec<String> = vec!["name", "birthdate"].iter().map(|x| x.to_string()).collect();
 
         let (_vp, vp_jwt) = match BBSPlusInstance::issue_vp(&vc, &disclosures, &issuer_pk, &holder_private_key) {
             Ok(vp) => { vp }
@@ -214,10 +474,75 @@ mod tests {
         };
 
         match BBSPlusInstance::verify_vp(&vp_jwt, &issuer_pk, &holder_public_key) {
-            Ok(_) => { println!("[BBS+] Successfully verified vp.")}
+            Ok(_) => { debug!("[BBS+] Successfully verified vp.")}
             Err(err) => { return Err(format!("[BBS+] Failed to verify vp [{err}].")) }
         };
 
         Ok(())
     }
+
+    #[test]
+    fn disclosure_extremes() -> Result<(), String> {
+
+        let raw_vc: Map<String, Value> = match serde_json::from_str::<Map<String, Value>>(VC) {
+            Ok(vc) => { vc }
+            Err(err) => { return Err(format!("[BBS+] Failed to parse Raw Verifiable Credential from string. [{err}]")); }
+        };
+
+        let key_material: Vec<u8> = random_bytes(Bls12381Sha256::IKM_LEN);
+        let issuer_keypair = match KeyPair::<BBSplus<Bls12381Sha256>>::generate(&key_material, None, None) {
+            Ok(keypair) => { keypair }
+            Err(err) => { return Err(format!("[BBS+] Error in issuing keypair [{err}]")) }
+        };
+        let issuer_sk = issuer_keypair.private_key();
+        let issuer_pk = issuer_keypair.public_key();
+        let (holder_public_key, holder_private_key) = CommonData::holder_keys()?;
+
+        let (vc, _vc_jwt) = BBSPlusInstance::issue_vc(&raw_vc, &issuer_pk, &issuer_sk)?;
+
+        // Possession-only presentation.
+        let (_vp, vp_jwt) = BBSPlusInstance::issue_vp(&vc, &vec![], &issuer_pk, &holder_private_key)?;
+        BBSPlusInstance::verify_vp(&vp_jwt, &issuer_pk, &holder_public_key)?;
+
+        let all_claims: Vec<String> = BBSPlusInstance::extract_claims(&raw_vc)?.keys().cloned().collect();
+        let (_vp, vp_jwt) = BBSPlusInstance::issue_vp(&vc, &all_claims, &issuer_pk, &holder_private_key)?;
+        BBSPlusInstance::verify_vp(&vp_jwt, &issuer_pk, &holder_public_key)?;
+
+        let unknown_claim: Vec<String> = vec!["not a claim".to_string()];
+        assert!(BBSPlusInstance::issue_vp(&vc, &unknown_claim, &issuer_pk, &holder_private_key).is_err(), "[BBS+] Unknown claim was disclosed.");
+
+        Ok(())
+    }
+
+    #[test]
+    fn prepared_presentation() -> Result<(), String> {
+
+        let raw_vc: Map<String, Value> = match serde_json::from_str::<Map<String, Value>>(VC) {
+            Ok(vc) => { vc }
+            Err(err) => { return Err(format!("[BBS+] Failed to parse Raw Verifiable Credential from string. [{err}]")); }
+        };
+
+        let key_material: Vec<u8> = random_bytes(Bls12381Sha256::IKM_LEN);
+        let issuer_keypair = match KeyPair::<BBSplus<Bls12381Sha256>>::generate(&key_material, None, None) {
+            Ok(keypair) => { keypair }
+            Err(err) => { return Err(format!("[BBS+] Error in issuing keypair [{err}]")) }
+        };
+        let issuer_sk = issuer_keypair.private_key();
+        let issuer_pk = issuer_keypair.public_key();
+        let (holder_public_key, holder_private_key) = CommonData::holder_keys()?;
+
+        let (vc, _vc_jwt) = BBSPlusInstance::issue_vc(&raw_vc, &issuer_pk, &issuer_sk)?;
+        let prepared = BBSPlusInstance::prepare_presentation(&vc)?;
+
+        // The same preparation serves presentations with different disclosures and nonces.
+        for (disclosures, nonce) in [(vec!["name".to_string()], b"first nonce"), (vec!["birthdate".to_string(), "field".to_string()], b"other nonce")] {
+            let (vp, vp_jwt) = BBSPlusInstance::issue_prepared_vp(&prepared, &disclosures, nonce, None, None, &issuer_pk, &holder_private_key)?;
+            BBSPlusInstance::verify_vp(&vp_jwt, &issuer_pk, &holder_public_key)?;
+
+            let presented_nonce: Vec<u8> = BBSPlusInstance::get_and_decode(BBSPlusInstance::unwrap_presentation(&vp)?, super::NONCE.to_string())?;
+            assert_eq!(presented_nonce, nonce.to_vec(), "[BBS+] Proof is not bound to the verifier nonce.");
+        }
+
+        Ok(())
+    }
 }
\ No newline at end of file
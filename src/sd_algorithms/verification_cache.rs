@@ -0,0 +1,176 @@
+use std::collections::{HashSet, VecDeque};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+/// Verifier-side cache of claim-level verification results.
+///
+/// Entries are keyed by a scope (e.g. the accumulator value or the Merkle root) and a digest of the claim
+/// (e.g. the claim scalar or the leaf hash), so that repeated presentations of the same credential can skip the
+/// pairing or hashing work that was already carried out for a previous presentation. The cache is bounded: once it
+/// holds `max_entries` pairs, the least recently inserted pair is evicted, so that a verifier presented with many
+/// distinct credentials does not grow it without limit.
+///
+/// # Examples
+/// ```
+/// use csd_jwt::sd_algorithms::verification_cache::VerificationCache;
+///
+/// let cache = VerificationCache::new();
+/// assert!(!cache.contains("root", "leaf"));
+/// cache.insert("root", "leaf");
+/// assert!(cache.contains("root", "leaf"));
+/// assert_eq!(cache.hit_rate(), 0.5);
+///
+/// let bounded = VerificationCache::with_max_entries(1);
+/// bounded.insert("root", "first leaf");
+/// bounded.insert("root", "second leaf");
+/// assert!(!bounded.contains("root", "first leaf"));
+/// assert_eq!(bounded.len(), 1);
+/// ```
+pub struct VerificationCache {
+    /// Maximum amount of (scope, claim digest) pairs kept.
+    max_entries: usize,
+    /// (scope, claim digest) pairs that were already successfully verified.
+    entries: Mutex<CacheEntries>,
+    /// Amount of lookups that found the entry in the cache.
+    hits: AtomicUsize,
+    /// Amount of lookups that did not find the entry in the cache.
+    misses: AtomicUsize,
+}
+
+/// Verified (scope, claim digest) pairs, along with their order of insertion for the eviction.
+#[derive(Default)]
+struct CacheEntries {
+    /// Verified pairs.
+    pairs: HashSet<(String, String)>,
+    /// Verified pairs, from the least to the most recently inserted.
+    order: VecDeque<(String, String)>,
+}
+
+/// Default maximum amount of (scope, claim digest) pairs kept by a VerificationCache.
+pub const DEFAULT_MAX_ENTRIES: usize = 65_536;
+
+impl VerificationCache {
+
+    /// Constructor for an empty VerificationCache keeping at most `DEFAULT_MAX_ENTRIES` pairs.
+    ///
+    /// # Returns
+    /// An empty cache with zeroed statistics.
+    pub fn new() -> Self {
+        Self::with_max_entries(DEFAULT_MAX_ENTRIES)
+    }
+
+    /// Constructor for an empty VerificationCache keeping at most `max_entries` pairs.
+    ///
+    /// # Arguments
+    /// * `max_entries` - Maximum amount of (scope, claim digest) pairs kept.
+    ///
+    /// # Returns
+    /// An empty cache with zeroed statistics.
+    pub fn with_max_entries(max_entries: usize) -> Self {
+        VerificationCache {
+            max_entries,
+            entries: Mutex::new(CacheEntries::default()),
+            hits: AtomicUsize::new(0),
+            misses: AtomicUsize::new(0),
+        }
+    }
+
+    /// Checks whether the pair (scope, digest) was already verified, updating the hit and miss counters.
+    ///
+    /// # Arguments
+    /// * `scope` - Value the claim was verified against (accumulator value, Merkle root, ...).
+    /// * `digest` - Digest identifying the claim inside the scope.
+    ///
+    /// # Returns
+    /// True if the pair is present in the cache, false otherwise.
+    pub fn contains(&self, scope: &str, digest: &str) -> bool {
+        let found = match self.entries.lock() {
+            Ok(entries) => { entries.pairs.contains(&(scope.to_string(), digest.to_string())) }
+            Err(_) => { false }
+        };
+
+        if found {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+        } else {
+            self.misses.fetch_add(1, Ordering::Relaxed);
+        }
+
+        found
+    }
+
+    /// Records the pair (scope, digest) as successfully verified, evicting the least recently inserted pair if the
+    /// cache is full.
+    ///
+    /// # Arguments
+    /// * `scope` - Value the claim was verified against (accumulator value, Merkle root, ...).
+    /// * `digest` - Digest identifying the claim inside the scope.
+    pub fn insert(&self, scope: &str, digest: &str) {
+        if self.max_entries == 0 {
+            return;
+        }
+
+        if let Ok(mut entries) = self.entries.lock() {
+            let pair: (String, String) = (scope.to_string(), digest.to_string());
+            if entries.pairs.contains(&pair) {
+                return;
+            }
+            if entries.pairs.len() >= self.max_entries {
+                if let Some(oldest) = entries.order.pop_front() {
+                    entries.pairs.remove(&oldest);
+                }
+            }
+            entries.pairs.insert(pair.clone());
+            entries.order.push_back(pair);
+        }
+    }
+
+    /// Amount of (scope, claim digest) pairs currently kept.
+    pub fn len(&self) -> usize {
+        match self.entries.lock() {
+            Ok(entries) => { entries.pairs.len() }
+            Err(_) => { 0 }
+        }
+    }
+
+    /// Whether the cache keeps no pair.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Removes every entry and resets the statistics.
+    pub fn clear(&self) {
+        if let Ok(mut entries) = self.entries.lock() {
+            entries.pairs.clear();
+            entries.order.clear();
+        }
+        self.hits.store(0, Ordering::Relaxed);
+        self.misses.store(0, Ordering::Relaxed);
+    }
+
+    /// Amount of lookups that found the entry in the cache.
+    pub fn hits(&self) -> usize {
+        self.hits.load(Ordering::Relaxed)
+    }
+
+    /// Amount of lookups that did not find the entry in the cache.
+    pub fn misses(&self) -> usize {
+        self.misses.load(Ordering::Relaxed)
+    }
+
+    /// Ratio between hits and total lookups.
+    ///
+    /// # Returns
+    /// The hit rate in the range [0, 1], or 0 if no lookup was performed yet.
+    pub fn hit_rate(&self) -> f64 {
+        let hits = self.hits() as f64;
+        let total = hits + self.misses() as f64;
+
+        if total == 0f64 { 0f64 } else { hits / total }
+    }
+}
+
+impl Default for VerificationCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,247 @@
+use std::sync::Arc;
+use axum::body::Bytes;
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use log::{info, warn};
+use serde_json::{json, Map, Value};
+use crate::adapters::adapter::Adapter;
+use crate::adapters::registry::AdapterRegistry;
+use crate::json;
+use crate::keys::jwks::{Jwks, JWKS_PATH};
+use crate::verifier_config::uniform_error;
+
+/// Field of every request selecting the algorithm.
+pub const ALGORITHM_FIELD: &str = "algorithm";
+/// Field of the issuance request holding the raw VC.
+pub const CREDENTIAL_FIELD: &str = "credential";
+/// Field of the presentation request holding the VC the VP is derived from.
+pub const VC_FIELD: &str = "vc";
+/// Field of the presentation request listing the claims to disclose.
+pub const DISCLOSURES_FIELD: &str = "disclosures";
+/// Optional field of the presentation request binding the VP to an audience.
+pub const AUDIENCE_FIELD: &str = "audience";
+/// Field of the verification request holding the VP JWT.
+pub const VP_JWT_FIELD: &str = "vp_jwt";
+
+/// Error returned by the service as a JSON body of the form `{"error": {"code": ..., "message": ...}}`.
+#[derive(Debug)]
+pub struct ApiError {
+    /// HTTP status of the response.
+    status: StatusCode,
+    /// Machine-readable code of the error.
+    code: &'static str,
+    /// Human-readable description of the error.
+    message: String,
+}
+
+impl ApiError {
+
+    /// Error due to a malformed request.
+    pub(crate) fn invalid_request(message: String) -> Self {
+        ApiError { status: StatusCode::BAD_REQUEST, code: "invalid_request", message }
+    }
+
+    /// Error due to an algorithm missing from the registry.
+    pub(crate) fn unknown_algorithm(algorithm: &str) -> Self {
+        ApiError { status: StatusCode::NOT_FOUND, code: "unknown_algorithm", message: format!("No adapter is registered for {algorithm}") }
+    }
+
+    /// Error due to a resource missing from the service.
+    pub(crate) fn not_found(message: String) -> Self {
+        ApiError { status: StatusCode::NOT_FOUND, code: "not_found", message }
+    }
+
+    /// Error due to a failure of the service itself.
+    pub(crate) fn internal_error(message: String) -> Self {
+        ApiError { status: StatusCode::INTERNAL_SERVER_ERROR, code: "internal_error", message }
+    }
+
+    /// Error returned by an adapter operation on a well-formed request.
+    fn operation_failed(code: &'static str, message: String) -> Self {
+        ApiError { status: StatusCode::UNPROCESSABLE_ENTITY, code, message }
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        warn!("Request failed with {}: {}", self.code, self.message);
+        (self.status, Json(json!({ "error": { "code": self.code, "message": self.message } }))).into_response()
+    }
+}
+
+
+/// Parses the body of a request as a JSON object.
+pub(crate) fn parse_request(body: &Bytes) -> Result<Map<String, Value>, ApiError> {
+    match json::from_slice::<Map<String, Value>>(body) {
+        Ok(request) => { Ok(request) }
+        Err(err) => { Err(ApiError::invalid_request(format!("Request body is not a JSON object: [{err}]"))) }
+    }
+}
+
+/// Retrieves a mandatory string field of a request.
+pub(crate) fn required_string(request: &Map<String, Value>, field: &str) -> Result<String, ApiError> {
+    match request.get(field) {
+        Some(Value::String(value)) if !value.is_empty() => { Ok(value.clone()) }
+        Some(_) => { Err(ApiError::invalid_request(format!("Field {field} must be a non-empty string"))) }
+        None => { Err(ApiError::invalid_request(format!("Missing field {field}"))) }
+    }
+}
+
+/// Retrieves a mandatory object field of a request.
+fn required_object(request: &Map<String, Value>, field: &str) -> Result<Map<String, Value>, ApiError> {
+    match request.get(field) {
+        Some(Value::Object(value)) => { Ok(value.clone()) }
+        Some(_) => { Err(ApiError::invalid_request(format!("Field {field} must be an object"))) }
+        None => { Err(ApiError::invalid_request(format!("Missing field {field}"))) }
+    }
+}
+
+/// Retrieves a mandatory field of a request holding an array of strings.
+pub(crate) fn required_strings(request: &Map<String, Value>, field: &str) -> Result<Vec<String>, ApiError> {
+    let values = match request.get(field) {
+        Some(Value::Array(values)) => { values }
+        Some(_) => { return Err(ApiError::invalid_request(format!("Field {field} must be an array of strings"))) }
+        None => { return Err(ApiError::invalid_request(format!("Missing field {field}"))) }
+    };
+
+    values.iter().map(|value| match value {
+        Value::String(value) => { Ok(value.clone()) }
+        _ => { Err(ApiError::invalid_request(format!("Field {field} must be an array of strings"))) }
+    }).collect()
+}
+
+/// Runs an adapter operation on the blocking thread pool, as the cryptographic operations would otherwise stall the
+/// asynchronous runtime.
+async fn run_operation<T, F>(registry: Arc<AdapterRegistry>, algorithm: String, operation: F) -> Result<Result<T, String>, ApiError>
+where
+    F: FnOnce(&dyn Adapter) -> Result<T, String> + Send + 'static,
+    T: Send + 'static,
+{
+    if registry.get(&algorithm).is_none() {
+        return Err(ApiError::unknown_algorithm(&algorithm));
+    }
+
+    let task = tokio::task::spawn_blocking(move || match registry.get(&algorithm) {
+        Some(adapter) => { operation(adapter) }
+        None => { Err(format!("No adapter is registered for {algorithm}")) }
+    });
+
+    match task.await {
+        Ok(result) => { Ok(result) }
+        Err(err) => { Err(ApiError::internal_error(format!("Operation aborted: [{err}]"))) }
+    }
+}
+
+
+/// Lists the algorithms of the registry.
+async fn algorithms(State(registry): State<Arc<AdapterRegistry>>) -> Json<Value> {
+    Json(json!({ "algorithms": registry.algorithms() }))
+}
+
+/// Publishes the issuer keys of every algorithm of the registry as a JWKS.
+async fn jwks(State(registry): State<Arc<AdapterRegistry>>) -> Result<Json<Value>, ApiError> {
+    let algorithms: Vec<String> = registry.algorithms();
+    match Jwks::from_adapters(algorithms.iter().filter_map(|algorithm| registry.get(algorithm))) {
+        Ok(jwks) => { Ok(Json(jwks.to_value())) }
+        Err(err) => { Err(ApiError::internal_error(err)) }
+    }
+}
+
+/// Issues a VC: `{"algorithm": ..., "credential": {...}}` returns `{"vc": {...}, "jwt": ...}`.
+async fn issue(State(registry): State<Arc<AdapterRegistry>>, body: Bytes) -> Result<Json<Value>, ApiError> {
+    let request = parse_request(&body)?;
+    let algorithm = required_string(&request, ALGORITHM_FIELD)?;
+    let credential = required_object(&request, CREDENTIAL_FIELD)?;
+
+    match run_operation(registry, algorithm, move |adapter| adapter.issue_vc(&credential)).await? {
+        Ok((vc, jwt)) => { Ok(Json(json!({ "vc": vc, "jwt": jwt }))) }
+        Err(err) => { Err(ApiError::operation_failed("issuance_failed", err)) }
+    }
+}
+
+/// Derives a VP: `{"algorithm": ..., "vc": {...}, "disclosures": [...], "audience": ...}` returns `{"vp": {...}, "jwt": ...}`.
+async fn present(State(registry): State<Arc<AdapterRegistry>>, body: Bytes) -> Result<Json<Value>, ApiError> {
+    let request = parse_request(&body)?;
+    let algorithm = required_string(&request, ALGORITHM_FIELD)?;
+    let vc = required_object(&request, VC_FIELD)?;
+    let disclosures = required_strings(&request, DISCLOSURES_FIELD)?;
+    let audience: Option<String> = match request.get(AUDIENCE_FIELD) {
+        None | Some(Value::Null) => { None }
+        Some(_) => { Some(required_string(&request, AUDIENCE_FIELD)?) }
+    };
+
+    match run_operation(registry, algorithm, move |adapter| adapter.issue_vp_for_audience(&vc, &disclosures, audience, None)).await? {
+        Ok((vp, jwt)) => { Ok(Json(json!({ "vp": vp, "jwt": jwt }))) }
+        Err(err) => { Err(ApiError::operation_failed("presentation_failed", err)) }
+    }
+}
+
+/// Verifies a VP: `{"algorithm": ..., "vp_jwt": ...}` returns `{"valid": true}`, or `{"valid": false, "reason": ...}`
+/// if the VP is rejected, the reason being uniform if the registry hides the reasons of failed verifications.
+async fn verify(State(registry): State<Arc<AdapterRegistry>>, body: Bytes) -> Result<Json<Value>, ApiError> {
+    let request = parse_request(&body)?;
+    let algorithm = required_string(&request, ALGORITHM_FIELD)?;
+    let vp_jwt = required_string(&request, VP_JWT_FIELD)?;
+    let uniform_errors: bool = registry.uniform_errors();
+
+    match run_operation(registry, algorithm, move |adapter| {
+        let result = adapter.verify_vp(&vp_jwt);
+        if uniform_errors { uniform_error(result) } else { result }
+    }).await? {
+        Ok(_) => { Ok(Json(json!({ "valid": true }))) }
+        Err(err) => { Ok(Json(json!({ "valid": false, "reason": err }))) }
+    }
+}
+
+
+/// Builds the router of the issuer/verifier service over the adapters of a registry, exposing `GET /algorithms`,
+/// `GET /.well-known/jwks.json`, `POST /issue`, `POST /present` and `POST /verify`.
+///
+/// # Arguments
+/// * `registry` - Adapters the service dispatches the requests to.
+///
+/// # Returns
+/// The router of the service.
+pub fn router(registry: AdapterRegistry) -> Router {
+    Router::new()
+        .route("/algorithms", get(algorithms))
+        .route(JWKS_PATH, get(jwks))
+        .route("/issue", post(issue))
+        .route("/present", post(present))
+        .route("/verify", post(verify))
+        .with_state(Arc::new(registry))
+}
+
+/// Serves the issuer/verifier service until the process is terminated.
+///
+/// # Arguments
+/// * `registry` - Adapters the service dispatches the requests to.
+/// * `address` - Address to listen on, e.g. `127.0.0.1:8080`.
+///
+/// # Returns
+/// A result containing a string in case the service cannot be started.
+///
+/// # Examples
+/// ```no_run
+/// use csd_jwt::adapters::registry::AdapterRegistry;
+/// use csd_jwt::server::serve;
+///
+/// # async fn run() -> Result<(), String> {
+/// serve(AdapterRegistry::with_all_adapters(32)?, "127.0.0.1:8080").await
+/// # }
+/// ```
+pub async fn serve(registry: AdapterRegistry, address: &str) -> Result<(), String> {
+    let listener = match tokio::net::TcpListener::bind(address).await {
+        Ok(listener) => { listener }
+        Err(err) => { return Err(format!("Failed to bind {address}: [{err}]")) }
+    };
+
+    info!("Serving {:?} on {}", registry.algorithms(), address);
+    match axum::serve(listener, router(registry)).await {
+        Ok(_) => { Ok(()) }
+        Err(err) => { Err(format!("Service terminated: [{err}]")) }
+    }
+}
@@ -0,0 +1,370 @@
+use serde_json::{Map, Value};
+use crate::claim_groups::ClaimGroups;
+use crate::common_data::{CLAIMS, ISSUED_AT, PRESENTATION_NONCE, VERIFIABLE_CREDENTIAL, VP, VP_CONTEXT, VP_TYPE};
+use crate::credential_ir::CredentialIr;
+use crate::encoding::Encoding;
+use crate::typed_claims::canonicalize_typed_claims;
+
+/// Length in bytes of the salts of SD-JWT and Merkle Trees and of the nonce of a presentation.
+const SALT_LEN: usize = 16;
+/// Length in bytes of a SHA-256 digest.
+const DIGEST_LEN: usize = 32;
+/// Length in bytes of a raw ES256 signature.
+const ES256_SIGNATURE_LEN: usize = 64;
+/// Length in characters of the ES256 or EdDSA signature of a VP JWT, once base64url encoded.
+const JWT_SIGNATURE_LEN: usize = 86;
+/// Length in bytes of a compressed BLS12-381 G1 point.
+const BLS12_381_G1_LEN: usize = 48;
+/// Length in bytes of a BLS12-381 or BN254 scalar.
+const SCALAR_LEN: usize = 32;
+/// Length in bytes of the nonce a BBS+ proof is bound to.
+const BBS_NONCE_LEN: usize = 32;
+/// Length in characters of the field names and punctuation of the serde encoding of a BBS+ signature.
+const BBS_SIGNATURE_OVERHEAD: usize = 12;
+/// Length in characters of the field names and punctuation of the serde encoding of a BBS+ proof of knowledge.
+const BBS_PROOF_OVERHEAD: usize = 72;
+/// Length in bytes of a compressed BN254 G1 point, i.e. of an accumulator, a witness or an aggregated witness.
+const BN254_G1_LEN: usize = 32;
+/// Approximate length in bytes of a compressed membership proof: seven G1 points, a target group element and five
+/// scalars.
+const MEMBERSHIP_PROOF_LEN: usize = 768;
+/// Length in digits of the issuance time of a presentation.
+const TIMESTAMP_DIGITS: usize = 10;
+
+
+/// Size model of an algorithm: the components its VCs and VPs carry besides the envelope, with the length of each of
+/// them derived from the sizes of the underlying primitives (digests, salts, group elements and signatures) and from
+/// how they are serialized, rather than from issuing a credential.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SizeModel {
+    /// Signed list of salted digests, with a salt-value container opening them.
+    SdJwt,
+    /// Signed root of a Merkle tree of salted claims, with multiproofs of the disclosed leaves.
+    MerkleTree,
+    /// BBS+ signature over the claims, with proofs of knowledge of the signature.
+    BbsPlus,
+    /// Accumulator of the claims with a membership witness per claim, presented as such.
+    CsdJwt {
+        /// Whether the claims are blinded by a random factor kept in the credential.
+        blinded: bool,
+    },
+    /// Accumulator of the claims, presented with zero-knowledge membership proofs.
+    CsdJwtMultiShow {
+        /// Whether the claims are blinded by a random factor kept in the credential.
+        blinded: bool,
+    },
+    /// Accumulator of the claims, presented with a single aggregated witness.
+    CsdJwtAggregated {
+        /// Whether the claims are blinded by a random factor kept in the credential.
+        blinded: bool,
+    },
+    /// Accumulator of the claims over BLS12-381 with a membership witness per claim, presented as such.
+    CsdJwtBls12381,
+}
+
+
+/// Amount and serialized length of a set of claims.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ClaimStatistics {
+    /// Amount of claims.
+    pub count: usize,
+    /// Total length of the names of the claims as JSON strings, quotes included.
+    pub name_bytes: usize,
+    /// Total length of the values of the claims as compact JSON.
+    pub value_bytes: usize,
+}
+
+impl ClaimStatistics {
+
+    /// Adds a claim to the statistics.
+    fn add(&mut self, name: &str, value: &Value) {
+        self.count += 1;
+        self.name_bytes += Value::String(name.to_string()).to_string().len();
+        self.value_bytes += value.to_string().len();
+    }
+
+    /// Length of a JSON object holding an entry per claim, given the length of every entry besides its name and value.
+    fn object(&self, entry_overhead: usize) -> usize {
+        collection(self.name_bytes + self.value_bytes + self.count * entry_overhead, self.count)
+    }
+
+    /// Length of a JSON object holding an entry per claim, keyed by its name and with a value of the same length for
+    /// every claim.
+    fn keyed_object(&self, value_len: usize) -> usize {
+        collection(self.name_bytes + self.count * (1 + value_len), self.count)
+    }
+}
+
+
+/// Estimated lengths of the VC and VP of a credential, and of the components making them up.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SizeEstimate {
+    /// Length of the encoded VC.
+    pub vc_jwt_length: usize,
+    /// Length of the encoded VP.
+    pub vp_jwt_length: usize,
+    /// Length in the payload of the VC of every component added by the algorithm to the envelope.
+    pub vc_components: Vec<(String, usize)>,
+    /// Length in the payload of the credential of the VP of every component added by the algorithm to the envelope.
+    pub vp_components: Vec<(String, usize)>,
+}
+
+impl SizeEstimate {
+
+    /// Relative error of an estimated length with respect to the measured one.
+    ///
+    /// # Arguments
+    /// * `estimated` - Estimated length.
+    /// * `measured` - Measured length.
+    ///
+    /// # Returns
+    /// The error as a signed fraction of the measured length, positive if the length is overestimated.
+    pub fn relative_error(estimated: usize, measured: usize) -> f64 {
+        if measured == 0 {
+            return 0f64;
+        }
+        (estimated as f64 - measured as f64) / measured as f64
+    }
+}
+
+
+impl SizeModel {
+
+    /// Estimates the lengths of the VC issued from a raw VC and of a VP disclosing some of its claims, without any
+    /// cryptographic operation. The envelope and the claims are measured exactly, after the same lowering issuance
+    /// performs, while the proof material is estimated from the component model of the algorithm, so that the estimate
+    /// is typically within a few percent of the issued credential.
+    ///
+    /// # Arguments
+    /// * `raw_vc` - Template VC, already bound to the holder.
+    /// * `disclosures` - Names of the claims disclosed by the VP.
+    ///
+    /// # Returns
+    /// A result containing the estimate or a string containing an error if the raw VC cannot be lowered.
+    ///
+    /// # Examples
+    /// ```
+    /// use csd_jwt::common_data::VC;
+    /// use csd_jwt::size_estimate::SizeModel;
+    ///
+    /// let raw_vc = serde_json::from_str(VC).unwrap();
+    /// let disclosures = vec!["name".to_string()];
+    /// let sd_jwt = SizeModel::SdJwt.estimate(&raw_vc, &disclosures).unwrap();
+    /// let merkle = SizeModel::MerkleTree.estimate(&raw_vc, &disclosures).unwrap();
+    /// assert!(sd_jwt.vp_jwt_length < sd_jwt.vc_jwt_length);
+    /// assert!(merkle.vp_components.iter().any(|(component, _)| component == "merkle_proof"));
+    /// ```
+    pub fn estimate(&self, raw_vc: &Map<String, Value>, disclosures: &[String]) -> Result<SizeEstimate, String> {
+        let credential: CredentialIr = CredentialIr::from_raw_vc(&canonicalize_typed_claims(&ClaimGroups::group_claims(raw_vc)?)?)?;
+
+        let mut claims: Vec<(&String, &Value)> = credential.claims().iter().collect();
+        claims.sort_by_key(|(name, _)| *name);
+        let mut all: ClaimStatistics = ClaimStatistics::default();
+        let mut disclosed: ClaimStatistics = ClaimStatistics::default();
+        let mut disclosed_indices: Vec<usize> = vec![];
+        for (index, (name, value)) in claims.into_iter().enumerate() {
+            all.add(name, value);
+            if disclosures.contains(name) {
+                disclosed.add(name, value);
+                disclosed_indices.push(index);
+            }
+        }
+
+        let vc_components: Vec<(String, usize)> = self.vc_components(&all);
+        let vp_components: Vec<(String, usize)> = self.vp_components(&all, &disclosed, &disclosed_indices);
+
+        let envelope: Vec<usize> = credential.envelope().iter().map(|(field, value)| {
+            Value::String(field.clone()).to_string().len() + 1 + value.to_string().len()
+        }).collect();
+        let vc_payload: usize = credential_length(&envelope, &vc_components);
+        let vp_payload: usize = presentation_wrapper_length() + credential_length(&envelope, &vp_components);
+
+        Ok(SizeEstimate {
+            vc_jwt_length: encoded(header_length("none")) + 1 + encoded(vc_payload) + 1,
+            vp_jwt_length: encoded(header_length("ES256")) + 1 + encoded(vp_payload) + 1 + JWT_SIGNATURE_LEN,
+            vc_components,
+            vp_components,
+        })
+    }
+
+    /// Components of the VC, with their length as members of its payload.
+    fn vc_components(&self, all: &ClaimStatistics) -> Vec<(String, usize)> {
+        match self {
+            SizeModel::SdJwt => { vec![
+                field("signature", byte_array(ES256_SIGNATURE_LEN)),
+                field("hashes", collection(all.count * string(DIGEST_LEN), all.count)),
+                field("svc", all.object(4 + string(SALT_LEN))),
+            ] }
+            SizeModel::MerkleTree => { vec![
+                clear_claims(all),
+                field("root", byte_array(DIGEST_LEN)),
+                field("leaves_len", all.count.to_string().len()),
+                field("salts", all.keyed_object(string(SALT_LEN))),
+                field("root_sig", byte_array(ES256_SIGNATURE_LEN)),
+            ] }
+            SizeModel::BbsPlus => { vec![
+                clear_claims(all),
+                field("signature", BBS_SIGNATURE_OVERHEAD + byte_array(BLS12_381_G1_LEN) + byte_array(SCALAR_LEN)),
+            ] }
+            SizeModel::CsdJwt { blinded } | SizeModel::CsdJwtMultiShow { blinded } | SizeModel::CsdJwtAggregated { blinded } => {
+                let mut components: Vec<(String, usize)> = vec![
+                    field("accumulator", string(BN254_G1_LEN)),
+                    field("wvc", all.object(4 + string(BN254_G1_LEN))),
+                ];
+                if *blinded {
+                    components.push(field("blinding", string(SALT_LEN)));
+                }
+                components
+            }
+            SizeModel::CsdJwtBls12381 => { vec![
+                field("accumulator", string(BLS12_381_G1_LEN)),
+                field("wvc", all.object(4 + string(BLS12_381_G1_LEN))),
+            ] }
+        }
+    }
+
+    /// Components of the credential of the VP, with their length as members of its payload.
+    fn vp_components(&self, all: &ClaimStatistics, disclosed: &ClaimStatistics, disclosed_indices: &[usize]) -> Vec<(String, usize)> {
+        let indices: usize = collection(disclosed_indices.iter().map(|index| index.to_string().len()).sum(), disclosed_indices.len());
+        match self {
+            SizeModel::SdJwt => { vec![
+                field("signature", byte_array(ES256_SIGNATURE_LEN)),
+                field("hashes", collection(all.count * string(DIGEST_LEN), all.count)),
+                field("svc", disclosed.object(4 + string(SALT_LEN))),
+            ] }
+            SizeModel::MerkleTree => { vec![
+                clear_claims(disclosed),
+                field("root", byte_array(DIGEST_LEN)),
+                field("leaves_len", all.count.to_string().len()),
+                field("salts", disclosed.keyed_object(string(SALT_LEN))),
+                field("root_sig", byte_array(ES256_SIGNATURE_LEN)),
+                field("merkle_proof", byte_array(DIGEST_LEN * merkle_proof_hashes(all.count, disclosed_indices))),
+                field("disclosed_indices", indices),
+            ] }
+            SizeModel::BbsPlus => {
+                let proof: usize = BBS_PROOF_OVERHEAD + 3 * byte_array(BLS12_381_G1_LEN) + (4 + all.count - disclosed.count) * byte_array(SCALAR_LEN);
+                vec![
+                    clear_claims(disclosed),
+                    field("signature", proof),
+                    field("indices", indices),
+                    field("nonce", byte_array(BBS_NONCE_LEN)),
+                ]
+            }
+            SizeModel::CsdJwt { blinded } | SizeModel::CsdJwtMultiShow { blinded } | SizeModel::CsdJwtAggregated { blinded } => {
+                let mut components: Vec<(String, usize)> = vec![field("accumulator", string(BN254_G1_LEN))];
+                if *blinded {
+                    components.push(field("blinding", string(SALT_LEN)));
+                }
+                components.push(match self {
+                    SizeModel::CsdJwtMultiShow { .. } => { field("pvc", disclosed.object(5 + string(MEMBERSHIP_PROOF_LEN) + string(SCALAR_LEN))) }
+                    SizeModel::CsdJwtAggregated { .. } => { field("dvc", disclosed.object(1)) }
+                    _ => { field("wvc", disclosed.object(4 + string(BN254_G1_LEN))) }
+                });
+                if let SizeModel::CsdJwtAggregated { .. } = self {
+                    components.push(field("aggregated_witness", string(BN254_G1_LEN)));
+                }
+                components
+            }
+            SizeModel::CsdJwtBls12381 => { vec![
+                field("accumulator", string(BLS12_381_G1_LEN)),
+                field("wvc", disclosed.object(4 + string(BLS12_381_G1_LEN))),
+            ] }
+        }
+    }
+}
+
+
+/// Length of a base64url encoding without padding of a given amount of bytes.
+fn encoded(len: usize) -> usize {
+    (4 * len).div_ceil(3)
+}
+
+/// Length of the JSON string holding the base64url encoding of a given amount of bytes.
+fn string(len: usize) -> usize {
+    encoded(len) + 2
+}
+
+/// Length of a JSON array or object, given the total length of its elements and their amount.
+fn collection(elements_len: usize, count: usize) -> usize {
+    2 + elements_len + count.saturating_sub(1)
+}
+
+/// Expected length of the JSON array serializing random bytes, whose elements average 2.57 digits.
+fn byte_array(len: usize) -> usize {
+    collection((257 * len).div_ceil(100), len)
+}
+
+/// Member of a payload holding a component serialized to JSON and encoded in base64url.
+fn field(name: &str, serialized_len: usize) -> (String, usize) {
+    (name.to_string(), name.len() + 5 + encoded(serialized_len))
+}
+
+/// Member of a payload holding the claims in clear.
+fn clear_claims(claims: &ClaimStatistics) -> (String, usize) {
+    (CLAIMS.to_string(), CLAIMS.len() + 3 + claims.object(1))
+}
+
+/// Length of the payload of a credential, given the length of the members of its envelope and of its components.
+fn credential_length(envelope: &[usize], components: &[(String, usize)]) -> usize {
+    let members: usize = envelope.len() + components.len();
+    collection(envelope.iter().sum::<usize>() + components.iter().map(|(_, len)| len).sum::<usize>(), members)
+}
+
+/// Length of the payload of a VP without the credential it wraps.
+fn presentation_wrapper_length() -> usize {
+    let mut presentation: Map<String, Value> = Map::new();
+    presentation.insert("@context".to_string(), Value::Array(vec![Value::String(VP_CONTEXT.to_string())]));
+    presentation.insert("type".to_string(), Value::Array(vec![Value::String(VP_TYPE.to_string())]));
+    presentation.insert(VERIFIABLE_CREDENTIAL.to_string(), Value::Array(vec![Value::Object(Map::new())]));
+
+    let mut envelope: Map<String, Value> = Map::new();
+    envelope.insert(VP.to_string(), Value::Object(presentation));
+    envelope.insert(PRESENTATION_NONCE.to_string(), Value::String(Encoding::Base64UrlNoPad.encode([0u8; SALT_LEN])));
+    envelope.insert(ISSUED_AT.to_string(), Value::from(10u64.pow(TIMESTAMP_DIGITS as u32 - 1)));
+
+    Value::Object(envelope).to_string().len() - 2
+}
+
+/// Length of the header of a JWT signed with a given algorithm.
+fn header_length(algorithm: &str) -> usize {
+    format!(r#"{{"alg":"{algorithm}","sd_enc":"{}"}}"#, Encoding::Base64UrlNoPad.name()).len()
+}
+
+/// Amount of sibling hashes in the multiproof of the disclosed leaves of a Merkle tree, in which the last node of an
+/// odd level is promoted without a sibling.
+fn merkle_proof_hashes(leaves: usize, disclosed_indices: &[usize]) -> usize {
+    let mut known: Vec<usize> = disclosed_indices.to_vec();
+    let mut level_len: usize = leaves;
+    let mut hashes: usize = 0;
+
+    while level_len > 1 && !known.is_empty() {
+        for index in &known {
+            let sibling: usize = index ^ 1;
+            if sibling < level_len && !known.contains(&sibling) {
+                hashes += 1;
+            }
+        }
+        known = known.iter().map(|index| index / 2).collect();
+        known.dedup();
+        level_len = level_len.div_ceil(2);
+    }
+
+    hashes
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::size_estimate::merkle_proof_hashes;
+
+    #[test]
+    fn merkle_multiproofs_share_siblings() -> Result<(), String> {
+        assert_eq!(merkle_proof_hashes(8, &[0]), 3);
+        assert_eq!(merkle_proof_hashes(8, &[0, 1]), 2);
+        assert_eq!(merkle_proof_hashes(8, &[0, 7]), 4);
+        assert_eq!(merkle_proof_hashes(8, &(0..8).collect::<Vec<usize>>()), 0);
+        assert_eq!(merkle_proof_hashes(5, &[4]), 1);
+
+        Ok(())
+    }
+}
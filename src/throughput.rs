@@ -0,0 +1,133 @@
+use std::thread;
+use std::time::{Duration, Instant};
+use crate::adapters::adapter::Adapter;
+
+/// Outcome of a throughput run of an adapter.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Throughput {
+    /// Amount of worker threads verifying presentations concurrently.
+    pub workers: usize,
+    /// Amount of successful verifications, summed over all the workers.
+    pub verifications: usize,
+    /// Wall-clock duration of the run.
+    pub elapsed: Duration,
+    /// Latency of every successful verification, in increasing order.
+    pub latencies: Vec<Duration>,
+}
+
+impl Throughput {
+
+    /// Verifications per second, over all the workers.
+    pub fn per_second(&self) -> f64 {
+        self.verifications as f64 / self.elapsed.as_secs_f64()
+    }
+
+    /// Latency percentile of the verifications under load, with the nearest-rank method.
+    ///
+    /// # Arguments
+    /// * `percentile` - Percentile, between 0 (excluded) and 100.
+    ///
+    /// # Returns
+    /// The latency at the given percentile, or None if no verification completed or the percentile is out of range.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::time::Duration;
+    /// use csd_jwt::throughput::Throughput;
+    ///
+    /// let latencies: Vec<Duration> = (1..=100).map(Duration::from_millis).collect();
+    /// let throughput = Throughput { workers: 1, verifications: 100, elapsed: Duration::from_secs(5), latencies };
+    /// assert_eq!(throughput.percentile(50.0), Some(Duration::from_millis(50)));
+    /// assert_eq!(throughput.percentile(99.0), Some(Duration::from_millis(99)));
+    /// assert_eq!(throughput.percentile(100.0), Some(Duration::from_millis(100)));
+    /// ```
+    pub fn percentile(&self, percentile: f64) -> Option<Duration> {
+        if self.latencies.is_empty() || !(percentile > 0f64 && percentile <= 100f64) {
+            return None;
+        }
+
+        let rank = (percentile / 100f64 * self.latencies.len() as f64).ceil() as usize;
+        self.latencies.get(rank.max(1) - 1).copied()
+    }
+}
+
+
+/// Verifies presentations on several worker threads sharing the same adapter for a given duration, capturing the
+/// contention effects (e.g. threads spawned per claim, locks of the verification cache) that latency benchmarks miss.
+///
+/// Every worker cycles over the presentations, starting from a different one, and starts no new verification once the
+/// duration is elapsed. The latency of every verification is recorded, so that the tail latency under load can be
+/// compared with the one of isolated verifications.
+///
+/// # Arguments
+/// * `adapter` - Adapter verifying the presentations.
+/// * `vp_jwts` - Presentations to be verified, which must all be valid.
+/// * `workers` - Amount of worker threads.
+/// * `duration` - Duration of the run.
+///
+/// # Returns
+/// A result containing the throughput or a string containing an error if a verification fails.
+///
+/// # Examples
+/// ```
+/// use std::time::Duration;
+/// use serde_json::{Map, Value};
+/// use csd_jwt::adapters::adapter::Adapter;
+/// use csd_jwt::adapters::hashes::sd_jwt_adapter::SdJwtAdapter;
+/// use csd_jwt::common_data::VC;
+/// use csd_jwt::throughput::verification_throughput;
+///
+/// let adapter = SdJwtAdapter::new(4).unwrap();
+/// let raw_vc: Map<String, Value> = serde_json::from_str(VC).unwrap();
+/// let (vc, _) = adapter.issue_vc(&raw_vc).unwrap();
+/// let (_, vp_jwt) = adapter.issue_vp(&vc, &vec!["name".to_string()]).unwrap();
+///
+/// let throughput = verification_throughput(&adapter, &[vp_jwt.clone()], 2, Duration::from_millis(100)).unwrap();
+/// assert_eq!(throughput.workers, 2);
+/// assert!(throughput.verifications >= 2 && throughput.per_second() > 0.0);
+/// assert_eq!(throughput.latencies.len(), throughput.verifications);
+/// assert!(throughput.latencies.windows(2).all(|pair| pair[0] <= pair[1]));
+///
+/// // Invalid presentations and empty runs are reported instead of being counted.
+/// assert!(verification_throughput(&adapter, &[vp_jwt.replace('.', "")], 2, Duration::from_millis(10)).is_err());
+/// assert!(verification_throughput(&adapter, &[], 2, Duration::from_millis(10)).is_err());
+/// assert!(verification_throughput(&adapter, &[vp_jwt], 0, Duration::from_millis(10)).is_err());
+/// ```
+pub fn verification_throughput(adapter: &dyn Adapter, vp_jwts: &[String], workers: usize, duration: Duration) -> Result<Throughput, String> {
+
+    if vp_jwts.is_empty() || workers == 0 {
+        return Err("Throughput requires at least one presentation and one worker".to_string());
+    }
+
+    let start = Instant::now();
+    let deadline = start + duration;
+
+    let outcomes: Vec<Result<Vec<Duration>, String>> = thread::scope(|scope| {
+        let handles: Vec<_> = (0..workers).map(|worker| scope.spawn(move || {
+            let mut latencies: Vec<Duration> = vec![];
+            while Instant::now() < deadline {
+                let vp_jwt = &vp_jwts[(worker + latencies.len()) % vp_jwts.len()];
+                let verification_start = Instant::now();
+                match adapter.verify_vp(vp_jwt) {
+                    Ok(_) => { latencies.push(verification_start.elapsed()) }
+                    Err(err) => { return Err(format!("Worker {worker} failed to verify a presentation: [{err}]")) }
+                }
+            }
+            Ok(latencies)
+        })).collect();
+
+        handles.into_iter().map(|handle| match handle.join() {
+            Ok(outcome) => { outcome }
+            Err(_) => { Err("Verification worker panicked".to_string()) }
+        }).collect()
+    });
+
+    let elapsed = start.elapsed();
+    let mut latencies: Vec<Duration> = vec![];
+    for outcome in outcomes {
+        latencies.extend(outcome?);
+    }
+    latencies.sort();
+
+    Ok(Throughput { workers, verifications: latencies.len(), elapsed, latencies })
+}
@@ -0,0 +1,210 @@
+use std::time::Duration;
+use crate::csv_writer::CSVWriter;
+use crate::transport::qr::chunk_count;
+
+/// Profile of a link over which credentials are transferred.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LinkProfile {
+    /// Name of the link, as written in the result file.
+    pub name: String,
+    /// Effective application-level throughput, in bits per second.
+    pub bits_per_second: f64,
+    /// Fixed latency of a transfer, e.g. connection setup or round-trip time.
+    pub latency: Duration,
+}
+
+impl LinkProfile {
+
+    /// Constructor for a link profile.
+    ///
+    /// # Arguments
+    /// * `name` - Name of the link.
+    /// * `kilobits_per_second` - Effective throughput, in kilobits per second.
+    /// * `latency_ms` - Fixed latency of a transfer, in milliseconds.
+    ///
+    /// # Returns
+    /// The link profile.
+    pub fn new(name: &str, kilobits_per_second: f64, latency_ms: u64) -> Self {
+        LinkProfile { name: name.to_string(), bits_per_second: kilobits_per_second * 1000f64, latency: Duration::from_millis(latency_ms) }
+    }
+
+    /// Default profiles, with rough effective throughputs and latencies of typical proximity and mobile links:
+    /// BLE (GATT transfer), NFC (ISO 14443 at 212 kbit/s), 3G (UMTS) and LTE.
+    ///
+    /// # Returns
+    /// The default link profiles.
+    pub fn defaults() -> Vec<Self> {
+        vec![
+            LinkProfile::new("BLE", 160f64, 30),
+            LinkProfile::new("NFC", 212f64, 5),
+            LinkProfile::new("3G", 384f64, 100),
+            LinkProfile::new("LTE", 10_000f64, 50),
+        ]
+    }
+
+    /// Parses a comma separated list of profiles of the form `name:kbps:latency_ms`.
+    ///
+    /// # Arguments
+    /// * `configuration` - List of profiles.
+    ///
+    /// # Returns
+    /// A result containing the profiles or a string containing an error.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::time::Duration;
+    /// use csd_jwt::transfer_model::LinkProfile;
+    ///
+    /// let profiles = LinkProfile::parse_list("BLE:160:30, WiFi:50000:10").unwrap();
+    /// assert_eq!(profiles.len(), 2);
+    /// assert_eq!(profiles[1].name, "WiFi");
+    /// assert_eq!(profiles[1].latency, Duration::from_millis(10));
+    /// assert_eq!(profiles[0].transfer_time(2000), Duration::from_millis(130));
+    /// ```
+    pub fn parse_list(configuration: &str) -> Result<Vec<Self>, String> {
+        configuration.split(',').map(|profile| {
+            let fields: Vec<&str> = profile.trim().split(':').collect();
+            let (name, kilobits_per_second, latency_ms) = match fields.as_slice() {
+                [name, kilobits_per_second, latency_ms] => { (name, kilobits_per_second, latency_ms) }
+                _ => { return Err(format!("Link profile {profile} is not of the form name:kbps:latency_ms")) }
+            };
+
+            let kilobits_per_second: f64 = match kilobits_per_second.parse::<f64>() {
+                Ok(kilobits_per_second) if kilobits_per_second > 0f64 => { kilobits_per_second }
+                _ => { return Err(format!("Invalid throughput in link profile {profile}")) }
+            };
+            let latency_ms: u64 = match latency_ms.parse::<u64>() {
+                Ok(latency_ms) => { latency_ms }
+                Err(err) => { return Err(format!("Invalid latency in link profile {profile}: [{err}]")) }
+            };
+
+            Ok(LinkProfile::new(name, kilobits_per_second, latency_ms))
+        }).collect()
+    }
+
+    /// Estimated time to transfer a payload over the link.
+    ///
+    /// # Arguments
+    /// * `bytes` - Size of the payload.
+    ///
+    /// # Returns
+    /// The fixed latency plus the time to send the payload at the effective throughput.
+    pub fn transfer_time(&self, bytes: usize) -> Duration {
+        self.latency + Duration::from_secs_f64(bytes as f64 * 8f64 / self.bits_per_second)
+    }
+}
+
+
+/// Size of a credential measured by the benchmark.
+struct MeasuredSize {
+    credential: &'static str,
+    n_claims: usize,
+    n_disclosures: usize,
+    algorithm: String,
+    bytes: usize,
+}
+
+/// Converts the measured sizes of VCs and VPs into estimated transfer times over several links and QR code counts.
+///
+/// # Examples
+/// ```
+/// use std::fs;
+/// use std::path::Path;
+/// use csd_jwt::csv_writer::CSV_DIR;
+/// use csd_jwt::transfer_model::{LinkProfile, TransferCostModel};
+/// use csd_jwt::transport::qr::QR_CAPACITY;
+///
+/// let mut model = TransferCostModel::new(LinkProfile::defaults(), QR_CAPACITY);
+/// model.record_vc(10, "SD-JWT", 1800);
+/// model.record_vp(10, 5, "SD-JWT", 1200);
+/// model.write_summary(&"transfer_costs_example".to_string()).unwrap();
+///
+/// let path = Path::new(CSV_DIR).join("transfer_costs_example.csv");
+/// let summary: String = fs::read_to_string(&path).unwrap();
+/// fs::remove_file(&path).unwrap();
+/// let rows: Vec<&str> = summary.lines().collect();
+/// assert_eq!(rows.len(), 3);
+/// assert!(rows[0].starts_with("credential,claims,disclosed,algorithm,bytes,") && rows[0].ends_with(",qr_chunks"));
+/// assert!(rows[1].starts_with("vc,10,10,SD-JWT,1800,"));
+/// assert!(rows[2].starts_with("vp,10,5,SD-JWT,1200,"));
+/// ```
+pub struct TransferCostModel {
+    /// Links the transfer is estimated over.
+    profiles: Vec<LinkProfile>,
+    /// Capacity of a single QR code.
+    qr_chunk_bytes: usize,
+    /// Sizes recorded during the benchmark.
+    sizes: Vec<MeasuredSize>,
+}
+
+impl TransferCostModel {
+
+    /// Constructor for a transfer cost model.
+    ///
+    /// # Arguments
+    /// * `profiles` - Links the transfer is estimated over.
+    /// * `qr_chunk_bytes` - Capacity of a single QR code, including the header of the chunk.
+    ///
+    /// # Returns
+    /// A model without any recorded size.
+    pub fn new(profiles: Vec<LinkProfile>, qr_chunk_bytes: usize) -> Self {
+        TransferCostModel { profiles, qr_chunk_bytes, sizes: vec![] }
+    }
+
+    /// Records the size of a VC.
+    ///
+    /// # Arguments
+    /// * `n_claims` - Amount of claims in the VC.
+    /// * `algorithm` - Name of the algorithm.
+    /// * `bytes` - Size of the encoded VC.
+    pub fn record_vc(&mut self, n_claims: usize, algorithm: &str, bytes: usize) {
+        self.sizes.push(MeasuredSize { credential: "vc", n_claims, n_disclosures: n_claims, algorithm: algorithm.to_string(), bytes });
+    }
+
+    /// Records the size of a VP.
+    ///
+    /// # Arguments
+    /// * `n_claims` - Amount of claims in the VC the VP is derived from.
+    /// * `n_disclosures` - Amount of claims disclosed in the VP.
+    /// * `algorithm` - Name of the algorithm.
+    /// * `bytes` - Size of the encoded VP.
+    pub fn record_vp(&mut self, n_claims: usize, n_disclosures: usize, algorithm: &str, bytes: usize) {
+        self.sizes.push(MeasuredSize { credential: "vp", n_claims, n_disclosures, algorithm: algorithm.to_string(), bytes });
+    }
+
+    /// Writes the estimated transfer time over every link, in microseconds, and the amount of QR chunks of every recorded
+    /// size to a CSV file.
+    ///
+    /// # Arguments
+    /// * `filename` - Name of the CSV file, without extension.
+    ///
+    /// # Returns
+    /// The result of the operation or a string containing an error.
+    pub fn write_summary(&self, filename: &String) -> Result<(), String> {
+
+        let mut columns: Vec<String> = ["credential", "claims", "disclosed", "algorithm", "bytes"]
+            .iter()
+            .map(|column| column.to_string())
+            .collect();
+        columns.extend(self.profiles.iter().map(|profile| format!("{}_us", profile.name)));
+        columns.push("qr_chunks".to_string());
+
+        let mut writer = CSVWriter::new(columns)?;
+        writer.add_file(filename)?;
+
+        for size in &self.sizes {
+            let mut record: Vec<String> = vec![
+                size.credential.to_string(),
+                size.n_claims.to_string(),
+                size.n_disclosures.to_string(),
+                size.algorithm.clone(),
+                size.bytes.to_string(),
+            ];
+            record.extend(self.profiles.iter().map(|profile| profile.transfer_time(size.bytes).as_micros().to_string()));
+            record.push(chunk_count(size.bytes, self.qr_chunk_bytes)?.to_string());
+            writer.write_record_to_file(filename, record)?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,223 @@
+use std::collections::BTreeMap;
+use serde_json::{Map, Value};
+use crate::common_data::CLAIMS;
+use crate::derived_claims::Date;
+
+/// Separator between the name of a claim and the transformation applied to it in the name of the transformed claim,
+/// e.g. `birthdate~year`.
+pub const TRANSFORMATION_SEPARATOR: &str = "~";
+/// Prefix of the name of the bracket transformation, followed by the width of the brackets, e.g. `bracket_10000`.
+const BRACKET_PREFIX: &str = "bracket_";
+
+
+/// Coarsening of the value of a claim that a holder may disclose instead of the exact value.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Transformation {
+    /// Truncates a `YYYY-MM-DD` date to its year.
+    Year,
+    /// Rounds a number down to a bracket of the given width, disclosed as `<lower>-<upper>`.
+    Bracket(u64),
+}
+
+impl Transformation {
+
+    /// Parses a transformation of the form `year` or `bracket_<width>`.
+    ///
+    /// # Arguments
+    /// * `transformation` - Transformation to be parsed.
+    ///
+    /// # Returns
+    /// A result containing the transformation or a string containing an error.
+    pub fn parse(transformation: &str) -> Result<Self, String> {
+        let transformation: &str = transformation.trim();
+        if transformation == "year" {
+            return Ok(Transformation::Year);
+        }
+
+        match transformation.strip_prefix(BRACKET_PREFIX).map(|width| width.parse::<u64>()) {
+            Some(Ok(width)) if width > 0 => { Ok(Transformation::Bracket(width)) }
+            Some(_) => { Err(format!("Invalid width in transformation {transformation}")) }
+            None => { Err(format!("Unknown transformation {transformation}, expected year or {BRACKET_PREFIX}<width>")) }
+        }
+    }
+
+    /// Name of the transformation, as parsed by `parse`.
+    pub fn name(&self) -> String {
+        match self {
+            Transformation::Year => { "year".to_string() }
+            Transformation::Bracket(width) => { format!("{BRACKET_PREFIX}{width}") }
+        }
+    }
+
+    /// Name of the claim holding the transformed value of a claim.
+    ///
+    /// # Arguments
+    /// * `claim` - Name of the original claim.
+    pub fn claim_name(&self, claim: &str) -> String {
+        format!("{claim}{TRANSFORMATION_SEPARATOR}{}", self.name())
+    }
+
+    /// Computes the transformed value of a claim, always as a string so that every algorithm can protect it.
+    ///
+    /// # Arguments
+    /// * `claim` - Name of the claim, for the errors.
+    /// * `value` - Value of the claim.
+    ///
+    /// # Returns
+    /// A result containing the transformed value or a string containing an error if the value cannot be transformed.
+    pub fn apply(&self, claim: &str, value: &Value) -> Result<Value, String> {
+        match self {
+            Transformation::Year => match value {
+                Value::String(date) => { Ok(Value::String(Date::parse(date)?.year.to_string())) }
+                _ => { Err(format!("Claim {claim} must be a date to be truncated to its year")) }
+            },
+            Transformation::Bracket(width) => {
+                let number: f64 = match value {
+                    Value::Number(number) => { number.as_f64().unwrap_or(f64::NAN) }
+                    Value::String(number) => { number.parse::<f64>().unwrap_or(f64::NAN) }
+                    _ => { f64::NAN }
+                };
+                if !number.is_finite() {
+                    return Err(format!("Claim {claim} must be a number to be rounded to a bracket"));
+                }
+
+                let lower: i64 = (number / *width as f64).floor() as i64 * *width as i64;
+                Ok(Value::String(format!("{lower}-{}", lower + *width as i64)))
+            }
+        }
+    }
+}
+
+
+/// Splits the name of a disclosed claim into the original claim and the transformation applied to it, so that a
+/// verifier is aware that it received a coarsened value.
+///
+/// # Arguments
+/// * `name` - Name of a disclosed claim.
+///
+/// # Returns
+/// The name of the original claim and the transformation, or None if the claim was not transformed.
+pub fn parse_transformed_claim(name: &str) -> Option<(&str, Transformation)> {
+    let (claim, transformation) = name.rsplit_once(TRANSFORMATION_SEPARATOR)?;
+    Transformation::parse(transformation).ok().map(|transformation| (claim, transformation))
+}
+
+
+/// Transformations the issuer pre-authorizes at issuance: the transformed value of every claim is added to the
+/// credential as an individually disclosable claim, named after the claim and the transformation, so that the holder
+/// can later disclose it instead of the exact value without breaking the proof of the issuer.
+///
+/// # Examples
+/// ```
+/// use serde_json::{json, Map, Value};
+/// use csd_jwt::transformations::{parse_transformed_claim, AuthorizedTransformations, Transformation};
+///
+/// let authorized = AuthorizedTransformations::parse("birthdate~year, salary~bracket_10000").unwrap();
+/// let mut raw_vc: Map<String, Value> = serde_json::from_value(json!({
+///     "credentialSubject": {"name": "Ada", "birthdate": "1990-06-30", "salary": 43250}
+/// })).unwrap();
+/// authorized.insert_into(&mut raw_vc).unwrap();
+/// assert_eq!(raw_vc["credentialSubject"]["birthdate~year"], json!("1990"));
+/// assert_eq!(raw_vc["credentialSubject"]["salary~bracket_10000"], json!("40000-50000"));
+///
+/// let claims = raw_vc["credentialSubject"].as_object().unwrap();
+/// let hooks = vec![("birthdate".to_string(), Transformation::Year)];
+/// let disclosures = AuthorizedTransformations::transform_disclosures(claims, &vec!["name".to_string(), "birthdate".to_string()], &hooks).unwrap();
+/// assert_eq!(disclosures, vec!["name".to_string(), "birthdate~year".to_string()]);
+/// assert_eq!(parse_transformed_claim("birthdate~year"), Some(("birthdate", Transformation::Year)));
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AuthorizedTransformations {
+    /// Transformations authorized for every claim.
+    transformations: BTreeMap<String, Vec<Transformation>>,
+}
+
+impl AuthorizedTransformations {
+
+    /// Parses a comma separated list of authorized transformations of the form `<claim>~<transformation>`, e.g.
+    /// `birthdate~year,salary~bracket_10000`.
+    ///
+    /// # Arguments
+    /// * `configuration` - List of transformations.
+    ///
+    /// # Returns
+    /// A result containing the authorized transformations or a string containing an error.
+    pub fn parse(configuration: &str) -> Result<Self, String> {
+        let mut transformations: BTreeMap<String, Vec<Transformation>> = BTreeMap::new();
+        for entry in configuration.split(',') {
+            let (claim, transformation) = match entry.trim().rsplit_once(TRANSFORMATION_SEPARATOR) {
+                Some((claim, transformation)) if !claim.is_empty() => { (claim, Transformation::parse(transformation)?) }
+                _ => { return Err(format!("Transformation {entry} is not of the form <claim>{TRANSFORMATION_SEPARATOR}<transformation>")) }
+            };
+            transformations.entry(claim.to_string()).or_default().push(transformation);
+        }
+
+        Ok(AuthorizedTransformations { transformations })
+    }
+
+    /// Names of the transformed claims added at issuance.
+    pub fn claim_names(&self) -> Vec<String> {
+        self.transformations.iter()
+            .flat_map(|(claim, transformations)| transformations.iter().map(move |transformation| transformation.claim_name(claim)))
+            .collect()
+    }
+
+    /// Adds the transformed claims to the claims of a raw VC.
+    ///
+    /// # Arguments
+    /// * `raw_vc` - Raw VC to which the transformed claims are added.
+    ///
+    /// # Returns
+    /// A result containing a string in case a claim is missing or cannot be transformed, or a transformed claim already exists.
+    pub fn insert_into(&self, raw_vc: &mut Map<String, Value>) -> Result<(), String> {
+        let claims = match raw_vc.get_mut(CLAIMS) {
+            Some(Value::Object(claims)) => { claims }
+            _ => { return Err(format!("Raw VC does not contain {CLAIMS}")) }
+        };
+
+        let mut transformed: Vec<(String, Value)> = vec![];
+        for (claim, transformations) in &self.transformations {
+            let value: &Value = match claims.get(claim) {
+                Some(value) => { value }
+                None => { return Err(format!("Claim {claim} is missing, it cannot be transformed")) }
+            };
+            for transformation in transformations {
+                let name: String = transformation.claim_name(claim);
+                if claims.contains_key(&name) {
+                    return Err(format!("Transformed claim {name} is already a claim of the credential"));
+                }
+                transformed.push((name, transformation.apply(claim, value)?));
+            }
+        }
+        claims.extend(transformed);
+
+        Ok(())
+    }
+
+    /// Holder-side hook applied at presentation time: replaces the disclosure of every claim the holder chose to
+    /// coarsen with the disclosure of its pre-authorized transformed claim.
+    ///
+    /// # Arguments
+    /// * `claims` - Claims of the VC.
+    /// * `disclosures` - Claims to be disclosed.
+    /// * `hooks` - Transformations the holder applies, by claim.
+    ///
+    /// # Returns
+    /// A result containing the disclosures to pass to the algorithm, or a string containing an error if the issuer did
+    /// not authorize a transformation.
+    pub fn transform_disclosures(claims: &Map<String, Value>, disclosures: &[String], hooks: &[(String, Transformation)]) -> Result<Vec<String>, String> {
+        let mut transformed_disclosures: Vec<String> = vec![];
+        for disclosure in disclosures {
+            let name: String = match hooks.iter().find(|(claim, _)| claim == disclosure) {
+                Some((claim, transformation)) => { transformation.claim_name(claim) }
+                None => { disclosure.clone() }
+            };
+            if !claims.contains_key(&name) {
+                return Err(format!("Credential does not contain the claim {name}, the issuer did not authorize it"));
+            }
+            transformed_disclosures.push(name);
+        }
+
+        Ok(transformed_disclosures)
+    }
+}
@@ -0,0 +1,310 @@
+use std::collections::{HashMap, VecDeque};
+use std::fs;
+use std::path::PathBuf;
+use std::thread;
+use std::time::Duration;
+use crate::transfer_model::LinkProfile;
+use crate::transport::http::HttpTransport;
+
+/// Roles exchanging VCs and VPs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Role {
+    Issuer,
+    Holder,
+    Verifier,
+}
+
+impl Role {
+
+    /// Name of the role, as used in paths and URLs.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Role::Issuer => { "issuer" }
+            Role::Holder => { "holder" }
+            Role::Verifier => { "verifier" }
+        }
+    }
+}
+
+
+/// Traffic generated by a transport.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TransportStatistics {
+    /// Amount of messages sent.
+    pub messages: usize,
+    /// Bytes carried by the medium in both directions, including the framing of the transport (e.g. HTTP headers).
+    pub bytes_on_wire: usize,
+    /// Amount of request-response round trips, counted as one per message by transports without requests.
+    pub round_trips: usize,
+}
+
+impl TransportStatistics {
+
+    /// Traffic generated since an earlier snapshot of the statistics.
+    ///
+    /// # Arguments
+    /// * `earlier` - Snapshot of the statistics of the same transport.
+    ///
+    /// # Returns
+    /// The difference between the statistics and the snapshot.
+    pub fn since(&self, earlier: &TransportStatistics) -> TransportStatistics {
+        TransportStatistics {
+            messages: self.messages - earlier.messages,
+            bytes_on_wire: self.bytes_on_wire - earlier.bytes_on_wire,
+            round_trips: self.round_trips - earlier.round_trips,
+        }
+    }
+}
+
+
+/// Medium over which VCs and VPs are moved between roles, instrumented to record the traffic it generates.
+///
+/// # Examples
+/// ```
+/// use csd_jwt::transport::channel::{InMemoryTransport, Role, Transport};
+///
+/// let mut transport = InMemoryTransport::new();
+/// transport.send(Role::Issuer, Role::Holder, "{\"vc\": {}}").unwrap();
+/// assert_eq!(transport.receive(Role::Holder).unwrap(), "{\"vc\": {}}");
+/// assert!(transport.receive(Role::Holder).is_err());
+/// assert_eq!(transport.statistics().bytes_on_wire, 10);
+/// ```
+pub trait Transport {
+
+    /// Name of the transport.
+    fn name(&self) -> String;
+
+    /// Sends a message to a role.
+    ///
+    /// # Arguments
+    /// * `from` - Role sending the message.
+    /// * `to` - Role the message is addressed to.
+    /// * `message` - Serialized message.
+    ///
+    /// # Returns
+    /// A result containing a string in case the message cannot be sent.
+    fn send(&mut self, from: Role, to: Role, message: &str) -> Result<(), String>;
+
+    /// Receives the oldest message addressed to a role.
+    ///
+    /// # Arguments
+    /// * `role` - Role receiving the message.
+    ///
+    /// # Returns
+    /// A result containing the message or a string containing an error if no message is pending.
+    fn receive(&mut self, role: Role) -> Result<String, String>;
+
+    /// Traffic generated since the creation of the transport.
+    fn statistics(&self) -> TransportStatistics;
+
+    /// Sends a message and receives it on the other side.
+    ///
+    /// # Arguments
+    /// * `from` - Role sending the message.
+    /// * `to` - Role the message is addressed to.
+    /// * `message` - Serialized message.
+    ///
+    /// # Returns
+    /// A result containing the message as received or a string containing an error.
+    fn transfer(&mut self, from: Role, to: Role, message: &str) -> Result<String, String> {
+        self.send(from, to, message)?;
+        self.receive(to)
+    }
+}
+
+
+/// Transport keeping the messages in memory, which only accounts for the size of the messages.
+pub struct InMemoryTransport {
+    /// Pending messages of every role.
+    mailboxes: HashMap<Role, VecDeque<String>>,
+    /// Traffic generated so far.
+    statistics: TransportStatistics,
+}
+
+impl InMemoryTransport {
+
+    /// Constructor for an in-memory transport.
+    pub fn new() -> Self {
+        InMemoryTransport { mailboxes: HashMap::new(), statistics: TransportStatistics::default() }
+    }
+}
+
+impl Default for InMemoryTransport {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Transport for InMemoryTransport {
+
+    fn name(&self) -> String {
+        "memory".to_string()
+    }
+
+    fn send(&mut self, _from: Role, to: Role, message: &str) -> Result<(), String> {
+        self.mailboxes.entry(to).or_default().push_back(message.to_string());
+        self.statistics.messages += 1;
+        self.statistics.bytes_on_wire += message.len();
+        self.statistics.round_trips += 1;
+        Ok(())
+    }
+
+    fn receive(&mut self, role: Role) -> Result<String, String> {
+        match self.mailboxes.get_mut(&role).and_then(|mailbox| mailbox.pop_front()) {
+            Some(message) => { Ok(message) }
+            None => { Err(format!("No message pending for the {}", role.name())) }
+        }
+    }
+
+    fn statistics(&self) -> TransportStatistics {
+        self.statistics
+    }
+}
+
+
+/// Transport writing every message to a file in the directory of the receiving role, and deleting it once received.
+pub struct FileTransport {
+    /// Directory holding one subdirectory per role.
+    directory: PathBuf,
+    /// Sequence number of the next message, keeping the files of a role in sending order.
+    sequence: usize,
+    /// Traffic generated so far.
+    statistics: TransportStatistics,
+}
+
+impl FileTransport {
+
+    /// Constructor for a file transport.
+    ///
+    /// # Arguments
+    /// * `directory` - Directory where the messages are written, created if missing.
+    ///
+    /// # Returns
+    /// A result containing the transport or a string containing an error.
+    pub fn new(directory: PathBuf) -> Result<Self, String> {
+        for role in [Role::Issuer, Role::Holder, Role::Verifier] {
+            if let Err(err) = fs::create_dir_all(directory.join(role.name())) {
+                return Err(format!("Failed to create the directory of the {}: [{err}]", role.name()));
+            }
+        }
+
+        Ok(FileTransport { directory, sequence: 0, statistics: TransportStatistics::default() })
+    }
+}
+
+impl Transport for FileTransport {
+
+    fn name(&self) -> String {
+        "file".to_string()
+    }
+
+    fn send(&mut self, _from: Role, to: Role, message: &str) -> Result<(), String> {
+        let path = self.directory.join(to.name()).join(format!("{:012}.json", self.sequence));
+        if let Err(err) = fs::write(&path, message) {
+            return Err(format!("Failed to write message {}: [{err}]", path.display()));
+        }
+
+        self.sequence += 1;
+        self.statistics.messages += 1;
+        self.statistics.bytes_on_wire += message.len();
+        self.statistics.round_trips += 1;
+        Ok(())
+    }
+
+    fn receive(&mut self, role: Role) -> Result<String, String> {
+        let entries = match fs::read_dir(self.directory.join(role.name())) {
+            Ok(entries) => { entries }
+            Err(err) => { return Err(format!("Failed to list the messages of the {}: [{err}]", role.name())) }
+        };
+
+        let oldest: Option<PathBuf> = entries.filter_map(|entry| entry.ok().map(|entry| entry.path())).min();
+        let path = match oldest {
+            Some(path) => { path }
+            None => { return Err(format!("No message pending for the {}", role.name())) }
+        };
+
+        let message = match fs::read_to_string(&path) {
+            Ok(message) => { message }
+            Err(err) => { return Err(format!("Failed to read message {}: [{err}]", path.display())) }
+        };
+        if let Err(err) = fs::remove_file(&path) {
+            return Err(format!("Failed to remove message {}: [{err}]", path.display()));
+        }
+
+        Ok(message)
+    }
+
+    fn statistics(&self) -> TransportStatistics {
+        self.statistics
+    }
+}
+
+
+/// Transport delaying every message sent over another transport as if it was sent over a link, i.e. by the latency of
+/// the link plus the time to send the message at its throughput.
+pub struct SimulatedLatencyTransport {
+    /// Transport actually moving the messages.
+    inner: Box<dyn Transport>,
+    /// Simulated link.
+    link: LinkProfile,
+}
+
+impl SimulatedLatencyTransport {
+
+    /// Constructor for a simulated-latency transport.
+    ///
+    /// # Arguments
+    /// * `inner` - Transport actually moving the messages.
+    /// * `link` - Simulated link.
+    ///
+    /// # Returns
+    /// The transport.
+    pub fn new(inner: Box<dyn Transport>, link: LinkProfile) -> Self {
+        SimulatedLatencyTransport { inner, link }
+    }
+}
+
+impl Transport for SimulatedLatencyTransport {
+
+    fn name(&self) -> String {
+        format!("{} over simulated {}", self.inner.name(), self.link.name)
+    }
+
+    fn send(&mut self, from: Role, to: Role, message: &str) -> Result<(), String> {
+        let delay: Duration = self.link.transfer_time(message.len());
+        thread::sleep(delay);
+        self.inner.send(from, to, message)
+    }
+
+    fn receive(&mut self, role: Role) -> Result<String, String> {
+        self.inner.receive(role)
+    }
+
+    fn statistics(&self) -> TransportStatistics {
+        self.inner.statistics()
+    }
+}
+
+
+/// Creates a transport from its configuration name: `memory`, `file:<directory>`, `http` (HTTP on a loopback mailbox
+/// server) or `simulated:<link>` (in-memory transport delayed as one of the default link profiles, e.g. `simulated:BLE`).
+///
+/// # Arguments
+/// * `name` - Configuration name of the transport.
+///
+/// # Returns
+/// A result containing the transport or a string containing an error if the name is unknown.
+pub fn transport_from_name(name: &str) -> Result<Box<dyn Transport>, String> {
+    match name.split_once(':') {
+        None if name == "memory" => { Ok(Box::new(InMemoryTransport::new())) }
+        None if name == "http" => { Ok(Box::new(HttpTransport::loopback()?)) }
+        Some(("file", directory)) => { Ok(Box::new(FileTransport::new(PathBuf::from(directory))?)) }
+        Some(("simulated", link)) => {
+            match LinkProfile::defaults().into_iter().find(|profile| profile.name == link) {
+                Some(profile) => { Ok(Box::new(SimulatedLatencyTransport::new(Box::new(InMemoryTransport::new()), profile))) }
+                None => { Err(format!("Unknown link profile {link}")) }
+            }
+        }
+        _ => { Err(format!("Unknown transport {name}")) }
+    }
+}
@@ -0,0 +1,245 @@
+use std::collections::{HashMap, VecDeque};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::thread::JoinHandle;
+use crate::transport::channel::{Role, Transport, TransportStatistics};
+
+/// Transport moving every message with HTTP/1.1 requests to a mailbox server on the loopback interface: a message is
+/// sent with `POST /<role>` and received with `GET /<role>`, so that the framing and connection costs of HTTP are
+/// included in the traffic and in the durations.
+///
+/// # Examples
+/// ```
+/// use csd_jwt::transport::channel::{Role, Transport};
+/// use csd_jwt::transport::http::HttpTransport;
+///
+/// let mut transport = HttpTransport::loopback().unwrap();
+/// assert_eq!(transport.transfer(Role::Holder, Role::Verifier, "eyJ...").unwrap(), "eyJ...");
+/// assert_eq!(transport.statistics().round_trips, 2);
+/// assert_eq!(transport.statistics().messages, 1);
+///
+/// // The HTTP framing is counted on top of the message, sent once and received once.
+/// assert!(transport.statistics().bytes_on_wire > 2 * "eyJ...".len());
+/// ```
+pub struct HttpTransport {
+    /// Address of the mailbox server.
+    address: SocketAddr,
+    /// Flag asking the mailbox server to stop.
+    stop: Arc<AtomicBool>,
+    /// Thread running the mailbox server.
+    server: Option<JoinHandle<()>>,
+    /// Traffic generated so far.
+    statistics: TransportStatistics,
+}
+
+impl HttpTransport {
+
+    /// Starts a mailbox server on an ephemeral loopback port and creates a transport using it.
+    ///
+    /// # Returns
+    /// A result containing the transport or a string containing an error if the server cannot be started.
+    pub fn loopback() -> Result<Self, String> {
+        let listener = match TcpListener::bind("127.0.0.1:0") {
+            Ok(listener) => { listener }
+            Err(err) => { return Err(format!("Failed to bind the mailbox server: [{err}]")) }
+        };
+        let address = match listener.local_addr() {
+            Ok(address) => { address }
+            Err(err) => { return Err(format!("Failed to retrieve the address of the mailbox server: [{err}]")) }
+        };
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let server_stop = stop.clone();
+        let server = thread::spawn(move || serve_mailboxes(listener, server_stop));
+
+        Ok(HttpTransport { address, stop, server: Some(server), statistics: TransportStatistics::default() })
+    }
+
+    /// Sends a request to the mailbox server.
+    ///
+    /// # Returns
+    /// A result containing the status and body of the response or a string containing an error.
+    fn request(&mut self, method: &str, role: Role, body: &str) -> Result<(u16, String), String> {
+        let mut stream = match TcpStream::connect(self.address) {
+            Ok(stream) => { stream }
+            Err(err) => { return Err(format!("Failed to connect to the mailbox server: [{err}]")) }
+        };
+
+        let request = format!(
+            "{method} /{} HTTP/1.1\r\nHost: {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+            role.name(), self.address, body.len()
+        );
+        if let Err(err) = stream.write_all(request.as_bytes()) {
+            return Err(format!("Failed to send the request: [{err}]"));
+        }
+
+        let mut response: Vec<u8> = vec![];
+        if let Err(err) = stream.read_to_end(&mut response) {
+            return Err(format!("Failed to read the response: [{err}]"));
+        }
+
+        self.statistics.bytes_on_wire += request.len() + response.len();
+        self.statistics.round_trips += 1;
+
+        let response = match String::from_utf8(response) {
+            Ok(response) => { response }
+            Err(err) => { return Err(format!("Response is not valid UTF-8: [{err}]")) }
+        };
+        let (head, body) = match response.split_once("\r\n\r\n") {
+            Some(parts) => { parts }
+            None => { return Err("Malformed HTTP response".to_string()) }
+        };
+        let status: u16 = match head.split_whitespace().nth(1).map(|status| status.parse::<u16>()) {
+            Some(Ok(status)) => { status }
+            _ => { return Err("Malformed HTTP status line".to_string()) }
+        };
+
+        Ok((status, body.to_string()))
+    }
+}
+
+impl Transport for HttpTransport {
+
+    fn name(&self) -> String {
+        "http".to_string()
+    }
+
+    fn send(&mut self, _from: Role, to: Role, message: &str) -> Result<(), String> {
+        match self.request("POST", to, message)? {
+            (204, _) => {
+                self.statistics.messages += 1;
+                Ok(())
+            }
+            (status, body) => { Err(format!("Mailbox server refused the message with status {status}: {body}")) }
+        }
+    }
+
+    fn receive(&mut self, role: Role) -> Result<String, String> {
+        match self.request("GET", role, "")? {
+            (200, message) => { Ok(message) }
+            (404, _) => { Err(format!("No message pending for the {}", role.name())) }
+            (status, body) => { Err(format!("Mailbox server failed with status {status}: {body}")) }
+        }
+    }
+
+    fn statistics(&self) -> TransportStatistics {
+        self.statistics
+    }
+}
+
+impl Drop for HttpTransport {
+    /// Stops the mailbox server, waking it up with a last connection.
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::SeqCst);
+        let _ = TcpStream::connect(self.address);
+        if let Some(server) = self.server.take() {
+            let _ = server.join();
+        }
+    }
+}
+
+
+/// Fetches a resource with an HTTP/1.1 `GET` request, e.g. the JWKS of an issuer.
+///
+/// # Arguments
+/// * `url` - `http://` URL of the resource.
+///
+/// # Returns
+/// A result containing the body of the response or a string containing an error if the request fails or the response
+/// status is not 200.
+pub fn http_get(url: &str) -> Result<String, String> {
+    let (authority, path) = match url.strip_prefix("http://") {
+        Some(location) => match location.find('/') {
+            Some(index) => { location.split_at(index) }
+            None => { (location, "/") }
+        },
+        None => { return Err(format!("Unsupported URL {url}, only http:// URLs can be fetched")) }
+    };
+    let address: String = if authority.contains(':') { authority.to_string() } else { format!("{authority}:80") };
+
+    let mut stream = match TcpStream::connect(address.as_str()) {
+        Ok(stream) => { stream }
+        Err(err) => { return Err(format!("Failed to connect to {authority}: [{err}]")) }
+    };
+    let request = format!("GET {path} HTTP/1.1\r\nHost: {authority}\r\nAccept: application/json\r\nConnection: close\r\n\r\n");
+    if let Err(err) = stream.write_all(request.as_bytes()) {
+        return Err(format!("Failed to send the request to {url}: [{err}]"));
+    }
+
+    let mut response: Vec<u8> = vec![];
+    if let Err(err) = stream.read_to_end(&mut response) {
+        return Err(format!("Failed to read the response of {url}: [{err}]"));
+    }
+    let response = match String::from_utf8(response) {
+        Ok(response) => { response }
+        Err(err) => { return Err(format!("Response of {url} is not valid UTF-8: [{err}]")) }
+    };
+    match response.split_once("\r\n\r\n") {
+        Some((head, body)) if head.split_whitespace().nth(1) == Some("200") => { Ok(body.to_string()) }
+        Some((head, _)) => { Err(format!("Request to {url} failed: {}", head.lines().next().unwrap_or_default())) }
+        None => { Err(format!("Malformed HTTP response from {url}")) }
+    }
+}
+
+
+/// Accepts connections until asked to stop, storing the posted messages in per-role mailboxes.
+fn serve_mailboxes(listener: TcpListener, stop: Arc<AtomicBool>) {
+    let mut mailboxes: HashMap<String, VecDeque<String>> = HashMap::new();
+
+    for stream in listener.incoming() {
+        if stop.load(Ordering::SeqCst) {
+            break;
+        }
+        if let Ok(stream) = stream {
+            // A failing connection only affects the request it carried, which the client reports.
+            let _ = handle_connection(stream, &mut mailboxes);
+        }
+    }
+}
+
+/// Serves a single request of the mailbox server.
+fn handle_connection(stream: TcpStream, mailboxes: &mut HashMap<String, VecDeque<String>>) -> std::io::Result<()> {
+    let mut reader = BufReader::new(stream);
+
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    let mut content_length: usize = 0;
+    loop {
+        let mut header = String::new();
+        reader.read_line(&mut header)?;
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                content_length = value.trim().parse().unwrap_or(0);
+            }
+        }
+    }
+
+    let mut body = vec![0u8; content_length];
+    reader.read_exact(&mut body)?;
+
+    let mut parts = request_line.split_whitespace();
+    let (status, response_body) = match (parts.next(), parts.next().map(|path| path.trim_start_matches('/').to_string())) {
+        (Some("POST"), Some(role)) => match String::from_utf8(body) {
+            Ok(message) => {
+                mailboxes.entry(role).or_default().push_back(message);
+                ("204 No Content", String::new())
+            }
+            Err(_) => { ("400 Bad Request", "Message is not valid UTF-8".to_string()) }
+        },
+        (Some("GET"), Some(role)) => match mailboxes.get_mut(&role).and_then(|mailbox| mailbox.pop_front()) {
+            Some(message) => { ("200 OK", message) }
+            None => { ("404 Not Found", String::new()) }
+        },
+        _ => { ("405 Method Not Allowed", String::new()) }
+    };
+
+    let response = format!("HTTP/1.1 {status}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{response_body}", response_body.len());
+    reader.get_mut().write_all(response.as_bytes())
+}
@@ -0,0 +1,3 @@
+pub mod qr;
+pub mod channel;
+pub mod http;
@@ -0,0 +1,151 @@
+use std::collections::BTreeMap;
+use crate::encoding::Encoding;
+use crate::hashing::sha256;
+
+/// Capacity in bytes of a QR code of version 40 with low error correction in byte mode, the largest available.
+pub const QR_CAPACITY: usize = 2953;
+/// Prefix of every chunk, identifying it as a fragment of a presentation.
+pub const CHUNK_PREFIX: &str = "VPQR";
+/// Length of the identifier shared by the chunks of the same payload.
+const PAYLOAD_ID_LEN: usize = 8;
+
+/// Identifier of a payload, i.e. the prefix of its base64url encoded SHA-256 digest, used to detect chunks of different
+/// payloads being mixed and corrupted reassemblies.
+fn payload_id(payload: &str) -> String {
+    let mut id = Encoding::Base64UrlNoPad.encode(sha256(payload));
+    id.truncate(PAYLOAD_ID_LEN);
+    id
+}
+
+/// Header of a chunk, of the form `VPQR:<id>:<index>/<total>:`, with indices starting from 1.
+fn chunk_header(id: &str, index: usize, total: usize) -> String {
+    format!("{CHUNK_PREFIX}:{id}:{index}/{total}:")
+}
+
+/// Amount of chunks a payload of the given length is split into, accounting for the headers of the chunks.
+///
+/// # Arguments
+/// * `payload_len` - Length of the payload in bytes.
+/// * `max_chunk_len` - Maximum length of a chunk, header included.
+///
+/// # Returns
+/// A result containing the amount of chunks or a string containing an error if a chunk cannot even hold its header.
+///
+/// # Examples
+/// ```
+/// use csd_jwt::transport::qr::{chunk_count, QR_CAPACITY};
+///
+/// assert_eq!(chunk_count(1000, QR_CAPACITY).unwrap(), 1);
+/// assert_eq!(chunk_count(10_000, QR_CAPACITY).unwrap(), 4);
+/// assert!(chunk_count(100, 10).is_err());
+/// ```
+pub fn chunk_count(payload_len: usize, max_chunk_len: usize) -> Result<usize, String> {
+    let mut total: usize = 1;
+    loop {
+        // The header is the longest for the last chunk, whose index has as many digits as the total.
+        let header_len = chunk_header(&"-".repeat(PAYLOAD_ID_LEN), total, total).len();
+        if max_chunk_len <= header_len {
+            return Err(format!("Chunks of {max_chunk_len} bytes cannot hold a header of {header_len} bytes"));
+        }
+
+        let needed = payload_len.div_ceil(max_chunk_len - header_len).max(1);
+        if needed <= total {
+            return Ok(needed);
+        }
+        total = needed;
+    }
+}
+
+/// Splits a payload, e.g. a VP JWT, into sequence-numbered chunks, each small enough to be displayed as a QR code.
+///
+/// # Arguments
+/// * `payload` - ASCII payload to be split.
+/// * `max_chunk_len` - Maximum length of a chunk, header included, e.g. `QR_CAPACITY`.
+///
+/// # Returns
+/// A result containing the chunks, in order, or a string containing an error.
+///
+/// # Examples
+/// ```
+/// use csd_jwt::transport::qr::{reassemble, split};
+///
+/// let payload: String = "eyJhbGciOiJFUzI1NiJ9.".repeat(20);
+/// let mut chunks = split(&payload, 100).unwrap();
+/// assert!(chunks.len() > 1 && chunks.iter().all(|chunk| chunk.len() <= 100));
+///
+/// chunks.reverse();
+/// assert_eq!(reassemble(&chunks).unwrap(), payload);
+/// assert!(reassemble(&chunks[1..]).is_err());
+/// ```
+pub fn split(payload: &str, max_chunk_len: usize) -> Result<Vec<String>, String> {
+    if !payload.is_ascii() {
+        return Err("Only ASCII payloads can be split into QR chunks".to_string());
+    }
+
+    let total = chunk_count(payload.len(), max_chunk_len)?;
+    let id = payload_id(payload);
+    let bytes = payload.as_bytes();
+
+    let mut chunks: Vec<String> = vec![];
+    let mut start: usize = 0;
+    for index in 1..=total {
+        let header = chunk_header(&id, index, total);
+        let end = (start + max_chunk_len - header.len()).min(bytes.len());
+        // Splitting ASCII on any byte boundary is safe.
+        chunks.push(format!("{header}{}", &payload[start..end]));
+        start = end;
+    }
+
+    Ok(chunks)
+}
+
+/// Reassembles a payload from its chunks, received in any order.
+///
+/// # Arguments
+/// * `chunks` - Every chunk of the payload.
+///
+/// # Returns
+/// A result containing the payload or a string containing an error if chunks are malformed, missing, duplicated, belong
+/// to different payloads or do not reassemble into the payload they were split from.
+pub fn reassemble(chunks: &[String]) -> Result<String, String> {
+    let mut expected: Option<(String, usize)> = None;
+    let mut parts: BTreeMap<usize, &str> = BTreeMap::new();
+
+    for chunk in chunks {
+        let fields: Vec<&str> = chunk.splitn(4, ':').collect();
+        let (id, position, part) = match fields.as_slice() {
+            [prefix, id, position, part] if *prefix == CHUNK_PREFIX => { (*id, *position, *part) }
+            _ => { return Err("Chunk is not a presentation QR chunk".to_string()) }
+        };
+
+        let (index, total) = match position.split_once('/').map(|(index, total)| (index.parse::<usize>(), total.parse::<usize>())) {
+            Some((Ok(index), Ok(total))) if index >= 1 && index <= total => { (index, total) }
+            _ => { return Err(format!("Chunk has an invalid position {position}")) }
+        };
+
+        match &expected {
+            None => { expected = Some((id.to_string(), total)) }
+            Some((expected_id, expected_total)) if expected_id == id && *expected_total == total => {}
+            Some(_) => { return Err("Chunks belong to different payloads".to_string()) }
+        }
+
+        if parts.insert(index, part).is_some() {
+            return Err(format!("Chunk {index} was received twice"));
+        }
+    }
+
+    let (id, total) = match expected {
+        Some(expected) => { expected }
+        None => { return Err("No chunk to reassemble".to_string()) }
+    };
+    if parts.len() != total {
+        return Err(format!("Received {} of {total} chunks", parts.len()));
+    }
+
+    let payload: String = parts.values().copied().collect();
+    if payload_id(&payload) != id {
+        return Err("Reassembled payload does not match its identifier".to_string());
+    }
+
+    Ok(payload)
+}
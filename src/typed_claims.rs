@@ -0,0 +1,236 @@
+use serde_json::{Map, Number, Value};
+use crate::claim_metadata::{ClaimDataType, ClaimMetadata};
+use crate::common_data::CLAIMS;
+use crate::derived_claims::Date;
+
+/// Largest magnitude of the decimal exponent of a number claim, as for the JSON numbers read as doubles.
+const MAX_EXPONENT: i64 = 308;
+
+
+/// Canonical encoding of a decimal number, independent of how it was written: no sign for zero, no leading zeros in
+/// the integer part, no trailing zeros in the fraction, no exponent and `.` as decimal separator. Only the JSON number
+/// syntax is accepted, so that localized forms such as `1,5` or `1 000` are rejected instead of misread.
+///
+/// # Arguments
+/// * `number` - Decimal number, e.g. `1.50`, `15e-1` or `-0`.
+///
+/// # Returns
+/// A result containing the canonical encoding or a string containing an error if the number is malformed.
+///
+/// # Examples
+/// ```
+/// use csd_jwt::typed_claims::canonical_number;
+///
+/// assert_eq!(canonical_number("1.50").unwrap(), "1.5");
+/// assert_eq!(canonical_number("15e-1").unwrap(), "1.5");
+/// assert_eq!(canonical_number("+0100").unwrap(), "100");
+/// assert_eq!(canonical_number("-0.0").unwrap(), "0");
+/// assert_eq!(canonical_number("2.5E3").unwrap(), "2500");
+/// assert!(canonical_number("1,5").is_err());
+/// ```
+pub fn canonical_number(number: &str) -> Result<String, String> {
+    let malformed = || format!("Number {number} is not a decimal number");
+
+    let trimmed: &str = number.trim();
+    let (negative, unsigned) = match trimmed.strip_prefix('-') {
+        Some(unsigned) => { (true, unsigned) }
+        None => { (false, trimmed.strip_prefix('+').unwrap_or(trimmed)) }
+    };
+    let (mantissa, exponent) = match unsigned.split_once(['e', 'E']) {
+        Some((mantissa, exponent)) => match exponent.parse::<i64>() {
+            Ok(exponent) if exponent.abs() <= MAX_EXPONENT => { (mantissa, exponent) }
+            _ => { return Err(malformed()) }
+        },
+        None => { (unsigned, 0) }
+    };
+    let (integer, fraction) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+    if integer.is_empty() || !integer.bytes().all(|byte| byte.is_ascii_digit()) || !fraction.bytes().all(|byte| byte.is_ascii_digit()) {
+        return Err(malformed());
+    }
+    if mantissa.contains('.') && fraction.is_empty() {
+        return Err(malformed());
+    }
+
+    // Digits of the number and position of the decimal point among them, moved by the exponent.
+    let digits: String = format!("{integer}{fraction}");
+    let point: i64 = integer.len() as i64 + exponent;
+    let (integer, fraction): (String, String) = if point <= 0 {
+        ("0".to_string(), format!("{}{digits}", "0".repeat(point.unsigned_abs() as usize)))
+    } else if point as usize >= digits.len() {
+        (format!("{digits}{}", "0".repeat(point as usize - digits.len())), String::new())
+    } else {
+        (digits[..point as usize].to_string(), digits[point as usize..].to_string())
+    };
+
+    let integer: &str = match integer.trim_start_matches('0') {
+        "" => { "0" }
+        integer => { integer }
+    };
+    let fraction: &str = fraction.trim_end_matches('0');
+    let sign: &str = if negative && (integer != "0" || !fraction.is_empty()) { "-" } else { "" };
+
+    if fraction.is_empty() {
+        Ok(format!("{sign}{integer}"))
+    } else {
+        Ok(format!("{sign}{integer}.{fraction}"))
+    }
+}
+
+
+/// Canonical encoding of an RFC 3339 date or date and time, independent of its time zone offset and precision: full
+/// dates are kept as `YYYY-MM-DD`, while dates and times are converted to UTC as `YYYY-MM-DDTHH:MM:SS[.fraction]Z`, with
+/// the trailing zeros of the fraction removed.
+///
+/// # Arguments
+/// * `date` - RFC 3339 full date or date and time.
+///
+/// # Returns
+/// A result containing the canonical encoding or a string containing an error if the date is malformed.
+///
+/// # Examples
+/// ```
+/// use csd_jwt::typed_claims::canonical_date;
+///
+/// assert_eq!(canonical_date("2024-01-15T12:30:00+02:00").unwrap(), "2024-01-15T10:30:00Z");
+/// assert_eq!(canonical_date("2024-01-15t10:30:00.500z").unwrap(), "2024-01-15T10:30:00.5Z");
+/// assert_eq!(canonical_date("2024-01-01T00:30:00+01:00").unwrap(), "2023-12-31T23:30:00Z");
+/// assert_eq!(canonical_date("1879-03-14").unwrap(), "1879-03-14");
+/// assert!(canonical_date("2023-02-29").is_err());
+/// assert!(canonical_date("14/03/1879").is_err());
+/// ```
+pub fn canonical_date(date: &str) -> Result<String, String> {
+    let malformed = || format!("Date {date} is not an RFC 3339 date or date and time");
+
+    let full_date: Date = match date.get(..10) {
+        Some(full_date) => { Date::parse(full_date).map_err(|_| malformed())? }
+        None => { return Err(malformed()) }
+    };
+    let days: i64 = full_date.days_since_epoch();
+    if Date::from_days(days) != full_date {
+        return Err(format!("Date {date} does not exist"));
+    }
+
+    let time: &str = &date[10..];
+    if time.is_empty() {
+        return Ok(format!("{:04}-{:02}-{:02}", full_date.year, full_date.month, full_date.day));
+    }
+    let time: &str = match time.strip_prefix(['T', 't', ' ']) {
+        Some(time) => { time }
+        None => { return Err(malformed()) }
+    };
+
+    let (time, offset_seconds): (&str, i64) = if let Some(time) = time.strip_suffix(['Z', 'z']) {
+        (time, 0)
+    } else {
+        match time.len().checked_sub(6).and_then(|split| time.get(split..).map(|offset| (&time[..split], offset))) {
+            Some((time, offset)) => { (time, parse_offset(offset).ok_or_else(malformed)?) }
+            None => { return Err(malformed()) }
+        }
+    };
+
+    let (clock, fraction) = time.split_once('.').unwrap_or((time, ""));
+    if time.contains('.') && (fraction.is_empty() || !fraction.bytes().all(|byte| byte.is_ascii_digit())) {
+        return Err(malformed());
+    }
+    let (hour, minute, second) = match clock.split(':').collect::<Vec<&str>>().as_slice() {
+        [hour, minute, second] if [hour, minute, second].iter().all(|part| part.len() == 2 && part.bytes().all(|byte| byte.is_ascii_digit())) => {
+            match (hour.parse::<i64>(), minute.parse::<i64>(), second.parse::<i64>()) {
+                (Ok(hour), Ok(minute), Ok(second)) if hour < 24 && minute < 60 && second <= 60 => { (hour, minute, second) }
+                _ => { return Err(malformed()) }
+            }
+        }
+        _ => { return Err(malformed()) }
+    };
+
+    let seconds: i64 = days * 86_400 + hour * 3_600 + minute * 60 + second - offset_seconds;
+    let utc: Date = Date::from_days(seconds.div_euclid(86_400));
+    let time_of_day: i64 = seconds.rem_euclid(86_400);
+    let fraction: &str = fraction.trim_end_matches('0');
+    Ok(format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}{}{}Z",
+        utc.year, utc.month, utc.day, time_of_day / 3_600, time_of_day % 3_600 / 60, time_of_day % 60,
+        if fraction.is_empty() { "" } else { "." }, fraction,
+    ))
+}
+
+
+/// Rewrites the claims of a raw VC tagged as `number` or `date` in its claim metadata with their canonical encoding,
+/// so that the digest, leaf, accumulator element or BBS+ message of a claim only depends on its logical value. Number
+/// claims keep their JSON type: numeric strings stay strings and JSON numbers stay numbers.
+///
+/// # Arguments
+/// * `raw_vc` - Template VC, with its claim groups already merged.
+///
+/// # Returns
+/// A result containing the raw VC with canonical typed claims or a string containing an error if a tagged claim is
+/// malformed.
+///
+/// # Examples
+/// ```
+/// use serde_json::json;
+/// use csd_jwt::typed_claims::canonicalize_typed_claims;
+///
+/// let raw_vc = json!({
+///     "credentialSubject": {"height": "1.80", "weight": 7.5e1, "birthdate": "1879-03-14T11:30:00+01:00"},
+///     "claimMetadata": {"height": {"type": "number"}, "weight": {"type": "number"}, "birthdate": {"type": "date"}}
+/// });
+/// let canonical = canonicalize_typed_claims(raw_vc.as_object().unwrap()).unwrap();
+/// assert_eq!(canonical["credentialSubject"], json!({"height": "1.8", "weight": 75, "birthdate": "1879-03-14T10:30:00Z"}));
+/// ```
+pub fn canonicalize_typed_claims(raw_vc: &Map<String, Value>) -> Result<Map<String, Value>, String> {
+    let metadata = ClaimMetadata::extract(raw_vc)?;
+    let mut raw_vc: Map<String, Value> = raw_vc.clone();
+    let claims: &mut Map<String, Value> = match raw_vc.get_mut(CLAIMS) {
+        Some(Value::Object(claims)) => { claims }
+        _ => { return Ok(raw_vc) }
+    };
+
+    for (name, claim_metadata) in metadata {
+        let value: &mut Value = match claims.get_mut(&name) {
+            Some(value) => { value }
+            None => { continue }
+        };
+        let canonical: Value = match (claim_metadata.data_type, &*value) {
+            (Some(ClaimDataType::Number), Value::String(number)) => { Value::String(canonical_number(number)?) }
+            (Some(ClaimDataType::Number), Value::Number(number)) => { canonical_json_number(number)? }
+            (Some(ClaimDataType::Date), Value::String(date)) => { Value::String(canonical_date(date)?) }
+            _ => { continue }
+        };
+        *value = canonical;
+    }
+
+    Ok(raw_vc)
+}
+
+
+/// Converts a JSON number to the number holding its canonical encoding, as an integer if it has no fraction.
+fn canonical_json_number(number: &Number) -> Result<Value, String> {
+    let canonical: String = canonical_number(&number.to_string())?;
+    if let Ok(integer) = canonical.parse::<i64>() {
+        return Ok(Value::from(integer));
+    }
+    if let Ok(integer) = canonical.parse::<u64>() {
+        return Ok(Value::from(integer));
+    }
+    match canonical.parse::<f64>().ok().and_then(Number::from_f64) {
+        Some(number) => { Ok(Value::Number(number)) }
+        None => { Err(format!("Number {number} cannot be represented in JSON")) }
+    }
+}
+
+/// Parses an RFC 3339 time zone offset of the form `+HH:MM` or `-HH:MM` to seconds east of UTC.
+fn parse_offset(offset: &str) -> Option<i64> {
+    let sign: i64 = match offset.get(..1)? {
+        "+" => { 1 }
+        "-" => { -1 }
+        _ => { return None }
+    };
+    let (hours, minutes) = offset[1..].split_once(':')?;
+    if hours.len() != 2 || minutes.len() != 2 || !format!("{hours}{minutes}").bytes().all(|byte| byte.is_ascii_digit()) {
+        return None;
+    }
+    match (hours.parse::<i64>(), minutes.parse::<i64>()) {
+        (Ok(hours), Ok(minutes)) if hours < 24 && minutes < 60 => { Some(sign * (hours * 3_600 + minutes * 60)) }
+        _ => { None }
+    }
+}
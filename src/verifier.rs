@@ -0,0 +1,328 @@
+use std::sync::Arc;
+use serde_json::{Map, Value};
+use crate::clock::{Clock, SystemClock};
+use crate::common_data::{ISSUED_AT, VERIFIABLE_CREDENTIAL, VP};
+use crate::disclosure_policy::DisclosurePolicy;
+use crate::ephemeral_binding::unverified_claims;
+use crate::key_compromise::CompromiseRegistry;
+use crate::pseudonym::open_pseudonymous_vp;
+use crate::roles::RoleAlgorithm;
+use crate::verifier_config::{uniform_error, FreshnessViolation, VerifierConfig};
+
+/// Structured outcome of the verification of a VP, separating the failure of its proofs from the violations of the
+/// validity and freshness requirements of the verifier.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VerificationReport {
+    /// Outcome of the verification of the proofs of the VP.
+    pub proof: Result<(), String>,
+    /// Violations of the validity period of the credential and of the freshness of the VP, checked only if the proofs
+    /// are valid.
+    pub violations: Vec<FreshnessViolation>,
+}
+
+impl VerificationReport {
+
+    /// Whether the VP is accepted.
+    pub fn is_valid(&self) -> bool {
+        self.proof.is_ok() && self.violations.is_empty()
+    }
+
+    /// Converts the report to a result, reporting the failure of the proofs or the first violation.
+    pub fn into_result(self) -> Result<(), String> {
+        self.proof?;
+        match self.violations.first() {
+            Some(violation) => { Err(violation.to_string()) }
+            None => { Ok(()) }
+        }
+    }
+}
+
+
+/// Verifier of an algorithm, holding only the public parameters of the issuer.
+pub struct Verifier<A: RoleAlgorithm> {
+    /// Public parameters of the issuer.
+    issuer_public: A::IssuerPublic,
+    /// Whether the reasons of failed verifications are replaced by a uniform error, and only logged.
+    uniform_errors: bool,
+    /// Validity and freshness requirements of the verifier.
+    config: VerifierConfig,
+    /// ID of the issuer key, looked up in the compromise registry.
+    issuer_kid: Option<String>,
+    /// Compromised issuer keys, with the time of their compromise.
+    compromises: CompromiseRegistry,
+    /// Claims the issuer discloses in every VP.
+    disclosure_policy: DisclosurePolicy,
+    /// Source of the current time the validity and freshness are checked at.
+    clock: Arc<dyn Clock>,
+}
+
+impl<A: RoleAlgorithm> Verifier<A> {
+
+    /// Constructor for a verifier.
+    ///
+    /// # Arguments
+    /// * `issuer_public` - Public parameters published by the issuer.
+    ///
+    /// # Returns
+    /// The verifier.
+    pub fn new(issuer_public: A::IssuerPublic) -> Self {
+        Verifier { issuer_public, uniform_errors: false, config: VerifierConfig::DEFAULT, issuer_kid: None, compromises: CompromiseRegistry::new(), disclosure_policy: DisclosurePolicy::new(), clock: Arc::new(SystemClock) }
+    }
+
+    /// Sets the requirements of the verifier, e.g. the maximum age of the VPs it accepts.
+    pub fn with_config(mut self, config: VerifierConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Sets the clock the validity period of the credentials and the freshness of the VPs are checked against.
+    ///
+    /// # Arguments
+    /// * `clock` - Source of the current time, e.g. a `SimulatedClock` shared with the issuer.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::sync::Arc;
+    /// use std::time::Duration;
+    /// use serde_json::{Map, Value};
+    /// use csd_jwt::clock::SimulatedClock;
+    /// use csd_jwt::common_data::VC;
+    /// use csd_jwt::holder::Holder;
+    /// use csd_jwt::issuer::Issuer;
+    /// use csd_jwt::issuer_config::IssuerConfig;
+    /// use csd_jwt::sd_algorithms::hashes::sd_jwt::SdJwtInstance;
+    /// use csd_jwt::verifier::Verifier;
+    ///
+    /// let clock: Arc<SimulatedClock> = Arc::new(SimulatedClock::new(1_700_000_000));
+    /// let issuer: Issuer<SdJwtInstance> = Issuer::new(4).unwrap()
+    ///     .with_config(IssuerConfig { max_validity: Some(86_400) })
+    ///     .with_clock(clock.clone());
+    /// let holder: Holder<SdJwtInstance> = Holder::new(issuer.public_parameters().clone()).unwrap();
+    /// let verifier: Verifier<SdJwtInstance> = Verifier::new(issuer.public_parameters().clone()).with_clock(clock.clone());
+    ///
+    /// let raw_vc: Map<String, Value> = serde_json::from_str(VC).unwrap();
+    /// let (vc, _) = issuer.issue_vc(&raw_vc, holder.public_key()).unwrap();
+    /// let (_, vp_jwt) = holder.issue_vp(&vc, &vec!["name".to_string()], None).unwrap();
+    /// assert!(verifier.verify_vp_report(&vp_jwt, holder.public_key()).is_valid());
+    ///
+    /// clock.advance(Duration::from_secs(2 * 86_400));
+    /// assert!(!verifier.verify_vp_report(&vp_jwt, holder.public_key()).is_valid());
+    /// ```
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Sets the trusted compromise registry and the ID of the issuer key, so that the VPs of credentials issued under
+    /// that key after its compromise are rejected with `FreshnessViolation::IssuerKeyCompromised`.
+    ///
+    /// # Arguments
+    /// * `issuer_kid` - ID of the issuer key, e.g. the `kid` of its JWK.
+    /// * `compromises` - Compromised issuer keys, with the time of their compromise.
+    ///
+    /// # Examples
+    /// ```
+    /// use serde_json::{Map, Value};
+    /// use csd_jwt::common_data::VC;
+    /// use csd_jwt::holder::Holder;
+    /// use csd_jwt::issuer::Issuer;
+    /// use csd_jwt::key_compromise::CompromiseRegistry;
+    /// use csd_jwt::sd_algorithms::hashes::sd_jwt::SdJwtInstance;
+    /// use csd_jwt::verifier::Verifier;
+    /// use csd_jwt::verifier_config::FreshnessViolation;
+    ///
+    /// let issuer: Issuer<SdJwtInstance> = Issuer::new(4).unwrap();
+    /// let holder: Holder<SdJwtInstance> = Holder::new(issuer.public_parameters().clone()).unwrap();
+    /// let raw_vc: Map<String, Value> = serde_json::from_str(VC).unwrap();
+    /// let (vc, _) = issuer.issue_vc(&raw_vc, holder.public_key()).unwrap();
+    /// let (_, vp_jwt) = holder.issue_vp(&vc, &vec!["name".to_string()], None).unwrap();
+    ///
+    /// let compromises: CompromiseRegistry = CompromiseRegistry::new().with_compromised("issuer-key-1", 0);
+    /// let verifier: Verifier<SdJwtInstance> = Verifier::new(issuer.public_parameters().clone())
+    ///     .with_compromise_registry("issuer-key-1", compromises);
+    /// let report = verifier.verify_vp_report(&vp_jwt, holder.public_key());
+    /// assert!(matches!(report.violations[..], [FreshnessViolation::IssuerKeyCompromised(_)]));
+    /// ```
+    pub fn with_compromise_registry(mut self, issuer_kid: &str, compromises: CompromiseRegistry) -> Self {
+        self.issuer_kid = Some(issuer_kid.to_string());
+        self.compromises = compromises;
+        self
+    }
+
+    /// Sets the disclosure policy of the issuer, so that VPs withholding a claim it discloses in every VP are rejected.
+    /// The disclosed claims are read from the proofs of the VP, regardless of the claim metadata it carries.
+    ///
+    /// # Arguments
+    /// * `disclosure_policy` - Claims the issuer discloses in every VP.
+    pub fn with_disclosure_policy(mut self, disclosure_policy: DisclosurePolicy) -> Self {
+        self.disclosure_policy = disclosure_policy;
+        self
+    }
+
+    /// Replaces the reason of every failed verification with a uniform error, logging the detailed reason internally,
+    /// for verifiers that expose their errors to untrusted callers.
+    pub fn with_uniform_errors(mut self) -> Self {
+        self.uniform_errors = true;
+        self
+    }
+
+    /// Name of the algorithm.
+    pub fn sd_algorithm(&self) -> String {
+        A::NAME.to_string()
+    }
+
+    /// Verifies a VP against the public parameters of the issuer and the public key of the holder presenting it.
+    ///
+    /// # Arguments
+    /// * `vp_jwt` - JWT of the VP.
+    /// * `holder_public_key` - Public key of the holder presenting the VP.
+    ///
+    /// # Returns
+    /// A result containing a string in case the VP is invalid.
+    pub fn verify_vp(&self, vp_jwt: &str, holder_public_key: &[u8]) -> Result<(), String> {
+        self.report(self.verify_vp_report(vp_jwt, holder_public_key).into_result())
+    }
+
+    /// Verifies a VP like `verify_vp`, reporting the failure of its proofs and every violation of the validity period
+    /// of its credential and of the freshness required by the verifier. A VP outside the validity window set by its
+    /// holder fails its proofs, as the window is checked, at the time of the clock, while verifying the VP.
+    ///
+    /// # Arguments
+    /// * `vp_jwt` - JWT of the VP.
+    /// * `holder_public_key` - Public key of the holder presenting the VP.
+    ///
+    /// # Returns
+    /// The report of the verification.
+    pub fn verify_vp_report(&self, vp_jwt: &str, holder_public_key: &[u8]) -> VerificationReport {
+        let now: u64 = match self.clock.now() {
+            Ok(now) => { now }
+            Err(err) => { return VerificationReport { proof: Err(err), violations: vec![] } }
+        };
+        let config = VerifierConfig { verification_time: Some(now), ..self.config };
+        let proof: Result<(), String> = A::verify_vp(vp_jwt, &self.issuer_public, holder_public_key, &config)
+            .and_then(|_| self.check_disclosure_policy(vp_jwt));
+        if proof.is_err() {
+            return VerificationReport { proof, violations: vec![] };
+        }
+
+        match unverified_claims(vp_jwt) {
+            Ok(envelope) => {
+                let mut violations: Vec<FreshnessViolation> = config.freshness_violations(&envelope, now);
+                violations.extend(self.compromise_violation(&envelope));
+                VerificationReport { proof, violations }
+            }
+            Err(err) => { VerificationReport { proof: Err(err), violations: vec![] } }
+        }
+    }
+
+    /// Verifies a pseudonymous presentation addressed to this verifier and the VP it carries.
+    ///
+    /// # Arguments
+    /// * `jwt` - JWT of the pseudonymous presentation.
+    /// * `verifier_id` - Identifier of this verifier.
+    /// * `holder_public_key` - Public key of the holder presenting the VP.
+    ///
+    /// # Returns
+    /// A result containing the pseudonym of the holder or a string containing an error if the presentation is invalid.
+    pub fn verify_pseudonymous_vp(&self, jwt: &str, verifier_id: &str, holder_public_key: &[u8]) -> Result<String, String> {
+        let (pseudonym, vp_jwt) = self.report(open_pseudonymous_vp(jwt, verifier_id, &holder_public_key))?;
+        self.verify_vp(&vp_jwt, holder_public_key)?;
+        Ok(pseudonym)
+    }
+
+    /// Checks that a verified VP discloses every claim of the disclosure policy of the issuer.
+    fn check_disclosure_policy(&self, vp_jwt: &str) -> Result<(), String> {
+        if self.disclosure_policy.is_empty() {
+            return Ok(());
+        }
+        self.disclosure_policy.check(&A::disclosed_claims(vp_jwt)?)
+    }
+
+    /// Checks the credential carried by a VP against the compromise registry. A malformed `iat` is already reported by
+    /// the freshness checks.
+    fn compromise_violation(&self, envelope: &Map<String, Value>) -> Option<FreshnessViolation> {
+        let issuer_kid: &str = self.issuer_kid.as_deref()?;
+        let issued_at: Option<u64> = envelope.get(VP)
+            .and_then(|presentation| presentation.get(VERIFIABLE_CREDENTIAL))
+            .and_then(|credentials| credentials.get(0))
+            .and_then(|credential| credential.get(ISSUED_AT))
+            .and_then(Value::as_u64);
+        self.compromises.check(issuer_kid, issued_at).err().map(FreshnessViolation::IssuerKeyCompromised)
+    }
+
+    /// Reports the outcome of a verification, with a uniform error if enabled.
+    fn report<T>(&self, result: Result<T, String>) -> Result<T, String> {
+        if self.uniform_errors {
+            uniform_error(result)
+        } else {
+            result
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{Map, Value};
+    use crate::common_data::{ISSUED_AT, VC};
+    use crate::holder::Holder;
+    use crate::issuer::Issuer;
+    use crate::metadata_disclosure::{DisclosableMetadata, IssuanceTime, TimeGranularity, ISSUANCE_DATE};
+    use crate::sd_algorithms::hashes::sd_jwt::SdJwtInstance;
+    use crate::verifier::Verifier;
+    use crate::verifier_config::VerifierConfig;
+
+    #[test]
+    fn runtime_limits_are_enforced() -> Result<(), String> {
+        let raw_vc: Map<String, Value> = match serde_json::from_str::<Map<String, Value>>(VC) {
+            Ok(vc) => { vc }
+            Err(err) => { return Err(format!("Failed to parse Raw Verifiable Credential from string. [{err}]")); }
+        };
+        let issuer: Issuer<SdJwtInstance> = Issuer::new(raw_vc.len())?;
+        let holder: Holder<SdJwtInstance> = Holder::new(issuer.public_parameters().clone())?;
+        let (vc, _vc_jwt) = issuer.issue_vc(&raw_vc, holder.public_key())?;
+        let (_vp, vp_jwt) = holder.issue_vp(&vc, &vec!["name".to_string()], None)?;
+        let verifier = |config: VerifierConfig| Verifier::<SdJwtInstance>::new(issuer.public_parameters().clone()).with_config(config);
+
+        verifier(VerifierConfig::DEFAULT).verify_vp(&vp_jwt, holder.public_key())?;
+
+        let oversized = VerifierConfig { max_jwt_length: vp_jwt.len() - 1, ..VerifierConfig::DEFAULT };
+        assert!(verifier(oversized).verify_vp(&vp_jwt, holder.public_key()).is_err(), "Oversized VP was verified.");
+
+        let too_deep = VerifierConfig { max_depth: 2, ..VerifierConfig::DEFAULT };
+        assert!(verifier(too_deep).verify_vp(&vp_jwt, holder.public_key()).is_err(), "Too deep VP was verified.");
+
+        let too_many_claims = VerifierConfig { max_claims: 1, ..VerifierConfig::DEFAULT };
+        assert!(verifier(too_many_claims).verify_vp(&vp_jwt, holder.public_key()).is_err(), "VP with too many claims was verified.");
+
+        Ok(())
+    }
+
+    #[test]
+    fn rounded_issuance_time_is_signed() -> Result<(), String> {
+        let mut raw_vc: Map<String, Value> = match serde_json::from_str::<Map<String, Value>>(VC) {
+            Ok(vc) => { vc }
+            Err(err) => { return Err(format!("Failed to parse Raw Verifiable Credential from string. [{err}]")); }
+        };
+        raw_vc.insert(ISSUED_AT.to_string(), Value::from(1_705_314_600u64));
+        raw_vc.insert(ISSUANCE_DATE.to_string(), Value::String("2024-01-15T10:30:00Z".to_string()));
+        let metadata: DisclosableMetadata = DisclosableMetadata::new().with_issuance_time(IssuanceTime::Rounded(TimeGranularity::Month));
+        let issuer: Issuer<SdJwtInstance> = Issuer::new(raw_vc.len())?.with_disclosable_metadata(metadata);
+        let holder: Holder<SdJwtInstance> = Holder::new(issuer.public_parameters().clone())?;
+        let verifier: Verifier<SdJwtInstance> = Verifier::new(issuer.public_parameters().clone());
+
+        let (vc, _vc_jwt) = issuer.issue_vc(&raw_vc, holder.public_key())?;
+        assert_eq!(vc.get(ISSUED_AT), Some(&Value::from(1_704_067_200u64)));
+        let (_vp, vp_jwt) = holder.issue_vp(&vc, &vec!["name".to_string()], None)?;
+        verifier.verify_vp(&vp_jwt, holder.public_key())?;
+
+        for (field, exact) in [(ISSUED_AT, Value::from(1_705_314_600u64)), (ISSUANCE_DATE, Value::String("2024-01-15T10:30:00Z".to_string()))] {
+            let mut altered_vc: Map<String, Value> = vc.clone();
+            altered_vc.insert(field.to_string(), exact);
+            let (_vp, vp_jwt) = holder.issue_vp(&altered_vc, &vec!["name".to_string()], None)?;
+            assert!(verifier.verify_vp(&vp_jwt, holder.public_key()).is_err(), "VP of a VC with an altered {field} was verified.");
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,233 @@
+use ark_bn254::Bn254;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use serde_json::{Map, Value};
+use vb_accumulator::setup::{PublicKey, SetupParams};
+use crate::encoding::Encoding;
+use crate::roles::CsdJwtPublicParameters;
+use crate::sd_algorithms::accumulators::csd_jwt::CsdJwtInstance;
+use crate::sd_algorithms::sd_algorithm::SdAlgorithm;
+
+/// Type of the bundle, written in every serialized bundle.
+pub const BUNDLE_TYPE: &str = "csd-jwt-verifier-bundle";
+/// Version of the format of the bundle.
+pub const BUNDLE_VERSION: u64 = 1;
+/// Curve of the accumulator.
+pub const CURVE: &str = "BN254";
+
+/// Field holding the type of the bundle.
+const TYPE_FIELD: &str = "type";
+/// Field holding the version of the format.
+const VERSION_FIELD: &str = "version";
+/// Field holding the name of the algorithm.
+const ALGORITHM_FIELD: &str = "algorithm";
+/// Field holding the name of the curve.
+const CURVE_FIELD: &str = "curve";
+/// Field holding the name of the encoding of the elements.
+const ENCODING_FIELD: &str = "encoding";
+/// Field holding the setup parameters of the accumulator.
+const PARAMS_FIELD: &str = "params";
+/// Field holding the accumulator public key of the issuer.
+const ISSUER_PUBLIC_KEY_FIELD: &str = "issuer_public_key";
+
+/// Public material a verifier needs to check CSD-JWT presentations, i.e. the setup parameters of the accumulator and
+/// the issuer public key, distributed separately from the issuer keypair. The serialized bundle is self-describing: it
+/// carries its type, the version of the format, the algorithm, the curve and the encoding of the compressed elements.
+///
+/// # Examples
+/// ```
+/// use serde_json::{Map, Value};
+/// use csd_jwt::adapters::accumulators::csd_jwt_adapter::CsdJwtAdapter;
+/// use csd_jwt::adapters::adapter::Adapter;
+/// use csd_jwt::common_data::{CommonData, VC};
+/// use csd_jwt::verifier_bundle::VerifierBundle;
+///
+/// let issuer = CsdJwtAdapter::new(16).unwrap();
+/// let exported: Vec<u8> = issuer.verifier_bundle().unwrap().to_cbor().unwrap();
+///
+/// let (holder_public_key, _) = CommonData::holder_keys().unwrap();
+/// let verifier = CsdJwtAdapter::verifier_from_bundle(VerifierBundle::from_cbor(&exported).unwrap(), holder_public_key).unwrap();
+/// assert_eq!(verifier.sd_algorithm(), "CSD-JWT");
+///
+/// let raw_vc: Map<String, Value> = serde_json::from_str(VC).unwrap();
+/// let (vc, _) = issuer.issue_vc(&raw_vc).unwrap();
+/// let (_, vp_jwt) = issuer.issue_vp(&vc, &vec!["name".to_string()]).unwrap();
+/// verifier.verify_vp(&vp_jwt).unwrap();
+///
+/// // A truncated bundle is rejected.
+/// assert!(VerifierBundle::from_cbor(&exported[..exported.len() / 2]).is_err());
+/// ```
+#[derive(Clone)]
+pub struct VerifierBundle {
+    /// Setup parameters of the accumulator.
+    pub params: SetupParams<Bn254>,
+    /// Accumulator public key of the issuer.
+    pub issuer_public_key: PublicKey<Bn254>,
+}
+
+impl VerifierBundle {
+
+    /// Constructor for a verifier bundle.
+    ///
+    /// # Arguments
+    /// * `params` - Setup parameters of the accumulator.
+    /// * `issuer_public_key` - Accumulator public key of the issuer.
+    ///
+    /// # Returns
+    /// The bundle.
+    pub fn new(params: SetupParams<Bn254>, issuer_public_key: PublicKey<Bn254>) -> Self {
+        VerifierBundle { params, issuer_public_key }
+    }
+
+    /// Converts the bundle to a JSON object.
+    ///
+    /// # Returns
+    /// A result containing the object or a string containing an error.
+    pub fn to_map(&self) -> Result<Map<String, Value>, String> {
+        let encoding: Encoding = CsdJwtInstance::ENCODING;
+
+        let mut bundle: Map<String, Value> = Map::new();
+        bundle.insert(TYPE_FIELD.to_string(), Value::String(BUNDLE_TYPE.to_string()));
+        bundle.insert(VERSION_FIELD.to_string(), Value::from(BUNDLE_VERSION));
+        bundle.insert(ALGORITHM_FIELD.to_string(), Value::String(CsdJwtInstance::ALGORITHM.to_string()));
+        bundle.insert(CURVE_FIELD.to_string(), Value::String(CURVE.to_string()));
+        bundle.insert(ENCODING_FIELD.to_string(), Value::String(encoding.name().to_string()));
+        bundle.insert(PARAMS_FIELD.to_string(), Value::String(encode_element(&self.params, encoding)?));
+        bundle.insert(ISSUER_PUBLIC_KEY_FIELD.to_string(), Value::String(encode_element(&self.issuer_public_key, encoding)?));
+
+        Ok(bundle)
+    }
+
+    /// Reads a bundle from a JSON object, checking its type, version, algorithm and curve.
+    ///
+    /// # Arguments
+    /// * `bundle` - Object produced by `to_map`.
+    ///
+    /// # Returns
+    /// A result containing the bundle or a string containing an error.
+    pub fn from_map(bundle: &Map<String, Value>) -> Result<Self, String> {
+        expect_field(bundle, TYPE_FIELD, &Value::String(BUNDLE_TYPE.to_string()))?;
+        expect_field(bundle, VERSION_FIELD, &Value::from(BUNDLE_VERSION))?;
+        expect_field(bundle, ALGORITHM_FIELD, &Value::String(CsdJwtInstance::ALGORITHM.to_string()))?;
+        expect_field(bundle, CURVE_FIELD, &Value::String(CURVE.to_string()))?;
+
+        let encoding: Encoding = Encoding::from_name(string_field(bundle, ENCODING_FIELD)?)?;
+        let params: SetupParams<Bn254> = decode_element(string_field(bundle, PARAMS_FIELD)?, encoding)?;
+        let issuer_public_key: PublicKey<Bn254> = decode_element(string_field(bundle, ISSUER_PUBLIC_KEY_FIELD)?, encoding)?;
+
+        Ok(VerifierBundle { params, issuer_public_key })
+    }
+
+    /// Serializes the bundle to JSON.
+    ///
+    /// # Returns
+    /// A result containing the JSON string or a string containing an error.
+    ///
+    /// # Examples
+    /// ```
+    /// use ark_std::rand::rngs::StdRng;
+    /// use ark_std::rand::SeedableRng;
+    /// use csd_jwt::sd_algorithms::accumulators::csd_jwt::CsdJwtInstance;
+    /// use csd_jwt::verifier_bundle::VerifierBundle;
+    ///
+    /// let (params, keypair) = CsdJwtInstance::initialize_params(&mut StdRng::seed_from_u64(0));
+    /// let bundle = VerifierBundle::new(params, keypair.public_key.clone());
+    ///
+    /// let json = bundle.to_json().unwrap();
+    /// assert!(!json.contains("secret"));
+    /// assert_eq!(VerifierBundle::from_json(&json).unwrap().issuer_public_key, bundle.issuer_public_key);
+    /// assert!(VerifierBundle::from_json(&json.replace("\"version\":1", "\"version\":2")).is_err());
+    /// ```
+    pub fn to_json(&self) -> Result<String, String> {
+        match serde_json::to_string(&self.to_map()?) {
+            Ok(json) => { Ok(json) }
+            Err(err) => { Err(format!("Failed to serialize verifier bundle to JSON: [{err}]")) }
+        }
+    }
+
+    /// Deserializes a bundle from JSON.
+    ///
+    /// # Arguments
+    /// * `json` - JSON string produced by `to_json`.
+    ///
+    /// # Returns
+    /// A result containing the bundle or a string containing an error.
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        match serde_json::from_str::<Map<String, Value>>(json) {
+            Ok(bundle) => { Self::from_map(&bundle) }
+            Err(err) => { Err(format!("Failed to parse verifier bundle from JSON: [{err}]")) }
+        }
+    }
+
+    /// Serializes the bundle to CBOR, with the same fields as the JSON form.
+    ///
+    /// # Returns
+    /// A result containing the CBOR bytes or a string containing an error.
+    pub fn to_cbor(&self) -> Result<Vec<u8>, String> {
+        let mut cbor: Vec<u8> = Vec::new();
+        match ciborium::ser::into_writer(&self.to_map()?, &mut cbor) {
+            Ok(()) => { Ok(cbor) }
+            Err(err) => { Err(format!("Failed to serialize verifier bundle to CBOR: [{err}]")) }
+        }
+    }
+
+    /// Deserializes a bundle from CBOR.
+    ///
+    /// # Arguments
+    /// * `cbor` - CBOR bytes produced by `to_cbor`.
+    ///
+    /// # Returns
+    /// A result containing the bundle or a string containing an error.
+    pub fn from_cbor(cbor: &[u8]) -> Result<Self, String> {
+        match ciborium::de::from_reader::<Map<String, Value>, _>(cbor) {
+            Ok(bundle) => { Self::from_map(&bundle) }
+            Err(err) => { Err(format!("Failed to parse verifier bundle from CBOR: [{err}]")) }
+        }
+    }
+}
+
+
+impl From<CsdJwtPublicParameters> for VerifierBundle {
+    fn from(public_parameters: CsdJwtPublicParameters) -> Self {
+        VerifierBundle::new(public_parameters.params, public_parameters.public_key)
+    }
+}
+
+
+/// Encodes the compressed serialization of an element.
+fn encode_element<S: CanonicalSerialize>(element: &S, encoding: Encoding) -> Result<String, String> {
+    let mut compressed_bytes: Vec<u8> = Vec::new();
+    match element.serialize_compressed(&mut compressed_bytes) {
+        Ok(()) => { Ok(encoding.encode(compressed_bytes)) }
+        Err(err) => { Err(format!("Error in serialization of element: [{err}]")) }
+    }
+}
+
+/// Decodes an element from the encoding of its compressed serialization.
+fn decode_element<D: CanonicalDeserialize>(encoded_element: &str, encoding: Encoding) -> Result<D, String> {
+    let decoded = match encoding.decode(encoded_element) {
+        Ok(byte_array) => { byte_array }
+        Err(err) => { return Err(format!("Error in decoding element: [{err}]")) }
+    };
+    match D::deserialize_compressed(&*decoded) {
+        Ok(element) => { Ok(element) }
+        Err(err) => { Err(format!("Error in deserializing element: [{err}]")) }
+    }
+}
+
+/// Retrieves a string field of a bundle.
+fn string_field<'a>(bundle: &'a Map<String, Value>, field: &str) -> Result<&'a str, String> {
+    match bundle.get(field) {
+        Some(Value::String(value)) => { Ok(value) }
+        Some(_) => { Err(format!("Field {field} of the verifier bundle must be a string")) }
+        None => { Err(format!("Verifier bundle does not contain {field}")) }
+    }
+}
+
+/// Checks that a field of a bundle holds the expected value.
+fn expect_field(bundle: &Map<String, Value>, field: &str, expected: &Value) -> Result<(), String> {
+    match bundle.get(field) {
+        Some(value) if value == expected => { Ok(()) }
+        Some(value) => { Err(format!("Unsupported {field} {value} in verifier bundle, expected {expected}")) }
+        None => { Err(format!("Verifier bundle does not contain {field}")) }
+    }
+}
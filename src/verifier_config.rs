@@ -0,0 +1,361 @@
+use std::fmt;
+use log::warn;
+use serde_json::{Map, Value};
+use crate::common_data::{EXPIRATION, ISSUED_AT, NOT_BEFORE, VERIFIABLE_CREDENTIAL, VP};
+use crate::deterministic::unix_time;
+use crate::key_compromise::KeyCompromise;
+
+pub use crate::primitives::check_all;
+
+/// Message returned in place of the reason of every failed verification when uniform errors are enabled.
+pub const UNIFORM_VERIFICATION_ERROR: &str = "Verification failed";
+
+/// Limits enforced by verifiers on untrusted VCs and VPs, so that a hostile input cannot exhaust the verifier's memory or time.
+///
+/// # Examples
+/// ```
+/// use csd_jwt::verifier_config::VerifierConfig;
+///
+/// let config = VerifierConfig { max_claims: 2, ..VerifierConfig::default() };
+/// assert!(config.check_claims_len(2).is_ok());
+/// assert!(config.check_claims_len(3).is_err());
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct VerifierConfig {
+    /// Maximum length in bytes of an encoded JWT, and of any encoded element embedded in a VC or VP.
+    pub max_jwt_length: usize,
+    /// Maximum amount of claims in a VC or VP.
+    pub max_claims: usize,
+    /// Maximum nesting depth of the JSON payload.
+    pub max_depth: usize,
+    /// Maximum length in bytes of an encoded witness or proof of a single claim.
+    pub max_witness_length: usize,
+    /// Maximum age in seconds of a VP, measured from its `iat`, if freshness is required.
+    pub max_presentation_age: Option<u64>,
+    /// Maximum lifetime in seconds of a VP, from its `nbf`, or its `iat` if absent, to its `exp`, if VPs must expire.
+    pub max_presentation_lifetime: Option<u64>,
+    /// Tolerated difference in seconds between the clocks of the verifier and of the issuer or holder.
+    pub clock_skew: u64,
+    /// Time in seconds since the UNIX epoch the validity window of VPs is checked at, or none for the current time.
+    pub verification_time: Option<u64>,
+}
+
+impl VerifierConfig {
+
+    /// Default limits, large enough for credentials with several thousands of claims.
+    pub const DEFAULT: VerifierConfig = VerifierConfig {
+        max_jwt_length: 16 * 1024 * 1024,
+        max_claims: 16 * 1024,
+        max_depth: 32,
+        max_witness_length: 4 * 1024,
+        max_presentation_age: None,
+        max_presentation_lifetime: None,
+        clock_skew: 60,
+        verification_time: None,
+    };
+
+    /// Checks the length of an encoded JWT or of an encoded element.
+    ///
+    /// # Arguments
+    /// * `jwt` - Encoded JWT or element.
+    ///
+    /// # Returns
+    /// Returns a string containing an error if the limit is exceeded.
+    pub fn check_jwt_length(&self, jwt: &str) -> Result<(), String> {
+        if jwt.len() > self.max_jwt_length {
+            return Err(format!("Input length {} exceeds the limit of {} bytes", jwt.len(), self.max_jwt_length));
+        }
+        Ok(())
+    }
+
+    /// Checks the amount of claims in a VC or VP.
+    ///
+    /// # Arguments
+    /// * `claims_len` - Amount of claims.
+    ///
+    /// # Returns
+    /// Returns a string containing an error if the limit is exceeded.
+    pub fn check_claims_len(&self, claims_len: usize) -> Result<(), String> {
+        if claims_len > self.max_claims {
+            return Err(format!("Amount of claims {} exceeds the limit of {}", claims_len, self.max_claims));
+        }
+        Ok(())
+    }
+
+    /// Checks the length of the encoding of a single witness or proof.
+    ///
+    /// # Arguments
+    /// * `witness` - Encoded witness.
+    ///
+    /// # Returns
+    /// Returns a string containing an error if the limit is exceeded.
+    pub fn check_witness_length(&self, witness: &str) -> Result<(), String> {
+        if witness.len() > self.max_witness_length {
+            return Err(format!("Witness length {} exceeds the limit of {} bytes", witness.len(), self.max_witness_length));
+        }
+        Ok(())
+    }
+
+    /// Checks the nesting depth of a JSON object, such as the payload of a JWT.
+    ///
+    /// # Arguments
+    /// * `map` - JSON object to be checked.
+    ///
+    /// # Returns
+    /// Returns a string containing an error if the limit is exceeded.
+    pub fn check_map_depth(&self, map: &Map<String, Value>) -> Result<(), String> {
+        for value in map.values() {
+            self.check_depth_from(value, 2)?;
+        }
+        Ok(())
+    }
+
+    /// Checks the nesting depth of a JSON value without recursion.
+    ///
+    /// # Arguments
+    /// * `value` - JSON value to be checked.
+    ///
+    /// # Returns
+    /// Returns a string containing an error if the limit is exceeded.
+    pub fn check_depth(&self, value: &Value) -> Result<(), String> {
+        self.check_depth_from(value, 1)
+    }
+
+    /// Checks the validity period of the credential carried by a VP, the validity window set by the holder on the VP
+    /// and, if required, the freshness and lifetime of the VP itself.
+    ///
+    /// # Arguments
+    /// * `envelope` - Payload of the VP JWT, whose signature has already been verified.
+    /// * `now` - Current time in seconds since the UNIX epoch.
+    ///
+    /// # Returns
+    /// The violations found, empty if the VP is fresh and its credential valid.
+    ///
+    /// # Examples
+    /// ```
+    /// use serde_json::json;
+    /// use csd_jwt::verifier_config::{FreshnessViolation, VerifierConfig};
+    ///
+    /// let config = VerifierConfig { max_presentation_age: Some(300), ..VerifierConfig::default() };
+    /// let envelope = json!({"iat": 1000, "vp": {"verifiableCredential": [{"exp": 2000}]}});
+    /// assert!(config.freshness_violations(envelope.as_object().unwrap(), 1200).is_empty());
+    /// assert_eq!(config.freshness_violations(envelope.as_object().unwrap(), 2100), vec![
+    ///     FreshnessViolation::PresentationTooOld { age: 1100, max_age: 300 },
+    ///     FreshnessViolation::CredentialExpired { expiration: 2000 },
+    /// ]);
+    /// ```
+    pub fn freshness_violations(&self, envelope: &Map<String, Value>, now: u64) -> Vec<FreshnessViolation> {
+        let mut violations: Vec<FreshnessViolation> = vec![];
+
+        if let Some(max_age) = self.max_presentation_age {
+            match timestamp(envelope, ISSUED_AT) {
+                Ok(Some(issued_at)) if issued_at > now.saturating_add(self.clock_skew) => {
+                    violations.push(FreshnessViolation::PresentationFromFuture { issued_at })
+                }
+                Ok(Some(issued_at)) if now.saturating_sub(issued_at) > max_age => {
+                    violations.push(FreshnessViolation::PresentationTooOld { age: now - issued_at, max_age })
+                }
+                Ok(Some(_)) => {}
+                Ok(None) => { violations.push(FreshnessViolation::MissingPresentationTime) }
+                Err(violation) => { violations.push(violation) }
+            }
+        }
+
+        let mut push = |violation: FreshnessViolation| {
+            if !violations.contains(&violation) {
+                violations.push(violation);
+            }
+        };
+        self.presentation_window_violations(envelope, now).into_iter().for_each(&mut push);
+        match timestamp(envelope, EXPIRATION) {
+            Ok(Some(expiration)) if expiration.saturating_add(self.clock_skew) < now => {}
+            Ok(Some(expiration)) => {
+                if let Some(max_lifetime) = self.max_presentation_lifetime {
+                    let start: Result<Option<u64>, FreshnessViolation> = match timestamp(envelope, NOT_BEFORE) {
+                        Ok(None) => { timestamp(envelope, ISSUED_AT) }
+                        not_before => { not_before }
+                    };
+                    match start {
+                        Ok(Some(start)) if expiration.saturating_sub(start) > max_lifetime => {
+                            push(FreshnessViolation::PresentationLifetimeTooLong { lifetime: expiration - start, max_lifetime })
+                        }
+                        Ok(Some(_)) => {}
+                        Ok(None) => { push(FreshnessViolation::MissingPresentationTime) }
+                        Err(violation) => { push(violation) }
+                    }
+                }
+            }
+            Ok(None) if self.max_presentation_lifetime.is_some() => { push(FreshnessViolation::MissingPresentationExpiration) }
+            Ok(None) | Err(_) => {}
+        }
+
+        let credential: Option<&Map<String, Value>> = envelope.get(VP)
+            .and_then(|presentation| presentation.get(VERIFIABLE_CREDENTIAL))
+            .and_then(|credentials| credentials.get(0))
+            .and_then(Value::as_object);
+        if let Some(credential) = credential {
+            match timestamp(credential, EXPIRATION) {
+                Ok(Some(expiration)) if expiration.saturating_add(self.clock_skew) < now => {
+                    violations.push(FreshnessViolation::CredentialExpired { expiration })
+                }
+                Ok(_) => {}
+                Err(violation) => { violations.push(violation) }
+            }
+            match timestamp(credential, ISSUED_AT) {
+                Ok(Some(issued_at)) if issued_at > now.saturating_add(self.clock_skew) => {
+                    violations.push(FreshnessViolation::CredentialNotYetValid { issued_at })
+                }
+                Ok(_) => {}
+                Err(violation) => { violations.push(violation) }
+            }
+        }
+
+        violations
+    }
+
+    /// Checks the validity window set by the holder on a VP, i.e. its `nbf` and `exp`.
+    ///
+    /// # Arguments
+    /// * `envelope` - Payload of the VP JWT.
+    /// * `now` - Current time in seconds since the UNIX epoch.
+    ///
+    /// # Returns
+    /// The violations found, empty if the VP is within its window or does not set one.
+    pub fn presentation_window_violations(&self, envelope: &Map<String, Value>, now: u64) -> Vec<FreshnessViolation> {
+        let mut violations: Vec<FreshnessViolation> = vec![];
+
+        match timestamp(envelope, NOT_BEFORE) {
+            Ok(Some(not_before)) if not_before > now.saturating_add(self.clock_skew) => {
+                violations.push(FreshnessViolation::PresentationNotYetValid { not_before })
+            }
+            Ok(_) => {}
+            Err(violation) => { violations.push(violation) }
+        }
+        match timestamp(envelope, EXPIRATION) {
+            Ok(Some(expiration)) if expiration.saturating_add(self.clock_skew) < now => {
+                violations.push(FreshnessViolation::PresentationExpired { expiration })
+            }
+            Ok(_) => {}
+            Err(violation) => { violations.push(violation) }
+        }
+
+        violations
+    }
+
+    /// Checks the validity window of a VP at the `verification_time` of the config, or at the current time if unset.
+    /// Every VP verification applies it, so that an expired VP is rejected whichever API verifies it.
+    ///
+    /// # Arguments
+    /// * `envelope` - Payload of the VP JWT.
+    ///
+    /// # Returns
+    /// Returns a string containing an error if the VP is outside its window.
+    pub fn check_presentation_window(&self, envelope: &Map<String, Value>) -> Result<(), String> {
+        let now: u64 = match self.verification_time {
+            Some(verification_time) => { verification_time }
+            None => { unix_time()? }
+        };
+        match self.presentation_window_violations(envelope, now).first() {
+            Some(violation) => { Err(violation.to_string()) }
+            None => { Ok(()) }
+        }
+    }
+
+    /// Checks the nesting depth of a JSON value, given the depth at which the value is found.
+    fn check_depth_from(&self, value: &Value, initial_depth: usize) -> Result<(), String> {
+        let mut stack: Vec<(&Value, usize)> = vec![(value, initial_depth)];
+
+        while let Some((value, depth)) = stack.pop() {
+            if depth > self.max_depth {
+                return Err(format!("JSON nesting depth exceeds the limit of {}", self.max_depth));
+            }
+            match value {
+                Value::Array(array) => { stack.extend(array.iter().map(|element| (element, depth + 1))) }
+                Value::Object(map) => { stack.extend(map.values().map(|element| (element, depth + 1))) }
+                _ => {}
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Violation of the validity period of a credential or of the freshness required of a presentation.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FreshnessViolation {
+    /// The VP does not carry its issuance time, although freshness is required.
+    MissingPresentationTime,
+    /// The VP was issued longer ago than allowed.
+    PresentationTooOld { age: u64, max_age: u64 },
+    /// The VP claims to be issued in the future.
+    PresentationFromFuture { issued_at: u64 },
+    /// The validity window set by the holder on the VP has not started.
+    PresentationNotYetValid { not_before: u64 },
+    /// The validity window set by the holder on the VP has ended.
+    PresentationExpired { expiration: u64 },
+    /// The VP does not expire, although its lifetime is limited.
+    MissingPresentationExpiration,
+    /// The validity window of the VP is longer than allowed.
+    PresentationLifetimeTooLong { lifetime: u64, max_lifetime: u64 },
+    /// The credential expired.
+    CredentialExpired { expiration: u64 },
+    /// The credential claims to be issued in the future.
+    CredentialNotYetValid { issued_at: u64 },
+    /// A timestamp is not a number of seconds.
+    MalformedTimestamp { field: String },
+    /// The credential is issued under an issuer key after its compromise.
+    IssuerKeyCompromised(KeyCompromise),
+}
+
+impl fmt::Display for FreshnessViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FreshnessViolation::MissingPresentationTime => { write!(f, "VP does not contain {ISSUED_AT}") }
+            FreshnessViolation::PresentationTooOld { age, max_age } => { write!(f, "VP is {age} seconds old, more than the limit of {max_age} seconds") }
+            FreshnessViolation::PresentationFromFuture { issued_at } => { write!(f, "VP is issued in the future, at {issued_at}") }
+            FreshnessViolation::PresentationNotYetValid { not_before } => { write!(f, "VP is not valid before {not_before}") }
+            FreshnessViolation::PresentationExpired { expiration } => { write!(f, "VP expired at {expiration}") }
+            FreshnessViolation::MissingPresentationExpiration => { write!(f, "VP does not contain {EXPIRATION}") }
+            FreshnessViolation::PresentationLifetimeTooLong { lifetime, max_lifetime } => { write!(f, "VP is valid for {lifetime} seconds, more than the limit of {max_lifetime} seconds") }
+            FreshnessViolation::CredentialExpired { expiration } => { write!(f, "Credential expired at {expiration}") }
+            FreshnessViolation::CredentialNotYetValid { issued_at } => { write!(f, "Credential is issued in the future, at {issued_at}") }
+            FreshnessViolation::MalformedTimestamp { field } => { write!(f, "Field {field} is not a timestamp in seconds") }
+            FreshnessViolation::IssuerKeyCompromised(compromise) => { write!(f, "{compromise}") }
+        }
+    }
+}
+
+
+/// Reads an optional timestamp in seconds from a VC or VP.
+fn timestamp(map: &Map<String, Value>, field: &str) -> Result<Option<u64>, FreshnessViolation> {
+    match map.get(field) {
+        None => { Ok(None) }
+        Some(value) => match value.as_u64() {
+            Some(timestamp) => { Ok(Some(timestamp)) }
+            None => { Err(FreshnessViolation::MalformedTimestamp { field: field.to_string() }) }
+        },
+    }
+}
+
+
+impl Default for VerifierConfig {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+
+/// Replaces the reason of a failed verification with `UNIFORM_VERIFICATION_ERROR`, logging the detailed reason
+/// internally, so that verifiers exposing their errors to untrusted callers do not leak which check failed.
+///
+/// # Arguments
+/// * `result` - Outcome of the verification.
+///
+/// # Returns
+/// The outcome with a uniform error.
+pub fn uniform_error<T>(result: Result<T, String>) -> Result<T, String> {
+    result.map_err(|err| {
+        warn!("Verification failed: {err}");
+        UNIFORM_VERIFICATION_ERROR.to_string()
+    })
+}